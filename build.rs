@@ -9,143 +9,18 @@ use std::env;
 use std::fs;
 use std::path::PathBuf;
 
-// Import the CLI structure - we need to replicate the structure here
-// since we can't import directly from main.rs during build
-use clap::Parser;
-
-/// CLI structure for build-time man page generation.
-/// This must match the structure in main.rs exactly.
-#[derive(Parser, Debug)]
-#[command(name = "tram")]
-#[command(about = "A batteries-included starter kit for building CLI applications in Rust")]
-struct Cli {
-    /// Global config options
-    #[command(flatten)]
-    pub global: GlobalOptions,
-
-    /// Subcommands
-    #[command(subcommand)]
-    pub command: Commands,
-}
-
-/// Global CLI options that apply to all commands.
-#[derive(Parser, Debug)]
-struct GlobalOptions {
-    /// Log level (debug, info, warn, error)
-    #[arg(long, default_value = "info")]
-    pub log_level: String,
-
-    /// Output format (json, yaml, table)
-    #[arg(long, default_value = "table")]
-    pub format: String,
-
-    /// Disable colored output
-    #[arg(long, default_value = "false")]
-    pub no_color: bool,
+// Share the CLI definition with the binary so man pages never drift from it
+#[allow(dead_code)]
+#[path = "src/cli.rs"]
+mod cli;
 
-    /// Config file path
-    #[arg(long)]
-    pub config: Option<PathBuf>,
-}
-
-/// Available CLI commands.
-#[derive(Parser, Debug)]
-enum Commands {
-    /// Create a new project interactively
-    New {
-        /// Project name
-        name: String,
-        /// Project type (rust, nodejs, python, go, java, generic)
-        #[arg(long, default_value = "rust")]
-        project_type: String,
-        /// Project description
-        #[arg(long)]
-        description: Option<String>,
-        /// Skip interactive prompts
-        #[arg(long)]
-        skip_prompts: bool,
-    },
-    /// Generate templates for common CLI patterns
-    Generate {
-        /// Template type (command, config-section, error-type, session-extension)
-        #[arg(long, default_value = "command")]
-        template_type: String,
-        /// Name of the item to generate (e.g., "backup", "deploy")
-        name: String,
-        /// Description for the generated template
-        #[arg(long)]
-        description: Option<String>,
-        /// Target directory (defaults to current directory)
-        #[arg(long)]
-        target_dir: Option<PathBuf>,
-        /// Write the template to filesystem (default: show to stdout)
-        #[arg(long)]
-        write: bool,
-    },
-    /// Initialize a new project (legacy command)
-    Init {
-        /// Project name
-        name: String,
-        /// Use verbose output
-        #[arg(short, long)]
-        verbose: bool,
-    },
-    /// Show workspace information
-    Workspace {
-        /// Show detailed project information
-        #[arg(short, long)]
-        detailed: bool,
-    },
-    /// Show configuration information
-    Config,
-    /// Watch mode - monitor files and reload config automatically
-    Watch {
-        /// Watch configuration files for hot reload
-        #[arg(long, default_value = "true")]
-        config: bool,
-        /// Run checks on file changes (format, lint, build, test)
-        #[arg(long, default_value = "true")]
-        check: bool,
-    },
-    /// Run interactive examples demonstrating CLI patterns
-    Examples {
-        /// Example to run
-        #[arg(value_enum)]
-        example: ExampleType,
-    },
-    /// Generate shell completions
-    Completions {
-        /// Shell to generate completions for
-        #[arg(value_enum)]
-        shell: clap_complete::shells::Shell,
-    },
-    /// Generate manual pages
-    Man {
-        /// Output directory for man pages
-        #[arg(short, long, default_value = "./man")]
-        output_dir: PathBuf,
-        /// Generate only specific section (1-9, default: all)
-        #[arg(short, long)]
-        section: Option<u8>,
-    },
-}
+// The exit code table is shared the same way, for the EXIT STATUS section
+#[allow(dead_code)]
+#[path = "crates/tram-core/src/exit_code.rs"]
+mod exit_code;
 
-/// Available example types
-#[derive(clap::ValueEnum, Clone, Debug)]
-enum ExampleType {
-    /// Basic CLI command patterns
-    BasicCommand,
-    /// Async operations and concurrency
-    AsyncOperations,
-    /// Configuration management
-    ConfigUsage,
-    /// Progress indicators and terminal UI
-    ProgressIndicators,
-    /// Interactive prompts and user input
-    InteractivePrompts,
-    /// File system operations
-    FileOperations,
-}
+use cli::Cli;
+use exit_code::ExitCode;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Only generate man pages in release builds or when explicitly requested
@@ -156,14 +31,44 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         generate_man_pages_to_out_dir()?;
     }
 
+    emit_build_info();
+
     // Rerun build script if CLI structure changes
-    println!("cargo:rerun-if-changed=src/main.rs");
+    println!("cargo:rerun-if-changed=src/cli.rs");
+    println!("cargo:rerun-if-changed=crates/tram-core/src/exit_code.rs");
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-env-changed=TRAM_GENERATE_MAN");
 
     Ok(())
 }
 
+/// Expose the commit, target, and profile to the binary for crash reports.
+fn emit_build_info() {
+    let commit = std::process::Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|commit| commit.trim().to_string())
+        .filter(|commit| !commit.is_empty());
+
+    if let Some(commit) = commit {
+        println!("cargo:rustc-env=TRAM_BUILD_COMMIT={}", commit);
+    }
+    for (name, var) in [
+        ("TARGET", "TRAM_BUILD_TARGET"),
+        ("PROFILE", "TRAM_BUILD_PROFILE"),
+    ] {
+        if let Ok(value) = env::var(name) {
+            println!("cargo:rustc-env={}={}", var, value);
+        }
+    }
+
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+}
+
 /// Generate man pages to the OUT_DIR during build.
 fn generate_man_pages_to_out_dir() -> Result<(), Box<dyn std::error::Error>> {
     let out_dir = env::var("OUT_DIR")?;
@@ -179,6 +84,7 @@ fn generate_man_pages_to_out_dir() -> Result<(), Box<dyn std::error::Error>> {
     let man = Man::new(cmd.clone());
     let mut buffer = Vec::new();
     man.render(&mut buffer)?;
+    buffer.extend_from_slice(ExitCode::man_section().as_bytes());
 
     let man_file = man_dir.join(format!("{}.1", app_name));
     fs::write(&man_file, buffer)?;
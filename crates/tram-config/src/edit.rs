@@ -0,0 +1,344 @@
+//! Reading and writing single settings by key path, for `tram config get`
+//! and `tram config set`.
+//!
+//! Keys are dotted paths such as `http.timeoutSecs`. Segments may be given
+//! in snake_case (`log_level`) for their camelCase keys, while the names of
+//! entries in tables such as `tasks` or `profiles` are kept as written.
+//!
+//! Edited files keep their format. TOML is edited in place, so comments and
+//! layout survive; JSON and YAML keep their key order, but YAML comments are
+//! dropped.
+
+use crate::{TramConfig, config_keys, describe_error};
+use schematic::{ConfigLoader, Format};
+use serde_json::Value;
+use std::error::Error;
+use std::path::Path;
+use tram_core::did_you_mean;
+
+/// A setting changed by [`set_config_value`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigEdit {
+    /// The key path as the config spells it, e.g. `logLevel`
+    pub key: String,
+    pub value: Value,
+    /// The file's new contents
+    pub contents: String,
+}
+
+/// The effective value of `key` in `config`.
+pub fn config_value(config: &TramConfig, key: &str) -> Result<Value, Box<dyn Error>> {
+    let (path, _) = resolve_key(key)?;
+    let value = serde_json::to_value(config)?;
+
+    path.iter()
+        .try_fold(&value, |value, segment| value.get(segment))
+        .cloned()
+        .ok_or_else(|| format!("'{}' is not set", path.join(".")).into())
+}
+
+/// Set `key` to `value` in the `contents` of config file `file`, which may
+/// be empty for a new file; the format follows the file's extension.
+/// `value` is read as a string for string settings, and as a YAML scalar or
+/// flow collection (`30`, `true`, `[a, b]`) otherwise. The result is
+/// validated before it's returned.
+pub fn set_config_value(
+    file: &Path,
+    contents: &str,
+    key: &str,
+    value: &str,
+) -> Result<ConfigEdit, Box<dyn Error>> {
+    let format = Format::detect(&file.to_string_lossy())?;
+    let (path, default) = resolve_key(key)?;
+    let value = parse_value(value, default.as_ref());
+
+    let contents = match format {
+        Format::Toml => set_toml(contents, &path, &value)?,
+        Format::Json | Format::Yaml => set_yaml(contents, format, &path, &value)?,
+        _ => return Err(format!("Unsupported config format: {:?}", format).into()),
+    };
+
+    let mut loader = ConfigLoader::<TramConfig>::new();
+    loader.code(contents.as_str(), format)?;
+    loader.load().map_err(|e| {
+        // Code sources have no name, so keep only what's wrong with the value
+        let message = describe_error(&e);
+        match message.split_once("<unknown>: ") {
+            Some((_, cause)) => cause.to_string(),
+            None => message,
+        }
+    })?;
+
+    Ok(ConfigEdit {
+        key: path.join("."),
+        value,
+        contents,
+    })
+}
+
+/// The key path as the config spells it, and its default value when the
+/// key is a known setting rather than part of a table entry.
+fn resolve_key(key: &str) -> Result<(Vec<String>, Option<Value>), Box<dyn Error>> {
+    let defaults = serde_json::to_value(TramConfig::default())?;
+    let segments: Vec<&str> = key.split('.').collect();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return Err(format!("Invalid config key '{}'", key).into());
+    }
+
+    let mut path: Vec<String> = Vec::new();
+    let mut node = Some(&defaults);
+    let mut in_table = false;
+
+    for segment in segments {
+        match node {
+            // A section of settings
+            Some(Value::Object(fields)) if !fields.is_empty() => {
+                let Some((name, value)) = [segment.to_string(), camel_case(segment)]
+                    .into_iter()
+                    .find_map(|name| fields.get(&name).map(|value| (name, value)))
+                else {
+                    return Err(unknown_key(key).into());
+                };
+                path.push(name);
+                node = Some(value);
+            }
+            // A table of named entries: the entry name is kept as written
+            Some(Value::Object(_)) => {
+                path.push(segment.to_string());
+                node = None;
+                in_table = true;
+            }
+            // Inside an entry, whose fields aren't in the defaults
+            None if in_table => path.push(camel_case(segment)),
+            _ => {
+                return Err(format!("'{}' is a setting, not a section", path.join(".")).into());
+            }
+        }
+    }
+
+    Ok((path, node.cloned()))
+}
+
+fn unknown_key(key: &str) -> String {
+    let keys = config_keys();
+    let suggestion = did_you_mean(
+        &camel_case_path(key),
+        keys.iter().map(|key| key.path.as_str()),
+    )
+    .map(|suggestion| format!(", did you mean '{}'?", suggestion))
+    .unwrap_or_default();
+
+    format!("Unknown config key '{}'{}", key, suggestion)
+}
+
+fn parse_value(raw: &str, default: Option<&Value>) -> Value {
+    match default {
+        Some(Value::String(_)) => Value::String(raw.to_string()),
+        _ if raw.is_empty() => Value::String(String::new()),
+        _ => serde_yaml::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string())),
+    }
+}
+
+fn set_toml(contents: &str, path: &[String], value: &Value) -> Result<String, Box<dyn Error>> {
+    let mut document: toml_edit::DocumentMut = contents.parse()?;
+    let (last, parents) = path.split_last().ok_or("Empty config key")?;
+
+    let mut table = document.as_table_mut() as &mut dyn toml_edit::TableLike;
+    for segment in parents {
+        let item = table
+            .entry(segment)
+            .or_insert_with(|| toml_edit::Item::Table(toml_edit::Table::new()));
+        table = item
+            .as_table_like_mut()
+            .ok_or_else(|| format!("'{}' in the config file isn't a table", segment))?;
+    }
+    let mut value = toml_value(value)?;
+    match table.get_mut(last) {
+        // Replace the value, keeping its key and comments
+        Some(toml_edit::Item::Value(existing)) => {
+            *value.decor_mut() = existing.decor().clone();
+            *existing = value;
+        }
+        _ => {
+            table.insert(last, toml_edit::Item::Value(value));
+        }
+    }
+
+    Ok(document.to_string())
+}
+
+fn toml_value(value: &Value) -> Result<toml_edit::Value, Box<dyn Error>> {
+    Ok(match value {
+        Value::Null => return Err("TOML has no null value".into()),
+        Value::Bool(value) => (*value).into(),
+        Value::Number(number) => match number.as_i64() {
+            Some(integer) => integer.into(),
+            None => number.as_f64().unwrap_or_default().into(),
+        },
+        Value::String(value) => value.as_str().into(),
+        Value::Array(values) => {
+            let mut array = toml_edit::Array::new();
+            for value in values {
+                array.push(toml_value(value)?);
+            }
+            array.into()
+        }
+        Value::Object(fields) => {
+            let mut table = toml_edit::InlineTable::new();
+            for (name, value) in fields {
+                table.insert(name, toml_value(value)?);
+            }
+            table.into()
+        }
+    })
+}
+
+/// JSON is valid YAML, and `serde_yaml` keeps mappings in order, so both are
+/// edited as YAML values.
+fn set_yaml(
+    contents: &str,
+    format: Format,
+    path: &[String],
+    value: &Value,
+) -> Result<String, Box<dyn Error>> {
+    let mut document: serde_yaml::Value = match contents.trim() {
+        "" => serde_yaml::Value::Mapping(Default::default()),
+        _ => serde_yaml::from_str(contents)?,
+    };
+
+    let mut node = &mut document;
+    for segment in path {
+        let mapping = node
+            .as_mapping_mut()
+            .ok_or_else(|| format!("'{}' in the config file isn't a mapping", segment))?;
+        node = mapping
+            .entry(segment.as_str().into())
+            .or_insert_with(|| serde_yaml::Value::Mapping(Default::default()));
+    }
+    *node = serde_yaml::to_value(value)?;
+
+    Ok(match format {
+        Format::Json => serde_json::to_string_pretty(&document)? + "\n",
+        _ => serde_yaml::to_string(&document)?,
+    })
+}
+
+/// `log_level` → `logLevel`
+fn camel_case(segment: &str) -> String {
+    let mut words = segment.split('_');
+    let first = words.next().unwrap_or_default().to_string();
+
+    words.fold(first, |mut name, word| {
+        let mut chars = word.chars();
+        if let Some(initial) = chars.next() {
+            name.extend(initial.to_uppercase());
+            name.push_str(chars.as_str());
+        }
+        name
+    })
+}
+
+fn camel_case_path(key: &str) -> String {
+    key.split('.').map(camel_case).collect::<Vec<_>>().join(".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_key() {
+        assert_eq!(resolve_key("log_level").unwrap().0, ["logLevel"]);
+        assert_eq!(
+            resolve_key("http.timeout_secs").unwrap().0,
+            ["http", "timeoutSecs"]
+        );
+        // Entry names are kept, their fields are camelCased
+        assert_eq!(
+            resolve_key("tasks.unit_tests.depends_on").unwrap().0,
+            ["tasks", "unit_tests", "dependsOn"]
+        );
+
+        let error = resolve_key("logLevl").unwrap_err().to_string();
+        assert!(error.contains("did you mean 'logLevel'"), "{}", error);
+        assert!(resolve_key("logLevel.inner").is_err());
+        assert!(resolve_key("http.").is_err());
+    }
+
+    #[test]
+    fn test_set_toml_keeps_comments() {
+        let contents = "# Team settings\nlogLevel = \"info\" # noisy otherwise\n\n[http]\n# Slow proxy\ntimeoutSecs = 10\n";
+
+        let edit =
+            set_config_value(Path::new("tram.toml"), contents, "log_level", "debug").unwrap();
+        assert_eq!(edit.key, "logLevel");
+        assert_eq!(
+            edit.contents,
+            "# Team settings\nlogLevel = \"debug\" # noisy otherwise\n\n[http]\n# Slow proxy\ntimeoutSecs = 10\n"
+        );
+
+        let edit = set_config_value(
+            Path::new("tram.toml"),
+            &edit.contents,
+            "http.timeoutSecs",
+            "30",
+        )
+        .unwrap();
+        assert_eq!(edit.value, Value::from(30));
+        assert!(edit.contents.contains("# Slow proxy\ntimeoutSecs = 30\n"));
+    }
+
+    #[test]
+    fn test_set_in_new_file() {
+        let edit = set_config_value(Path::new("tram.toml"), "", "http.timeoutSecs", "30").unwrap();
+        assert_eq!(edit.contents, "[http]\ntimeoutSecs = 30\n");
+
+        let edit = set_config_value(Path::new(".tram.yml"), "", "offline", "true").unwrap();
+        assert_eq!(edit.contents, "offline: true\n");
+
+        let edit = set_config_value(Path::new("tram.json"), "", "logLevel", "warn").unwrap();
+        assert_eq!(edit.contents, "{\n  \"logLevel\": \"warn\"\n}\n");
+    }
+
+    #[test]
+    fn test_set_json_keeps_key_order() {
+        let contents = "{\n  \"outputFormat\": \"json\",\n  \"logLevel\": \"info\"\n}\n";
+
+        let edit = set_config_value(Path::new("tram.json"), contents, "logLevel", "error").unwrap();
+        assert_eq!(
+            edit.contents,
+            "{\n  \"outputFormat\": \"json\",\n  \"logLevel\": \"error\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_set_table_entry() {
+        let edit = set_config_value(
+            Path::new("tram.yaml"),
+            "",
+            "tasks.lint.command",
+            "cargo clippy -- -D warnings",
+        )
+        .unwrap();
+        assert_eq!(
+            edit.contents,
+            "tasks:\n  lint:\n    command: cargo clippy -- -D warnings\n"
+        );
+    }
+
+    #[test]
+    fn test_set_rejects_invalid_values() {
+        assert!(set_config_value(Path::new("tram.toml"), "", "logLevel", "loud").is_err());
+        assert!(set_config_value(Path::new("tram.toml"), "", "http.timeoutSecs", "soon").is_err());
+        assert!(set_config_value(Path::new("tram.toml"), "", "nope", "1").is_err());
+    }
+
+    #[test]
+    fn test_config_value() {
+        let config = TramConfig::default();
+
+        assert_eq!(config_value(&config, "log_level").unwrap(), "info");
+        assert_eq!(config_value(&config, "offline").unwrap(), false);
+        assert!(config_value(&config, "tasks.missing").is_err());
+    }
+}
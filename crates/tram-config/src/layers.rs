@@ -0,0 +1,305 @@
+//! Loading the config from every layer that has a file.
+//!
+//! Config files are merged in this order, each overriding the ones before:
+//!
+//! 1. System: `config.{json,yaml,yml,toml}` in `/etc/tram`
+//! 2. User: `config.{json,yaml,yml,toml}` in the user config directory, e.g.
+//!    `$XDG_CONFIG_HOME/tram`
+//! 3. Workspace: `tram.*` or `.tram.*` at the workspace root
+//! 4. Local: `tram.*` or `.tram.*` in the current directory
+//!
+//! Each layer uses the first of its file names that exists, and the
+//! workspace layer is skipped when the current directory is the workspace
+//! root. Named entries such as tasks and profiles are merged by name, so a
+//! layer can add a task while an entry of the same name replaces the earlier
+//! one as a whole. Env vars, the active profile, and flags are applied over
+//! the merged files.
+
+use crate::{LOCAL_CONFIG_FILES, TramConfig, USER_CONFIG_FILES};
+use schematic::ConfigLoader;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use tram_workspace::WorkspaceDetector;
+
+/// Settings whose entries are merged by name across layers.
+const MERGED_MAPS: [&str; 4] = ["tasks", "schedules", "profiles", "watch.tasks"];
+
+/// Map settings a later layer replaces as a whole.
+const REPLACED_MAPS: [&str; 1] = ["telemetry.headers"];
+
+/// Where a config file sits in the precedence order, lowest first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigLayer {
+    System,
+    User,
+    Workspace,
+    Local,
+}
+
+impl std::fmt::Display for ConfigLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigLayer::System => write!(f, "system"),
+            ConfigLayer::User => write!(f, "user"),
+            ConfigLayer::Workspace => write!(f, "workspace"),
+            ConfigLayer::Local => write!(f, "local"),
+        }
+    }
+}
+
+/// A config file and the layer it was found in.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFile {
+    pub layer: ConfigLayer,
+    pub path: PathBuf,
+}
+
+/// The config merged from every layer, and where its values came from.
+#[derive(Clone, Debug)]
+pub struct LayeredConfig {
+    pub config: TramConfig,
+    /// The files that were merged, lowest precedence first
+    pub files: Vec<ConfigFile>,
+    /// The file that supplied each value set in a file, by dotted key path
+    /// such as `http.timeoutSecs` or `tasks.lint.command`
+    pub sources: BTreeMap<String, ConfigFile>,
+}
+
+impl LayeredConfig {
+    /// The file that supplied `key`, or `None` for defaults and values only
+    /// set by env vars.
+    pub fn source(&self, key: &str) -> Option<&ConfigFile> {
+        self.sources.get(key)
+    }
+}
+
+impl TramConfig {
+    /// Load and merge the config files of every layer, over the defaults and
+    /// under env vars.
+    pub fn load_layered() -> Result<LayeredConfig, Box<dyn Error>> {
+        Self::load_files(Self::config_files())
+    }
+
+    /// The config file of each layer that has one, lowest precedence first.
+    pub fn config_files() -> Vec<ConfigFile> {
+        let cwd = tram_core::current_dir();
+        let workspace_root = WorkspaceDetector::from_dir(cwd.clone()).detect_root().ok();
+
+        layer_files(
+            tram_core::system_config_dir().as_deref(),
+            tram_core::config_dir().as_deref(),
+            workspace_root.as_deref(),
+            &cwd,
+        )
+    }
+
+    /// Merge `files`, lowest precedence first, over the defaults and under
+    /// env vars.
+    pub fn load_files(files: Vec<ConfigFile>) -> Result<LayeredConfig, Box<dyn Error>> {
+        let mut loader = ConfigLoader::<Self>::new();
+        let mut sources = BTreeMap::new();
+
+        for file in &files {
+            loader.file(&file.path)?;
+
+            let keys = Self::keys_set_in_file(&file.path)?;
+            for prefix in keys.iter().filter_map(|key| replaced_prefix(key)) {
+                let nested = format!("{}.", prefix);
+                sources.retain(|key: &String, _| *key != prefix && !key.starts_with(&nested));
+            }
+            for key in keys {
+                sources.insert(key, file.clone());
+            }
+        }
+
+        let result = loader.load()?;
+        Ok(LayeredConfig {
+            config: result.config.normalized(),
+            files,
+            sources,
+        })
+    }
+
+    /// Dotted paths of every value set in a config file.
+    pub fn keys_set_in_file(path: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut loader = ConfigLoader::<Self>::new();
+        loader.file(path)?;
+
+        // The partial has only what the file sets: no defaults or env vars
+        let partial = loader.load_partial(&Default::default())?;
+        Ok(value_keys(&serde_json::to_value(partial)?))
+    }
+}
+
+/// Dotted paths of every value set in a serialized (partial) config.
+pub(crate) fn value_keys(value: &serde_json::Value) -> Vec<String> {
+    fn walk(prefix: &str, value: &serde_json::Value, keys: &mut Vec<String>) {
+        match value {
+            serde_json::Value::Null => {}
+            serde_json::Value::Object(fields) => {
+                for (name, value) in fields {
+                    let path = match prefix {
+                        "" => name.clone(),
+                        _ => format!("{}.{}", prefix, name),
+                    };
+                    walk(&path, value, keys);
+                }
+            }
+            _ => keys.push(prefix.to_string()),
+        }
+    }
+
+    let mut keys = Vec::new();
+    walk("", value, &mut keys);
+    keys
+}
+
+/// The part of the config a later layer replaces by setting `key`: the
+/// map entry or map it belongs to, if any.
+fn replaced_prefix(key: &str) -> Option<String> {
+    for map in MERGED_MAPS {
+        if let Some(entry) = key
+            .strip_prefix(map)
+            .and_then(|rest| rest.strip_prefix('.'))
+        {
+            let name = entry.split('.').next().unwrap_or(entry);
+            return Some(format!("{}.{}", map, name));
+        }
+    }
+
+    REPLACED_MAPS
+        .iter()
+        .find(|map| key.starts_with(&format!("{}.", map)))
+        .map(|map| map.to_string())
+}
+
+fn layer_files(
+    system_dir: Option<&Path>,
+    user_dir: Option<&Path>,
+    workspace_root: Option<&Path>,
+    cwd: &Path,
+) -> Vec<ConfigFile> {
+    let first = |dir: &Path, names: &[&str]| {
+        names
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+    };
+
+    let layers = [
+        (ConfigLayer::System, system_dir, &USER_CONFIG_FILES[..]),
+        (ConfigLayer::User, user_dir, &USER_CONFIG_FILES[..]),
+        (
+            ConfigLayer::Workspace,
+            workspace_root.filter(|root| *root != cwd),
+            &LOCAL_CONFIG_FILES[..],
+        ),
+        (ConfigLayer::Local, Some(cwd), &LOCAL_CONFIG_FILES[..]),
+    ];
+
+    let mut files: Vec<ConfigFile> = Vec::new();
+    for (layer, dir, names) in layers {
+        let Some(path) = dir.and_then(|dir| first(dir, names)) else {
+            continue;
+        };
+        // e.g. the user config directory set to the current directory
+        if !files.iter().any(|file| file.path == path) {
+            files.push(ConfigFile { layer, path });
+        }
+    }
+
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LogLevel;
+    use tempfile::TempDir;
+
+    fn write(path: PathBuf, contents: &str) -> PathBuf {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_layer_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let system = write(dir.join("etc/config.toml"), "");
+        let user = write(dir.join("home/config.yaml"), "");
+        write(dir.join("home/config.toml"), "");
+        let workspace = write(dir.join("repo/tram.toml"), "");
+        let local = write(dir.join("repo/app/.tram.json"), "");
+
+        let files = layer_files(
+            Some(&dir.join("etc")),
+            Some(&dir.join("home")),
+            Some(&dir.join("repo")),
+            &dir.join("repo/app"),
+        );
+        let found: Vec<(ConfigLayer, &Path)> = files
+            .iter()
+            .map(|file| (file.layer, file.path.as_path()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (ConfigLayer::System, system.as_path()),
+                (ConfigLayer::User, user.as_path()),
+                (ConfigLayer::Workspace, workspace.as_path()),
+                (ConfigLayer::Local, local.as_path()),
+            ]
+        );
+
+        // At the workspace root its files are the local layer
+        let files = layer_files(None, None, Some(&dir.join("repo")), &dir.join("repo"));
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].layer, ConfigLayer::Local);
+    }
+
+    #[test]
+    fn test_load_files_merges_layers() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let system = ConfigFile {
+            layer: ConfigLayer::System,
+            path: write(
+                dir.join("config.toml"),
+                "logLevel = \"warn\"\noffline = true\n\n[http]\ntimeoutSecs = 60\n\n[tasks.lint]\ncommand = \"cargo clippy\"\n\n[tasks.test]\ncommand = \"make test\"\ncwd = \"tests\"\n",
+            ),
+        };
+        let local = ConfigFile {
+            layer: ConfigLayer::Local,
+            path: write(
+                dir.join("tram.yaml"),
+                "logLevel: debug\ntasks:\n  test:\n    command: cargo test\n",
+            ),
+        };
+
+        let layered = TramConfig::load_files(vec![system.clone(), local.clone()]).unwrap();
+        assert_eq!(layered.config.log_level, LogLevel::Debug);
+        assert!(layered.config.offline);
+        assert_eq!(layered.config.http.timeout_secs, 60);
+        assert_eq!(
+            layered.config.tasks.keys().collect::<Vec<_>>(),
+            ["lint", "test"]
+        );
+
+        assert_eq!(layered.source("logLevel"), Some(&local));
+        assert_eq!(layered.source("offline"), Some(&system));
+        assert_eq!(layered.source("http.timeoutSecs"), Some(&system));
+        assert_eq!(layered.source("tasks.lint.command"), Some(&system));
+        // The local test task replaced the system one as a whole
+        assert_eq!(layered.config.tasks["test"].command, "cargo test");
+        assert_eq!(layered.config.tasks["test"].cwd, None);
+        assert_eq!(layered.source("tasks.test.command"), Some(&local));
+        assert_eq!(layered.source("tasks.test.cwd"), None);
+        assert_eq!(layered.source("color"), None);
+    }
+}
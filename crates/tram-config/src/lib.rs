@@ -5,33 +5,40 @@
 //! Includes hot reload functionality for development workflows.
 
 use async_trait::async_trait;
+use notify::event::ModifyKind;
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use schematic::{Config, ConfigLoader};
+use schematic::{Config, ConfigLoader, PartialConfig};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::{RwLock, mpsc};
+use std::time::Duration;
+use tokio::sync::{RwLock, mpsc, watch};
 use tracing::{debug, error, info, warn};
+use tram_core::{BorderStyle, EnvVar, LogRotation, did_you_mean};
+
+mod edit;
+mod layers;
+
+pub use edit::*;
+pub use layers::*;
 
 /// Log level configuration.
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum LogLevel {
+    Trace,
     Debug,
+    #[default]
     Info,
     Warn,
     Error,
 }
 
-impl Default for LogLevel {
-    fn default() -> Self {
-        Self::Info
-    }
-}
-
 impl std::fmt::Display for LogLevel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            LogLevel::Trace => write!(f, "trace"),
             LogLevel::Debug => write!(f, "debug"),
             LogLevel::Info => write!(f, "info"),
             LogLevel::Warn => write!(f, "warn"),
@@ -45,6 +52,7 @@ impl std::str::FromStr for LogLevel {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
+            "trace" => Ok(LogLevel::Trace),
             "debug" => Ok(LogLevel::Debug),
             "info" => Ok(LogLevel::Info),
             "warn" => Ok(LogLevel::Warn),
@@ -61,18 +69,16 @@ impl From<&str> for LogLevel {
 }
 
 /// Output format configuration.
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
     Json,
     Yaml,
+    #[default]
     Table,
-}
-
-impl Default for OutputFormat {
-    fn default() -> Self {
-        Self::Table
-    }
+    Plain,
+    Csv,
+    Tsv,
 }
 
 impl std::fmt::Display for OutputFormat {
@@ -81,6 +87,9 @@ impl std::fmt::Display for OutputFormat {
             OutputFormat::Json => write!(f, "json"),
             OutputFormat::Yaml => write!(f, "yaml"),
             OutputFormat::Table => write!(f, "table"),
+            OutputFormat::Plain => write!(f, "plain"),
+            OutputFormat::Csv => write!(f, "csv"),
+            OutputFormat::Tsv => write!(f, "tsv"),
         }
     }
 }
@@ -93,6 +102,9 @@ impl std::str::FromStr for OutputFormat {
             "json" => Ok(OutputFormat::Json),
             "yaml" => Ok(OutputFormat::Yaml),
             "table" => Ok(OutputFormat::Table),
+            "plain" => Ok(OutputFormat::Plain),
+            "csv" => Ok(OutputFormat::Csv),
+            "tsv" => Ok(OutputFormat::Tsv),
             _ => Err(format!("Invalid output format: {}", s)),
         }
     }
@@ -104,14 +116,57 @@ impl From<&str> for OutputFormat {
     }
 }
 
+/// Where metrics are pushed.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricsExporter {
+    /// Metrics aren't exported
+    #[default]
+    None,
+    /// An OpenTelemetry collector, over OTLP/HTTP with JSON
+    Otlp,
+    /// A Prometheus Pushgateway
+    Prometheus,
+}
+
+impl std::fmt::Display for MetricsExporter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetricsExporter::None => write!(f, "none"),
+            MetricsExporter::Otlp => write!(f, "otlp"),
+            MetricsExporter::Prometheus => write!(f, "prometheus"),
+        }
+    }
+}
+
+impl std::str::FromStr for MetricsExporter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(MetricsExporter::None),
+            "otlp" => Ok(MetricsExporter::Otlp),
+            "prometheus" => Ok(MetricsExporter::Prometheus),
+            _ => Err(format!("Invalid metrics exporter: {}", s)),
+        }
+    }
+}
+
+impl From<&str> for MetricsExporter {
+    fn from(s: &str) -> Self {
+        s.parse().unwrap_or_default()
+    }
+}
+
 /// Main configuration structure using schematic.
 #[derive(Clone, Debug, Deserialize, Serialize, Config)]
+#[serde(rename_all = "camelCase")]
 pub struct TramConfig {
-    /// Log level (debug, info, warn, error)
+    /// Log level (trace, debug, info, warn, error)
     #[setting(default = "info", env = "TRAM_LOG_LEVEL")]
     pub log_level: LogLevel,
 
-    /// Output format (json, yaml, table)
+    /// Output format (json, yaml, table, plain, csv, tsv)
     #[setting(default = "table", env = "TRAM_OUTPUT_FORMAT")]
     pub output_format: OutputFormat,
 
@@ -119,9 +174,460 @@ pub struct TramConfig {
     #[setting(default = true, env = "TRAM_COLOR")]
     pub color: bool,
 
+    /// Table borders (unicode, ascii, none)
+    #[setting(default = "unicode", env = "TRAM_TABLE_BORDER")]
+    pub table_border: BorderStyle,
+
     /// Workspace root directory
     #[setting(env = "TRAM_WORKSPACE_ROOT")]
     pub workspace_root: Option<PathBuf>,
+
+    /// Also write complete debug logs to this file, replacing it each run
+    #[setting(env = "TRAM_LOG_FILE")]
+    pub log_file: Option<PathBuf>,
+
+    /// When the log file starts over: `never` replaces it each run, `daily`
+    /// writes to `<logFile>.YYYY-MM-DD`, and a size such as `10MB` appends
+    /// until it's reached, keeping older logs as `<logFile>.1`, `.2`, ...
+    #[setting(default = "never", env = "TRAM_LOG_ROTATION")]
+    pub log_rotation: LogRotation,
+
+    /// Check for newer releases (at most once per day)
+    #[setting(default = false, env = "TRAM_CHECK_FOR_UPDATES")]
+    pub check_for_updates: bool,
+
+    /// Never use the network: update checks and metrics export are skipped,
+    /// downloads come from the cache, and anything else that needs the
+    /// network fails
+    #[setting(default = false, env = "TRAM_OFFLINE")]
+    pub offline: bool,
+
+    /// Styles for success, warnings, errors, and headings
+    #[setting(nested)]
+    pub theme: ThemeConfig,
+
+    /// Watch mode settings
+    #[setting(nested)]
+    pub watch: WatchConfig,
+
+    /// HTTP client settings
+    #[setting(nested)]
+    pub http: HttpConfig,
+
+    /// Cache size budget and garbage collection
+    #[setting(nested)]
+    pub cache: CacheConfig,
+
+    /// Where update checks and `tram self-update` look for releases
+    #[setting(nested)]
+    pub update: UpdateConfig,
+
+    /// Metrics export, separate from the opt-in usage telemetry of
+    /// `tram telemetry`
+    #[setting(nested)]
+    pub telemetry: TelemetryConfig,
+
+    /// Tasks for `tram run`, by name
+    #[setting(nested, merge = schematic::merge::merge_btreemap)]
+    pub tasks: BTreeMap<String, TaskConfig>,
+
+    /// Commands run on a schedule while `tram watch` or the daemon is
+    /// running, by name
+    #[setting(nested, merge = schematic::merge::merge_btreemap)]
+    pub schedules: BTreeMap<String, ScheduleConfig>,
+
+    /// Named overlays of settings, selected with `--profile` or `TRAM_PROFILE`
+    #[setting(merge = schematic::merge::merge_btreemap)]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+}
+
+/// Settings a profile overrides, in the same shape as the rest of the
+/// config; anything unset keeps the base value.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(transparent)]
+pub struct ProfileConfig(pub PartialTramConfig);
+
+impl ProfileConfig {
+    /// Dotted paths of the settings the profile sets, such as `logLevel` or
+    /// `http.timeoutSecs`.
+    pub fn keys(&self) -> Vec<String> {
+        serde_json::to_value(&self.0)
+            .map(|value| layers::value_keys(&value))
+            .unwrap_or_default()
+    }
+}
+
+/// Styles for each role output can play, as words such as `"bold green"`:
+/// a color (black, red, green, yellow, blue, magenta, cyan, white, gray),
+/// `bold`, `italic`, `underline`, or `none`.
+#[derive(Clone, Debug, Deserialize, Serialize, Config)]
+#[serde(rename_all = "camelCase")]
+pub struct ThemeConfig {
+    /// Finished work and passing checks
+    #[setting(default = "green")]
+    pub success: String,
+
+    /// Warnings and skipped work
+    #[setting(default = "yellow")]
+    pub warning: String,
+
+    /// Failures
+    #[setting(default = "red")]
+    pub error: String,
+
+    /// Titles and table headers
+    #[setting(default = "bold")]
+    pub heading: String,
+
+    /// Highlights such as spinner frames and prompt answers
+    #[setting(default = "cyan")]
+    pub info: String,
+
+    /// Secondary text such as hints
+    #[setting(default = "gray")]
+    pub muted: String,
+}
+
+/// Watch mode settings.
+#[derive(Clone, Debug, Deserialize, Serialize, Config)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchConfig {
+    /// Milliseconds without changes before a batch of changes is handled,
+    /// so a formatter touching many files triggers one run
+    #[setting(default = 200)]
+    pub debounce_ms: u64,
+
+    /// Shell command `watch --check` runs instead of `just check` or the
+    /// project type's checks
+    pub check_command: Option<String>,
+
+    /// Tasks run when matching files change, by name
+    #[setting(nested, merge = schematic::merge::merge_btreemap)]
+    pub tasks: BTreeMap<String, WatchTaskConfig>,
+}
+
+/// HTTP client settings, for update checks, downloads, and API calls.
+#[derive(Clone, Debug, Deserialize, Serialize, Config)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpConfig {
+    /// Proxy for every request (e.g. `http://proxy.internal:3128`); when
+    /// unset, `HTTPS_PROXY`, `HTTP_PROXY`, and `ALL_PROXY` are honored
+    #[setting(env = "TRAM_HTTP_PROXY")]
+    pub proxy: Option<String>,
+
+    /// PEM file of CA certificates to trust instead of the bundled roots,
+    /// for networks that intercept TLS
+    #[setting(env = "TRAM_HTTP_CA_FILE")]
+    pub ca_file: Option<PathBuf>,
+
+    /// Seconds before a request, including reading its body, is abandoned
+    #[setting(default = 30)]
+    pub timeout_secs: u64,
+
+    /// Times a request is retried after a connection failure or a 429 or
+    /// 5xx response
+    #[setting(default = 3)]
+    pub retries: u32,
+}
+
+/// Cache size budget and garbage collection.
+#[derive(Clone, Debug, Deserialize, Serialize, Config)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheConfig {
+    /// Largest the cache may grow (e.g. `1GB` or `512MiB`) before the least
+    /// recently used entries are evicted; unlimited when unset
+    #[setting(env = "TRAM_CACHE_MAX_SIZE")]
+    pub max_size: Option<String>,
+
+    /// Hours between background garbage collections, which remove expired
+    /// entries and orphaned files and enforce `maxSize`; 0 turns them off
+    #[setting(default = 24)]
+    pub gc_interval_hours: u64,
+}
+
+/// Where update checks and `tram self-update` look for releases.
+#[derive(Clone, Debug, Deserialize, Serialize, Config)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateConfig {
+    /// GitHub repository publishing releases, as `owner/name` or a
+    /// `https://github.com/` URL; defaults to tram's own repository
+    #[setting(env = "TRAM_UPDATE_REPOSITORY")]
+    pub repository: Option<String>,
+
+    /// Base URL of the GitHub API, for GitHub Enterprise
+    /// (`https://github.example.com/api/v3`) or a mirror
+    #[setting(env = "TRAM_UPDATE_API_URL")]
+    pub api_url: Option<String>,
+}
+
+/// Metrics export: command durations, cache hits and misses, and watch
+/// triggers, pushed when each command finishes.
+#[derive(Clone, Debug, Deserialize, Serialize, Config)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryConfig {
+    /// Where to push metrics: none, otlp, or prometheus
+    #[setting(default = "none", env = "TRAM_METRICS_EXPORTER")]
+    pub exporter: MetricsExporter,
+
+    /// Collector URL; defaults to `http://localhost:4318` for OTLP (with
+    /// `/v1/metrics` appended) and `http://localhost:9091` for a Pushgateway
+    #[setting(env = "TRAM_METRICS_ENDPOINT")]
+    pub endpoint: Option<String>,
+
+    /// Extra request headers, such as an API key; values may be `secret://`
+    /// references
+    pub headers: BTreeMap<String, String>,
+
+    /// The OTLP `service.name` and the Pushgateway job
+    #[setting(default = "tram")]
+    pub service_name: String,
+}
+
+/// A command run by watch mode when files matching a glob change.
+#[derive(Clone, Debug, Deserialize, Serialize, Config)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchTaskConfig {
+    /// Workspace-relative glob of files that trigger the task (e.g. `src/**/*.rs`)
+    pub glob: String,
+
+    /// Shell command to run (e.g. `cargo test`)
+    pub run: String,
+}
+
+/// A named command run by `tram run`.
+#[derive(Clone, Debug, Deserialize, Serialize, Config)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskConfig {
+    /// Shell command to run (e.g. `cargo build --release`)
+    pub command: String,
+
+    /// Working directory, relative to the workspace root
+    pub cwd: Option<PathBuf>,
+
+    /// Extra environment variables
+    pub env: BTreeMap<String, String>,
+
+    /// Tasks that must succeed before this one runs
+    pub depends_on: Vec<String>,
+}
+
+/// A command run on a cron schedule while `tram watch` or the daemon is
+/// running.
+#[derive(Clone, Debug, Deserialize, Serialize, Config)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleConfig {
+    /// Cron expression in local time (e.g. `0 3 * * *` or `@hourly`)
+    pub cron: String,
+
+    /// Shell command to run from the workspace root (e.g. `tram cache prune`)
+    pub run: String,
+
+    /// Seconds each run may be delayed by, so machines sharing a config
+    /// don't all start at once
+    #[setting(default = 0)]
+    pub jitter_secs: u64,
+}
+
+/// Config file names looked up in the current directory, in priority order.
+pub const LOCAL_CONFIG_FILES: [&str; 8] = [
+    "tram.json",
+    "tram.yaml",
+    "tram.yml",
+    "tram.toml",
+    ".tram.json",
+    ".tram.yaml",
+    ".tram.yml",
+    ".tram.toml",
+];
+
+/// Config file names looked up in the user config directory, in priority order.
+pub const USER_CONFIG_FILES: [&str; 4] =
+    ["config.json", "config.yaml", "config.yml", "config.toml"];
+
+/// Values accepted by settings of an enum type, by the type's name in the
+/// config metadata. The values are the enum's serde variant names.
+fn enum_values(type_alias: &str) -> Option<&'static [&'static str]> {
+    match type_alias {
+        "LogLevel" => Some(serde_variants::<LogLevel>()),
+        "OutputFormat" => Some(serde_variants::<OutputFormat>()),
+        "BorderStyle" => Some(serde_variants::<BorderStyle>()),
+        "MetricsExporter" => Some(serde_variants::<MetricsExporter>()),
+        _ => None,
+    }
+}
+
+/// The names an enum deserializes from, as its serde derive lists them.
+fn serde_variants<T: serde::de::DeserializeOwned>() -> &'static [&'static str] {
+    use serde::de::{Error, Visitor, value};
+
+    /// Records the variants serde asks for, then gives up.
+    struct Variants<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> serde::Deserializer<'de> for Variants<'_> {
+        type Error = value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, value::Error> {
+            Err(value::Error::custom("not an enum"))
+        }
+
+        fn deserialize_enum<V: Visitor<'de>>(
+            self,
+            _: &'static str,
+            variants: &'static [&'static str],
+            _: V,
+        ) -> Result<V::Value, value::Error> {
+            *self.0 = variants;
+            Err(value::Error::custom("only reading the variants"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map struct identifier ignored_any
+        }
+    }
+
+    let mut variants: &'static [&'static str] = &[];
+    let _ = T::deserialize(Variants(&mut variants));
+    variants
+}
+
+/// A config key, as a dotted path such as `http.timeoutSecs`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigKey {
+    pub path: String,
+    /// The values it accepts, when there's a fixed set
+    pub values: Vec<&'static str>,
+}
+
+/// Every config key, for completing key paths and their values.
+///
+/// Keys come from the shape of the default config and their values from the
+/// config metadata, so new settings are picked up without being listed here.
+/// Tables of named entries, such as `tasks` or `telemetry.headers`, are
+/// listed themselves rather than their entries.
+pub fn config_keys() -> Vec<ConfigKey> {
+    fn types(
+        prefix: &str,
+        settings: schematic::ConfigSettingMap,
+        map: &mut BTreeMap<String, String>,
+    ) {
+        for (name, setting) in settings {
+            let path = if prefix.is_empty() {
+                name
+            } else {
+                format!("{}.{}", prefix, name)
+            };
+            if let Some(nested) = setting.nested {
+                types(&path, nested, map);
+            }
+            map.insert(path, setting.type_alias);
+        }
+    }
+
+    fn walk(
+        prefix: &str,
+        value: &serde_json::Value,
+        types: &BTreeMap<String, String>,
+        keys: &mut Vec<ConfigKey>,
+    ) {
+        match value {
+            serde_json::Value::Object(fields) if !fields.is_empty() => {
+                for (name, value) in fields {
+                    let path = if prefix.is_empty() {
+                        name.clone()
+                    } else {
+                        format!("{}.{}", prefix, name)
+                    };
+                    walk(&path, value, types, keys);
+                }
+            }
+            _ => {
+                let values = match value {
+                    serde_json::Value::Bool(_) => vec!["true", "false"],
+                    _ => types
+                        .get(prefix)
+                        .and_then(|type_alias| enum_values(type_alias))
+                        .map(<[_]>::to_vec)
+                        .unwrap_or_default(),
+                };
+                keys.push(ConfigKey {
+                    path: prefix.to_string(),
+                    values,
+                });
+            }
+        }
+    }
+
+    let mut type_aliases = BTreeMap::new();
+    types("", TramConfig::settings(), &mut type_aliases);
+
+    let mut keys = Vec::new();
+    if let Ok(defaults) = serde_json::to_value(TramConfig::default()) {
+        walk("", &defaults, &type_aliases, &mut keys);
+    }
+    keys
+}
+
+/// Environment variables bound to config settings, nested ones included,
+/// taken from the `env` attributes on the config structs.
+pub fn config_env_vars() -> Vec<EnvVar> {
+    fn walk(prefix: &str, settings: schematic::ConfigSettingMap, vars: &mut Vec<EnvVar>) {
+        for (name, setting) in settings {
+            let path = if prefix.is_empty() {
+                name
+            } else {
+                format!("{}.{}", prefix, name)
+            };
+
+            if let Some(env_key) = setting.env_key {
+                vars.push(
+                    EnvVar::new(env_key, format!("Sets the `{}` config setting", path))
+                        .with_setting(&path),
+                );
+            }
+            if let Some(nested) = setting.nested {
+                walk(&path, nested, vars);
+            }
+        }
+    }
+
+    let mut vars = Vec::new();
+    walk("", TramConfig::settings(), &mut vars);
+    vars
+}
+
+/// Top-level settings that can be set from the environment, with their
+/// variables.
+pub fn setting_env_vars() -> Vec<(String, String)> {
+    config_env_vars()
+        .into_iter()
+        .filter_map(|var| Some((var.setting?, var.name)))
+        .filter(|(key, _)| !key.contains('.'))
+        .collect()
+}
+
+/// Where a setting's effective value came from.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SettingSource {
+    Default,
+    File(PathBuf),
+    Env(String),
+    Profile(String),
+    Flag(String),
+}
+
+impl std::fmt::Display for SettingSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingSource::Default => write!(f, "default"),
+            SettingSource::File(path) => write!(f, "{}", path.display()),
+            SettingSource::Env(var) => write!(f, "${}", var),
+            SettingSource::Profile(name) => write!(f, "profile {}", name),
+            SettingSource::Flag(flag) => write!(f, "{}", flag),
+        }
+    }
 }
 
 impl TramConfig {
@@ -129,12 +635,12 @@ impl TramConfig {
     pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
         let loader = ConfigLoader::<Self>::new();
         let result = loader.load()?;
-        Ok(result.config)
+        Ok(result.config.normalized())
     }
 
-    /// Load configuration from a specific file.
+    /// Load configuration from a specific file. `~` and `$VAR` are expanded.
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
-        let path = path.as_ref();
+        let path = tram_core::expand_path(path);
 
         // Validate file extension
         match path.extension().and_then(|ext| ext.to_str()) {
@@ -145,43 +651,245 @@ impl TramConfig {
         }
 
         let mut loader = ConfigLoader::<Self>::new();
-        loader.file(path)?;
+        loader.file(&path)?;
         let result = loader.load()?;
-        Ok(result.config)
+        Ok(result.config.normalized())
     }
 
-    /// Find and load from common config file locations.
+    /// Load several config files merged in order, each overriding the ones
+    /// before it.
+    pub fn load_from_files(paths: &[PathBuf]) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut loader = ConfigLoader::<Self>::new();
+        for path in paths {
+            loader.file(tram_core::expand_path(path))?;
+        }
+
+        let result = loader.load()?;
+        Ok(result.config.normalized())
+    }
+
+    /// Load the config files of every layer (system, user, workspace, and
+    /// current directory) merged in precedence order. See
+    /// [`TramConfig::load_layered`] for where each value came from.
     pub fn load_from_common_paths() -> Result<Self, Box<dyn std::error::Error>> {
-        let config_paths = [
-            "tram.json",
-            "tram.yaml",
-            "tram.yml",
-            "tram.toml",
-            ".tram.json",
-            ".tram.yaml",
-            ".tram.yml",
-            ".tram.toml",
-        ];
+        Ok(Self::load_layered()?.config)
+    }
 
+    /// The highest-precedence config file `load_from_common_paths` reads,
+    /// which is the one `tram config set` edits.
+    pub fn find_config_file() -> Option<PathBuf> {
+        Self::config_files().pop().map(|file| file.path)
+    }
+
+    /// Top-level keys set in a config file, such as `logLevel`.
+    pub fn keys_in_file<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
         let mut loader = ConfigLoader::<Self>::new();
+        loader.file(tram_core::expand_path(path))?;
+
+        // The partial has only what the file sets: no defaults or env vars
+        let partial = loader.load_partial(&Default::default())?;
+        let keys = match serde_json::to_value(partial)? {
+            serde_json::Value::Object(map) => map
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, _)| key)
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        Ok(keys)
+    }
 
-        // Look for the first existing config file
-        for path in &config_paths {
-            let path_buf = PathBuf::from(path);
-            if path_buf.exists() {
-                loader.file(&path_buf)?;
-                break;
+    /// Overlay a profile's settings the way a config layer would: nested
+    /// sections are merged setting by setting, and a named entry such as a
+    /// task replaces the entry of the same name. Settings given as env vars
+    /// keep their env value, as env vars win over everything in the config
+    /// file.
+    pub fn apply_profile(&mut self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(profile) = self.profiles.get(name).cloned() else {
+            let available: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            let suggestion = did_you_mean(name, available.iter().copied())
+                .map(|suggestion| format!(", did you mean '{}'?", suggestion))
+                .unwrap_or_default();
+
+            return Err(format!(
+                "Unknown profile '{}'{} (available: {})",
+                name,
+                suggestion,
+                if available.is_empty() {
+                    "none".to_string()
+                } else {
+                    available.join(", ")
+                }
+            )
+            .into());
+        };
+
+        let mut overlay = serde_json::to_value(&profile.0)?;
+        // Settings given as env vars keep their env value
+        for var in config_env_vars() {
+            if let Some(key) = var.setting.filter(|_| env_is_set(&var.name)) {
+                remove_key(&mut overlay, &key);
             }
         }
+        let mut overlay: PartialTramConfig = serde_json::from_value(overlay)?;
+        overlay.profiles = None;
 
-        // Debug: removed for cleaner error messages
+        // Merged as another layer would be, then defaults fill in new entries
+        let mut merged: PartialTramConfig = serde_json::from_value(serde_json::to_value(&*self)?)?;
+        merged.merge(&(), overlay)?;
+        *self = Self::from_partial(merged.finalize(&())?).normalized();
 
-        // Load with whatever we found (or just env vars if no file found)
-        let result = loader.load()?;
-        Ok(result.config)
+        Ok(())
+    }
+
+    /// Overlay settings given as command-line flags. They win over
+    /// everything else, env vars included, so they're set directly rather
+    /// than merged as a layer, which would apply env vars again.
+    pub fn apply_flags(
+        &mut self,
+        flags: PartialTramConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut config = serde_json::to_value(&*self)?;
+        set_values(&mut config, serde_json::to_value(flags)?);
+        *self = serde_json::from_value::<Self>(config)?.normalized();
+
+        Ok(())
+    }
+
+    /// Where each top-level setting came from, below command-line flags.
+    /// `files` are the config files that were merged, lowest precedence
+    /// first.
+    ///
+    /// Env vars win over the active profile, which wins over the rest of the
+    /// config files, which win over the defaults.
+    pub fn setting_sources(
+        &self,
+        files: &[PathBuf],
+        profile: Option<&str>,
+    ) -> Result<BTreeMap<String, SettingSource>, Box<dyn std::error::Error>> {
+        let file_keys = files
+            .iter()
+            .map(|path| Ok((path, Self::keys_in_file(path)?)))
+            .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+        let profile_keys = profile
+            .and_then(|name| self.profiles.get(name))
+            .map(ProfileConfig::keys)
+            .unwrap_or_default();
+
+        let sources = setting_env_vars()
+            .into_iter()
+            .map(|(key, var)| {
+                let source = if env_is_set(&var) {
+                    SettingSource::Env(var)
+                } else if let Some(name) = profile.filter(|_| profile_keys.contains(&key)) {
+                    SettingSource::Profile(name.to_string())
+                } else if let Some((path, _)) =
+                    file_keys.iter().rev().find(|(_, keys)| keys.contains(&key))
+                {
+                    SettingSource::File(path.to_path_buf())
+                } else {
+                    SettingSource::Default
+                };
+
+                (key, source)
+            })
+            .collect();
+
+        Ok(sources)
+    }
+
+    /// Expand user-supplied paths so `~/projects` works in config files and env vars.
+    fn normalized(mut self) -> Self {
+        self.workspace_root = self.workspace_root.map(tram_core::expand_path);
+        self.log_file = self.log_file.map(tram_core::expand_path);
+        self
+    }
+}
+
+/// A readable message for a config load error: the file that failed and the
+/// underlying cause, with the closest valid key suggested for a mistyped one.
+pub fn describe_error(error: &(dyn std::error::Error + 'static)) -> String {
+    let mut cause = None;
+    let mut source = error.source();
+    while let Some(inner) = source {
+        cause = Some(inner.to_string());
+        source = inner.source();
+    }
+
+    let message = error.to_string();
+    match cause {
+        Some(cause) => format!(
+            "{}: {}",
+            message.trim_end_matches('.'),
+            suggest_key(&cause).unwrap_or(cause)
+        ),
+        None => message,
     }
 }
 
+/// Rewrite serde's "unknown field `x`, expected one of `a`, `b`" as a
+/// suggestion, if one of the expected keys is close.
+fn suggest_key(cause: &str) -> Option<String> {
+    let (path, rest) = cause.split_once(": unknown field `")?;
+    let (field, rest) = rest.split_once('`')?;
+    let (_, expected) = rest.split_once("expected")?;
+
+    let candidates: Vec<&str> = expected.split('`').skip(1).step_by(2).collect();
+    let suggestion = did_you_mean(field, candidates)?;
+
+    Some(format!(
+        "unknown key `{}`, did you mean `{}`?",
+        path, suggestion
+    ))
+}
+
+/// Remove the setting at a dotted `key` such as `http.proxy` from `value`.
+fn remove_key(value: &mut serde_json::Value, key: &str) {
+    match key.split_once('.') {
+        Some((section, rest)) => {
+            if let Some(nested) = value.get_mut(section) {
+                remove_key(nested, rest);
+            }
+        }
+        None => {
+            if let serde_json::Value::Object(settings) = value {
+                settings.remove(key);
+            }
+        }
+    }
+}
+
+/// Set every value in `values` that isn't null in `config`, section by
+/// section.
+fn set_values(config: &mut serde_json::Value, values: serde_json::Value) {
+    let serde_json::Value::Object(values) = values else {
+        return;
+    };
+
+    for (key, value) in values {
+        match (config.get_mut(&key), value) {
+            (_, serde_json::Value::Null) => {}
+            (
+                Some(section @ serde_json::Value::Object(_)),
+                value @ serde_json::Value::Object(_),
+            ) => set_values(section, value),
+            (_, value) => {
+                if let serde_json::Value::Object(settings) = config {
+                    settings.insert(key, value);
+                }
+            }
+        }
+    }
+}
+
+/// Whether an env var is set to something, as schematic only reads non-empty ones.
+fn env_is_set(var: &str) -> bool {
+    std::env::var_os(var).is_some_and(|value| !value.is_empty())
+}
+
 /// Trait for handling configuration changes during hot reload.
 #[async_trait]
 pub trait ConfigChangeHandler: Send + Sync {
@@ -192,94 +900,77 @@ pub trait ConfigChangeHandler: Send + Sync {
     async fn handle_config_error(&self, error: Box<dyn std::error::Error + Send + Sync>);
 }
 
+/// How long config files must be quiet before changes are reloaded, so a
+/// burst of events such as an editor's write-then-rename save reloads once.
+pub const CONFIG_RELOAD_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// What's applied over the config files each time they're loaded: the
+/// profile selected with `--profile`, then settings given as flags.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigOverlay {
+    pub profile: Option<String>,
+    pub flags: PartialTramConfig,
+}
+
+impl ConfigOverlay {
+    /// Apply the profile, then the flags, to a freshly loaded config.
+    pub fn apply(&self, config: &mut TramConfig) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(profile) = &self.profile {
+            config.apply_profile(profile)?;
+        }
+
+        config.apply_flags(self.flags.clone())
+    }
+}
+
 /// Configuration watcher that provides hot reload functionality.
+///
+/// Each config file's directory is watched as well as the file itself, so
+/// reloads survive editors (vim, VS Code) that save by writing a new file
+/// and renaming it over the old one: the directory sees the rename, and the
+/// new file is watched in place of the one that was replaced. Every reload
+/// reapplies the [`ConfigOverlay`] the initial config was loaded with.
 pub struct ConfigWatcher {
     config: Arc<RwLock<TramConfig>>,
     config_paths: Vec<PathBuf>,
-    _watcher: RecommendedWatcher,
-    shutdown_tx: Option<mpsc::Sender<()>>,
+    overlay: Arc<ConfigOverlay>,
+    shutdown_tx: watch::Sender<bool>,
 }
 
 impl ConfigWatcher {
-    /// Create a new config watcher for the specified paths.
-    /// If no paths are provided, watches common config file locations.
+    /// Create a new config watcher for the specified paths, lowest
+    /// precedence first. If no paths are provided, watches the config file
+    /// of each layer. A change to any of them reloads them all, merged, with
+    /// `overlay` applied over them.
     pub async fn new(
         initial_config: TramConfig,
         config_paths: Option<Vec<PathBuf>>,
+        overlay: ConfigOverlay,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         let paths = config_paths.unwrap_or_else(|| {
-            vec![
-                "tram.json".into(),
-                "tram.yaml".into(),
-                "tram.yml".into(),
-                "tram.toml".into(),
-                ".tram.json".into(),
-                ".tram.yaml".into(),
-                ".tram.yml".into(),
-                ".tram.toml".into(),
-            ]
+            TramConfig::config_files()
+                .into_iter()
+                .map(|file| file.path)
+                .collect()
         });
 
-        let config = Arc::new(RwLock::new(initial_config));
-        let (shutdown_tx, mut shutdown_rx) = mpsc::channel::<()>(1);
-        let (event_tx, mut event_rx) = mpsc::channel::<Result<Event, notify::Error>>(1000);
-
-        // Create the file watcher
-        let mut watcher = notify::recommended_watcher(move |res| {
-            let _ = event_tx.blocking_send(res);
-        })?;
-
-        // Watch existing config files
-        let existing_paths: Vec<_> = paths.iter().filter(|p| p.exists()).collect();
-
-        for path in &existing_paths {
-            debug!("Watching config file: {}", path.display());
-            watcher.watch(path, RecursiveMode::NonRecursive)?;
-        }
-
-        if existing_paths.is_empty() {
+        let watched = paths.iter().filter(|path| path.exists()).count();
+        if watched == 0 {
             warn!("No existing config files found to watch");
         } else {
-            info!(
-                "Watching {} config file(s) for changes",
-                existing_paths.len()
-            );
-        }
-
-        // Clone config for the watch task
-        let config_clone = Arc::clone(&config);
-        let paths_clone = paths.clone();
-
-        // Spawn the watch task
-        tokio::spawn(async move {
-            loop {
-                tokio::select! {
-                    Some(event_result) = event_rx.recv() => {
-                        match event_result {
-                            Ok(event) => {
-                                if let Err(e) = Self::handle_file_event(&config_clone, &paths_clone, event).await {
-                                    error!("Error handling config file event: {}", e);
-                                }
-                            }
-                            Err(e) => {
-                                error!("File watcher error: {}", e);
-                            }
-                        }
-                    }
-                    _ = shutdown_rx.recv() => {
-                        debug!("Config watcher shutting down");
-                        break;
-                    }
-                }
-            }
-        });
+            info!("Watching {} config file(s) for changes", watched);
+        }
 
-        Ok(Self {
-            config,
+        let (shutdown_tx, _) = watch::channel(false);
+        let watcher = Self {
+            config: Arc::new(RwLock::new(initial_config)),
             config_paths: paths,
-            _watcher: watcher,
-            shutdown_tx: Some(shutdown_tx),
-        })
+            overlay: Arc::new(overlay),
+            shutdown_tx,
+        };
+        watcher.spawn(None)?;
+
+        Ok(watcher)
     }
 
     /// Get the current configuration (thread-safe).
@@ -295,151 +986,233 @@ impl ConfigWatcher {
     where
         H: ConfigChangeHandler + 'static,
     {
-        let handler = Arc::new(handler);
-        let config_clone = Arc::clone(&self.config);
-        let paths_clone = self.config_paths.clone();
-        let (event_tx, mut event_rx) = mpsc::channel::<Result<Event, notify::Error>>(1000);
-
-        // Create a new watcher for this handler
-        let mut watcher = notify::recommended_watcher(move |res| {
-            let _ = event_tx.blocking_send(res);
-        })?;
-
-        // Watch existing config files
-        for path in &paths_clone {
-            if path.exists() {
-                watcher.watch(path, RecursiveMode::NonRecursive)?;
-            }
-        }
-
-        // Process events with the handler
-        tokio::spawn(async move {
-            while let Some(event_result) = event_rx.recv().await {
-                match event_result {
-                    Ok(event) => {
-                        if let Err(e) = Self::handle_file_event_with_handler(
-                            &config_clone,
-                            &paths_clone,
-                            event,
-                            &handler,
-                        )
-                        .await
-                        {
-                            error!("Error handling config file event with handler: {}", e);
-                        }
-                    }
-                    Err(e) => {
-                        error!("File watcher error: {}", e);
-                    }
-                }
-            }
-        });
-
-        Ok(())
+        self.spawn(Some(Arc::new(handler)))
     }
 
-    /// Handle a file system event for config files.
-    async fn handle_file_event(
-        config: &Arc<RwLock<TramConfig>>,
-        config_paths: &[PathBuf],
-        event: Event,
+    /// Watch the config files until stopped, reloading on changes and
+    /// telling `handler` about them.
+    fn spawn(
+        &self,
+        handler: Option<Arc<dyn ConfigChangeHandler>>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        if !matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
-            return Ok(());
-        }
+        let (event_tx, event_rx) = mpsc::channel::<Result<Event, notify::Error>>(1000);
+        let files = ConfigFiles::watch(&self.config_paths, event_tx)?;
+
+        tokio::spawn(Self::watch_loop(
+            files,
+            event_rx,
+            self.shutdown_tx.subscribe(),
+            Arc::clone(&self.config),
+            Arc::clone(&self.overlay),
+            handler,
+        ));
 
-        for path in &event.paths {
-            if config_paths.iter().any(|p| p == path) {
-                debug!("Config file changed: {}", path.display());
+        Ok(())
+    }
 
-                match Self::reload_config_from_path(path).await {
-                    Ok(new_config) => {
-                        {
-                            let mut config_guard = config.write().await;
-                            *config_guard = new_config;
+    /// Collect changed config files until they've been quiet for
+    /// [`CONFIG_RELOAD_DEBOUNCE`], then reload once.
+    async fn watch_loop(
+        mut files: ConfigFiles,
+        mut event_rx: mpsc::Receiver<Result<Event, notify::Error>>,
+        mut shutdown_rx: watch::Receiver<bool>,
+        config: Arc<RwLock<TramConfig>>,
+        overlay: Arc<ConfigOverlay>,
+        handler: Option<Arc<dyn ConfigChangeHandler>>,
+    ) {
+        let mut changed: Vec<PathBuf> = Vec::new();
+
+        loop {
+            let quiet = tokio::time::sleep(CONFIG_RELOAD_DEBOUNCE);
+            tokio::select! {
+                event = event_rx.recv() => match event {
+                    Some(Ok(event)) => {
+                        for path in files.changed_paths(&event) {
+                            if !changed.contains(&path) {
+                                changed.push(path);
+                            }
                         }
-                        info!("Configuration reloaded from {}", path.display());
-                    }
-                    Err(e) => {
-                        warn!("Failed to reload config from {}: {}", path.display(), e);
                     }
+                    Some(Err(e)) => error!("File watcher error: {}", e),
+                    None => break,
+                },
+                _ = quiet, if !changed.is_empty() => {
+                    let changed = std::mem::take(&mut changed);
+                    Self::reload_changed(
+                        &config,
+                        &files.paths,
+                        &overlay,
+                        &changed,
+                        handler.as_deref(),
+                    )
+                    .await;
+                }
+                _ = shutdown_rx.changed() => {
+                    debug!("Config watcher shutting down");
+                    break;
                 }
             }
         }
-
-        Ok(())
     }
 
-    /// Handle a file system event with a custom handler.
-    async fn handle_file_event_with_handler<H>(
+    /// Reload after `changed` config files changed.
+    async fn reload_changed(
         config: &Arc<RwLock<TramConfig>>,
         config_paths: &[PathBuf],
-        event: Event,
-        handler: &Arc<H>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
-    where
-        H: ConfigChangeHandler,
-    {
-        if !matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
-            return Ok(());
-        }
-
-        for path in &event.paths {
-            if config_paths.iter().any(|p| p == path) {
-                debug!("Config file changed: {}", path.display());
-
-                match Self::reload_config_from_path(path).await {
-                    Ok(new_config) => {
-                        {
-                            let mut config_guard = config.write().await;
-                            *config_guard = new_config.clone();
-                        }
-                        info!("Configuration reloaded from {}", path.display());
-                        handler.handle_config_change(&new_config).await;
-                    }
-                    Err(e) => {
-                        warn!("Failed to reload config from {}: {}", path.display(), e);
-                        handler.handle_config_error(e).await;
-                    }
+        overlay: &Arc<ConfigOverlay>,
+        changed: &[PathBuf],
+        handler: Option<&dyn ConfigChangeHandler>,
+    ) {
+        let names: Vec<String> = changed
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        debug!("Config file changed: {}", names.join(", "));
+
+        match Self::reload_config_from_paths(config_paths, overlay).await {
+            Ok(new_config) => {
+                *config.write().await = new_config.clone();
+                info!("Configuration reloaded from {}", names.join(", "));
+                if let Some(handler) = handler {
+                    handler.handle_config_change(&new_config).await;
+                }
+            }
+            Err(e) => {
+                warn!("Failed to reload config from {}: {}", names.join(", "), e);
+                if let Some(handler) = handler {
+                    handler.handle_config_error(e).await;
                 }
             }
         }
-
-        Ok(())
     }
 
-    /// Reload configuration from a specific path.
-    async fn reload_config_from_path(
-        path: &Path,
+    /// Reload configuration from the watched files that exist, with the
+    /// overlay applied.
+    async fn reload_config_from_paths(
+        paths: &[PathBuf],
+        overlay: &Arc<ConfigOverlay>,
     ) -> Result<TramConfig, Box<dyn std::error::Error + Send + Sync>> {
-        let path = path.to_owned();
+        let paths: Vec<PathBuf> = paths.iter().filter(|path| path.exists()).cloned().collect();
+        let overlay = Arc::clone(overlay);
         tokio::task::spawn_blocking(move || {
-            TramConfig::load_from_file(path).map_err(
-                |e| -> Box<dyn std::error::Error + Send + Sync> {
+            TramConfig::load_from_files(&paths)
+                .and_then(|mut config| overlay.apply(&mut config).map(|()| config))
+                .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> {
                     Box::new(std::io::Error::new(
                         std::io::ErrorKind::InvalidData,
                         format!("Failed to load config: {}", e),
                     ))
-                },
-            )
+                })
         })
         .await
         .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { Box::new(e) })?
     }
 
+    /// Reload the configuration now, e.g. on SIGHUP, from the watched files
+    /// that exist.
+    pub async fn reload(&self) -> Result<TramConfig, Box<dyn std::error::Error + Send + Sync>> {
+        if !self.config_paths.iter().any(|path| path.exists()) {
+            return Err("No config file to reload".into());
+        }
+
+        let new_config = Self::reload_config_from_paths(&self.config_paths, &self.overlay).await?;
+        *self.config.write().await = new_config.clone();
+        info!("Configuration reloaded");
+
+        Ok(new_config)
+    }
+
     /// Stop watching for configuration changes.
     pub async fn stop(&mut self) {
-        if let Some(shutdown_tx) = self.shutdown_tx.take() {
-            let _ = shutdown_tx.send(()).await;
-        }
+        let _ = self.shutdown_tx.send(true);
     }
 }
 
 impl Drop for ConfigWatcher {
     fn drop(&mut self) {
-        if let Some(shutdown_tx) = self.shutdown_tx.take() {
-            let _ = shutdown_tx.try_send(());
+        let _ = self.shutdown_tx.send(true);
+    }
+}
+
+/// The notify watches behind a [`ConfigWatcher`].
+struct ConfigFiles {
+    watcher: RecommendedWatcher,
+    paths: Vec<PathBuf>,
+}
+
+impl ConfigFiles {
+    /// Watch each config file and, as a fallback that sees files renamed
+    /// into place or created later, the directory it's in.
+    fn watch(
+        paths: &[PathBuf],
+        event_tx: mpsc::Sender<Result<Event, notify::Error>>,
+    ) -> Result<Self, notify::Error> {
+        let watcher = notify::recommended_watcher(move |res| {
+            let _ = event_tx.blocking_send(res);
+        })?;
+        let mut files = Self {
+            watcher,
+            paths: paths.to_vec(),
+        };
+
+        let mut dirs: Vec<&Path> = paths
+            .iter()
+            .filter_map(|path| path.parent())
+            .filter(|dir| dir.is_dir())
+            .collect();
+        dirs.sort();
+        dirs.dedup();
+        for dir in dirs {
+            debug!("Watching config directory: {}", dir.display());
+            files.watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+
+        for path in paths {
+            files.watch_file(path);
+        }
+
+        Ok(files)
+    }
+
+    /// Watch a config file itself, as some platforms only report content
+    /// changes to a watch on the file. A watch on a file that was replaced
+    /// follows the old file, so it's dropped first.
+    fn watch_file(&mut self, path: &Path) {
+        let _ = self.watcher.unwatch(path);
+        if !path.is_file() {
+            return;
+        }
+
+        debug!("Watching config file: {}", path.display());
+        if let Err(e) = self.watcher.watch(path, RecursiveMode::NonRecursive) {
+            debug!("Failed to watch {}: {}", path.display(), e);
+        }
+    }
+
+    /// The config files an event touched, watching again any that were
+    /// created, removed, or renamed.
+    fn changed_paths(&mut self, event: &Event) -> Vec<PathBuf> {
+        let replaced = match event.kind {
+            EventKind::Create(_) | EventKind::Remove(_) => true,
+            EventKind::Modify(ModifyKind::Name(_)) => true,
+            EventKind::Modify(ModifyKind::Metadata(_)) => return Vec::new(),
+            EventKind::Modify(_) => false,
+            _ => return Vec::new(),
+        };
+
+        let changed: Vec<PathBuf> = event
+            .paths
+            .iter()
+            .filter(|path| self.paths.contains(path))
+            .cloned()
+            .collect();
+        if replaced {
+            for path in &changed {
+                self.watch_file(path);
+            }
         }
+
+        changed
     }
 }
 
@@ -469,6 +1242,125 @@ mod tests {
         assert!(config.workspace_root.is_none());
     }
 
+    #[test]
+    #[serial]
+    fn test_setting_sources() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("tram.toml");
+        fs::write(&config_path, "logLevel = \"debug\"\ncolor = false\n").unwrap();
+        let user_path = temp_dir.path().join("config.toml");
+        fs::write(&user_path, "logLevel = \"warn\"\noffline = true\n").unwrap();
+
+        unsafe {
+            env::remove_var("TRAM_LOG_LEVEL");
+            env::remove_var("TRAM_OUTPUT_FORMAT");
+            env::remove_var("TRAM_OFFLINE");
+            env::set_var("TRAM_COLOR", "true");
+        }
+
+        let config = TramConfig::load_from_file(&config_path).unwrap();
+        let sources = config
+            .setting_sources(&[user_path.clone(), config_path.clone()], None)
+            .unwrap();
+
+        unsafe {
+            env::remove_var("TRAM_COLOR");
+        }
+
+        assert_eq!(sources["logLevel"], SettingSource::File(config_path));
+        assert_eq!(sources["offline"], SettingSource::File(user_path));
+        assert_eq!(
+            sources["color"],
+            SettingSource::Env("TRAM_COLOR".to_string())
+        );
+        assert_eq!(sources["outputFormat"], SettingSource::Default);
+    }
+
+    #[test]
+    fn test_unknown_key_suggestion() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("tram.toml");
+
+        fs::write(&config_path, "logLvel = \"debug\"\n").unwrap();
+        let error = TramConfig::load_from_file(&config_path).unwrap_err();
+        let message = describe_error(error.as_ref());
+        assert!(
+            message.ends_with("unknown key `logLvel`, did you mean `logLevel`?"),
+            "{}",
+            message
+        );
+
+        fs::write(&config_path, "[watch]\ndebounceMS = 100\n").unwrap();
+        let error = TramConfig::load_from_file(&config_path).unwrap_err();
+        assert!(describe_error(error.as_ref()).contains("did you mean `debounceMs`?"));
+
+        // Unrelated keys keep serde's list of valid ones
+        fs::write(&config_path, "zzz = 1\n").unwrap();
+        let error = TramConfig::load_from_file(&config_path).unwrap_err();
+        assert!(describe_error(error.as_ref()).contains("expected one of `logLevel`"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_apply_profile() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("tram.toml");
+        fs::write(
+            &config_path,
+            "logLevel = \"debug\"\n\n[http]\ntimeoutSecs = 60\nretries = 5\n\n[tasks.test]\ncommand = \"cargo test\"\n\n[profiles.ci]\noutputFormat = \"plain\"\ncolor = false\n\n[profiles.ci.http]\ntimeoutSecs = 120\n\n[profiles.ci.tasks.lint]\ncommand = \"cargo clippy\"\n",
+        )
+        .unwrap();
+
+        unsafe {
+            env::remove_var("TRAM_LOG_LEVEL");
+            env::remove_var("TRAM_OUTPUT_FORMAT");
+            env::set_var("TRAM_COLOR", "true");
+        }
+
+        let mut config = TramConfig::load_from_file(&config_path).unwrap();
+        config.apply_profile("ci").unwrap();
+        let sources = config
+            .setting_sources(std::slice::from_ref(&config_path), Some("ci"))
+            .unwrap();
+        let unknown = config.apply_profile("nope").unwrap_err();
+        let typo = config.apply_profile("cii").unwrap_err();
+
+        unsafe {
+            env::remove_var("TRAM_COLOR");
+        }
+
+        assert_eq!(config.output_format, OutputFormat::Plain);
+        assert_eq!(config.log_level, LogLevel::Debug);
+        // The env var still wins over the profile
+        assert!(config.color);
+        // Nested sections merge setting by setting
+        assert_eq!(config.http.timeout_secs, 120);
+        assert_eq!(config.http.retries, 5);
+        assert_eq!(config.tasks.keys().collect::<Vec<_>>(), ["lint", "test"]);
+        assert!(config.tasks["lint"].depends_on.is_empty());
+        assert_eq!(
+            sources["outputFormat"],
+            SettingSource::Profile("ci".to_string())
+        );
+        assert_eq!(
+            sources["color"],
+            SettingSource::Env("TRAM_COLOR".to_string())
+        );
+        assert!(unknown.to_string().contains("available: ci"));
+        assert!(typo.to_string().contains("did you mean 'ci'?"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_profile_with_unknown_key_fails_to_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("tram.toml");
+        fs::write(&config_path, "[profiles.ci.http]\ntimeout = 5\n").unwrap();
+
+        let error = TramConfig::load_from_file(&config_path).unwrap_err();
+        assert!(describe_error(error.as_ref()).contains("timeout"));
+    }
+
     #[test]
     #[serial]
     fn test_config_load_from_json_file() {
@@ -545,6 +1437,170 @@ color = true
         assert!(config.color);
     }
 
+    #[test]
+    fn test_watch_tasks_from_toml_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_file = temp_dir.path().join("tram.toml");
+
+        let config_content = r#"
+[watch.tasks.test]
+glob = "src/**/*.rs"
+run = "cargo test"
+"#;
+        fs::write(&config_file, config_content).unwrap();
+
+        let config = TramConfig::load_from_file(&config_file).unwrap();
+        let task = &config.watch.tasks["test"];
+        assert_eq!(config.watch.debounce_ms, 200);
+        assert_eq!(config.watch.check_command, None);
+        assert_eq!(task.glob, "src/**/*.rs");
+        assert_eq!(task.run, "cargo test");
+    }
+
+    #[test]
+    fn test_theme_from_toml_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_file = temp_dir.path().join("tram.toml");
+        fs::write(&config_file, "[theme]\nsuccess = \"bold blue\"\n").unwrap();
+
+        let config = TramConfig::load_from_file(&config_file).unwrap();
+        assert_eq!(config.theme.success, "bold blue");
+        assert_eq!(config.theme.error, "red");
+        assert_eq!(config.theme.heading, "bold");
+    }
+
+    #[test]
+    fn test_http_from_toml_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_file = temp_dir.path().join("tram.toml");
+        fs::write(
+            &config_file,
+            "[http]\nproxy = \"http://proxy.internal:3128\"\nretries = 0\n",
+        )
+        .unwrap();
+
+        let config = TramConfig::load_from_file(&config_file).unwrap();
+        assert_eq!(
+            config.http.proxy.as_deref(),
+            Some("http://proxy.internal:3128")
+        );
+        assert_eq!(config.http.retries, 0);
+        assert_eq!(config.http.timeout_secs, 30);
+        assert_eq!(config.http.ca_file, None);
+    }
+
+    #[test]
+    fn test_cache_from_toml_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_file = temp_dir.path().join("tram.toml");
+        fs::write(&config_file, "[cache]\nmaxSize = \"1GB\"\n").unwrap();
+
+        let config = TramConfig::load_from_file(&config_file).unwrap();
+        assert_eq!(config.cache.max_size.as_deref(), Some("1GB"));
+        assert_eq!(config.cache.gc_interval_hours, 24);
+    }
+
+    #[test]
+    fn test_log_rotation_from_toml_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_file = temp_dir.path().join("tram.toml");
+        fs::write(&config_file, "logRotation = \"10MB\"\n").unwrap();
+
+        let config = TramConfig::load_from_file(&config_file).unwrap();
+        assert_eq!(config.log_rotation, LogRotation::Size(10_000_000));
+        assert_eq!(TramConfig::default().log_rotation, LogRotation::Never);
+
+        fs::write(&config_file, "logRotation = \"weekly\"\n").unwrap();
+        let error = TramConfig::load_from_file(&config_file).unwrap_err();
+        assert!(
+            format!("{:?}", error).contains("Invalid log rotation 'weekly'"),
+            "{:?}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_update_from_toml_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_file = temp_dir.path().join("tram.toml");
+        fs::write(
+            &config_file,
+            "[update]\nrepository = \"acme/tram-fork\"\napiUrl = \"https://github.acme.dev/api/v3\"\n",
+        )
+        .unwrap();
+
+        let config = TramConfig::load_from_file(&config_file).unwrap();
+        assert_eq!(config.update.repository.as_deref(), Some("acme/tram-fork"));
+        assert_eq!(
+            config.update.api_url.as_deref(),
+            Some("https://github.acme.dev/api/v3")
+        );
+    }
+
+    #[test]
+    fn test_telemetry_from_toml_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_file = temp_dir.path().join("tram.toml");
+        fs::write(
+            &config_file,
+            "[telemetry]\nexporter = \"otlp\"\n\n[telemetry.headers]\nx-api-key = \"secret://otlp\"\n",
+        )
+        .unwrap();
+
+        let config = TramConfig::load_from_file(&config_file).unwrap();
+        assert_eq!(config.telemetry.exporter, MetricsExporter::Otlp);
+        assert_eq!(config.telemetry.endpoint, None);
+        assert_eq!(config.telemetry.headers["x-api-key"], "secret://otlp");
+        assert_eq!(config.telemetry.service_name, "tram");
+        assert_eq!(
+            TramConfig::default().telemetry.exporter,
+            MetricsExporter::None
+        );
+    }
+
+    #[test]
+    fn test_schedules_from_toml_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_file = temp_dir.path().join("tram.toml");
+        fs::write(
+            &config_file,
+            "[schedules.prune]\ncron = \"@hourly\"\nrun = \"tram cache prune\"\njitterSecs = 60\n\n[schedules.update]\ncron = \"0 3 * * *\"\nrun = \"tram update\"\n",
+        )
+        .unwrap();
+
+        let config = TramConfig::load_from_file(&config_file).unwrap();
+        let prune = &config.schedules["prune"];
+        assert_eq!(prune.cron, "@hourly");
+        assert_eq!(prune.run, "tram cache prune");
+        assert_eq!(prune.jitter_secs, 60);
+        assert_eq!(config.schedules["update"].jitter_secs, 0);
+    }
+
+    #[test]
+    fn test_tasks_from_toml_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_file = temp_dir.path().join("tram.toml");
+
+        let config_content = r#"
+[tasks.build]
+command = "cargo build"
+cwd = "app"
+env = { RUSTFLAGS = "-Dwarnings" }
+
+[tasks.test]
+command = "cargo test"
+dependsOn = ["build"]
+"#;
+        fs::write(&config_file, config_content).unwrap();
+
+        let config = TramConfig::load_from_file(&config_file).unwrap();
+        let build = &config.tasks["build"];
+        assert_eq!(build.cwd, Some(PathBuf::from("app")));
+        assert_eq!(build.env["RUSTFLAGS"], "-Dwarnings");
+        assert!(build.depends_on.is_empty());
+        assert_eq!(config.tasks["test"].depends_on, ["build"]);
+    }
+
     #[test]
     fn test_unsupported_file_format() {
         let temp_dir = TempDir::new().unwrap();
@@ -593,6 +1649,8 @@ color = true
         assert_eq!(OutputFormat::Json.to_string(), "json");
         assert_eq!(OutputFormat::Yaml.to_string(), "yaml");
         assert_eq!(OutputFormat::Table.to_string(), "table");
+        assert_eq!(OutputFormat::Csv.to_string(), "csv");
+        assert_eq!("TSV".parse::<OutputFormat>(), Ok(OutputFormat::Tsv));
     }
 
     #[test]
@@ -682,4 +1740,248 @@ color = true
             env::remove_var("TRAM_LOG_LEVEL");
         }
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_config_watcher_reload() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("tram.toml");
+        fs::write(&config_path, "logLevel = \"warn\"\n").unwrap();
+
+        unsafe {
+            env::remove_var("TRAM_LOG_LEVEL");
+        }
+
+        let watcher = ConfigWatcher::new(
+            TramConfig::default(),
+            Some(vec![config_path.clone()]),
+            ConfigOverlay::default(),
+        )
+        .await
+        .unwrap();
+        fs::write(&config_path, "logLevel = \"error\"\n").unwrap();
+
+        let reloaded = watcher.reload().await.unwrap();
+        assert_eq!(reloaded.log_level, LogLevel::Error);
+        assert_eq!(watcher.get_config().await.log_level, LogLevel::Error);
+
+        let missing = ConfigWatcher::new(
+            TramConfig::default(),
+            Some(vec![temp_dir.path().join("missing.toml")]),
+            ConfigOverlay::default(),
+        )
+        .await
+        .unwrap();
+        assert!(missing.reload().await.is_err());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_config_watcher_reload_keeps_profile_and_flags() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("tram.toml");
+        let write = |level: &str| {
+            fs::write(
+                &config_path,
+                format!(
+                    "logLevel = \"{}\"\n\n[profiles.ci]\ncolor = false\n\n[profiles.ci.http]\ntimeoutSecs = 120\n",
+                    level
+                ),
+            )
+            .unwrap();
+        };
+        write("warn");
+
+        unsafe {
+            env::remove_var("TRAM_LOG_LEVEL");
+            env::remove_var("TRAM_COLOR");
+            // The flag still wins over the env var
+            env::set_var("TRAM_OUTPUT_FORMAT", "yaml");
+        }
+
+        let overlay = ConfigOverlay {
+            profile: Some("ci".to_string()),
+            flags: PartialTramConfig {
+                output_format: Some(OutputFormat::Json),
+                ..Default::default()
+            },
+        };
+        let watcher = ConfigWatcher::new(
+            TramConfig::default(),
+            Some(vec![config_path.clone()]),
+            overlay,
+        )
+        .await
+        .unwrap();
+        write("error");
+
+        let reloaded = watcher.reload().await.unwrap();
+        assert_eq!(reloaded.log_level, LogLevel::Error);
+        assert!(!reloaded.color);
+        assert_eq!(reloaded.http.timeout_secs, 120);
+        assert_eq!(reloaded.output_format, OutputFormat::Json);
+
+        unsafe {
+            env::remove_var("TRAM_OUTPUT_FORMAT");
+        }
+    }
+
+    /// Wait up to five seconds for the watcher to load `level`.
+    async fn wait_for_level(watcher: &ConfigWatcher, level: LogLevel) -> bool {
+        for _ in 0..50 {
+            if watcher.get_config().await.log_level == level {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        false
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_config_watcher_survives_atomic_saves() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("tram.toml");
+        fs::write(&config_path, "logLevel = \"warn\"\n").unwrap();
+
+        unsafe {
+            env::remove_var("TRAM_LOG_LEVEL");
+        }
+
+        let watcher = ConfigWatcher::new(
+            TramConfig::default(),
+            Some(vec![config_path.clone()]),
+            ConfigOverlay::default(),
+        )
+        .await
+        .unwrap();
+
+        // Saved the way vim and VS Code do: a new file renamed over the old
+        let save = |level: &str| {
+            let temp = temp_dir.path().join("tram.toml.swp");
+            fs::write(&temp, format!("logLevel = \"{}\"\n", level)).unwrap();
+            fs::rename(&temp, &config_path).unwrap();
+        };
+
+        save("error");
+        assert!(wait_for_level(&watcher, LogLevel::Error).await);
+        save("debug");
+        assert!(wait_for_level(&watcher, LogLevel::Debug).await);
+
+        // Removed and written again, and created after the watcher started
+        fs::remove_file(&config_path).unwrap();
+        fs::write(&config_path, "logLevel = \"trace\"\n").unwrap();
+        assert!(wait_for_level(&watcher, LogLevel::Trace).await);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_config_watcher_coalesces_bursts() {
+        struct CountingHandler(Arc<std::sync::atomic::AtomicUsize>);
+
+        #[async_trait]
+        impl ConfigChangeHandler for CountingHandler {
+            async fn handle_config_change(&self, _new_config: &TramConfig) {
+                self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            }
+
+            async fn handle_config_error(&self, _error: Box<dyn std::error::Error + Send + Sync>) {}
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("tram.toml");
+        fs::write(&config_path, "logLevel = \"warn\"\n").unwrap();
+
+        unsafe {
+            env::remove_var("TRAM_LOG_LEVEL");
+        }
+
+        let watcher = ConfigWatcher::new(
+            TramConfig::default(),
+            Some(vec![config_path.clone()]),
+            ConfigOverlay::default(),
+        )
+        .await
+        .unwrap();
+        let reloads = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        watcher
+            .start_with_handler(CountingHandler(Arc::clone(&reloads)))
+            .await
+            .unwrap();
+
+        for _ in 0..5 {
+            fs::write(&config_path, "logLevel = \"warn\"\n").unwrap();
+        }
+        fs::write(&config_path, "logLevel = \"error\"\n").unwrap();
+
+        assert!(wait_for_level(&watcher, LogLevel::Error).await);
+        tokio::time::sleep(CONFIG_RELOAD_DEBOUNCE * 5).await;
+        assert_eq!(reloads.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_config_keys() {
+        let keys = config_keys();
+        let find = |path: &str| keys.iter().find(|key| key.path == path);
+
+        assert_eq!(
+            find("logLevel").unwrap().values,
+            ["trace", "debug", "info", "warn", "error"]
+        );
+        assert_eq!(find("color").unwrap().values, ["true", "false"]);
+        assert!(find("http.timeoutSecs").unwrap().values.is_empty());
+        assert!(find("cache.maxSize").is_some());
+        assert!(find("telemetry.headers").is_some());
+        assert!(find("tasks").is_some());
+        assert!(find("http").is_none());
+
+        assert_eq!(
+            find("telemetry.exporter").unwrap().values,
+            ["none", "otlp", "prometheus"]
+        );
+        assert!(find("logRotation").unwrap().values.is_empty());
+
+        // Every enum value offered is one the config accepts
+        let temp_dir = TempDir::new().unwrap();
+        let config_file = temp_dir.path().join("tram.toml");
+        for key in keys.iter().filter(|key| key.values != ["true", "false"]) {
+            for value in &key.values {
+                fs::write(&config_file, format!("{} = \"{}\"\n", key.path, value)).unwrap();
+                assert!(
+                    TramConfig::load_from_file(&config_file).is_ok(),
+                    "{} = {} is rejected",
+                    key.path,
+                    value
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_config_env_vars() {
+        let vars = config_env_vars();
+        let setting = |name: &str| {
+            vars.iter()
+                .find(|var| var.name == name)
+                .and_then(|var| var.setting.clone())
+        };
+
+        assert_eq!(setting("TRAM_LOG_LEVEL").as_deref(), Some("logLevel"));
+        assert_eq!(setting("TRAM_HTTP_PROXY").as_deref(), Some("http.proxy"));
+        assert_eq!(
+            setting("TRAM_METRICS_EXPORTER").as_deref(),
+            Some("telemetry.exporter")
+        );
+
+        // Top-level settings are the ones without a section
+        let top_level = setting_env_vars();
+        assert!(top_level.contains(&("logRotation".to_string(), "TRAM_LOG_ROTATION".to_string())));
+        assert!(top_level.iter().all(|(key, _)| !key.contains('.')));
+        assert_eq!(
+            top_level.len(),
+            vars.iter()
+                .filter(|var| var.setting.as_ref().is_some_and(|key| !key.contains('.')))
+                .count()
+        );
+    }
 }
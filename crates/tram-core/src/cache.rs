@@ -0,0 +1,1207 @@
+//! Content-addressable cache for CLI artifacts.
+//!
+//! Provides hash-keyed storage of arbitrary payloads (downloads, generated
+//! output, analysis results) with optional expiry, a JSON manifest describing
+//! every entry, and least-recently-used eviction to a size budget so work can
+//! be reused between runs. [`Cache::prune`] also removes files the manifest
+//! doesn't know about, left behind by interrupted writes.
+//!
+//! Several processes may share a cache: every change to the manifest is made
+//! while holding an exclusive lock on a file next to it, so one writer never
+//! drops another's entries.
+
+use crate::{AppResult, TramError};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Name of the manifest file stored at the cache root.
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Directory (relative to the cache root) holding the cached objects.
+const OBJECTS_DIR: &str = "objects";
+
+/// File locked while the manifest is read, changed, and written back.
+const LOCK_FILE: &str = "manifest.lock";
+
+/// File recording when the cache was last pruned.
+const LAST_GC_FILE: &str = "last-gc";
+
+/// Lookups in any cache by this process, for hit-rate metrics.
+static HITS: AtomicU64 = AtomicU64::new(0);
+static MISSES: AtomicU64 = AtomicU64::new(0);
+
+/// How old an unreferenced file must be before it counts as orphaned, so
+/// an object another process is still writing isn't deleted under it.
+const ORPHAN_GRACE: Duration = Duration::from_secs(60 * 60);
+
+/// How stale an entry's access time may get before a read updates it, so
+/// a hit doesn't rewrite the manifest every time. LRU eviction only needs
+/// to know roughly when an entry was last used.
+const ACCESS_RESOLUTION: Duration = Duration::from_secs(60);
+
+/// Key identifying an entry in the cache.
+///
+/// Keys are always SHA-256 digests, either of a caller-provided label
+/// (e.g. a URL) or of the content itself for content-addressed storage.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    digest: String,
+    label: String,
+}
+
+impl CacheKey {
+    /// Create a key by hashing a descriptive label such as a URL or file path.
+    pub fn new(label: impl Into<String>) -> Self {
+        let label = label.into();
+
+        Self {
+            digest: hash_bytes(label.as_bytes()),
+            label,
+        }
+    }
+
+    /// Create a key by hashing several parts (e.g. tool version and inputs).
+    pub fn from_parts<I, S>(parts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let parts: Vec<String> = parts.into_iter().map(|p| p.as_ref().to_string()).collect();
+        let mut hasher = Sha256::new();
+
+        for part in &parts {
+            hasher.update(part.as_bytes());
+            // Separate parts so ["ab", "c"] and ["a", "bc"] hash differently
+            hasher.update([0u8]);
+        }
+
+        Self {
+            digest: format!("{:x}", hasher.finalize()),
+            label: parts.join(":"),
+        }
+    }
+
+    /// Create a content-addressed key from the payload itself.
+    pub fn from_content(data: &[u8]) -> Self {
+        let digest = hash_bytes(data);
+
+        Self {
+            label: digest.clone(),
+            digest,
+        }
+    }
+
+    /// The hex-encoded SHA-256 digest used for storage.
+    pub fn digest(&self) -> &str {
+        &self.digest
+    }
+
+    /// The human-readable label the key was created from.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+}
+
+/// Metadata describing a single cached object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheEntry {
+    /// Hex-encoded digest of the key
+    pub digest: String,
+    /// Label the key was created from
+    pub label: String,
+    /// Size of the stored payload in bytes
+    pub size: u64,
+    /// Creation time as seconds since the Unix epoch
+    pub created_at: u64,
+    /// Expiry time as seconds since the Unix epoch, if any
+    pub expires_at: Option<u64>,
+    /// Last read as seconds since the Unix epoch; 0 if never read
+    #[serde(default)]
+    pub accessed_at: u64,
+}
+
+impl CacheEntry {
+    /// Whether the entry has outlived its time-to-live.
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    /// When the entry was last written or read, for LRU eviction.
+    pub fn last_used(&self) -> u64 {
+        self.created_at.max(self.accessed_at)
+    }
+}
+
+/// Manifest listing every entry stored in the cache.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheManifest {
+    /// Manifest format version
+    pub version: u32,
+    /// Entries keyed by digest
+    pub entries: BTreeMap<String, CacheEntry>,
+}
+
+impl CacheManifest {
+    /// Total size of all entries in bytes.
+    pub fn total_size(&self) -> u64 {
+        self.entries.values().map(|entry| entry.size).sum()
+    }
+}
+
+/// Entry counts and total size of a cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    /// Number of entries, including expired ones not yet pruned
+    pub entries: usize,
+    /// Number of entries past their time-to-live
+    pub expired: usize,
+    /// Total size of all entries in bytes
+    pub size: u64,
+    /// Size of the expired entries in bytes
+    pub expired_size: u64,
+}
+
+/// What [`Cache::prune`] removed, or would remove.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachePrune {
+    /// Entries past their time-to-live
+    pub expired: usize,
+    /// Least recently used entries evicted to fit the size budget
+    pub evicted: usize,
+    /// Files in the cache directory no entry refers to
+    pub orphaned: usize,
+    /// Total bytes freed
+    pub freed: u64,
+}
+
+impl CachePrune {
+    /// Entries removed, not counting orphaned files.
+    pub fn entries(&self) -> usize {
+        self.expired + self.evicted
+    }
+}
+
+/// Hits and misses of [`Cache::get`] and [`Cache::path`] in this process.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CacheLookups {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Cache lookups made by this process so far, across every [`Cache`].
+pub fn cache_lookups() -> CacheLookups {
+    CacheLookups {
+        hits: HITS.load(Ordering::Relaxed),
+        misses: MISSES.load(Ordering::Relaxed),
+    }
+}
+
+/// On-disk, hash-keyed artifact cache.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    root: PathBuf,
+    max_size: Option<u64>,
+    default_ttl: Option<Duration>,
+}
+
+impl Cache {
+    /// Open (or create) a cache rooted at the given directory.
+    pub fn new(root: impl Into<PathBuf>) -> AppResult<Self> {
+        let root = root.into();
+
+        fs::create_dir_all(root.join(OBJECTS_DIR)).map_err(|e| TramError::Cache {
+            message: format!("Failed to create cache directory {}: {}", root.display(), e),
+        })?;
+
+        Ok(Self {
+            root,
+            max_size: None,
+            default_ttl: None,
+        })
+    }
+
+    /// Open the default cache in the user's cache directory.
+    pub fn open_default() -> AppResult<Self> {
+        let cache_dir = crate::cache_dir().ok_or_else(|| TramError::Cache {
+            message: "Unable to determine the cache directory".to_string(),
+        })?;
+
+        Self::new(cache_dir)
+    }
+
+    /// Limit the total size of the cache; the least recently used entries
+    /// are evicted to fit.
+    pub fn with_max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// Apply a time-to-live to entries stored without an explicit one.
+    pub fn with_default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = Some(ttl);
+        self
+    }
+
+    /// Root directory of the cache.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Store a payload under the given key using the default time-to-live.
+    pub fn put(&self, key: &CacheKey, data: &[u8]) -> AppResult<CacheEntry> {
+        self.put_with_ttl(key, data, self.default_ttl)
+    }
+
+    /// Store a payload under the given key with an explicit time-to-live.
+    pub fn put_with_ttl(
+        &self,
+        key: &CacheKey,
+        data: &[u8],
+        ttl: Option<Duration>,
+    ) -> AppResult<CacheEntry> {
+        write_atomic(&self.prepare_object(key)?, data)?;
+
+        self.record(key, data.len() as u64, ttl)
+    }
+
+    /// Move a file into the cache under the given key, using the default
+    /// time-to-live. Write the file under [`Cache::root`] so it can be
+    /// renamed into place; from elsewhere it's copied.
+    pub fn put_file(&self, key: &CacheKey, path: &Path) -> AppResult<CacheEntry> {
+        let object_path = self.prepare_object(key)?;
+        let move_error = |e: std::io::Error| TramError::Cache {
+            message: format!("Failed to move {} into the cache: {}", path.display(), e),
+        };
+
+        let size = fs::metadata(path).map_err(move_error)?.len();
+        if fs::rename(path, &object_path).is_err() {
+            let tmp = temp_file_for(&object_path).map_err(move_error)?;
+            fs::copy(path, tmp.path()).map_err(move_error)?;
+            tmp.persist(&object_path).map_err(|e| move_error(e.error))?;
+            let _ = fs::remove_file(path);
+        }
+
+        self.record(key, size, self.default_ttl)
+    }
+
+    /// Add an entry for an object just written to the manifest, evicting
+    /// older entries if the cache is now too big.
+    fn record(&self, key: &CacheKey, size: u64, ttl: Option<Duration>) -> AppResult<CacheEntry> {
+        let now = unix_now();
+        let entry = CacheEntry {
+            digest: key.digest().to_string(),
+            label: key.label().to_string(),
+            size,
+            created_at: now,
+            expires_at: ttl.map(|ttl| now + ttl.as_secs()),
+            accessed_at: 0,
+        };
+
+        self.update_manifest(|manifest| {
+            manifest.entries.insert(entry.digest.clone(), entry.clone());
+
+            if let Some(max_size) = self.max_size {
+                self.evict_to_fit(manifest, max_size, Some(key.digest()));
+            }
+
+            Ok(())
+        })?;
+
+        Ok(entry)
+    }
+
+    /// Store a payload under its own content hash and return the key.
+    pub fn put_content(&self, data: &[u8]) -> AppResult<CacheKey> {
+        let key = CacheKey::from_content(data);
+        self.put(&key, data)?;
+        Ok(key)
+    }
+
+    /// Retrieve a payload, returning `None` if it is missing or expired.
+    pub fn get(&self, key: &CacheKey) -> AppResult<Option<Vec<u8>>> {
+        count_lookup(self.read(key))
+    }
+
+    fn read(&self, key: &CacheKey) -> AppResult<Option<Vec<u8>>> {
+        let manifest = self.manifest()?;
+        let now = unix_now();
+
+        let Some(entry) = manifest.entries.get(key.digest()) else {
+            return Ok(None);
+        };
+
+        if entry.is_expired(now) {
+            self.forget(key.digest(), |entry| entry.is_expired(now))?;
+            return Ok(None);
+        }
+
+        match fs::read(self.object_path(key.digest())) {
+            Ok(data) => {
+                self.touch(entry, now)?;
+                Ok(Some(data))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                // The object vanished behind our back; forget about it
+                self.forget(key.digest(), |entry| {
+                    !self.object_path(&entry.digest).exists()
+                })?;
+                Ok(None)
+            }
+            Err(e) => Err(TramError::Cache {
+                message: format!("Failed to read cache entry {}: {}", key.label(), e),
+            }
+            .into()),
+        }
+    }
+
+    /// Path of a non-expired entry's file, for reading it without loading
+    /// it into memory. Don't modify or remove it; use [`Cache::remove`].
+    pub fn path(&self, key: &CacheKey) -> AppResult<Option<PathBuf>> {
+        count_lookup(self.locate(key))
+    }
+
+    fn locate(&self, key: &CacheKey) -> AppResult<Option<PathBuf>> {
+        let manifest = self.manifest()?;
+        let now = unix_now();
+
+        let Some(entry) = manifest
+            .entries
+            .get(key.digest())
+            .filter(|entry| !entry.is_expired(now))
+        else {
+            return Ok(None);
+        };
+
+        let path = self.object_path(key.digest());
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        self.touch(entry, now)?;
+        Ok(Some(path))
+    }
+
+    /// Record that an entry was just read, unless its access time is
+    /// already within [`ACCESS_RESOLUTION`].
+    fn touch(&self, entry: &CacheEntry, now: u64) -> AppResult<()> {
+        if now.saturating_sub(entry.last_used()) < ACCESS_RESOLUTION.as_secs() {
+            return Ok(());
+        }
+
+        self.update_manifest(|manifest| {
+            if let Some(entry) = manifest.entries.get_mut(&entry.digest) {
+                entry.accessed_at = entry.accessed_at.max(now);
+            }
+            Ok(())
+        })
+    }
+
+    /// Remove an entry and its object if, under the lock, it still matches
+    /// `stale`; another process may have replaced it since it was read.
+    fn forget(&self, digest: &str, stale: impl Fn(&CacheEntry) -> bool) -> AppResult<()> {
+        self.update_manifest(|manifest| {
+            if manifest.entries.get(digest).is_some_and(stale) {
+                manifest.entries.remove(digest);
+                self.remove_object(digest);
+            }
+            Ok(())
+        })
+    }
+
+    /// Whether a non-expired entry exists for the key.
+    pub fn contains(&self, key: &CacheKey) -> AppResult<bool> {
+        let manifest = self.manifest()?;
+
+        Ok(manifest
+            .entries
+            .get(key.digest())
+            .is_some_and(|entry| !entry.is_expired(unix_now())))
+    }
+
+    /// Remove a single entry, returning whether it existed.
+    pub fn remove(&self, key: &CacheKey) -> AppResult<bool> {
+        self.update_manifest(|manifest| {
+            let existed = manifest.entries.remove(key.digest()).is_some();
+            if existed {
+                self.remove_object(key.digest());
+            }
+
+            Ok(existed)
+        })
+    }
+
+    /// Remove every expired entry, returning how many were evicted.
+    pub fn prune_expired(&self) -> AppResult<usize> {
+        let now = unix_now();
+
+        self.update_manifest(|manifest| {
+            let expired: Vec<String> = manifest
+                .entries
+                .values()
+                .filter(|entry| entry.is_expired(now))
+                .map(|entry| entry.digest.clone())
+                .collect();
+
+            for digest in &expired {
+                self.remove_object(digest);
+                manifest.entries.remove(digest);
+            }
+
+            Ok(expired.len())
+        })
+    }
+
+    /// Remove expired entries and orphaned files, then evict the least
+    /// recently used entries until the cache fits in `max_size`, if given.
+    /// Records the run for [`Cache::gc_due`].
+    pub fn prune(&self, max_size: Option<u64>) -> AppResult<CachePrune> {
+        // Orphans are found under the lock too, so an entry being recorded
+        // by another process can't look unreferenced
+        let summary = self.update_manifest(|manifest| {
+            let plan = self.plan_prune(manifest, max_size)?;
+
+            for digest in plan.expired.iter().chain(&plan.evicted) {
+                self.remove_object(digest);
+                manifest.entries.remove(digest);
+            }
+            for digest in &plan.missing {
+                manifest.entries.remove(digest);
+            }
+            for path in &plan.orphans {
+                let _ = fs::remove_file(path);
+            }
+
+            Ok(plan.summary)
+        })?;
+        self.mark_gc()?;
+
+        Ok(summary)
+    }
+
+    /// What [`Cache::prune`] would remove, without removing anything.
+    pub fn prune_preview(&self, max_size: Option<u64>) -> AppResult<CachePrune> {
+        Ok(self.plan_prune(&self.manifest()?, max_size)?.summary)
+    }
+
+    /// Whether it's been at least `interval` since the cache was last
+    /// pruned. A cache never pruned starts counting now, so a fresh cache
+    /// isn't collected on its first use.
+    pub fn gc_due(&self, interval: Duration) -> AppResult<bool> {
+        let path = self.root.join(LAST_GC_FILE);
+
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                let last = content.trim().parse::<u64>().unwrap_or_default();
+                Ok(unix_now().saturating_sub(last) >= interval.as_secs())
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                self.mark_gc()?;
+                Ok(false)
+            }
+            Err(e) => Err(TramError::Cache {
+                message: format!("Failed to read {}: {}", path.display(), e),
+            }
+            .into()),
+        }
+    }
+
+    /// Whether garbage collection is due, recording the run if so. The
+    /// check and the record happen under the cache lock, so when several
+    /// processes finish at once only one of them claims it.
+    pub fn claim_gc(&self, interval: Duration) -> AppResult<bool> {
+        let _lock = self.lock()?;
+        if !self.gc_due(interval)? {
+            return Ok(false);
+        }
+
+        self.mark_gc()?;
+        Ok(true)
+    }
+
+    /// Record that the cache was pruned now, e.g. before starting a
+    /// background prune so other processes don't start one too.
+    pub fn mark_gc(&self) -> AppResult<()> {
+        write_atomic(
+            &self.root.join(LAST_GC_FILE),
+            unix_now().to_string().as_bytes(),
+        )
+    }
+
+    /// Files under the objects directory that no entry refers to, such as
+    /// leftovers from interrupted writes, older than [`ORPHAN_GRACE`].
+    pub fn orphans(&self) -> AppResult<Vec<PathBuf>> {
+        self.find_orphans(&self.manifest()?)
+    }
+
+    /// Remove every entry from the cache.
+    pub fn clear(&self) -> AppResult<()> {
+        let _lock = self.lock()?;
+        let objects_dir = self.root.join(OBJECTS_DIR);
+
+        if objects_dir.exists() {
+            fs::remove_dir_all(&objects_dir).map_err(|e| TramError::Cache {
+                message: format!("Failed to clear cache: {}", e),
+            })?;
+        }
+
+        fs::create_dir_all(&objects_dir).map_err(|e| TramError::Cache {
+            message: format!("Failed to recreate cache directory: {}", e),
+        })?;
+
+        self.save_manifest(&CacheManifest::default())
+    }
+
+    /// Count the entries and their total size.
+    pub fn stats(&self) -> AppResult<CacheStats> {
+        let manifest = self.manifest()?;
+        let now = unix_now();
+        let expired: Vec<&CacheEntry> = manifest
+            .entries
+            .values()
+            .filter(|entry| entry.is_expired(now))
+            .collect();
+
+        Ok(CacheStats {
+            entries: manifest.entries.len(),
+            expired: expired.len(),
+            size: manifest.total_size(),
+            expired_size: expired.iter().map(|entry| entry.size).sum(),
+        })
+    }
+
+    /// Read the cache manifest, returning an empty one if none exists yet.
+    pub fn manifest(&self) -> AppResult<CacheManifest> {
+        let path = self.root.join(MANIFEST_FILE);
+
+        if !path.exists() {
+            return Ok(CacheManifest {
+                version: 1,
+                ..Default::default()
+            });
+        }
+
+        let content = fs::read_to_string(&path).map_err(|e| TramError::Cache {
+            message: format!("Failed to read cache manifest: {}", e),
+        })?;
+
+        serde_json::from_str(&content).map_err(|e| {
+            TramError::Cache {
+                message: format!("Cache manifest is corrupted: {}", e),
+            }
+            .into()
+        })
+    }
+
+    /// Change the manifest while holding the cache lock, reading it afresh
+    /// so changes other processes made meanwhile are kept. It's only
+    /// written back if `change` changed it.
+    fn update_manifest<T>(
+        &self,
+        change: impl FnOnce(&mut CacheManifest) -> AppResult<T>,
+    ) -> AppResult<T> {
+        let _lock = self.lock()?;
+        let mut manifest = self.manifest()?;
+        let before = manifest.clone();
+
+        let result = change(&mut manifest)?;
+        if manifest != before {
+            self.save_manifest(&manifest)?;
+        }
+
+        Ok(result)
+    }
+
+    /// Take the cache's exclusive lock, waiting for any other holder. It's
+    /// released when the returned file is dropped.
+    fn lock(&self) -> AppResult<fs::File> {
+        let path = self.root.join(LOCK_FILE);
+        let lock_error = |e: std::io::Error| TramError::Cache {
+            message: format!("Failed to lock {}: {}", path.display(), e),
+        };
+
+        let file = fs::File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(lock_error)?;
+        file.lock().map_err(lock_error)?;
+
+        Ok(file)
+    }
+
+    /// Evict the least recently used entries until the cache fits within
+    /// `max_size`. The entry identified by `keep` is never evicted.
+    fn evict_to_fit(&self, manifest: &mut CacheManifest, max_size: u64, keep: Option<&str>) {
+        let entries: Vec<&CacheEntry> = manifest.entries.values().collect();
+
+        for digest in lru_victims(entries, max_size, keep) {
+            manifest.entries.remove(&digest);
+            self.remove_object(&digest);
+        }
+    }
+
+    /// Work out what a prune removes.
+    fn plan_prune(&self, manifest: &CacheManifest, max_size: Option<u64>) -> AppResult<PrunePlan> {
+        let now = unix_now();
+        let mut plan = PrunePlan::default();
+        let mut live = Vec::new();
+
+        for entry in manifest.entries.values() {
+            if entry.is_expired(now) {
+                plan.expired.push(entry.digest.clone());
+                plan.summary.freed += entry.size;
+            } else if !self.object_path(&entry.digest).exists() {
+                plan.missing.push(entry.digest.clone());
+            } else {
+                live.push(entry);
+            }
+        }
+
+        if let Some(max_size) = max_size {
+            for digest in lru_victims(live, max_size, None) {
+                plan.summary.freed += manifest.entries[&digest].size;
+                plan.evicted.push(digest);
+            }
+        }
+
+        plan.orphans = self.find_orphans(manifest)?;
+        plan.summary.freed += plan
+            .orphans
+            .iter()
+            .filter_map(|path| fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .sum::<u64>();
+
+        plan.summary.expired = plan.expired.len();
+        plan.summary.evicted = plan.evicted.len();
+        plan.summary.orphaned = plan.orphans.len();
+
+        Ok(plan)
+    }
+
+    fn find_orphans(&self, manifest: &CacheManifest) -> AppResult<Vec<PathBuf>> {
+        let objects_dir = self.root.join(OBJECTS_DIR);
+        let read_error = |e: std::io::Error| TramError::Cache {
+            message: format!("Failed to read {}: {}", objects_dir.display(), e),
+        };
+        let cutoff = SystemTime::now() - ORPHAN_GRACE;
+        let mut orphans = Vec::new();
+
+        let shards = match fs::read_dir(&objects_dir) {
+            Ok(shards) => shards,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(orphans),
+            Err(e) => return Err(read_error(e).into()),
+        };
+
+        for shard in shards {
+            let shard = shard.map_err(read_error)?.path();
+            if !shard.is_dir() {
+                continue;
+            }
+
+            for file in fs::read_dir(&shard).map_err(read_error)? {
+                let file = file.map_err(read_error)?;
+                let referenced = file
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| manifest.entries.contains_key(name));
+                let old_enough = file
+                    .metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .is_ok_and(|modified| modified < cutoff);
+
+                if !referenced && old_enough {
+                    orphans.push(file.path());
+                }
+            }
+        }
+
+        orphans.sort();
+        Ok(orphans)
+    }
+
+    fn save_manifest(&self, manifest: &CacheManifest) -> AppResult<()> {
+        let content = serde_json::to_vec_pretty(manifest).map_err(|e| TramError::Cache {
+            message: format!("Failed to serialize cache manifest: {}", e),
+        })?;
+
+        write_atomic(&self.root.join(MANIFEST_FILE), &content)
+    }
+
+    /// Path for a key's object, with its shard directory created.
+    fn prepare_object(&self, key: &CacheKey) -> AppResult<PathBuf> {
+        let object_path = self.object_path(key.digest());
+
+        if let Some(parent) = object_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| TramError::Cache {
+                message: format!("Failed to create {}: {}", parent.display(), e),
+            })?;
+        }
+
+        Ok(object_path)
+    }
+
+    fn object_path(&self, digest: &str) -> PathBuf {
+        // Shard objects by the first two hex characters to keep directories small
+        let (shard, _) = digest.split_at(2.min(digest.len()));
+        self.root.join(OBJECTS_DIR).join(shard).join(digest)
+    }
+
+    fn remove_object(&self, digest: &str) {
+        let _ = fs::remove_file(self.object_path(digest));
+    }
+}
+
+/// Count a lookup towards [`cache_lookups`].
+fn count_lookup<T>(result: AppResult<Option<T>>) -> AppResult<Option<T>> {
+    if let Ok(found) = &result {
+        let counter = if found.is_some() { &HITS } else { &MISSES };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    result
+}
+
+/// Entries and files a prune removes.
+#[derive(Debug, Default)]
+struct PrunePlan {
+    expired: Vec<String>,
+    evicted: Vec<String>,
+    /// Entries whose object is gone; dropped without freeing anything
+    missing: Vec<String>,
+    orphans: Vec<PathBuf>,
+    summary: CachePrune,
+}
+
+/// Digests to evict, least recently used first, until the entries fit in
+/// `max_size`. The entry identified by `keep` is never chosen.
+fn lru_victims(mut entries: Vec<&CacheEntry>, max_size: u64, keep: Option<&str>) -> Vec<String> {
+    let mut total: u64 = entries.iter().map(|entry| entry.size).sum();
+    entries.sort_by_key(|entry| (entry.last_used(), entry.created_at));
+
+    let mut victims = Vec::new();
+    for entry in entries {
+        if total <= max_size {
+            break;
+        }
+        if Some(entry.digest.as_str()) == keep {
+            continue;
+        }
+
+        total = total.saturating_sub(entry.size);
+        victims.push(entry.digest.clone());
+    }
+
+    victims
+}
+
+/// Hex-encoded SHA-256 digest of a byte slice.
+pub fn hash_bytes(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// Human-readable size in binary units, e.g. `1.5 MiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Parse a size such as `1GB`, `512 MiB`, `1.5G`, or `1048576` into bytes.
+/// `KB`, `MB`, ... are powers of 1000 and `KiB`, `MiB`, ... (or a bare
+/// `K`, `M`, ...) powers of 1024, matching [`format_bytes`].
+pub fn parse_bytes(input: &str) -> AppResult<u64> {
+    let invalid = || TramError::InvalidInput {
+        input: input.to_string(),
+        message: "expected a size such as 500MB or 1GiB".to_string(),
+    };
+
+    let trimmed = input.trim();
+    let split = trimmed
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1000,
+        "mb" => 1000_u64.pow(2),
+        "gb" => 1000_u64.pow(3),
+        "tb" => 1000_u64.pow(4),
+        "k" | "kib" => 1024,
+        "m" | "mib" => 1024_u64.pow(2),
+        "g" | "gib" => 1024_u64.pow(3),
+        "t" | "tib" => 1024_u64.pow(4),
+        _ => return Err(invalid().into()),
+    };
+
+    Ok((number * multiplier as f64).round() as u64)
+}
+
+/// Current time as seconds since the Unix epoch.
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Write a file by writing to a sibling temp file and renaming it into place.
+fn write_atomic(path: &Path, data: &[u8]) -> AppResult<()> {
+    let write_error = |e: std::io::Error| TramError::Cache {
+        message: format!("Failed to write {}: {}", path.display(), e),
+    };
+
+    let mut tmp = temp_file_for(path).map_err(write_error)?;
+    tmp.write_all(data).map_err(write_error)?;
+    tmp.persist(path).map_err(|e| write_error(e.error))?;
+
+    Ok(())
+}
+
+/// A uniquely named temp file next to `path`, so concurrent writers of the
+/// same file don't share one. It's deleted if dropped before being
+/// persisted.
+fn temp_file_for(path: &Path) -> std::io::Result<tempfile::NamedTempFile> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    tempfile::NamedTempFile::new_in(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_put_and_get_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = Cache::new(temp_dir.path()).unwrap();
+        let key = CacheKey::new("https://example.com/artifact.tar.gz");
+
+        cache.put(&key, b"artifact contents").unwrap();
+
+        let before = cache_lookups();
+        assert!(cache.contains(&key).unwrap());
+        assert_eq!(
+            cache.get(&key).unwrap(),
+            Some(b"artifact contents".to_vec())
+        );
+        assert_eq!(cache.get(&CacheKey::new("missing")).unwrap(), None);
+
+        // Other tests look things up concurrently, so only a lower bound holds
+        let after = cache_lookups();
+        assert!(after.hits > before.hits);
+        assert!(after.misses > before.misses);
+        assert_eq!(cache.manifest().unwrap().entries.len(), 1);
+    }
+
+    #[test]
+    fn test_put_file_moves_it_in() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = Cache::new(temp_dir.path().join("cache")).unwrap();
+        let key = CacheKey::new("https://example.com/tool.zip");
+        assert_eq!(cache.path(&key).unwrap(), None);
+
+        let download = cache.root().join("download.part");
+        fs::write(&download, b"zip contents").unwrap();
+        let entry = cache.put_file(&key, &download).unwrap();
+
+        assert_eq!(entry.size, 12);
+        assert!(!download.exists());
+        let path = cache.path(&key).unwrap().unwrap();
+        assert_eq!(fs::read(path).unwrap(), b"zip contents");
+        assert_eq!(cache.get(&key).unwrap(), Some(b"zip contents".to_vec()));
+    }
+
+    #[test]
+    fn test_content_addressed_keys_are_stable() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = Cache::new(temp_dir.path()).unwrap();
+
+        let first = cache.put_content(b"same bytes").unwrap();
+        let second = cache.put_content(b"same bytes").unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(cache.manifest().unwrap().entries.len(), 1);
+        assert_ne!(
+            CacheKey::from_parts(["ab", "c"]),
+            CacheKey::from_parts(["a", "bc"])
+        );
+    }
+
+    #[test]
+    fn test_expired_entries_are_not_returned() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = Cache::new(temp_dir.path()).unwrap();
+        let key = CacheKey::new("short-lived");
+
+        cache
+            .put_with_ttl(&key, b"data", Some(Duration::ZERO))
+            .unwrap();
+
+        assert_eq!(cache.get(&key).unwrap(), None);
+        assert!(cache.manifest().unwrap().entries.is_empty());
+    }
+
+    #[test]
+    fn test_size_based_eviction_removes_oldest() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = Cache::new(temp_dir.path()).unwrap().with_max_size(10);
+
+        let old = CacheKey::new("old");
+        let new = CacheKey::new("new");
+
+        cache.put(&old, b"123456").unwrap();
+        cache.put(&new, b"abcdef").unwrap();
+
+        assert_eq!(cache.get(&old).unwrap(), None);
+        assert_eq!(cache.get(&new).unwrap(), Some(b"abcdef".to_vec()));
+    }
+
+    #[test]
+    fn test_clear_and_prune() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = Cache::new(temp_dir.path()).unwrap();
+
+        cache
+            .put_with_ttl(&CacheKey::new("expired"), b"x", Some(Duration::ZERO))
+            .unwrap();
+        cache.put(&CacheKey::new("kept"), b"y").unwrap();
+
+        assert_eq!(cache.prune_expired().unwrap(), 1);
+        assert_eq!(cache.manifest().unwrap().entries.len(), 1);
+
+        cache.clear().unwrap();
+        assert!(cache.manifest().unwrap().entries.is_empty());
+    }
+
+    #[test]
+    fn test_stats_count_entries_and_size() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = Cache::new(temp_dir.path()).unwrap();
+
+        cache
+            .put_with_ttl(&CacheKey::new("expired"), b"abc", Some(Duration::ZERO))
+            .unwrap();
+        cache.put(&CacheKey::new("kept"), b"defgh").unwrap();
+
+        assert_eq!(
+            cache.stats().unwrap(),
+            CacheStats {
+                entries: 2,
+                expired: 1,
+                size: 8,
+                expired_size: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn test_eviction_keeps_recently_read_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = Cache::new(temp_dir.path()).unwrap().with_max_size(12);
+
+        let read = CacheKey::new("read");
+        let unread = CacheKey::new("unread");
+        cache.put(&read, b"123456").unwrap();
+        cache.put(&unread, b"abcdef").unwrap();
+
+        // Written a while ago; the first is read since
+        let mut manifest = cache.manifest().unwrap();
+        for entry in manifest.entries.values_mut() {
+            entry.created_at -= 100;
+        }
+        cache.save_manifest(&manifest).unwrap();
+        cache.get(&read).unwrap();
+
+        cache.put(&CacheKey::new("new"), b"xyz").unwrap();
+
+        assert!(cache.contains(&read).unwrap());
+        assert!(!cache.contains(&unread).unwrap());
+    }
+
+    #[test]
+    fn test_reads_update_access_time_at_most_once_per_resolution() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = Cache::new(temp_dir.path()).unwrap();
+
+        let key = CacheKey::new("read");
+        cache.put(&key, b"data").unwrap();
+        cache.get(&key).unwrap();
+        assert_eq!(
+            cache.manifest().unwrap().entries[key.digest()].accessed_at,
+            0
+        );
+
+        let mut manifest = cache.manifest().unwrap();
+        manifest.entries.get_mut(key.digest()).unwrap().created_at -= 100;
+        cache.save_manifest(&manifest).unwrap();
+        cache.get(&key).unwrap();
+        assert!(cache.manifest().unwrap().entries[key.digest()].accessed_at > 0);
+    }
+
+    #[test]
+    fn test_concurrent_writers_keep_every_entry() {
+        let temp_dir = TempDir::new().unwrap();
+
+        std::thread::scope(|scope| {
+            for writer in 0..8 {
+                let root = temp_dir.path();
+                scope.spawn(move || {
+                    let cache = Cache::new(root).unwrap();
+                    for item in 0..10 {
+                        let key = CacheKey::new(format!("{}-{}", writer, item));
+                        cache.put(&key, key.label().as_bytes()).unwrap();
+                    }
+                });
+            }
+        });
+
+        let cache = Cache::new(temp_dir.path()).unwrap();
+        assert_eq!(cache.manifest().unwrap().entries.len(), 80);
+        assert_eq!(
+            cache.get(&CacheKey::new("7-9")).unwrap(),
+            Some(b"7-9".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_prune_alongside_writers_keeps_their_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                let cache = Cache::new(root).unwrap();
+                for item in 0..20 {
+                    cache
+                        .put(&CacheKey::new(item.to_string()), b"data")
+                        .unwrap();
+                }
+            });
+            scope.spawn(|| {
+                let cache = Cache::new(root).unwrap();
+                for _ in 0..20 {
+                    cache.prune(None).unwrap();
+                }
+            });
+        });
+
+        let cache = Cache::new(root).unwrap();
+        assert_eq!(cache.manifest().unwrap().entries.len(), 20);
+        assert!(cache.contains(&CacheKey::new("19")).unwrap());
+    }
+
+    #[test]
+    fn test_prune_to_size_and_orphans() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = Cache::new(temp_dir.path()).unwrap();
+
+        cache
+            .put_with_ttl(&CacheKey::new("expired"), b"x", Some(Duration::ZERO))
+            .unwrap();
+        cache.put(&CacheKey::new("big"), b"0123456789").unwrap();
+        let small = CacheKey::new("small");
+        cache.put(&small, b"abc").unwrap();
+
+        // Read the small entry last so the big one is evicted first
+        let mut manifest = cache.manifest().unwrap();
+        manifest
+            .entries
+            .get_mut(small.digest())
+            .unwrap()
+            .accessed_at = unix_now() + 10;
+        cache.save_manifest(&manifest).unwrap();
+
+        // An old file no entry refers to, and a new one that may still be
+        // being written
+        let old = cache.root().join(OBJECTS_DIR).join("ff").join("stale.tmp");
+        fs::create_dir_all(old.parent().unwrap()).unwrap();
+        fs::write(&old, b"orphan").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(SystemTime::now() - 2 * ORPHAN_GRACE)
+            .unwrap();
+        let fresh = old.with_file_name("fresh.tmp");
+        fs::write(&fresh, b"writing").unwrap();
+
+        let expected = CachePrune {
+            expired: 1,
+            evicted: 1,
+            orphaned: 1,
+            freed: 1 + 10 + 6,
+        };
+        assert_eq!(cache.prune_preview(Some(5)).unwrap(), expected);
+        assert!(old.exists());
+
+        assert_eq!(cache.prune(Some(5)).unwrap(), expected);
+        assert!(!old.exists());
+        assert!(fresh.exists());
+        assert_eq!(cache.manifest().unwrap().entries.len(), 1);
+        assert!(cache.contains(&small).unwrap());
+        assert_eq!(cache.prune(Some(5)).unwrap(), CachePrune::default());
+    }
+
+    #[test]
+    fn test_gc_due() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = Cache::new(temp_dir.path()).unwrap();
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        // The first check starts the clock
+        assert!(!cache.gc_due(day).unwrap());
+        assert!(!cache.gc_due(day).unwrap());
+        assert!(cache.gc_due(Duration::ZERO).unwrap());
+
+        fs::write(cache.root().join(LAST_GC_FILE), "0").unwrap();
+        assert!(cache.gc_due(day).unwrap());
+        cache.prune(None).unwrap();
+        assert!(!cache.gc_due(day).unwrap());
+
+        // Only the first claim of a due run gets it
+        fs::write(cache.root().join(LAST_GC_FILE), "0").unwrap();
+        assert!(cache.claim_gc(day).unwrap());
+        assert!(!cache.claim_gc(day).unwrap());
+    }
+
+    #[test]
+    fn test_parse_bytes() {
+        assert_eq!(parse_bytes("1024").unwrap(), 1024);
+        assert_eq!(parse_bytes("1GB").unwrap(), 1_000_000_000);
+        assert_eq!(parse_bytes("512 MiB").unwrap(), 512 * 1024 * 1024);
+        assert_eq!(parse_bytes("1.5k").unwrap(), 1536);
+        assert!(parse_bytes("lots").is_err());
+        assert!(parse_bytes("1 parsec").is_err());
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MiB");
+    }
+}
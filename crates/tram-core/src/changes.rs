@@ -0,0 +1,222 @@
+//! File system changes made by commands, with dry-run support.
+//!
+//! Commands that create files write through a [`ChangeSet`], which records
+//! every directory and file created or modified. In dry-run mode the changes
+//! are recorded but nothing touches disk, so a command can report exactly
+//! what it would do. Changes also go into the run summary, if one is being
+//! collected.
+
+use crate::{AppResult, TramError};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What a change does to a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FileChangeKind {
+    CreateDir,
+    CreateFile,
+    ModifyFile,
+}
+
+impl std::fmt::Display for FileChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileChangeKind::CreateDir => write!(f, "create directory"),
+            FileChangeKind::CreateFile => write!(f, "create"),
+            FileChangeKind::ModifyFile => write!(f, "modify"),
+        }
+    }
+}
+
+/// A single created or modified path.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub kind: FileChangeKind,
+}
+
+/// Writes files and directories, recording each change.
+#[derive(Debug, Clone, Default)]
+pub struct ChangeSet {
+    dry_run: bool,
+    changes: Vec<FileChange>,
+    /// In a dry run, the contents each file would be written with
+    planned: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+impl ChangeSet {
+    /// Create a change set; with `dry_run`, changes are only recorded.
+    pub fn new(dry_run: bool) -> Self {
+        Self {
+            dry_run,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Create a directory and any missing parents, recording each one.
+    pub fn create_dir_all(&mut self, path: &Path) -> AppResult<()> {
+        let mut missing: Vec<PathBuf> = path
+            .ancestors()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .take_while(|dir| !dir.exists() && !self.is_planned(dir))
+            .map(Path::to_path_buf)
+            .collect();
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        if !self.dry_run {
+            fs::create_dir_all(path).map_err(|e| TramError::Io {
+                message: format!("Failed to create directory {}: {}", path.display(), e),
+            })?;
+        }
+
+        // Outermost first, in the order they're created
+        missing.reverse();
+        for path in missing {
+            self.record(path, FileChangeKind::CreateDir);
+        }
+
+        Ok(())
+    }
+
+    /// Write a file, creating its parent directories.
+    pub fn write_file(&mut self, path: &Path, contents: impl AsRef<[u8]>) -> AppResult<()> {
+        if let Some(parent) = path.parent() {
+            self.create_dir_all(parent)?;
+        }
+
+        let kind = if path.exists() || self.is_planned(path) {
+            FileChangeKind::ModifyFile
+        } else {
+            FileChangeKind::CreateFile
+        };
+
+        if !self.dry_run {
+            fs::write(path, contents).map_err(|e| TramError::Io {
+                message: format!("Failed to write {}: {}", path.display(), e),
+            })?;
+
+            crate::emit_file_written(path);
+        } else {
+            self.planned
+                .insert(path.to_path_buf(), contents.as_ref().to_vec());
+        }
+
+        self.record(path, kind);
+
+        Ok(())
+    }
+
+    /// Record a change made some other way, such as by a child process.
+    pub fn record(&mut self, path: impl Into<PathBuf>, kind: FileChangeKind) {
+        let change = FileChange {
+            path: path.into(),
+            kind,
+        };
+        crate::record_file_change(&change);
+        self.changes.push(change);
+    }
+
+    pub fn changes(&self) -> &[FileChange] {
+        &self.changes
+    }
+
+    /// The contents a dry run would have written to `path`.
+    pub fn planned_contents(&self, path: &Path) -> Option<&[u8]> {
+        self.planned.get(path).map(Vec::as_slice)
+    }
+
+    pub fn into_changes(self) -> Vec<FileChange> {
+        self.changes
+    }
+
+    fn is_planned(&self, path: &Path) -> bool {
+        self.changes.iter().any(|change| change.path == path)
+    }
+}
+
+/// A unified diff from `from` to `to`, naming `path` in its `---` and `+++`
+/// headers, or an empty string when they're the same.
+pub fn unified_diff(path: &str, from: &str, to: &str) -> String {
+    if from == to {
+        return String::new();
+    }
+
+    let patch = diffy::create_patch(from, to).to_string();
+    let hunks = patch
+        .split_once("+++ modified\n")
+        .map_or("", |(_, hunks)| hunks);
+    format!("--- a/{path}\n+++ b/{path}\n{hunks}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_dry_run_records_without_writing() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("existing.txt"), "old").unwrap();
+
+        let mut changes = ChangeSet::new(true);
+        changes
+            .write_file(&root.join("app/src/main.rs"), "fn main() {}")
+            .unwrap();
+        changes
+            .write_file(&root.join("app/README.md"), "# app")
+            .unwrap();
+        changes
+            .write_file(&root.join("existing.txt"), "new")
+            .unwrap();
+
+        let recorded: Vec<(PathBuf, FileChangeKind)> = changes
+            .changes()
+            .iter()
+            .map(|change| (change.path.clone(), change.kind))
+            .collect();
+        assert_eq!(
+            recorded,
+            [
+                (root.join("app"), FileChangeKind::CreateDir),
+                (root.join("app/src"), FileChangeKind::CreateDir),
+                (root.join("app/src/main.rs"), FileChangeKind::CreateFile),
+                (root.join("app/README.md"), FileChangeKind::CreateFile),
+                (root.join("existing.txt"), FileChangeKind::ModifyFile),
+            ]
+        );
+
+        assert!(!root.join("app").exists());
+        assert_eq!(
+            changes.planned_contents(&root.join("app/README.md")),
+            Some(b"# app".as_slice())
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("existing.txt")).unwrap(),
+            "old"
+        );
+    }
+
+    #[test]
+    fn test_writes_when_not_dry_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("nested/file.txt");
+
+        let mut changes = ChangeSet::new(false);
+        changes.write_file(&path, "contents").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "contents");
+        assert_eq!(changes.changes().len(), 2);
+    }
+}
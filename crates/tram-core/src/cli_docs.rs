@@ -0,0 +1,463 @@
+//! Markdown reference pages generated from a clap command tree.
+//!
+//! [`MarkdownDocs`] renders one page per command, named like the man pages
+//! (`tram.md`, `tram-cache.md`, `tram-cache-prune.md`), for docs sites that
+//! publish the CLI reference next to the rest of the documentation. Each
+//! page has the command's description, usage, tables of its arguments,
+//! options, and subcommands, and any examples registered for it. Global
+//! options are listed once, on the top-level page.
+
+use crate::{AppResult, TramError};
+use clap::{Arg, ArgAction, Command};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// An example invocation shown on a command's page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocExample {
+    /// What the example does, e.g. `Prune the cache to 1 GB`
+    pub description: String,
+    /// The command line, e.g. `tram cache prune --max-size 1GB`
+    pub command: String,
+}
+
+/// A rendered page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocPage {
+    /// File name, e.g. `tram-cache-prune.md`
+    pub file_name: String,
+    /// The command's full name, e.g. `tram cache prune`
+    pub title: String,
+    pub contents: String,
+}
+
+/// Generates Markdown reference pages for a command and its subcommands.
+#[derive(Debug, Clone)]
+pub struct MarkdownDocs {
+    command: Command,
+    /// Examples by command path below the root, `""` for the root
+    examples: BTreeMap<String, Vec<DocExample>>,
+    /// Extra Markdown appended to pages, by command path
+    sections: BTreeMap<String, Vec<String>>,
+}
+
+impl MarkdownDocs {
+    pub fn new(command: Command) -> Self {
+        Self {
+            command,
+            examples: BTreeMap::new(),
+            sections: BTreeMap::new(),
+        }
+    }
+
+    /// Show an example on the page of the command at `path`, given without
+    /// the program name (e.g. `cache prune`, or `""` for the top level).
+    pub fn with_example(
+        mut self,
+        path: &str,
+        description: impl Into<String>,
+        command: impl Into<String>,
+    ) -> Self {
+        self.examples
+            .entry(normalize(path))
+            .or_default()
+            .push(DocExample {
+                description: description.into(),
+                command: command.into(),
+            });
+        self
+    }
+
+    /// Append a Markdown section, heading included, to the page of the
+    /// command at `path`.
+    pub fn with_section(mut self, path: &str, markdown: impl Into<String>) -> Self {
+        self.sections
+            .entry(normalize(path))
+            .or_default()
+            .push(markdown.into());
+        self
+    }
+
+    /// Every page, the top-level command first, then subcommands depth-first
+    /// in declaration order. Hidden commands are left out.
+    pub fn render(&self) -> Vec<DocPage> {
+        let mut command = self.command.clone();
+        command.build();
+
+        let mut pages = Vec::new();
+        self.render_command(&command, &[], &mut pages);
+        pages
+    }
+
+    /// Write every page into `dir`, creating it if needed, and return the
+    /// paths written.
+    pub fn write(&self, dir: &Path) -> AppResult<Vec<PathBuf>> {
+        let write_error = |path: &Path, e: std::io::Error| TramError::Io {
+            message: format!("Failed to write documentation to {}: {}", path.display(), e),
+        };
+
+        fs::create_dir_all(dir).map_err(|e| write_error(dir, e))?;
+
+        self.render()
+            .into_iter()
+            .map(|page| {
+                let path = dir.join(&page.file_name);
+                fs::write(&path, page.contents).map_err(|e| write_error(&path, e))?;
+                Ok(path)
+            })
+            .collect()
+    }
+
+    fn render_command(&self, command: &Command, parents: &[&str], pages: &mut Vec<DocPage>) {
+        let mut names: Vec<&str> = parents.to_vec();
+        names.push(command.get_name());
+        let is_root = parents.is_empty();
+        let path = names[1..].join(" ");
+
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}\n", names.join(" "));
+
+        if let Some(about) = command.get_long_about().or(command.get_about()) {
+            let _ = writeln!(out, "{}\n", about.to_string().trim());
+        }
+
+        let mut usage = command.clone();
+        let _ = writeln!(
+            out,
+            "## Usage\n\n```text\n{}\n```\n",
+            usage
+                .render_usage()
+                .to_string()
+                .trim()
+                .trim_start_matches("Usage: ")
+        );
+
+        let arguments: Vec<&Arg> = command
+            .get_positionals()
+            .filter(|arg| !arg.is_hide_set())
+            .collect();
+        if !arguments.is_empty() {
+            out.push_str("## Arguments\n\n| Argument | Description |\n| --- | --- |\n");
+            for arg in arguments {
+                let _ = writeln!(out, "| `{}` | {} |", positional_name(arg), describe(arg));
+            }
+            out.push('\n');
+        }
+
+        // Global options are described once, on the top-level page
+        let options: Vec<&Arg> = command
+            .get_opts()
+            .chain(command.get_arguments().filter(|arg| is_flag(arg)))
+            .filter(|arg| !arg.is_hide_set() && !arg.is_positional())
+            .filter(|arg| is_root || !arg.is_global_set())
+            .fold(Vec::new(), |mut options, arg| {
+                if !options
+                    .iter()
+                    .any(|seen: &&Arg| seen.get_id() == arg.get_id())
+                {
+                    options.push(arg);
+                }
+                options
+            });
+        if !options.is_empty() {
+            out.push_str("## Options\n\n| Option | Description |\n| --- | --- |\n");
+            for arg in sorted_by_declaration(command, options) {
+                let _ = writeln!(out, "| `{}` | {} |", option_name(arg), describe(arg));
+            }
+            out.push('\n');
+        }
+
+        let subcommands: Vec<&Command> = command
+            .get_subcommands()
+            .filter(|sub| !sub.is_hide_set() && sub.get_name() != "help")
+            .collect();
+        if !subcommands.is_empty() {
+            out.push_str("## Commands\n\n| Command | Description |\n| --- | --- |\n");
+            for sub in &subcommands {
+                let file_name = page_file_name(&names, sub.get_name());
+                let _ = writeln!(
+                    out,
+                    "| [`{}`]({}) | {} |",
+                    sub.get_name(),
+                    file_name,
+                    escape_cell(
+                        &sub.get_about()
+                            .map(|about| about.to_string())
+                            .unwrap_or_default()
+                    )
+                );
+            }
+            out.push('\n');
+        }
+
+        if let Some(examples) = self.examples.get(&path) {
+            out.push_str("## Examples\n\n");
+            for example in examples {
+                let _ = writeln!(
+                    out,
+                    "{}:\n\n```sh\n{}\n```\n",
+                    example.description, example.command
+                );
+            }
+        }
+
+        if let Some(after) = command.get_after_long_help().or(command.get_after_help()) {
+            let _ = writeln!(out, "{}\n", after.to_string().trim());
+        }
+
+        for section in self.sections.get(&path).into_iter().flatten() {
+            let _ = writeln!(out, "{}\n", section.trim());
+        }
+
+        if let Some((parent, grandparents)) = parents.split_last() {
+            let _ = writeln!(
+                out,
+                "See also: [`{}`]({})",
+                parents.join(" "),
+                page_file_name(grandparents, parent)
+            );
+        }
+
+        pages.push(DocPage {
+            file_name: page_file_name(parents, command.get_name()),
+            title: names.join(" "),
+            contents: format!("{}\n", out.trim_end()),
+        });
+
+        for sub in subcommands {
+            self.render_command(sub, &names, pages);
+        }
+    }
+}
+
+/// `cache  prune` and ` cache prune ` both become `cache prune`.
+fn normalize(path: &str) -> String {
+    path.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `tram-cache-prune.md` for `prune` under `tram cache`.
+fn page_file_name(parents: &[&str], name: &str) -> String {
+    let mut names = parents.to_vec();
+    names.push(name);
+    format!("{}.md", names.join("-"))
+}
+
+fn is_flag(arg: &Arg) -> bool {
+    !arg.get_action().takes_values()
+}
+
+/// Options in the order the command declares them.
+fn sorted_by_declaration<'a>(command: &Command, options: Vec<&'a Arg>) -> Vec<&'a Arg> {
+    let order: Vec<_> = command.get_arguments().map(Arg::get_id).collect();
+    let mut options = options;
+    options.sort_by_key(|arg| order.iter().position(|id| *id == arg.get_id()));
+    options
+}
+
+/// `<NAME>`, or `[NAME]...` for an optional repeated argument.
+fn positional_name(arg: &Arg) -> String {
+    let name = value_names(arg).join(" ");
+    let repeated = matches!(arg.get_action(), ArgAction::Append)
+        || arg
+            .get_num_args()
+            .is_some_and(|range| range.max_values() > 1);
+    let dots = if repeated { "..." } else { "" };
+
+    if arg.is_required_set() {
+        format!("<{}>{}", name, dots)
+    } else {
+        format!("[{}]{}", name, dots)
+    }
+}
+
+/// `-o, --output <DIR>`.
+fn option_name(arg: &Arg) -> String {
+    let mut names = Vec::new();
+    if let Some(short) = arg.get_short() {
+        names.push(format!("-{}", short));
+    }
+    if let Some(long) = arg.get_long() {
+        names.push(format!("--{}", long));
+    }
+    let mut name = names.join(", ");
+
+    if !is_flag(arg) {
+        let values: Vec<String> = value_names(arg)
+            .iter()
+            .map(|value| format!("<{}>", value))
+            .collect();
+        let _ = write!(name, " {}", values.join(" "));
+    }
+
+    name
+}
+
+fn value_names(arg: &Arg) -> Vec<String> {
+    match arg.get_value_names() {
+        Some(names) if !names.is_empty() => names.iter().map(ToString::to_string).collect(),
+        _ => vec![arg.get_id().as_str().to_uppercase()],
+    }
+}
+
+/// The help text, then the possible values, default, and environment
+/// variable.
+fn describe(arg: &Arg) -> String {
+    let mut parts = Vec::new();
+
+    if let Some(help) = arg.get_help() {
+        parts.push(escape_cell(help.to_string().trim()));
+    }
+
+    let possible: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| format!("`{}`", value.get_name()))
+        .collect();
+    if !possible.is_empty() && !is_flag(arg) {
+        parts.push(format!("Possible values: {}.", possible.join(", ")));
+    }
+
+    let defaults: Vec<String> = arg
+        .get_default_values()
+        .iter()
+        .map(|value| value.to_string_lossy().into_owned())
+        .collect();
+    if !defaults.is_empty() && !is_flag(arg) {
+        parts.push(format!("Default: `{}`.", defaults.join(",")));
+    }
+
+    if let Some(env) = arg.get_env() {
+        parts.push(format!("Environment: `{}`.", env.to_string_lossy()));
+    }
+
+    parts.join(" ")
+}
+
+/// Keep a value on one table row.
+fn escape_cell(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Arg;
+
+    fn command() -> Command {
+        Command::new("app")
+            .about("An example app")
+            .arg(
+                Arg::new("verbose")
+                    .short('v')
+                    .long("verbose")
+                    .help("Say more")
+                    .action(ArgAction::SetTrue)
+                    .global(true),
+            )
+            .subcommand(
+                Command::new("cache").about("Manage the cache").subcommand(
+                    Command::new("prune")
+                        .about("Remove old entries | keep recent ones")
+                        .arg(
+                            Arg::new("max_size")
+                                .long("max-size")
+                                .value_name("SIZE")
+                                .help("Size budget")
+                                .env("APP_MAX_SIZE"),
+                        )
+                        .arg(
+                            Arg::new("format")
+                                .long("format")
+                                .value_parser(["json", "plain"])
+                                .default_value("plain"),
+                        ),
+                ),
+            )
+            .subcommand(
+                Command::new("new")
+                    .about("Create a project")
+                    .arg(Arg::new("name").required(true).help("Project name")),
+            )
+            .subcommand(Command::new("secret").hide(true))
+    }
+
+    #[test]
+    fn test_one_page_per_command() {
+        let pages = MarkdownDocs::new(command()).render();
+        let names: Vec<&str> = pages.iter().map(|page| page.file_name.as_str()).collect();
+
+        assert_eq!(
+            names,
+            ["app.md", "app-cache.md", "app-cache-prune.md", "app-new.md"]
+        );
+        assert_eq!(pages[2].title, "app cache prune");
+    }
+
+    #[test]
+    fn test_page_contents() {
+        let pages = MarkdownDocs::new(command())
+            .with_example(
+                "cache  prune",
+                "Prune to 1 GB",
+                "app cache prune --max-size 1GB",
+            )
+            .with_section("", "## Exit status\n\n0 on success")
+            .render();
+
+        let root = &pages[0].contents;
+        assert!(root.starts_with("# app\n\nAn example app\n"));
+        assert!(root.contains("## Options"));
+        assert!(root.contains("| `-v, --verbose` | Say more |"));
+        assert!(root.contains("| [`cache`](app-cache.md) | Manage the cache |"));
+        assert!(!root.contains("secret"));
+        assert!(root.contains("## Exit status\n\n0 on success"));
+
+        let cache = &pages[1].contents;
+        assert!(cache.contains(
+            "| [`prune`](app-cache-prune.md) | Remove old entries \\| keep recent ones |"
+        ));
+        assert!(cache.ends_with("See also: [`app`](app.md)\n"));
+
+        let prune = &pages[2].contents;
+        assert!(prune.contains("```text\napp cache prune [OPTIONS]\n```"));
+        assert!(!prune.contains("--verbose"));
+        assert!(
+            prune.contains("| `--max-size <SIZE>` | Size budget Environment: `APP_MAX_SIZE`. |")
+        );
+        assert!(prune.contains(
+            "| `--format <FORMAT>` | Possible values: `json`, `plain`. Default: `plain`. |"
+        ));
+        assert!(prune.contains("Prune to 1 GB:\n\n```sh\napp cache prune --max-size 1GB\n```"));
+        assert!(prune.contains("See also: [`app cache`](app-cache.md)"));
+
+        let new = &pages[3].contents;
+        assert!(new.contains("## Arguments"));
+        assert!(new.contains("| `<NAME>` | Project name |"));
+    }
+
+    #[test]
+    fn test_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("docs/cli");
+
+        let written = MarkdownDocs::new(command()).write(&out).unwrap();
+        assert_eq!(written.len(), 4);
+        assert!(
+            fs::read_to_string(out.join("app-new.md"))
+                .unwrap()
+                .starts_with("# app new")
+        );
+
+        // A file in the way is an I/O failure, not bad input
+        let blocked = dir.path().join("blocked");
+        fs::write(&blocked, "").unwrap();
+        let error = MarkdownDocs::new(command()).write(&blocked).unwrap_err();
+        assert_eq!(crate::exit_code_for(&error), crate::ExitCode::IoError);
+    }
+}
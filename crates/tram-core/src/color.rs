@@ -0,0 +1,184 @@
+//! Terminal color detection.
+//!
+//! Whether to emit ANSI colors is decided once at startup from `--color`,
+//! the `NO_COLOR`, `CLICOLOR`, and `CLICOLOR_FORCE` conventions, `TERM=dumb`,
+//! and whether each stream is a terminal. Stdout and stderr are decided
+//! independently, so `tram config | less` keeps colored warnings on stderr.
+//! Downstream code asks [`color_enabled`] instead of checking for itself.
+
+use std::io::IsTerminal;
+use std::sync::RwLock;
+
+static SUPPORT: RwLock<Option<ColorSupport>> = RwLock::new(None);
+
+/// When to use colors, as chosen with `--color`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color terminals unless the environment says otherwise
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl std::fmt::Display for ColorChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColorChoice::Auto => write!(f, "auto"),
+            ColorChoice::Always => write!(f, "always"),
+            ColorChoice::Never => write!(f, "never"),
+        }
+    }
+}
+
+impl std::str::FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!("Invalid color choice: {}", s)),
+        }
+    }
+}
+
+/// An output stream that may or may not be colored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// The color decision for each output stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorSupport {
+    pub stdout: bool,
+    pub stderr: bool,
+}
+
+impl ColorSupport {
+    /// Decide for both streams from the process environment.
+    pub fn detect(choice: ColorChoice) -> Self {
+        let env = |name: &str| std::env::var(name).ok();
+
+        Self {
+            stdout: should_color(choice, env, std::io::stdout().is_terminal()),
+            stderr: should_color(choice, env, std::io::stderr().is_terminal()),
+        }
+    }
+
+    pub fn enabled(&self, stream: Stream) -> bool {
+        match stream {
+            Stream::Stdout => self.stdout,
+            Stream::Stderr => self.stderr,
+        }
+    }
+}
+
+/// Decide whether a stream should be colored.
+///
+/// With `auto`: `CLICOLOR_FORCE` (non-zero) forces color, then `NO_COLOR`
+/// (non-empty), `CLICOLOR=0`, and `TERM=dumb` disable it, and otherwise
+/// only terminals are colored.
+fn should_color(
+    choice: ColorChoice,
+    env: impl Fn(&str) -> Option<String>,
+    is_terminal: bool,
+) -> bool {
+    match choice {
+        ColorChoice::Always => return true,
+        ColorChoice::Never => return false,
+        ColorChoice::Auto => {}
+    }
+
+    if env("CLICOLOR_FORCE").is_some_and(|v| !v.is_empty() && v != "0") {
+        return true;
+    }
+
+    if env("NO_COLOR").is_some_and(|v| !v.is_empty())
+        || env("CLICOLOR").is_some_and(|v| v == "0")
+        || env("TERM").is_some_and(|v| v == "dumb")
+    {
+        return false;
+    }
+
+    is_terminal
+}
+
+/// Detect color support for this run and make it available to [`color_enabled`].
+pub fn init_color(choice: ColorChoice) -> ColorSupport {
+    let support = ColorSupport::detect(choice);
+
+    if let Ok(mut current) = SUPPORT.write() {
+        *current = Some(support);
+    }
+
+    support
+}
+
+/// The color decision for this run, detected with `auto` if
+/// [`init_color`] hasn't been called.
+pub fn color_support() -> ColorSupport {
+    if let Some(support) = SUPPORT.read().ok().and_then(|current| *current) {
+        return support;
+    }
+
+    init_color(ColorChoice::Auto)
+}
+
+/// Whether output written to a stream should be colored.
+pub fn color_enabled(stream: Stream) -> bool {
+    color_support().enabled(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn decide(choice: ColorChoice, vars: &[(&str, &str)], is_terminal: bool) -> bool {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+
+        should_color(choice, |name| vars.get(name).cloned(), is_terminal)
+    }
+
+    #[test]
+    fn test_auto_follows_terminal() {
+        assert!(decide(ColorChoice::Auto, &[], true));
+        assert!(!decide(ColorChoice::Auto, &[], false));
+    }
+
+    #[test]
+    fn test_env_conventions() {
+        assert!(!decide(ColorChoice::Auto, &[("NO_COLOR", "1")], true));
+        assert!(decide(ColorChoice::Auto, &[("NO_COLOR", "")], true));
+        assert!(!decide(ColorChoice::Auto, &[("CLICOLOR", "0")], true));
+        assert!(!decide(ColorChoice::Auto, &[("TERM", "dumb")], true));
+        assert!(decide(ColorChoice::Auto, &[("CLICOLOR_FORCE", "1")], false));
+        assert!(decide(
+            ColorChoice::Auto,
+            &[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")],
+            false
+        ));
+        assert!(!decide(
+            ColorChoice::Auto,
+            &[("CLICOLOR_FORCE", "0")],
+            false
+        ));
+    }
+
+    #[test]
+    fn test_explicit_choice_wins() {
+        assert!(decide(ColorChoice::Always, &[("NO_COLOR", "1")], false));
+        assert!(!decide(
+            ColorChoice::Never,
+            &[("CLICOLOR_FORCE", "1")],
+            true
+        ));
+    }
+}
@@ -0,0 +1,579 @@
+//! Crash report capture for CLI applications.
+//!
+//! [`CrashReporter::install`] replaces the default panic output with a
+//! miette-styled report: an apology, the path of a report written to the
+//! data directory, and a link for filing an issue with the version, build,
+//! and panic details already filled in. The process then exits with
+//! [`ExitCode::InternalError`]. The report file holds everything needed to
+//! triage the crash: the panic message and location, a backtrace, the version
+//! and build, the platform, and the active configuration with secrets
+//! redacted.
+
+use crate::{ExitCode, Stream, color_enabled};
+use miette::{Diagnostic, GraphicalReportHandler, GraphicalTheme};
+use serde::Serialize;
+use serde_json::Value;
+use std::backtrace::Backtrace;
+use std::fmt::{Display, Write as _};
+use std::fs;
+use std::io::Write as _;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Placeholder for redacted config values.
+pub const REDACTED: &str = "[redacted]";
+
+/// Config keys containing any of these words are redacted from reports.
+const SENSITIVE_WORDS: [&str; 12] = [
+    "password",
+    "passwd",
+    "passphrase",
+    "secret",
+    "secrets",
+    "token",
+    "tokens",
+    "credential",
+    "credentials",
+    "auth",
+    "key",
+    "apikey",
+];
+
+/// How the binary was built, for crash reports and issue links.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildInfo {
+    /// Short git commit the binary was built from
+    pub commit: Option<String>,
+    /// Target triple, e.g. `x86_64-unknown-linux-gnu`
+    pub target: Option<String>,
+    /// Cargo profile, e.g. `release`
+    pub profile: Option<String>,
+}
+
+impl Display for BuildInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<&str> = [&self.commit, &self.target, &self.profile]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+
+        if parts.is_empty() {
+            write!(f, "unknown")
+        } else {
+            write!(f, "{}", parts.join(" "))
+        }
+    }
+}
+
+/// Everything captured about a single crash.
+#[derive(Debug, Clone)]
+pub struct CrashReport {
+    pub app_name: String,
+    pub version: String,
+    pub build: BuildInfo,
+    pub os: String,
+    pub arch: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+    pub config: Option<Value>,
+    pub timestamp: u64,
+}
+
+impl CrashReport {
+    /// Render the report as plain text.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        let _ = writeln!(out, "# {} crash report", self.app_name);
+        let _ = writeln!(out);
+        let _ = writeln!(out, "Version: {}", self.version);
+        let _ = writeln!(out, "Build: {}", self.build);
+        let _ = writeln!(out, "OS: {} ({})", self.os, self.arch);
+        let _ = writeln!(out, "Time: {} (unix seconds)", self.timestamp);
+        let _ = writeln!(out);
+        let _ = writeln!(out, "## Panic");
+        let _ = writeln!(out);
+        let _ = writeln!(out, "{}", self.message);
+
+        if let Some(location) = &self.location {
+            let _ = writeln!(out, "at {}", location);
+        }
+
+        if let Some(config) = &self.config {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "## Configuration");
+            let _ = writeln!(out);
+            let _ = writeln!(
+                out,
+                "{}",
+                serde_json::to_string_pretty(config).unwrap_or_default()
+            );
+        }
+
+        let _ = writeln!(out);
+        let _ = writeln!(out, "## Backtrace");
+        let _ = writeln!(out);
+        let _ = writeln!(out, "{}", self.backtrace);
+
+        out
+    }
+
+    /// Write the report into `dir`, returning the file path.
+    pub fn write_to(&self, dir: &Path) -> std::io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+
+        let path = dir.join(format!(
+            "crash-{}-{}.log",
+            self.timestamp,
+            std::process::id()
+        ));
+        fs::write(&path, self.render())?;
+
+        Ok(path)
+    }
+
+    /// A new-issue URL with the title and body filled in from this report.
+    ///
+    /// Only the version, build, platform, and panic are included; the
+    /// backtrace and configuration stay in the report file, both to keep the
+    /// URL short and so nothing is shared without the user reviewing it.
+    pub fn issue_url(&self, base: &str) -> String {
+        let title = format!("Crash: {}", first_line(&self.message));
+
+        let mut body = String::new();
+        let _ = writeln!(
+            body,
+            "**What were you doing when {} crashed?**",
+            self.app_name
+        );
+        let _ = writeln!(body);
+        let _ = writeln!(body);
+        let _ = writeln!(body, "**Crash details**");
+        let _ = writeln!(body);
+        let _ = writeln!(body, "- Version: {}", self.version);
+        let _ = writeln!(body, "- Build: {}", self.build);
+        let _ = writeln!(body, "- OS: {} ({})", self.os, self.arch);
+        let _ = writeln!(body, "- Panic: {}", self.message);
+        if let Some(location) = &self.location {
+            let _ = writeln!(body, "- Location: {}", location);
+        }
+
+        let separator = if base.contains('?') { '&' } else { '?' };
+        format!(
+            "{}{}title={}&body={}",
+            base,
+            separator,
+            encode_query_value(&title),
+            encode_query_value(&body)
+        )
+    }
+}
+
+/// The terminal report for a crash, rendered by miette.
+#[derive(Debug)]
+struct CrashDiagnostic {
+    message: String,
+    code: String,
+    help: String,
+}
+
+impl CrashDiagnostic {
+    fn new(
+        report: &CrashReport,
+        written: &std::io::Result<Option<PathBuf>>,
+        issue_url: Option<&str>,
+    ) -> Self {
+        let mut help = format!("This is a bug in {}, sorry about that!", report.app_name);
+
+        match written {
+            Ok(Some(path)) => {
+                let _ = write!(help, "\nA crash report was written to: {}", path.display());
+            }
+            Ok(None) => {}
+            Err(e) => {
+                let _ = write!(help, "\nFailed to write a crash report: {}", e);
+            }
+        }
+
+        if let Some(url) = issue_url {
+            let _ = write!(
+                help,
+                "\nPlease report it, attaching the crash report: {}",
+                report.issue_url(url)
+            );
+        }
+
+        let mut message = format!(
+            "{} {} crashed: {}",
+            report.app_name, report.version, report.message
+        );
+        if let Some(location) = &report.location {
+            let _ = write!(message, "\n  at {}", location);
+        }
+
+        Self {
+            message,
+            code: format!("{}::internal_error", report.app_name),
+            help,
+        }
+    }
+
+    fn render(&self) -> String {
+        let theme = if color_enabled(Stream::Stderr) {
+            GraphicalTheme::unicode()
+        } else {
+            GraphicalTheme::unicode_nocolor()
+        };
+
+        // Wrapping would break the issue link across lines
+        let handler = GraphicalReportHandler::new_themed(theme).with_wrap_lines(false);
+
+        let mut out = String::new();
+        match handler.render_report(&mut out, self) {
+            Ok(()) => out,
+            Err(_) => format!("{}\n{}", self.message, self.help),
+        }
+    }
+}
+
+impl Display for CrashDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for CrashDiagnostic {}
+
+impl Diagnostic for CrashDiagnostic {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(&self.code))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(&self.help))
+    }
+}
+
+/// Builder for the crash reporting panic hook.
+#[derive(Debug, Clone)]
+pub struct CrashReporter {
+    app_name: String,
+    version: String,
+    issue_url: Option<String>,
+    report_dir: Option<PathBuf>,
+    config: Option<Value>,
+    build: BuildInfo,
+    exit_code: u8,
+}
+
+impl CrashReporter {
+    pub fn new(app_name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            app_name: app_name.into(),
+            version: version.into(),
+            issue_url: None,
+            report_dir: None,
+            config: None,
+            build: BuildInfo::default(),
+            exit_code: ExitCode::InternalError.code(),
+        }
+    }
+
+    /// Include how the binary was built.
+    pub fn with_build_info(mut self, build: BuildInfo) -> Self {
+        self.build = build;
+        self
+    }
+
+    /// Code the process exits with after a panic. Defaults to
+    /// [`ExitCode::InternalError`].
+    pub fn with_exit_code(mut self, code: u8) -> Self {
+        self.exit_code = code;
+        self
+    }
+
+    /// Link shown to users for filing an issue, e.g. a GitHub new-issue URL.
+    /// The issue's title and body are added as query parameters.
+    pub fn with_issue_url(mut self, url: impl Into<String>) -> Self {
+        self.issue_url = Some(url.into());
+        self
+    }
+
+    /// Directory reports are written to. Defaults to `<data dir>/crashes`.
+    pub fn with_report_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.report_dir = Some(dir.into());
+        self
+    }
+
+    /// Include the active configuration, with secrets redacted.
+    pub fn with_config<T: Serialize>(mut self, config: &T) -> Self {
+        self.config = serde_json::to_value(config).ok().map(sanitize_config);
+        self
+    }
+
+    /// Directory reports will be written to, if one can be determined.
+    pub fn report_dir(&self) -> Option<PathBuf> {
+        self.report_dir
+            .clone()
+            .or_else(|| crate::data_dir().map(|dir| dir.join("crashes")))
+    }
+
+    /// Capture a report for the given panic.
+    pub fn capture(&self, info: &PanicHookInfo<'_>) -> CrashReport {
+        CrashReport {
+            app_name: self.app_name.clone(),
+            version: self.version.clone(),
+            build: self.build.clone(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            message: panic_message(info),
+            location: info
+                .location()
+                .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+            backtrace: Backtrace::force_capture().to_string(),
+            config: self.config.clone(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Install the panic hook. After reporting, the process exits with the
+    /// configured exit code, whichever thread panicked.
+    ///
+    /// When `RUST_BACKTRACE` is set the default panic output is still printed
+    /// afterwards, so developers keep the raw backtrace they asked for.
+    ///
+    /// A panic from printing to a closed pipe, as in `tram env | head`, isn't
+    /// a bug: the process exits quietly without a report.
+    pub fn install(self) {
+        let default_hook = std::panic::take_hook();
+        let show_default = std::env::var_os("RUST_BACKTRACE").is_some_and(|v| v != "0");
+
+        std::panic::set_hook(Box::new(move |info| {
+            if is_broken_pipe(&panic_message(info)) {
+                std::process::exit(ExitCode::Success.code().into());
+            }
+
+            let report = self.capture(info);
+            let written = self
+                .report_dir()
+                .map(|dir| report.write_to(&dir))
+                .transpose();
+
+            let diagnostic = CrashDiagnostic::new(&report, &written, self.issue_url.as_deref());
+            // stderr may be closed too; panicking again here would abort
+            let _ = write!(std::io::stderr().lock(), "\n{}", diagnostic.render());
+
+            if show_default {
+                default_hook(info);
+            }
+
+            std::process::exit(self.exit_code.into());
+        }));
+    }
+}
+
+/// Redact sensitive values and the user's home directory from a config.
+pub fn sanitize_config(value: Value) -> Value {
+    let home = crate::home_dir().map(|home| home.to_string_lossy().into_owned());
+    sanitize_value(value, home.as_deref())
+}
+
+fn sanitize_value(value: Value, home: Option<&str>) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    let value = if is_sensitive_key(&key) && !value.is_null() {
+                        Value::String(REDACTED.to_string())
+                    } else {
+                        sanitize_value(value, home)
+                    };
+                    (key, value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| sanitize_value(item, home))
+                .collect(),
+        ),
+        Value::String(text) => match home {
+            Some(home) if !home.is_empty() && text.starts_with(home) => {
+                Value::String(format!("~{}", &text[home.len()..]))
+            }
+            _ => Value::String(text),
+        },
+        other => other,
+    }
+}
+
+/// Whether a key such as `apiToken`, `db_password`, or `auth-header` holds a secret.
+/// Whether a key looks like it holds a secret, by its words in any case
+/// style (`apiKey`, `db_password`, `auth-token`).
+pub fn is_sensitive_key(key: &str) -> bool {
+    let mut words = vec![String::new()];
+
+    for c in key.chars() {
+        if matches!(c, '_' | '-' | '.' | ' ') {
+            words.push(String::new());
+            continue;
+        }
+        if c.is_uppercase() && words.last().is_some_and(|w| !w.is_empty()) {
+            words.push(String::new());
+        }
+        if let Some(word) = words.last_mut() {
+            word.extend(c.to_lowercase());
+        }
+    }
+
+    words
+        .iter()
+        .any(|word| SENSITIVE_WORDS.contains(&word.as_str()))
+}
+
+/// The first line of a possibly multi-line message.
+fn first_line(message: &str) -> &str {
+    message.lines().next().unwrap_or_default()
+}
+
+/// Percent-encode a URL query value, keeping only unreserved characters.
+fn encode_query_value(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => {
+                let _ = write!(encoded, "%{:02X}", byte);
+            }
+        }
+    }
+
+    encoded
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    let payload = info.payload();
+
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Unknown panic".to_string())
+}
+
+/// Whether a panic came from `print!` or `eprint!` writing to a closed pipe.
+fn is_broken_pipe(message: &str) -> bool {
+    message.starts_with("failed printing to std") && message.contains("Broken pipe")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sanitize_config_redacts_secrets() {
+        let sanitized = sanitize_value(
+            json!({
+                "logLevel": "info",
+                "apiToken": "abc123",
+                "registry": { "password": "hunter2", "url": "https://example.com" },
+                "workspaceRoot": "/home/me/project",
+                "authHeader": null,
+                "author": "Jane"
+            }),
+            Some("/home/me"),
+        );
+
+        assert_eq!(sanitized["logLevel"], "info");
+        assert_eq!(sanitized["apiToken"], REDACTED);
+        assert_eq!(sanitized["registry"]["password"], REDACTED);
+        assert_eq!(sanitized["registry"]["url"], "https://example.com");
+        assert_eq!(sanitized["workspaceRoot"], "~/project");
+        assert!(sanitized["authHeader"].is_null());
+        assert_eq!(sanitized["author"], "Jane");
+    }
+
+    fn report() -> CrashReport {
+        CrashReport {
+            app_name: "tram".into(),
+            version: "0.1.0".into(),
+            build: BuildInfo {
+                commit: Some("abc1234".into()),
+                target: Some("x86_64-unknown-linux-gnu".into()),
+                profile: Some("release".into()),
+            },
+            os: "linux".into(),
+            arch: "x86_64".into(),
+            message: "boom".into(),
+            location: Some("src/main.rs:1:1".into()),
+            backtrace: "<backtrace>".into(),
+            config: Some(json!({ "color": true })),
+            timestamp: 42,
+        }
+    }
+
+    #[test]
+    fn test_broken_pipe_panics_are_not_bugs() {
+        assert!(is_broken_pipe(
+            "failed printing to stdout: Broken pipe (os error 32)"
+        ));
+        assert!(is_broken_pipe(
+            "failed printing to stderr: Broken pipe (os error 32)"
+        ));
+        assert!(!is_broken_pipe(
+            "called `Option::unwrap()` on a `None` value"
+        ));
+    }
+
+    #[test]
+    fn test_report_written_to_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = report().write_to(temp_dir.path()).unwrap();
+        let content = fs::read_to_string(path).unwrap();
+
+        assert!(content.contains("# tram crash report"));
+        assert!(content.contains("Version: 0.1.0"));
+        assert!(content.contains("Build: abc1234 x86_64-unknown-linux-gnu release"));
+        assert!(content.contains("boom\nat src/main.rs:1:1"));
+        assert!(content.contains("\"color\": true"));
+    }
+
+    #[test]
+    fn test_issue_url_is_prefilled() {
+        let url = report().issue_url("https://github.com/o/r/issues/new?template=crash.md");
+
+        assert!(url.starts_with(
+            "https://github.com/o/r/issues/new?template=crash.md&title=Crash%3A%20boom&body="
+        ));
+        assert!(url.contains("Build%3A%20abc1234%20x86_64-unknown-linux-gnu%20release"));
+        assert!(url.contains("Location%3A%20src%2Fmain.rs%3A1%3A1"));
+        assert!(!url.contains("color"));
+
+        let url = report().issue_url("https://github.com/o/r/issues/new");
+        assert!(url.starts_with("https://github.com/o/r/issues/new?title="));
+    }
+
+    #[test]
+    fn test_diagnostic_has_code_help_and_link() {
+        let written = Ok(Some(PathBuf::from("/tmp/crash.log")));
+        let diagnostic = CrashDiagnostic::new(&report(), &written, Some("https://example.com/new"));
+        let rendered = diagnostic.render();
+
+        assert!(rendered.contains("tram::internal_error"));
+        assert!(rendered.contains("tram 0.1.0 crashed: boom"));
+        assert!(rendered.contains("/tmp/crash.log"));
+        assert!(rendered.contains("https://example.com/new?title="));
+    }
+}
@@ -0,0 +1,240 @@
+//! Deprecation warnings for commands, flags, and config keys.
+//!
+//! Deprecations are registered once at startup and checked against what the
+//! user actually invoked. Each deprecated item warns at most once per run,
+//! and warnings can be silenced with `TRAM_NO_DEPRECATION_WARNINGS=1` for
+//! scripts that can't migrate yet. The full list is available through
+//! [`deprecations`] so it can be printed in machine-readable form.
+
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock, RwLock};
+
+/// Environment variable that silences deprecation warnings.
+pub const NO_DEPRECATION_WARNINGS_ENV: &str = "TRAM_NO_DEPRECATION_WARNINGS";
+
+static REGISTRY: OnceLock<RwLock<Vec<Deprecation>>> = OnceLock::new();
+static WARNED: OnceLock<Mutex<HashSet<(DeprecationKind, String)>>> = OnceLock::new();
+static SUPPRESSED: AtomicBool = AtomicBool::new(false);
+
+/// What kind of item is deprecated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DeprecationKind {
+    /// A subcommand, e.g. `init`
+    Command,
+    /// A flag, e.g. `--verbose` or `init --verbose`
+    Flag,
+    /// A config file key, e.g. `outputFormat`
+    ConfigKey,
+}
+
+impl std::fmt::Display for DeprecationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeprecationKind::Command => write!(f, "command"),
+            DeprecationKind::Flag => write!(f, "flag"),
+            DeprecationKind::ConfigKey => write!(f, "config key"),
+        }
+    }
+}
+
+/// A deprecated item and how to migrate away from it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Deprecation {
+    pub kind: DeprecationKind,
+    pub name: String,
+    /// Version the item was deprecated in
+    pub since: String,
+    /// What to use instead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+    /// Version the item is planned to be removed in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub removal: Option<String>,
+}
+
+impl Deprecation {
+    pub fn new(kind: DeprecationKind, name: impl Into<String>, since: impl Into<String>) -> Self {
+        Self {
+            kind,
+            name: name.into(),
+            since: since.into(),
+            replacement: None,
+            removal: None,
+        }
+    }
+
+    /// Deprecate a subcommand.
+    pub fn command(name: impl Into<String>, since: impl Into<String>) -> Self {
+        Self::new(DeprecationKind::Command, name, since)
+    }
+
+    /// Deprecate a flag.
+    pub fn flag(name: impl Into<String>, since: impl Into<String>) -> Self {
+        Self::new(DeprecationKind::Flag, name, since)
+    }
+
+    /// Deprecate a config key.
+    pub fn config_key(name: impl Into<String>, since: impl Into<String>) -> Self {
+        Self::new(DeprecationKind::ConfigKey, name, since)
+    }
+
+    pub fn with_replacement(mut self, replacement: impl Into<String>) -> Self {
+        self.replacement = Some(replacement.into());
+        self
+    }
+
+    pub fn with_removal(mut self, version: impl Into<String>) -> Self {
+        self.removal = Some(version.into());
+        self
+    }
+
+    /// The warning shown to users when the item is used.
+    pub fn message(&self) -> String {
+        crate::t!("deprecated-warning", description = self.describe())
+    }
+
+    /// One sentence describing the deprecation and how to migrate.
+    pub fn describe(&self) -> String {
+        let mut message = crate::t!(
+            "deprecated-item",
+            kind = self.kind.to_string(),
+            name = self.name.as_str(),
+            since = self.since.as_str()
+        );
+
+        if let Some(removal) = &self.removal {
+            message.push_str(&crate::t!("deprecated-removal", version = removal.as_str()));
+        }
+
+        message.push('.');
+
+        if let Some(replacement) = &self.replacement {
+            message.push_str(&crate::t!(
+                "deprecated-replacement",
+                replacement = replacement.as_str()
+            ));
+        }
+
+        message
+    }
+}
+
+fn registry() -> &'static RwLock<Vec<Deprecation>> {
+    REGISTRY.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// Register a deprecated item, replacing any previous entry for it.
+pub fn register_deprecation(deprecation: Deprecation) {
+    if let Ok(mut entries) = registry().write() {
+        entries.retain(|d| !(d.kind == deprecation.kind && d.name == deprecation.name));
+        entries.push(deprecation);
+    }
+}
+
+/// All registered deprecations, in registration order.
+pub fn deprecations() -> Vec<Deprecation> {
+    registry()
+        .read()
+        .map(|entries| entries.clone())
+        .unwrap_or_default()
+}
+
+/// Look up the deprecation for an item, if it is deprecated.
+pub fn find_deprecation(kind: DeprecationKind, name: &str) -> Option<Deprecation> {
+    registry()
+        .read()
+        .ok()?
+        .iter()
+        .find(|d| d.kind == kind && d.name == name)
+        .cloned()
+}
+
+/// Silence (or re-enable) deprecation warnings for this run.
+pub fn suppress_deprecation_warnings(suppress: bool) {
+    SUPPRESSED.store(suppress, Ordering::Relaxed);
+}
+
+fn warnings_suppressed() -> bool {
+    SUPPRESSED.load(Ordering::Relaxed)
+        || std::env::var(NO_DEPRECATION_WARNINGS_ENV)
+            .is_ok_and(|v| !v.is_empty() && v != "0" && v != "false")
+}
+
+/// Warn on stderr if the item is deprecated.
+///
+/// Each item warns at most once per run. Returns the deprecation so callers
+/// can react further (for example by emitting an event).
+pub fn warn_if_deprecated(kind: DeprecationKind, name: &str) -> Option<Deprecation> {
+    let deprecation = find_deprecation(kind, name)?;
+
+    let first_time = WARNED
+        .get_or_init(|| Mutex::new(HashSet::new()))
+        .lock()
+        .map(|mut warned| warned.insert((kind, name.to_string())))
+        .unwrap_or(true);
+
+    if first_time && !warnings_suppressed() {
+        eprintln!("{}", deprecation.message());
+        crate::record_warning(deprecation.message());
+    }
+
+    Some(deprecation)
+}
+
+/// Warn about any deprecated keys set in a loaded config file, given as
+/// dotted paths such as `http.timeoutSecs`.
+pub fn warn_deprecated_config_keys<'a>(keys: impl IntoIterator<Item = &'a str>) {
+    for key in keys {
+        warn_if_deprecated(DeprecationKind::ConfigKey, key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_and_find() {
+        register_deprecation(
+            Deprecation::flag("build --fast", "0.1.0").with_replacement("build --profile fast"),
+        );
+        register_deprecation(Deprecation::flag("build --fast", "0.2.0"));
+
+        let found = find_deprecation(DeprecationKind::Flag, "build --fast").unwrap();
+        assert_eq!(found.since, "0.2.0", "re-registering replaces the entry");
+        assert_eq!(
+            deprecations()
+                .iter()
+                .filter(|d| d.name == "build --fast")
+                .count(),
+            1
+        );
+
+        assert!(find_deprecation(DeprecationKind::Command, "build --fast").is_none());
+        assert!(warn_if_deprecated(DeprecationKind::Flag, "build --slow").is_none());
+    }
+
+    #[test]
+    fn test_message_and_serialization() {
+        let deprecation = Deprecation::command("init", "0.1.0")
+            .with_removal("0.3.0")
+            .with_replacement("tram new");
+
+        assert_eq!(
+            deprecation.message(),
+            "warning: The `init` command is deprecated since 0.1.0 and will be removed in 0.3.0. Use `tram new` instead."
+        );
+
+        let json = serde_json::to_value(&deprecation).unwrap();
+        assert_eq!(json["kind"], "command");
+        assert_eq!(json["replacement"], "tram new");
+
+        let bare = serde_json::to_value(Deprecation::config_key("color", "0.1.0")).unwrap();
+        assert_eq!(bare["kind"], "configKey");
+        assert!(bare.get("removal").is_none());
+    }
+}
@@ -0,0 +1,188 @@
+//! Reference of the environment variables a CLI reads.
+//!
+//! tram-core knows the variables it reads itself (directories, color, locale,
+//! telemetry, CI detection, and so on). Config settings bound to a variable
+//! come from the config crate, and downstream CLIs add their own with
+//! [`register_env_var`], so `config env-vars` can list everything in one
+//! place. Registering a name again replaces its entry.
+
+use crate::{
+    CACHE_DIR_ENV, CI_ENV_VARS, CONFIG_DIR_ENV, DATA_DIR_ENV, DO_NOT_TRACK_ENV, LOCALE_ENV,
+    NO_DEPRECATION_WARNINGS_ENV, SYSTEM_CONFIG_DIR_ENV, TELEMETRY_ENV,
+};
+use serde::Serialize;
+use std::sync::{OnceLock, RwLock};
+
+static REGISTRY: OnceLock<RwLock<Vec<EnvVar>>> = OnceLock::new();
+
+/// An environment variable and what it does.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvVar {
+    /// Variable name; a trailing `*` stands for a family, e.g. `TRAM_SECRET_*`
+    pub name: String,
+    pub description: String,
+    /// Config key the variable overrides, if it is bound to a setting
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub setting: Option<String>,
+}
+
+impl EnvVar {
+    pub fn new(name: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            setting: None,
+        }
+    }
+
+    /// Mark the variable as overriding a config key, e.g. `http.proxy`.
+    pub fn with_setting(mut self, key: impl Into<String>) -> Self {
+        self.setting = Some(key.into());
+        self
+    }
+
+    /// Whether the variable is a family of names like `TRAM_SECRET_*`.
+    pub fn is_prefix(&self) -> bool {
+        self.name.ends_with('*')
+    }
+
+    /// Whether `name` is this variable, or belongs to its family.
+    pub fn matches(&self, name: &str) -> bool {
+        match self.name.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix) && name.len() > prefix.len(),
+            None => self.name == name,
+        }
+    }
+}
+
+fn registry() -> &'static RwLock<Vec<EnvVar>> {
+    REGISTRY.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// Register a variable read by the CLI, replacing any previous entry for it.
+pub fn register_env_var(var: EnvVar) {
+    if let Ok(mut entries) = registry().write() {
+        entries.retain(|v| v.name != var.name);
+        entries.push(var);
+    }
+}
+
+/// Variables tram-core reads itself.
+pub fn core_env_vars() -> Vec<EnvVar> {
+    let mut vars = vec![
+        EnvVar::new(CONFIG_DIR_ENV, "Directory for the user config file"),
+        EnvVar::new(
+            SYSTEM_CONFIG_DIR_ENV,
+            "Directory for the system-wide config file, instead of /etc/tram",
+        ),
+        EnvVar::new(DATA_DIR_ENV, "Directory for state, telemetry, and plugins"),
+        EnvVar::new(
+            CACHE_DIR_ENV,
+            "Directory for cached downloads and artifacts",
+        ),
+        EnvVar::new(
+            "HOME",
+            "Home directory the default config, data, and cache directories are under",
+        ),
+        EnvVar::new(
+            "XDG_CONFIG_HOME",
+            "Base config directory when TRAM_CONFIG_DIR is unset",
+        ),
+        EnvVar::new(
+            "XDG_DATA_HOME",
+            "Base data directory when TRAM_DATA_DIR is unset",
+        ),
+        EnvVar::new(
+            "XDG_CACHE_HOME",
+            "Base cache directory when TRAM_CACHE_DIR is unset",
+        ),
+        EnvVar::new(LOCALE_ENV, "Language for messages, e.g. de-DE"),
+        EnvVar::new("LC_ALL", "Language for messages when TRAM_LOCALE is unset"),
+        EnvVar::new(
+            "LC_MESSAGES",
+            "Language for messages when TRAM_LOCALE and LC_ALL are unset",
+        ),
+        EnvVar::new("LANG", "Language for messages when nothing else sets it"),
+        EnvVar::new("NO_COLOR", "Any non-empty value turns colors off"),
+        EnvVar::new("CLICOLOR", "0 turns colors off"),
+        EnvVar::new(
+            "CLICOLOR_FORCE",
+            "Any value but 0 forces colors, even when not a terminal",
+        ),
+        EnvVar::new("TERM", "dumb turns colors off"),
+        EnvVar::new("COLUMNS", "Terminal width used to fit tables"),
+        EnvVar::new(
+            TELEMETRY_ENV,
+            "1/on or 0/off overrides the telemetry answer",
+        ),
+        EnvVar::new(DO_NOT_TRACK_ENV, "Any value but 0 turns telemetry off"),
+        EnvVar::new(NO_DEPRECATION_WARNINGS_ENV, "Silences deprecation warnings"),
+        EnvVar::new(
+            "TRAM_SECRET_*",
+            "Value for secret:// references, e.g. TRAM_SECRET_API_KEY for secret://api-key",
+        ),
+        EnvVar::new("RUST_BACKTRACE", "Include a backtrace in crash reports"),
+    ];
+
+    vars.extend(
+        CI_ENV_VARS
+            .iter()
+            .map(|name| EnvVar::new(*name, "Detected as CI: prompts are turned off")),
+    );
+
+    vars
+}
+
+/// Every known variable: tram-core's own, then registered ones in
+/// registration order. Registered entries replace core ones of the same name.
+pub fn env_vars() -> Vec<EnvVar> {
+    let registered = registry()
+        .read()
+        .map(|entries| entries.clone())
+        .unwrap_or_default();
+
+    let mut vars: Vec<EnvVar> = core_env_vars()
+        .into_iter()
+        .filter(|var| !registered.iter().any(|r| r.name == var.name))
+        .collect();
+    vars.extend(registered);
+    vars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registered_vars_replace_core_ones() {
+        register_env_var(EnvVar::new("TRAM_TEST_ENV_VAR", "first"));
+        register_env_var(EnvVar::new("TRAM_TEST_ENV_VAR", "second").with_setting("test.key"));
+        register_env_var(EnvVar::new("NO_COLOR", "overridden"));
+
+        let vars = env_vars();
+        let find = |name: &str| vars.iter().filter(|v| v.name == name).collect::<Vec<_>>();
+
+        let test_var = find("TRAM_TEST_ENV_VAR");
+        assert_eq!(test_var.len(), 1);
+        assert_eq!(test_var[0].description, "second");
+        assert_eq!(test_var[0].setting.as_deref(), Some("test.key"));
+        assert_eq!(find("NO_COLOR")[0].description, "overridden");
+        assert_eq!(find("NO_COLOR").len(), 1);
+        assert!(!find("CI").is_empty());
+    }
+
+    #[test]
+    fn test_prefix_matching() {
+        let secrets = EnvVar::new("TRAM_SECRET_*", "secrets");
+        assert!(secrets.is_prefix());
+        assert!(secrets.matches("TRAM_SECRET_API_KEY"));
+        assert!(!secrets.matches("TRAM_SECRET_"));
+        assert!(!secrets.matches("TRAM_SECRETS"));
+
+        let plain = EnvVar::new("NO_COLOR", "colors");
+        assert!(!plain.is_prefix());
+        assert!(plain.matches("NO_COLOR"));
+        assert!(!plain.matches("NO_COLOR_X"));
+    }
+}
@@ -3,7 +3,9 @@
 //! Provides error types commonly needed in CLI applications with good
 //! diagnostic messages.
 
+use crate::{ExitCode, Signal};
 use miette::Diagnostic;
+use serde::Serialize;
 use thiserror::Error;
 
 /// Common CLI application errors with good user-facing diagnostics.
@@ -20,10 +22,299 @@ pub enum TramError {
     #[diagnostic(code(tram::invalid_config))]
     InvalidConfig { message: String },
 
+    #[error("Already exists: {path}")]
+    #[diagnostic(
+        code(tram::conflict),
+        help("Choose another name, or move the existing one out of the way")
+    )]
+    Conflict { path: String },
+
+    #[error("Missing required input: {}", inputs.join(", "))]
+    #[diagnostic(
+        code(tram::missing_input),
+        help(
+            "Pass it on the command line. Prompts are off without a terminal, in CI, and with --yes"
+        )
+    )]
+    MissingInput { inputs: Vec<String> },
+
+    #[error("Invalid {input}: {message}")]
+    #[diagnostic(code(tram::invalid_input))]
+    InvalidInput { input: String, message: String },
+
+    #[error("Prompt failed: {message}")]
+    #[diagnostic(code(tram::prompt))]
+    Prompt { message: String },
+
+    #[error("Cancelled")]
+    #[diagnostic(code(tram::cancelled))]
+    Cancelled,
+
+    #[error("Interrupted")]
+    #[diagnostic(code(tram::interrupted))]
+    Interrupted,
+
+    #[error("Stopped by {signal}")]
+    #[diagnostic(code(tram::signal))]
+    Signal { signal: Signal },
+
+    #[error("Timed out after {seconds}s")]
+    #[diagnostic(
+        code(tram::timed_out),
+        help("Raise --timeout, or leave it off to let the command take as long as it needs")
+    )]
+    TimedOut { seconds: u64 },
+
     #[error("Workspace not found")]
     #[diagnostic(
         code(tram::workspace_not_found),
         help("Make sure you're running this command from within a project")
     )]
     WorkspaceNotFound,
+
+    #[error("I/O error: {message}")]
+    #[diagnostic(
+        code(tram::io),
+        help("Check that the path exists and that you have permission to use it")
+    )]
+    Io { message: String },
+
+    #[error("Cache error: {message}")]
+    #[diagnostic(
+        code(tram::cache),
+        help("The cache can be safely cleared if it has become corrupted")
+    )]
+    Cache { message: String },
+
+    #[error("State error: {message}")]
+    #[diagnostic(code(tram::state))]
+    State { message: String },
+
+    #[error("Network error: {message}")]
+    #[diagnostic(
+        code(tram::network),
+        help("Check your network connection and try again")
+    )]
+    Network { message: String },
+
+    #[error("Localization error: {message}")]
+    #[diagnostic(code(tram::i18n))]
+    Localization { message: String },
+
+    #[error("Secret error: {message}")]
+    #[diagnostic(
+        code(tram::secret),
+        help("Secrets are referenced as secret://name or secret://provider/name")
+    )]
+    Secret { message: String },
+
+    #[error("Update failed: {message}")]
+    #[diagnostic(
+        code(tram::update),
+        help("The current version was left in place; you can also download the release manually")
+    )]
+    Update { message: String },
+
+    #[error("Plugin error: {message}")]
+    #[diagnostic(code(tram::plugin))]
+    Plugin { message: String },
+
+    #[error("Process error: {message}")]
+    #[diagnostic(code(tram::process))]
+    Process { message: String },
+
+    #[error("Task error: {message}")]
+    #[diagnostic(
+        code(tram::task),
+        help("Run `tram run --list` to see the tasks defined in the config")
+    )]
+    Task { message: String },
+
+    #[error("{failed} of {total} batch commands failed")]
+    #[diagnostic(
+        code(tram::batch),
+        help("Each command's error is listed in the batch results above")
+    )]
+    Batch { failed: usize, total: usize },
+
+    #[error("{failed} of {total} doctor checks failed")]
+    #[diagnostic(
+        code(tram::doctor),
+        help("Each failed check's fix is listed in the report above")
+    )]
+    Doctor { failed: usize, total: usize },
+
+    #[error("Daemon error: {message}")]
+    #[diagnostic(
+        code(tram::daemon),
+        help("Run `tram daemon status` to see whether a daemon is running")
+    )]
+    Daemon { message: String },
+
+    #[error("Upgrade failed: {message}")]
+    #[diagnostic(
+        code(tram::upgrade),
+        help("Only projects created by `tram new` can be upgraded")
+    )]
+    Upgrade { message: String },
+
+    #[error("Migration failed: {message}")]
+    #[diagnostic(
+        code(tram::migration),
+        help("Your files were left as they were; back up the data directory and report the error")
+    )]
+    Migration { message: String },
+
+    #[error("{action} needs the network, but offline mode is on")]
+    #[diagnostic(
+        code(tram::offline),
+        help("Drop --offline, or unset `offline` in the config and TRAM_OFFLINE")
+    )]
+    Offline { action: String },
+
+    #[error("Output error: {message}")]
+    #[diagnostic(
+        code(tram::output),
+        help("This is a bug in tram; please report it with the command you ran")
+    )]
+    Output { message: String },
+}
+
+impl TramError {
+    /// The process exit code for this error.
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            TramError::ConfigNotFound { .. } | TramError::InvalidConfig { .. } => ExitCode::Config,
+            TramError::MissingInput { .. } | TramError::InvalidInput { .. } => ExitCode::Usage,
+            TramError::WorkspaceNotFound => ExitCode::WorkspaceNotFound,
+            TramError::Conflict { .. } => ExitCode::Conflict,
+            TramError::Cancelled => ExitCode::Cancelled,
+            TramError::Interrupted => ExitCode::Interrupted,
+            TramError::Signal { signal } => signal.exit_code(),
+            TramError::TimedOut { .. } => ExitCode::TimedOut,
+            TramError::Network { .. } | TramError::Update { .. } | TramError::Offline { .. } => {
+                ExitCode::Network
+            }
+            TramError::Task { .. } | TramError::Process { .. } => ExitCode::TaskFailed,
+            TramError::Io { .. } => ExitCode::IoError,
+            TramError::Output { .. } => ExitCode::InternalError,
+            TramError::Cache { .. }
+            | TramError::State { .. }
+            | TramError::Localization { .. }
+            | TramError::Secret { .. }
+            | TramError::Prompt { .. }
+            | TramError::Plugin { .. }
+            | TramError::Batch { .. }
+            | TramError::Doctor { .. }
+            | TramError::Daemon { .. }
+            | TramError::Upgrade { .. }
+            | TramError::Migration { .. } => ExitCode::Failure,
+        }
+    }
+}
+
+impl From<&TramError> for ExitCode {
+    fn from(error: &TramError) -> Self {
+        error.exit_code()
+    }
+}
+
+impl From<TramError> for ExitCode {
+    fn from(error: TramError) -> Self {
+        error.exit_code()
+    }
+}
+
+/// The process exit code for an error report: its [`TramError`]'s code, or
+/// [`ExitCode::Failure`] for anything else.
+pub fn exit_code_for(report: &miette::Report) -> ExitCode {
+    report
+        .downcast_ref::<TramError>()
+        .map(ExitCode::from)
+        .unwrap_or(ExitCode::Failure)
+}
+
+/// A failure as structured data, printed instead of the human report when
+/// the output format is json.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorReport {
+    /// Diagnostic code, e.g. `tram::workspace_not_found`
+    pub code: Option<String>,
+    pub exit_code: u8,
+    pub message: String,
+    pub help: Option<String>,
+    /// Underlying causes, outermost first
+    pub chain: Vec<String>,
+}
+
+impl ErrorReport {
+    pub fn from_report(report: &miette::Report) -> Self {
+        Self {
+            code: report.code().map(|code| code.to_string()),
+            exit_code: exit_code_for(report).code(),
+            message: report.to_string(),
+            help: report.help().map(|help| help.to_string()),
+            chain: report.chain().skip(1).map(ToString::to_string).collect(),
+        }
+    }
+
+    /// The report as a single-line `{"error": {...}}` JSON object.
+    pub fn to_json(&self) -> String {
+        serde_json::json!({ "error": self }).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_report() {
+        let report: miette::Report = TramError::WorkspaceNotFound.into();
+        let report = report.wrap_err("Failed to show workspace");
+        let error = ErrorReport::from_report(&report);
+
+        assert_eq!(error.message, "Failed to show workspace");
+        assert_eq!(error.chain, vec![TramError::WorkspaceNotFound.to_string()]);
+        assert_eq!(error.exit_code, ExitCode::WorkspaceNotFound.code());
+
+        let report: miette::Report = TramError::WorkspaceNotFound.into();
+        let json: serde_json::Value =
+            serde_json::from_str(&ErrorReport::from_report(&report).to_json()).unwrap();
+        assert_eq!(json["error"]["code"], "tram::workspace_not_found");
+        assert!(json["error"]["help"].is_string());
+    }
+
+    #[test]
+    fn test_exit_code_for_report() {
+        let report: miette::Report = TramError::WorkspaceNotFound.into();
+        assert_eq!(exit_code_for(&report), ExitCode::WorkspaceNotFound);
+
+        let report: miette::Report = TramError::Io {
+            message: "disk full".to_string(),
+        }
+        .into();
+        assert_eq!(exit_code_for(&report), ExitCode::IoError);
+
+        let report = miette::miette!("something else");
+        assert_eq!(exit_code_for(&report), ExitCode::Failure);
+    }
+
+    #[test]
+    fn test_exit_code_from_error() {
+        assert_eq!(ExitCode::from(TramError::Cancelled), ExitCode::Cancelled);
+        assert_eq!(
+            ExitCode::from(&TramError::InvalidConfig {
+                message: "bad".to_string()
+            }),
+            ExitCode::Config
+        );
+        assert_eq!(
+            ExitCode::from(TramError::Output {
+                message: "bad".to_string()
+            }),
+            ExitCode::InternalError
+        );
+    }
 }
@@ -0,0 +1,189 @@
+//! Machine-readable lifecycle events.
+//!
+//! When enabled with `--events json` or `--events-fd <fd>`, every [`Event`]
+//! is written as a single JSON line so editors, GUIs, and CI wrappers can
+//! follow along without scraping human-oriented output. Emitting is a no-op
+//! until [`init_events`] has been called, so library code can emit freely.
+
+use crate::{AppResult, TramError};
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static SINK: OnceLock<Mutex<Box<dyn Write + Send>>> = OnceLock::new();
+
+/// A lifecycle event emitted while a command runs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum Event {
+    /// A command began executing.
+    CommandStarted { command: String },
+
+    /// Progress was made on a long-running operation.
+    #[serde(rename_all = "camelCase")]
+    Progress {
+        message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        current: Option<u64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        total: Option<u64>,
+    },
+
+    /// A file was created or overwritten.
+    FileWritten { path: PathBuf },
+
+    /// A command finished executing.
+    #[serde(rename_all = "camelCase")]
+    CommandFinished {
+        command: String,
+        success: bool,
+        duration_ms: u64,
+    },
+}
+
+/// An event plus the time it was emitted, as written to the stream.
+#[derive(Serialize)]
+struct EventLine<'a> {
+    timestamp: u64,
+    #[serde(flatten)]
+    event: &'a Event,
+}
+
+/// Where events are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventTarget {
+    /// Write to stderr, keeping stdout for command output.
+    Stderr,
+    /// Write to an already-open file descriptor inherited from the parent.
+    Fd(i32),
+}
+
+/// Start writing events to the given target.
+///
+/// Only the first call has any effect.
+pub fn init_events(target: EventTarget) -> AppResult<()> {
+    let writer: Box<dyn Write + Send> = match target {
+        EventTarget::Stderr => Box::new(std::io::stderr()),
+        EventTarget::Fd(fd) => open_fd(fd)?,
+    };
+
+    let _ = SINK.set(Mutex::new(writer));
+
+    Ok(())
+}
+
+/// Whether an event stream has been configured.
+pub fn events_enabled() -> bool {
+    SINK.get().is_some()
+}
+
+/// Write an event to the stream, if one is configured.
+///
+/// Write failures are logged and otherwise ignored; a consumer going away
+/// must never fail the command itself.
+pub fn emit(event: Event) {
+    let Some(sink) = SINK.get() else {
+        return;
+    };
+
+    let line = EventLine {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default(),
+        event: &event,
+    };
+
+    let Ok(json) = serde_json::to_string(&line) else {
+        return;
+    };
+
+    if let Ok(mut writer) = sink.lock()
+        && let Err(e) = writeln!(writer, "{}", json).and_then(|_| writer.flush())
+    {
+        tracing::debug!("Failed to write event: {}", e);
+    }
+}
+
+/// Emit a [`Event::Progress`] event.
+pub fn emit_progress(message: impl Into<String>, current: Option<u64>, total: Option<u64>) {
+    if events_enabled() {
+        emit(Event::Progress {
+            message: message.into(),
+            current,
+            total,
+        });
+    }
+}
+
+/// Emit a [`Event::FileWritten`] event.
+pub fn emit_file_written(path: impl Into<PathBuf>) {
+    if events_enabled() {
+        emit(Event::FileWritten { path: path.into() });
+    }
+}
+
+#[cfg(unix)]
+fn open_fd(fd: i32) -> AppResult<Box<dyn Write + Send>> {
+    use std::os::fd::FromRawFd;
+
+    // Refuse stdin and make sure the descriptor is actually open before
+    // taking ownership of it
+    if fd < 1 || unsafe { libc::fcntl(fd, libc::F_GETFD) } < 0 {
+        return Err(TramError::InvalidConfig {
+            message: format!("File descriptor {} is not open for writing", fd),
+        }
+        .into());
+    }
+
+    // SAFETY: the descriptor was checked to be open above, and the parent
+    // process handed it to us for the lifetime of this process.
+    let file = unsafe { std::fs::File::from_raw_fd(fd) };
+
+    Ok(Box::new(file))
+}
+
+#[cfg(not(unix))]
+fn open_fd(fd: i32) -> AppResult<Box<dyn Write + Send>> {
+    Err(TramError::InvalidConfig {
+        message: format!(
+            "Writing events to file descriptor {} is only supported on Unix",
+            fd
+        ),
+    }
+    .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_serialization() {
+        let event = Event::CommandFinished {
+            command: "new".into(),
+            success: true,
+            duration_ms: 12,
+        };
+        let line = EventLine {
+            timestamp: 1,
+            event: &event,
+        };
+
+        assert_eq!(
+            serde_json::to_string(&line).unwrap(),
+            r#"{"timestamp":1,"event":"commandFinished","command":"new","success":true,"durationMs":12}"#
+        );
+
+        let progress = serde_json::to_value(Event::Progress {
+            message: "Copying".into(),
+            current: Some(1),
+            total: None,
+        })
+        .unwrap();
+        assert_eq!(progress["event"], "progress");
+        assert!(progress.get("total").is_none());
+    }
+}
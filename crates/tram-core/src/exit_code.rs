@@ -0,0 +1,195 @@
+//! Stable, documented process exit codes.
+//!
+//! Every way a command can end maps to one of these codes, so scripts and CI
+//! can tell a bad config from a failed task without parsing error output.
+//! The table is listed by `tram exit-codes` and in the man page's EXIT
+//! STATUS section; codes are never renumbered once released.
+//!
+//! This module has no dependencies so the build script can share it.
+
+/// How a command ended, as a process exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExitCode {
+    Success,
+    Failure,
+    Usage,
+    Config,
+    WorkspaceNotFound,
+    Conflict,
+    Network,
+    TaskFailed,
+    IoError,
+    Cancelled,
+    InternalError,
+    TimedOut,
+    HangUp,
+    Interrupted,
+    Terminated,
+}
+
+impl ExitCode {
+    /// Every exit code, in numeric order.
+    pub const ALL: [ExitCode; 15] = [
+        ExitCode::Success,
+        ExitCode::Failure,
+        ExitCode::Usage,
+        ExitCode::Config,
+        ExitCode::WorkspaceNotFound,
+        ExitCode::Conflict,
+        ExitCode::Network,
+        ExitCode::TaskFailed,
+        ExitCode::IoError,
+        ExitCode::Cancelled,
+        ExitCode::InternalError,
+        ExitCode::TimedOut,
+        ExitCode::HangUp,
+        ExitCode::Interrupted,
+        ExitCode::Terminated,
+    ];
+
+    /// The numeric code the process exits with.
+    pub fn code(self) -> u8 {
+        match self {
+            ExitCode::Success => 0,
+            ExitCode::Failure => 1,
+            ExitCode::Usage => 2,
+            ExitCode::Config => 3,
+            ExitCode::WorkspaceNotFound => 4,
+            ExitCode::Conflict => 5,
+            ExitCode::Network => 6,
+            ExitCode::TaskFailed => 7,
+            ExitCode::IoError => 8,
+            ExitCode::Cancelled => 9,
+            // EX_SOFTWARE from sysexits.h
+            ExitCode::InternalError => 70,
+            // The code coreutils `timeout` uses
+            ExitCode::TimedOut => 124,
+            // 128 + the signal number, as shells report for a process the
+            // signal killed
+            ExitCode::HangUp => 129,
+            ExitCode::Interrupted => 130,
+            ExitCode::Terminated => 143,
+        }
+    }
+
+    /// Stable kebab-case name, e.g. `workspace-not-found`.
+    pub fn name(self) -> &'static str {
+        match self {
+            ExitCode::Success => "success",
+            ExitCode::Failure => "failure",
+            ExitCode::Usage => "usage",
+            ExitCode::Config => "config",
+            ExitCode::WorkspaceNotFound => "workspace-not-found",
+            ExitCode::Conflict => "conflict",
+            ExitCode::Network => "network",
+            ExitCode::TaskFailed => "task-failed",
+            ExitCode::IoError => "io-error",
+            ExitCode::Cancelled => "cancelled",
+            ExitCode::InternalError => "internal-error",
+            ExitCode::TimedOut => "timed-out",
+            ExitCode::HangUp => "hang-up",
+            ExitCode::Interrupted => "interrupted",
+            ExitCode::Terminated => "terminated",
+        }
+    }
+
+    /// One-line explanation for docs and `tram exit-codes`.
+    pub fn description(self) -> &'static str {
+        match self {
+            ExitCode::Success => "The command completed successfully",
+            ExitCode::Failure => "An error not covered by a more specific code",
+            ExitCode::Usage => "Invalid command-line arguments",
+            ExitCode::Config => "The config file is missing or invalid",
+            ExitCode::WorkspaceNotFound => "No workspace was found where one is required",
+            ExitCode::Conflict => "A project directory or template file already exists",
+            ExitCode::Network => "A network request or update failed",
+            ExitCode::TaskFailed => "A task or child process failed",
+            ExitCode::IoError => "Reading or writing a file failed",
+            ExitCode::Cancelled => {
+                "A confirmation or choice prompt was dismissed with Esc, so nothing was done"
+            }
+            ExitCode::InternalError => {
+                "tram crashed or failed internally; this is a bug, please report it"
+            }
+            ExitCode::TimedOut => "The command ran longer than --timeout",
+            ExitCode::HangUp => "The terminal closed (SIGHUP)",
+            ExitCode::Interrupted => "Interrupted with Ctrl+C",
+            ExitCode::Terminated => "Stopped by SIGTERM, or the console window closed",
+        }
+    }
+
+    /// The EXIT STATUS section of a man page, in roff.
+    pub fn man_section() -> String {
+        let mut roff = String::from(".SH \"EXIT STATUS\"\n");
+
+        for exit_code in Self::ALL {
+            roff.push_str(&format!(
+                ".TP\n\\fB{}\\fR ({})\n{}\n",
+                exit_code.code(),
+                exit_code.name().replace('-', "\\-"),
+                exit_code.description().replace('-', "\\-")
+            ));
+        }
+
+        roff
+    }
+
+    /// An "Exit status" section for Markdown docs, as a table.
+    pub fn markdown_section() -> String {
+        let mut markdown =
+            String::from("## Exit status\n\n| Code | Name | Meaning |\n| --- | --- | --- |\n");
+
+        for exit_code in Self::ALL {
+            markdown.push_str(&format!(
+                "| {} | `{}` | {} |\n",
+                exit_code.code(),
+                exit_code.name(),
+                exit_code.description()
+            ));
+        }
+
+        markdown
+    }
+}
+
+impl From<ExitCode> for std::process::ExitCode {
+    fn from(exit_code: ExitCode) -> Self {
+        std::process::ExitCode::from(exit_code.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_are_unique_and_ordered() {
+        let codes: Vec<u8> = ExitCode::ALL.iter().map(|code| code.code()).collect();
+        let mut sorted = codes.clone();
+        sorted.sort();
+        sorted.dedup();
+
+        assert_eq!(codes, sorted);
+        assert_eq!(ExitCode::Success.code(), 0);
+        assert_eq!(ExitCode::Usage.code(), 2);
+        assert_eq!(ExitCode::IoError.code(), 8);
+        assert_eq!(ExitCode::Cancelled.code(), 9);
+    }
+
+    #[test]
+    fn test_man_section_escapes_hyphens() {
+        let roff = ExitCode::man_section();
+
+        assert!(roff.starts_with(".SH \"EXIT STATUS\""));
+        assert!(roff.contains("\\fB4\\fR (workspace\\-not\\-found)"));
+    }
+
+    #[test]
+    fn test_markdown_section_lists_every_code() {
+        let markdown = ExitCode::markdown_section();
+
+        assert!(markdown.starts_with("## Exit status\n"));
+        assert!(markdown.contains("| 4 | `workspace-not-found` |"));
+        assert_eq!(markdown.lines().count(), 4 + ExitCode::ALL.len());
+    }
+}
@@ -0,0 +1,287 @@
+//! Message localization for user-facing strings.
+//!
+//! Messages are stored as [Fluent](https://projectfluent.org) bundles keyed by
+//! locale. Tram ships an `en-US` bundle which is always used as the final
+//! fallback; downstream CLIs can register their own bundles (or override
+//! Tram's messages) with [`add_bundle`]. The active locale is detected from
+//! `TRAM_LOCALE`, `LC_ALL`, `LC_MESSAGES`, and `LANG`, in that order.
+//!
+//! Use the [`t!`](crate::t) macro to look up a message:
+//!
+//! ```
+//! let text = tram_core::t!("project-initialized", name = "demo");
+//! assert_eq!(text, "Project 'demo' initialized!");
+//! ```
+
+use crate::{AppResult, TramError};
+use fluent_bundle::FluentResource;
+use fluent_bundle::concurrent::FluentBundle;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use unic_langid::LanguageIdentifier;
+
+pub use fluent_bundle::{FluentArgs, FluentValue};
+
+/// Locale whose messages are used when a message is missing elsewhere.
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+/// Environment variable overriding the detected locale.
+pub const LOCALE_ENV: &str = "TRAM_LOCALE";
+
+/// Messages bundled with Tram.
+const EN_US_MESSAGES: &str = include_str!("locales/en-US/tram.ftl");
+
+type Bundle = FluentBundle<FluentResource>;
+
+static CATALOG: OnceLock<RwLock<Catalog>> = OnceLock::new();
+
+/// All registered bundles plus the active locale.
+struct Catalog {
+    locale: LanguageIdentifier,
+    bundles: HashMap<LanguageIdentifier, Bundle>,
+}
+
+impl Catalog {
+    fn new(locale: LanguageIdentifier) -> Self {
+        let mut catalog = Self {
+            locale,
+            bundles: HashMap::new(),
+        };
+
+        catalog
+            .add(default_locale(), EN_US_MESSAGES)
+            .expect("built-in messages must be valid Fluent");
+        catalog
+    }
+
+    fn add(&mut self, locale: LanguageIdentifier, source: &str) -> AppResult<()> {
+        let resource = FluentResource::try_new(source.to_string()).map_err(|(_, errors)| {
+            TramError::Localization {
+                message: format!(
+                    "Failed to parse messages for {}: {}",
+                    locale,
+                    errors
+                        .iter()
+                        .map(|e| e.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            }
+        })?;
+
+        let bundle = self.bundles.entry(locale.clone()).or_insert_with(|| {
+            let mut bundle = Bundle::new_concurrent(vec![locale]);
+            // Unicode isolation marks are noise in terminal output
+            bundle.set_use_isolating(false);
+            bundle
+        });
+
+        // Later bundles win so downstream CLIs can reword Tram's messages
+        bundle.add_resource_overriding(resource);
+
+        Ok(())
+    }
+
+    /// Locales to try, most specific first: `de-AT`, `de`, then `en-US`.
+    fn fallback_chain(&self) -> Vec<LanguageIdentifier> {
+        let mut chain = vec![self.locale.clone()];
+
+        let language_only = LanguageIdentifier::from_parts(self.locale.language, None, None, &[]);
+        if language_only != self.locale {
+            chain.push(language_only);
+        }
+
+        // Any registered region of the same language, e.g. `de-DE` for `de-AT`
+        let mut siblings = self
+            .bundles
+            .keys()
+            .filter(|id| id.language == self.locale.language && !chain.contains(id))
+            .cloned()
+            .collect::<Vec<_>>();
+        siblings.sort_by_key(|id| id.to_string());
+        chain.extend(siblings);
+
+        let default = default_locale();
+        if !chain.contains(&default) {
+            chain.push(default);
+        }
+
+        chain
+    }
+
+    fn format(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        for locale in self.fallback_chain() {
+            let Some(bundle) = self.bundles.get(&locale) else {
+                continue;
+            };
+            let Some(pattern) = bundle.get_message(id).and_then(|m| m.value()) else {
+                continue;
+            };
+
+            let mut errors = vec![];
+            let text = bundle.format_pattern(pattern, args, &mut errors);
+
+            for error in errors {
+                tracing::debug!("Error formatting message '{}': {}", id, error);
+            }
+
+            return text.into_owned();
+        }
+
+        tracing::debug!("Missing message '{}'", id);
+        id.to_string()
+    }
+}
+
+fn catalog() -> &'static RwLock<Catalog> {
+    CATALOG.get_or_init(|| RwLock::new(Catalog::new(detect_locale())))
+}
+
+fn default_locale() -> LanguageIdentifier {
+    DEFAULT_LOCALE.parse().expect("default locale is valid")
+}
+
+/// Parse a locale such as `de_DE.UTF-8`, `fr-CA`, or `ja`.
+///
+/// Returns `None` for the `C` / `POSIX` locales and unparsable values.
+pub fn parse_locale(value: &str) -> Option<LanguageIdentifier> {
+    // Strip the encoding and modifier: `de_DE.UTF-8@euro` -> `de_DE`
+    let value = value.split(['.', '@']).next().unwrap_or_default().trim();
+
+    if value.is_empty() || value.eq_ignore_ascii_case("C") || value.eq_ignore_ascii_case("POSIX") {
+        return None;
+    }
+
+    value.replace('_', "-").parse().ok()
+}
+
+/// Detect the user's locale from the environment, defaulting to `en-US`.
+pub fn detect_locale() -> LanguageIdentifier {
+    [LOCALE_ENV, "LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find_map(|value| parse_locale(&value))
+        .unwrap_or_else(default_locale)
+}
+
+/// The locale messages are currently rendered in.
+pub fn current_locale() -> LanguageIdentifier {
+    catalog()
+        .read()
+        .map(|catalog| catalog.locale.clone())
+        .unwrap_or_else(|_| default_locale())
+}
+
+/// Switch the active locale, e.g. from a `--locale` flag.
+pub fn set_locale(locale: &str) -> AppResult<()> {
+    let locale = parse_locale(locale).ok_or_else(|| TramError::Localization {
+        message: format!("Invalid locale '{}'", locale),
+    })?;
+
+    if let Ok(mut catalog) = catalog().write() {
+        catalog.locale = locale;
+    }
+
+    Ok(())
+}
+
+/// Register Fluent messages for a locale.
+///
+/// Messages added later take precedence over earlier ones with the same id,
+/// so a downstream CLI can both add new messages and reword Tram's own.
+pub fn add_bundle(locale: &str, source: &str) -> AppResult<()> {
+    let locale = parse_locale(locale).ok_or_else(|| TramError::Localization {
+        message: format!("Invalid locale '{}'", locale),
+    })?;
+
+    catalog()
+        .write()
+        .map_err(|_| TramError::Localization {
+            message: "Message catalog is unavailable".to_string(),
+        })?
+        .add(locale, source)
+}
+
+/// Look up a message in the active locale.
+///
+/// Falls back to `en-US`, and finally to the message id itself so a missing
+/// translation never hides output.
+pub fn localize(id: &str) -> String {
+    localize_with_args(id, None)
+}
+
+/// Look up a message and fill in its `{ $variables }`.
+pub fn localize_with_args(id: &str, args: Option<&FluentArgs>) -> String {
+    match catalog().read() {
+        Ok(catalog) => catalog.format(id, args),
+        Err(_) => id.to_string(),
+    }
+}
+
+/// Look up a localized message, optionally with named arguments.
+///
+/// ```
+/// use tram_core::t;
+///
+/// assert_eq!(t!("done"), "Done!");
+/// assert_eq!(t!("workspace-root", path = "/src"), "Workspace root: /src");
+/// ```
+#[macro_export]
+macro_rules! t {
+    ($id:expr) => {
+        $crate::i18n::localize($id)
+    };
+    ($id:expr, $($key:ident = $value:expr),+ $(,)?) => {{
+        let mut args = $crate::i18n::FluentArgs::new();
+        $( args.set(stringify!($key), $value); )+
+        $crate::i18n::localize_with_args($id, Some(&args))
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_locale() {
+        assert_eq!(parse_locale("de_DE.UTF-8").unwrap().to_string(), "de-DE");
+        assert_eq!(parse_locale("fr-CA").unwrap().to_string(), "fr-CA");
+        assert_eq!(parse_locale("ja").unwrap().to_string(), "ja");
+        assert!(parse_locale("C").is_none());
+        assert!(parse_locale("POSIX").is_none());
+        assert!(parse_locale("").is_none());
+    }
+
+    #[test]
+    fn test_fallback_to_default_and_overrides() {
+        let mut catalog = Catalog::new("de-AT".parse().unwrap());
+        catalog
+            .add("de".parse().unwrap(), "done = Fertig!")
+            .unwrap();
+
+        // Language-only bundle is used for a regional locale
+        assert_eq!(catalog.format("done", None), "Fertig!");
+
+        // Messages missing from the locale fall back to en-US
+        let mut args = FluentArgs::new();
+        args.set("name", "demo");
+        assert_eq!(
+            catalog.format("project-initialized", Some(&args)),
+            "Project 'demo' initialized!"
+        );
+
+        // Unknown ids are returned as-is
+        assert_eq!(catalog.format("no-such-message", None), "no-such-message");
+
+        // Downstream bundles can override built-in messages
+        catalog.add(default_locale(), "done = All done.").unwrap();
+        catalog.locale = default_locale();
+        assert_eq!(catalog.format("done", None), "All done.");
+    }
+
+    #[test]
+    fn test_invalid_bundle_is_rejected() {
+        let mut catalog = Catalog::new(default_locale());
+        assert!(catalog.add(default_locale(), "= missing id").is_err());
+    }
+}
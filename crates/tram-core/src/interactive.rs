@@ -0,0 +1,73 @@
+//! Deciding whether a command may prompt.
+//!
+//! Prompts only appear when someone can answer them: stdin and stderr are
+//! both terminals, no CI environment is detected, and `--yes` wasn't given.
+//! Otherwise commands take their defaults, or fail with
+//! [`TramError::MissingInput`](crate::TramError::MissingInput) listing the
+//! inputs that have none, so scripts never hang on a hidden prompt.
+
+use std::io::IsTerminal;
+
+/// Environment variables set by common CI services.
+pub const CI_ENV_VARS: [&str; 11] = [
+    "CI",
+    "CONTINUOUS_INTEGRATION",
+    "BUILD_NUMBER",
+    "RUN_ID",
+    "GITHUB_ACTIONS",
+    "GITLAB_CI",
+    "BUILDKITE",
+    "CIRCLECI",
+    "TF_BUILD",
+    "JENKINS_URL",
+    "TEAMCITY_VERSION",
+];
+
+/// Whether the process runs in CI, judging by `env`. Variables set to an
+/// empty string, `0`, or `false` don't count, so `CI=false` opts back in.
+pub fn is_ci(env: impl Fn(&str) -> Option<String>) -> bool {
+    CI_ENV_VARS.iter().any(|name| {
+        env(name).is_some_and(|value| {
+            let value = value.trim();
+            !value.is_empty() && value != "0" && !value.eq_ignore_ascii_case("false")
+        })
+    })
+}
+
+/// Whether prompts may be shown: not turned off with `--yes`, not in CI, and
+/// with a terminal on stdin and stderr.
+pub fn can_prompt(non_interactive: bool) -> bool {
+    !non_interactive
+        && !is_ci(|name| std::env::var(name).ok())
+        && std::io::stdin().is_terminal()
+        && std::io::stderr().is_terminal()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ci(vars: &[(&str, &str)]) -> bool {
+        is_ci(|name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn test_is_ci() {
+        assert!(!ci(&[]));
+        assert!(ci(&[("CI", "true")]));
+        assert!(ci(&[("GITHUB_ACTIONS", "true")]));
+        assert!(ci(&[("BUILD_NUMBER", "42")]));
+        assert!(!ci(&[("CI", "false")]));
+        assert!(!ci(&[("CI", "0")]));
+        assert!(!ci(&[("CI", "")]));
+    }
+
+    #[test]
+    fn test_non_interactive_flag_wins() {
+        assert!(!can_prompt(true));
+    }
+}
@@ -3,15 +3,67 @@
 //! This crate provides common utilities for building CLI applications with
 //! clap and starbase, without unnecessary abstractions.
 
+pub mod cache;
+pub mod changes;
+pub mod cli_docs;
+pub mod color;
+pub mod crash;
+pub mod deprecation;
+pub mod env_vars;
 pub mod error;
+pub mod events;
+pub mod exit_code;
+pub mod i18n;
+pub mod interactive;
+pub mod license;
 pub mod logging;
+pub mod offline;
+pub mod packaging;
+pub mod paths;
+pub mod plugins;
+pub mod progress;
 pub mod project_init;
+pub mod secrets;
+pub mod signals;
+pub mod state;
+pub mod suggest;
+pub mod summary;
+pub mod table;
+pub mod telemetry;
 pub mod template_gen;
+pub mod timings;
+pub mod upgrade;
 
+pub use cache::*;
+pub use changes::*;
+pub use cli_docs::*;
+pub use color::*;
+pub use crash::*;
+pub use deprecation::*;
+pub use env_vars::*;
 pub use error::*;
+pub use events::*;
+pub use exit_code::*;
+pub use i18n::*;
+pub use interactive::*;
+pub use license::*;
 pub use logging::*;
+pub use offline::*;
+pub use packaging::*;
+pub use paths::*;
+pub use plugins::*;
+pub use progress::*;
 pub use project_init::*;
+pub use secrets::*;
+pub use signals::*;
+pub use state::*;
+pub use suggest::*;
+pub use summary::*;
+pub use table::*;
+pub use telemetry::*;
 pub use template_gen::*;
+pub use timings::*;
+pub use upgrade::*;
 
 // Re-export commonly used types for convenience
 pub use miette::{IntoDiagnostic, Result as AppResult, miette};
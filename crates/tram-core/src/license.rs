@@ -0,0 +1,161 @@
+//! Licenses for new projects.
+//!
+//! A [`LicenseKind`] is recorded in the project's manifest by its SPDX
+//! identifier. Built-in licenses also have their text written to `LICENSE`,
+//! with the copyright year and holder filled in where the license has them.
+
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::str::FromStr;
+
+/// A project license, by SPDX identifier.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", from = "String")]
+pub enum LicenseKind {
+    Mit,
+    Apache2,
+    Gpl3,
+    Bsd3Clause,
+    Mpl2,
+    Isc,
+    Unlicense,
+    /// Any other SPDX identifier, recorded in the manifest without a
+    /// `LICENSE` file
+    Other(String),
+}
+
+impl LicenseKind {
+    /// Licenses with a built-in `LICENSE` template, most common first.
+    pub const BUILT_IN: [LicenseKind; 7] = [
+        LicenseKind::Mit,
+        LicenseKind::Apache2,
+        LicenseKind::Gpl3,
+        LicenseKind::Bsd3Clause,
+        LicenseKind::Mpl2,
+        LicenseKind::Isc,
+        LicenseKind::Unlicense,
+    ];
+
+    /// The SPDX identifier, e.g. `Apache-2.0`.
+    pub fn spdx_id(&self) -> &str {
+        match self {
+            LicenseKind::Mit => "MIT",
+            LicenseKind::Apache2 => "Apache-2.0",
+            LicenseKind::Gpl3 => "GPL-3.0-only",
+            LicenseKind::Bsd3Clause => "BSD-3-Clause",
+            LicenseKind::Mpl2 => "MPL-2.0",
+            LicenseKind::Isc => "ISC",
+            LicenseKind::Unlicense => "Unlicense",
+            LicenseKind::Other(id) => id,
+        }
+    }
+
+    /// The `LICENSE` file for a project by `author` started in `year`, or
+    /// `None` without a built-in template.
+    pub fn text(&self, year: i32, author: &str) -> Option<String> {
+        let template = match self {
+            LicenseKind::Mit => include_str!("templates/licenses/MIT.txt"),
+            LicenseKind::Apache2 => include_str!("templates/licenses/Apache-2.0.txt"),
+            LicenseKind::Gpl3 => include_str!("templates/licenses/GPL-3.0.txt"),
+            LicenseKind::Bsd3Clause => include_str!("templates/licenses/BSD-3-Clause.txt"),
+            LicenseKind::Mpl2 => include_str!("templates/licenses/MPL-2.0.txt"),
+            LicenseKind::Isc => include_str!("templates/licenses/ISC.txt"),
+            LicenseKind::Unlicense => include_str!("templates/licenses/Unlicense.txt"),
+            LicenseKind::Other(_) => return None,
+        };
+
+        Some(
+            template
+                .replace("{{year}}", &year.to_string())
+                .replace("{{author}}", author),
+        )
+    }
+}
+
+impl FromStr for LicenseKind {
+    type Err = Infallible;
+
+    /// Parse an SPDX identifier, ignoring case. `GPL-3.0` is read as
+    /// `GPL-3.0-only`, and unknown identifiers become [`LicenseKind::Other`].
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        let id = id.trim();
+        if id.eq_ignore_ascii_case("GPL-3.0") {
+            return Ok(LicenseKind::Gpl3);
+        }
+
+        Ok(Self::BUILT_IN
+            .into_iter()
+            .find(|kind| kind.spdx_id().eq_ignore_ascii_case(id))
+            .unwrap_or_else(|| LicenseKind::Other(id.to_string())))
+    }
+}
+
+impl From<String> for LicenseKind {
+    fn from(id: String) -> Self {
+        match id.parse() {
+            Ok(kind) => kind,
+        }
+    }
+}
+
+impl From<LicenseKind> for String {
+    fn from(kind: LicenseKind) -> Self {
+        kind.spdx_id().to_string()
+    }
+}
+
+impl std::fmt::Display for LicenseKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.spdx_id())
+    }
+}
+
+/// The current year, for the copyright line of a new project's license.
+pub fn current_year() -> i32 {
+    chrono::Local::now().year()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spdx_ids() {
+        assert_eq!("mit".parse::<LicenseKind>().unwrap(), LicenseKind::Mit);
+        assert_eq!("GPL-3.0".parse::<LicenseKind>().unwrap(), LicenseKind::Gpl3);
+        assert_eq!(
+            "LGPL-2.1-only".parse::<LicenseKind>().unwrap(),
+            LicenseKind::Other("LGPL-2.1-only".to_string())
+        );
+
+        for kind in LicenseKind::BUILT_IN {
+            assert_eq!(kind.spdx_id().parse::<LicenseKind>().unwrap(), kind);
+        }
+    }
+
+    #[test]
+    fn test_license_text_substitution() {
+        let mit = LicenseKind::Mit.text(2026, "Jane Doe").unwrap();
+        assert!(mit.starts_with("MIT License\n\nCopyright (c) 2026 Jane Doe\n"));
+
+        let apache = LicenseKind::Apache2.text(2026, "Jane Doe").unwrap();
+        assert!(apache.contains("Version 2.0, January 2004"));
+        assert!(!apache.contains("Jane Doe"));
+
+        assert_eq!(
+            LicenseKind::Other("EUPL-1.2".to_string()).text(2026, "Jane Doe"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_serializes_as_spdx_id() {
+        let json = serde_json::to_string(&LicenseKind::Apache2).unwrap();
+        assert_eq!(json, "\"Apache-2.0\"");
+        assert_eq!(
+            serde_json::from_str::<LicenseKind>("\"MPL-2.0\"").unwrap(),
+            LicenseKind::Mpl2
+        );
+    }
+}
@@ -0,0 +1,377 @@
+# Messages for the Tram CLI (en-US).
+#
+# This bundle is the fallback for every other locale, so every message used by
+# Tram must be defined here.
+
+## Session lifecycle
+
+working-in-workspace = Working in { $path } workspace
+detected-project = Detected { $kind } project
+done = Done!
+log-file-written = Full logs written to { $path }
+summary-write-failed = Failed to write the run summary to { $path }: { $error }
+update-available = A new version of { $app } is available: { $current } -> { $latest }
+
+## Suggestions
+
+did-you-mean = tip: did you mean '{ $suggestion }'?
+
+## Deprecations
+
+deprecated-warning = warning: { $description }
+deprecated-item = The `{ $name }` { $kind } is deprecated since { $since }
+deprecated-removal = {" "}and will be removed in { $version }
+deprecated-replacement = {" "}Use `{ $replacement }` instead.
+deprecations-none = No deprecated commands, flags, or config keys.
+deprecation-kind = Kind
+deprecation-name = Name
+deprecation-since = Since
+deprecation-removal = Removal
+deprecation-replacement = Replacement
+
+## new
+
+project-created = ✓ Created new { $kind } project: { $name }
+project-description = {"  "}Description: { $description }
+project-author = {"  "}Author: { $author }
+project-license = {"  "}License: { $license }
+dry-run-header = Dry run, nothing was written. This would:
+dry-run-create-dir = {"  "}create directory { $path }
+dry-run-create-file = {"  "}create { $path }
+dry-run-modify-file = {"  "}modify { $path }
+new-prompt-type = Project type
+new-prompt-description = Description
+new-prompt-author = Author
+new-prompt-license = License
+new-prompt-license-none = None
+new-prompt-git = Initialize a git repository?
+new-prompt-ci = Add a GitHub Actions CI workflow?
+
+## generate
+
+template-written = ✓ Generated { $kind } template: { $name } -> { $path }
+template-preview = Generated { $kind } template for '{ $name }':
+template-file-path = File path: { $path }
+template-write-hint = To write to filesystem, add the --write flag
+template-diff = { $path } already exists; generating would change it:
+template-unchanged = { $path } already matches the generated template
+template-force-hint = To overwrite it, add --write --force
+template-registered = ✓ Registered the command in { $path }
+template-marker-missing = Couldn't find `{ $marker }` under src/; add that part by hand
+
+## upgrade
+
+upgrade-up-to-date = ✓ Project is up to date with the templates from tram { $version }
+upgrade-summary = Upgraded templates from tram { $from } to { $to }
+upgrade-updated = updated { $path }
+upgrade-merged = merged { $path }
+upgrade-added = added { $path }
+upgrade-current = already up to date { $path }
+upgrade-conflict = conflict { $path }
+upgrade-missing = missing { $path } (deleted locally, not restored)
+upgrade-conflicts = { $count ->
+    [one] 1 file has edits that overlap the template changes and was left alone; apply the changes shown above by hand
+   *[other] { $count } files have edits that overlap the template changes and were left alone; apply the changes shown above by hand
+}
+
+## init
+
+project-initializing = 🚀 Initializing project: { $name }
+verbose-enabled = Verbose mode enabled
+project-files-warning = Warning: Could not create project files: { $error }
+project-initialized = Project '{ $name }' initialized!
+
+## workspace
+
+workspace-root = Workspace root: { $path }
+workspace-project-type = Project type: { $kind }
+workspace-ignore-patterns = Ignore patterns: { $patterns }
+workspace-property = Property
+workspace-root-label = Workspace root
+workspace-project-type-label = Project type
+workspace-ignore-patterns-label = Ignore patterns
+workspace-members = Members:
+workspace-member = { $name } ({ $kind }) at { $path }
+workspace-member-label = Member
+workspace-git = Git: { $summary }
+workspace-git-label = Git
+workspace-git-no-commits = no commits
+workspace-git-dirty = dirty
+workspace-git-clean = clean
+
+## config
+
+config-header = Current configuration:
+config-setting = Setting
+config-value = Value
+config-log-level = Log level
+config-output-format = Output format
+config-colors = Colors
+config-table-border = Table border
+config-update-checks = Update checks
+config-workspace-root = Workspace root
+config-log-file = Log file
+config-log-rotation = Log rotation
+config-watch-tasks = Watch tasks
+config-tasks = Tasks
+config-schedules = Schedules
+config-profile = Profile
+config-profiles = Profiles
+config-set = Set { $key } to { $value } in { $path }
+config-file-created = Created { $path }
+
+## doctor
+
+doctor-check = Check
+doctor-status = Status
+doctor-details = Details
+doctor-hint = Fix
+doctor-pass = pass
+doctor-warn = warn
+doctor-fail = fail
+doctor-summary = { $passed } passed, { $warnings ->
+        [one] 1 warning
+       *[other] { $warnings } warnings
+    }, { $failures } failed
+doctor-config = Config
+doctor-config-defaults = No config file found, using defaults
+doctor-config-valid = Valid: { $paths }
+doctor-config-invalid = { $path } is invalid: { $error }
+doctor-config-hint = Fix the file, or run `tram config` to see the expected settings
+doctor-workspace = Workspace
+doctor-workspace-found = { $path }
+doctor-workspace-missing = No workspace detected
+doctor-workspace-hint = Run tram from a project directory
+doctor-tool = Tool: { $tool }
+doctor-tool-missing = { $tool } not found on PATH
+doctor-tool-hint = Install { $tool }, which { $kind } projects need to build
+doctor-cache = Cache directory
+doctor-cache-unknown = Could not determine the cache directory
+doctor-cache-unwritable = { $path } is not writable: { $error }
+doctor-cache-hint = Set TRAM_CACHE_DIR to a writable directory
+doctor-completions = Shell completions
+doctor-completions-found = Installed for { $shell }
+doctor-completions-missing = Not installed for { $shell }
+doctor-completions-no-shell = Could not detect your shell
+doctor-completions-hint = Run `tram completions { $shell } --install`
+
+## cache
+cache-path = Cache directory: { $path }
+cache-entries = Entries: { $count } ({ $expired } expired)
+cache-size = Size: { $size }
+cache-path-label = Cache directory
+cache-entries-label = Entries
+cache-expired-label = Expired
+cache-size-label = Size
+cache-size-budget = Size: { $size } of { $max_size }
+cache-max-size-label = Size budget
+cache-unlimited = unlimited
+cache-orphaned = Also removed { $count ->
+        [one] 1 orphaned file
+       *[other] { $count } orphaned files
+    }
+cache-orphaned-dry-run = It would also remove { $count ->
+        [one] 1 orphaned file
+       *[other] { $count } orphaned files
+    }
+cache-removed = Removed { $count ->
+        [one] 1 entry
+       *[other] { $count } entries
+    } ({ $size }) from { $path }
+cache-dry-run = Dry run, nothing was removed. This would remove { $count ->
+        [one] 1 entry
+       *[other] { $count } entries
+    } ({ $size }) from { $path }
+
+## exit-codes
+exit-codes-code = Code
+exit-codes-name = Name
+exit-codes-description = Description
+
+## env
+env-settings = Settings:
+env-variables = Environment variables:
+env-paths = Paths:
+env-name = Name
+env-value = Value
+env-source = Source
+env-source-variable = environment
+env-not-set = (not set)
+env-config-file = Config file
+env-config-dir = Config directory
+env-data-dir = Data directory
+env-cache-dir = Cache directory
+env-workspace-root = Workspace root
+env-current-dir = Current directory
+env-executable = Executable
+env-effect = Effect
+env-var-overridden = { $value } (overridden by { $flag })
+
+## shell
+
+shell-welcome = tram { $version } interactive shell. Type a command, or `exit` to quit.
+shell-nested = Already in the tram shell
+shell-unbalanced-quotes = error: unbalanced quotes
+
+## batch
+
+batch-succeeded = ✓ { $command }
+batch-failed = ✗ { $command }: { $error }
+batch-summary = { $succeeded } of { $total } commands succeeded
+batch-unsupported = `{ $command }` can't run in batch mode
+batch-unbalanced-quotes = Unbalanced quotes
+batch-invalid-json = Invalid JSON command: { $error }
+batch-line = Line
+batch-command = Command
+batch-status = Status
+batch-status-ok = ok
+batch-status-failed = failed
+
+## daemon
+
+daemon-started = Daemon listening on { $socket }. Press Ctrl+C to stop.
+daemon-stopped = Daemon stopped.
+daemon-running = Daemon running (pid { $pid }) on { $socket }, up { $uptime }, { $requests ->
+        [one] 1 request
+       *[other] { $requests } requests
+    } served
+daemon-not-running = No daemon is running on { $socket }
+daemon-stopping = Daemon on { $socket } is stopping
+daemon-unsupported = `{ $command }` can't run in the daemon
+daemon-outside-workspace = { $path } is outside the daemon's workspace
+daemon-different-env = The caller's { $vars } differ from the daemon's
+daemon-status-label = Status
+daemon-pid-label = PID
+daemon-socket-label = Socket
+daemon-workspace-label = Workspace
+daemon-uptime-label = Uptime
+daemon-requests-label = Requests served
+daemon-state-running = running
+daemon-state-stopped = not running
+
+## plugins
+plugins-none = No WASM plugins installed. Plugins are loaded from: { $dirs }
+plugins-commands = commands: { $names }
+plugins-templates = templates: { $names }
+plugins-detector = workspace detector
+plugins-name = Name
+plugins-version = Version
+plugins-provides = Provides
+plugins-path = Path
+plugins-template-written = ✓ Generated { $template } template from { $plugin }: { $name }
+
+## auth
+auth-token-prompt = Token for { $name }
+auth-saved = ✓ Saved { $name } ({ $backend })
+auth-removed = ✓ Removed { $name } ({ $backend })
+auth-stored = { $name } is saved ({ $backend })
+auth-missing = No token named { $name } ({ $backend })
+auth-dry-run = Dry run, nothing was changed.
+
+## self-update
+
+self-update-done = ✓ Updated tram from { $from } to { $to }
+self-update-current = tram { $version } is already the latest version
+self-update-available = tram { $latest } is available (running { $current }); run `tram self-update` to install it
+
+## telemetry
+
+telemetry-notice = tram can record anonymous usage data to help improve it: the command name, whether it succeeded, how long it took, and the tram version and platform. Arguments, paths, and config values are never recorded. It stays off unless you run `tram telemetry on`.
+telemetry-on = Telemetry is on. Thanks for helping improve tram! Run `tram telemetry off` to stop.
+telemetry-off = Telemetry is off. Nothing will be recorded.
+telemetry-status = Telemetry: { $status }
+telemetry-status-undecided = off (not yet enabled; run `tram telemetry on` to opt in)
+telemetry-status-enabled = on
+telemetry-status-disabled = off
+telemetry-env-override = TRAM_TELEMETRY or DO_NOT_TRACK turns telemetry { $state } for this run
+telemetry-events = Recorded events: { $count } in { $path }
+telemetry-label = Telemetry
+telemetry-consent-label = Consent
+telemetry-override-label = Environment override
+telemetry-events-label = Recorded events
+telemetry-path-label = Events file
+
+## watch
+
+watch-started = Watch mode started. Press Ctrl+C to stop.
+schedules-started = { $count ->
+    [one] Running 1 scheduled task.
+   *[other] Running { $count } scheduled tasks.
+}
+watch-stopped = Watch mode stopped.
+watch-batch = { $count ->
+        [one] 1 file changed
+       *[other] { $count } files changed
+    }: { $paths }
+watch-more-paths = { $paths } and { $count } more
+watch-checks-running = Running checks...
+watch-checks-passed = ✓ Checks passed
+watch-checks-failed = ✗ Checks failed
+watch-task-running = Running { $name } for { $paths }
+watch-task-passed = ✓ { $name } passed in { $duration }
+watch-task-failed = ✗ { $name } failed with exit code { $code }
+watch-no-checks = No check commands for this project type; changes will only be logged
+watch-ui-keys = r re-run · 1-9 show one task · a show all · ↑↓ PgUp PgDn scroll · End follow · Ctrl+C quit
+watch-ui-not-terminal = Not a terminal; printing plain logs instead of the UI
+watch-ui-rerun = Re-running { $name }
+watch-ui-rerun-all = Re-running checks and tasks
+watch-ui-rerun-trigger = a re-run
+
+## dashboard
+dashboard-unavailable = this build has no dashboard; rebuild with `cargo install tram --features dashboard`
+dashboard-not-terminal = Not a terminal; printing plain logs instead of the dashboard
+dashboard-config-files = config files
+dashboard-config-reloaded = Configuration reloaded
+dashboard-request-declined = Declined `{ $command }`: { $reason }
+
+## run
+
+run-task = Task
+run-command = Command
+run-depends-on = Depends on
+run-no-tasks = No tasks defined. Add them under [tasks] in the config.
+run-task-starting = ▶ { $name }: { $command }
+run-status = Status
+run-duration = Duration
+run-passed = passed
+run-failed = failed
+run-failed-code = failed (exit code { $code })
+run-skipped = skipped
+run-summary = { $passed } passed, { $failed } failed, { $skipped } skipped in { $duration }
+
+## completions
+
+completions-detected-shell = Detected { $shell } from $SHELL
+completions-installed = Installed { $shell } completions to { $path }
+completions-no-shell = Could not detect your shell; name it: `tram completions <shell> --install`
+completions-unsupported-shell = Your shell, { $shell }, has no completion directory tram knows; name one of bash, zsh, fish, or powershell: `tram completions <shell> --install`
+completions-zsh-fpath = Make sure ~/.zsh/completions is on your fpath: add `fpath=(~/.zsh/completions $fpath)` before `compinit` in ~/.zshrc
+completions-powershell-registered = Added a line loading them to your PowerShell profile, { $path }; restart PowerShell to use them
+completions-powershell-already-registered = Your PowerShell profile, { $path }, already loads them
+completions-install-unsupported = --install isn't supported for { $shell }; redirect `tram completions { $shell }` to your profile instead
+
+## man
+
+man-installed = Installed { $count } man pages to { $path }
+man-install-dry-run = Would install { $count } man pages to { $path } (dry run, nothing was written)
+man-mandb-updated = Updated the man database
+man-install-view = Run `man tram` to read them
+man-install-no-dir = Couldn't find a man directory; pass --prefix
+man-install-windows = Windows has no `man`, so no pages were installed. Use `tram <command> --help`, or `tram docs` for Markdown pages; pass --prefix to install them under an MSYS2 or Cygwin root
+docs-generated = Wrote { $count } Markdown pages to { $path }
+docs-dry-run = Would write { $count } Markdown pages to { $path } (dry run, nothing was written)
+package-generated = Wrote { $format } packaging for { $version } to { $path }
+package-dry-run = Would write { $format } packaging for { $version } to { $path } (dry run, nothing was written)
+
+## prompts
+
+prompt-required = a value is required
+prompt-too-short = must be at least { $min } characters
+prompt-too-long = must be at most { $max } characters
+prompt-not-a-number = must be a whole number
+prompt-out-of-range = must be between { $min } and { $max }
+prompt-not-one-of = must be one of: { $values }
+prompt-invalid-identifier = use letters, digits, '-', and '_', starting with a letter
+prompt-password-mismatch = the passwords don't match
+migrated-data = Migrated data from version { $from } to { $to }.
+migrated-data-with-backup = Migrated data from version { $from } to { $to }; the previous files are in { $backup }.
@@ -1,39 +1,454 @@
 //! Logging and tracing setup for CLI applications.
 //!
 //! Provides utilities for setting up structured logging with appropriate
-//! formatting for different environments.
+//! formatting for different environments, optionally copying complete debug
+//! logs to a file for bug reports. The file is replaced each run unless a
+//! [`LogRotation`] keeps older logs. Logs to stderr can be paused while a
+//! full-screen view owns the terminal; the log file still gets them.
 
-use std::sync::Once;
-use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
+use crate::{AppResult, TramError};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, Once, OnceLock};
+use tracing::field::{Field, Visit};
+use tracing::{Level, Subscriber};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::{EnvFilter, Layer, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
 static INIT: Once = Once::new();
 
+/// The log file in use, once logging is initialized with one.
+static LOG_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Whether logs to stderr are being dropped, see [`pause_console_logs`].
+static CONSOLE_PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// How many rotated log files are kept besides the one being written.
+pub const KEPT_LOG_FILES: usize = 5;
+
+/// When the log file starts over. In config files and env vars it's written
+/// as `never`, `daily`, or a size such as `10MB`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum LogRotation {
+    /// Replace the file each run
+    #[default]
+    Never,
+    /// Append to `<file>.YYYY-MM-DD`, starting a new file each day (UTC)
+    Daily,
+    /// Append to the file, moving it to `<file>.1` (and `.1` to `.2`, ...)
+    /// before it grows past this many bytes
+    Size(u64),
+}
+
+impl std::str::FromStr for LogRotation {
+    type Err = String;
+
+    /// Parse `never`, `daily`, or a size such as `10MB`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "never" => Ok(LogRotation::Never),
+            "daily" => Ok(LogRotation::Daily),
+            _ => match crate::parse_bytes(s) {
+                Ok(bytes) if bytes > 0 => Ok(LogRotation::Size(bytes)),
+                _ => Err(format!(
+                    "Invalid log rotation '{}': expected never, daily, or a size such as 10MB",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for LogRotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogRotation::Never => write!(f, "never"),
+            LogRotation::Daily => write!(f, "daily"),
+            LogRotation::Size(bytes) => write!(f, "{}", bytes),
+        }
+    }
+}
+
+impl TryFrom<String> for LogRotation {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<LogRotation> for String {
+    fn from(rotation: LogRotation) -> Self {
+        rotation.to_string()
+    }
+}
+
+impl From<&str> for LogRotation {
+    fn from(s: &str) -> Self {
+        s.parse().unwrap_or_default()
+    }
+}
+
+/// How much output the user asked for with `-q` / `-v`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Only errors
+    Quiet,
+    #[default]
+    Normal,
+    /// `-v`: debug logs and extra detail
+    Verbose,
+    /// `-vv` or more: trace logs
+    Trace,
+}
+
+impl Verbosity {
+    /// Map `--quiet` and the number of `-v` flags to a verbosity.
+    pub fn from_flags(quiet: bool, verbose: u8) -> Self {
+        match (quiet, verbose) {
+            (true, _) => Verbosity::Quiet,
+            (false, 0) => Verbosity::Normal,
+            (false, 1) => Verbosity::Verbose,
+            (false, _) => Verbosity::Trace,
+        }
+    }
+
+    /// Log level implied by the flags, or `None` to keep the configured one.
+    pub fn log_level(self) -> Option<&'static str> {
+        match self {
+            Verbosity::Quiet => Some("error"),
+            Verbosity::Normal => None,
+            Verbosity::Verbose => Some("debug"),
+            Verbosity::Trace => Some("trace"),
+        }
+    }
+
+    pub fn is_quiet(self) -> bool {
+        self == Verbosity::Quiet
+    }
+
+    pub fn is_verbose(self) -> bool {
+        self >= Verbosity::Verbose
+    }
+}
+
 /// Initialize tracing with appropriate configuration for CLI applications.
+/// Logs go to stderr so stdout stays clean for command output.
 /// This function can be called multiple times safely - it will only initialize once.
-pub fn init_tracing(log_level: &str, use_json: bool) -> crate::AppResult<()> {
-    INIT.call_once(|| {
-        let filter = match EnvFilter::try_new(log_level) {
-            Ok(filter) => filter,
-            Err(_) => {
-                // Fall back to "info" level if the provided level is invalid
-                EnvFilter::try_new("info").unwrap_or_else(|_| EnvFilter::new("info"))
-            }
+pub fn init_tracing(log_level: &str, use_json: bool) -> AppResult<()> {
+    LoggingOptions::new(log_level).with_json(use_json).init()
+}
+
+/// Where logs go and how much of them.
+#[derive(Debug, Clone)]
+pub struct LoggingOptions {
+    level: String,
+    json: bool,
+    file: Option<PathBuf>,
+    rotation: LogRotation,
+}
+
+impl LoggingOptions {
+    /// Log to stderr at the given level (e.g. `info`, or a filter directive).
+    pub fn new(level: impl Into<String>) -> Self {
+        Self {
+            level: level.into(),
+            json: false,
+            file: None,
+            rotation: LogRotation::Never,
+        }
+    }
+
+    /// Write stderr logs as JSON lines.
+    pub fn with_json(mut self, json: bool) -> Self {
+        self.json = json;
+        self
+    }
+
+    /// Also write logs to a file, replacing its contents unless rotated.
+    /// The file gets debug logs (or trace, if asked for) whatever the
+    /// stderr level is.
+    pub fn with_file(mut self, file: Option<PathBuf>) -> Self {
+        self.file = file;
+        self
+    }
+
+    /// Keep older logs in the log file, starting a new one each day or
+    /// once it reaches a size.
+    pub fn with_rotation(mut self, rotation: LogRotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Install the global subscriber. Only the first call in a process takes
+    /// effect; later calls are ignored.
+    pub fn init(self) -> AppResult<()> {
+        if INIT.is_completed() {
+            return Ok(());
+        }
+
+        // Open the file up front so a bad path is reported, not swallowed
+        let file = match &self.file {
+            Some(path) => Some(open_log_writer(path, self.rotation)?),
+            None => None,
         };
 
-        let registry = tracing_subscriber::registry().with(filter);
+        INIT.call_once(|| {
+            // Fall back to "info" level if the provided level is invalid
+            let filter = EnvFilter::try_new(&self.level).unwrap_or_else(|_| EnvFilter::new("info"));
 
-        if use_json {
-            registry
-                .with(fmt::layer().json().with_target(true).with_level(true))
-                .init();
-        } else {
-            registry
-                .with(fmt::layer().with_target(false).with_level(true).compact())
+            let console = if self.json {
+                fmt::layer()
+                    .json()
+                    .with_target(true)
+                    .with_level(true)
+                    .with_ansi(false)
+                    .with_writer(ConsoleWriter::new)
+                    .boxed()
+            } else {
+                fmt::layer()
+                    .with_target(false)
+                    .with_level(true)
+                    .with_ansi(crate::color_enabled(crate::Stream::Stderr))
+                    .with_writer(ConsoleWriter::new)
+                    .compact()
+                    .boxed()
+            };
+
+            let (writer, path) = file.unzip();
+            let file_layer = writer.map(|writer| {
+                fmt::layer()
+                    .with_target(true)
+                    .with_level(true)
+                    .with_ansi(false)
+                    .with_writer(writer)
+                    .with_filter(EnvFilter::new(file_level(&self.level)))
+            });
+
+            tracing_subscriber::registry()
+                .with(console.with_filter(filter))
+                .with(file_layer)
+                .with(WarningRecorder.with_filter(LevelFilter::WARN))
                 .init();
+
+            if let Some(path) = path {
+                let _ = LOG_FILE.set(path);
+            }
+        });
+
+        Ok(())
+    }
+}
+
+/// Stop (or resume) writing logs to stderr, e.g. while a full-screen
+/// dashboard would be garbled by them. The log file is unaffected.
+pub fn pause_console_logs(paused: bool) {
+    CONSOLE_PAUSED.store(paused, Ordering::Relaxed);
+}
+
+/// Writes logs to stderr unless they're paused.
+struct ConsoleWriter(std::io::Stderr);
+
+impl ConsoleWriter {
+    fn new() -> Self {
+        Self(std::io::stderr())
+    }
+}
+
+impl Write for ConsoleWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if CONSOLE_PAUSED.load(Ordering::Relaxed) {
+            return Ok(buf.len());
         }
-    });
+        self.0.write(buf)
+    }
 
-    Ok(())
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Copies `warn!` messages into the run summary, whatever the console
+/// level, so `-q` runs still report them.
+struct WarningRecorder;
+
+impl<S: Subscriber> Layer<S> for WarningRecorder {
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+        if *event.metadata().level() != Level::WARN || !crate::summary_enabled() {
+            return;
+        }
+
+        let mut message = MessageVisitor(String::new());
+        event.record(&mut message);
+        crate::record_warning(message.0);
+    }
+}
+
+/// Reads an event's message field.
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.0 = value.to_string();
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{:?}", value);
+        }
+    }
+}
+
+/// The log file this run is writing to, if any.
+pub fn log_file() -> Option<&'static Path> {
+    LOG_FILE.get().map(PathBuf::as_path)
+}
+
+/// Level for the log file: at least debug, so it has everything a bug
+/// report needs.
+fn file_level(level: &str) -> &'static str {
+    if level.eq_ignore_ascii_case("trace") {
+        "trace"
+    } else {
+        "debug"
+    }
+}
+
+/// Open the log file at `path` for `rotation`, returning its writer and
+/// the file being written to.
+fn open_log_writer(path: &Path, rotation: LogRotation) -> AppResult<(BoxMakeWriter, PathBuf)> {
+    let open_failed = |e: &dyn std::fmt::Display| TramError::InvalidConfig {
+        message: format!("Failed to open log file {}: {}", path.display(), e),
+    };
+
+    match rotation {
+        LogRotation::Never => {
+            let file = open_log_file(path, false)?;
+            Ok((BoxMakeWriter::new(Mutex::new(file)), path.to_path_buf()))
+        }
+        LogRotation::Daily => {
+            let dir = log_dir(path)?;
+            let name = path
+                .file_name()
+                .ok_or_else(|| open_failed(&"not a file path"))?
+                .to_string_lossy()
+                .into_owned();
+            let appender = RollingFileAppender::builder()
+                .rotation(Rotation::DAILY)
+                .filename_prefix(&name)
+                .max_log_files(KEPT_LOG_FILES + 1)
+                .build(&dir)
+                .map_err(|e| open_failed(&e))?;
+
+            let today = chrono::Utc::now().format("%Y-%m-%d");
+            let current = dir.join(format!("{}.{}", name, today));
+            Ok((BoxMakeWriter::new(appender), current))
+        }
+        LogRotation::Size(max_bytes) => {
+            let file = SizeRotatingFile::open(path, max_bytes)?;
+            Ok((BoxMakeWriter::new(Mutex::new(file)), path.to_path_buf()))
+        }
+    }
+}
+
+/// The directory of a log file, created if it's missing.
+fn log_dir(path: &Path) -> AppResult<PathBuf> {
+    let Some(parent) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) else {
+        return Ok(PathBuf::from("."));
+    };
+
+    std::fs::create_dir_all(parent).map_err(|e| TramError::Io {
+        message: format!("Failed to create log directory {}: {}", parent.display(), e),
+    })?;
+    Ok(parent.to_path_buf())
+}
+
+fn open_log_file(path: &Path, append: bool) -> AppResult<File> {
+    log_dir(path)?;
+
+    OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)
+        .map_err(|e| {
+            TramError::InvalidConfig {
+                message: format!("Failed to open log file {}: {}", path.display(), e),
+            }
+            .into()
+        })
+}
+
+/// A log file appended to until it would pass `max_bytes`, then moved to
+/// `<file>.1`, shifting older files up and dropping the oldest.
+struct SizeRotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    file: File,
+    written: u64,
+}
+
+impl SizeRotatingFile {
+    fn open(path: &Path, max_bytes: u64) -> AppResult<Self> {
+        let file = open_log_file(path, true)?;
+        let written = file.metadata().map(|m| m.len()).unwrap_or_default();
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_bytes,
+            file,
+            written,
+        })
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+
+        let _ = std::fs::remove_file(self.rotated(KEPT_LOG_FILES));
+        for n in (1..KEPT_LOG_FILES).rev() {
+            let _ = std::fs::rename(self.rotated(n), self.rotated(n + 1));
+        }
+        std::fs::rename(&self.path, self.rotated(1))?;
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for SizeRotatingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
 }
 
 #[cfg(test)]
@@ -65,6 +480,72 @@ mod tests {
         assert!(result.is_ok(), "Should initialize tracing with JSON format");
     }
 
+    #[test]
+    fn test_log_file_gets_at_least_debug() {
+        assert_eq!(file_level("info"), "debug");
+        assert_eq!(file_level("error"), "debug");
+        assert_eq!(file_level("TRACE"), "trace");
+    }
+
+    #[test]
+    fn test_log_file_open_failure_is_reported() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let blocker = temp_dir.path().join("not-a-dir");
+        std::fs::write(&blocker, "").unwrap();
+
+        assert!(open_log_file(&blocker.join("tram.log"), false).is_err());
+    }
+
+    #[test]
+    fn test_parse_log_rotation() {
+        assert_eq!("never".parse(), Ok(LogRotation::Never));
+        assert_eq!("Daily".parse(), Ok(LogRotation::Daily));
+        assert_eq!("10MB".parse(), Ok(LogRotation::Size(10_000_000)));
+        assert!("0".parse::<LogRotation>().is_err());
+        assert!("weekly".parse::<LogRotation>().is_err());
+    }
+
+    #[test]
+    fn test_size_rotation_keeps_older_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("tram.log");
+        std::fs::write(&path, "old run\n").unwrap();
+
+        let mut file = SizeRotatingFile::open(&path, 16).unwrap();
+        file.write_all(b"first line\n").unwrap();
+        file.write_all(b"second line\n").unwrap();
+        file.flush().unwrap();
+
+        let read = |path: PathBuf| std::fs::read_to_string(path).unwrap();
+        assert_eq!(read(path.clone()), "second line\n");
+        assert_eq!(read(file.rotated(1)), "first line\n");
+        assert_eq!(read(file.rotated(2)), "old run\n");
+
+        for _ in 0..KEPT_LOG_FILES + 2 {
+            file.write_all(b"another line\n").unwrap();
+        }
+        assert!(file.rotated(KEPT_LOG_FILES).exists());
+        assert!(!file.rotated(KEPT_LOG_FILES + 1).exists());
+    }
+
+    #[test]
+    fn test_paused_console_logs_are_dropped() {
+        pause_console_logs(true);
+        assert_eq!(ConsoleWriter::new().write(b"hidden\n").unwrap(), 7);
+        pause_console_logs(false);
+        assert!(!CONSOLE_PAUSED.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_verbosity_from_flags() {
+        assert_eq!(Verbosity::from_flags(false, 0), Verbosity::Normal);
+        assert_eq!(Verbosity::from_flags(false, 1).log_level(), Some("debug"));
+        assert_eq!(Verbosity::from_flags(false, 3).log_level(), Some("trace"));
+        assert_eq!(Verbosity::from_flags(true, 2), Verbosity::Quiet);
+        assert!(Verbosity::Trace.is_verbose());
+        assert!(!Verbosity::Normal.is_verbose());
+    }
+
     #[test]
     fn test_tracing_logs_are_captured() {
         // This test verifies that tracing is working by checking if logs can be captured
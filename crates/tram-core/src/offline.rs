@@ -0,0 +1,51 @@
+//! Offline mode, a process-wide switch checked before any network access.
+//!
+//! `--offline` and the `offline` setting turn it on at startup with
+//! [`set_offline`]. Code that would reach the network calls
+//! [`ensure_online`] first, so it fails with a
+//! [`TramError::Offline`] naming what needed the network instead of
+//! hanging on a connection that isn't there. Work that can fall back to a
+//! cached or vendored copy should check [`is_offline`] and use it.
+
+use crate::{AppResult, TramError};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Fail if offline mode is on. `action` says what needed the network, e.g.
+/// `Request to https://example.com`.
+pub fn ensure_online(action: impl FnOnce() -> String) -> AppResult<()> {
+    if is_offline() {
+        return Err(TramError::Offline { action: action() }.into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ensure_online() {
+        assert!(ensure_online(|| "Checking".to_string()).is_ok());
+
+        set_offline(true);
+        let error = ensure_online(|| "Request to https://example.com".to_string()).unwrap_err();
+        set_offline(false);
+
+        assert_eq!(
+            error.to_string(),
+            "Request to https://example.com needs the network, but offline mode is on"
+        );
+        assert_eq!(crate::exit_code_for(&error), crate::ExitCode::Network);
+    }
+}
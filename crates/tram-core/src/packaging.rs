@@ -0,0 +1,627 @@
+//! OS package manifests generated from a CLI's metadata.
+//!
+//! [`Packager`] takes the name, version, and description from a clap
+//! command, plus the man pages and completion scripts generated for it, and
+//! renders what each package manager needs:
+//!
+//! - Homebrew: a formula, `<name>.rb`
+//! - Debian: a `dpkg-deb` staging tree, `<name>_<version>_amd64/`, with
+//!   `DEBIAN/control` and the man pages and completions in place; add the
+//!   binary as `usr/bin/<name>` and run `dpkg-deb --build`
+//! - RPM: a spec file, `<name>.spec`
+//! - Scoop: an app manifest, `<name>.json`
+//!
+//! The Homebrew formula and RPM spec install from a release archive holding
+//! the binary next to `man/` and `completions/` directories, which are
+//! rendered alongside them so they can be added to the archive.
+
+use crate::{AppResult, TramError};
+use clap::Command;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A package manager to generate a manifest for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageFormat {
+    Homebrew,
+    Deb,
+    Rpm,
+    Scoop,
+}
+
+impl PackageFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Homebrew => "homebrew",
+            Self::Deb => "deb",
+            Self::Rpm => "rpm",
+            Self::Scoop => "scoop",
+        }
+    }
+
+    /// The Rust target of the release archive the manifest installs from.
+    fn target(&self) -> &'static str {
+        match self {
+            Self::Homebrew => "aarch64-apple-darwin",
+            Self::Deb | Self::Rpm => "x86_64-unknown-linux-gnu",
+            Self::Scoop => "x86_64-pc-windows-msvc",
+        }
+    }
+
+    fn archive_extension(&self) -> &'static str {
+        match self {
+            Self::Scoop => "zip",
+            _ => "tar.gz",
+        }
+    }
+}
+
+/// A shell with completions installed by packages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl CompletionShell {
+    /// The script's file name in a release archive's `completions/`.
+    fn file_name(&self, name: &str) -> String {
+        match self {
+            Self::Bash => format!("{}.bash", name),
+            Self::Zsh => format!("_{}", name),
+            Self::Fish => format!("{}.fish", name),
+        }
+    }
+
+    /// Where Debian packages install the script, under `usr/share`.
+    fn deb_path(&self, name: &str) -> String {
+        match self {
+            Self::Bash => format!("bash-completion/completions/{}", name),
+            Self::Zsh => format!("zsh/vendor-completions/_{}", name),
+            Self::Fish => format!("fish/vendor_completions.d/{}.fish", name),
+        }
+    }
+
+    /// Where RPM packages install the script, under `%{_datadir}`.
+    fn rpm_path(&self, name: &str) -> String {
+        match self {
+            Self::Bash => format!("bash-completion/completions/{}", name),
+            Self::Zsh => format!("zsh/site-functions/_{}", name),
+            Self::Fish => format!("fish/vendor_completions.d/{}.fish", name),
+        }
+    }
+}
+
+/// A rendered file, relative to the output directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageFile {
+    pub path: PathBuf,
+    pub contents: Vec<u8>,
+}
+
+impl PackageFile {
+    fn new(path: impl Into<PathBuf>, contents: impl Into<Vec<u8>>) -> Self {
+        Self {
+            path: path.into(),
+            contents: contents.into(),
+        }
+    }
+}
+
+/// Generates package manifests for a CLI.
+#[derive(Debug, Clone)]
+pub struct Packager {
+    name: String,
+    version: String,
+    description: String,
+    homepage: Option<String>,
+    repository: Option<String>,
+    license: Option<String>,
+    maintainer: Option<String>,
+    url: Option<String>,
+    sha256: Option<String>,
+    /// Section 1 man pages, as file names and roff contents
+    man_pages: Vec<(String, Vec<u8>)>,
+    completions: Vec<(CompletionShell, Vec<u8>)>,
+}
+
+impl Packager {
+    /// A packager for `command`, taking its name, version, and about text.
+    pub fn new(command: &Command) -> Self {
+        Self {
+            name: command.get_name().to_string(),
+            version: command.get_version().unwrap_or("0.0.0").to_string(),
+            description: command
+                .get_about()
+                .map(|about| about.to_string().trim().trim_end_matches('.').to_string())
+                .unwrap_or_default(),
+            homepage: None,
+            repository: None,
+            license: None,
+            maintainer: None,
+            url: None,
+            sha256: None,
+            man_pages: Vec::new(),
+            completions: Vec::new(),
+        }
+    }
+
+    pub fn with_homepage(mut self, homepage: impl Into<String>) -> Self {
+        self.homepage = Some(homepage.into());
+        self
+    }
+
+    /// The repository releases are published to, for the default archive
+    /// URL: `<repository>/releases/download/v<version>/<name>-<version>-<target>.tar.gz`.
+    pub fn with_repository(mut self, repository: impl Into<String>) -> Self {
+        self.repository = Some(repository.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    /// An SPDX license identifier, e.g. `MIT`.
+    pub fn with_license(mut self, license: impl Into<String>) -> Self {
+        self.license = Some(license.into());
+        self
+    }
+
+    /// Who maintains the package, as `Name <email>`. Debian packages
+    /// require one.
+    pub fn with_maintainer(mut self, maintainer: impl Into<String>) -> Self {
+        self.maintainer = Some(maintainer.into());
+        self
+    }
+
+    /// The release archive's URL, with `{version}` replaced by the version.
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// The release archive's SHA-256 checksum. Without one, manifests that
+    /// can verify downloads leave the check out.
+    pub fn with_sha256(mut self, sha256: impl Into<String>) -> Self {
+        self.sha256 = Some(sha256.into().to_lowercase());
+        self
+    }
+
+    /// Install a section 1 man page, e.g. `app.1`.
+    pub fn with_man_page(mut self, file_name: impl Into<String>, contents: Vec<u8>) -> Self {
+        self.man_pages.push((file_name.into(), contents));
+        self
+    }
+
+    pub fn with_completion(mut self, shell: CompletionShell, script: Vec<u8>) -> Self {
+        self.completions.push((shell, script));
+        self
+    }
+
+    /// The files for `format`, relative to the output directory.
+    pub fn render(&self, format: PackageFormat) -> AppResult<Vec<PackageFile>> {
+        let files = match format {
+            PackageFormat::Homebrew => {
+                let mut files = self.archive_files();
+                files.push(PackageFile::new(
+                    format!("{}.rb", self.name),
+                    self.homebrew_formula(),
+                ));
+                files
+            }
+            PackageFormat::Deb => self.deb_tree()?,
+            PackageFormat::Rpm => {
+                let mut files = self.archive_files();
+                files.push(PackageFile::new(
+                    format!("{}.spec", self.name),
+                    self.rpm_spec(),
+                ));
+                files
+            }
+            PackageFormat::Scoop => vec![PackageFile::new(
+                format!("{}.json", self.name),
+                self.scoop_manifest()?,
+            )],
+        };
+
+        Ok(files)
+    }
+
+    /// Write the files for `format` into `dir`, creating it if needed, and
+    /// return the paths written.
+    pub fn write(&self, format: PackageFormat, dir: &Path) -> AppResult<Vec<PathBuf>> {
+        let write_error = |path: &Path, e: std::io::Error| TramError::Io {
+            message: format!("Failed to write package files to {}: {}", path.display(), e),
+        };
+
+        self.render(format)?
+            .into_iter()
+            .map(|file| {
+                let path = dir.join(&file.path);
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| write_error(parent, e))?;
+                }
+                fs::write(&path, file.contents).map_err(|e| write_error(&path, e))?;
+                Ok(path)
+            })
+            .collect()
+    }
+
+    /// The release archive's URL for `format`.
+    fn archive_url(&self, format: PackageFormat) -> String {
+        match &self.url {
+            Some(url) => url.replace("{version}", &self.version),
+            None => format!(
+                "{}/releases/download/v{}/{}-{}-{}.{}",
+                self.repository
+                    .as_deref()
+                    .or(self.homepage.as_deref())
+                    .unwrap_or_default(),
+                self.version,
+                self.name,
+                self.version,
+                format.target(),
+                format.archive_extension()
+            ),
+        }
+    }
+
+    /// `man/` and `completions/`, as they sit next to the binary in a
+    /// release archive.
+    fn archive_files(&self) -> Vec<PackageFile> {
+        let man = self.man_pages.iter().map(|(file_name, contents)| {
+            PackageFile::new(format!("man/{}", file_name), contents.clone())
+        });
+        let completions = self.completions.iter().map(|(shell, script)| {
+            PackageFile::new(
+                format!("completions/{}", shell.file_name(&self.name)),
+                script.clone(),
+            )
+        });
+
+        man.chain(completions).collect()
+    }
+
+    fn homebrew_formula(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "class {} < Formula", class_name(&self.name));
+        let _ = writeln!(out, "  desc \"{}\"", ruby_string(&self.description));
+        if let Some(homepage) = &self.homepage {
+            let _ = writeln!(out, "  homepage \"{}\"", ruby_string(homepage));
+        }
+        let _ = writeln!(
+            out,
+            "  url \"{}\"",
+            ruby_string(&self.archive_url(PackageFormat::Homebrew))
+        );
+        if let Some(sha256) = &self.sha256 {
+            let _ = writeln!(out, "  sha256 \"{}\"", sha256);
+        }
+        let _ = writeln!(out, "  version \"{}\"", ruby_string(&self.version));
+        if let Some(license) = &self.license {
+            let _ = writeln!(out, "  license \"{}\"", ruby_string(license));
+        }
+
+        let _ = writeln!(out, "\n  def install");
+        let _ = writeln!(out, "    bin.install \"{}\"", ruby_string(&self.name));
+        if !self.man_pages.is_empty() {
+            let _ = writeln!(out, "    man1.install Dir[\"man/*.1\"]");
+        }
+        for (shell, _) in &self.completions {
+            let file = shell.file_name(&self.name);
+            let _ = match shell {
+                CompletionShell::Bash => writeln!(
+                    out,
+                    "    bash_completion.install \"completions/{}\" => \"{}\"",
+                    ruby_string(&file),
+                    ruby_string(&self.name)
+                ),
+                CompletionShell::Zsh => writeln!(
+                    out,
+                    "    zsh_completion.install \"completions/{}\"",
+                    ruby_string(&file)
+                ),
+                CompletionShell::Fish => writeln!(
+                    out,
+                    "    fish_completion.install \"completions/{}\"",
+                    ruby_string(&file)
+                ),
+            };
+        }
+        let _ = writeln!(out, "  end");
+
+        let _ = writeln!(out, "\n  test do");
+        let _ = writeln!(
+            out,
+            "    assert_match version.to_s, shell_output(\"#{{bin}}/{} --version\")",
+            ruby_string(&self.name)
+        );
+        let _ = writeln!(out, "  end");
+        let _ = writeln!(out, "end");
+        out
+    }
+
+    fn deb_tree(&self) -> AppResult<Vec<PackageFile>> {
+        let maintainer = self
+            .maintainer
+            .as_deref()
+            .ok_or_else(|| TramError::InvalidInput {
+                input: "maintainer".to_string(),
+                message: "Debian packages need a maintainer, as `Name <email>`".to_string(),
+            })?;
+        let version = package_version(&self.version);
+        let root = format!("{}_{}_amd64", self.name, version);
+
+        let mut control = String::new();
+        let _ = writeln!(control, "Package: {}", self.name);
+        let _ = writeln!(control, "Version: {}", version);
+        let _ = writeln!(control, "Section: utils");
+        let _ = writeln!(control, "Priority: optional");
+        let _ = writeln!(control, "Architecture: amd64");
+        let _ = writeln!(control, "Maintainer: {}", maintainer);
+        if let Some(homepage) = &self.homepage {
+            let _ = writeln!(control, "Homepage: {}", homepage);
+        }
+        let _ = writeln!(control, "Description: {}", self.description);
+
+        let mut files = vec![PackageFile::new(
+            format!("{}/DEBIAN/control", root),
+            control,
+        )];
+        for (file_name, contents) in &self.man_pages {
+            files.push(PackageFile::new(
+                format!("{}/usr/share/man/man1/{}", root, file_name),
+                contents.clone(),
+            ));
+        }
+        for (shell, script) in &self.completions {
+            files.push(PackageFile::new(
+                format!("{}/usr/share/{}", root, shell.deb_path(&self.name)),
+                script.clone(),
+            ));
+        }
+
+        Ok(files)
+    }
+
+    fn rpm_spec(&self) -> String {
+        let name = &self.name;
+        let mut out = String::new();
+        let _ = writeln!(out, "Name:           {}", name);
+        let _ = writeln!(out, "Version:        {}", package_version(&self.version));
+        let _ = writeln!(out, "Release:        1%{{?dist}}");
+        let _ = writeln!(out, "Summary:        {}", self.description);
+        if let Some(license) = &self.license {
+            let _ = writeln!(out, "License:        {}", license);
+        }
+        if let Some(homepage) = &self.homepage {
+            let _ = writeln!(out, "URL:            {}", homepage);
+        }
+        let _ = writeln!(
+            out,
+            "Source0:        {}",
+            self.archive_url(PackageFormat::Rpm)
+        );
+        if let Some(maintainer) = &self.maintainer {
+            let _ = writeln!(out, "Packager:       {}", maintainer);
+        }
+        let _ = writeln!(out, "\n%description\n{}", self.description);
+        let _ = writeln!(out, "\n%prep\n%setup -q -c");
+        let _ = writeln!(out, "\n%build");
+
+        let _ = writeln!(out, "\n%install");
+        let _ = writeln!(
+            out,
+            "install -Dm755 {} %{{buildroot}}%{{_bindir}}/{}",
+            name, name
+        );
+        if !self.man_pages.is_empty() {
+            let _ = writeln!(
+                out,
+                "install -Dm644 -t %{{buildroot}}%{{_mandir}}/man1 man/*.1"
+            );
+        }
+        for (shell, _) in &self.completions {
+            let _ = writeln!(
+                out,
+                "install -Dm644 completions/{} %{{buildroot}}%{{_datadir}}/{}",
+                shell.file_name(name),
+                shell.rpm_path(name)
+            );
+        }
+
+        let _ = writeln!(out, "\n%files");
+        let _ = writeln!(out, "%{{_bindir}}/{}", name);
+        if !self.man_pages.is_empty() {
+            let _ = writeln!(out, "%{{_mandir}}/man1/*");
+        }
+        for (shell, _) in &self.completions {
+            let _ = writeln!(out, "%{{_datadir}}/{}", shell.rpm_path(name));
+        }
+        out
+    }
+
+    fn scoop_manifest(&self) -> AppResult<String> {
+        let url = self.archive_url(PackageFormat::Scoop);
+        let mut architecture = serde_json::json!({ "url": url });
+        if let Some(sha256) = &self.sha256 {
+            architecture["hash"] = sha256.clone().into();
+        }
+
+        let mut manifest = serde_json::json!({
+            "version": self.version,
+            "description": self.description,
+            "architecture": { "64bit": architecture },
+            "bin": format!("{}.exe", self.name),
+        });
+        if let Some(homepage) = &self.homepage {
+            manifest["homepage"] = homepage.clone().into();
+        }
+        if let Some(license) = &self.license {
+            manifest["license"] = license.clone().into();
+        }
+        // Let `scoop checkver` find new releases when the URL follows them
+        if self.url.is_none()
+            && let Some(repository) = &self.repository
+        {
+            manifest["checkver"] = serde_json::json!({ "github": repository });
+            manifest["autoupdate"] = serde_json::json!({
+                "architecture": {
+                    "64bit": {
+                        "url": url.replace(&self.version, "$version"),
+                    }
+                }
+            });
+        }
+
+        serde_json::to_string_pretty(&manifest)
+            .map(|json| format!("{}\n", json))
+            .map_err(|e| {
+                TramError::InvalidInput {
+                    input: "scoop".to_string(),
+                    message: format!("failed to serialize manifest: {}", e),
+                }
+                .into()
+            })
+    }
+}
+
+/// Debian and RPM versions sort `~` before the release, as semver sorts
+/// pre-releases, and RPM doesn't allow `-`: `1.0.0-beta.1` becomes
+/// `1.0.0~beta.1`.
+fn package_version(version: &str) -> String {
+    version.replacen('-', "~", 1)
+}
+
+/// `my-cli` becomes `MyCli`, the formula's class name.
+fn class_name(name: &str) -> String {
+    name.split(['-', '_', '.'])
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn ruby_string(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('#', "\\#")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packager() -> Packager {
+        let command = Command::new("my-cli")
+            .version("1.2.0-beta.1")
+            .about("Does \"things\".");
+
+        Packager::new(&command)
+            .with_repository("https://github.com/example/my-cli/")
+            .with_homepage("https://example.com")
+            .with_license("MIT")
+            .with_man_page("my-cli.1", b".TH my-cli 1".to_vec())
+            .with_completion(CompletionShell::Bash, b"complete".to_vec())
+            .with_completion(CompletionShell::Zsh, b"#compdef".to_vec())
+    }
+
+    fn paths(files: &[PackageFile]) -> Vec<String> {
+        files
+            .iter()
+            .map(|file| file.path.display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_homebrew_formula() {
+        let files = packager()
+            .with_sha256("ABC123")
+            .render(PackageFormat::Homebrew)
+            .unwrap();
+
+        assert_eq!(
+            paths(&files),
+            [
+                "man/my-cli.1",
+                "completions/my-cli.bash",
+                "completions/_my-cli",
+                "my-cli.rb"
+            ]
+        );
+
+        let formula = String::from_utf8(files[3].contents.clone()).unwrap();
+        assert!(formula.starts_with("class MyCli < Formula\n"));
+        assert!(formula.contains("desc \"Does \\\"things\\\"\""));
+        assert!(formula.contains(
+            "url \"https://github.com/example/my-cli/releases/download/v1.2.0-beta.1/my-cli-1.2.0-beta.1-aarch64-apple-darwin.tar.gz\""
+        ));
+        assert!(formula.contains("sha256 \"abc123\""));
+        assert!(
+            formula.contains("bash_completion.install \"completions/my-cli.bash\" => \"my-cli\"")
+        );
+        assert!(formula.contains("man1.install Dir[\"man/*.1\"]"));
+    }
+
+    #[test]
+    fn test_deb_tree_needs_a_maintainer() {
+        assert!(packager().render(PackageFormat::Deb).is_err());
+
+        let files = packager()
+            .with_maintainer("Jane Doe <jane@example.com>")
+            .render(PackageFormat::Deb)
+            .unwrap();
+
+        assert_eq!(
+            paths(&files),
+            [
+                "my-cli_1.2.0~beta.1_amd64/DEBIAN/control",
+                "my-cli_1.2.0~beta.1_amd64/usr/share/man/man1/my-cli.1",
+                "my-cli_1.2.0~beta.1_amd64/usr/share/bash-completion/completions/my-cli",
+                "my-cli_1.2.0~beta.1_amd64/usr/share/zsh/vendor-completions/_my-cli",
+            ]
+        );
+
+        let control = String::from_utf8(files[0].contents.clone()).unwrap();
+        assert!(control.contains("Version: 1.2.0~beta.1\n"));
+        assert!(control.contains("Maintainer: Jane Doe <jane@example.com>\n"));
+        assert!(control.contains("Description: Does \"things\"\n"));
+    }
+
+    #[test]
+    fn test_rpm_spec() {
+        let files = packager()
+            .with_url("https://downloads.example.com/{version}/my-cli.tar.gz")
+            .render(PackageFormat::Rpm)
+            .unwrap();
+        let spec = String::from_utf8(files.last().unwrap().contents.clone()).unwrap();
+
+        assert!(spec.contains("Version:        1.2.0~beta.1\n"));
+        assert!(spec.contains(
+            "Source0:        https://downloads.example.com/1.2.0-beta.1/my-cli.tar.gz\n"
+        ));
+        assert!(spec.contains(
+            "install -Dm644 completions/_my-cli %{buildroot}%{_datadir}/zsh/site-functions/_my-cli\n"
+        ));
+        assert!(spec.contains("%{_datadir}/bash-completion/completions/my-cli\n"));
+    }
+
+    #[test]
+    fn test_scoop_manifest() {
+        let files = packager().render(PackageFormat::Scoop).unwrap();
+        assert_eq!(paths(&files), ["my-cli.json"]);
+
+        let manifest: serde_json::Value = serde_json::from_slice(&files[0].contents).unwrap();
+        assert_eq!(manifest["bin"], "my-cli.exe");
+        assert_eq!(manifest["license"], "MIT");
+        assert!(manifest["architecture"]["64bit"].get("hash").is_none());
+        assert_eq!(
+            manifest["autoupdate"]["architecture"]["64bit"]["url"],
+            "https://github.com/example/my-cli/releases/download/v$version/my-cli-$version-x86_64-pc-windows-msvc.zip"
+        );
+    }
+}
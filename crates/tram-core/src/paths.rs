@@ -0,0 +1,262 @@
+//! Path expansion and platform directory helpers.
+//!
+//! Resolves `~` and environment variables in user-supplied paths and locates
+//! the per-user config, data, and cache directories following each
+//! platform's conventions (XDG on Linux, `Library` on macOS, `AppData` on
+//! Windows).
+
+use std::path::{Path, PathBuf};
+
+/// Application name used for the per-user directories.
+pub const APP_NAME: &str = "tram";
+
+/// Environment variable overriding the config directory.
+pub const CONFIG_DIR_ENV: &str = "TRAM_CONFIG_DIR";
+
+/// Environment variable overriding the system-wide config directory.
+pub const SYSTEM_CONFIG_DIR_ENV: &str = "TRAM_SYSTEM_CONFIG_DIR";
+
+/// Environment variable overriding the data directory.
+pub const DATA_DIR_ENV: &str = "TRAM_DATA_DIR";
+
+/// Environment variable overriding the cache directory.
+pub const CACHE_DIR_ENV: &str = "TRAM_CACHE_DIR";
+
+/// The current user's home directory.
+pub fn home_dir() -> Option<PathBuf> {
+    let var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+
+    std::env::var_os(var)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// The current working directory, falling back to `.` if it can't be read.
+pub fn current_dir() -> PathBuf {
+    std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
+}
+
+/// Expand a leading `~` and `$VAR` / `${VAR}` references in a path.
+///
+/// Unknown variables are left untouched so the resulting error message
+/// still shows what the user typed.
+pub fn expand_path(path: impl AsRef<Path>) -> PathBuf {
+    let raw = path.as_ref().to_string_lossy();
+    let expanded = expand_env_vars(&raw);
+
+    if expanded == "~" {
+        return home_dir().unwrap_or_else(|| PathBuf::from(expanded));
+    }
+
+    if let Some(rest) = expanded
+        .strip_prefix("~/")
+        .or_else(|| expanded.strip_prefix("~\\"))
+        && let Some(home) = home_dir()
+    {
+        return home.join(rest);
+    }
+
+    PathBuf::from(expanded)
+}
+
+/// Per-user configuration directory (e.g. `~/.config/tram`).
+pub fn config_dir() -> Option<PathBuf> {
+    if let Some(dir) = env_dir(CONFIG_DIR_ENV) {
+        return Some(dir);
+    }
+
+    let base = if cfg!(windows) {
+        env_dir("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        home_dir().map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env_dir("XDG_CONFIG_HOME").or_else(|| home_dir().map(|home| home.join(".config")))
+    };
+
+    base.map(|base| base.join(APP_NAME))
+}
+
+/// System-wide configuration directory shared by all users (`/etc/tram`,
+/// or `%ProgramData%\tram` on Windows).
+pub fn system_config_dir() -> Option<PathBuf> {
+    if let Some(dir) = env_dir(SYSTEM_CONFIG_DIR_ENV) {
+        return Some(dir);
+    }
+
+    if cfg!(windows) {
+        env_dir("ProgramData").map(|base| base.join(APP_NAME))
+    } else {
+        Some(PathBuf::from("/etc").join(APP_NAME))
+    }
+}
+
+/// Per-user data directory for persistent state (e.g. `~/.local/share/tram`).
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(dir) = env_dir(DATA_DIR_ENV) {
+        return Some(dir);
+    }
+
+    let base = if cfg!(windows) {
+        env_dir("LOCALAPPDATA")
+    } else if cfg!(target_os = "macos") {
+        home_dir().map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env_dir("XDG_DATA_HOME")
+            .or_else(|| home_dir().map(|home| home.join(".local").join("share")))
+    };
+
+    base.map(|base| base.join(APP_NAME))
+}
+
+/// Per-user cache directory for disposable artifacts (e.g. `~/.cache/tram`).
+pub fn cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = env_dir(CACHE_DIR_ENV) {
+        return Some(dir);
+    }
+
+    if cfg!(windows) {
+        env_dir("LOCALAPPDATA").map(|base| base.join(APP_NAME).join("cache"))
+    } else if cfg!(target_os = "macos") {
+        home_dir().map(|home| home.join("Library").join("Caches").join(APP_NAME))
+    } else {
+        env_dir("XDG_CACHE_HOME")
+            .or_else(|| home_dir().map(|home| home.join(".cache")))
+            .map(|base| base.join(APP_NAME))
+    }
+}
+
+/// Directories listed in `PATH`, in order.
+pub fn search_path() -> Vec<PathBuf> {
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect())
+        .unwrap_or_default()
+}
+
+/// Find an executable on `PATH`, like `which`.
+pub fn find_executable(name: &str) -> Option<PathBuf> {
+    let file_name = format!("{}{}", name, std::env::consts::EXE_SUFFIX);
+
+    search_path()
+        .into_iter()
+        .map(|dir| dir.join(&file_name))
+        .find(|path| is_executable(path))
+}
+
+/// Whether a path is a file that can be run: executable by someone on Unix,
+/// any file elsewhere.
+#[cfg(unix)]
+pub fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+/// Whether a path is a file that can be run: executable by someone on Unix,
+/// any file elsewhere.
+#[cfg(not(unix))]
+pub fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Read a directory from an environment variable, expanding `~`.
+fn env_dir(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var)
+        .filter(|value| !value.is_empty())
+        .map(expand_path)
+}
+
+/// Replace `$VAR` and `${VAR}` with their values from the environment.
+fn expand_env_vars(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        if c != '$' {
+            output.push(c);
+            continue;
+        }
+
+        let braced = matches!(chars.peek(), Some((_, '{')));
+        if braced {
+            chars.next();
+        }
+
+        let mut name = String::new();
+        while let Some(&(_, next)) = chars.peek() {
+            if next.is_ascii_alphanumeric() || next == '_' {
+                name.push(next);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+
+        let closed = !braced || matches!(chars.peek(), Some((_, '}')));
+        if braced && closed {
+            chars.next();
+        }
+
+        match std::env::var(&name) {
+            Ok(value) if !name.is_empty() && closed => output.push_str(&value),
+            _ => {
+                // Leave unknown or malformed references as written
+                let end = chars.peek().map(|(i, _)| *i).unwrap_or(input.len());
+                output.push_str(&input[start..end]);
+            }
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_tilde() {
+        let home = home_dir().expect("tests require a home directory");
+
+        assert_eq!(expand_path("~"), home);
+        assert_eq!(expand_path("~/projects/app"), home.join("projects/app"));
+        assert_eq!(expand_path("/abs/path"), PathBuf::from("/abs/path"));
+        assert_eq!(expand_path("relative/~"), PathBuf::from("relative/~"));
+    }
+
+    #[test]
+    fn test_expand_env_vars() {
+        let home = std::env::var(if cfg!(windows) { "USERPROFILE" } else { "HOME" }).unwrap();
+        let var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+
+        assert_eq!(
+            expand_env_vars(&format!("${}/a", var)),
+            format!("{}/a", home)
+        );
+        assert_eq!(
+            expand_env_vars(&format!("${{{}}}/b", var)),
+            format!("{}/b", home)
+        );
+        assert_eq!(
+            expand_env_vars("$TRAM_SURELY_UNDEFINED_VAR/c"),
+            "$TRAM_SURELY_UNDEFINED_VAR/c"
+        );
+        assert_eq!(expand_env_vars("cost: $"), "cost: $");
+    }
+
+    #[test]
+    fn test_platform_dirs_end_with_app_name() {
+        for dir in [config_dir(), data_dir(), system_config_dir()]
+            .into_iter()
+            .flatten()
+        {
+            assert!(
+                dir.ends_with(APP_NAME),
+                "{} should end with tram",
+                dir.display()
+            );
+        }
+
+        assert!(cache_dir().is_some());
+    }
+}
@@ -0,0 +1,141 @@
+//! External plugin subcommands.
+//!
+//! Like cargo, an unknown subcommand `foo` runs a `<app>-foo` executable found
+//! on `PATH`, so teams can extend a CLI without recompiling it. Plugins get
+//! the remaining arguments, inherit the environment and standard streams
+//! along with any variables the CLI adds, and their exit code becomes the
+//! CLI's exit code.
+
+use crate::paths::{is_executable, search_path};
+use crate::signals::status_exit_code;
+use crate::{AppResult, TramError};
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// An executable that extends the CLI with a subcommand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plugin {
+    /// Subcommand name, e.g. `foo` for `tram-foo`
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Find the executable for a plugin subcommand.
+pub fn find_plugin(app_name: &str, name: &str) -> Option<PathBuf> {
+    crate::find_executable(&format!("{}-{}", app_name, name))
+}
+
+/// Every plugin on `PATH`, sorted by name. When several directories provide
+/// the same plugin, the first one on `PATH` wins, matching [`find_plugin`].
+pub fn discover_plugins(app_name: &str) -> Vec<Plugin> {
+    let prefix = format!("{}-", app_name);
+    let mut plugins = BTreeMap::new();
+
+    for dir in search_path() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let file_name = entry.file_name();
+            let Some(name) = file_name
+                .to_str()
+                .and_then(|file_name| file_name.strip_prefix(&prefix))
+                .and_then(|name| name.strip_suffix(std::env::consts::EXE_SUFFIX))
+            else {
+                continue;
+            };
+
+            let path = entry.path();
+            if name.is_empty() || plugins.contains_key(name) || !is_executable(&path) {
+                continue;
+            }
+
+            plugins.insert(name.to_string(), path);
+        }
+    }
+
+    plugins
+        .into_iter()
+        .map(|(name, path)| Plugin { name, path })
+        .collect()
+}
+
+/// Run a plugin to completion with extra environment variables, returning
+/// its exit code.
+pub fn run_plugin<K, V>(
+    path: &Path,
+    args: &[OsString],
+    envs: impl IntoIterator<Item = (K, V)>,
+) -> AppResult<i32>
+where
+    K: AsRef<OsStr>,
+    V: AsRef<OsStr>,
+{
+    let status = Command::new(path)
+        .args(args)
+        .envs(envs)
+        .status()
+        .map_err(|e| TramError::Plugin {
+            message: format!("Failed to run {}: {}", path.display(), e),
+        })?;
+
+    Ok(status_exit_code(status))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    fn write_script(dir: &Path, name: &str, body: &str, mode: u32) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_run_plugin_forwards_args_and_exit_code() {
+        let temp_dir = TempDir::new().unwrap();
+        let script = write_script(
+            temp_dir.path(),
+            "tram-fail",
+            r#"[ "$1" = "--flag" ] && exit 7; exit 1"#,
+            0o755,
+        );
+
+        let no_env: [(&str, &str); 0] = [];
+        assert_eq!(run_plugin(&script, &["--flag".into()], no_env).unwrap(), 7);
+        assert_eq!(run_plugin(&script, &[], no_env).unwrap(), 1);
+
+        let killed = write_script(temp_dir.path(), "tram-killed", "kill -9 $$", 0o755);
+        assert_eq!(run_plugin(&killed, &[], no_env).unwrap(), 137);
+
+        let env = write_script(
+            temp_dir.path(),
+            "tram-env",
+            r#"[ "$TRAM_TEST_VAR" = "set" ] && exit 5; exit 1"#,
+            0o755,
+        );
+        assert_eq!(
+            run_plugin(&env, &[], [("TRAM_TEST_VAR", "set")]).unwrap(),
+            5
+        );
+    }
+
+    #[test]
+    fn test_executable_check() {
+        let temp_dir = TempDir::new().unwrap();
+        let runnable = write_script(temp_dir.path(), "tram-yes", "true", 0o755);
+        let plain = write_script(temp_dir.path(), "tram-no", "true", 0o644);
+
+        assert!(is_executable(&runnable));
+        assert!(!is_executable(&plain));
+        assert!(!is_executable(temp_dir.path()));
+    }
+}
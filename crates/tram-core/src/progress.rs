@@ -0,0 +1,181 @@
+//! Progress indicators for long-running commands.
+//!
+//! A [`Progress`] counts named steps. With a [`ProgressDisplay`], such as
+//! `tram_ui::Spinner` on an interactive stderr, each step is drawn there;
+//! without one, each step is logged at `info` level instead, so piped output
+//! and logs never contain cursor movement. Every step is also emitted as a
+//! `progress` event.
+
+use crate::emit_progress;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use tracing::info;
+
+/// Somewhere to draw a [`Progress`], e.g. a spinner.
+pub trait ProgressDisplay: Debug + Send + Sync {
+    /// Show the current step's line.
+    fn show(&self, line: &str);
+
+    /// Stop drawing and remove what was drawn.
+    fn clear(&self);
+}
+
+#[derive(Debug, Default)]
+struct State {
+    message: String,
+    current: u64,
+    total: Option<u64>,
+    display: Option<Box<dyn ProgressDisplay>>,
+    finished: bool,
+}
+
+impl State {
+    fn line(&self) -> String {
+        match self.total {
+            Some(total) => format!("[{}/{}] {}", self.current, total, self.message),
+            None => self.message.clone(),
+        }
+    }
+
+    fn finish(&mut self) {
+        if let Some(display) = &self.display
+            && !self.finished
+            && self.current > 0
+        {
+            display.clear();
+        }
+        self.finished = true;
+    }
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+}
+
+impl Drop for Shared {
+    fn drop(&mut self) {
+        if let Ok(state) = self.state.get_mut() {
+            state.finish();
+        }
+    }
+}
+
+/// A progress indicator made of named steps. Clones share the same
+/// indicator, which is cleared when the last clone is dropped.
+#[derive(Debug, Clone)]
+pub struct Progress {
+    shared: Arc<Shared>,
+}
+
+impl Progress {
+    /// Start an indicator, with `total` steps if the count is known.
+    pub fn new(total: Option<u64>) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State {
+                    total,
+                    ..State::default()
+                }),
+            }),
+        }
+    }
+
+    /// Draw the steps on `display` instead of logging them.
+    pub fn with_display(self, display: impl ProgressDisplay + 'static) -> Self {
+        if let Ok(mut state) = self.shared.state.lock() {
+            state.display = Some(Box::new(display));
+        }
+        self
+    }
+
+    /// Move on to the next step.
+    pub fn step(&self, message: impl Into<String>) {
+        let message = message.into();
+        let Ok(mut state) = self.shared.state.lock() else {
+            return;
+        };
+
+        state.current += 1;
+        state.message = message.clone();
+        emit_progress(message, Some(state.current), state.total);
+
+        let line = state.line();
+        match &state.display {
+            Some(display) if !state.finished => display.show(&line),
+            Some(_) => {}
+            None => info!("{}", line),
+        }
+    }
+
+    /// Steps taken so far.
+    pub fn current(&self) -> u64 {
+        self.shared
+            .state
+            .lock()
+            .map(|state| state.current)
+            .unwrap_or_default()
+    }
+
+    /// Stop and clear the display, before printing the command's result.
+    pub fn finish(&self) {
+        if let Ok(mut state) = self.shared.state.lock() {
+            state.finish();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Default, Clone)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl ProgressDisplay for Recorder {
+        fn show(&self, line: &str) {
+            self.0.lock().unwrap().push(line.to_string());
+        }
+
+        fn clear(&self) {
+            self.0.lock().unwrap().push("<clear>".to_string());
+        }
+    }
+
+    #[test]
+    fn test_steps_are_counted() {
+        let progress = Progress::new(Some(2));
+        progress.step("Writing files");
+        progress.clone().step("Initializing git");
+
+        assert_eq!(progress.current(), 2);
+        assert_eq!(
+            progress.shared.state.lock().unwrap().line(),
+            "[2/2] Initializing git"
+        );
+    }
+
+    #[test]
+    fn test_steps_are_drawn_on_the_display_until_finished() {
+        let recorder = Recorder::default();
+        let progress = Progress::new(Some(2)).with_display(recorder.clone());
+
+        progress.step("Writing files");
+        progress.finish();
+        progress.step("Initializing git");
+        drop(progress);
+
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            ["[1/2] Writing files", "<clear>"]
+        );
+    }
+
+    #[test]
+    fn test_unused_display_is_not_cleared() {
+        let recorder = Recorder::default();
+        drop(Progress::new(None).with_display(recorder.clone()));
+
+        assert!(recorder.0.lock().unwrap().is_empty());
+    }
+}
@@ -1,14 +1,21 @@
 //! Project initialization utilities for CLI applications.
 //!
-//! Provides functionality for creating new projects with templates
-//! and interactive prompts.
-
-use crate::{AppResult, TramError};
-use std::fs;
+//! Provides functionality for creating new projects with templates,
+//! optionally with a git repository and a CI workflow. Each project records
+//! the files it was created with in a [`ProjectManifest`], so `tram upgrade`
+//! can later bring it up to date with newer templates.
+
+use crate::{
+    AppResult, ChangeSet, FileChange, FileChangeKind, LicenseKind, Progress, ProjectManifest,
+    TramError,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// Supported project types for initialization.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum InitProjectType {
     Rust,
     NodeJs,
@@ -25,79 +32,165 @@ pub struct InitConfig {
     pub path: PathBuf,
     pub project_type: InitProjectType,
     pub description: Option<String>,
+    /// Author recorded in the project manifest, e.g. `Jane Doe <jane@example.com>`
     pub author: Option<String>,
+    /// License recorded in the project manifest and, for built-in licenses,
+    /// written to `LICENSE`
+    pub license: Option<LicenseKind>,
+    /// Copyright year for the `LICENSE` file
+    pub year: i32,
+    /// Initialize a git repository with a `.gitignore`
+    pub git: bool,
+    /// Add a GitHub Actions workflow that builds and tests the project
+    pub ci: bool,
 }
 
 /// Service for creating new projects.
-pub struct ProjectInitializer;
+#[derive(Debug, Clone, Default)]
+pub struct ProjectInitializer {
+    dry_run: bool,
+    progress: Option<Progress>,
+}
 
 impl ProjectInitializer {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Report the files that would be created without writing anything.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
     }
 
-    /// Create a new project with the given configuration.
+    /// Report each phase of project creation on a progress indicator.
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// The number of progress steps `create_project` takes for `config`.
+    pub fn steps(config: &InitConfig) -> u64 {
+        1 + u64::from(config.ci) + u64::from(config.git)
+    }
+
+    fn step(&self, message: String) {
+        if let Some(progress) = &self.progress {
+            progress.step(message);
+        }
+    }
+
+    /// Create a new project with the given configuration, returning every
+    /// directory and file created (or, in a dry run, that would be).
     /// This is the main behavior users expect when initializing a project.
-    pub fn create_project(&self, config: &InitConfig) -> AppResult<()> {
+    pub fn create_project(&self, config: &InitConfig) -> AppResult<Vec<FileChange>> {
         // Behavior: Should create project directory
         if config.path.exists() {
-            return Err(TramError::InvalidConfig {
-                message: format!("Directory {} already exists", config.path.display()),
+            return Err(TramError::Conflict {
+                path: config.path.display().to_string(),
             }
             .into());
         }
 
-        fs::create_dir_all(&config.path).map_err(|e| TramError::InvalidConfig {
-            message: format!("Failed to create project directory: {}", e),
-        })?;
+        let mut changes = ChangeSet::new(self.dry_run);
+        changes.create_dir_all(&config.path)?;
 
         // Behavior: Should create appropriate project files based on type
-        self.create_project_files(config)?;
+        self.step(format!("Writing {} project files", config.name));
+        self.create_project_files(config, &mut changes)?;
 
-        Ok(())
+        if config.ci {
+            self.step("Adding CI workflow".to_string());
+            self.create_ci_workflow(config, &mut changes)?;
+        }
+
+        if config.git {
+            self.step("Initializing git repository".to_string());
+            self.write_gitignore(config, &mut changes)?;
+            self.init_git(config, &mut changes)?;
+        }
+
+        let manifest = ProjectManifest::new(config, self.render(config)?);
+        changes.write_file(&ProjectManifest::path(&config.path), manifest.to_json()?)?;
+
+        Ok(changes.into_changes())
     }
 
-    /// Create the basic project structure based on project type.
-    fn create_project_files(&self, config: &InitConfig) -> AppResult<()> {
-        match config.project_type {
-            InitProjectType::Rust => self.create_rust_project(config),
-            InitProjectType::NodeJs => self.create_nodejs_project(config),
-            InitProjectType::Python => self.create_python_project(config),
-            InitProjectType::Go => self.create_go_project(config),
-            InitProjectType::Java => self.create_java_project(config),
-            InitProjectType::Generic => self.create_generic_project(config),
+    /// The files the current templates produce for `config`, keyed by their
+    /// path relative to the project root, without writing anything.
+    pub fn render(&self, config: &InitConfig) -> AppResult<BTreeMap<String, String>> {
+        let mut changes = ChangeSet::new(true);
+        self.create_project_files(config, &mut changes)?;
+        if config.ci {
+            self.create_ci_workflow(config, &mut changes)?;
+        }
+        if config.git {
+            self.write_gitignore(config, &mut changes)?;
         }
+
+        Ok(changes
+            .changes()
+            .iter()
+            .filter_map(|change| {
+                let contents = changes.planned_contents(&change.path)?;
+                let relative = change.path.strip_prefix(&config.path).ok()?;
+                Some((
+                    relative.to_string_lossy().replace('\\', "/"),
+                    String::from_utf8_lossy(contents).into_owned(),
+                ))
+            })
+            .collect())
+    }
+
+    /// Create the basic project structure based on project type, and its
+    /// license.
+    fn create_project_files(&self, config: &InitConfig, changes: &mut ChangeSet) -> AppResult<()> {
+        match config.project_type {
+            InitProjectType::Rust => self.create_rust_project(config, changes),
+            InitProjectType::NodeJs => self.create_nodejs_project(config, changes),
+            InitProjectType::Python => self.create_python_project(config, changes),
+            InitProjectType::Go => self.create_go_project(config, changes),
+            InitProjectType::Java => self.create_java_project(config, changes),
+            InitProjectType::Generic => self.create_generic_project(config, changes),
+        }?;
+
+        self.write_license(config, changes)
     }
 
-    fn create_rust_project(&self, config: &InitConfig) -> AppResult<()> {
+    fn create_rust_project(&self, config: &InitConfig, changes: &mut ChangeSet) -> AppResult<()> {
         // Create Cargo.toml
         let cargo_toml = format!(
             r#"[package]
 name = "{}"
 version = "0.1.0"
 edition = "2021"
-{}
-
+{}{}{}
 [dependencies]
 "#,
             config.name,
             config
                 .description
                 .as_ref()
-                .map(|d| format!("description = \"{}\"", d))
+                .map(|d| format!("description = \"{}\"\n", d))
+                .unwrap_or_default(),
+            config
+                .author
+                .as_ref()
+                .map(|a| format!("authors = [\"{}\"]\n", a))
+                .unwrap_or_default(),
+            config
+                .license
+                .as_ref()
+                .map(|l| format!("license = \"{}\"\n", l))
                 .unwrap_or_default()
         );
 
         let cargo_path = config.path.join("Cargo.toml");
-        fs::write(cargo_path, cargo_toml).map_err(|e| TramError::InvalidConfig {
-            message: format!("Failed to write Cargo.toml: {}", e),
-        })?;
+        changes.write_file(&cargo_path, &cargo_toml)?;
 
         // Create src directory and main.rs
         let src_dir = config.path.join("src");
-        fs::create_dir(&src_dir).map_err(|e| TramError::InvalidConfig {
-            message: format!("Failed to create src directory: {}", e),
-        })?;
+        changes.create_dir_all(&src_dir)?;
 
         let main_rs = r#"fn main() {
     println!("Hello, world!");
@@ -105,20 +198,18 @@ edition = "2021"
 "#;
 
         let main_path = src_dir.join("main.rs");
-        fs::write(main_path, main_rs).map_err(|e| TramError::InvalidConfig {
-            message: format!("Failed to write main.rs: {}", e),
-        })?;
+        changes.write_file(&main_path, main_rs)?;
 
         Ok(())
     }
 
-    fn create_nodejs_project(&self, config: &InitConfig) -> AppResult<()> {
+    fn create_nodejs_project(&self, config: &InitConfig, changes: &mut ChangeSet) -> AppResult<()> {
         // Create package.json
         let package_json = format!(
             r#"{{
   "name": "{}",
   "version": "1.0.0",
-  "description": "{}",
+  "description": "{}",{}{}
   "main": "index.js",
   "scripts": {{
     "start": "node index.js"
@@ -126,27 +217,33 @@ edition = "2021"
 }}
 "#,
             config.name,
-            config.description.as_deref().unwrap_or("")
+            config.description.as_deref().unwrap_or(""),
+            config
+                .author
+                .as_ref()
+                .map(|a| format!("\n  \"author\": \"{}\",", a))
+                .unwrap_or_default(),
+            config
+                .license
+                .as_ref()
+                .map(|l| format!("\n  \"license\": \"{}\",", l))
+                .unwrap_or_default()
         );
 
         let package_path = config.path.join("package.json");
-        fs::write(package_path, package_json).map_err(|e| TramError::InvalidConfig {
-            message: format!("Failed to write package.json: {}", e),
-        })?;
+        changes.write_file(&package_path, &package_json)?;
 
         // Create index.js
         let index_js = r#"console.log('Hello, world!');
 "#;
 
         let index_path = config.path.join("index.js");
-        fs::write(index_path, index_js).map_err(|e| TramError::InvalidConfig {
-            message: format!("Failed to write index.js: {}", e),
-        })?;
+        changes.write_file(&index_path, index_js)?;
 
         Ok(())
     }
 
-    fn create_python_project(&self, config: &InitConfig) -> AppResult<()> {
+    fn create_python_project(&self, config: &InitConfig, changes: &mut ChangeSet) -> AppResult<()> {
         // Create pyproject.toml
         let pyproject_toml = format!(
             r#"[build-system]
@@ -157,20 +254,34 @@ build-backend = "hatchling.build"
 name = "{}"
 version = "0.0.1"
 description = "{}"
-
+{}{}
 [project.scripts]
 {} = "{}:main"
 "#,
             config.name,
             config.description.as_deref().unwrap_or(""),
+            config
+                .author
+                .as_deref()
+                .map(|a| match split_author(a) {
+                    (name, Some(email)) => format!(
+                        "authors = [{{ name = \"{}\", email = \"{}\" }}]\n",
+                        name, email
+                    ),
+                    (name, None) => format!("authors = [{{ name = \"{}\" }}]\n", name),
+                })
+                .unwrap_or_default(),
+            config
+                .license
+                .as_ref()
+                .map(|l| format!("license = \"{}\"\n", l))
+                .unwrap_or_default(),
             config.name,
             config.name.replace("-", "_")
         );
 
         let pyproject_path = config.path.join("pyproject.toml");
-        fs::write(pyproject_path, pyproject_toml).map_err(|e| TramError::InvalidConfig {
-            message: format!("Failed to write pyproject.toml: {}", e),
-        })?;
+        changes.write_file(&pyproject_path, &pyproject_toml)?;
 
         // Create main module
         let main_py = r#"def main():
@@ -183,21 +294,17 @@ if __name__ == "__main__":
         let main_path = config
             .path
             .join(format!("{}.py", config.name.replace("-", "_")));
-        fs::write(main_path, main_py).map_err(|e| TramError::InvalidConfig {
-            message: format!("Failed to write main module: {}", e),
-        })?;
+        changes.write_file(&main_path, main_py)?;
 
         Ok(())
     }
 
-    fn create_go_project(&self, config: &InitConfig) -> AppResult<()> {
+    fn create_go_project(&self, config: &InitConfig, changes: &mut ChangeSet) -> AppResult<()> {
         // Create go.mod
         let go_mod = format!("module {}\n\ngo 1.21\n", config.name);
 
         let go_mod_path = config.path.join("go.mod");
-        fs::write(go_mod_path, go_mod).map_err(|e| TramError::InvalidConfig {
-            message: format!("Failed to write go.mod: {}", e),
-        })?;
+        changes.write_file(&go_mod_path, &go_mod)?;
 
         // Create main.go
         let main_go = r#"package main
@@ -210,20 +317,22 @@ func main() {
 "#;
 
         let main_path = config.path.join("main.go");
-        fs::write(main_path, main_go).map_err(|e| TramError::InvalidConfig {
-            message: format!("Failed to write main.go: {}", e),
-        })?;
+        changes.write_file(&main_path, main_go)?;
 
         Ok(())
     }
 
-    fn create_java_project(&self, _config: &InitConfig) -> AppResult<()> {
+    fn create_java_project(&self, _config: &InitConfig, _changes: &mut ChangeSet) -> AppResult<()> {
         // For simplicity, create a basic project structure
         // In a real implementation, this would use Maven/Gradle templates
         Ok(())
     }
 
-    fn create_generic_project(&self, config: &InitConfig) -> AppResult<()> {
+    fn create_generic_project(
+        &self,
+        config: &InitConfig,
+        changes: &mut ChangeSet,
+    ) -> AppResult<()> {
         // Create a simple README
         let readme = format!(
             "# {}\n\n{}\n",
@@ -232,23 +341,140 @@ func main() {
         );
 
         let readme_path = config.path.join("README.md");
-        fs::write(readme_path, readme).map_err(|e| TramError::InvalidConfig {
-            message: format!("Failed to write README.md: {}", e),
-        })?;
+        changes.write_file(&readme_path, &readme)?;
+
+        Ok(())
+    }
+
+    /// Write `.github/workflows/ci.yml` with the project type's build and
+    /// test steps.
+    fn create_ci_workflow(&self, config: &InitConfig, changes: &mut ChangeSet) -> AppResult<()> {
+        let (setup, steps): (&[&str], &[&str]) = match config.project_type {
+            InitProjectType::Rust => (
+                &["uses: dtolnay/rust-toolchain@stable"],
+                &["cargo build --locked", "cargo test"],
+            ),
+            InitProjectType::NodeJs => (
+                &["uses: actions/setup-node@v4\n        with:\n          node-version: 20"],
+                &["npm install", "npm test --if-present"],
+            ),
+            InitProjectType::Python => (
+                &[
+                    "uses: actions/setup-python@v5\n        with:\n          python-version: \"3.12\"",
+                ],
+                &["pip install ."],
+            ),
+            InitProjectType::Go => (
+                &["uses: actions/setup-go@v5\n        with:\n          go-version: \"1.21\""],
+                &["go build ./...", "go test ./..."],
+            ),
+            InitProjectType::Java => (
+                &[
+                    "uses: actions/setup-java@v4\n        with:\n          distribution: temurin\n          java-version: 21",
+                ],
+                &["mvn --batch-mode verify"],
+            ),
+            InitProjectType::Generic => (&[], &[]),
+        };
+
+        let mut workflow = String::from(
+            "name: CI\n\non:\n  push:\n  pull_request:\n\njobs:\n  build:\n    runs-on: ubuntu-latest\n    steps:\n      - uses: actions/checkout@v4\n",
+        );
+        for step in setup {
+            workflow.push_str(&format!("      - {}\n", step));
+        }
+        for step in steps {
+            workflow.push_str(&format!("      - run: {}\n", step));
+        }
+
+        let workflow_path = config.path.join(".github/workflows/ci.yml");
+        changes.write_file(&workflow_path, &workflow)
+    }
+
+    /// Write a `.gitignore` for the project type.
+    fn write_gitignore(&self, config: &InitConfig, changes: &mut ChangeSet) -> AppResult<()> {
+        let ignored = match config.project_type {
+            InitProjectType::Rust => "/target\n",
+            InitProjectType::NodeJs => "node_modules/\n",
+            InitProjectType::Python => "__pycache__/\n.venv/\n*.egg-info/\n",
+            InitProjectType::Go => "",
+            InitProjectType::Java => "target/\n",
+            InitProjectType::Generic => "",
+        };
+
+        if !ignored.is_empty() {
+            changes.write_file(&config.path.join(".gitignore"), ignored)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write `LICENSE` for a built-in license. The copyright holder is the
+    /// author or, without one, the project's authors.
+    fn write_license(&self, config: &InitConfig, changes: &mut ChangeSet) -> AppResult<()> {
+        let author = config
+            .author
+            .clone()
+            .unwrap_or_else(|| format!("The {} authors", config.name));
+
+        if let Some(text) = config
+            .license
+            .as_ref()
+            .and_then(|license| license.text(config.year, &author))
+        {
+            changes.write_file(&config.path.join("LICENSE"), text)?;
+        }
+
+        Ok(())
+    }
+
+    /// Run `git init` in the project directory.
+    fn init_git(&self, config: &InitConfig, changes: &mut ChangeSet) -> AppResult<()> {
+        changes.record(config.path.join(".git"), FileChangeKind::CreateDir);
+        if changes.is_dry_run() {
+            return Ok(());
+        }
+
+        let status = std::process::Command::new("git")
+            .args(["init", "--quiet"])
+            .current_dir(&config.path)
+            .stdin(std::process::Stdio::null())
+            .status()
+            .map_err(|e| TramError::Process {
+                message: format!("Failed to run `git init`: {}", e),
+            })?;
+
+        if !status.success() {
+            return Err(TramError::Process {
+                message: format!(
+                    "`git init` failed with exit code {}",
+                    status.code().unwrap_or(1)
+                ),
+            }
+            .into());
+        }
 
         Ok(())
     }
 }
 
-impl Default for ProjectInitializer {
-    fn default() -> Self {
-        Self::new()
+/// Split `Name <email>` into the name and email, for manifests that record
+/// them separately.
+fn split_author(author: &str) -> (&str, Option<&str>) {
+    match author
+        .trim()
+        .strip_suffix('>')
+        .and_then(|rest| rest.split_once('<'))
+    {
+        Some((name, email)) => (name.trim(), Some(email.trim())),
+        None => (author.trim(), None),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
     use tempfile::TempDir;
 
     #[test]
@@ -262,6 +488,10 @@ mod tests {
             project_type: InitProjectType::Rust,
             description: Some("A test project".to_string()),
             author: None,
+            license: None,
+            year: 2026,
+            git: false,
+            ci: false,
         };
 
         let initializer = ProjectInitializer::new();
@@ -294,6 +524,10 @@ mod tests {
             project_type: InitProjectType::NodeJs,
             description: Some("A test Node.js project".to_string()),
             author: None,
+            license: None,
+            year: 2026,
+            git: false,
+            ci: false,
         };
 
         let initializer = ProjectInitializer::new();
@@ -325,6 +559,10 @@ mod tests {
             project_type: InitProjectType::Rust,
             description: None,
             author: None,
+            license: None,
+            year: 2026,
+            git: false,
+            ci: false,
         };
 
         let initializer = ProjectInitializer::new();
@@ -332,4 +570,84 @@ mod tests {
 
         assert!(result.is_err(), "Should fail when directory already exists");
     }
+
+    #[test]
+    fn test_license_and_ci_workflow() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path().join("licensed");
+
+        let config = InitConfig {
+            name: "licensed".to_string(),
+            path: project_path.clone(),
+            project_type: InitProjectType::Rust,
+            description: None,
+            author: Some("Jane Doe <jane@example.com>".to_string()),
+            license: Some(LicenseKind::Mit),
+            year: 2026,
+            git: false,
+            ci: true,
+        };
+
+        ProjectInitializer::new().create_project(&config).unwrap();
+
+        let cargo_toml = fs::read_to_string(project_path.join("Cargo.toml")).unwrap();
+        assert!(cargo_toml.contains("license = \"MIT\"\n"));
+        assert!(cargo_toml.contains("authors = [\"Jane Doe <jane@example.com>\"]\n"));
+
+        let license = fs::read_to_string(project_path.join("LICENSE")).unwrap();
+        assert!(license.contains("Copyright (c) 2026 Jane Doe <jane@example.com>\n"));
+
+        let workflow = fs::read_to_string(project_path.join(".github/workflows/ci.yml")).unwrap();
+        assert!(workflow.contains("      - uses: actions/checkout@v4\n"));
+        assert!(workflow.contains("      - run: cargo test\n"));
+        assert!(!project_path.join(".gitignore").exists());
+    }
+
+    #[test]
+    fn test_split_author() {
+        assert_eq!(
+            split_author("Jane Doe <jane@example.com>"),
+            ("Jane Doe", Some("jane@example.com"))
+        );
+        assert_eq!(split_author(" Jane Doe "), ("Jane Doe", None));
+    }
+
+    #[test]
+    fn test_dry_run_reports_without_writing() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path().join("planned");
+
+        let config = InitConfig {
+            name: "planned".to_string(),
+            path: project_path.clone(),
+            project_type: InitProjectType::Rust,
+            description: None,
+            author: None,
+            license: None,
+            year: 2026,
+            git: true,
+            ci: false,
+        };
+
+        let changes = ProjectInitializer::new()
+            .with_dry_run(true)
+            .create_project(&config)
+            .unwrap();
+        let paths: Vec<PathBuf> = changes.into_iter().map(|change| change.path).collect();
+
+        assert_eq!(
+            paths,
+            [
+                project_path.clone(),
+                project_path.join("Cargo.toml"),
+                project_path.join("src"),
+                project_path.join("src/main.rs"),
+                project_path.join(".gitignore"),
+                project_path.join(".git"),
+                project_path.join(".tram"),
+                project_path.join(".tram/project.json"),
+            ]
+        );
+        assert!(!project_path.exists());
+    }
 }
@@ -0,0 +1,282 @@
+//! Secret lookup through pluggable providers.
+//!
+//! Config values and command arguments can reference secrets as
+//! `secret://name` (search every provider in order) or
+//! `secret://provider/name` (ask one provider). Tram ships providers for
+//! environment variables, files (e.g. Docker or Kubernetes secret mounts),
+//! and the `tram-keyring` crate adds the OS keychain. Anything else, such
+//! as Vault, plugs in by implementing [`SecretProvider`].
+
+use crate::{AppResult, TramError};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+/// Prefix marking a value as a secret reference.
+pub const SECRET_SCHEME: &str = "secret://";
+
+/// A source of secret values.
+pub trait SecretProvider: Send + Sync {
+    /// Short name used in `secret://<name>/<key>` references.
+    fn name(&self) -> &str;
+
+    /// Look up a secret, returning `None` if this provider doesn't have it.
+    fn get(&self, key: &str) -> AppResult<Option<String>>;
+}
+
+/// Secrets stored in environment variables.
+///
+/// `secret://db-password` reads `TRAM_SECRET_DB_PASSWORD` with the default
+/// prefix.
+#[derive(Debug, Clone)]
+pub struct EnvSecretProvider {
+    prefix: String,
+}
+
+impl EnvSecretProvider {
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+        }
+    }
+
+    /// Environment variable a key is read from.
+    pub fn var_name(&self, key: &str) -> String {
+        let key: String = key
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_uppercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+
+        format!("{}{}", self.prefix, key)
+    }
+}
+
+impl Default for EnvSecretProvider {
+    fn default() -> Self {
+        Self::new("TRAM_SECRET_")
+    }
+}
+
+impl SecretProvider for EnvSecretProvider {
+    fn name(&self) -> &str {
+        "env"
+    }
+
+    fn get(&self, key: &str) -> AppResult<Option<String>> {
+        Ok(std::env::var(self.var_name(key)).ok())
+    }
+}
+
+/// Secrets stored as one file per key in a directory, such as `/run/secrets`.
+#[derive(Debug, Clone)]
+pub struct FileSecretProvider {
+    dir: PathBuf,
+}
+
+impl FileSecretProvider {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: crate::expand_path(dir.into()),
+        }
+    }
+}
+
+impl SecretProvider for FileSecretProvider {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    fn get(&self, key: &str) -> AppResult<Option<String>> {
+        // Keys are plain file names; never let a reference escape the directory
+        if key.is_empty() || key.contains(['/', '\\']) || key.starts_with('.') {
+            return Err(TramError::Secret {
+                message: format!("Invalid secret name '{}'", key),
+            }
+            .into());
+        }
+
+        let path = self.dir.join(key);
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let value = fs::read_to_string(&path).map_err(|e| TramError::Secret {
+            message: format!("Failed to read {}: {}", path.display(), e),
+        })?;
+
+        // Secret files are usually written with a trailing newline
+        Ok(Some(value.trim_end_matches(['\n', '\r']).to_string()))
+    }
+}
+
+/// Resolves `secret://` references against an ordered list of providers.
+#[derive(Default)]
+pub struct SecretResolver {
+    providers: Vec<Box<dyn SecretProvider>>,
+}
+
+impl SecretResolver {
+    /// A resolver with no providers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in providers: environment variables.
+    pub fn with_defaults() -> Self {
+        Self::new().with_provider(EnvSecretProvider::default())
+    }
+
+    /// Add a provider. Providers are searched in the order they were added.
+    pub fn with_provider(mut self, provider: impl SecretProvider + 'static) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+
+    /// Names of the registered providers, in search order.
+    pub fn provider_names(&self) -> Vec<&str> {
+        self.providers.iter().map(|p| p.name()).collect()
+    }
+
+    /// Look up a secret by name, optionally scoped as `provider/name`.
+    pub fn get(&self, reference: &str) -> AppResult<String> {
+        let scoped = reference.split_once('/').and_then(|(provider, key)| {
+            self.providers
+                .iter()
+                .find(|p| p.name() == provider)
+                .map(|p| (p, key))
+        });
+
+        let value = match scoped {
+            Some((provider, key)) => provider.get(key)?,
+            None => self.search(reference)?,
+        };
+
+        value.ok_or_else(|| {
+            TramError::Secret {
+                message: format!(
+                    "Secret '{}' not found (searched: {})",
+                    reference,
+                    self.provider_names().join(", ")
+                ),
+            }
+            .into()
+        })
+    }
+
+    fn search(&self, key: &str) -> AppResult<Option<String>> {
+        for provider in &self.providers {
+            if let Some(value) = provider.get(key)? {
+                return Ok(Some(value));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Resolve a value if it is a `secret://` reference, otherwise return it unchanged.
+    pub fn resolve(&self, value: &str) -> AppResult<String> {
+        match value.strip_prefix(SECRET_SCHEME) {
+            Some(reference) => self.get(reference),
+            None => Ok(value.to_string()),
+        }
+    }
+
+    /// Replace every `secret://` string in a JSON value, recursively.
+    pub fn resolve_value(&self, value: &mut Value) -> AppResult<()> {
+        match value {
+            Value::String(text) if text.starts_with(SECRET_SCHEME) => {
+                *text = self.resolve(text)?;
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.resolve_value(item)?;
+                }
+            }
+            Value::Object(map) => {
+                for item in map.values_mut() {
+                    self.resolve_value(item)?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether a value is a `secret://` reference.
+pub fn is_secret_reference(value: &str) -> bool {
+    value.starts_with(SECRET_SCHEME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    struct MapProvider(&'static str, HashMap<&'static str, &'static str>);
+
+    impl SecretProvider for MapProvider {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn get(&self, key: &str) -> AppResult<Option<String>> {
+            Ok(self.1.get(key).map(|v| v.to_string()))
+        }
+    }
+
+    #[test]
+    fn test_resolve_searches_providers_in_order() {
+        let resolver = SecretResolver::new()
+            .with_provider(MapProvider("vault", HashMap::from([("db", "from-vault")])))
+            .with_provider(MapProvider(
+                "other",
+                HashMap::from([("db", "from-other"), ("api", "from-other")]),
+            ));
+
+        assert_eq!(resolver.resolve("secret://db").unwrap(), "from-vault");
+        assert_eq!(resolver.resolve("secret://api").unwrap(), "from-other");
+        assert_eq!(resolver.resolve("secret://other/db").unwrap(), "from-other");
+        assert_eq!(resolver.resolve("plain").unwrap(), "plain");
+
+        let err = resolver.resolve("secret://missing").unwrap_err();
+        assert!(err.to_string().contains("Secret 'missing' not found"));
+    }
+
+    #[test]
+    fn test_file_provider_and_value_interpolation() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("token"), "s3cr3t\n").unwrap();
+
+        let resolver =
+            SecretResolver::new().with_provider(FileSecretProvider::new(temp_dir.path()));
+
+        let mut config = json!({
+            "registry": { "token": "secret://file/token", "url": "https://example.com" },
+            "list": ["secret://token"]
+        });
+        resolver.resolve_value(&mut config).unwrap();
+
+        assert_eq!(config["registry"]["token"], "s3cr3t");
+        assert_eq!(config["registry"]["url"], "https://example.com");
+        assert_eq!(config["list"][0], "s3cr3t");
+
+        assert!(resolver.resolve("secret://file/../token").is_err());
+    }
+
+    #[test]
+    fn test_env_var_name() {
+        let provider = EnvSecretProvider::default();
+        assert_eq!(provider.var_name("db-password"), "TRAM_SECRET_DB_PASSWORD");
+        assert_eq!(provider.var_name("api.key"), "TRAM_SECRET_API_KEY");
+    }
+}
@@ -0,0 +1,215 @@
+//! Termination signals.
+//!
+//! Once a [`Signals`] listener exists, Ctrl+C, SIGTERM, and SIGHUP no longer
+//! kill the process outright: the listener reports them so the command can
+//! run the session's shutdown phase and exit with 128 plus the signal number,
+//! as shells report for a process the signal killed. On Windows, Ctrl+C and
+//! Ctrl+Break count as SIGINT, and closing the console window, logging off,
+//! or shutting down count as SIGTERM.
+
+use crate::{AppResult, ExitCode, TramError};
+use tokio::sync::mpsc;
+
+/// A signal asking the process to stop, or with SIGHUP to reload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// SIGHUP: the terminal closed, or a request to reload the config
+    Hangup,
+    /// SIGINT: Ctrl+C
+    Interrupt,
+    /// SIGTERM: a request to stop, e.g. from `kill` or a service manager
+    Terminate,
+}
+
+impl Signal {
+    /// The POSIX signal number.
+    pub fn number(self) -> u8 {
+        match self {
+            Signal::Hangup => 1,
+            Signal::Interrupt => 2,
+            Signal::Terminate => 15,
+        }
+    }
+
+    /// The exit code for a command the signal stopped.
+    pub fn exit_code(self) -> ExitCode {
+        match self {
+            Signal::Hangup => ExitCode::HangUp,
+            Signal::Interrupt => ExitCode::Interrupted,
+            Signal::Terminate => ExitCode::Terminated,
+        }
+    }
+}
+
+impl std::fmt::Display for Signal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Signal::Hangup => write!(f, "SIGHUP"),
+            Signal::Interrupt => write!(f, "SIGINT"),
+            Signal::Terminate => write!(f, "SIGTERM"),
+        }
+    }
+}
+
+/// Exit code for a finished child process, using the shell's `128 + signal`
+/// convention for processes killed by a signal.
+pub fn status_exit_code(status: std::process::ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+
+    status.code().unwrap_or(1)
+}
+
+/// Listens for termination signals until dropped.
+#[derive(Debug)]
+pub struct Signals {
+    #[cfg(unix)]
+    hangup: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    interrupt: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+
+    #[cfg(windows)]
+    ctrl_c: tokio::signal::windows::CtrlC,
+    #[cfg(windows)]
+    ctrl_break: tokio::signal::windows::CtrlBreak,
+    #[cfg(windows)]
+    ctrl_close: tokio::signal::windows::CtrlClose,
+    #[cfg(windows)]
+    ctrl_logoff: tokio::signal::windows::CtrlLogoff,
+    #[cfg(windows)]
+    ctrl_shutdown: tokio::signal::windows::CtrlShutdown,
+}
+
+impl Signals {
+    /// Start listening. Must be called inside a Tokio runtime.
+    pub fn new() -> AppResult<Self> {
+        let error = |e: std::io::Error| TramError::Process {
+            message: format!("Failed to listen for signals: {}", e),
+        };
+
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{SignalKind, signal};
+
+            Ok(Self {
+                hangup: signal(SignalKind::hangup()).map_err(error)?,
+                interrupt: signal(SignalKind::interrupt()).map_err(error)?,
+                terminate: signal(SignalKind::terminate()).map_err(error)?,
+            })
+        }
+
+        #[cfg(windows)]
+        {
+            use tokio::signal::windows;
+
+            Ok(Self {
+                ctrl_c: windows::ctrl_c().map_err(error)?,
+                ctrl_break: windows::ctrl_break().map_err(error)?,
+                ctrl_close: windows::ctrl_close().map_err(error)?,
+                ctrl_logoff: windows::ctrl_logoff().map_err(error)?,
+                ctrl_shutdown: windows::ctrl_shutdown().map_err(error)?,
+            })
+        }
+    }
+
+    /// Wait for the next signal.
+    pub async fn recv(&mut self) -> Signal {
+        #[cfg(unix)]
+        {
+            tokio::select! {
+                _ = self.hangup.recv() => Signal::Hangup,
+                _ = self.interrupt.recv() => Signal::Interrupt,
+                _ = self.terminate.recv() => Signal::Terminate,
+            }
+        }
+
+        #[cfg(windows)]
+        {
+            tokio::select! {
+                _ = self.ctrl_c.recv() => Signal::Interrupt,
+                _ = self.ctrl_break.recv() => Signal::Interrupt,
+                _ = self.ctrl_close.recv() => Signal::Terminate,
+                _ = self.ctrl_logoff.recv() => Signal::Terminate,
+                _ = self.ctrl_shutdown.recv() => Signal::Terminate,
+            }
+        }
+    }
+}
+
+/// Listen for signals on a dedicated thread with its own runtime, so they
+/// are seen even while every worker of the main runtime is busy with
+/// blocking work. Signals stop killing the process once this returns.
+pub fn spawn_signal_listener() -> AppResult<mpsc::UnboundedReceiver<Signal>> {
+    let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+    let (signal_tx, signal_rx) = mpsc::unbounded_channel();
+
+    std::thread::Builder::new()
+        .name("signals".to_string())
+        .spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(TramError::Process {
+                        message: format!("Failed to listen for signals: {}", e),
+                    }));
+                    return;
+                }
+            };
+
+            runtime.block_on(async move {
+                let mut signals = match Signals::new() {
+                    Ok(signals) => signals,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(TramError::Process {
+                            message: e.to_string(),
+                        }));
+                        return;
+                    }
+                };
+                let _ = ready_tx.send(Ok(()));
+
+                while signal_tx.send(signals.recv().await).is_ok() {}
+            });
+        })
+        .map_err(|e| TramError::Process {
+            message: format!("Failed to listen for signals: {}", e),
+        })?;
+
+    ready_rx.recv().map_err(|e| TramError::Process {
+        message: format!("Failed to listen for signals: {}", e),
+    })??;
+
+    Ok(signal_rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes_are_128_plus_signal() {
+        for signal in [Signal::Hangup, Signal::Interrupt, Signal::Terminate] {
+            assert_eq!(signal.exit_code().code(), 128 + signal.number());
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_receives_signal() {
+        let mut signals = Signals::new().unwrap();
+        unsafe { libc::raise(libc::SIGHUP) };
+
+        assert_eq!(signals.recv().await, Signal::Hangup);
+    }
+}
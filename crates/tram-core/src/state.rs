@@ -0,0 +1,170 @@
+//! Persistent key-value state for CLI applications.
+//!
+//! Provides a small JSON-backed store for data that must survive between
+//! runs but isn't user configuration, such as the last update check time.
+
+use crate::{AppResult, TramError};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// State key recording when the CLI first ran.
+const FIRST_RUN_KEY: &str = "firstRunAt";
+
+/// JSON file backed key-value store.
+#[derive(Debug, Clone)]
+pub struct StateStore {
+    path: PathBuf,
+}
+
+impl StateStore {
+    /// Open a state store backed by the given file. The file is created lazily.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Open the default state store in the user's data directory.
+    pub fn open_default() -> AppResult<Self> {
+        let data_dir = crate::data_dir().ok_or_else(|| TramError::State {
+            message: "Unable to determine the data directory".to_string(),
+        })?;
+
+        Ok(Self::new(data_dir.join("state.json")))
+    }
+
+    /// Path of the backing file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read a value, returning `None` if the key is missing.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> AppResult<Option<T>> {
+        let mut values = self.load()?;
+
+        match values.remove(key) {
+            Some(value) => serde_json::from_value(value).map(Some).map_err(|e| {
+                TramError::State {
+                    message: format!("Invalid value for state key '{}': {}", key, e),
+                }
+                .into()
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /// Store a value under the given key.
+    pub fn set<T: Serialize>(&self, key: &str, value: &T) -> AppResult<()> {
+        let value = serde_json::to_value(value).map_err(|e| TramError::State {
+            message: format!("Failed to serialize state key '{}': {}", key, e),
+        })?;
+
+        let mut values = self.load()?;
+        values.insert(key.to_string(), value);
+        self.save(&values)
+    }
+
+    /// Remove a key, returning whether it existed.
+    pub fn remove(&self, key: &str) -> AppResult<bool> {
+        let mut values = self.load()?;
+        let existed = values.remove(key).is_some();
+
+        if existed {
+            self.save(&values)?;
+        }
+
+        Ok(existed)
+    }
+
+    /// Whether this is the first run, recording it if so. Later calls, in
+    /// this or any other process, return `false`.
+    pub fn first_run(&self) -> AppResult<bool> {
+        if self.get::<u64>(FIRST_RUN_KEY)?.is_some() {
+            return Ok(false);
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        self.set(FIRST_RUN_KEY, &now)?;
+
+        Ok(true)
+    }
+
+    fn load(&self) -> AppResult<Map<String, Value>> {
+        if !self.path.exists() {
+            return Ok(Map::new());
+        }
+
+        let content = fs::read_to_string(&self.path).map_err(|e| TramError::State {
+            message: format!("Failed to read {}: {}", self.path.display(), e),
+        })?;
+
+        if content.trim().is_empty() {
+            return Ok(Map::new());
+        }
+
+        serde_json::from_str(&content).map_err(|e| {
+            TramError::State {
+                message: format!("State file {} is corrupted: {}", self.path.display(), e),
+            }
+            .into()
+        })
+    }
+
+    fn save(&self, values: &Map<String, Value>) -> AppResult<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| TramError::State {
+                message: format!("Failed to create {}: {}", parent.display(), e),
+            })?;
+        }
+
+        let content = serde_json::to_string_pretty(values).map_err(|e| TramError::State {
+            message: format!("Failed to serialize state: {}", e),
+        })?;
+
+        fs::write(&self.path, content).map_err(|e| {
+            TramError::State {
+                message: format!("Failed to write {}: {}", self.path.display(), e),
+            }
+            .into()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_state_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = StateStore::new(temp_dir.path().join("nested").join("state.json"));
+
+        assert_eq!(store.get::<u64>("counter").unwrap(), None);
+
+        store.set("counter", &42u64).unwrap();
+        store.set("name", &"tram").unwrap();
+
+        assert_eq!(store.get::<u64>("counter").unwrap(), Some(42));
+        assert_eq!(store.get::<String>("name").unwrap(), Some("tram".into()));
+
+        assert!(store.remove("counter").unwrap());
+        assert!(!store.remove("counter").unwrap());
+        assert_eq!(store.get::<u64>("counter").unwrap(), None);
+    }
+
+    #[test]
+    fn test_first_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = StateStore::new(temp_dir.path().join("state.json"));
+
+        assert!(store.first_run().unwrap());
+        assert!(!store.first_run().unwrap());
+        assert!(!StateStore::new(store.path()).first_run().unwrap());
+    }
+}
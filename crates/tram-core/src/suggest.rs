@@ -0,0 +1,108 @@
+//! "Did you mean" suggestions for mistyped names.
+//!
+//! Unknown subcommands, task names, and config keys are compared against the
+//! valid names by edit distance, counting a swap of two adjacent letters as a
+//! single edit so `wrokspace` is one typo away from `workspace`.
+
+/// Edit distance between two strings, ignoring case: insertions, deletions,
+/// substitutions, and transpositions of adjacent characters each cost one.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+
+    // rows[i][j] is the distance between a[..i] and b[..j]
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+
+            rows[i][j] = distance;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+/// The candidate closest to `input`, if it's close enough to be a typo: at
+/// most one edit per three characters, and at least one. Ties go to the
+/// earliest candidate.
+pub fn did_you_mean<'a, I>(input: &str, candidates: I) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let max_distance = (input.chars().count() / 3).max(1);
+
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != input)
+        .map(|candidate| (edit_distance(input, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMMANDS: [&str; 8] = [
+        "new",
+        "generate",
+        "init",
+        "workspace",
+        "config",
+        "doctor",
+        "env",
+        "completions",
+    ];
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("workspace", "workspace"), 0);
+        assert_eq!(edit_distance("wrokspace", "workspace"), 1);
+        assert_eq!(edit_distance("confg", "config"), 1);
+        assert_eq!(edit_distance("Config", "config"), 0);
+        assert_eq!(edit_distance("", "new"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_common_misspellings() {
+        let cases = [
+            ("wrokspace", "workspace"),
+            ("workspce", "workspace"),
+            ("confg", "config"),
+            ("conifg", "config"),
+            ("genrate", "generate"),
+            ("generat", "generate"),
+            ("docter", "doctor"),
+            ("nwe", "new"),
+            ("completion", "completions"),
+        ];
+
+        for (typo, expected) in cases {
+            assert_eq!(did_you_mean(typo, COMMANDS), Some(expected), "for {}", typo);
+        }
+    }
+
+    #[test]
+    fn test_no_suggestion_for_unrelated_input() {
+        assert_eq!(did_you_mean("deploy", COMMANDS), None);
+        assert_eq!(did_you_mean("xyz", COMMANDS), None);
+        assert_eq!(did_you_mean("anything", []), None);
+    }
+}
@@ -0,0 +1,157 @@
+//! A machine-readable summary of what a command did.
+//!
+//! Once [`start_summary`] is called, files written through a
+//! [`ChangeSet`](crate::ChangeSet), deprecation warnings, and `warn!` logs
+//! are collected for the rest of the process, and [`finish_summary`] turns
+//! them into a [`RunSummary`] with the command's exit code and duration.
+//! CI steps and wrappers read it instead of scraping human output.
+//! Recording is a no-op until collection starts, so library code can record
+//! freely, and a long-running process that never asked for a summary
+//! doesn't accumulate one.
+
+use crate::{FileChange, FileChangeKind};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Instant;
+
+static COLLECTED: Mutex<Option<Collected>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Collected {
+    command: String,
+    started: Instant,
+    dry_run: bool,
+    files_created: Vec<PathBuf>,
+    files_modified: Vec<PathBuf>,
+    warnings: Vec<String>,
+}
+
+/// What a command did, written with `--summary-file` or `--format json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunSummary {
+    pub command: String,
+    pub success: bool,
+    pub exit_code: u8,
+    pub duration_ms: u64,
+    /// Files were only planned, not written
+    pub dry_run: bool,
+    pub files_created: Vec<PathBuf>,
+    pub files_modified: Vec<PathBuf>,
+    pub warnings: Vec<String>,
+}
+
+/// Start collecting a summary for `command`, timed from now. Calling it
+/// again starts over.
+pub fn start_summary(command: impl Into<String>, dry_run: bool) {
+    if let Ok(mut collected) = COLLECTED.lock() {
+        *collected = Some(Collected {
+            command: command.into(),
+            started: Instant::now(),
+            dry_run,
+            files_created: Vec::new(),
+            files_modified: Vec::new(),
+            warnings: Vec::new(),
+        });
+    }
+}
+
+/// Whether a summary is being collected.
+pub fn summary_enabled() -> bool {
+    COLLECTED
+        .lock()
+        .map(|collected| collected.is_some())
+        .unwrap_or(false)
+}
+
+/// Record a created or modified file. Directories aren't listed, and each
+/// file is listed once: a file created and then modified counts as created.
+pub fn record_file_change(change: &FileChange) {
+    update(|collected| {
+        let path = &change.path;
+        if collected.files_created.contains(path) || collected.files_modified.contains(path) {
+            return;
+        }
+
+        match change.kind {
+            FileChangeKind::CreateFile => collected.files_created.push(path.clone()),
+            FileChangeKind::ModifyFile => collected.files_modified.push(path.clone()),
+            FileChangeKind::CreateDir => {}
+        }
+    });
+}
+
+/// Record a warning shown to the user.
+pub fn record_warning(message: impl Into<String>) {
+    let message = message.into();
+    update(|collected| collected.warnings.push(message));
+}
+
+/// Stop collecting and return the summary for a command that exited with
+/// `exit_code`, or `None` if collection was never started.
+pub fn finish_summary(exit_code: u8) -> Option<RunSummary> {
+    let collected = COLLECTED.lock().ok()?.take()?;
+
+    Some(RunSummary {
+        command: collected.command,
+        success: exit_code == 0,
+        exit_code,
+        duration_ms: collected.started.elapsed().as_millis() as u64,
+        dry_run: collected.dry_run,
+        files_created: collected.files_created,
+        files_modified: collected.files_modified,
+        warnings: collected.warnings,
+    })
+}
+
+fn update(f: impl FnOnce(&mut Collected)) {
+    if let Ok(mut collected) = COLLECTED.lock()
+        && let Some(collected) = collected.as_mut()
+    {
+        f(collected);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_collects_files_and_warnings() {
+        // Nothing is kept before collection starts
+        record_warning("too early");
+
+        start_summary("new", false);
+        assert!(summary_enabled());
+        for (path, kind) in [
+            ("demo", FileChangeKind::CreateDir),
+            ("demo/README.md", FileChangeKind::CreateFile),
+            ("demo/README.md", FileChangeKind::ModifyFile),
+            ("Cargo.toml", FileChangeKind::ModifyFile),
+        ] {
+            record_file_change(&FileChange {
+                path: path.into(),
+                kind,
+            });
+        }
+        record_warning("No git repository");
+
+        let summary = finish_summary(3).unwrap();
+        assert_eq!(summary.command, "new");
+        assert!(!summary.success);
+        assert_eq!(summary.exit_code, 3);
+        // Other tests write files and log warnings at the same time
+        assert!(summary.files_created.contains(&"demo/README.md".into()));
+        assert!(!summary.files_created.contains(&"demo".into()));
+        assert!(summary.files_modified.contains(&"Cargo.toml".into()));
+        assert!(!summary.files_modified.contains(&"demo/README.md".into()));
+        assert!(summary.warnings.contains(&"No git repository".to_string()));
+        assert!(!summary.warnings.contains(&"too early".to_string()));
+        assert!(finish_summary(0).is_none());
+
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["exitCode"], 3);
+        assert!(json["durationMs"].is_u64());
+    }
+}
@@ -0,0 +1,80 @@
+//! Table settings shared by configuration and rendering.
+//!
+//! The table itself is rendered by `tram-ui`; the border style lives here so
+//! it can be a config setting.
+
+use serde::{Deserialize, Serialize};
+
+/// Characters used to draw table borders.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BorderStyle {
+    /// Box-drawing characters
+    #[default]
+    Unicode,
+    /// `+`, `-`, and `|`, for terminals without Unicode support
+    Ascii,
+    /// No borders; a dashed rule under the header
+    None,
+}
+
+impl std::fmt::Display for BorderStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BorderStyle::Unicode => write!(f, "unicode"),
+            BorderStyle::Ascii => write!(f, "ascii"),
+            BorderStyle::None => write!(f, "none"),
+        }
+    }
+}
+
+impl std::str::FromStr for BorderStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "unicode" => Ok(BorderStyle::Unicode),
+            "ascii" => Ok(BorderStyle::Ascii),
+            "none" => Ok(BorderStyle::None),
+            _ => Err(format!("Invalid table border: {}", s)),
+        }
+    }
+}
+
+impl From<&str> for BorderStyle {
+    fn from(s: &str) -> Self {
+        s.parse().unwrap_or_default()
+    }
+}
+
+/// Width of the terminal, from `COLUMNS` or stdout, if it is a terminal.
+pub fn terminal_width() -> Option<usize> {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .filter(|columns| *columns > 0)
+        .or_else(|| terminal_size::terminal_size().map(|(width, _)| width.0 as usize))
+}
+
+/// Height of the terminal, from `LINES` or stdout, if it is a terminal.
+pub fn terminal_height() -> Option<usize> {
+    std::env::var("LINES")
+        .ok()
+        .and_then(|lines| lines.parse().ok())
+        .filter(|lines| *lines > 0)
+        .or_else(|| terminal_size::terminal_size().map(|(_, height)| height.0 as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_border_style_round_trips() {
+        for style in [BorderStyle::Unicode, BorderStyle::Ascii, BorderStyle::None] {
+            assert_eq!(style.to_string().parse::<BorderStyle>(), Ok(style));
+        }
+        assert_eq!(BorderStyle::from("ASCII"), BorderStyle::Ascii);
+        assert!("double".parse::<BorderStyle>().is_err());
+    }
+}
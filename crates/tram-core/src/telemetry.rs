@@ -0,0 +1,276 @@
+//! Opt-in usage telemetry.
+//!
+//! Nothing is recorded until the user runs `tram telemetry on`. The answer is
+//! kept in the [`StateStore`], and the `TRAM_TELEMETRY` and `DO_NOT_TRACK`
+//! environment variables override it for a single run. While enabled, each
+//! command appends a [`UsageEvent`] to `telemetry.jsonl` in the data
+//! directory: the command name, whether it succeeded, how long it took, and
+//! the tram version and platform. Arguments, paths, and config values are
+//! never recorded.
+
+use crate::{AppResult, StateStore, TramError};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// State key under which the user's answer is stored.
+const STATE_KEY: &str = "telemetry";
+
+/// Environment variable that turns telemetry on (`1`, `on`) or off (`0`,
+/// `off`) for one run, whatever the stored answer.
+pub const TELEMETRY_ENV: &str = "TRAM_TELEMETRY";
+
+/// Cross-tool opt-out: any value but `0` or empty turns telemetry off.
+pub const DO_NOT_TRACK_ENV: &str = "DO_NOT_TRACK";
+
+/// Whether the user agreed to telemetry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TelemetryConsent {
+    Enabled,
+    Disabled,
+    /// The user hasn't answered yet; telemetry stays off
+    Undecided,
+}
+
+impl std::fmt::Display for TelemetryConsent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TelemetryConsent::Enabled => write!(f, "enabled"),
+            TelemetryConsent::Disabled => write!(f, "disabled"),
+            TelemetryConsent::Undecided => write!(f, "undecided"),
+        }
+    }
+}
+
+/// Persisted answer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TelemetryState {
+    enabled: bool,
+    decided_at: u64,
+}
+
+/// One recorded command run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageEvent {
+    pub command: String,
+    pub success: bool,
+    pub duration_ms: u64,
+    pub version: String,
+    pub os: String,
+    pub arch: String,
+    pub timestamp: u64,
+}
+
+impl UsageEvent {
+    /// An event for the current version and platform, stamped now.
+    pub fn new(command: impl Into<String>, success: bool, duration_ms: u64) -> Self {
+        Self {
+            command: command.into(),
+            success,
+            duration_ms,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            timestamp: unix_now(),
+        }
+    }
+}
+
+/// Consent management and recording for usage telemetry.
+#[derive(Debug, Clone)]
+pub struct Telemetry {
+    state: StateStore,
+    events_path: PathBuf,
+    env_override: Option<bool>,
+}
+
+impl Telemetry {
+    /// Keep consent in the given store and record events to the given file.
+    pub fn new(state: StateStore, events_path: impl Into<PathBuf>) -> Self {
+        Self {
+            state,
+            events_path: events_path.into(),
+            env_override: None,
+        }
+    }
+
+    /// Use the default state store and data directory, honoring the
+    /// environment overrides.
+    pub fn open_default() -> AppResult<Self> {
+        let data_dir = crate::data_dir().ok_or_else(|| TramError::State {
+            message: "Unable to determine the data directory".to_string(),
+        })?;
+
+        Ok(Self::new(
+            StateStore::open_default()?,
+            data_dir.join("telemetry.jsonl"),
+        )
+        .with_env_override(env_override(
+            std::env::var(TELEMETRY_ENV).ok().as_deref(),
+            std::env::var(DO_NOT_TRACK_ENV).ok().as_deref(),
+        )))
+    }
+
+    /// Turn telemetry on or off for this run, whatever the stored answer.
+    pub fn with_env_override(mut self, enabled: Option<bool>) -> Self {
+        self.env_override = enabled;
+        self
+    }
+
+    /// File events are recorded to.
+    pub fn events_path(&self) -> &Path {
+        &self.events_path
+    }
+
+    /// The stored answer, ignoring environment overrides.
+    pub fn consent(&self) -> AppResult<TelemetryConsent> {
+        Ok(match self.state.get::<TelemetryState>(STATE_KEY)? {
+            Some(state) if state.enabled => TelemetryConsent::Enabled,
+            Some(_) => TelemetryConsent::Disabled,
+            None => TelemetryConsent::Undecided,
+        })
+    }
+
+    /// The environment override in effect, if any.
+    pub fn env_override(&self) -> Option<bool> {
+        self.env_override
+    }
+
+    /// Whether events are recorded on this run.
+    pub fn is_enabled(&self) -> AppResult<bool> {
+        match self.env_override {
+            Some(enabled) => Ok(enabled),
+            None => Ok(self.consent()? == TelemetryConsent::Enabled),
+        }
+    }
+
+    /// Store the user's answer.
+    pub fn set_enabled(&self, enabled: bool) -> AppResult<()> {
+        self.state.set(
+            STATE_KEY,
+            &TelemetryState {
+                enabled,
+                decided_at: unix_now(),
+            },
+        )
+    }
+
+    /// Record an event if telemetry is enabled, returning whether it was.
+    pub fn record(&self, event: &UsageEvent) -> AppResult<bool> {
+        if !self.is_enabled()? {
+            return Ok(false);
+        }
+
+        let error = |e: std::io::Error| TramError::State {
+            message: format!("Failed to write {}: {}", self.events_path.display(), e),
+        };
+
+        if let Some(parent) = self.events_path.parent() {
+            fs::create_dir_all(parent).map_err(error)?;
+        }
+
+        let line = serde_json::to_string(event).map_err(|e| TramError::State {
+            message: format!("Failed to serialize telemetry event: {}", e),
+        })?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.events_path)
+            .map_err(error)?;
+        writeln!(file, "{}", line).map_err(error)?;
+
+        Ok(true)
+    }
+
+    /// Number of events recorded so far.
+    pub fn recorded_events(&self) -> usize {
+        fs::read_to_string(&self.events_path)
+            .map(|content| content.lines().filter(|l| !l.trim().is_empty()).count())
+            .unwrap_or_default()
+    }
+}
+
+/// Interpret the `TRAM_TELEMETRY` and `DO_NOT_TRACK` values. `DO_NOT_TRACK`
+/// wins, so a global opt-out can't be undone by a project setting.
+pub fn env_override(telemetry: Option<&str>, do_not_track: Option<&str>) -> Option<bool> {
+    if let Some(value) = do_not_track.map(str::trim)
+        && !value.is_empty()
+        && value != "0"
+    {
+        return Some(false);
+    }
+
+    match telemetry?.trim().to_ascii_lowercase().as_str() {
+        "1" | "on" | "true" | "yes" => Some(true),
+        "0" | "off" | "false" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn telemetry(temp_dir: &TempDir) -> Telemetry {
+        Telemetry::new(
+            StateStore::new(temp_dir.path().join("state.json")),
+            temp_dir.path().join("telemetry.jsonl"),
+        )
+    }
+
+    #[test]
+    fn test_consent_gates_recording() {
+        let temp_dir = TempDir::new().unwrap();
+        let telemetry = telemetry(&temp_dir);
+        let event = UsageEvent::new("doctor", true, 12);
+
+        assert_eq!(telemetry.consent().unwrap(), TelemetryConsent::Undecided);
+        assert!(!telemetry.record(&event).unwrap());
+        assert!(!telemetry.events_path().exists());
+
+        telemetry.set_enabled(true).unwrap();
+        assert_eq!(telemetry.consent().unwrap(), TelemetryConsent::Enabled);
+        assert!(telemetry.record(&event).unwrap());
+        assert_eq!(telemetry.recorded_events(), 1);
+
+        telemetry.set_enabled(false).unwrap();
+        assert_eq!(telemetry.consent().unwrap(), TelemetryConsent::Disabled);
+        assert!(!telemetry.record(&event).unwrap());
+        assert_eq!(telemetry.recorded_events(), 1);
+    }
+
+    #[test]
+    fn test_env_override_beats_consent() {
+        let temp_dir = TempDir::new().unwrap();
+        let telemetry = telemetry(&temp_dir);
+        telemetry.set_enabled(true).unwrap();
+
+        let telemetry = telemetry.with_env_override(Some(false));
+        assert!(!telemetry.is_enabled().unwrap());
+        assert_eq!(telemetry.consent().unwrap(), TelemetryConsent::Enabled);
+    }
+
+    #[test]
+    fn test_env_override_values() {
+        assert_eq!(env_override(None, None), None);
+        assert_eq!(env_override(Some("on"), None), Some(true));
+        assert_eq!(env_override(Some("0"), None), Some(false));
+        assert_eq!(env_override(Some("maybe"), None), None);
+        assert_eq!(env_override(Some("1"), Some("1")), Some(false));
+        assert_eq!(env_override(Some("1"), Some("0")), Some(true));
+    }
+}
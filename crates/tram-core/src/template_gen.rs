@@ -2,13 +2,54 @@
 //!
 //! Provides utilities for generating boilerplate code for common CLI patterns,
 //! helping developers quickly add new functionality to their applications.
-
-use crate::{AppResult, TramError};
+//!
+//! Generated commands can also be wired into the application, so they
+//! compile straight away: [`TemplateGenerator::register_command`] adds the
+//! module declaration, the `Commands` variant, and the dispatch arm at
+//! marker comments in the project's sources:
+//!
+//! ```text
+//! // src/commands/mod.rs
+//! pub mod deploy;
+//! // tram:generate:modules
+//!
+//! // src/main.rs
+//! enum Commands {
+//!     Deploy(crate::commands::deploy::DeployArgs),
+//!     // tram:generate:commands
+//! }
+//!
+//! match cli.command {
+//!     Commands::Deploy(args) => crate::commands::deploy::execute(args).await,
+//!     // tram:generate:dispatch
+//! }
+//! ```
+//!
+//! Workspaces can add their own template types as Handlebars files in
+//! [`CUSTOM_TEMPLATES_DIR`], loaded with
+//! [`TemplateGenerator::with_custom_templates`] and generated as
+//! [`TemplateType::Custom`]. They're rendered with the same context as the
+//! built-in templates: `name`, `name_pascal`, `name_upper`, `description`,
+//! and `parameters`.
+
+use crate::{AppResult, ChangeSet, FileChange, Progress, TramError};
 use handlebars::Handlebars;
 use serde_json::{Value, json};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Marks where generated command modules are declared.
+pub const COMMAND_MODULES_MARKER: &str = "// tram:generate:modules";
+/// Marks where generated commands are added to the `Commands` enum.
+pub const COMMAND_VARIANTS_MARKER: &str = "// tram:generate:commands";
+/// Marks where generated commands are added to the `match` that runs them.
+pub const COMMAND_DISPATCH_MARKER: &str = "// tram:generate:dispatch";
+
+/// Where a workspace's own templates live, relative to its root. A file
+/// named `handler.rs.hbs` is the `handler` template, and generates
+/// `<name>.rs` in the target directory; `handler.hbs` generates `<name>`.
+pub const CUSTOM_TEMPLATES_DIR: &str = ".tram/templates";
 
 /// Supported template types for CLI applications.
 #[derive(Debug, Clone, PartialEq)]
@@ -21,6 +62,8 @@ pub enum TemplateType {
     ErrorType,
     /// Generate session extension
     SessionExtension,
+    /// A template from the workspace's [`CUSTOM_TEMPLATES_DIR`], by name
+    Custom(String),
 }
 
 /// Configuration for template generation.
@@ -40,6 +83,14 @@ pub struct TemplateConfig {
 pub struct TemplateGenerator {
     /// Handlebars instance for template rendering
     handlebars: Handlebars<'static>,
+    /// Report writes without touching disk
+    dry_run: bool,
+    /// Overwrite existing files
+    force: bool,
+    /// Reports rendering and writing
+    progress: Option<Progress>,
+    /// Extensions of the files custom templates generate, by template name
+    custom: BTreeMap<String, Option<String>>,
 }
 
 impl TemplateGenerator {
@@ -49,7 +100,79 @@ impl TemplateGenerator {
         // Register built-in templates
         Self::register_templates(&mut handlebars)?;
 
-        Ok(Self { handlebars })
+        Ok(Self {
+            handlebars,
+            dry_run: false,
+            force: false,
+            progress: None,
+            custom: BTreeMap::new(),
+        })
+    }
+
+    /// Load the templates in `root`'s [`CUSTOM_TEMPLATES_DIR`], if it has
+    /// one, so they can be generated as [`TemplateType::Custom`].
+    pub fn with_custom_templates(mut self, root: &Path) -> AppResult<Self> {
+        let dir = root.join(CUSTOM_TEMPLATES_DIR);
+        let Ok(entries) = fs::read_dir(&dir) else {
+            return Ok(self);
+        };
+        let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+        paths.sort();
+
+        for path in paths.into_iter().filter(|path| path.is_file()) {
+            let Some(stem) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(".hbs"))
+            else {
+                continue;
+            };
+            let (name, extension) = match stem.split_once('.') {
+                Some((name, extension)) => (name, Some(extension.to_string())),
+                None => (stem, None),
+            };
+            if name.is_empty() {
+                continue;
+            }
+
+            self.handlebars
+                .register_template_string(&custom_template_name(name), read_source(&path)?)
+                .map_err(|e| TramError::InvalidConfig {
+                    message: format!("Failed to register template {}: {}", path.display(), e),
+                })?;
+            self.custom.insert(name.to_string(), extension);
+        }
+
+        Ok(self)
+    }
+
+    /// Names of the custom templates loaded, in order.
+    pub fn custom_templates(&self) -> impl Iterator<Item = &str> {
+        self.custom.keys().map(String::as_str)
+    }
+
+    /// Report the files `write_template` would write without writing them.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Let `write_template` replace a file that already exists.
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Report rendering and writing on a progress indicator.
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    fn step(&self, message: String) {
+        if let Some(progress) = &self.progress {
+            progress.step(message);
+        }
     }
 
     /// Generate a template based on the provided configuration.
@@ -74,18 +197,30 @@ impl TemplateGenerator {
             .into());
         }
 
-        // Behavior: Should generate appropriate content based on template type
-        let content = self.render_template(config)?;
-        let file_path = self.determine_file_path(config)?;
-
-        // Behavior: Should not overwrite existing files without confirmation
-        if file_path.exists() {
-            return Err(TramError::InvalidConfig {
-                message: format!("File {} already exists", file_path.display()),
+        if let TemplateType::Custom(name) = &config.template_type
+            && !self.custom.contains_key(name)
+        {
+            let found: Vec<&str> = self.custom_templates().collect();
+            return Err(TramError::InvalidInput {
+                input: format!("custom:{}", name),
+                message: match found.is_empty() {
+                    true => format!("no templates found in {}", CUSTOM_TEMPLATES_DIR),
+                    false => format!(
+                        "no template named '{}' in {} (found: {})",
+                        name,
+                        CUSTOM_TEMPLATES_DIR,
+                        found.join(", ")
+                    ),
+                },
             }
             .into());
         }
 
+        // Behavior: Should generate appropriate content based on template type
+        self.step(format!("Rendering {} template", config.name));
+        let content = self.render_template(config)?;
+        let file_path = self.determine_file_path(config)?;
+
         Ok(GeneratedTemplate {
             content,
             file_path,
@@ -94,27 +229,150 @@ impl TemplateGenerator {
         })
     }
 
-    /// Write the generated template to the filesystem.
-    pub fn write_template(&self, template: &GeneratedTemplate) -> AppResult<()> {
-        // Behavior: Should create parent directories if needed
-        if let Some(parent) = template.file_path.parent() {
-            fs::create_dir_all(parent).map_err(|e| TramError::InvalidConfig {
-                message: format!("Failed to create directory {}: {}", parent.display(), e),
-            })?;
+    /// A unified diff from the file already at the template's path to the
+    /// generated content, or `None` if there's no file there yet. The diff
+    /// is empty when the file matches.
+    pub fn diff_template(&self, template: &GeneratedTemplate) -> AppResult<Option<String>> {
+        if !template.file_path.exists() {
+            return Ok(None);
         }
 
-        // Behavior: Should write content to file
-        fs::write(&template.file_path, &template.content).map_err(|e| {
-            TramError::InvalidConfig {
-                message: format!(
-                    "Failed to write file {}: {}",
-                    template.file_path.display(),
-                    e
-                ),
+        let existing = read_source(&template.file_path)?;
+        let path = template
+            .file_path
+            .strip_prefix(crate::current_dir())
+            .unwrap_or(&template.file_path);
+        Ok(Some(crate::unified_diff(
+            &path.to_string_lossy(),
+            &existing,
+            &template.content,
+        )))
+    }
+
+    /// Write the generated template to the filesystem, returning the
+    /// directories and file created (or, in a dry run, that would be).
+    /// An existing file is only overwritten [`with_force`](Self::with_force).
+    pub fn write_template(&self, template: &GeneratedTemplate) -> AppResult<Vec<FileChange>> {
+        // Behavior: Should refuse to overwrite, create parent directories if
+        // needed, then write content
+        if template.file_path.exists() && !self.force {
+            return Err(TramError::Conflict {
+                path: template.file_path.display().to_string(),
+            }
+            .into());
+        }
+
+        self.step(format!("Writing {}", template.file_path.display()));
+        let mut changes = ChangeSet::new(self.dry_run);
+        changes.write_file(&template.file_path, &template.content)?;
+
+        Ok(changes.into_changes())
+    }
+
+    /// Wire a generated command into the project at `config.target_dir`:
+    /// declare its module, add a `Commands` variant for its arguments, and
+    /// dispatch the variant to its `execute` function.
+    ///
+    /// The module is declared in `src/commands/mod.rs` (or
+    /// `src/commands.rs`) at [`COMMAND_MODULES_MARKER`], or in a new
+    /// `src/commands/mod.rs` if neither exists. The variant and dispatch arm
+    /// go before [`COMMAND_VARIANTS_MARKER`] and [`COMMAND_DISPATCH_MARKER`]
+    /// in whichever file under `src` has them. Lines already present are
+    /// left alone, and markers that can't be found are reported rather than
+    /// failing.
+    pub fn register_command(&self, config: &TemplateConfig) -> AppResult<CommandRegistration> {
+        if config.template_type != TemplateType::Command {
+            return Err(TramError::InvalidInput {
+                input: config.name.clone(),
+                message: "only command templates can be registered".to_string(),
             }
-        })?;
+            .into());
+        }
 
-        Ok(())
+        let module = config.name.replace('-', "_");
+        let name_pascal = to_pascal_case(&config.name);
+        let description = config
+            .parameters
+            .get("description")
+            .cloned()
+            .unwrap_or_else(|| format!("{} functionality", config.name));
+        let src = config.target_dir.join("src");
+
+        self.step(format!("Registering {} command", config.name));
+        // Updated contents by file, so a file with several markers is
+        // written once
+        let mut updates: BTreeMap<PathBuf, String> = BTreeMap::new();
+        let mut missing_markers = Vec::new();
+
+        // Module declaration
+        let declaration = format!("pub mod {};", module);
+        let modules_file = [src.join("commands").join("mod.rs"), src.join("commands.rs")]
+            .into_iter()
+            .find(|path| path.exists());
+        match modules_file {
+            Some(path) => {
+                let contents = read_source(&path)?;
+                if !has_line(&contents, &declaration) {
+                    match insert_before_marker(&contents, COMMAND_MODULES_MARKER, &[&declaration]) {
+                        Some(updated) => {
+                            updates.insert(path, updated);
+                        }
+                        None => missing_markers.push(COMMAND_MODULES_MARKER),
+                    }
+                }
+            }
+            None => {
+                updates.insert(
+                    src.join("commands").join("mod.rs"),
+                    format!("{}\n{}\n", declaration, COMMAND_MODULES_MARKER),
+                );
+            }
+        }
+
+        // `Commands` variant and dispatch arm
+        let variant = format!(
+            "{}(crate::commands::{}::{}Args),",
+            name_pascal, module, name_pascal
+        );
+        let doc = format!("/// {}", description);
+        let arm = format!(
+            "Commands::{}(args) => crate::commands::{}::execute(args).await,",
+            name_pascal, module
+        );
+
+        for (marker, lines) in [
+            (
+                COMMAND_VARIANTS_MARKER,
+                vec![doc.as_str(), variant.as_str()],
+            ),
+            (COMMAND_DISPATCH_MARKER, vec![arm.as_str()]),
+        ] {
+            let Some(path) = find_marker(&src, marker)? else {
+                missing_markers.push(marker);
+                continue;
+            };
+
+            let contents = match updates.get(&path) {
+                Some(updated) => updated.clone(),
+                None => read_source(&path)?,
+            };
+            if has_line(&contents, lines[lines.len() - 1]) {
+                continue;
+            }
+            if let Some(updated) = insert_before_marker(&contents, marker, &lines) {
+                updates.insert(path, updated);
+            }
+        }
+
+        let mut changes = ChangeSet::new(self.dry_run);
+        for (path, contents) in updates {
+            changes.write_file(&path, contents)?;
+        }
+
+        Ok(CommandRegistration {
+            changes: changes.into_changes(),
+            missing_markers,
+        })
     }
 
     /// Register all built-in templates with Handlebars.
@@ -162,7 +420,7 @@ impl TemplateGenerator {
         let context = self.build_template_context(config);
 
         self.handlebars
-            .render(template_name, &context)
+            .render(&template_name, &context)
             .map_err(|e| {
                 TramError::InvalidConfig {
                     message: format!("Failed to render {} template: {}", template_name, e),
@@ -172,12 +430,13 @@ impl TemplateGenerator {
     }
 
     /// Get the template name for a given template type.
-    fn get_template_name(&self, template_type: &TemplateType) -> &'static str {
+    fn get_template_name(&self, template_type: &TemplateType) -> String {
         match template_type {
-            TemplateType::Command => "command",
-            TemplateType::ConfigSection => "config_section",
-            TemplateType::ErrorType => "error_type",
-            TemplateType::SessionExtension => "session_extension",
+            TemplateType::Command => "command".to_string(),
+            TemplateType::ConfigSection => "config_section".to_string(),
+            TemplateType::ErrorType => "error_type".to_string(),
+            TemplateType::SessionExtension => "session_extension".to_string(),
+            TemplateType::Custom(name) => custom_template_name(name),
         }
     }
 
@@ -203,7 +462,7 @@ impl TemplateGenerator {
 
     /// Determine the appropriate file path for the generated template.
     fn determine_file_path(&self, config: &TemplateConfig) -> AppResult<PathBuf> {
-        match config.template_type {
+        match &config.template_type {
             TemplateType::Command => Ok(config
                 .target_dir
                 .join("src")
@@ -224,6 +483,13 @@ impl TemplateGenerator {
                 .join("src")
                 .join("session")
                 .join(format!("{}.rs", config.name))),
+            TemplateType::Custom(template) => {
+                let file_name = match self.custom.get(template).cloned().flatten() {
+                    Some(extension) => format!("{}.{}", config.name, extension),
+                    None => config.name.clone(),
+                };
+                Ok(config.target_dir.join(file_name))
+            }
         }
     }
 }
@@ -247,6 +513,81 @@ pub struct GeneratedTemplate {
     pub name: String,
 }
 
+/// Result of [`TemplateGenerator::register_command`].
+#[derive(Debug, Clone, Default)]
+pub struct CommandRegistration {
+    /// Files modified or created, or that would be in a dry run
+    pub changes: Vec<FileChange>,
+    /// Markers that weren't found, so those parts need wiring by hand
+    pub missing_markers: Vec<&'static str>,
+}
+
+/// Custom templates are registered apart from the built-in ones, so a
+/// `command.hbs` doesn't replace the built-in `command` template.
+fn custom_template_name(name: &str) -> String {
+    format!("custom:{}", name)
+}
+
+fn read_source(path: &Path) -> AppResult<String> {
+    fs::read_to_string(path).map_err(|e| {
+        TramError::InvalidConfig {
+            message: format!("Failed to read {}: {}", path.display(), e),
+        }
+        .into()
+    })
+}
+
+fn has_line(contents: &str, line: &str) -> bool {
+    contents.lines().any(|existing| existing.trim() == line)
+}
+
+/// Insert `lines` before the line holding `marker`, indented like it.
+fn insert_before_marker(contents: &str, marker: &str, lines: &[&str]) -> Option<String> {
+    let mut updated = String::with_capacity(contents.len());
+    let mut found = false;
+
+    for line in contents.split_inclusive('\n') {
+        if !found && line.trim() == marker {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            for new_line in lines {
+                updated.push_str(indent);
+                updated.push_str(new_line);
+                updated.push('\n');
+            }
+            found = true;
+        }
+        updated.push_str(line);
+    }
+
+    found.then_some(updated)
+}
+
+/// The first Rust file under `dir`, in path order, with a line holding
+/// `marker`.
+fn find_marker(dir: &Path, marker: &str) -> AppResult<Option<PathBuf>> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(None);
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    paths.sort();
+
+    for path in paths {
+        if path.is_dir() {
+            if let Some(found) = find_marker(&path, marker)? {
+                return Ok(Some(found));
+            }
+        } else if path.extension().is_some_and(|ext| ext == "rs")
+            && read_source(&path)?
+                .lines()
+                .any(|line| line.trim() == marker)
+        {
+            return Ok(Some(path));
+        }
+    }
+
+    Ok(None)
+}
+
 /// Convert a string to PascalCase.
 fn to_pascal_case(s: &str) -> String {
     s.split(['-', '_'])
@@ -374,6 +715,218 @@ mod tests {
         assert_eq!(content, "test content");
     }
 
+    #[test]
+    fn test_write_template_refuses_to_overwrite() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("existing.rs");
+        std::fs::write(&file_path, "hand-written").unwrap();
+
+        let template = GeneratedTemplate {
+            content: "generated".to_string(),
+            file_path: file_path.clone(),
+            template_type: TemplateType::Command,
+            name: "existing".to_string(),
+        };
+
+        let error = TemplateGenerator::new()
+            .unwrap()
+            .write_template(&template)
+            .unwrap_err();
+
+        assert_eq!(crate::exit_code_for(&error), crate::ExitCode::Conflict);
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "hand-written");
+
+        TemplateGenerator::new()
+            .unwrap()
+            .with_force(true)
+            .write_template(&template)
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "generated");
+    }
+
+    #[test]
+    fn test_diff_template_against_existing_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("deploy.rs");
+        let mut template = GeneratedTemplate {
+            content: "fn main() {\n    deploy();\n}\n".to_string(),
+            file_path: file_path.clone(),
+            template_type: TemplateType::Command,
+            name: "deploy".to_string(),
+        };
+        let generator = TemplateGenerator::new().unwrap();
+
+        assert_eq!(generator.diff_template(&template).unwrap(), None);
+
+        std::fs::write(&file_path, "fn main() {\n    ship();\n}\n").unwrap();
+        let diff = generator.diff_template(&template).unwrap().unwrap();
+        assert!(diff.contains("deploy.rs\n+++ b/"), "{}", diff);
+        assert!(diff.contains("-    ship();\n+    deploy();\n"), "{}", diff);
+
+        template.content = std::fs::read_to_string(&file_path).unwrap();
+        assert_eq!(
+            generator.diff_template(&template).unwrap(),
+            Some(String::new())
+        );
+    }
+
+    #[test]
+    fn test_register_command_at_markers() {
+        let temp_dir = TempDir::new().unwrap();
+        let src = temp_dir.path().join("src");
+        std::fs::create_dir_all(src.join("commands")).unwrap();
+        std::fs::write(
+            src.join("commands").join("mod.rs"),
+            format!("pub mod build;\n{}\n", COMMAND_MODULES_MARKER),
+        )
+        .unwrap();
+        std::fs::write(
+            src.join("main.rs"),
+            format!(
+                "enum Commands {{\n    Build,\n    {}\n}}\n\nfn run() {{\n    match command {{\n        {}\n    }}\n}}\n",
+                COMMAND_VARIANTS_MARKER, COMMAND_DISPATCH_MARKER
+            ),
+        )
+        .unwrap();
+
+        let config = TemplateConfig {
+            name: "deploy-app".to_string(),
+            template_type: TemplateType::Command,
+            target_dir: temp_dir.path().to_path_buf(),
+            parameters: [("description".to_string(), "Deploy the app".to_string())]
+                .into_iter()
+                .collect(),
+        };
+        let generator = TemplateGenerator::new().unwrap();
+        let registration = generator.register_command(&config).unwrap();
+
+        assert!(registration.missing_markers.is_empty());
+        assert_eq!(registration.changes.len(), 2);
+
+        let modules = std::fs::read_to_string(src.join("commands").join("mod.rs")).unwrap();
+        assert_eq!(
+            modules,
+            format!(
+                "pub mod build;\npub mod deploy_app;\n{}\n",
+                COMMAND_MODULES_MARKER
+            )
+        );
+
+        let main = std::fs::read_to_string(src.join("main.rs")).unwrap();
+        assert!(main.contains(
+            "    Build,\n    /// Deploy the app\n    DeployApp(crate::commands::deploy_app::DeployAppArgs),\n    // tram:generate:commands\n"
+        ));
+        assert!(main.contains(
+            "        Commands::DeployApp(args) => crate::commands::deploy_app::execute(args).await,\n        // tram:generate:dispatch\n"
+        ));
+
+        // Registering again changes nothing
+        let again = generator.register_command(&config).unwrap();
+        assert!(again.changes.is_empty());
+    }
+
+    #[test]
+    fn test_register_command_reports_missing_markers() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+        std::fs::write(
+            temp_dir.path().join("src").join("main.rs"),
+            "fn main() {}\n",
+        )
+        .unwrap();
+
+        let config = TemplateConfig {
+            name: "deploy".to_string(),
+            template_type: TemplateType::Command,
+            target_dir: temp_dir.path().to_path_buf(),
+            parameters: HashMap::new(),
+        };
+        let registration = TemplateGenerator::new()
+            .unwrap()
+            .with_dry_run(true)
+            .register_command(&config)
+            .unwrap();
+
+        assert_eq!(
+            registration.missing_markers,
+            [COMMAND_VARIANTS_MARKER, COMMAND_DISPATCH_MARKER]
+        );
+        // The new module file is only planned
+        assert_eq!(registration.changes.len(), 2);
+        assert!(!temp_dir.path().join("src").join("commands").exists());
+    }
+
+    #[test]
+    fn test_generate_custom_template() {
+        let temp_dir = TempDir::new().unwrap();
+        let templates = temp_dir.path().join(CUSTOM_TEMPLATES_DIR);
+        std::fs::create_dir_all(&templates).unwrap();
+        std::fs::write(
+            templates.join("handler.rs.hbs"),
+            "pub struct {{name_pascal}}Handler; // {{description}}\n",
+        )
+        .unwrap();
+        std::fs::write(templates.join("notes.hbs"), "# {{name}}\n").unwrap();
+        std::fs::write(templates.join("README.md"), "Not a template").unwrap();
+
+        let generator = TemplateGenerator::new()
+            .unwrap()
+            .with_custom_templates(temp_dir.path())
+            .unwrap();
+        assert_eq!(
+            generator.custom_templates().collect::<Vec<_>>(),
+            ["handler", "notes"]
+        );
+
+        let config = TemplateConfig {
+            name: "user-signup".to_string(),
+            template_type: TemplateType::Custom("handler".to_string()),
+            target_dir: temp_dir.path().to_path_buf(),
+            parameters: HashMap::new(),
+        };
+        let template = generator.generate_template(&config).unwrap();
+        assert_eq!(
+            template.content,
+            "pub struct UserSignupHandler; // user-signup functionality\n"
+        );
+        assert_eq!(template.file_path, temp_dir.path().join("user-signup.rs"));
+
+        let config = TemplateConfig {
+            template_type: TemplateType::Custom("notes".to_string()),
+            ..config
+        };
+        let template = generator.generate_template(&config).unwrap();
+        assert_eq!(template.file_path, temp_dir.path().join("user-signup"));
+
+        // Built-in templates are unaffected
+        let config = TemplateConfig {
+            template_type: TemplateType::Command,
+            ..config
+        };
+        assert!(generator.generate_template(&config).is_ok());
+    }
+
+    #[test]
+    fn test_generate_unknown_custom_template() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let config = TemplateConfig {
+            name: "deploy".to_string(),
+            template_type: TemplateType::Custom("missing".to_string()),
+            target_dir: temp_dir.path().to_path_buf(),
+            parameters: HashMap::new(),
+        };
+        let error = TemplateGenerator::new()
+            .unwrap()
+            .with_custom_templates(temp_dir.path())
+            .unwrap()
+            .generate_template(&config)
+            .unwrap_err();
+
+        assert_eq!(crate::exit_code_for(&error), crate::ExitCode::Usage);
+        assert!(error.to_string().contains("custom:missing"));
+    }
+
     #[test]
     fn test_to_pascal_case() {
         assert_eq!(to_pascal_case("hello"), "Hello");
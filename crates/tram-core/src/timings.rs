@@ -0,0 +1,295 @@
+//! Timing collection for profiling slow invocations.
+//!
+//! [`Timings`] records how long named steps take, including nested sub-steps,
+//! and can render them as a breakdown table or export them as a Chrome trace
+//! (`chrome://tracing`, Perfetto) for a closer look. Cloning a `Timings`
+//! shares the underlying recorder, so it can be handed to sessions and tasks.
+
+use crate::{AppResult, TramError};
+use serde_json::json;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A completed, timed step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimingSpan {
+    pub name: String,
+    /// Nesting level; 0 for top-level steps
+    pub depth: usize,
+    /// Offset from when recording began
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+#[derive(Debug)]
+struct Recorder {
+    origin: Instant,
+    depth: AtomicUsize,
+    spans: Mutex<Vec<TimingSpan>>,
+}
+
+/// Shared recorder of timed steps.
+#[derive(Debug, Clone)]
+pub struct Timings {
+    inner: Arc<Recorder>,
+}
+
+impl Timings {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Recorder {
+                origin: Instant::now(),
+                depth: AtomicUsize::new(0),
+                spans: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Start timing a step. The step is recorded when the guard is dropped;
+    /// steps started while it is alive are nested beneath it.
+    pub fn start(&self, name: impl Into<String>) -> TimingGuard {
+        let depth = self.inner.depth.fetch_add(1, Ordering::SeqCst);
+
+        TimingGuard {
+            timings: self.clone(),
+            name: name.into(),
+            depth,
+            started: Instant::now(),
+        }
+    }
+
+    /// Time a closure.
+    pub fn measure<T>(&self, name: impl Into<String>, f: impl FnOnce() -> T) -> T {
+        let _guard = self.start(name);
+        f()
+    }
+
+    /// Record a step that was timed elsewhere.
+    pub fn record(
+        &self,
+        name: impl Into<String>,
+        depth: usize,
+        started: Instant,
+        duration: Duration,
+    ) {
+        let span = TimingSpan {
+            name: name.into(),
+            depth,
+            start: started.saturating_duration_since(self.inner.origin),
+            duration,
+        };
+
+        if let Ok(mut spans) = self.inner.spans.lock() {
+            spans.push(span);
+        }
+    }
+
+    /// Recorded steps, ordered by start time.
+    pub fn spans(&self) -> Vec<TimingSpan> {
+        let mut spans = self
+            .inner
+            .spans
+            .lock()
+            .map(|spans| spans.clone())
+            .unwrap_or_default();
+
+        spans.sort_by(|a, b| a.start.cmp(&b.start).then(a.depth.cmp(&b.depth)));
+        spans
+    }
+
+    /// Time since recording began.
+    pub fn elapsed(&self) -> Duration {
+        self.inner.origin.elapsed()
+    }
+
+    /// Render a breakdown table with nested steps indented beneath their parent.
+    pub fn render_table(&self) -> String {
+        let spans = self.spans();
+        let labels: Vec<String> = spans
+            .iter()
+            .map(|span| format!("{}{}", "  ".repeat(span.depth), span.name))
+            .collect();
+        let width = labels
+            .iter()
+            .map(|label| label.chars().count())
+            .chain(["Step".len()])
+            .max()
+            .unwrap_or_default();
+
+        let total = self.elapsed();
+        let mut out = String::new();
+
+        let _ = writeln!(out, "{:<width$}  {:>10}  {:>6}", "Step", "Time", "%");
+        let _ = writeln!(out, "{}", "-".repeat(width + 20));
+
+        for (label, span) in labels.iter().zip(&spans) {
+            let percent = if total.is_zero() {
+                0.0
+            } else {
+                span.duration.as_secs_f64() / total.as_secs_f64() * 100.0
+            };
+
+            let _ = writeln!(
+                out,
+                "{:<width$}  {:>10}  {:>5.1}%",
+                label,
+                format_duration(span.duration),
+                percent
+            );
+        }
+
+        let _ = writeln!(out, "{}", "-".repeat(width + 20));
+        let _ = writeln!(out, "{:<width$}  {:>10}", "Total", format_duration(total));
+
+        out
+    }
+
+    /// Export the steps in Chrome's trace event format.
+    pub fn to_chrome_trace(&self) -> serde_json::Value {
+        let events: Vec<_> = self
+            .spans()
+            .into_iter()
+            .map(|span| {
+                json!({
+                    "name": span.name,
+                    "cat": crate::APP_NAME,
+                    "ph": "X",
+                    "ts": span.start.as_micros() as u64,
+                    "dur": span.duration.as_micros() as u64,
+                    "pid": std::process::id(),
+                    "tid": 1,
+                })
+            })
+            .collect();
+
+        json!({
+            "traceEvents": events,
+            "displayTimeUnit": "ms",
+        })
+    }
+
+    /// Write a Chrome trace to a file.
+    pub fn write_chrome_trace(&self, path: &Path) -> AppResult<()> {
+        let content = serde_json::to_string_pretty(&self.to_chrome_trace()).map_err(|e| {
+            TramError::InvalidConfig {
+                message: format!("Failed to serialize timings: {}", e),
+            }
+        })?;
+
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent).map_err(|e| TramError::Io {
+                message: format!("Failed to create {}: {}", parent.display(), e),
+            })?;
+        }
+
+        std::fs::write(path, content).map_err(|e| {
+            TramError::Io {
+                message: format!("Failed to write trace {}: {}", path.display(), e),
+            }
+            .into()
+        })
+    }
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Records a step when dropped.
+#[must_use = "the step is recorded when the guard is dropped"]
+pub struct TimingGuard {
+    timings: Timings,
+    name: String,
+    depth: usize,
+    started: Instant,
+}
+
+impl Drop for TimingGuard {
+    fn drop(&mut self) {
+        self.timings.inner.depth.fetch_sub(1, Ordering::SeqCst);
+        self.timings.record(
+            std::mem::take(&mut self.name),
+            self.depth,
+            self.started,
+            self.started.elapsed(),
+        );
+    }
+}
+
+/// Format a duration compactly, e.g. `850µs`, `12.3ms`, or `1.52s`.
+pub fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_micros();
+
+    if micros < 1_000 {
+        format!("{}µs", micros)
+    } else if micros < 1_000_000 {
+        format!("{:.1}ms", micros as f64 / 1_000.0)
+    } else {
+        format!("{:.2}s", duration.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_spans() {
+        let timings = Timings::new();
+
+        {
+            let _outer = timings.start("startup");
+            timings.measure("load config", || {});
+            timings.measure("detect workspace", || {});
+        }
+        timings.measure("execute", || {});
+
+        let spans = timings.spans();
+        let names: Vec<_> = spans.iter().map(|s| (s.name.as_str(), s.depth)).collect();
+        assert_eq!(
+            names,
+            [
+                ("startup", 0),
+                ("load config", 1),
+                ("detect workspace", 1),
+                ("execute", 0)
+            ]
+        );
+
+        let table = timings.render_table();
+        assert!(table.contains("  load config"));
+        assert!(table.contains("Total"));
+    }
+
+    #[test]
+    fn test_chrome_trace() {
+        let timings = Timings::new();
+        timings.record(
+            "step",
+            0,
+            timings.inner.origin + Duration::from_millis(2),
+            Duration::from_millis(5),
+        );
+
+        let trace = timings.to_chrome_trace();
+        let event = &trace["traceEvents"][0];
+        assert_eq!(event["name"], "step");
+        assert_eq!(event["ph"], "X");
+        assert_eq!(event["ts"], 2_000);
+        assert_eq!(event["dur"], 5_000);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_micros(850)), "850µs");
+        assert_eq!(format_duration(Duration::from_micros(12_345)), "12.3ms");
+        assert_eq!(format_duration(Duration::from_millis(1_520)), "1.52s");
+    }
+}
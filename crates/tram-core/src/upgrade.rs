@@ -0,0 +1,430 @@
+//! Upgrading generated projects to newer templates.
+//!
+//! `tram new` records the files it generated in `.tram/project.json`. An
+//! upgrade renders the current templates with the same settings and merges
+//! each file three ways: the recorded original is the common ancestor, the
+//! file on disk holds the user's edits, and the new render holds the template
+//! changes. Files that merge cleanly are written; conflicting files are left
+//! alone and reported, so the upgrade can be finished by hand.
+
+use crate::TramError;
+use crate::{
+    AppResult, ChangeSet, FileChange, InitConfig, InitProjectType, LicenseKind, ProjectInitializer,
+};
+use diffy::{ConflictStyle, MergeOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where a generated project's manifest lives, relative to its root.
+pub const PROJECT_MANIFEST: &str = ".tram/project.json";
+
+/// The settings a project was generated with, and the files it was
+/// generated with, as of its creation or last upgrade.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectManifest {
+    /// Version of tram whose templates produced `files`
+    pub tram_version: String,
+    pub name: String,
+    pub project_type: InitProjectType,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub license: Option<LicenseKind>,
+    /// Copyright year in the license; manifests from before licenses were
+    /// written take the current year
+    #[serde(default = "crate::current_year")]
+    pub year: i32,
+    pub git: bool,
+    pub ci: bool,
+    /// Template output, keyed by path relative to the project root
+    pub files: BTreeMap<String, String>,
+}
+
+impl ProjectManifest {
+    pub fn new(config: &InitConfig, files: BTreeMap<String, String>) -> Self {
+        Self {
+            tram_version: env!("CARGO_PKG_VERSION").to_string(),
+            name: config.name.clone(),
+            project_type: config.project_type.clone(),
+            description: config.description.clone(),
+            author: config.author.clone(),
+            license: config.license.clone(),
+            year: config.year,
+            git: config.git,
+            ci: config.ci,
+            files,
+        }
+    }
+
+    /// The manifest's path in the project at `root`.
+    pub fn path(root: &Path) -> PathBuf {
+        root.join(PROJECT_MANIFEST)
+    }
+
+    /// Read the manifest of the project at `root`.
+    pub fn load(root: &Path) -> AppResult<Self> {
+        let path = Self::path(root);
+        let contents = fs::read_to_string(&path).map_err(|e| TramError::Upgrade {
+            message: format!("Failed to read {}: {}", path.display(), e),
+        })?;
+
+        Ok(
+            serde_json::from_str(&contents).map_err(|e| TramError::Upgrade {
+                message: format!("Invalid project manifest {}: {}", path.display(), e),
+            })?,
+        )
+    }
+
+    pub fn to_json(&self) -> AppResult<String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| TramError::Upgrade {
+            message: format!("Failed to serialize project manifest: {}", e),
+        })?;
+
+        Ok(format!("{}\n", json))
+    }
+
+    /// The settings to render the templates with for the project at `root`.
+    pub fn init_config(&self, root: &Path) -> InitConfig {
+        InitConfig {
+            name: self.name.clone(),
+            path: root.to_path_buf(),
+            project_type: self.project_type.clone(),
+            description: self.description.clone(),
+            author: self.author.clone(),
+            license: self.license.clone(),
+            year: self.year,
+            git: self.git,
+            ci: self.ci,
+        }
+    }
+}
+
+/// What an upgrade did to a file the templates changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UpgradeStatus {
+    /// Unedited, so replaced with the new template
+    Updated,
+    /// Edited, and the template changes merged in cleanly
+    Merged,
+    /// New in the templates
+    Added,
+    /// Already matches the new template
+    UpToDate,
+    /// Edits overlap the template changes; left alone
+    Conflict,
+    /// Deleted from the project; not restored
+    Missing,
+}
+
+impl std::fmt::Display for UpgradeStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpgradeStatus::Updated => write!(f, "updated"),
+            UpgradeStatus::Merged => write!(f, "merged"),
+            UpgradeStatus::Added => write!(f, "added"),
+            UpgradeStatus::UpToDate => write!(f, "up to date"),
+            UpgradeStatus::Conflict => write!(f, "conflict"),
+            UpgradeStatus::Missing => write!(f, "missing"),
+        }
+    }
+}
+
+/// A file the new templates changed, and what the upgrade did with it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileUpgrade {
+    /// Path relative to the project root
+    pub path: String,
+    pub status: UpgradeStatus,
+    /// A unified diff of the change to the file on disk or, for a conflict,
+    /// the file with diff3 conflict markers showing the user's version, the
+    /// original template, and the new template
+    pub diff: Option<String>,
+}
+
+/// The result of upgrading a project.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpgradeReport {
+    pub path: PathBuf,
+    /// Version of tram the project was last generated or upgraded with
+    pub from_version: String,
+    pub to_version: String,
+    pub files: Vec<FileUpgrade>,
+    pub dry_run: bool,
+    pub changes: Vec<FileChange>,
+}
+
+impl UpgradeReport {
+    pub fn conflicts(&self) -> usize {
+        self.files
+            .iter()
+            .filter(|file| file.status == UpgradeStatus::Conflict)
+            .count()
+    }
+}
+
+/// Re-applies the current templates to a generated project.
+#[derive(Debug, Clone, Default)]
+pub struct ProjectUpgrader {
+    dry_run: bool,
+}
+
+impl ProjectUpgrader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report what would change without writing anything.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Upgrade the project at `root`, writing every file that merges cleanly
+    /// and recording the new template output for those files in the manifest.
+    pub fn upgrade(&self, root: &Path) -> AppResult<UpgradeReport> {
+        let manifest = ProjectManifest::load(root)?;
+        let rendered = ProjectInitializer::new().render(&manifest.init_config(root))?;
+
+        let mut changes = ChangeSet::new(self.dry_run);
+        let mut files = Vec::new();
+        let mut recorded = manifest.files.clone();
+
+        for (path, new) in &rendered {
+            let original = manifest.files.get(path);
+            if original == Some(new) {
+                continue;
+            }
+
+            let full_path = root.join(path);
+            let current = fs::read_to_string(&full_path).ok();
+            let (status, merged, diff) =
+                merge(path, original.map(String::as_str), current.as_deref(), new);
+
+            if let Some(merged) = merged {
+                changes.write_file(&full_path, merged)?;
+            }
+            if !matches!(status, UpgradeStatus::Conflict | UpgradeStatus::Missing) {
+                recorded.insert(path.clone(), new.clone());
+            }
+
+            files.push(FileUpgrade {
+                path: path.clone(),
+                status,
+                diff,
+            });
+        }
+
+        let upgraded = ProjectManifest {
+            tram_version: env!("CARGO_PKG_VERSION").to_string(),
+            files: recorded,
+            ..manifest.clone()
+        };
+        if upgraded != manifest {
+            changes.write_file(&ProjectManifest::path(root), upgraded.to_json()?)?;
+        }
+
+        Ok(UpgradeReport {
+            path: root.to_path_buf(),
+            from_version: manifest.tram_version,
+            to_version: upgraded.tram_version,
+            files,
+            dry_run: self.dry_run,
+            changes: changes.into_changes(),
+        })
+    }
+}
+
+/// Merge one file, returning its status, the contents to write if any, and
+/// the diff to show.
+fn merge(
+    path: &str,
+    original: Option<&str>,
+    current: Option<&str>,
+    new: &str,
+) -> (UpgradeStatus, Option<String>, Option<String>) {
+    let patch = |from: &str, to: &str| Some(crate::unified_diff(path, from, to));
+
+    match (original, current) {
+        (_, Some(current)) if current == new => (UpgradeStatus::UpToDate, None, None),
+        (None, None) => (UpgradeStatus::Added, Some(new.to_string()), patch("", new)),
+        (Some(_), None) => (UpgradeStatus::Missing, None, None),
+        (Some(original), Some(current)) if current == original => (
+            UpgradeStatus::Updated,
+            Some(new.to_string()),
+            patch(current, new),
+        ),
+        (original, Some(current)) => {
+            let mut options = MergeOptions::new();
+            options.set_conflict_style(ConflictStyle::Diff3);
+
+            match options.merge(original.unwrap_or_default(), current, new) {
+                Ok(merged) if merged == current => (UpgradeStatus::UpToDate, None, None),
+                Ok(merged) => {
+                    let diff = patch(current, &merged);
+                    (UpgradeStatus::Merged, Some(merged), diff)
+                }
+                Err(conflicted) => (
+                    UpgradeStatus::Conflict,
+                    None,
+                    Some(label_conflicts(&conflicted)),
+                ),
+            }
+        }
+    }
+}
+
+/// Name the sides of diff3 conflict markers after what they hold.
+fn label_conflicts(conflicted: &str) -> String {
+    conflicted
+        .lines()
+        .map(|line| match line {
+            "<<<<<<< ours" => "<<<<<<< local",
+            "||||||| original" => "||||||| original template",
+            ">>>>>>> theirs" => ">>>>>>> new template",
+            line => line,
+        })
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create(root: &Path) -> InitConfig {
+        let config = InitConfig {
+            name: "app".to_string(),
+            path: root.to_path_buf(),
+            project_type: InitProjectType::Rust,
+            description: None,
+            author: None,
+            license: None,
+            year: 2026,
+            git: false,
+            ci: false,
+        };
+        ProjectInitializer::new().create_project(&config).unwrap();
+        config
+    }
+
+    /// Pretend the project was generated by older templates.
+    fn age(root: &Path, path: &str, original: &str) {
+        let mut manifest = ProjectManifest::load(root).unwrap();
+        manifest.tram_version = "0.0.1".to_string();
+        manifest
+            .files
+            .insert(path.to_string(), original.to_string());
+        fs::write(ProjectManifest::path(root), manifest.to_json().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_new_project_records_manifest() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("app");
+        let config = create(&root);
+
+        let manifest = ProjectManifest::load(&root).unwrap();
+        assert_eq!(manifest.init_config(&root).name, config.name);
+        assert_eq!(
+            manifest.files.keys().collect::<Vec<_>>(),
+            ["Cargo.toml", "src/main.rs"]
+        );
+
+        let report = ProjectUpgrader::new().upgrade(&root).unwrap();
+        assert!(report.files.is_empty());
+        assert!(report.changes.is_empty());
+    }
+
+    #[test]
+    fn test_unedited_file_is_updated() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("app");
+        create(&root);
+
+        let old_main = "fn main() {\n    println!(\"Hi\");\n}\n";
+        fs::write(root.join("src/main.rs"), old_main).unwrap();
+        age(&root, "src/main.rs", old_main);
+
+        let report = ProjectUpgrader::new().upgrade(&root).unwrap();
+        assert_eq!(report.from_version, "0.0.1");
+        assert_eq!(report.files[0].status, UpgradeStatus::Updated);
+        let diff = report.files[0].diff.as_ref().unwrap();
+        assert!(diff.starts_with("--- a/src/main.rs\n+++ b/src/main.rs\n"));
+        assert!(diff.contains("+    println!(\"Hello, world!\");"));
+        assert!(
+            fs::read_to_string(root.join("src/main.rs"))
+                .unwrap()
+                .contains("Hello, world!")
+        );
+
+        // The manifest now records the new template, so nothing is left to do
+        let report = ProjectUpgrader::new().upgrade(&root).unwrap();
+        assert!(report.files.is_empty());
+    }
+
+    #[test]
+    fn test_edits_merge_with_template_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("app");
+        create(&root);
+
+        let cargo_toml = fs::read_to_string(root.join("Cargo.toml")).unwrap();
+        let original = cargo_toml.replace("edition = \"2021\"", "edition = \"2018\"");
+        let edited = format!("{}serde = \"1\"\n", original);
+        fs::write(root.join("Cargo.toml"), &edited).unwrap();
+        age(&root, "Cargo.toml", &original);
+
+        let report = ProjectUpgrader::new()
+            .with_dry_run(true)
+            .upgrade(&root)
+            .unwrap();
+        assert_eq!(report.files[0].status, UpgradeStatus::Merged);
+        assert_eq!(fs::read_to_string(root.join("Cargo.toml")).unwrap(), edited);
+
+        ProjectUpgrader::new().upgrade(&root).unwrap();
+        let merged = fs::read_to_string(root.join("Cargo.toml")).unwrap();
+        assert!(merged.contains("edition = \"2021\""));
+        assert!(merged.contains("serde = \"1\""));
+    }
+
+    #[test]
+    fn test_overlapping_edits_conflict() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("app");
+        create(&root);
+
+        let original = "fn main() {\n    println!(\"Hi\");\n}\n";
+        let edited = "fn main() {\n    println!(\"Howdy\");\n}\n";
+        fs::write(root.join("src/main.rs"), edited).unwrap();
+        age(&root, "src/main.rs", original);
+
+        let report = ProjectUpgrader::new().upgrade(&root).unwrap();
+        assert_eq!(report.conflicts(), 1);
+        let diff = report.files[0].diff.as_ref().unwrap();
+        assert!(diff.contains("<<<<<<< local\n"));
+        assert!(diff.contains(">>>>>>> new template\n"));
+        assert!(diff.contains("Hi"));
+        assert!(diff.contains("Howdy"));
+        assert!(diff.contains("Hello, world!"));
+        assert_eq!(
+            fs::read_to_string(root.join("src/main.rs")).unwrap(),
+            edited
+        );
+
+        // The conflict stays pending for the next upgrade
+        let manifest = ProjectManifest::load(&root).unwrap();
+        assert_eq!(manifest.files["src/main.rs"], original);
+    }
+
+    #[test]
+    fn test_project_without_manifest() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(ProjectUpgrader::new().upgrade(temp_dir.path()).is_err());
+    }
+}
@@ -0,0 +1,387 @@
+//! A configured HTTP client with retries and JSON helpers.
+
+use crate::retry::{RetryPolicy, is_retryable_error, is_retryable_status, parse_retry_after};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+use tram_config::HttpConfig;
+use tram_core::{AppResult, TramError};
+use ureq::http::Response;
+use ureq::tls::{PemItem, RootCerts, TlsConfig};
+use ureq::{Agent, Body, Proxy};
+
+/// User-Agent sent with every request unless changed.
+pub const DEFAULT_USER_AGENT: &str = concat!("tram/", env!("CARGO_PKG_VERSION"));
+
+/// An HTTP client. Cloning is cheap and clones share connections.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    agent: Agent,
+    retry: RetryPolicy,
+    user_agent: String,
+    pub(crate) progress: bool,
+}
+
+impl HttpClient {
+    /// A client with the default `[http]` settings: a 30 second timeout,
+    /// 3 retries, and the proxy from the environment.
+    pub fn new() -> Self {
+        let config = HttpConfig::default();
+
+        Self::with_agent(
+            agent_config(&config).build().into(),
+            RetryPolicy::new(config.retries),
+        )
+    }
+
+    /// A client using the config's `[http]` section. Fails if the proxy
+    /// URL is invalid or the CA file can't be read.
+    pub fn from_config(config: &HttpConfig) -> AppResult<Self> {
+        let mut builder = agent_config(config);
+
+        if let Some(proxy) = &config.proxy {
+            let proxy = Proxy::new(proxy).map_err(|e| TramError::InvalidConfig {
+                message: format!("Invalid HTTP proxy '{}': {}", proxy, e),
+            })?;
+            builder = builder.proxy(Some(proxy));
+        }
+
+        if let Some(ca_file) = &config.ca_file {
+            let pem = std::fs::read(ca_file).map_err(|e| TramError::InvalidConfig {
+                message: format!("Failed to read CA file {}: {}", ca_file.display(), e),
+            })?;
+
+            let certs: Vec<_> = ureq::tls::parse_pem(&pem)
+                .filter_map(|item| match item {
+                    Ok(PemItem::Certificate(cert)) => Some(cert),
+                    _ => None,
+                })
+                .collect();
+            if certs.is_empty() {
+                return Err(TramError::InvalidConfig {
+                    message: format!("No certificates found in CA file {}", ca_file.display()),
+                }
+                .into());
+            }
+
+            builder = builder.tls_config(
+                TlsConfig::builder()
+                    .root_certs(RootCerts::new_with_certs(&certs))
+                    .build(),
+            );
+        }
+
+        Ok(Self::with_agent(
+            builder.build().into(),
+            RetryPolicy::new(config.retries),
+        ))
+    }
+
+    fn with_agent(agent: Agent, retry: RetryPolicy) -> Self {
+        Self {
+            agent,
+            retry,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            progress: false,
+        }
+    }
+
+    /// Retry failed requests this many times.
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retry.retries = retries;
+        self
+    }
+
+    /// Wait this long before the first retry; each later retry waits twice
+    /// as long.
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.retry.backoff = backoff;
+        self
+    }
+
+    /// Identify as `user_agent`, e.g. `my-cli/1.2.0`.
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Draw a progress bar on standard error while downloading.
+    pub fn with_progress(mut self, progress: bool) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// The retry policy in use.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry
+    }
+
+    /// GET a URL and return the body as text.
+    pub fn get_text(&self, url: &str) -> AppResult<String> {
+        let mut response = self.get(url, "*/*")?;
+
+        response
+            .body_mut()
+            .read_to_string()
+            .map_err(|e| request_failed(url, e).into())
+    }
+
+    /// GET a URL and parse the JSON body.
+    pub fn get_json<T: DeserializeOwned>(&self, url: &str) -> AppResult<T> {
+        let response = self.get(url, "application/json")?;
+        parse_json(url, response)
+    }
+
+    /// POST `body` as JSON and parse the JSON response.
+    pub fn post_json<B, T>(&self, url: &str, body: &B) -> AppResult<T>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let body = serde_json::to_vec(body).map_err(|e| TramError::Network {
+            message: format!("Failed to encode the request to {}: {}", url, e),
+        })?;
+
+        let response = self.send(url, || {
+            self.agent
+                .post(url)
+                .header("User-Agent", &self.user_agent)
+                .header("Accept", "application/json")
+                .content_type("application/json")
+                .send(&body[..])
+        })?;
+
+        parse_json(url, response)
+    }
+
+    /// POST a raw `body` with extra `headers`, such as metrics in a
+    /// collector's format, ignoring the response body.
+    pub fn post(
+        &self,
+        url: &str,
+        content_type: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> AppResult<()> {
+        self.send(url, || {
+            let mut request = self.agent.post(url).header("User-Agent", &self.user_agent);
+            for (name, value) in headers {
+                request = request.header(name, value);
+            }
+
+            request.content_type(content_type).send(body)
+        })?;
+
+        Ok(())
+    }
+
+    /// GET a URL, retrying as configured.
+    pub(crate) fn get(&self, url: &str, accept: &str) -> AppResult<Response<Body>> {
+        self.send(url, || {
+            self.agent
+                .get(url)
+                .header("User-Agent", &self.user_agent)
+                .header("Accept", accept)
+                .call()
+        })
+    }
+
+    /// Send a request until it succeeds, fails in a way retrying won't fix,
+    /// or runs out of retries.
+    fn send(
+        &self,
+        url: &str,
+        request: impl Fn() -> Result<Response<Body>, ureq::Error>,
+    ) -> AppResult<Response<Body>> {
+        tram_core::ensure_online(|| format!("Request to {}", url))?;
+
+        let mut retry = 0;
+
+        loop {
+            let (failure, retry_after) = match request() {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) => {
+                    let failure = TramError::Network {
+                        message: format!("Request to {} failed: HTTP {}", url, response.status()),
+                    };
+                    if !is_retryable_status(response.status().as_u16()) {
+                        return Err(failure.into());
+                    }
+
+                    let retry_after = response
+                        .headers()
+                        .get("Retry-After")
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(parse_retry_after);
+                    (failure, retry_after)
+                }
+                Err(e) if is_retryable_error(&e) => (request_failed(url, e), None),
+                Err(e) => return Err(request_failed(url, e).into()),
+            };
+
+            if retry >= self.retry.retries {
+                return Err(failure.into());
+            }
+
+            let delay = self.retry.delay(retry, retry_after);
+            tracing::debug!("{}; retrying in {:?}", failure, delay);
+            std::thread::sleep(delay);
+            retry += 1;
+        }
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Settings shared by every agent: the timeout, and statuses returned as
+/// responses rather than errors so retries can tell them apart.
+fn agent_config(config: &HttpConfig) -> ureq::config::ConfigBuilder<ureq::typestate::AgentScope> {
+    Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(config.timeout_secs)))
+        .http_status_as_error(false)
+}
+
+fn parse_json<T: DeserializeOwned>(url: &str, mut response: Response<Body>) -> AppResult<T> {
+    let body = response
+        .body_mut()
+        .read_to_string()
+        .map_err(|e| request_failed(url, e))?;
+
+    serde_json::from_str(&body).map_err(|e| {
+        TramError::Network {
+            message: format!("Invalid JSON from {}: {}", url, e),
+        }
+        .into()
+    })
+}
+
+pub(crate) fn request_failed(url: &str, error: impl std::fmt::Display) -> TramError {
+    TramError::Network {
+        message: format!("Request to {} failed: {}", url, error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::TestServer;
+    use serde::Deserialize;
+    use tempfile::TempDir;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Release {
+        version: String,
+    }
+
+    fn client() -> HttpClient {
+        HttpClient::new().with_backoff(Duration::from_millis(1))
+    }
+
+    #[test]
+    fn test_get_json_retries_server_errors() {
+        let server = TestServer::start(vec![
+            (503, "", "busy"),
+            (429, "Retry-After: 0\r\n", ""),
+            (200, "", r#"{"version": "1.2.0"}"#),
+        ]);
+
+        let release: Release = client().get_json(&server.url).unwrap();
+        assert_eq!(release.version, "1.2.0");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].contains(DEFAULT_USER_AGENT));
+        assert!(requests[0].contains("application/json"));
+    }
+
+    #[test]
+    fn test_gives_up_on_client_errors_and_after_retries() {
+        let server = TestServer::start(vec![(404, "", "missing")]);
+        let error = client().get_text(&server.url).unwrap_err();
+        assert!(error.to_string().contains("404"));
+        assert_eq!(server.requests().len(), 1);
+        assert!(matches!(
+            error.downcast_ref::<TramError>(),
+            Some(TramError::Network { .. })
+        ));
+
+        let server = TestServer::start(vec![(500, "", ""), (502, "", "")]);
+        let error = client().with_retries(1).get_text(&server.url).unwrap_err();
+        assert!(error.to_string().contains("502"));
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn test_post_json() {
+        let server = TestServer::start(vec![(200, "", r#"{"version": "2.0.0"}"#)]);
+
+        let release: Release = client()
+            .with_user_agent("my-cli/0.1.0")
+            .post_json(&server.url, &serde_json::json!({ "channel": "beta" }))
+            .unwrap();
+        assert_eq!(release.version, "2.0.0");
+
+        let request = &server.requests()[0];
+        assert!(request.starts_with("POST / "));
+        assert!(request.contains("my-cli/0.1.0"));
+        assert!(
+            request
+                .to_lowercase()
+                .contains("content-type: application/json")
+        );
+        assert!(request.ends_with(r#"{"channel":"beta"}"#));
+    }
+
+    #[test]
+    fn test_post_with_headers_retries() {
+        let server = TestServer::start(vec![(503, "", ""), (202, "", "")]);
+
+        client()
+            .post(
+                &server.url,
+                "text/plain",
+                &[("Authorization".to_string(), "Bearer abc".to_string())],
+                b"metric 1\n",
+            )
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        let headers = requests[1].to_lowercase();
+        assert!(headers.contains("authorization: bearer abc"));
+        assert!(headers.contains("content-type: text/plain"));
+        assert!(requests[1].ends_with("metric 1\n"));
+    }
+
+    #[test]
+    fn test_invalid_json_is_a_network_error() {
+        let server = TestServer::start(vec![(200, "", "not json")]);
+        let error = client().get_json::<Release>(&server.url).unwrap_err();
+        assert!(error.to_string().contains("Invalid JSON"));
+    }
+
+    #[test]
+    fn test_from_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = |proxy: Option<&str>, ca_file: Option<&str>| HttpConfig {
+            proxy: proxy.map(str::to_string),
+            ca_file: ca_file.map(|name| temp_dir.path().join(name)),
+            retries: 5,
+            ..HttpConfig::default()
+        };
+
+        let client = HttpClient::from_config(&config(Some("http://proxy.internal:3128"), None));
+        assert_eq!(client.unwrap().retry_policy().retries, 5);
+
+        assert!(HttpClient::from_config(&config(Some("ftp://"), None)).is_err());
+        assert!(HttpClient::from_config(&config(None, Some("missing.pem"))).is_err());
+
+        std::fs::write(temp_dir.path().join("empty.pem"), "no certificates").unwrap();
+        let error = HttpClient::from_config(&config(None, Some("empty.pem"))).unwrap_err();
+        assert!(error.to_string().contains("No certificates"));
+    }
+}
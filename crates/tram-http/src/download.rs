@@ -0,0 +1,169 @@
+//! Downloading files, with progress.
+
+use crate::client::{HttpClient, request_failed};
+use std::io::{Read, Write};
+use std::path::Path;
+use tram_core::{AppResult, Cache, CacheKey, TramError};
+use tram_ui::ProgressBar;
+
+impl HttpClient {
+    /// Download a URL to `dest`, returning the number of bytes written.
+    /// The file is written next to `dest` and renamed into place once
+    /// complete, so an interrupted download never leaves a partial file.
+    pub fn download(&self, url: &str, dest: &Path) -> AppResult<u64> {
+        let partial = dest.with_file_name(format!(
+            ".{}.part",
+            dest.file_name().unwrap_or_default().to_string_lossy()
+        ));
+        let write_error = |path: &Path, e: std::io::Error| TramError::Network {
+            message: format!("Failed to write {}: {}", path.display(), e),
+        };
+
+        if let Some(parent) = dest.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent).map_err(|e| write_error(parent, e))?;
+        }
+
+        let mut file = std::fs::File::create(&partial).map_err(|e| write_error(&partial, e))?;
+        let written = self.fetch(url, &mut file).and_then(|written| {
+            file.sync_all().map_err(|e| write_error(&partial, e))?;
+            Ok(written)
+        });
+        drop(file);
+
+        match written {
+            Ok(written) => {
+                std::fs::rename(&partial, dest).map_err(|e| write_error(dest, e))?;
+                Ok(written)
+            }
+            Err(e) => {
+                let _ = std::fs::remove_file(&partial);
+                Err(e)
+            }
+        }
+    }
+
+    /// Download a URL into memory.
+    pub fn get_bytes(&self, url: &str) -> AppResult<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.fetch(url, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Download a URL into `cache`, keyed by the URL, or return the cached
+    /// copy without a request if there is one.
+    pub fn download_cached(&self, url: &str, cache: &Cache) -> AppResult<Vec<u8>> {
+        let key = CacheKey::new(url);
+        if let Some(bytes) = cache.get(&key)? {
+            return Ok(bytes);
+        }
+
+        let bytes = self.get_bytes(url)?;
+        cache.put(&key, &bytes)?;
+
+        Ok(bytes)
+    }
+
+    /// Stream a URL's body into `sink`, drawing a progress bar when enabled
+    /// and the server sends the size. Only the request is retried; a body
+    /// cut off partway fails.
+    pub(crate) fn fetch(&self, url: &str, sink: &mut impl Write) -> AppResult<u64> {
+        let mut response = self.get(url, "*/*")?;
+        let total = response
+            .headers()
+            .get("Content-Length")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+
+        let progress = match total {
+            Some(total) if self.progress => {
+                Some(ProgressBar::new(total).with_message(url.rsplit('/').next().unwrap_or(url)))
+            }
+            _ => None,
+        };
+
+        let mut reader = response.body_mut().as_reader();
+        let mut buffer = [0; 64 * 1024];
+        let mut written = 0;
+        let result = loop {
+            let read = match reader.read(&mut buffer) {
+                Ok(0) => break Ok(written),
+                Ok(read) => read,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => break Err(request_failed(url, e)),
+            };
+
+            if let Err(e) = sink.write_all(&buffer[..read]) {
+                break Err(TramError::Network {
+                    message: format!("Failed to save the download from {}: {}", url, e),
+                });
+            }
+
+            written += read as u64;
+            if let Some(progress) = &progress {
+                progress.inc(read as u64);
+            }
+        };
+
+        if let Some(progress) = progress {
+            progress.finish_and_clear();
+        }
+
+        Ok(result?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::TestServer;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_download_writes_the_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let dest = temp_dir.path().join("tools").join("tool.tar.gz");
+        let server = TestServer::start(vec![(200, "", "archive bytes")]);
+
+        let written = HttpClient::new()
+            .with_progress(true)
+            .download(&format!("{}/tool.tar.gz", server.url), &dest)
+            .unwrap();
+
+        assert_eq!(written, 13);
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), "archive bytes");
+        assert_eq!(
+            std::fs::read_dir(dest.parent().unwrap()).unwrap().count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_failed_download_leaves_nothing_behind() {
+        let temp_dir = TempDir::new().unwrap();
+        let dest = temp_dir.path().join("tool.tar.gz");
+        let server = TestServer::start(vec![(403, "", "denied")]);
+
+        assert!(HttpClient::new().download(&server.url, &dest).is_err());
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_download_cached_reuses_the_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = Cache::new(temp_dir.path()).unwrap();
+        let server = TestServer::start(vec![(200, "", "payload")]);
+        let client = HttpClient::new();
+
+        assert_eq!(
+            client.download_cached(&server.url, &cache).unwrap(),
+            b"payload"
+        );
+        assert_eq!(
+            client.download_cached(&server.url, &cache).unwrap(),
+            b"payload"
+        );
+        assert_eq!(server.requests().len(), 1);
+    }
+}
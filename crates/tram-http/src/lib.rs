@@ -0,0 +1,30 @@
+//! HTTP for Tram CLI applications.
+//!
+//! - [`HttpClient`] sends requests with the proxy, CA certificates, and
+//!   timeout from the config's `[http]` section, retrying connection
+//!   failures and 429/5xx responses with exponential backoff
+//! - JSON helpers ([`HttpClient::get_json`], [`HttpClient::post_json`])
+//!   serialize requests and parse responses into your types
+//! - [`HttpClient::post`] sends other bodies with extra headers, e.g.
+//!   metrics for a collector
+//! - [`HttpClient::download`] and [`HttpClient::download_cached`] fetch
+//!   files with a progress bar, the latter reusing copies in the cache
+//!   directory
+//! - [`HttpClient::download_verified`] checks a download against a
+//!   [`Checksum`] and optional minisign signature before it reaches the
+//!   cache, used by `tram-update` for self-updates
+//!
+//! Failures are [`TramError::Network`](tram_core::TramError::Network)
+//! errors naming the URL, so they exit with the network exit code.
+
+pub mod client;
+mod download;
+pub mod retry;
+pub mod verify;
+
+pub use client::*;
+pub use retry::*;
+pub use verify::*;
+
+#[cfg(test)]
+mod test_server;
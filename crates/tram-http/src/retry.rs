@@ -0,0 +1,109 @@
+//! When to retry a failed request, and how long to wait first.
+
+use std::time::Duration;
+
+/// Delay before the first retry; each later retry waits twice as long.
+pub const DEFAULT_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Longest wait between attempts, including waits a server asks for with
+/// `Retry-After`.
+pub const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How many times a request is retried, and the backoff between attempts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; 0 disables retrying
+    pub retries: u32,
+    /// Delay before the first retry
+    pub backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Retry `retries` times, starting at [`DEFAULT_BACKOFF`].
+    pub fn new(retries: u32) -> Self {
+        Self {
+            retries,
+            backoff: DEFAULT_BACKOFF,
+        }
+    }
+
+    /// The wait before retry number `retry` (starting at 0): the backoff
+    /// doubled for each earlier retry, or what the server asked for if
+    /// that's longer, capped at [`MAX_BACKOFF`].
+    pub fn delay(&self, retry: u32, retry_after: Option<Duration>) -> Duration {
+        let backoff = self
+            .backoff
+            .checked_mul(2u32.saturating_pow(retry))
+            .unwrap_or(MAX_BACKOFF);
+
+        backoff
+            .max(retry_after.unwrap_or_default())
+            .min(MAX_BACKOFF)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+/// Whether a response status is worth retrying: the server is overloaded,
+/// rate limiting, or failed in a way that may not happen again.
+pub fn is_retryable_status(status: u16) -> bool {
+    matches!(status, 408 | 429 | 500 | 502 | 503 | 504)
+}
+
+/// Whether a request that got no response is worth retrying. Connection
+/// failures and timeouts are; bad URLs, unknown hosts, and TLS failures
+/// won't fix themselves.
+pub(crate) fn is_retryable_error(error: &ureq::Error) -> bool {
+    matches!(
+        error,
+        ureq::Error::Io(_) | ureq::Error::Timeout(_) | ureq::Error::ConnectionFailed
+    )
+}
+
+/// A `Retry-After` header given in seconds. The HTTP-date form is rare
+/// enough from APIs that it's ignored.
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse().ok().map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_doubles_and_is_capped() {
+        let policy = RetryPolicy {
+            retries: 10,
+            backoff: Duration::from_secs(1),
+        };
+
+        assert_eq!(policy.delay(0, None), Duration::from_secs(1));
+        assert_eq!(policy.delay(2, None), Duration::from_secs(4));
+        assert_eq!(policy.delay(9, None), MAX_BACKOFF);
+        assert_eq!(policy.delay(40, None), MAX_BACKOFF);
+        assert_eq!(
+            policy.delay(0, Some(Duration::from_secs(7))),
+            Duration::from_secs(7)
+        );
+        assert_eq!(
+            policy.delay(0, Some(Duration::from_secs(3600))),
+            MAX_BACKOFF
+        );
+    }
+
+    #[test]
+    fn test_retryable() {
+        assert!(is_retryable_status(503));
+        assert!(is_retryable_status(429));
+        assert!(!is_retryable_status(404));
+        assert!(!is_retryable_status(501));
+        assert!(is_retryable_error(&ureq::Error::ConnectionFailed));
+        assert!(!is_retryable_error(&ureq::Error::HostNotFound));
+        assert_eq!(parse_retry_after(" 5 "), Some(Duration::from_secs(5)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+    }
+}
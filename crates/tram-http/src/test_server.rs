@@ -0,0 +1,66 @@
+//! A local HTTP server answering with canned responses, for tests.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+/// A server that answers one connection per response, in order, and
+/// records each request it receives.
+pub struct TestServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl TestServer {
+    /// Serve `responses` as `(status, headers, body)`.
+    pub fn start(responses: Vec<(u16, &'static str, &'static str)>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&requests);
+
+        std::thread::spawn(move || {
+            for (status, headers, body) in responses {
+                let Ok((stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut reader = BufReader::new(stream);
+
+                let mut request = String::new();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        content_length = value.trim().parse().unwrap_or(0);
+                    }
+                    request.push_str(&line);
+                }
+                let mut request_body = vec![0; content_length];
+                let _ = reader.read_exact(&mut request_body);
+                request.push_str(&String::from_utf8_lossy(&request_body));
+                recorded.lock().unwrap().push(request);
+
+                let response = format!(
+                    "HTTP/1.1 {} Canned\r\nContent-Length: {}\r\nConnection: close\r\n{}\r\n{}",
+                    status,
+                    body.len(),
+                    headers,
+                    body
+                );
+                let _ = reader.get_mut().write_all(response.as_bytes());
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// Requests received so far: the request line, headers, and body.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
@@ -0,0 +1,271 @@
+//! Downloads checked against a published checksum and signature.
+
+use crate::client::HttpClient;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tram_core::{AppResult, Cache, CacheKey, TramError};
+
+/// Distinguishes partial downloads written by the same process.
+static DOWNLOAD_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// The digest a download must have.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Checksum {
+    /// Hex-encoded SHA-256 digest
+    Sha256(String),
+}
+
+impl Checksum {
+    /// The SHA-256 digest from a checksum file in `sha256sum` format
+    /// (`<digest>  <file>`) or a bare digest.
+    pub fn sha256_from_file(contents: &str) -> Self {
+        Checksum::Sha256(
+            contents
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_lowercase(),
+        )
+    }
+}
+
+impl fmt::Display for Checksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Checksum::Sha256(digest) => write!(f, "sha256:{}", digest.to_lowercase()),
+        }
+    }
+}
+
+/// What a download must match: a checksum and, optionally, a minisign
+/// signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verification {
+    checksum: Checksum,
+    minisign: Option<(String, String)>,
+}
+
+impl Verification {
+    pub fn new(checksum: Checksum) -> Self {
+        Self {
+            checksum,
+            minisign: None,
+        }
+    }
+
+    /// Also require a minisign `signature` (the `.minisig` file's contents)
+    /// made with the base64 `public_key`.
+    pub fn with_minisign(
+        mut self,
+        signature: impl Into<String>,
+        public_key: impl Into<String>,
+    ) -> Self {
+        self.minisign = Some((signature.into(), public_key.into()));
+        self
+    }
+}
+
+impl From<Checksum> for Verification {
+    fn from(checksum: Checksum) -> Self {
+        Self::new(checksum)
+    }
+}
+
+impl HttpClient {
+    /// Download a URL into `cache` and return the file's path, once it
+    /// matches `verification`. The body is streamed to a temporary file in
+    /// the cache, hashed on the way, and moved into place only if it
+    /// verifies, so a corrupt or tampered download is never used. A copy
+    /// already cached for the same URL and checksum is returned without a
+    /// request.
+    pub fn download_verified(
+        &self,
+        url: &str,
+        verification: impl Into<Verification>,
+        cache: &Cache,
+    ) -> AppResult<PathBuf> {
+        let verification = verification.into();
+        let key = CacheKey::from_parts([url.to_string(), verification.checksum.to_string()]);
+        if let Some(path) = cache.path(&key)? {
+            return Ok(path);
+        }
+
+        let signature = match &verification.minisign {
+            Some((signature, public_key)) => Some((
+                minisign_verify::Signature::decode(signature)
+                    .map_err(|e| verify_error(url, format!("invalid signature: {}", e)))?,
+                minisign_verify::PublicKey::from_base64(public_key)
+                    .map_err(|e| verify_error(url, format!("invalid public key: {}", e)))?,
+            )),
+            None => None,
+        };
+
+        let partial = cache.root().join(format!(
+            ".download-{}-{}.part",
+            std::process::id(),
+            DOWNLOAD_COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = std::fs::File::create(&partial).map_err(|e| TramError::Network {
+            message: format!("Failed to write {}: {}", partial.display(), e),
+        })?;
+
+        let verified = (|| -> AppResult<()> {
+            let mut sink = VerifyingWriter {
+                file,
+                sha256: Sha256::new(),
+                minisign: match &signature {
+                    Some((signature, public_key)) => Some(
+                        public_key
+                            .verify_stream(signature)
+                            .map_err(|e| verify_error(url, e))?,
+                    ),
+                    None => None,
+                },
+            };
+            self.fetch(url, &mut sink)?;
+            sink.file.sync_all().map_err(|e| TramError::Network {
+                message: format!("Failed to write {}: {}", partial.display(), e),
+            })?;
+
+            let Checksum::Sha256(expected) = &verification.checksum;
+            let actual = format!("{:x}", sink.sha256.finalize());
+            if !expected.eq_ignore_ascii_case(&actual) {
+                return Err(verify_error(
+                    url,
+                    format!("checksum mismatch: expected {}, got {}", expected, actual),
+                )
+                .into());
+            }
+
+            if let Some(minisign) = &mut sink.minisign {
+                minisign
+                    .finalize()
+                    .map_err(|e| verify_error(url, format!("bad signature: {}", e)))?;
+            }
+
+            Ok(())
+        })();
+
+        if let Err(e) = verified {
+            let _ = std::fs::remove_file(&partial);
+            return Err(e);
+        }
+
+        cache.put_file(&key, &partial)?;
+        cache.path(&key)?.ok_or_else(|| {
+            TramError::Cache {
+                message: format!("The download from {} expired as it was cached", url),
+            }
+            .into()
+        })
+    }
+}
+
+/// Writes a download to a file while hashing it for verification.
+struct VerifyingWriter<'a> {
+    file: std::fs::File,
+    sha256: Sha256,
+    minisign: Option<minisign_verify::StreamVerifier<'a>>,
+}
+
+impl Write for VerifyingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.file.write(buf)?;
+        self.sha256.update(&buf[..written]);
+        if let Some(minisign) = &mut self.minisign {
+            minisign.update(&buf[..written]);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn verify_error(url: &str, message: impl fmt::Display) -> TramError {
+    TramError::Network {
+        message: format!("Download from {} failed verification: {}", url, message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::TestServer;
+    use tempfile::TempDir;
+
+    /// SHA-256 of `test`
+    const TEST_SHA256: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+    const PUBLIC_KEY: &str = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+    const SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RUQf6LRCGA9i559r3g7V1qNyJDApGip8MfqcadIgT9CuhV3EMhHoN1mGTkUidF/z7SrlQgXdy8ofjb7bNJJylDOocrCo8KLzZwo=
+trusted comment: timestamp:1633700835\tfile:test\tprehashed
+wLMDjy9FLAuxZ3q4NlEvkgtyhrr0gtTu6KC4KBJdITbbOeAi1zBIYo0v4iTgt8jJpIidRJnp94ABQkJAgAooBQ==";
+
+    #[test]
+    fn test_checksum_from_file() {
+        assert_eq!(
+            Checksum::sha256_from_file("ABC123  tram-linux-x86_64\n"),
+            Checksum::Sha256("abc123".into())
+        );
+        assert_eq!(Checksum::Sha256("ABC".into()).to_string(), "sha256:abc");
+    }
+
+    #[test]
+    fn test_download_verified_caches_matching_downloads() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = Cache::new(temp_dir.path()).unwrap();
+        let server = TestServer::start(vec![(200, "", "test")]);
+        let client = HttpClient::new();
+        let verification = Verification::new(Checksum::Sha256(TEST_SHA256.into()))
+            .with_minisign(SIGNATURE, PUBLIC_KEY);
+
+        let path = client
+            .download_verified(&server.url, verification.clone(), &cache)
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "test");
+        assert!(path.starts_with(cache.root()));
+
+        let again = client
+            .download_verified(&server.url, verification, &cache)
+            .unwrap();
+        assert_eq!(again, path);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn test_download_verified_rejects_mismatches() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = Cache::new(temp_dir.path()).unwrap();
+        let server = TestServer::start(vec![(200, "", "tampered"), (200, "", "tampered")]);
+        let client = HttpClient::new();
+
+        let error = client
+            .download_verified(&server.url, Checksum::Sha256("0".repeat(64)), &cache)
+            .unwrap_err();
+        assert!(error.to_string().contains("checksum mismatch"));
+
+        let tampered = format!("{:x}", Sha256::digest(b"tampered"));
+        let error = client
+            .download_verified(
+                &server.url,
+                Verification::new(Checksum::Sha256(tampered)).with_minisign(SIGNATURE, PUBLIC_KEY),
+                &cache,
+            )
+            .unwrap_err();
+        assert!(error.to_string().contains("bad signature"));
+
+        // Nothing was cached, and no partial downloads were left behind
+        assert!(cache.manifest().unwrap().entries.is_empty());
+        let leftovers = std::fs::read_dir(cache.root())
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".part"))
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+}
@@ -0,0 +1,97 @@
+//! Choosing a credential backend.
+
+use crate::file::EncryptedFileStore;
+use crate::os::OsKeychain;
+use crate::store::CredentialStore;
+use std::sync::Arc;
+use tram_core::{AppResult, TramError};
+
+/// Environment variable forcing a backend: `keychain` or `file`.
+pub const BACKEND_ENV: &str = "TRAM_KEYRING_BACKEND";
+
+/// Environment variable holding the encrypted file's passphrase. Without
+/// it the file is encrypted with a generated key file.
+pub const PASSPHRASE_ENV: &str = "TRAM_KEYRING_PASSPHRASE";
+
+/// Name of the encrypted credentials file in the data directory.
+const CREDENTIALS_FILE: &str = "credentials";
+
+/// Credential storage for an application. Cloning is cheap and clones
+/// share the backend.
+#[derive(Clone)]
+pub struct Keyring {
+    store: Arc<dyn CredentialStore>,
+}
+
+impl Keyring {
+    /// Keep credentials in `store`.
+    pub fn new(store: impl CredentialStore + 'static) -> Self {
+        Self {
+            store: Arc::new(store),
+        }
+    }
+
+    /// The OS keychain when it can be reached, otherwise an encrypted file
+    /// in the data directory. [`BACKEND_ENV`] picks one explicitly.
+    pub fn open_default() -> AppResult<Self> {
+        let backend = std::env::var(BACKEND_ENV).unwrap_or_default();
+
+        match backend.as_str() {
+            "keychain" => Ok(Self::new(OsKeychain::new())),
+            "file" => Self::encrypted_file(),
+            "" if OsKeychain::new().is_available(tram_core::APP_NAME) => {
+                Ok(Self::new(OsKeychain::new()))
+            }
+            "" => Self::encrypted_file(),
+            other => Err(TramError::InvalidConfig {
+                message: format!(
+                    "Unknown {} '{}' (expected keychain or file)",
+                    BACKEND_ENV, other
+                ),
+            }
+            .into()),
+        }
+    }
+
+    /// The encrypted file in the data directory, with the passphrase from
+    /// [`PASSPHRASE_ENV`] when set.
+    fn encrypted_file() -> AppResult<Self> {
+        let dir = tram_core::data_dir().ok_or_else(|| TramError::Secret {
+            message: "No data directory for the credentials file".to_string(),
+        })?;
+        let mut store = EncryptedFileStore::new(dir.join(CREDENTIALS_FILE));
+        if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+            store = store.with_passphrase(passphrase);
+        }
+
+        Ok(Self::new(store))
+    }
+
+    /// Name of the backend in use.
+    pub fn backend(&self) -> &str {
+        self.store.name()
+    }
+
+    /// Save a credential, replacing any existing one.
+    pub fn store(&self, service: &str, key: &str, secret: &str) -> AppResult<()> {
+        self.store.store(service, key, secret)
+    }
+
+    /// Look up a credential.
+    pub fn get(&self, service: &str, key: &str) -> AppResult<Option<String>> {
+        self.store.get(service, key)
+    }
+
+    /// Remove a credential, returning whether one was stored.
+    pub fn delete(&self, service: &str, key: &str) -> AppResult<bool> {
+        self.store.delete(service, key)
+    }
+}
+
+impl std::fmt::Debug for Keyring {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Keyring")
+            .field("backend", &self.backend())
+            .finish()
+    }
+}
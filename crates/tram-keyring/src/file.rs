@@ -0,0 +1,342 @@
+//! An encrypted credentials file, for machines without a usable keychain
+//! such as headless Linux servers and containers.
+//!
+//! Credentials are kept as one file encrypted with XChaCha20-Poly1305. The
+//! key is either derived from a passphrase with Argon2id or, by default,
+//! read from a random key file next to the credentials. A key file only
+//! protects the credentials if it is kept apart from them (e.g. the
+//! credentials are backed up but the key isn't); both are written readable
+//! by the owner only.
+
+use crate::store::CredentialStore;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tram_core::{AppResult, TramError};
+
+/// First bytes of a credentials file, including the format version.
+const MAGIC: &[u8] = b"TRAMKR1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+
+/// Credentials by service, then key.
+type Credentials = BTreeMap<String, BTreeMap<String, String>>;
+
+/// Where the encryption key comes from.
+#[derive(Clone)]
+enum KeySource {
+    KeyFile(PathBuf),
+    Passphrase(String),
+}
+
+/// Credentials in an encrypted file.
+#[derive(Clone)]
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    key: KeySource,
+}
+
+impl EncryptedFileStore {
+    /// Credentials in `path`, encrypted with a random key kept in
+    /// `<path>.key` and created on first write.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let mut key_file = path.clone().into_os_string();
+        key_file.push(".key");
+
+        Self {
+            key: KeySource::KeyFile(key_file.into()),
+            path,
+        }
+    }
+
+    /// Derive the key from `passphrase` instead of using a key file.
+    pub fn with_passphrase(mut self, passphrase: impl Into<String>) -> Self {
+        self.key = KeySource::Passphrase(passphrase.into());
+        self
+    }
+
+    /// The credentials file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load(&self) -> AppResult<Credentials> {
+        let data = match fs::read(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Credentials::new()),
+            Err(e) => return Err(io_error("read", &self.path, e).into()),
+        };
+
+        let header_len = MAGIC.len() + SALT_LEN;
+        if data.len() < header_len + NONCE_LEN || !data.starts_with(MAGIC) {
+            return Err(
+                file_error(format!("{} is not a credentials file", self.path.display())).into(),
+            );
+        }
+
+        let (header, rest) = data.split_at(header_len);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        let key = self.cipher_key(&header[MAGIC.len()..], false)?;
+
+        let plaintext = XChaCha20Poly1305::new(&key.into())
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: header,
+                },
+            )
+            .map_err(|_| {
+                file_error(format!(
+                    "Failed to decrypt {}: wrong key or passphrase, or the file is damaged",
+                    self.path.display()
+                ))
+            })?;
+
+        serde_json::from_slice(&plaintext).map_err(|e| {
+            file_error(format!(
+                "Invalid credentials in {}: {}",
+                self.path.display(),
+                e
+            ))
+            .into()
+        })
+    }
+
+    fn save(&self, credentials: &Credentials) -> AppResult<()> {
+        let mut salt = [0; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let key = self.cipher_key(&salt, true)?;
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+        let mut header = MAGIC.to_vec();
+        header.extend_from_slice(&salt);
+
+        let plaintext = serde_json::to_vec(credentials)
+            .map_err(|e| file_error(format!("Failed to serialize credentials: {}", e)))?;
+        let ciphertext = XChaCha20Poly1305::new(&key.into())
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &plaintext,
+                    aad: &header,
+                },
+            )
+            .map_err(|_| file_error("Failed to encrypt credentials".to_string()))?;
+
+        let mut data = header;
+        data.extend_from_slice(&nonce);
+        data.extend_from_slice(&ciphertext);
+
+        write_private(&self.path, &data)
+    }
+
+    /// The encryption key, creating the key file if `create` is set.
+    fn cipher_key(&self, salt: &[u8], create: bool) -> AppResult<[u8; KEY_LEN]> {
+        let mut key = [0; KEY_LEN];
+
+        match &self.key {
+            KeySource::Passphrase(passphrase) => {
+                argon2::Argon2::default()
+                    .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+                    .map_err(|e| file_error(format!("Failed to derive the key: {}", e)))?;
+            }
+            KeySource::KeyFile(path) => match fs::read(path) {
+                Ok(data) if data.len() == KEY_LEN => key.copy_from_slice(&data),
+                Ok(_) => {
+                    return Err(file_error(format!("{} is not a key file", path.display())).into());
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound && create => {
+                    OsRng.fill_bytes(&mut key);
+                    write_private(path, &key)?;
+                }
+                Err(e) => return Err(io_error("read", path, e).into()),
+            },
+        }
+
+        Ok(key)
+    }
+}
+
+impl std::fmt::Debug for EncryptedFileStore {
+    // Never print the passphrase
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptedFileStore")
+            .field("path", &self.path)
+            .field(
+                "key",
+                &match &self.key {
+                    KeySource::KeyFile(path) => path.display().to_string(),
+                    KeySource::Passphrase(_) => "<passphrase>".to_string(),
+                },
+            )
+            .finish()
+    }
+}
+
+impl CredentialStore for EncryptedFileStore {
+    fn name(&self) -> &str {
+        "encrypted-file"
+    }
+
+    fn store(&self, service: &str, key: &str, secret: &str) -> AppResult<()> {
+        let mut credentials = self.load()?;
+        credentials
+            .entry(service.to_string())
+            .or_default()
+            .insert(key.to_string(), secret.to_string());
+
+        self.save(&credentials)
+    }
+
+    fn get(&self, service: &str, key: &str) -> AppResult<Option<String>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+
+        Ok(self
+            .load()?
+            .get(service)
+            .and_then(|keys| keys.get(key))
+            .cloned())
+    }
+
+    fn delete(&self, service: &str, key: &str) -> AppResult<bool> {
+        if !self.path.exists() {
+            return Ok(false);
+        }
+
+        let mut credentials = self.load()?;
+        let Some(keys) = credentials.get_mut(service) else {
+            return Ok(false);
+        };
+        if keys.remove(key).is_none() {
+            return Ok(false);
+        }
+        if keys.is_empty() {
+            credentials.remove(service);
+        }
+
+        self.save(&credentials)?;
+        Ok(true)
+    }
+}
+
+/// Atomically write a file only its owner can read.
+fn write_private(path: &Path, data: &[u8]) -> AppResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| io_error("create", parent, e))?;
+    }
+
+    let mut staged = path.as_os_str().to_owned();
+    staged.push(".tmp");
+    let staged = PathBuf::from(staged);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let written = options.open(&staged).and_then(|mut file| {
+        std::io::Write::write_all(&mut file, data)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|_| fs::rename(&staged, path)) {
+        let _ = fs::remove_file(&staged);
+        return Err(io_error("write", path, e).into());
+    }
+
+    Ok(())
+}
+
+fn io_error(action: &str, path: &Path, e: std::io::Error) -> TramError {
+    file_error(format!("Failed to {} {}: {}", action, path.display(), e))
+}
+
+fn file_error(message: String) -> TramError {
+    TramError::Secret { message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_store_get_delete() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = EncryptedFileStore::new(temp_dir.path().join("credentials"));
+
+        assert_eq!(store.get("tram", "registry").unwrap(), None);
+        assert!(!store.delete("tram", "registry").unwrap());
+
+        store.store("tram", "registry", "s3cr3t").unwrap();
+        store.store("other", "registry", "other").unwrap();
+        assert_eq!(
+            store.get("tram", "registry").unwrap().as_deref(),
+            Some("s3cr3t")
+        );
+        assert_eq!(
+            store.get("other", "registry").unwrap().as_deref(),
+            Some("other")
+        );
+
+        // Nothing is stored in the clear
+        let data = fs::read(store.path()).unwrap();
+        assert!(!data.windows(6).any(|window| window == b"s3cr3t"));
+
+        assert!(store.delete("tram", "registry").unwrap());
+        assert_eq!(store.get("tram", "registry").unwrap(), None);
+        assert_eq!(
+            store.get("other", "registry").unwrap().as_deref(),
+            Some("other")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_files_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let store = EncryptedFileStore::new(temp_dir.path().join("credentials"));
+        store.store("tram", "registry", "s3cr3t").unwrap();
+
+        for name in ["credentials", "credentials.key"] {
+            let mode = fs::metadata(temp_dir.path().join(name))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_passphrase_must_match() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("credentials");
+
+        EncryptedFileStore::new(&path)
+            .with_passphrase("correct horse")
+            .store("tram", "registry", "s3cr3t")
+            .unwrap();
+        assert!(!temp_dir.path().join("credentials.key").exists());
+
+        let store = EncryptedFileStore::new(&path).with_passphrase("correct horse");
+        assert_eq!(
+            store.get("tram", "registry").unwrap().as_deref(),
+            Some("s3cr3t")
+        );
+
+        let err = EncryptedFileStore::new(&path)
+            .with_passphrase("battery staple")
+            .get("tram", "registry")
+            .unwrap_err();
+        assert!(err.to_string().contains("wrong key or passphrase"));
+    }
+}
@@ -0,0 +1,26 @@
+//! Credential storage for Tram CLI applications.
+//!
+//! [`Keyring`] stores, reads, and deletes credentials by service and key:
+//!
+//! - [`OsKeychain`] uses the macOS Keychain, Windows Credential Manager,
+//!   or the Secret Service (GNOME Keyring, KWallet) on Linux
+//! - [`EncryptedFileStore`] is the fallback where no keychain is running,
+//!   such as servers and containers: an XChaCha20-Poly1305 encrypted file
+//!   with a generated key file or a passphrase
+//! - [`KeyringSecretProvider`] lets `secret://keyring/<key>` references in
+//!   config resolve from the keyring
+//!
+//! Other backends plug in by implementing [`CredentialStore`]. Failures are
+//! [`TramError::Secret`](tram_core::TramError::Secret) errors.
+
+pub mod backend;
+pub mod file;
+pub mod os;
+pub mod provider;
+pub mod store;
+
+pub use backend::*;
+pub use file::*;
+pub use os::*;
+pub use provider::*;
+pub use store::*;
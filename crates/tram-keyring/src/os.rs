@@ -0,0 +1,70 @@
+//! The operating system's keychain.
+//!
+//! macOS Keychain, Windows Credential Manager, or the Secret Service
+//! (GNOME Keyring, KWallet) on Linux and the BSDs.
+
+use crate::store::CredentialStore;
+use tram_core::{AppResult, TramError};
+
+/// Key looked up to find out whether the keychain can be reached.
+const PROBE_KEY: &str = "tram-keyring-probe";
+
+/// Credentials in the OS keychain.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsKeychain;
+
+impl OsKeychain {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Whether the keychain can be used, e.g. there is a Secret Service
+    /// running on Linux. Missing the probe entry counts as available.
+    pub fn is_available(&self, service: &str) -> bool {
+        match keyring::Entry::new(service, PROBE_KEY).and_then(|entry| entry.get_password()) {
+            Ok(_) | Err(keyring::Error::NoEntry) => true,
+            Err(e) => {
+                tracing::debug!("OS keychain unavailable: {}", e);
+                false
+            }
+        }
+    }
+}
+
+impl CredentialStore for OsKeychain {
+    fn name(&self) -> &str {
+        "keychain"
+    }
+
+    fn store(&self, service: &str, key: &str, secret: &str) -> AppResult<()> {
+        entry(service, key)?
+            .set_password(secret)
+            .map_err(|e| keychain_error(format!("Failed to save '{}': {}", key, e)).into())
+    }
+
+    fn get(&self, service: &str, key: &str) -> AppResult<Option<String>> {
+        match entry(service, key)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(keychain_error(format!("Failed to read '{}': {}", key, e)).into()),
+        }
+    }
+
+    fn delete(&self, service: &str, key: &str) -> AppResult<bool> {
+        match entry(service, key)?.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(e) => Err(keychain_error(format!("Failed to delete '{}': {}", key, e)).into()),
+        }
+    }
+}
+
+fn entry(service: &str, key: &str) -> AppResult<keyring::Entry> {
+    keyring::Entry::new(service, key).map_err(|e| {
+        keychain_error(format!("Failed to open keychain entry '{}': {}", key, e)).into()
+    })
+}
+
+fn keychain_error(message: String) -> TramError {
+    TramError::Secret { message }
+}
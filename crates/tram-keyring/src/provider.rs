@@ -0,0 +1,57 @@
+//! `secret://` references resolved from the keyring.
+
+use crate::backend::Keyring;
+use tram_core::{AppResult, SecretProvider};
+
+/// Secrets stored in a [`Keyring`] under one service, for
+/// [`SecretResolver`](tram_core::SecretResolver): `secret://keyring/token`
+/// reads the `token` credential.
+#[derive(Debug, Clone)]
+pub struct KeyringSecretProvider {
+    keyring: Keyring,
+    service: String,
+}
+
+impl KeyringSecretProvider {
+    /// Look up secrets stored under the given service name.
+    pub fn new(keyring: Keyring, service: impl Into<String>) -> Self {
+        Self {
+            keyring,
+            service: service.into(),
+        }
+    }
+}
+
+impl SecretProvider for KeyringSecretProvider {
+    fn name(&self) -> &str {
+        "keyring"
+    }
+
+    fn get(&self, key: &str) -> AppResult<Option<String>> {
+        self.keyring.get(&self.service, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EncryptedFileStore;
+    use tempfile::TempDir;
+    use tram_core::SecretResolver;
+
+    #[test]
+    fn test_resolves_keyring_references() {
+        let temp_dir = TempDir::new().unwrap();
+        let keyring = Keyring::new(EncryptedFileStore::new(temp_dir.path().join("credentials")));
+        keyring.store("tram", "registry", "s3cr3t").unwrap();
+
+        let resolver =
+            SecretResolver::new().with_provider(KeyringSecretProvider::new(keyring, "tram"));
+
+        assert_eq!(
+            resolver.resolve("secret://keyring/registry").unwrap(),
+            "s3cr3t"
+        );
+        assert!(resolver.resolve("secret://missing").is_err());
+    }
+}
@@ -0,0 +1,21 @@
+//! The interface every credential backend implements.
+
+use tram_core::AppResult;
+
+/// A place credentials are kept, addressed by service and key.
+///
+/// The service groups an application's credentials (usually the app name);
+/// the key names one of them, such as `registry` or `github`.
+pub trait CredentialStore: Send + Sync {
+    /// Short name of the backend, shown by status commands.
+    fn name(&self) -> &str;
+
+    /// Save a credential, replacing any existing one.
+    fn store(&self, service: &str, key: &str, secret: &str) -> AppResult<()>;
+
+    /// Look up a credential, returning `None` if it isn't stored.
+    fn get(&self, service: &str, key: &str) -> AppResult<Option<String>>;
+
+    /// Remove a credential, returning whether one was stored.
+    fn delete(&self, service: &str, key: &str) -> AppResult<bool>;
+}
@@ -0,0 +1,26 @@
+//! Versioned migrations of data and config files for Tram CLI applications.
+//!
+//! When a release changes the shape of a file it keeps between runs, it
+//! registers a [`Migration`] to the next data version with
+//! [`register_migration`]. At startup a [`Migrator`] compares the version
+//! recorded in the state store with the latest migration and runs whatever
+//! is pending, in order:
+//!
+//! - migrations read and write files through [`MigrationFiles`], which
+//!   stages every change in memory, so a failing migration leaves the files
+//!   as they were
+//! - once all of them succeed, the files about to change are copied to a
+//!   backup under `backups/` with a manifest, then replaced atomically
+//! - the new version is recorded, and a [`MigrationReport`] says what ran
+//!
+//! Files written by a newer release than the running one are refused
+//! rather than misread. Failures are
+//! [`TramError::Migration`](tram_core::TramError::Migration) errors.
+
+pub mod migration;
+pub mod migrator;
+pub mod registry;
+
+pub use migration::*;
+pub use migrator::*;
+pub use registry::*;
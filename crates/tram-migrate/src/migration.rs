@@ -0,0 +1,215 @@
+//! Migrations and the files they change.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tram_core::{AppResult, TramError};
+
+type Apply = dyn Fn(&mut MigrationFiles) -> AppResult<()> + Send + Sync;
+
+/// One step from a data version to the next.
+#[derive(Clone)]
+pub struct Migration {
+    /// The data version once this migration has run; versions start at 1
+    pub version: u32,
+    /// What changes, e.g. `Rename cache.ttl to cache.ttlSecs`
+    pub description: String,
+    apply: Arc<Apply>,
+}
+
+impl Migration {
+    /// A migration to `version` that rewrites files through
+    /// [`MigrationFiles`]. It should leave files it doesn't find alone, as
+    /// a fresh install has none.
+    pub fn new(
+        version: u32,
+        description: impl Into<String>,
+        apply: impl Fn(&mut MigrationFiles) -> AppResult<()> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            version,
+            description: description.into(),
+            apply: Arc::new(apply),
+        }
+    }
+
+    pub(crate) fn apply(&self, files: &mut MigrationFiles) -> AppResult<()> {
+        (self.apply)(files)
+    }
+}
+
+impl fmt::Debug for Migration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Migration")
+            .field("version", &self.version)
+            .field("description", &self.description)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Files as the migrations so far have left them.
+///
+/// Reads come from disk the first time and from earlier writes after
+/// that; nothing is written to disk until every pending migration has
+/// succeeded. Relative paths are resolved against the migrator's root.
+#[derive(Debug)]
+pub struct MigrationFiles {
+    root: PathBuf,
+    /// Contents on disk before migrating, `None` for missing files
+    original: BTreeMap<PathBuf, Option<String>>,
+    /// Contents after the migrations so far
+    current: BTreeMap<PathBuf, Option<String>>,
+}
+
+impl MigrationFiles {
+    pub(crate) fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            original: BTreeMap::new(),
+            current: BTreeMap::new(),
+        }
+    }
+
+    /// The absolute path of a file.
+    pub fn path(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.root.join(path)
+    }
+
+    /// A file's contents, or `None` if it doesn't exist.
+    pub fn read(&mut self, path: impl AsRef<Path>) -> AppResult<Option<String>> {
+        let path = self.load(path.as_ref())?;
+        Ok(self.current[&path].clone())
+    }
+
+    pub fn exists(&mut self, path: impl AsRef<Path>) -> AppResult<bool> {
+        let path = self.load(path.as_ref())?;
+        Ok(self.current[&path].is_some())
+    }
+
+    /// Replace a file's contents, creating it if needed.
+    pub fn write(&mut self, path: impl AsRef<Path>, contents: impl Into<String>) -> AppResult<()> {
+        let path = self.load(path.as_ref())?;
+        self.current.insert(path, Some(contents.into()));
+        Ok(())
+    }
+
+    pub fn remove(&mut self, path: impl AsRef<Path>) -> AppResult<()> {
+        let path = self.load(path.as_ref())?;
+        self.current.insert(path, None);
+        Ok(())
+    }
+
+    /// A JSON file's contents, or `None` if it doesn't exist.
+    pub fn read_json(&mut self, path: impl AsRef<Path>) -> AppResult<Option<Value>> {
+        let path = path.as_ref();
+
+        match self.read(path)? {
+            Some(contents) => serde_json::from_str(&contents).map(Some).map_err(|e| {
+                migration_error(format!(
+                    "{} is not valid JSON: {}",
+                    self.path(path).display(),
+                    e
+                ))
+                .into()
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /// Replace a JSON file's contents, pretty-printed.
+    pub fn write_json(&mut self, path: impl AsRef<Path>, value: &Value) -> AppResult<()> {
+        let contents = serde_json::to_string_pretty(value).map_err(|e| {
+            migration_error(format!(
+                "Failed to serialize {}: {}",
+                self.path(path.as_ref()).display(),
+                e
+            ))
+        })?;
+
+        self.write(path, contents)
+    }
+
+    /// Files whose contents differ from what's on disk, with their new
+    /// contents (`None` to remove them).
+    pub(crate) fn changes(&self) -> Vec<(&Path, Option<&str>)> {
+        self.current
+            .iter()
+            .filter(|(path, contents)| self.original.get(*path) != Some(*contents))
+            .map(|(path, contents)| (path.as_path(), contents.as_deref()))
+            .collect()
+    }
+
+    /// Read a file from disk the first time it's used.
+    fn load(&mut self, path: &Path) -> AppResult<PathBuf> {
+        let path = self.path(path);
+
+        if !self.original.contains_key(&path) {
+            let contents = match fs::read_to_string(&path) {
+                Ok(contents) => Some(contents),
+                Err(e) if e.kind() == ErrorKind::NotFound => None,
+                Err(e) => {
+                    return Err(migration_error(format!(
+                        "Failed to read {}: {}",
+                        path.display(),
+                        e
+                    ))
+                    .into());
+                }
+            };
+
+            self.original.insert(path.clone(), contents.clone());
+            self.current.insert(path.clone(), contents);
+        }
+
+        Ok(path)
+    }
+}
+
+pub(crate) fn migration_error(message: String) -> TramError {
+    TramError::Migration { message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_writes_are_staged() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("state.json"), r#"{"a": 1}"#).unwrap();
+        let mut files = MigrationFiles::new(dir.path());
+
+        let mut state = files.read_json("state.json").unwrap().unwrap();
+        state["b"] = json!(2);
+        files.write_json("state.json", &state).unwrap();
+        files.write("new.txt", "hello").unwrap();
+        files.write("gone.txt", "temporary").unwrap();
+        files.remove("gone.txt").unwrap();
+
+        assert_eq!(files.read_json("state.json").unwrap().unwrap()["b"], 2);
+        assert!(files.exists("new.txt").unwrap());
+        assert!(!dir.path().join("new.txt").exists());
+
+        let changed: Vec<&Path> = files.changes().into_iter().map(|(path, _)| path).collect();
+        assert_eq!(
+            changed,
+            [dir.path().join("new.txt"), dir.path().join("state.json")]
+        );
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("state.json"), "{").unwrap();
+
+        let error = MigrationFiles::new(dir.path())
+            .read_json("state.json")
+            .unwrap_err();
+        assert!(error.to_string().contains("not valid JSON"));
+    }
+}
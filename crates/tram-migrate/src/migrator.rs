@@ -0,0 +1,443 @@
+//! Running pending migrations with backups.
+
+use crate::migration::{Migration, MigrationFiles, migration_error};
+use crate::registry::registered_migrations;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+use tram_core::{AppResult, StateStore};
+
+/// State key holding the data version the files are at.
+pub const DATA_VERSION_KEY: &str = "dataVersion";
+
+/// Directory under the root that backups are written to.
+pub const BACKUPS_DIR: &str = "backups";
+
+/// Name of the file in each backup that lists what it holds.
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// A migration that ran.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub description: String,
+}
+
+/// What [`Migrator::run`] did.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationReport {
+    pub from: u32,
+    pub to: u32,
+    pub applied: Vec<AppliedMigration>,
+    /// Files written or removed
+    pub changed: Vec<PathBuf>,
+    /// Where the previous contents were saved, if any file changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup: Option<PathBuf>,
+}
+
+impl MigrationReport {
+    /// Whether nothing was pending.
+    pub fn is_empty(&self) -> bool {
+        self.applied.is_empty()
+    }
+}
+
+/// Lists the files in a backup so it can be restored.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    /// The data version the backed-up files are at
+    version: u32,
+    files: Vec<BackedUpFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackedUpFile {
+    path: PathBuf,
+    /// Name of the copy in the backup, `None` for files the migration
+    /// created
+    backup: Option<String>,
+}
+
+/// Brings files up to the latest data version.
+///
+/// The version the files are at is kept in the state store under
+/// [`DATA_VERSION_KEY`]; with no version recorded, every migration runs.
+#[derive(Debug, Clone)]
+pub struct Migrator {
+    root: PathBuf,
+    state: StateStore,
+    migrations: Vec<Migration>,
+}
+
+impl Migrator {
+    /// Migrate files under `root`, tracking the version in `state`.
+    pub fn new(root: impl Into<PathBuf>, state: StateStore) -> Self {
+        Self {
+            root: root.into(),
+            state,
+            migrations: Vec::new(),
+        }
+    }
+
+    /// Migrate the data directory with every registered migration.
+    pub fn open_default() -> AppResult<Self> {
+        let root = tram_core::data_dir()
+            .ok_or_else(|| migration_error("Unable to determine the data directory".to_string()))?;
+
+        Ok(Self::new(root, StateStore::open_default()?).with_migrations(registered_migrations()))
+    }
+
+    pub fn with_migration(mut self, migration: Migration) -> Self {
+        self.migrations.push(migration);
+        self.migrations.sort_by_key(|m| m.version);
+        self
+    }
+
+    pub fn with_migrations(mut self, migrations: impl IntoIterator<Item = Migration>) -> Self {
+        self.migrations.extend(migrations);
+        self.migrations.sort_by_key(|m| m.version);
+        self
+    }
+
+    /// Every migration, in version order.
+    pub fn migrations(&self) -> &[Migration] {
+        &self.migrations
+    }
+
+    /// The version the files are at, `0` if none is recorded.
+    pub fn current_version(&self) -> AppResult<u32> {
+        Ok(self.state.get(DATA_VERSION_KEY)?.unwrap_or(0))
+    }
+
+    /// The version of the last migration, `0` without any.
+    pub fn latest_version(&self) -> u32 {
+        self.migrations.last().map(|m| m.version).unwrap_or(0)
+    }
+
+    /// Migrations newer than the files, in the order they'll run.
+    ///
+    /// Fails when two migrations share a version, or the files are at a
+    /// version newer than any migration, as written by a later release.
+    pub fn pending(&self) -> AppResult<Vec<&Migration>> {
+        if let Some(pair) = self
+            .migrations
+            .windows(2)
+            .find(|pair| pair[0].version == pair[1].version)
+        {
+            return Err(migration_error(format!(
+                "Two migrations to version {}: '{}' and '{}'",
+                pair[0].version, pair[0].description, pair[1].description
+            ))
+            .into());
+        }
+
+        let current = self.current_version()?;
+        let latest = self.latest_version();
+        if current > latest {
+            return Err(migration_error(format!(
+                "The data in {} is at version {}, but this release only understands up to version {}; upgrade to a newer release",
+                self.root.display(),
+                current,
+                latest
+            ))
+            .into());
+        }
+
+        Ok(self
+            .migrations
+            .iter()
+            .filter(|m| m.version > current)
+            .collect())
+    }
+
+    /// Run every pending migration and record the new version.
+    ///
+    /// Migrations run against staged copies of the files. Only when all of
+    /// them succeed are the originals backed up and the new contents written,
+    /// each through a temporary file; if writing fails part way, the backup
+    /// is put back. A failed migration changes nothing.
+    pub fn run(&self) -> AppResult<MigrationReport> {
+        let from = self.current_version()?;
+        let pending = self.pending()?;
+        let Some(to) = pending.last().map(|m| m.version) else {
+            return Ok(MigrationReport {
+                from,
+                to: from,
+                applied: Vec::new(),
+                changed: Vec::new(),
+                backup: None,
+            });
+        };
+
+        let mut files = MigrationFiles::new(&self.root);
+        for migration in &pending {
+            info!(
+                "Running migration {}: {}",
+                migration.version, migration.description
+            );
+            migration.apply(&mut files).map_err(|e| {
+                migration_error(format!(
+                    "version {} ({}): {}",
+                    migration.version, migration.description, e
+                ))
+            })?;
+        }
+
+        let changes = files.changes();
+        let backup = if changes.is_empty() {
+            None
+        } else {
+            let backup = self.back_up(from, to, &changes)?;
+
+            if let Err(e) = commit(&changes) {
+                match restore(&backup) {
+                    Ok(_) => warn!("Restored files from {}", backup.display()),
+                    Err(restore_error) => warn!(
+                        "Failed to restore files from {}: {}",
+                        backup.display(),
+                        restore_error
+                    ),
+                }
+                return Err(e);
+            }
+
+            Some(backup)
+        };
+
+        self.state.set(DATA_VERSION_KEY, &to)?;
+
+        Ok(MigrationReport {
+            from,
+            to,
+            applied: pending
+                .iter()
+                .map(|m| AppliedMigration {
+                    version: m.version,
+                    description: m.description.clone(),
+                })
+                .collect(),
+            changed: changes.iter().map(|(path, _)| path.to_path_buf()).collect(),
+            backup,
+        })
+    }
+
+    /// Copy the current contents of every file about to change into a new
+    /// backup directory, with a manifest to restore them from.
+    fn back_up(&self, from: u32, to: u32, changes: &[(&Path, Option<&str>)]) -> AppResult<PathBuf> {
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let dir = self
+            .root
+            .join(BACKUPS_DIR)
+            .join(format!("v{}-to-v{}-{}", from, to, stamp));
+        fs::create_dir_all(&dir).map_err(|e| io_error("create", &dir, e))?;
+
+        let mut manifest = Manifest {
+            version: from,
+            files: Vec::new(),
+        };
+        for (index, (path, _)) in changes.iter().enumerate() {
+            let backup = if path.exists() {
+                let file_name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let name = format!("{}-{}", index, file_name);
+                fs::copy(path, dir.join(&name)).map_err(|e| io_error("back up", path, e))?;
+                Some(name)
+            } else {
+                None
+            };
+
+            manifest.files.push(BackedUpFile {
+                path: path.to_path_buf(),
+                backup,
+            });
+        }
+
+        let manifest_path = dir.join(MANIFEST_FILE);
+        let json = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| migration_error(format!("Failed to serialize the manifest: {}", e)))?;
+        fs::write(&manifest_path, json).map_err(|e| io_error("write", &manifest_path, e))?;
+
+        Ok(dir)
+    }
+
+    /// Put back the files in a backup made by [`run`](Self::run) and record
+    /// the version they were at.
+    pub fn restore(&self, backup: &Path) -> AppResult<()> {
+        let version = restore(backup)?;
+        self.state.set(DATA_VERSION_KEY, &version)
+    }
+}
+
+/// Write every change, each through a temporary file.
+fn commit(changes: &[(&Path, Option<&str>)]) -> AppResult<()> {
+    for (path, contents) in changes {
+        match contents {
+            Some(contents) => write_atomic(path, contents)?,
+            None => match fs::remove_file(path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(io_error("remove", path, e).into()),
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// Put back the files listed in a backup's manifest, returning the version
+/// they were at.
+fn restore(backup: &Path) -> AppResult<u32> {
+    let manifest_path = backup.join(MANIFEST_FILE);
+    let manifest =
+        fs::read_to_string(&manifest_path).map_err(|e| io_error("read", &manifest_path, e))?;
+    let manifest: Manifest = serde_json::from_str(&manifest)
+        .map_err(|e| migration_error(format!("{} is corrupted: {}", manifest_path.display(), e)))?;
+
+    for file in &manifest.files {
+        match &file.backup {
+            Some(name) => {
+                let contents = fs::read_to_string(backup.join(name))
+                    .map_err(|e| io_error("read", &backup.join(name), e))?;
+                write_atomic(&file.path, &contents)?;
+            }
+            None => {
+                if file.path.exists() {
+                    fs::remove_file(&file.path).map_err(|e| io_error("remove", &file.path, e))?;
+                }
+            }
+        }
+    }
+
+    Ok(manifest.version)
+}
+
+fn write_atomic(path: &Path, contents: &str) -> AppResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| io_error("create", parent, e))?;
+    }
+
+    let mut staged = path.as_os_str().to_owned();
+    staged.push(".tmp");
+    let staged = PathBuf::from(staged);
+
+    if let Err(e) = fs::write(&staged, contents).and_then(|_| fs::rename(&staged, path)) {
+        let _ = fs::remove_file(&staged);
+        return Err(io_error("write", path, e).into());
+    }
+
+    Ok(())
+}
+
+fn io_error(action: &str, path: &Path, e: std::io::Error) -> tram_core::TramError {
+    migration_error(format!("Failed to {} {}: {}", action, path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tram_core::TramError;
+
+    fn migrator(root: &Path) -> Migrator {
+        let state = StateStore::new(root.join("state.json"));
+
+        Migrator::new(root, state)
+            .with_migration(Migration::new(2, "Rename ttl to ttlSecs", |files| {
+                if let Some(mut settings) = files.read_json("settings.json")? {
+                    if let Some(ttl) = settings.as_object_mut().and_then(|s| s.remove("ttl")) {
+                        settings["ttlSecs"] = ttl;
+                    }
+                    files.write_json("settings.json", &settings)?;
+                }
+                Ok(())
+            }))
+            .with_migration(Migration::new(1, "Drop the legacy lock file", |files| {
+                files.remove("legacy.lock")
+            }))
+    }
+
+    #[test]
+    fn test_runs_pending_migrations_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("settings.json"), r#"{"ttl": 60}"#).unwrap();
+        fs::write(dir.path().join("legacy.lock"), "").unwrap();
+        let migrator = migrator(dir.path());
+
+        assert_eq!(migrator.current_version().unwrap(), 0);
+        assert_eq!(migrator.latest_version(), 2);
+
+        let report = migrator.run().unwrap();
+        assert_eq!((report.from, report.to), (0, 2));
+        assert_eq!(
+            report.applied.iter().map(|m| m.version).collect::<Vec<_>>(),
+            [1, 2]
+        );
+        assert_eq!(report.changed.len(), 2);
+
+        let settings = fs::read_to_string(dir.path().join("settings.json")).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&settings).unwrap(),
+            json!({ "ttlSecs": 60 })
+        );
+        assert!(!dir.path().join("legacy.lock").exists());
+        assert_eq!(migrator.current_version().unwrap(), 2);
+
+        // Already up to date
+        assert!(migrator.run().unwrap().is_empty());
+
+        // The backup puts everything back
+        migrator.restore(&report.backup.unwrap()).unwrap();
+        let settings = fs::read_to_string(dir.path().join("settings.json")).unwrap();
+        assert_eq!(settings, r#"{"ttl": 60}"#);
+        assert!(dir.path().join("legacy.lock").exists());
+        assert_eq!(migrator.current_version().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_failed_migration_changes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("legacy.lock"), "").unwrap();
+        let migrator = migrator(dir.path()).with_migration(Migration::new(3, "Break", |_| {
+            Err(TramError::State {
+                message: "boom".to_string(),
+            }
+            .into())
+        }));
+
+        let error = migrator.run().unwrap_err();
+        assert!(error.to_string().contains("Migration failed"));
+        assert!(format!("{:?}", error).contains("version 3 (Break): State error: boom"));
+
+        assert!(dir.path().join("legacy.lock").exists());
+        assert!(!dir.path().join(BACKUPS_DIR).exists());
+        assert_eq!(migrator.current_version().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_rejects_newer_data_and_duplicate_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let migrator = migrator(dir.path());
+        StateStore::new(dir.path().join("state.json"))
+            .set(DATA_VERSION_KEY, &5)
+            .unwrap();
+
+        let error = migrator.pending().unwrap_err();
+        assert!(format!("{:?}", error).contains("only understands up to version 2"));
+
+        let duplicate = migrator.with_migration(Migration::new(2, "Again", |_| Ok(())));
+        let error = duplicate.pending().unwrap_err();
+        assert!(format!("{:?}", error).contains("Two migrations to version 2"));
+    }
+}
@@ -0,0 +1,28 @@
+//! The process-wide list of migrations.
+
+use crate::migration::Migration;
+use std::sync::{OnceLock, RwLock};
+
+static REGISTRY: OnceLock<RwLock<Vec<Migration>>> = OnceLock::new();
+
+fn registry() -> &'static RwLock<Vec<Migration>> {
+    REGISTRY.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// Register a migration, replacing any previous one to the same version.
+pub fn register_migration(migration: Migration) {
+    if let Ok(mut migrations) = registry().write() {
+        migrations.retain(|m| m.version != migration.version);
+        migrations.push(migration);
+    }
+}
+
+/// All registered migrations, in version order.
+pub fn registered_migrations() -> Vec<Migration> {
+    let mut migrations = registry()
+        .read()
+        .map(|migrations| migrations.clone())
+        .unwrap_or_default();
+    migrations.sort_by_key(|m| m.version);
+    migrations
+}
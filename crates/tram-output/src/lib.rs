@@ -0,0 +1,302 @@
+//! Output rendering for Tram CLI applications.
+//!
+//! Commands build a serializable result, implement [`Render`] for its
+//! human-readable forms, and capture it as a [`CommandOutput`] instead of
+//! printing ad hoc. A [`Renderer`] then prints it in the format
+//! `TramConfig::output_format` selects, so every command supports the same
+//! machine-readable formats:
+//!
+//! - `json` and `yaml` serialize the result
+//! - `table`, `csv`, and `tsv` use the result's [`Table`], falling back to
+//!   its plain text when it has none
+//! - `plain` prints the plain text
+//!
+//! ```
+//! use serde::Serialize;
+//! use tram_config::{OutputFormat, TramConfig};
+//! use tram_output::{CommandOutput, Render, Renderer};
+//!
+//! #[derive(Serialize)]
+//! struct Greeting {
+//!     name: String,
+//! }
+//!
+//! impl Render for Greeting {
+//!     fn render_plain(&self) -> String {
+//!         format!("Hello, {}!", self.name)
+//!     }
+//! }
+//!
+//! let config = TramConfig {
+//!     output_format: OutputFormat::Json,
+//!     ..Default::default()
+//! };
+//! let output = CommandOutput::new(&Greeting { name: "tram".into() }).unwrap();
+//! let rendered = Renderer::from_config(&config).render(&output).unwrap();
+//! assert_eq!(rendered, "{\n  \"name\": \"tram\"\n}");
+//! ```
+
+use serde::Serialize;
+use tram_config::{OutputFormat, TramConfig};
+use tram_core::{
+    AppResult, BorderStyle, Stream, TramError, Verbosity, color_enabled, terminal_width,
+};
+use tram_ui::Table;
+
+/// Human-readable forms of a command result.
+pub trait Render: Serialize {
+    /// Text for `plain` output, and for `table`, `csv`, and `tsv` output when
+    /// there's no table.
+    fn render_plain(&self) -> String;
+
+    /// Tabular form for `table`, `csv`, and `tsv` output, if the result has
+    /// one.
+    fn render_table(&self) -> Option<Table> {
+        None
+    }
+}
+
+/// A rendered-ready command result.
+#[derive(Debug, Clone)]
+pub struct CommandOutput {
+    value: serde_json::Value,
+    plain: String,
+    table: Option<Table>,
+}
+
+impl CommandOutput {
+    /// Capture every form of a result up front.
+    pub fn new<T: Render>(result: &T) -> AppResult<Self> {
+        let value = serde_json::to_value(result).map_err(|e| TramError::Output {
+            message: format!("Failed to serialize command output: {}", e),
+        })?;
+
+        Ok(Self {
+            value,
+            plain: result.render_plain(),
+            table: result.render_table(),
+        })
+    }
+
+    /// The result as JSON.
+    pub fn value(&self) -> &serde_json::Value {
+        &self.value
+    }
+
+    /// The result as plain text.
+    pub fn plain(&self) -> &str {
+        &self.plain
+    }
+}
+
+/// Renders command results for an output format.
+#[derive(Debug, Clone)]
+pub struct Renderer {
+    format: OutputFormat,
+    border: BorderStyle,
+    color: bool,
+    max_width: Option<usize>,
+    verbosity: Verbosity,
+}
+
+impl Renderer {
+    /// A renderer using the configured format and table borders, coloring
+    /// when stdout supports it and fitting tables to the terminal.
+    pub fn from_config(config: &TramConfig) -> Self {
+        Self {
+            format: config.output_format.clone(),
+            border: config.table_border,
+            color: color_enabled(Stream::Stdout),
+            max_width: terminal_width(),
+            verbosity: Verbosity::Normal,
+        }
+    }
+
+    /// Print nothing in quiet mode.
+    pub fn with_verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.verbosity = verbosity;
+        self
+    }
+
+    /// Color the output or not, instead of detecting it from stdout.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Fit tables to `max_width` columns instead of this terminal's width.
+    pub fn with_max_width(mut self, max_width: Option<usize>) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Render a result as the text to print on stdout.
+    pub fn render(&self, output: &CommandOutput) -> AppResult<String> {
+        let rendered = match self.format {
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(&output.value).map_err(|e| TramError::Output {
+                    message: format!("Failed to render JSON output: {}", e),
+                })?
+            }
+            OutputFormat::Yaml => serde_yaml::to_string(&output.value)
+                .map_err(|e| TramError::Output {
+                    message: format!("Failed to render YAML output: {}", e),
+                })?
+                .trim_end()
+                .to_string(),
+            OutputFormat::Table => match &output.table {
+                Some(table) => table
+                    .clone()
+                    .with_border(self.border)
+                    .with_color(self.color)
+                    .with_max_width(self.max_width)
+                    .render(),
+                None => output.plain.clone(),
+            },
+            OutputFormat::Plain => output.plain.clone(),
+            OutputFormat::Csv => match &output.table {
+                Some(table) => table.to_csv(),
+                None => output.plain.clone(),
+            },
+            OutputFormat::Tsv => match &output.table {
+                Some(table) => table.to_tsv(),
+                None => output.plain.clone(),
+            },
+        };
+
+        Ok(rendered)
+    }
+
+    /// Render and print a result to stdout, unless in quiet mode.
+    pub fn print(&self, output: &CommandOutput) -> AppResult<()> {
+        if self.verbosity.is_quiet() {
+            return Ok(());
+        }
+
+        let rendered = self.render(output)?;
+
+        if !rendered.is_empty() {
+            write_stdout(&format!("{}\n", rendered))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Write to stdout without panicking. A closed pipe, as in `tram env | head`,
+/// means the reader has all it wants, so it isn't an error.
+pub fn write_stdout(text: &str) -> AppResult<()> {
+    use std::io::Write;
+
+    let mut stdout = std::io::stdout().lock();
+    match stdout
+        .write_all(text.as_bytes())
+        .and_then(|()| stdout.flush())
+    {
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(TramError::Io {
+            message: format!("Failed to write to stdout: {}", e),
+        }
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Tasks {
+        tasks: Vec<(String, String)>,
+    }
+
+    impl Render for Tasks {
+        fn render_plain(&self) -> String {
+            self.tasks
+                .iter()
+                .map(|(name, command)| format!("{}: {}", name, command))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+
+        fn render_table(&self) -> Option<Table> {
+            let mut table = Table::new(["Task", "Command"]);
+            for (name, command) in &self.tasks {
+                table.add_row([name.clone(), command.clone()]);
+            }
+            Some(table)
+        }
+    }
+
+    #[derive(Serialize)]
+    struct Message {
+        text: String,
+    }
+
+    impl Render for Message {
+        fn render_plain(&self) -> String {
+            self.text.clone()
+        }
+    }
+
+    fn renderer(format: OutputFormat) -> Renderer {
+        let config = TramConfig {
+            output_format: format,
+            ..Default::default()
+        };
+        Renderer::from_config(&config)
+            .with_color(false)
+            .with_max_width(None)
+    }
+
+    fn tasks() -> CommandOutput {
+        CommandOutput::new(&Tasks {
+            tasks: vec![("test".to_string(), "cargo test".to_string())],
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_renders_structured_formats() {
+        let output = tasks();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&renderer(OutputFormat::Json).render(&output).unwrap()).unwrap();
+        assert_eq!(json["tasks"][0][1], "cargo test");
+
+        let yaml = renderer(OutputFormat::Yaml).render(&output).unwrap();
+        assert!(yaml.starts_with("tasks:"));
+        assert!(!yaml.ends_with('\n'));
+
+        assert_eq!(
+            renderer(OutputFormat::Csv).render(&output).unwrap(),
+            "Task,Command\ntest,cargo test"
+        );
+        assert_eq!(
+            renderer(OutputFormat::Tsv).render(&output).unwrap(),
+            "Task\tCommand\ntest\tcargo test"
+        );
+        assert_eq!(
+            renderer(OutputFormat::Plain).render(&output).unwrap(),
+            "test: cargo test"
+        );
+
+        let table = renderer(OutputFormat::Table).render(&output).unwrap();
+        assert!(table.contains("Task"));
+        assert!(table.contains("cargo test"));
+    }
+
+    #[test]
+    fn test_tabular_formats_fall_back_to_plain() {
+        let output = CommandOutput::new(&Message {
+            text: "Nothing to do".to_string(),
+        })
+        .unwrap();
+        assert_eq!(output.plain(), "Nothing to do");
+        assert_eq!(output.value()["text"], "Nothing to do");
+
+        for format in [OutputFormat::Table, OutputFormat::Csv, OutputFormat::Tsv] {
+            assert_eq!(renderer(format).render(&output).unwrap(), "Nothing to do");
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! The paths a plugin may read and write.
+//!
+//! Grants are directories (or files) that cover everything beneath them.
+//! Paths are checked after resolving `..` and symlinks, so a plugin can't
+//! reach outside a grant through either.
+
+use std::path::{Component, Path, PathBuf};
+
+/// Filesystem access granted to a plugin for one call.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Where relative paths resolve from
+    base: PathBuf,
+    read: Vec<PathBuf>,
+    write: Vec<PathBuf>,
+}
+
+impl Capabilities {
+    /// No filesystem access, with relative paths resolving against `base`.
+    pub fn new(base: impl Into<PathBuf>) -> Self {
+        Self {
+            base: base.into(),
+            read: Vec::new(),
+            write: Vec::new(),
+        }
+    }
+
+    /// Allow reading `path` and everything beneath it.
+    pub fn with_read(mut self, path: impl AsRef<Path>) -> Self {
+        self.read.push(self.grant(path.as_ref()));
+        self
+    }
+
+    /// Allow writing, and reading, `path` and everything beneath it.
+    pub fn with_write(mut self, path: impl AsRef<Path>) -> Self {
+        self.write.push(self.grant(path.as_ref()));
+        self
+    }
+
+    /// Where relative paths resolve from.
+    pub fn base(&self) -> &Path {
+        &self.base
+    }
+
+    /// The path to read for a plugin's `path`, if it may be read.
+    pub fn readable(&self, path: &str) -> Option<PathBuf> {
+        let path = resolve(&self.base, Path::new(path));
+        self.read
+            .iter()
+            .chain(&self.write)
+            .any(|grant| path.starts_with(grant))
+            .then_some(path)
+    }
+
+    /// The path to write for a plugin's `path`, if it may be written.
+    pub fn writable(&self, path: &str) -> Option<PathBuf> {
+        let path = resolve(&self.base, Path::new(path));
+        self.write
+            .iter()
+            .any(|grant| path.starts_with(grant))
+            .then_some(path)
+    }
+
+    fn grant(&self, path: &Path) -> PathBuf {
+        resolve(&self.base, path)
+    }
+}
+
+/// `path` made absolute against `base`, without `.` or `..`, and with
+/// symlinks resolved as far as the path exists.
+fn resolve(base: &Path, path: &Path) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    for component in base.join(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            component => resolved.push(component),
+        }
+    }
+
+    // Canonicalize the longest existing prefix, keeping the rest as-is, so
+    // files that don't exist yet are still checked against real paths
+    let mut missing = Vec::new();
+    let mut existing = resolved.as_path();
+    loop {
+        if let Ok(real) = existing.canonicalize() {
+            return missing
+                .iter()
+                .rev()
+                .fold(real, |path, name| path.join(name));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name.to_owned());
+                existing = parent;
+            }
+            _ => return resolved,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_grants_cover_paths_beneath_them() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("out")).unwrap();
+
+        let capabilities = Capabilities::new(&root).with_read("src").with_write("out");
+
+        assert_eq!(
+            capabilities.readable("src/main.rs"),
+            Some(root.join("src/main.rs"))
+        );
+        assert!(capabilities.readable("out/new/file.txt").is_some());
+        assert!(capabilities.readable("Cargo.toml").is_none());
+        assert!(capabilities.writable("src/main.rs").is_none());
+        assert_eq!(
+            capabilities.writable("out/new/file.txt"),
+            Some(root.join("out/new/file.txt"))
+        );
+        assert!(Capabilities::new(&root).readable("src").is_none());
+    }
+
+    #[test]
+    fn test_paths_cannot_escape_grants() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("secret"), "").unwrap();
+
+        let capabilities = Capabilities::new(root.join("src")).with_read(".");
+
+        assert!(capabilities.readable("lib.rs").is_some());
+        assert!(capabilities.readable("../secret").is_none());
+        assert!(capabilities.readable("a/../../secret").is_none());
+        assert!(
+            capabilities
+                .readable(root.join("secret").to_str().unwrap())
+                .is_none()
+        );
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(root.join("secret"), root.join("src/link")).unwrap();
+            assert!(capabilities.readable("link").is_none());
+        }
+    }
+}
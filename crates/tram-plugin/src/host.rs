@@ -0,0 +1,590 @@
+//! Loading plugin modules and calling into them.
+
+use crate::capabilities::Capabilities;
+use crate::manifest::{
+    CommandOutcome, CommandRequest, DetectRequest, DetectResponse, PluginManifest, TemplateFile,
+    TemplateRequest, TemplateResponse,
+};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use tram_core::{AppResult, ChangeSet, FileChange, TramError};
+use wasmtime::{
+    Caller, Config, Engine, Extern, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder,
+    Trap,
+};
+
+/// Returned by filesystem host functions for paths outside a grant.
+pub const ERR_DENIED: i32 = -1;
+
+/// Returned by filesystem host functions when the operation fails.
+pub const ERR_IO: i32 = -2;
+
+/// Roughly the number of instructions a call may run.
+const DEFAULT_FUEL: u64 = 10_000_000_000;
+
+/// Memory a plugin instance may grow to.
+const DEFAULT_MEMORY: usize = 256 * 1024 * 1024;
+
+/// Bounds on each call into a plugin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PluginLimits {
+    pub fuel: u64,
+    pub memory_bytes: usize,
+}
+
+impl Default for PluginLimits {
+    fn default() -> Self {
+        Self {
+            fuel: DEFAULT_FUEL,
+            memory_bytes: DEFAULT_MEMORY,
+        }
+    }
+}
+
+/// What host functions see during a call.
+struct HostState {
+    plugin: String,
+    capabilities: Capabilities,
+    limits: StoreLimits,
+}
+
+/// A compiled WASM plugin.
+pub struct WasmPlugin {
+    path: PathBuf,
+    engine: Engine,
+    module: Module,
+    manifest: PluginManifest,
+    limits: PluginLimits,
+}
+
+impl std::fmt::Debug for WasmPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WasmPlugin")
+            .field("path", &self.path)
+            .field("manifest", &self.manifest)
+            .field("limits", &self.limits)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WasmPlugin {
+    /// Compile the plugin at `path` and read its manifest.
+    pub fn load(path: &Path) -> AppResult<Self> {
+        let bytes = std::fs::read(path).map_err(|e| TramError::Plugin {
+            message: format!("Failed to read {}: {}", path.display(), e),
+        })?;
+
+        Self::from_bytes(path, &bytes)
+    }
+
+    /// Compile a plugin from its module bytes, with `path` naming it in
+    /// errors.
+    pub fn from_bytes(path: impl Into<PathBuf>, bytes: &[u8]) -> AppResult<Self> {
+        let path = path.into();
+        let invalid = |e: wasmtime::Error| TramError::Plugin {
+            message: format!("{} is not a valid plugin: {:#}", path.display(), e),
+        };
+
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(invalid)?;
+        let module = Module::from_binary(&engine, bytes).map_err(invalid)?;
+
+        let mut plugin = Self {
+            engine,
+            module,
+            manifest: PluginManifest {
+                name: path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                version: None,
+                description: None,
+                commands: Vec::new(),
+                templates: Vec::new(),
+                detector: false,
+            },
+            path,
+            limits: PluginLimits::default(),
+        };
+        plugin.manifest = plugin.call("tram_manifest", None::<&()>, Capabilities::default())?;
+
+        Ok(plugin)
+    }
+
+    /// Bound each call by `limits` instead of the defaults.
+    pub fn with_limits(mut self, limits: PluginLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn manifest(&self) -> &PluginManifest {
+        &self.manifest
+    }
+
+    pub fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    /// Run one of the plugin's commands in `capabilities.base()`.
+    pub fn run_command(
+        &self,
+        command: &str,
+        args: &[String],
+        capabilities: Capabilities,
+    ) -> AppResult<CommandOutcome> {
+        let cwd = capabilities.base().to_path_buf();
+        let request = CommandRequest {
+            command,
+            args,
+            cwd: &cwd,
+        };
+
+        self.call("tram_command", Some(&request), capabilities)
+    }
+
+    /// Render one of the plugin's templates for `name`. Templates get no
+    /// filesystem access; the files they return are written by the host.
+    pub fn render_template(
+        &self,
+        template: &str,
+        name: &str,
+        variables: &BTreeMap<String, String>,
+    ) -> AppResult<Vec<TemplateFile>> {
+        let request = TemplateRequest {
+            template,
+            name,
+            variables,
+        };
+        let response: TemplateResponse =
+            self.call("tram_template", Some(&request), Capabilities::default())?;
+
+        // Rendered files must stay inside the target directory
+        for file in &response.files {
+            let contained = !file.path.as_os_str().is_empty()
+                && file
+                    .path
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_)));
+            if !contained {
+                return Err(TramError::Plugin {
+                    message: format!(
+                        "Plugin {} rendered a file outside the target directory: {}",
+                        self.name(),
+                        file.path.display()
+                    ),
+                }
+                .into());
+            }
+        }
+
+        Ok(response.files)
+    }
+
+    /// Render a template and write its files under `target_dir`, refusing
+    /// to overwrite any that exist.
+    pub fn generate(
+        &self,
+        template: &str,
+        name: &str,
+        variables: &BTreeMap<String, String>,
+        target_dir: &Path,
+        dry_run: bool,
+    ) -> AppResult<Vec<FileChange>> {
+        let files = self.render_template(template, name, variables)?;
+
+        if let Some(existing) = files
+            .iter()
+            .map(|file| target_dir.join(&file.path))
+            .find(|path| path.exists())
+        {
+            return Err(TramError::Conflict {
+                path: existing.display().to_string(),
+            }
+            .into());
+        }
+
+        let mut changes = ChangeSet::new(dry_run);
+        for file in files {
+            changes.write_file(&target_dir.join(&file.path), file.contents)?;
+        }
+
+        Ok(changes.into_changes())
+    }
+
+    /// Ask the plugin's detector what kind of project is at `path`, giving
+    /// it read access to that directory.
+    pub fn detect(&self, path: &Path) -> AppResult<Option<String>> {
+        let request = DetectRequest { path };
+        let capabilities = Capabilities::new(path).with_read(path);
+        let response: DetectResponse = self.call("tram_detect", Some(&request), capabilities)?;
+
+        Ok(response.project_type.filter(|kind| !kind.is_empty()))
+    }
+
+    /// Call an export in a fresh instance, passing `input` as JSON and
+    /// parsing the JSON it returns.
+    fn call<I: Serialize, O: DeserializeOwned>(
+        &self,
+        export: &str,
+        input: Option<&I>,
+        capabilities: Capabilities,
+    ) -> AppResult<O> {
+        let state = HostState {
+            plugin: self.manifest.name.clone(),
+            capabilities,
+            limits: StoreLimitsBuilder::new()
+                .memory_size(self.limits.memory_bytes)
+                .build(),
+        };
+        let mut store = Store::new(&self.engine, state);
+        store.limiter(|state| &mut state.limits);
+        store
+            .set_fuel(self.limits.fuel)
+            .map_err(|e| self.failed(export, e))?;
+
+        let instance = linker(&self.engine)
+            .and_then(|linker| linker.instantiate(&mut store, &self.module))
+            .map_err(|e| self.failed(export, e))?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| self.missing("memory"))?;
+
+        let output = match input {
+            Some(input) => {
+                let input = serde_json::to_vec(input).map_err(|e| TramError::Plugin {
+                    message: format!("Failed to encode a request for {}: {}", export, e),
+                })?;
+                let func = instance
+                    .get_typed_func::<(i32, i32), i64>(&mut store, export)
+                    .map_err(|_| self.missing(export))?;
+                let alloc = instance
+                    .get_typed_func::<i32, i32>(&mut store, "tram_alloc")
+                    .map_err(|_| self.missing("tram_alloc"))?;
+
+                let ptr = alloc
+                    .call(&mut store, input.len() as i32)
+                    .map_err(|e| self.failed(export, e))?;
+                memory
+                    .write(&mut store, ptr as usize, &input)
+                    .map_err(|e| self.failed(export, e.into()))?;
+                func.call(&mut store, (ptr, input.len() as i32))
+            }
+            None => instance
+                .get_typed_func::<(), i64>(&mut store, export)
+                .map_err(|_| self.missing(export))?
+                .call(&mut store, ()),
+        }
+        .map_err(|e| self.failed(export, e))?;
+
+        let bytes = read(&memory, &store, output).ok_or_else(|| TramError::Plugin {
+            message: format!(
+                "Plugin {} returned an out-of-bounds buffer from {}",
+                self.name(),
+                export
+            ),
+        })?;
+        serde_json::from_slice(&bytes).map_err(|e| {
+            TramError::Plugin {
+                message: format!(
+                    "Plugin {} returned invalid JSON from {}: {}",
+                    self.name(),
+                    export,
+                    e
+                ),
+            }
+            .into()
+        })
+    }
+
+    fn missing(&self, export: &str) -> TramError {
+        TramError::Plugin {
+            message: format!(
+                "Plugin {} ({}) doesn't export `{}`",
+                self.name(),
+                self.path.display(),
+                export
+            ),
+        }
+    }
+
+    fn failed(&self, export: &str, e: wasmtime::Error) -> TramError {
+        let reason = match e.downcast_ref::<Trap>() {
+            Some(Trap::OutOfFuel) => "it ran too long".to_string(),
+            _ => format!("{:#}", e),
+        };
+
+        TramError::Plugin {
+            message: format!("Plugin {} failed in {}: {}", self.name(), export, reason),
+        }
+    }
+}
+
+/// The `tram` host functions.
+fn linker(engine: &Engine) -> wasmtime::Result<Linker<HostState>> {
+    let mut linker = Linker::new(engine);
+
+    linker.func_wrap(
+        "tram",
+        "log",
+        |mut caller: Caller<'_, HostState>, level: i32, ptr: i32, len: i32| {
+            let Some(message) = caller_bytes(&mut caller, ptr, len) else {
+                return;
+            };
+            let message = String::from_utf8_lossy(&message);
+            let plugin = &caller.data().plugin;
+
+            match level {
+                0 => tracing::error!(plugin, "{}", message),
+                1 => tracing::warn!(plugin, "{}", message),
+                2 => tracing::info!(plugin, "{}", message),
+                3 => tracing::debug!(plugin, "{}", message),
+                _ => tracing::trace!(plugin, "{}", message),
+            }
+        },
+    )?;
+
+    linker.func_wrap(
+        "tram",
+        "read_file",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> wasmtime::Result<i64> {
+            let Some(path) = caller_path(&mut caller, ptr, len)
+                .and_then(|path| caller.data().capabilities.readable(&path))
+            else {
+                return Ok(ERR_DENIED.into());
+            };
+
+            match std::fs::read(&path) {
+                Ok(contents) => give(&mut caller, &contents),
+                Err(_) => Ok(ERR_IO.into()),
+            }
+        },
+    )?;
+
+    linker.func_wrap(
+        "tram",
+        "list_dir",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> wasmtime::Result<i64> {
+            let Some(path) = caller_path(&mut caller, ptr, len)
+                .and_then(|path| caller.data().capabilities.readable(&path))
+            else {
+                return Ok(ERR_DENIED.into());
+            };
+
+            let Ok(entries) = std::fs::read_dir(&path) else {
+                return Ok(ERR_IO.into());
+            };
+            let mut names: Vec<String> = entries
+                .flatten()
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect();
+            names.sort();
+
+            give(&mut caller, &serde_json::to_vec(&names)?)
+        },
+    )?;
+
+    linker.func_wrap(
+        "tram",
+        "write_file",
+        |mut caller: Caller<'_, HostState>,
+         path_ptr: i32,
+         path_len: i32,
+         ptr: i32,
+         len: i32|
+         -> i32 {
+            let Some(path) = caller_path(&mut caller, path_ptr, path_len)
+                .and_then(|path| caller.data().capabilities.writable(&path))
+            else {
+                return ERR_DENIED;
+            };
+            let Some(contents) = caller_bytes(&mut caller, ptr, len) else {
+                return ERR_IO;
+            };
+
+            let written = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(&path, contents));
+            match written {
+                Ok(()) => 0,
+                Err(_) => ERR_IO,
+            }
+        },
+    )?;
+
+    Ok(linker)
+}
+
+/// The caller's exported memory.
+fn caller_memory(caller: &mut Caller<'_, HostState>) -> Option<Memory> {
+    match caller.get_export("memory") {
+        Some(Extern::Memory(memory)) => Some(memory),
+        _ => None,
+    }
+}
+
+/// Copy `len` bytes at `ptr` out of the caller's memory.
+fn caller_bytes(caller: &mut Caller<'_, HostState>, ptr: i32, len: i32) -> Option<Vec<u8>> {
+    let memory = caller_memory(caller)?;
+    let start = usize::try_from(ptr).ok()?;
+    let end = start.checked_add(usize::try_from(len).ok()?)?;
+
+    memory.data(&*caller).get(start..end).map(<[u8]>::to_vec)
+}
+
+fn caller_path(caller: &mut Caller<'_, HostState>, ptr: i32, len: i32) -> Option<String> {
+    String::from_utf8(caller_bytes(caller, ptr, len)?).ok()
+}
+
+/// Copy `data` into a buffer from the caller's `tram_alloc`, returning it
+/// packed for the plugin.
+fn give(caller: &mut Caller<'_, HostState>, data: &[u8]) -> wasmtime::Result<i64> {
+    let alloc = match caller.get_export("tram_alloc") {
+        Some(Extern::Func(func)) => func.typed::<i32, i32>(&*caller)?,
+        _ => return Err(wasmtime::Error::msg("plugin doesn't export `tram_alloc`")),
+    };
+    let len = i32::try_from(data.len())?;
+    let ptr = alloc.call(&mut *caller, len)?;
+
+    let memory = caller_memory(caller)
+        .ok_or_else(|| wasmtime::Error::msg("plugin doesn't export `memory`"))?;
+    memory.write(&mut *caller, ptr as u32 as usize, data)?;
+
+    Ok(pack(ptr as u32, len as u32))
+}
+
+/// A buffer as passed across the interface: pointer high, length low.
+fn pack(ptr: u32, len: u32) -> i64 {
+    (((ptr as u64) << 32) | len as u64) as i64
+}
+
+/// The bytes of a buffer returned by a plugin.
+fn read(memory: &Memory, store: &Store<HostState>, packed: i64) -> Option<Vec<u8>> {
+    let packed = packed as u64;
+    let start = (packed >> 32) as usize;
+    let end = start.checked_add((packed & 0xffff_ffff) as usize)?;
+
+    memory.data(store).get(start..end).map(<[u8]>::to_vec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_plugin;
+    use tempfile::TempDir;
+
+    fn plugin() -> WasmPlugin {
+        WasmPlugin::from_bytes("example.wasm", &test_plugin::wasm()).unwrap()
+    }
+
+    #[test]
+    fn test_load_reads_the_manifest() {
+        let plugin = plugin();
+
+        assert_eq!(plugin.name(), "example");
+        assert_eq!(plugin.manifest().commands[0].name, "greet");
+        assert_eq!(plugin.manifest().templates[0].name, "readme");
+        assert!(plugin.manifest().detector);
+
+        let err = WasmPlugin::from_bytes("junk.wasm", b"not wasm").unwrap_err();
+        assert!(err.to_string().contains("junk.wasm is not a valid plugin"));
+    }
+
+    #[test]
+    fn test_commands_read_only_what_they_are_granted() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("input.txt"), "hello").unwrap();
+        let plugin = plugin();
+
+        let outcome = plugin
+            .run_command(
+                "greet",
+                &[],
+                Capabilities::new(temp_dir.path()).with_read("."),
+            )
+            .unwrap();
+        assert_eq!(outcome.exit_code, 0);
+        assert_eq!(outcome.stdout, "hello");
+
+        let outcome = plugin
+            .run_command("greet", &[], Capabilities::new(temp_dir.path()))
+            .unwrap();
+        assert_eq!(outcome.exit_code, 3);
+        assert_eq!(outcome.stderr, "denied");
+    }
+
+    #[test]
+    fn test_detect_and_generate() {
+        let temp_dir = TempDir::new().unwrap();
+        let plugin = plugin();
+
+        assert_eq!(plugin.detect(temp_dir.path()).unwrap(), None);
+        std::fs::write(temp_dir.path().join("marker.toml"), "").unwrap();
+        assert_eq!(
+            plugin.detect(temp_dir.path()).unwrap().as_deref(),
+            Some("marker")
+        );
+
+        let changes = plugin
+            .generate("readme", "demo", &BTreeMap::new(), temp_dir.path(), true)
+            .unwrap();
+        assert_eq!(
+            changes.last().map(|change| change.path.clone()),
+            Some(temp_dir.path().join("docs/README.md"))
+        );
+        assert!(!temp_dir.path().join("docs/README.md").exists());
+
+        plugin
+            .generate("readme", "demo", &BTreeMap::new(), temp_dir.path(), false)
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("docs/README.md")).unwrap(),
+            "# Hello"
+        );
+
+        let err = plugin
+            .generate("readme", "demo", &BTreeMap::new(), temp_dir.path(), false)
+            .unwrap_err();
+        assert!(err.to_string().contains("Already exists"), "{}", err);
+    }
+
+    #[test]
+    fn test_templates_cannot_escape_the_target() {
+        let plugin = WasmPlugin::from_bytes(
+            "evil.wasm",
+            &test_plugin::wasm_with_template(r#"{"files":[{"path":"../x","contents":""}]}"#),
+        )
+        .unwrap();
+
+        let err = plugin
+            .render_template("readme", "demo", &BTreeMap::new())
+            .unwrap_err();
+        assert!(err.to_string().contains("outside the target directory"));
+    }
+
+    #[test]
+    fn test_runaway_plugins_are_stopped() {
+        let plugin = WasmPlugin::from_bytes("spin.wasm", &test_plugin::spinning())
+            .unwrap()
+            .with_limits(PluginLimits {
+                fuel: 100_000,
+                ..PluginLimits::default()
+            });
+
+        let err = plugin
+            .run_command("spin", &[], Capabilities::new(std::env::temp_dir()))
+            .unwrap_err();
+        assert!(
+            format!("{:?}", err).contains("it ran too long"),
+            "{:?}",
+            err
+        );
+    }
+}
@@ -0,0 +1,55 @@
+//! WASM plugins for Tram CLI applications.
+//!
+//! Plugins are WebAssembly modules run with wasmtime, so third parties can
+//! extend a CLI without recompiling it and without being trusted with the
+//! machine. A plugin can provide:
+//!
+//! - subcommands, run for `tram <command>` like the `tram-<name>`
+//!   executables in `tram_core::plugins`
+//! - templates, rendered into files the host writes for it
+//! - a workspace detector, naming project types tram doesn't know
+//!
+//! Plugins only see the filesystem through the host functions below, and
+//! only the paths their [`Capabilities`] grant. Each call gets a fresh
+//! instance with bounded memory and fuel, so a plugin can't keep state
+//! between calls or run forever.
+//!
+//! # Interface
+//!
+//! Messages are JSON, passed through the plugin's memory. A buffer is
+//! returned as an `i64` holding its pointer in the high 32 bits and its
+//! length in the low 32 bits. A plugin exports:
+//!
+//! - `memory`
+//! - `tram_alloc(len: i32) -> i32`, returning a buffer the host writes
+//!   requests and file contents into
+//! - `tram_manifest() -> i64`, returning a [`PluginManifest`]
+//! - `tram_command(ptr: i32, len: i32) -> i64`, for plugins with commands
+//! - `tram_template(ptr: i32, len: i32) -> i64`, for plugins with templates
+//! - `tram_detect(ptr: i32, len: i32) -> i64`, for detectors
+//!
+//! and may import from the `tram` module:
+//!
+//! - `log(level: i32, ptr: i32, len: i32)`, with levels 0 (error) to 4
+//!   (trace)
+//! - `read_file(ptr: i32, len: i32) -> i64`, returning the file's contents
+//! - `list_dir(ptr: i32, len: i32) -> i64`, returning a JSON array of names
+//! - `write_file(path_ptr: i32, path_len: i32, ptr: i32, len: i32) -> i32`,
+//!   returning 0
+//!
+//! Relative paths resolve against the directory the plugin was run in. The
+//! filesystem functions return [`ERR_DENIED`] for paths outside the
+//! plugin's capabilities and [`ERR_IO`] when the operation fails.
+
+pub mod capabilities;
+pub mod host;
+pub mod manifest;
+pub mod registry;
+
+pub use capabilities::*;
+pub use host::*;
+pub use manifest::*;
+pub use registry::*;
+
+#[cfg(test)]
+mod test_plugin;
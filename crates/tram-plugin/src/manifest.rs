@@ -0,0 +1,93 @@
+//! Messages exchanged with plugins.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// What a plugin provides, from its `tram_manifest` export.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginManifest {
+    pub name: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub commands: Vec<PluginCommand>,
+    #[serde(default)]
+    pub templates: Vec<PluginTemplate>,
+    /// Whether the plugin exports `tram_detect`
+    #[serde(default)]
+    pub detector: bool,
+}
+
+/// A subcommand a plugin adds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginCommand {
+    pub name: String,
+    #[serde(default)]
+    pub about: Option<String>,
+}
+
+/// A template a plugin can render.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Sent to `tram_command`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CommandRequest<'a> {
+    pub command: &'a str,
+    pub args: &'a [String],
+    pub cwd: &'a std::path::Path,
+}
+
+/// What a plugin command printed, and its exit code.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CommandOutcome {
+    pub exit_code: i32,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Sent to `tram_template`.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct TemplateRequest<'a> {
+    pub template: &'a str,
+    pub name: &'a str,
+    pub variables: &'a BTreeMap<String, String>,
+}
+
+/// Returned by `tram_template`.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct TemplateResponse {
+    #[serde(default)]
+    pub files: Vec<TemplateFile>,
+}
+
+/// A file rendered by a plugin template, relative to the target directory.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TemplateFile {
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+/// Sent to `tram_detect`.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct DetectRequest<'a> {
+    pub path: &'a std::path::Path,
+}
+
+/// Returned by `tram_detect`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DetectResponse {
+    #[serde(default)]
+    pub project_type: Option<String>,
+}
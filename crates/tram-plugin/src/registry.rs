@@ -0,0 +1,158 @@
+//! Finding installed plugins.
+//!
+//! Plugins are `.wasm` files in a workspace's `.tram/plugins` directory or
+//! the user's `plugins` data directory. Workspace plugins come first, so a
+//! project can pin its own version of a plugin.
+
+use crate::host::WasmPlugin;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Plugins found in the plugin directories, in priority order.
+#[derive(Debug, Default)]
+pub struct PluginRegistry {
+    plugins: Vec<WasmPlugin>,
+}
+
+impl PluginRegistry {
+    /// Where plugins are installed: `<workspace>/.tram/plugins`, then the
+    /// `plugins` directory in the data directory.
+    pub fn plugin_dirs(workspace_root: Option<&Path>) -> Vec<PathBuf> {
+        workspace_root
+            .map(|root| root.join(".tram").join("plugins"))
+            .into_iter()
+            .chain(tram_core::data_dir().map(|dir| dir.join("plugins")))
+            .collect()
+    }
+
+    /// Load every `.wasm` file in `dirs`, sorted by file name within each.
+    /// Plugins that fail to load are skipped with a warning.
+    pub fn discover(dirs: impl IntoIterator<Item = PathBuf>) -> Self {
+        let mut plugins = Vec::new();
+
+        for dir in dirs {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            let mut paths: Vec<PathBuf> = entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+                .collect();
+            paths.sort();
+
+            for path in paths {
+                match WasmPlugin::load(&path) {
+                    Ok(plugin) => plugins.push(plugin),
+                    Err(e) => warn!("Skipping plugin {}: {}", path.display(), e),
+                }
+            }
+        }
+
+        Self { plugins }
+    }
+
+    /// Every plugin, in priority order.
+    pub fn plugins(&self) -> &[WasmPlugin] {
+        &self.plugins
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// The first plugin that provides `command`.
+    pub fn find_command(&self, command: &str) -> Option<&WasmPlugin> {
+        self.plugins.iter().find(|plugin| {
+            plugin
+                .manifest()
+                .commands
+                .iter()
+                .any(|provided| provided.name == command)
+        })
+    }
+
+    /// The first plugin that provides `template`.
+    pub fn find_template(&self, template: &str) -> Option<&WasmPlugin> {
+        self.plugins.iter().find(|plugin| {
+            plugin
+                .manifest()
+                .templates
+                .iter()
+                .any(|provided| provided.name == template)
+        })
+    }
+
+    /// The project type the first detector recognizes at `path`, with the
+    /// plugin that named it. Detectors that fail are skipped.
+    pub fn detect(&self, path: &Path) -> Option<(String, &WasmPlugin)> {
+        self.plugins
+            .iter()
+            .filter(|plugin| plugin.manifest().detector)
+            .find_map(|plugin| match plugin.detect(path) {
+                Ok(kind) => kind.map(|kind| (kind, plugin)),
+                Err(e) => {
+                    warn!("Workspace detector {} failed: {}", plugin.name(), e);
+                    None
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_plugin;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_discover_loads_wasm_files_in_order() {
+        let workspace = TempDir::new().unwrap();
+        let user = TempDir::new().unwrap();
+        std::fs::write(workspace.path().join("example.wasm"), test_plugin::wasm()).unwrap();
+        std::fs::write(workspace.path().join("broken.wasm"), "not wasm").unwrap();
+        std::fs::write(workspace.path().join("notes.txt"), "").unwrap();
+        std::fs::write(user.path().join("spin.wasm"), test_plugin::spinning()).unwrap();
+        std::fs::write(user.path().join("example.wasm"), test_plugin::wasm()).unwrap();
+
+        let registry = PluginRegistry::discover([
+            workspace.path().to_path_buf(),
+            user.path().join("missing"),
+            user.path().to_path_buf(),
+        ]);
+
+        let paths: Vec<&Path> = registry.plugins().iter().map(|p| p.path()).collect();
+        assert_eq!(
+            paths,
+            [
+                workspace.path().join("example.wasm"),
+                user.path().join("example.wasm"),
+                user.path().join("spin.wasm"),
+            ]
+        );
+        assert_eq!(
+            registry.find_command("greet").map(|p| p.path()),
+            Some(workspace.path().join("example.wasm").as_path())
+        );
+        assert_eq!(
+            registry.find_command("spin").map(|p| p.name()),
+            Some("spin")
+        );
+        assert!(registry.find_command("missing").is_none());
+        assert_eq!(
+            registry.find_template("readme").map(|p| p.name()),
+            Some("example")
+        );
+
+        std::fs::write(workspace.path().join("marker.toml"), "").unwrap();
+        let (kind, plugin) = registry.detect(workspace.path()).unwrap();
+        assert_eq!((kind.as_str(), plugin.name()), ("marker", "example"));
+        assert!(registry.detect(user.path()).is_none());
+    }
+
+    #[test]
+    fn test_plugin_dirs() {
+        let dirs = PluginRegistry::plugin_dirs(Some(Path::new("/work")));
+        assert_eq!(dirs[0], Path::new("/work/.tram/plugins"));
+    }
+}
@@ -0,0 +1,122 @@
+//! Plugins written in WAT for tests.
+
+/// The manifest of [`wasm`].
+const MANIFEST: &str = r#"{"name":"example","version":"1.0.0","commands":[{"name":"greet","about":"Print input.txt"}],"templates":[{"name":"readme"}],"detector":true}"#;
+
+/// The files the `readme` template of [`wasm`] renders.
+const TEMPLATE: &str = r##"{"files":[{"path":"docs/README.md","contents":"# Hello"}]}"##;
+
+/// A plugin whose `greet` command prints `input.txt`, whose detector finds
+/// `marker.toml` projects, and whose template renders `docs/README.md`.
+pub fn wasm() -> Vec<u8> {
+    wasm_with_template(TEMPLATE)
+}
+
+/// [`wasm`], with its template returning `template` as-is.
+pub fn wasm_with_template(template: &str) -> Vec<u8> {
+    let stdout_start = r#"{"exitCode":0,"stdout":""#;
+    let stdout_end = r#""}"#;
+    let denied = r#"{"exitCode":3,"stderr":"denied"}"#;
+    let detected = r#"{"projectType":"marker"}"#;
+
+    let wat = format!(
+        r#"(module
+  (import "tram" "read_file" (func $read_file (param i32 i32) (result i64)))
+  (memory (export "memory") 1)
+  (global $heap (mut i32) (i32.const 8192))
+
+  (data (i32.const 0) "{manifest}")
+  (data (i32.const 1024) "input.txt")
+  (data (i32.const 1040) "marker.toml")
+  (data (i32.const 1056) "{stdout_start}")
+  (data (i32.const 1088) "{stdout_end}")
+  (data (i32.const 1104) "{denied}")
+  (data (i32.const 1152) "{detected}")
+  (data (i32.const 1184) "{{}}")
+  (data (i32.const 2048) "{template}")
+
+  (func $pack (param $ptr i32) (param $len i32) (result i64)
+    (i64.or
+      (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+      (i64.extend_i32_u (local.get $len))))
+
+  (func $alloc (export "tram_alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+    (if (i32.gt_u (global.get $heap) (i32.mul (memory.size) (i32.const 65536)))
+      (then (drop (memory.grow (i32.add (i32.shr_u (local.get $len) (i32.const 16)) (i32.const 1))))))
+    (local.get $ptr))
+
+  (func (export "tram_manifest") (result i64)
+    (call $pack (i32.const 0) (i32.const {manifest_len})))
+
+  (func (export "tram_command") (param i32 i32) (result i64)
+    (local $file i64) (local $ptr i32) (local $len i32) (local $out i32)
+    (local.set $file (call $read_file (i32.const 1024) (i32.const 9)))
+    (if (i64.lt_s (local.get $file) (i64.const 0))
+      (then (return (call $pack (i32.const 1104) (i32.const {denied_len})))))
+    (local.set $ptr (i32.wrap_i64 (i64.shr_u (local.get $file) (i64.const 32))))
+    (local.set $len (i32.wrap_i64 (local.get $file)))
+    (local.set $out (call $alloc (i32.add (local.get $len) (i32.const {wrap_len}))))
+    (memory.copy (local.get $out) (i32.const 1056) (i32.const {start_len}))
+    (memory.copy
+      (i32.add (local.get $out) (i32.const {start_len}))
+      (local.get $ptr)
+      (local.get $len))
+    (memory.copy
+      (i32.add (i32.add (local.get $out) (i32.const {start_len})) (local.get $len))
+      (i32.const 1088)
+      (i32.const {end_len}))
+    (call $pack (local.get $out) (i32.add (local.get $len) (i32.const {wrap_len}))))
+
+  (func (export "tram_detect") (param i32 i32) (result i64)
+    (if (i64.lt_s (call $read_file (i32.const 1040) (i32.const 11)) (i64.const 0))
+      (then (return (call $pack (i32.const 1184) (i32.const 2)))))
+    (call $pack (i32.const 1152) (i32.const {detected_len})))
+
+  (func (export "tram_template") (param i32 i32) (result i64)
+    (call $pack (i32.const 2048) (i32.const {template_len}))))
+"#,
+        manifest = escape(MANIFEST),
+        manifest_len = MANIFEST.len(),
+        stdout_start = escape(stdout_start),
+        start_len = stdout_start.len(),
+        stdout_end = escape(stdout_end),
+        end_len = stdout_end.len(),
+        wrap_len = stdout_start.len() + stdout_end.len(),
+        denied = escape(denied),
+        denied_len = denied.len(),
+        detected = escape(detected),
+        detected_len = detected.len(),
+        template = escape(template),
+        template_len = template.len(),
+    );
+
+    wat::parse_str(wat).unwrap()
+}
+
+/// A plugin whose `spin` command never returns.
+pub fn spinning() -> Vec<u8> {
+    let manifest = r#"{"name":"spin","commands":[{"name":"spin"}]}"#;
+    let wat = format!(
+        r#"(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "{manifest}")
+  (func (export "tram_alloc") (param i32) (result i32) (i32.const 1024))
+  (func (export "tram_manifest") (result i64) (i64.const {manifest_len}))
+  (func (export "tram_command") (param i32 i32) (result i64)
+    (loop $forever (br $forever))
+    (i64.const 0)))
+"#,
+        manifest = escape(manifest),
+        manifest_len = manifest.len(),
+    );
+
+    wat::parse_str(wat).unwrap()
+}
+
+/// `s` as the contents of a WAT string.
+fn escape(s: &str) -> String {
+    s.replace('\\', r"\\").replace('"', r#"\""#)
+}
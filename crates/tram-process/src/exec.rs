@@ -0,0 +1,652 @@
+//! Running child processes.
+//!
+//! A [`Process`] runs a program or a shell command line with its own working
+//! directory and environment. Its output is streamed line by line as it
+//! arrives (optionally prefixed, so output from several tasks stays
+//! readable), captured for the caller, or both, and the last lines are kept
+//! so failures can be summarized after the fact. Every run ends in an
+//! [`ExecResult`].
+//!
+//! Each process starts in its own process group, so a timeout or
+//! [`kill_all`] stops everything the command started, not just the shell
+//! running it.
+
+use crate::shell::Shell;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tram_core::{AppResult, TramError, status_exit_code};
+
+/// Lines of output kept for failure summaries.
+pub const DEFAULT_TAIL_LINES: usize = 20;
+
+/// Exit code reported for a process stopped by its timeout, as `timeout(1)`
+/// uses.
+pub const TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// Variables kept by [`Process::with_clean_env`], so programs can still be
+/// found and started.
+const ESSENTIAL_ENV: [&str; 4] = ["PATH", "HOME", "SYSTEMROOT", "TEMP"];
+
+/// Processes started by [`Process::run`] that are still running, by ID.
+/// Set to `None` by [`kill_all`], after which new processes are killed as
+/// soon as they start.
+static RUNNING: Mutex<Option<BTreeSet<u32>>> = Mutex::new(Some(BTreeSet::new()));
+
+/// Kill every process [`Process::run`] started that is still running,
+/// along with everything it started, and any process started from now on.
+/// For when tram is stopped by a signal or `--timeout` and exits without
+/// waiting for its commands.
+pub fn kill_all() {
+    let running = RUNNING.lock().ok().and_then(|mut running| running.take());
+
+    for pid in running.into_iter().flatten() {
+        kill_tree(pid);
+    }
+}
+
+/// Kill a process and its descendants, which share its process group on
+/// Unix.
+fn kill_tree(pid: u32) {
+    #[cfg(unix)]
+    {
+        // SAFETY: killpg only sends a signal
+        unsafe { libc::killpg(pid as libc::pid_t, libc::SIGKILL) };
+    }
+
+    #[cfg(windows)]
+    {
+        let _ = Command::new("taskkill")
+            .args(["/F", "/T", "/PID", &pid.to_string()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+}
+
+/// Keeps a running process in [`RUNNING`] until it's dropped.
+struct Tracked(u32);
+
+impl Tracked {
+    fn new(pid: u32) -> Self {
+        if let Ok(mut running) = RUNNING.lock() {
+            match running.as_mut() {
+                Some(running) => {
+                    running.insert(pid);
+                }
+                None => kill_tree(pid),
+            }
+        }
+
+        Self(pid)
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        if let Ok(mut running) = RUNNING.lock()
+            && let Some(running) = running.as_mut()
+        {
+            running.remove(&self.0);
+        }
+    }
+}
+
+/// What happens to a process's output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputMode {
+    /// Forward each line to this process's stdout and stderr as it arrives
+    #[default]
+    Stream,
+    /// Keep the output in [`ExecResult::stdout`] and [`ExecResult::stderr`]
+    /// without printing it
+    Capture,
+    /// Forward and keep the output
+    Tee,
+}
+
+impl OutputMode {
+    fn streams(self) -> bool {
+        matches!(self, OutputMode::Stream | OutputMode::Tee)
+    }
+
+    fn captures(self) -> bool {
+        matches!(self, OutputMode::Capture | OutputMode::Tee)
+    }
+}
+
+/// Receives each line of output, for [`Process::with_line_handler`].
+#[derive(Clone)]
+struct LineHandler(Arc<dyn Fn(&str) + Send + Sync>);
+
+impl std::fmt::Debug for LineHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("LineHandler")
+    }
+}
+
+/// A command to run as a child process.
+#[derive(Debug, Clone)]
+pub struct Process {
+    program: String,
+    args: Vec<String>,
+    /// The shell running the command line, for [`Process::command_line`]
+    shell: Option<(Shell, String)>,
+    cwd: Option<PathBuf>,
+    env: BTreeMap<String, String>,
+    env_removed: Vec<String>,
+    clean_env: bool,
+    prefix: Option<String>,
+    stdout_to_stderr: bool,
+    tail_lines: usize,
+    output: OutputMode,
+    line_handler: Option<LineHandler>,
+    timeout: Option<Duration>,
+}
+
+impl Process {
+    /// Run a program directly with arguments.
+    pub fn new<I, S>(program: impl Into<String>, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            program: program.into(),
+            args: args.into_iter().map(Into::into).collect(),
+            shell: None,
+            cwd: None,
+            env: BTreeMap::new(),
+            env_removed: Vec::new(),
+            clean_env: false,
+            prefix: None,
+            stdout_to_stderr: false,
+            tail_lines: DEFAULT_TAIL_LINES,
+            output: OutputMode::Stream,
+            line_handler: None,
+            timeout: None,
+        }
+    }
+
+    /// Run a command line through the platform shell (`sh -c` or `cmd /C`),
+    /// so pipes, `&&`, and globs work as they do in a terminal.
+    pub fn shell(command_line: impl Into<String>) -> Self {
+        Self::shell_with(Shell::platform_default(), command_line)
+    }
+
+    /// Run a command line through a particular shell.
+    pub fn shell_with(shell: Shell, command_line: impl Into<String>) -> Self {
+        let command_line = command_line.into();
+
+        Self {
+            shell: Some((shell, command_line.clone())),
+            ..Self::new(shell.program(), shell.command_args(&command_line))
+        }
+    }
+
+    /// Run in this directory instead of the current one.
+    pub fn with_cwd(mut self, cwd: impl AsRef<Path>) -> Self {
+        self.cwd = Some(cwd.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set an environment variable for the process.
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set several environment variables for the process.
+    pub fn with_envs<K, V>(mut self, vars: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.env.extend(
+            vars.into_iter()
+                .map(|(key, value)| (key.into(), value.into())),
+        );
+        self
+    }
+
+    /// Don't pass this variable on from tram's environment.
+    pub fn without_env(mut self, key: impl Into<String>) -> Self {
+        self.env_removed.push(key.into());
+        self
+    }
+
+    /// Start from an empty environment instead of tram's, keeping only what
+    /// programs need to start (`PATH`, `HOME`, and on Windows `SYSTEMROOT`
+    /// and `TEMP`) plus the variables set with [`Process::with_env`].
+    pub fn with_clean_env(mut self) -> Self {
+        self.clean_env = true;
+        self
+    }
+
+    /// Prefix every forwarded line, e.g. `[test] `.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Forward the process's stdout to tram's stderr, keeping tram's stdout
+    /// for a structured result such as a JSON document.
+    pub fn with_stdout_to_stderr(mut self, stdout_to_stderr: bool) -> Self {
+        self.stdout_to_stderr = stdout_to_stderr;
+        self
+    }
+
+    /// How many trailing lines of output to keep (default 20).
+    pub fn with_tail_lines(mut self, lines: usize) -> Self {
+        self.tail_lines = lines;
+        self
+    }
+
+    /// Stream, capture, or stream and capture the output (default stream).
+    pub fn with_output(mut self, output: OutputMode) -> Self {
+        self.output = output;
+        self
+    }
+
+    /// Also pass every line of output, from either stream and without the
+    /// prefix, to `handler` as it arrives, e.g. to show it in a UI. Combine
+    /// with [`OutputMode::Capture`] to keep the lines from being printed.
+    pub fn with_line_handler(mut self, handler: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.line_handler = Some(LineHandler(Arc::new(handler)));
+        self
+    }
+
+    /// Kill the process if it's still running after `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// The command as it'd be typed in a terminal: the command line for
+    /// shell commands, the quoted program and arguments otherwise.
+    pub fn command_line(&self) -> String {
+        match &self.shell {
+            Some((_, command_line)) => command_line.clone(),
+            None => {
+                Shell::platform_default().join(std::iter::once(&self.program).chain(&self.args))
+            }
+        }
+    }
+
+    /// Run to completion, handling output as configured.
+    pub fn run(&self) -> AppResult<ExecResult> {
+        if let Some(cwd) = &self.cwd
+            && !cwd.is_dir()
+        {
+            return Err(TramError::Process {
+                message: format!(
+                    "Can't run `{}`: the working directory {} doesn't exist",
+                    self.command_line(),
+                    cwd.display()
+                ),
+            }
+            .into());
+        }
+
+        let started = Instant::now();
+        let mut child = self.command().spawn().map_err(|e| TramError::Process {
+            message: format!("Failed to run `{}`: {}", self.command_line(), e),
+        })?;
+        let tracked = Tracked::new(child.id());
+
+        let tail = Arc::new(Mutex::new(VecDeque::with_capacity(self.tail_lines)));
+        let stdout = child.stdout.take().map(|pipe| match self.stdout_to_stderr {
+            true => self.forward(pipe, std::io::stderr, Arc::clone(&tail)),
+            false => self.forward(pipe, std::io::stdout, Arc::clone(&tail)),
+        });
+        let stderr = child
+            .stderr
+            .take()
+            .map(|pipe| self.forward(pipe, std::io::stderr, Arc::clone(&tail)));
+
+        let (status, timed_out) = self.wait(&mut child)?;
+        let duration = started.elapsed();
+        drop(tracked);
+
+        // After a timeout, processes the command started may still hold the
+        // pipes open, so don't wait for them to finish
+        let mut captured = [stdout, stderr].map(|reader| {
+            reader
+                .filter(|_| !timed_out)
+                .and_then(|reader| reader.join().ok())
+                .unwrap_or_default()
+        });
+
+        let output_tail = tail
+            .lock()
+            .map(|lines| lines.iter().cloned().collect())
+            .unwrap_or_default();
+
+        Ok(ExecResult {
+            command: self.command_line(),
+            exit_code: if timed_out {
+                TIMEOUT_EXIT_CODE
+            } else {
+                status_exit_code(status)
+            },
+            duration,
+            stdout: std::mem::take(&mut captured[0]),
+            stderr: std::mem::take(&mut captured[1]),
+            output_tail,
+            timed_out,
+        })
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        if self.clean_env {
+            command.env_clear();
+            for key in ESSENTIAL_ENV {
+                if let Some(value) = std::env::var_os(key) {
+                    command.env(key, value);
+                }
+            }
+        }
+        for key in &self.env_removed {
+            command.env_remove(key);
+        }
+        command.envs(&self.env);
+
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(cwd);
+        }
+
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+        command
+    }
+
+    /// Wait for the process to exit, killing it at the timeout. Returns the
+    /// status and whether the timeout was hit.
+    fn wait(&self, child: &mut Child) -> AppResult<(ExitStatus, bool)> {
+        let error = |e: std::io::Error| TramError::Process {
+            message: format!("Failed to wait for `{}`: {}", self.command_line(), e),
+        };
+
+        let Some(timeout) = self.timeout else {
+            return Ok((child.wait().map_err(error)?, false));
+        };
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(status) = child.try_wait().map_err(error)? {
+                return Ok((status, false));
+            }
+            if Instant::now() >= deadline {
+                kill_tree(child.id());
+                let _ = child.kill();
+                return Ok((child.wait().map_err(error)?, true));
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Read lines from a pipe on a background thread, forwarding them to an
+    /// output stream and remembering the most recent ones. The thread
+    /// returns the captured output.
+    fn forward<R, W>(
+        &self,
+        pipe: R,
+        stream: fn() -> W,
+        tail: Arc<Mutex<VecDeque<String>>>,
+    ) -> std::thread::JoinHandle<String>
+    where
+        R: Read + Send + 'static,
+        W: Write + 'static,
+    {
+        let prefix = self.prefix.clone().unwrap_or_default();
+        let tail_lines = self.tail_lines;
+        let output = self.output;
+        let handler = self.line_handler.clone();
+
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(pipe);
+            let mut buffer = Vec::new();
+            let mut captured = String::new();
+
+            while reader.read_until(b'\n', &mut buffer).unwrap_or(0) > 0 {
+                let text = String::from_utf8_lossy(&buffer);
+                let line = text.trim_end_matches(['\n', '\r']);
+
+                if output.streams() {
+                    // Write the whole line at once so parallel processes don't interleave mid-line
+                    let _ = writeln!(stream(), "{}{}", prefix, line);
+                }
+                if output.captures() {
+                    captured.push_str(&text);
+                }
+                if let Some(handler) = &handler {
+                    (handler.0)(line);
+                }
+
+                if tail_lines > 0
+                    && let Ok(mut tail) = tail.lock()
+                {
+                    if tail.len() == tail_lines {
+                        tail.pop_front();
+                    }
+                    tail.push_back(line.to_string());
+                }
+
+                buffer.clear();
+            }
+
+            captured
+        })
+    }
+}
+
+/// How a process finished.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecResult {
+    /// The command, as [`Process::command_line`] shows it
+    pub command: String,
+    /// Exit code, `128 + signal` for processes killed by a signal, or
+    /// [`TIMEOUT_EXIT_CODE`] for processes stopped by their timeout
+    pub exit_code: i32,
+    pub duration: Duration,
+    /// Captured stdout, empty unless output was captured or the process
+    /// timed out
+    pub stdout: String,
+    /// Captured stderr, like [`ExecResult::stdout`]
+    pub stderr: String,
+    /// The last lines of combined stdout and stderr
+    pub output_tail: Vec<String>,
+    /// Whether the process was killed for running past its timeout
+    pub timed_out: bool,
+}
+
+impl ExecResult {
+    pub fn success(&self) -> bool {
+        self.exit_code == 0 && !self.timed_out
+    }
+
+    /// This result if the process succeeded, an error saying how it failed,
+    /// with the last lines of output, otherwise.
+    pub fn check(self) -> AppResult<Self> {
+        if self.success() {
+            return Ok(self);
+        }
+
+        let mut message = if self.timed_out {
+            format!("`{}` timed out after {:.1?}", self.command, self.duration)
+        } else {
+            format!(
+                "`{}` failed with exit code {}",
+                self.command, self.exit_code
+            )
+        };
+        for line in &self.output_tail {
+            message.push_str("\n  ");
+            message.push_str(line);
+        }
+
+        Err(TramError::Process { message }.into())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_shell_command_exit_code_and_tail() {
+        let result = Process::shell("echo one; echo two >&2; echo three; exit 3")
+            .with_tail_lines(2)
+            .run()
+            .unwrap();
+
+        assert!(!result.success());
+        assert_eq!(result.exit_code, 3);
+        assert_eq!(result.output_tail.len(), 2);
+        assert!(result.output_tail.contains(&"three".to_string()));
+        assert_eq!(result.stdout, "");
+    }
+
+    #[test]
+    fn test_cwd_and_env() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("marker"), "").unwrap();
+
+        let result = Process::shell(r#"test -f marker && [ "$TRAM_TEST_VALUE" = "yes" ]"#)
+            .with_cwd(temp_dir.path())
+            .with_env("TRAM_TEST_VALUE", "yes")
+            .run()
+            .unwrap();
+
+        assert!(result.success());
+    }
+
+    #[test]
+    fn test_missing_program_and_cwd_are_errors() {
+        assert!(
+            Process::new("tram-surely-missing-program", ["x"])
+                .run()
+                .is_err()
+        );
+
+        let error = Process::shell("true")
+            .with_cwd("/tram/surely/missing")
+            .run()
+            .unwrap_err();
+        assert!(error.to_string().contains("doesn't exist"), "{}", error);
+    }
+
+    #[test]
+    fn test_capture() {
+        let result = Process::shell("echo out; echo err >&2")
+            .with_output(OutputMode::Capture)
+            .run()
+            .unwrap();
+
+        assert_eq!(result.stdout, "out\n");
+        assert_eq!(result.stderr, "err\n");
+        assert_eq!(result.command, "echo out; echo err >&2");
+    }
+
+    #[test]
+    fn test_line_handler() {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&lines);
+
+        let result = Process::shell("echo one; echo two >&2")
+            .with_output(OutputMode::Capture)
+            .with_prefix("[x] ")
+            .with_line_handler(move |line| seen.lock().unwrap().push(line.to_string()))
+            .run()
+            .unwrap();
+
+        let mut lines = lines.lock().unwrap().clone();
+        lines.sort();
+        assert_eq!(lines, ["one", "two"]);
+        assert_eq!(result.stdout, "one\n");
+    }
+
+    #[test]
+    fn test_env_scoping() {
+        // SAFETY: no other test reads or writes this variable
+        unsafe { std::env::set_var("TRAM_PROCESS_INHERITED", "inherited") };
+        let run = |process: Process| {
+            process
+                .with_env("TRAM_PROCESS_SET", "set")
+                .with_output(OutputMode::Capture)
+                .run()
+                .unwrap()
+                .stdout
+        };
+        let script = r#"printf '%s|%s|%s' "${TRAM_PROCESS_INHERITED-unset}" "$TRAM_PROCESS_SET" "${PATH+path}""#;
+
+        assert_eq!(run(Process::shell(script)), "inherited|set|path");
+        assert_eq!(
+            run(Process::shell(script).without_env("TRAM_PROCESS_INHERITED")),
+            "unset|set|path"
+        );
+        assert_eq!(
+            run(Process::shell(script).with_clean_env()),
+            "unset|set|path"
+        );
+    }
+
+    #[test]
+    fn test_timeout() {
+        let result = Process::shell("sleep 5")
+            .with_timeout(Duration::from_millis(100))
+            .run()
+            .unwrap();
+
+        assert!(result.timed_out);
+        assert_eq!(result.exit_code, TIMEOUT_EXIT_CODE);
+        assert!(result.duration < Duration::from_secs(2));
+        assert!(
+            result
+                .check()
+                .unwrap_err()
+                .to_string()
+                .contains("`sleep 5` timed out")
+        );
+    }
+
+    #[test]
+    fn test_timeout_kills_what_the_process_started() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let result = Process::shell("(sleep 1 && touch finished) & sleep 5")
+            .with_cwd(temp_dir.path())
+            .with_timeout(Duration::from_millis(100))
+            .run()
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(1500));
+
+        assert!(result.timed_out);
+        assert!(!temp_dir.path().join("finished").exists());
+    }
+
+    #[test]
+    fn test_check_includes_the_tail() {
+        let error = Process::new("sh", ["-c", "echo boom; exit 2"])
+            .run()
+            .unwrap()
+            .check()
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("`sh -c 'echo boom; exit 2'` failed with exit code 2"));
+        assert!(error.contains("boom"));
+    }
+}
@@ -0,0 +1,32 @@
+//! Running external tools for Tram CLI applications.
+//!
+//! - [`Process`] runs a program or a shell command line with its own working
+//!   directory and environment, streaming or capturing its output, and
+//!   returns an [`ExecResult`]
+//! - [`Shell`] picks the shell a command line runs in, detects the user's
+//!   shell, and quotes arguments for it
+//! - [`Script`] and [`run_script`] run multi-line scripts with the right
+//!   interpreter
+//! - [`which`] finds programs on `PATH`
+//! - [`TaskGraph`] and [`TaskRunner`] run named tasks in dependency order,
+//!   in parallel where they can
+//! - [`Scheduler`] runs [`ScheduledTask`]s on cron schedules, with jitter,
+//!   and keeps each task's last run in the state store
+//!
+//! Watch tasks, `tram run`, and `tram doctor` all start their processes
+//! here, so quoting, environment handling, and failure reporting match
+//! everywhere.
+
+pub mod exec;
+pub mod schedule;
+pub mod script;
+pub mod shell;
+pub mod tasks;
+pub mod which;
+
+pub use exec::*;
+pub use schedule::*;
+pub use script::*;
+pub use shell::*;
+pub use tasks::*;
+pub use which::*;
@@ -0,0 +1,537 @@
+//! Tasks run on cron schedules.
+//!
+//! A [`Scheduler`] runs [`ScheduledTask`]s on a background thread for as
+//! long as its [`SchedulerHandle`] is kept, which `tram watch` and the
+//! daemon do while they run. Schedules are cron expressions in local time:
+//! five fields, an optional leading seconds field, or a nickname such as
+//! `@hourly` or `@daily`.
+//!
+//! Each task may have jitter: a delay of up to that long, fixed for a given
+//! task and occurrence, so machines sharing a config don't all start at
+//! once. A task still running when its next occurrence comes round skips
+//! that occurrence rather than running twice.
+//!
+//! With a [`StateStore`], the last run of each task is kept under
+//! [`SCHEDULE_STATE_KEY`]. An occurrence missed while nothing was running
+//! runs once when the scheduler starts, and processes sharing the store
+//! claim each occurrence before running it, so only one of them does.
+
+use crate::tasks::Task;
+use chrono::{DateTime, Local};
+use croner::Cron;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+use tram_core::{AppResult, StateStore, TramError};
+
+/// State key holding the last run of every scheduled task, by name.
+pub const SCHEDULE_STATE_KEY: &str = "schedules";
+
+/// Longest the scheduler sleeps between checks, so a changed clock or a
+/// resumed laptop is noticed within a minute.
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+/// Most occurrences stepped through when catching up, so a per-second
+/// schedule left for a year doesn't spin.
+const MAX_CATCH_UP: usize = 100_000;
+
+/// A parsed cron expression.
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    expression: String,
+    cron: Cron,
+}
+
+impl Schedule {
+    /// Parse a cron expression such as `0 3 * * *` or `@hourly`.
+    pub fn parse(expression: &str) -> AppResult<Self> {
+        let cron = Cron::new(expression.trim())
+            .with_seconds_optional()
+            .parse()
+            .map_err(|e| TramError::InvalidInput {
+                input: expression.to_string(),
+                message: format!("not a cron schedule: {}", e),
+            })?;
+
+        Ok(Self {
+            expression: expression.trim().to_string(),
+            cron,
+        })
+    }
+
+    /// The expression as written.
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// The first occurrence strictly after `time`.
+    pub fn next_after(&self, time: &DateTime<Local>) -> Option<DateTime<Local>> {
+        self.cron.find_next_occurrence(time, false).ok()
+    }
+}
+
+/// A shell command run on a schedule.
+#[derive(Debug, Clone)]
+pub struct ScheduledTask {
+    pub name: String,
+    pub schedule: Schedule,
+    /// Shell command line, run from the scheduler's root
+    pub command: String,
+    /// Longest a run may be delayed past its occurrence
+    pub jitter: Duration,
+}
+
+impl ScheduledTask {
+    pub fn new(name: impl Into<String>, schedule: Schedule, command: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            schedule,
+            command: command.into(),
+            jitter: Duration::ZERO,
+        }
+    }
+
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// When the run for `occurrence` starts: the occurrence plus a delay of
+    /// up to the jitter, the same for every process that computes it.
+    pub fn start_time(&self, occurrence: &DateTime<Local>) -> DateTime<Local> {
+        let jitter_ms = self.jitter.as_millis() as u64;
+        if jitter_ms == 0 {
+            return *occurrence;
+        }
+
+        let mut hasher = DefaultHasher::new();
+        self.name.hash(&mut hasher);
+        occurrence.timestamp().hash(&mut hasher);
+        let delay = hasher.finish() % (jitter_ms + 1);
+
+        *occurrence + Duration::from_millis(delay)
+    }
+
+    /// The latest occurrence from `first` on that isn't after `now`, or
+    /// `first` itself if even that is still to come.
+    fn latest_occurrence(&self, first: DateTime<Local>, now: &DateTime<Local>) -> DateTime<Local> {
+        let mut latest = first;
+
+        for _ in 0..MAX_CATCH_UP {
+            match self.schedule.next_after(&latest) {
+                Some(next) if next <= *now => latest = next,
+                _ => break,
+            }
+        }
+
+        latest
+    }
+}
+
+/// The last run of a scheduled task, as kept in the state store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleRun {
+    /// The occurrence the run was for, in seconds since the Unix epoch
+    pub occurrence: i64,
+    /// When the run started, in seconds since the Unix epoch
+    pub started_at: i64,
+    /// When it finished; unset while it's running or if it was cut short
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+}
+
+/// The last run of every task in `state`, by name.
+pub fn schedule_runs(state: &StateStore) -> AppResult<BTreeMap<String, ScheduleRun>> {
+    Ok(state.get(SCHEDULE_STATE_KEY)?.unwrap_or_default())
+}
+
+/// Runs scheduled tasks on a background thread.
+#[derive(Debug, Clone)]
+pub struct Scheduler {
+    root: PathBuf,
+    tasks: Vec<ScheduledTask>,
+    state: Option<StateStore>,
+}
+
+impl Scheduler {
+    /// Run `tasks` from `root`.
+    pub fn new(root: impl Into<PathBuf>, tasks: impl IntoIterator<Item = ScheduledTask>) -> Self {
+        Self {
+            root: root.into(),
+            tasks: tasks.into_iter().collect(),
+            state: None,
+        }
+    }
+
+    /// Keep each task's last run in `state`.
+    pub fn with_state(mut self, state: StateStore) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    pub fn tasks(&self) -> &[ScheduledTask] {
+        &self.tasks
+    }
+
+    /// Start running tasks as they come due, until the handle is dropped.
+    pub fn start(self) -> SchedulerHandle {
+        let (stop, stopped) = mpsc::channel::<()>();
+
+        std::thread::spawn(move || {
+            let shared = Arc::new(Shared {
+                root: self.root,
+                state: self.state,
+                state_lock: Mutex::new(()),
+            });
+            let now = Local::now();
+            let mut slots: Vec<Slot> = self
+                .tasks
+                .into_iter()
+                .filter_map(|task| Slot::new(task, &shared, &now))
+                .collect();
+
+            loop {
+                let now = Local::now();
+                for slot in &mut slots {
+                    slot.run_if_due(&shared, &now);
+                }
+
+                let wait = slots
+                    .iter()
+                    .filter_map(|slot| slot.start.as_ref())
+                    .map(|start| (*start - Local::now()).to_std().unwrap_or_default())
+                    .min()
+                    .unwrap_or(MAX_SLEEP)
+                    .min(MAX_SLEEP);
+
+                match stopped.recv_timeout(wait) {
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Ok(()) | Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        });
+
+        SchedulerHandle { _stop: stop }
+    }
+
+    /// Record that `task` is running for `occurrence`, unless another
+    /// process already has. Without a state store every claim succeeds.
+    pub fn claim(&self, task: &ScheduledTask, occurrence: &DateTime<Local>) -> AppResult<bool> {
+        match &self.state {
+            Some(state) => claim(state, &task.name, occurrence),
+            None => Ok(true),
+        }
+    }
+}
+
+/// Keeps a [`Scheduler`] running; dropping it stops the scheduler. Runs
+/// already started are left to finish.
+#[derive(Debug)]
+pub struct SchedulerHandle {
+    _stop: mpsc::Sender<()>,
+}
+
+/// What the scheduler thread shares with the runs it starts.
+struct Shared {
+    root: PathBuf,
+    state: Option<StateStore>,
+    /// Runs finish on their own threads; keep their state updates apart
+    state_lock: Mutex<()>,
+}
+
+/// A task and its next occurrence.
+struct Slot {
+    task: ScheduledTask,
+    /// The next occurrence, or `None` once the schedule has no more
+    occurrence: Option<DateTime<Local>>,
+    /// When the run for that occurrence starts, jitter included
+    start: Option<DateTime<Local>>,
+    running: Arc<AtomicBool>,
+}
+
+impl Slot {
+    /// Start from the occurrence after the task's last run, so a missed
+    /// occurrence runs now, or from the next one if it has never run.
+    fn new(task: ScheduledTask, shared: &Shared, now: &DateTime<Local>) -> Option<Self> {
+        let last_run = shared.state.as_ref().and_then(|state| {
+            schedule_runs(state)
+                .map_err(|e| warn!("Failed to read schedule state: {}", e))
+                .ok()?
+                .remove(&task.name)
+        });
+        let after = last_run
+            .and_then(|run| DateTime::from_timestamp(run.occurrence, 0))
+            .map(|time| time.with_timezone(&Local))
+            .unwrap_or(*now);
+
+        let mut slot = Self {
+            task,
+            occurrence: None,
+            start: None,
+            running: Arc::new(AtomicBool::new(false)),
+        };
+        slot.advance(&after);
+
+        match slot.occurrence {
+            Some(occurrence) => {
+                debug!(
+                    "Scheduled task '{}' next runs at {}",
+                    slot.task.name, occurrence
+                );
+                Some(slot)
+            }
+            None => {
+                warn!(
+                    "Schedule '{}' of task '{}' never occurs",
+                    slot.task.schedule.expression(),
+                    slot.task.name
+                );
+                None
+            }
+        }
+    }
+
+    fn advance(&mut self, after: &DateTime<Local>) {
+        self.occurrence = self.task.schedule.next_after(after);
+        self.start = self
+            .occurrence
+            .as_ref()
+            .map(|occurrence| self.task.start_time(occurrence));
+    }
+
+    fn run_if_due(&mut self, shared: &Arc<Shared>, now: &DateTime<Local>) {
+        let (Some(occurrence), Some(start)) = (self.occurrence, self.start) else {
+            return;
+        };
+        if start > *now {
+            return;
+        }
+
+        // Occurrences missed while nothing was running collapse into one run
+        let occurrence = self.task.latest_occurrence(occurrence, now);
+        self.advance(&occurrence);
+
+        if self.running.load(Ordering::SeqCst) {
+            warn!(
+                "Skipping scheduled task '{}': the previous run is still going",
+                self.task.name
+            );
+            return;
+        }
+
+        if let Some(state) = &shared.state {
+            let _guard = shared.state_lock.lock();
+            match claim(state, &self.task.name, &occurrence) {
+                Ok(true) => {}
+                Ok(false) => {
+                    debug!(
+                        "Scheduled task '{}' already ran for {}",
+                        self.task.name, occurrence
+                    );
+                    return;
+                }
+                Err(e) => warn!("Failed to record scheduled run: {}", e),
+            }
+        }
+
+        self.running.store(true, Ordering::SeqCst);
+        let task = self.task.clone();
+        let running = Arc::clone(&self.running);
+        let shared = Arc::clone(shared);
+
+        std::thread::spawn(move || {
+            let exit_code = run(&task, &shared.root);
+
+            if let Some(state) = &shared.state {
+                let _guard = shared.state_lock.lock();
+                if let Err(e) = finish(state, &task.name, &occurrence, exit_code) {
+                    warn!("Failed to record scheduled run: {}", e);
+                }
+            }
+            running.store(false, Ordering::SeqCst);
+        });
+    }
+}
+
+/// Run a task, returning its exit code; `None` if it couldn't start.
+fn run(task: &ScheduledTask, root: &Path) -> Option<i32> {
+    info!("Running scheduled task '{}'", task.name);
+
+    match Task::new(&task.name, &task.command).run(root) {
+        Ok(result) if result.success() => {
+            info!(
+                "Scheduled task '{}' finished in {:.1?}",
+                task.name, result.duration
+            );
+            Some(result.exit_code)
+        }
+        Ok(result) => {
+            warn!(
+                "Scheduled task '{}' failed with exit code {}",
+                task.name, result.exit_code
+            );
+            Some(result.exit_code)
+        }
+        Err(e) => {
+            warn!("Scheduled task '{}' failed to start: {}", task.name, e);
+            None
+        }
+    }
+}
+
+fn claim(state: &StateStore, name: &str, occurrence: &DateTime<Local>) -> AppResult<bool> {
+    let mut runs = schedule_runs(state)?;
+    if runs
+        .get(name)
+        .is_some_and(|run| run.occurrence >= occurrence.timestamp())
+    {
+        return Ok(false);
+    }
+
+    runs.insert(
+        name.to_string(),
+        ScheduleRun {
+            occurrence: occurrence.timestamp(),
+            started_at: Local::now().timestamp(),
+            finished_at: None,
+            exit_code: None,
+        },
+    );
+    state.set(SCHEDULE_STATE_KEY, &runs)?;
+
+    Ok(true)
+}
+
+fn finish(
+    state: &StateStore,
+    name: &str,
+    occurrence: &DateTime<Local>,
+    exit_code: Option<i32>,
+) -> AppResult<()> {
+    let mut runs = schedule_runs(state)?;
+
+    // A later occurrence may have been claimed elsewhere in the meantime
+    if let Some(run) = runs
+        .get_mut(name)
+        .filter(|run| run.occurrence == occurrence.timestamp())
+    {
+        run.finished_at = Some(Local::now().timestamp());
+        run.exit_code = exit_code;
+        state.set(SCHEDULE_STATE_KEY, &runs)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::time::Instant;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2026, 3, 10, hour, minute, 0)
+            .single()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let hourly = Schedule::parse("@hourly").unwrap();
+        assert_eq!(hourly.next_after(&at(9, 15)), Some(at(10, 0)));
+
+        let nightly = Schedule::parse(" 30 2 * * * ").unwrap();
+        assert_eq!(nightly.expression(), "30 2 * * *");
+        assert_eq!(nightly.next_after(&at(1, 0)), Some(at(2, 30)));
+
+        let error = Schedule::parse("every hour").unwrap_err();
+        assert!(error.to_string().contains("every hour"));
+    }
+
+    #[test]
+    fn test_jitter_is_bounded_and_stable() {
+        let schedule = Schedule::parse("@hourly").unwrap();
+        let task = ScheduledTask::new("prune", schedule.clone(), "true");
+        assert_eq!(task.start_time(&at(10, 0)), at(10, 0));
+
+        let task = task.with_jitter(Duration::from_secs(300));
+        let other =
+            ScheduledTask::new("update", schedule, "true").with_jitter(Duration::from_secs(300));
+
+        let mut delays = Vec::new();
+        for hour in 0..24 {
+            let start = task.start_time(&at(hour, 0));
+            assert_eq!(start, task.start_time(&at(hour, 0)));
+            assert!(start >= at(hour, 0) && start <= at(hour, 5));
+            delays.push(start - at(hour, 0));
+            delays.push(other.start_time(&at(hour, 0)) - at(hour, 0));
+        }
+
+        delays.dedup();
+        assert!(delays.len() > 1, "jitter never varies");
+    }
+
+    #[test]
+    fn test_missed_occurrences_collapse() {
+        let task = ScheduledTask::new("prune", Schedule::parse("@hourly").unwrap(), "true");
+
+        assert_eq!(task.latest_occurrence(at(3, 0), &at(9, 30)), at(9, 0));
+        assert_eq!(task.latest_occurrence(at(10, 0), &at(9, 30)), at(10, 0));
+    }
+
+    #[test]
+    fn test_each_occurrence_is_claimed_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = StateStore::new(dir.path().join("state.json"));
+        let task = ScheduledTask::new("prune", Schedule::parse("@hourly").unwrap(), "true");
+        let scheduler = Scheduler::new(dir.path(), [task.clone()]).with_state(state.clone());
+
+        assert!(scheduler.claim(&task, &at(9, 0)).unwrap());
+        assert!(!scheduler.claim(&task, &at(9, 0)).unwrap());
+        assert!(!scheduler.claim(&task, &at(8, 0)).unwrap());
+
+        finish(&state, "prune", &at(9, 0), Some(0)).unwrap();
+        let run = &schedule_runs(&state).unwrap()["prune"];
+        assert_eq!(run.occurrence, at(9, 0).timestamp());
+        assert_eq!(run.exit_code, Some(0));
+        assert!(run.finished_at.is_some());
+
+        assert!(scheduler.claim(&task, &at(10, 0)).unwrap());
+        assert!(
+            Scheduler::new(dir.path(), [])
+                .claim(&task, &at(10, 0))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_scheduler_runs_due_tasks() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = StateStore::new(dir.path().join("state.json"));
+        let every_second = Schedule::parse("* * * * * *").unwrap();
+        let task = ScheduledTask::new("mark", every_second, "echo ran > marker");
+
+        let handle = Scheduler::new(dir.path(), [task])
+            .with_state(state.clone())
+            .start();
+
+        let started = Instant::now();
+        while !dir.path().join("marker").exists() && started.elapsed() < Duration::from_secs(10) {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        drop(handle);
+
+        assert!(dir.path().join("marker").exists());
+        assert!(schedule_runs(&state).unwrap().contains_key("mark"));
+    }
+}
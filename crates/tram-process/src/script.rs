@@ -0,0 +1,167 @@
+//! Running multi-line scripts.
+//!
+//! A [`Script`] is written to a temporary file and run by its interpreter:
+//! the one named by a `#!` line if it has one, otherwise a shell. Unlike
+//! `sh -c`, this works for `cmd`, which only runs one line at a time, and
+//! for scripts in other languages.
+
+use crate::exec::{ExecResult, Process};
+use crate::shell::Shell;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tram_core::{AppResult, TramError};
+
+/// Distinguishes script files written by the same process.
+static SCRIPT_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// A script and the interpreter that runs it.
+#[derive(Debug, Clone)]
+pub struct Script {
+    body: String,
+    shell: Shell,
+}
+
+impl Script {
+    /// A script run by the platform shell unless it starts with `#!`.
+    pub fn new(body: impl Into<String>) -> Self {
+        Self {
+            body: body.into(),
+            shell: Shell::platform_default(),
+        }
+    }
+
+    /// Run the script in `shell` when it has no `#!` line.
+    pub fn with_shell(mut self, shell: Shell) -> Self {
+        self.shell = shell;
+        self
+    }
+
+    /// The interpreter and its arguments from the `#!` line, if there is
+    /// one. `/usr/bin/env` is dropped, and on Windows the interpreter's
+    /// directory is too, so `#!/usr/bin/env python3` runs `python3` from
+    /// `PATH` everywhere.
+    pub fn shebang(&self) -> Option<Vec<String>> {
+        let line = self.body.lines().next()?.strip_prefix("#!")?;
+        let mut words: Vec<String> = line.split_whitespace().map(str::to_string).collect();
+
+        if words
+            .first()
+            .is_some_and(|program| Path::new(program).file_name() == Some("env".as_ref()))
+        {
+            words.remove(0);
+            // `env -S` splits the rest into words, which we've done already
+            if words.first().is_some_and(|flag| flag == "-S") {
+                words.remove(0);
+            }
+        }
+
+        let program = words.first_mut()?;
+        if cfg!(windows)
+            && let Some(name) = Path::new(program.as_str()).file_name()
+        {
+            *program = name.to_string_lossy().into_owned();
+        }
+
+        Some(words)
+    }
+
+    /// Write the script to a temporary file, run it, and remove the file.
+    /// `configure` sets up the process, e.g. its working directory,
+    /// environment, and output handling.
+    pub fn run(&self, configure: impl FnOnce(Process) -> Process) -> AppResult<ExecResult> {
+        let path = self.write()?;
+
+        let process = match self.shebang().as_deref() {
+            Some([program, args @ ..]) => Process::new(
+                program,
+                args.iter().cloned().chain([path.display().to_string()]),
+            ),
+            _ => Process::new(self.shell.program(), self.shell.script_args(&path)),
+        };
+
+        let result = configure(process).run();
+        let _ = std::fs::remove_file(&path);
+
+        result
+    }
+
+    /// Write the body to a new temporary file named for the interpreter.
+    fn write(&self) -> AppResult<PathBuf> {
+        let extension = match self.shebang() {
+            Some(_) => "script",
+            None => self.shell.script_extension(),
+        };
+        let path = std::env::temp_dir().join(format!(
+            "tram-script-{}-{}.{}",
+            std::process::id(),
+            SCRIPT_COUNT.fetch_add(1, Ordering::Relaxed),
+            extension
+        ));
+
+        // cmd needs CRLF line endings to run a script reliably
+        let body = if self.shell == Shell::Cmd && self.shebang().is_none() {
+            self.body.replace("\r\n", "\n").replace('\n', "\r\n")
+        } else {
+            self.body.clone()
+        };
+
+        std::fs::write(&path, body).map_err(|e| TramError::Process {
+            message: format!("Failed to write script to {}: {}", path.display(), e),
+        })?;
+
+        Ok(path)
+    }
+}
+
+/// Run a multi-line script with the platform shell, or the interpreter named
+/// by its `#!` line, streaming its output.
+pub fn run_script(body: &str) -> AppResult<ExecResult> {
+    Script::new(body).run(|process| process)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shebang() {
+        let shebang = |body: &str| Script::new(body).shebang();
+
+        assert_eq!(
+            shebang("#!/usr/bin/env python3\nprint(1)"),
+            Some(vec!["python3".to_string()])
+        );
+        assert_eq!(
+            shebang("#!/usr/bin/env -S node --no-warnings\n"),
+            Some(vec!["node".to_string(), "--no-warnings".to_string()])
+        );
+        assert_eq!(shebang("echo hi\n#!/bin/sh"), None);
+        assert_eq!(shebang("#!\n"), None);
+        if cfg!(unix) {
+            assert_eq!(
+                shebang("#!/bin/bash -e"),
+                Some(vec!["/bin/bash".to_string(), "-e".to_string()])
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_multi_line_scripts() {
+        use crate::exec::OutputMode;
+
+        let capture = |process: Process| process.with_output(OutputMode::Capture);
+
+        let result = Script::new("greeting=hello\necho \"$greeting\"\nexit 3\n")
+            .run(capture)
+            .unwrap();
+        assert_eq!(result.stdout, "hello\n");
+        assert_eq!(result.exit_code, 3);
+
+        let result = Script::new("#!/bin/sh -e\nfalse\necho unreachable\n")
+            .run(capture)
+            .unwrap();
+        assert!(!result.success());
+        assert_eq!(result.stdout, "");
+    }
+}
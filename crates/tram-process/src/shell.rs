@@ -0,0 +1,255 @@
+//! Shells that run command lines, and quoting arguments for them.
+//!
+//! Tasks run in [`Shell::platform_default`] so they behave the same for
+//! everyone; [`Shell::detect`] finds the user's own shell, for things that
+//! should feel like their terminal.
+
+use std::fmt;
+use std::path::Path;
+
+/// A shell that can run a command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Shell {
+    Sh,
+    Bash,
+    Zsh,
+    Fish,
+    /// PowerShell 7+ (`pwsh`)
+    PowerShell,
+    Cmd,
+}
+
+impl Shell {
+    const ALL: [(Shell, &str); 6] = [
+        (Shell::Sh, "sh"),
+        (Shell::Bash, "bash"),
+        (Shell::Zsh, "zsh"),
+        (Shell::Fish, "fish"),
+        (Shell::PowerShell, "pwsh"),
+        (Shell::Cmd, "cmd"),
+    ];
+
+    /// The shell task and watch commands run in: `cmd` on Windows, `sh`
+    /// elsewhere, so commands behave the same on every machine whatever the
+    /// user's interactive shell is.
+    pub fn platform_default() -> Self {
+        if cfg!(windows) { Shell::Cmd } else { Shell::Sh }
+    }
+
+    /// The user's shell: `SHELL` where it's set (Unix, and Git Bash or MSYS
+    /// on Windows), otherwise PowerShell or `cmd` on Windows, told apart by
+    /// `cmd` setting `PROMPT`.
+    pub fn detect() -> Option<Self> {
+        Self::detect_with(|key| std::env::var(key).ok())
+    }
+
+    /// [`Shell::detect`], reading variables from `env`.
+    pub fn detect_with(env: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let var = |key: &str| env(key).filter(|value| !value.is_empty());
+
+        if let Some(shell) = var("SHELL") {
+            return Path::new(&shell).file_name()?.to_str()?.parse().ok();
+        }
+
+        if var("PSModulePath").is_some() && var("PROMPT").is_none() {
+            Some(Shell::PowerShell)
+        } else if var("ComSpec").is_some() {
+            Some(Shell::Cmd)
+        } else {
+            None
+        }
+    }
+
+    /// The user's shell, or the platform default when it can't be detected.
+    pub fn user_default() -> Self {
+        Self::detect().unwrap_or_else(Self::platform_default)
+    }
+
+    /// The program to run, e.g. `pwsh`.
+    pub fn program(self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(shell, _)| *shell == self)
+            .map(|(_, name)| *name)
+            .unwrap_or_default()
+    }
+
+    /// Arguments that make the shell run `command_line` and exit.
+    pub fn command_args(self, command_line: &str) -> Vec<String> {
+        let flags: &[&str] = match self {
+            Shell::Sh | Shell::Bash | Shell::Zsh | Shell::Fish => &["-c"],
+            Shell::PowerShell => &["-NoProfile", "-NonInteractive", "-Command"],
+            Shell::Cmd => &["/C"],
+        };
+
+        flags
+            .iter()
+            .map(|flag| flag.to_string())
+            .chain([command_line.to_string()])
+            .collect()
+    }
+
+    /// Arguments that make the shell run the script file at `path`.
+    pub fn script_args(self, path: &Path) -> Vec<String> {
+        let path = path.display().to_string();
+
+        match self {
+            Shell::Sh | Shell::Bash | Shell::Zsh | Shell::Fish => vec![path],
+            Shell::PowerShell => ["-NoProfile", "-NonInteractive", "-File"]
+                .iter()
+                .map(|flag| flag.to_string())
+                .chain([path])
+                .collect(),
+            Shell::Cmd => vec!["/C".to_string(), path],
+        }
+    }
+
+    /// The extension the shell expects script files to have.
+    pub fn script_extension(self) -> &'static str {
+        match self {
+            Shell::Sh | Shell::Bash | Shell::Zsh => "sh",
+            Shell::Fish => "fish",
+            Shell::PowerShell => "ps1",
+            Shell::Cmd => "cmd",
+        }
+    }
+
+    /// Quote an argument so the shell passes it through as one word, with
+    /// no expansion. Words made only of safe characters are left as-is.
+    ///
+    /// `cmd` has no quoting that's safe for every program; double quotes are
+    /// used, which suits programs that parse arguments the usual way.
+    pub fn quote(self, arg: &str) -> String {
+        let safe = !arg.is_empty()
+            && arg
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+        if safe {
+            return arg.to_string();
+        }
+
+        match self {
+            Shell::Sh | Shell::Bash | Shell::Zsh => format!("'{}'", arg.replace('\'', r"'\''")),
+            Shell::Fish => format!("'{}'", arg.replace('\\', r"\\").replace('\'', r"\'")),
+            Shell::PowerShell => format!("'{}'", arg.replace('\'', "''")),
+            Shell::Cmd => format!("\"{}\"", arg.replace('"', "\"\"")),
+        }
+    }
+
+    /// Quote each argument and join them into a command line.
+    pub fn join<S: AsRef<str>>(self, args: impl IntoIterator<Item = S>) -> String {
+        args.into_iter()
+            .map(|arg| self.quote(arg.as_ref()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl fmt::Display for Shell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.program())
+    }
+}
+
+impl std::str::FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_lowercase();
+        let name = name.strip_suffix(".exe").unwrap_or(&name);
+
+        match name {
+            "powershell" => Ok(Shell::PowerShell),
+            _ => Self::ALL
+                .iter()
+                .find(|(_, known)| *known == name)
+                .map(|(shell, _)| *shell)
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown shell '{}' (expected sh, bash, zsh, fish, pwsh, or cmd)",
+                        s
+                    )
+                }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(Shell::Sh.quote("src/main.rs"), "src/main.rs");
+        assert_eq!(Shell::Sh.quote(""), "''");
+        assert_eq!(Shell::Bash.quote("it's $HOME"), r"'it'\''s $HOME'");
+        assert_eq!(Shell::Fish.quote(r"a\b 'c'"), r"'a\\b \'c\''");
+        assert_eq!(Shell::PowerShell.quote("it's"), "'it''s'");
+        assert_eq!(Shell::Cmd.quote(r#"say "hi""#), r#""say ""hi""""#);
+        assert_eq!(
+            Shell::Sh.join(["grep", "-r", "two words", "."]),
+            "grep -r 'two words' ."
+        );
+    }
+
+    #[test]
+    fn test_names_and_args() {
+        assert_eq!("PowerShell.exe".parse::<Shell>(), Ok(Shell::PowerShell));
+        assert_eq!("zsh".parse::<Shell>(), Ok(Shell::Zsh));
+        assert!("tcsh".parse::<Shell>().is_err());
+        assert_eq!(Shell::Fish.to_string(), "fish");
+        assert_eq!(Shell::Cmd.command_args("dir"), ["/C", "dir"]);
+        assert_eq!(
+            Shell::PowerShell.command_args("ls"),
+            ["-NoProfile", "-NonInteractive", "-Command", "ls"]
+        );
+    }
+
+    #[test]
+    fn test_detect() {
+        let detect = |vars: &[(&str, &str)]| {
+            Shell::detect_with(|key| {
+                vars.iter()
+                    .find(|(name, _)| *name == key)
+                    .map(|(_, value)| value.to_string())
+            })
+        };
+
+        assert_eq!(detect(&[("SHELL", "/usr/bin/zsh")]), Some(Shell::Zsh));
+        assert_eq!(
+            detect(&[("SHELL", "/opt/homebrew/bin/fish")]),
+            Some(Shell::Fish)
+        );
+        assert_eq!(detect(&[("SHELL", "/bin/tcsh")]), None);
+        assert_eq!(
+            detect(&[("PSModulePath", "C:\\Modules"), ("ComSpec", "cmd.exe")]),
+            Some(Shell::PowerShell)
+        );
+        assert_eq!(
+            detect(&[
+                ("PSModulePath", "C:\\Modules"),
+                ("PROMPT", "$P$G"),
+                ("ComSpec", "cmd.exe")
+            ]),
+            Some(Shell::Cmd)
+        );
+        assert_eq!(detect(&[("SHELL", "")]), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_quoted_words_survive_the_shell() {
+        let tricky = ["it's", "$HOME", "a b", "*", "\"", ""];
+        let line = format!("printf '%s|' {}", Shell::Sh.join(tricky));
+
+        let output = std::process::Command::new("sh")
+            .args(Shell::Sh.command_args(&line))
+            .output()
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "it's|$HOME|a b|*|\"||"
+        );
+    }
+}
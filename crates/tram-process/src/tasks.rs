@@ -0,0 +1,530 @@
+//! Named tasks with dependencies.
+//!
+//! A [`TaskGraph`] holds the tasks declared in a project's config and works
+//! out which ones to run, in dependency order, for a requested task. Each
+//! task is a shell command or script run through [`Process`] with its own
+//! working directory and environment. A [`TaskRunner`] runs independent
+//! tasks in parallel, up to a job limit, starting each task once its
+//! dependencies have passed.
+
+use crate::exec::{ExecResult, Process};
+use crate::script::Script;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use tram_core::{AppResult, TramError, did_you_mean};
+
+/// A command that can be run by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Task {
+    pub name: String,
+    /// Shell command line
+    pub command: String,
+    /// Working directory, relative to the graph's root
+    pub cwd: Option<PathBuf>,
+    pub env: BTreeMap<String, String>,
+    /// Tasks that must finish successfully first
+    pub depends_on: Vec<String>,
+}
+
+impl Task {
+    pub fn new(name: impl Into<String>, command: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            command: command.into(),
+            cwd: None,
+            env: BTreeMap::new(),
+            depends_on: Vec::new(),
+        }
+    }
+
+    pub fn with_cwd(mut self, cwd: impl Into<PathBuf>) -> Self {
+        self.cwd = Some(cwd.into());
+        self
+    }
+
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_depends_on<I, S>(mut self, tasks: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.depends_on = tasks.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// The process that runs this task's command line from `root`, with
+    /// output prefixed by the task name.
+    pub fn process(&self, root: &Path) -> Process {
+        self.configure(Process::shell(&self.command), root)
+    }
+
+    /// Run the task from `root`. Commands spanning several lines run as a
+    /// [`Script`], so they work in every shell and can start with `#!`.
+    pub fn run(&self, root: &Path) -> AppResult<ExecResult> {
+        self.run_with(root, false)
+    }
+
+    /// Run the task, optionally forwarding its stdout to tram's stderr.
+    fn run_with(&self, root: &Path, stdout_to_stderr: bool) -> AppResult<ExecResult> {
+        let configure = |process: Process| {
+            self.configure(process, root)
+                .with_stdout_to_stderr(stdout_to_stderr)
+        };
+
+        if self.command.trim().contains('\n') {
+            Script::new(&self.command).run(configure)
+        } else {
+            configure(Process::shell(&self.command)).run()
+        }
+    }
+
+    fn configure(&self, process: Process, root: &Path) -> Process {
+        let cwd = match &self.cwd {
+            Some(cwd) => root.join(tram_core::expand_path(cwd)),
+            None => root.to_path_buf(),
+        };
+
+        process
+            .with_cwd(cwd)
+            .with_envs(&self.env)
+            .with_prefix(format!("[{}] ", self.name))
+    }
+}
+
+/// A set of tasks and the dependencies between them.
+#[derive(Debug, Clone, Default)]
+pub struct TaskGraph {
+    root: PathBuf,
+    tasks: BTreeMap<String, Task>,
+}
+
+impl TaskGraph {
+    /// Create a graph whose task directories are relative to `root`.
+    pub fn new(root: impl Into<PathBuf>, tasks: impl IntoIterator<Item = Task>) -> Self {
+        Self {
+            root: root.into(),
+            tasks: tasks
+                .into_iter()
+                .map(|task| (task.name.clone(), task))
+                .collect(),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Every task, sorted by name.
+    pub fn tasks(&self) -> impl Iterator<Item = &Task> {
+        self.tasks.values()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Task> {
+        self.tasks.get(name)
+    }
+
+    /// The task and everything it depends on, dependencies first.
+    ///
+    /// Fails for unknown tasks and for dependency cycles.
+    pub fn execution_order(&self, target: &str) -> AppResult<Vec<&Task>> {
+        let mut order = Vec::new();
+        let mut done = HashSet::new();
+        let mut path = Vec::new();
+
+        self.visit(target, &mut path, &mut done, &mut order)?;
+
+        Ok(order)
+    }
+
+    fn visit<'a>(
+        &'a self,
+        name: &str,
+        path: &mut Vec<String>,
+        done: &mut HashSet<String>,
+        order: &mut Vec<&'a Task>,
+    ) -> AppResult<()> {
+        if done.contains(name) {
+            return Ok(());
+        }
+
+        if let Some(start) = path.iter().position(|seen| seen == name) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(name.to_string());
+
+            return Err(TramError::Task {
+                message: format!("Dependency cycle: {}", cycle.join(" -> ")),
+            }
+            .into());
+        }
+
+        let task = self.tasks.get(name).ok_or_else(|| {
+            let suggestion = did_you_mean(name, self.tasks.keys().map(String::as_str))
+                .map(|suggestion| format!(", did you mean '{}'?", suggestion))
+                .unwrap_or_default();
+
+            TramError::Task {
+                message: match path.last() {
+                    Some(parent) => format!(
+                        "Task '{}' depends on unknown task '{}'{}",
+                        parent, name, suggestion
+                    ),
+                    None => format!(
+                        "Unknown task '{}'{} (available: {})",
+                        name,
+                        suggestion,
+                        self.tasks.keys().cloned().collect::<Vec<_>>().join(", ")
+                    ),
+                },
+            }
+        })?;
+
+        path.push(name.to_string());
+        for dependency in &task.depends_on {
+            self.visit(dependency, path, done, order)?;
+        }
+        path.pop();
+
+        done.insert(name.to_string());
+        order.push(task);
+
+        Ok(())
+    }
+
+    /// A runner for tasks in this graph, one job per CPU, stopping at the
+    /// first failure.
+    pub fn runner(&self) -> TaskRunner<'_> {
+        TaskRunner {
+            graph: self,
+            jobs: std::thread::available_parallelism().unwrap_or(NonZeroUsize::MIN),
+            keep_going: false,
+            stdout_to_stderr: false,
+        }
+    }
+}
+
+/// How a task ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    Passed,
+    Failed,
+    /// Not run, because a dependency failed or the run stopped early
+    Skipped,
+}
+
+/// The result of one task in a run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskOutcome {
+    pub name: String,
+    pub status: TaskStatus,
+    /// Exit code, if the process ran
+    pub exit_code: Option<i32>,
+    pub duration: Duration,
+}
+
+impl TaskOutcome {
+    fn skipped(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            status: TaskStatus::Skipped,
+            exit_code: None,
+            duration: Duration::ZERO,
+        }
+    }
+}
+
+/// Runs a task and its dependencies, in parallel where the graph allows.
+#[derive(Debug, Clone)]
+pub struct TaskRunner<'a> {
+    graph: &'a TaskGraph,
+    jobs: NonZeroUsize,
+    keep_going: bool,
+    stdout_to_stderr: bool,
+}
+
+impl TaskRunner<'_> {
+    /// Run at most this many tasks at once.
+    pub fn with_jobs(mut self, jobs: NonZeroUsize) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    /// Keep starting tasks that don't depend on a failed one, instead of
+    /// stopping at the first failure.
+    pub fn with_keep_going(mut self, keep_going: bool) -> Self {
+        self.keep_going = keep_going;
+        self
+    }
+
+    /// Forward the tasks' stdout to tram's stderr, so tram's stdout only
+    /// holds the run's result.
+    pub fn with_stdout_to_stderr(mut self, stdout_to_stderr: bool) -> Self {
+        self.stdout_to_stderr = stdout_to_stderr;
+        self
+    }
+
+    /// Run `target` after its dependencies, calling `on_start` as each task
+    /// starts. Returns an outcome for every task involved, in dependency order.
+    pub fn run(&self, target: &str, on_start: impl Fn(&Task)) -> AppResult<Vec<TaskOutcome>> {
+        let order = self.graph.execution_order(target)?;
+        let root = self.graph.root();
+        let mut outcomes: HashMap<&str, TaskOutcome> = HashMap::new();
+        let mut running = 0;
+        let mut stopped = false;
+
+        std::thread::scope(|scope| {
+            let (tx, rx) = mpsc::channel();
+
+            loop {
+                // Start every task whose dependencies have all passed
+                if !stopped {
+                    for task in &order {
+                        if running == self.jobs.get() {
+                            break;
+                        }
+
+                        let ready = !outcomes.contains_key(task.name.as_str())
+                            && task.depends_on.iter().all(|dependency| {
+                                outcomes
+                                    .get(dependency.as_str())
+                                    .map(|outcome| outcome.status)
+                                    == Some(TaskStatus::Passed)
+                            });
+                        if !ready {
+                            continue;
+                        }
+
+                        on_start(task);
+
+                        // Placeholder until the task finishes, so it isn't started twice
+                        outcomes.insert(&task.name, TaskOutcome::skipped(&task.name));
+                        running += 1;
+
+                        let tx = tx.clone();
+                        let task: &Task = task;
+                        let stdout_to_stderr = self.stdout_to_stderr;
+                        scope.spawn(move || {
+                            let _ = tx.send((task, task.run_with(root, stdout_to_stderr)));
+                        });
+                    }
+                }
+
+                if running == 0 {
+                    break;
+                }
+
+                let Ok((task, result)) = rx.recv() else {
+                    break;
+                };
+                running -= 1;
+
+                let outcome = match result {
+                    Ok(result) => TaskOutcome {
+                        name: task.name.clone(),
+                        status: if result.success() {
+                            TaskStatus::Passed
+                        } else {
+                            TaskStatus::Failed
+                        },
+                        exit_code: Some(result.exit_code),
+                        duration: result.duration,
+                    },
+                    Err(e) => {
+                        tracing::warn!("{}", e);
+                        TaskOutcome {
+                            status: TaskStatus::Failed,
+                            ..TaskOutcome::skipped(&task.name)
+                        }
+                    }
+                };
+
+                if outcome.status == TaskStatus::Failed && !self.keep_going {
+                    stopped = true;
+                }
+                outcomes.insert(&task.name, outcome);
+            }
+        });
+
+        Ok(order
+            .iter()
+            .map(|task| {
+                outcomes
+                    .remove(task.name.as_str())
+                    .unwrap_or_else(|| TaskOutcome::skipped(&task.name))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(tasks: Vec<Task>) -> TaskGraph {
+        TaskGraph::new(".", tasks)
+    }
+
+    fn names(order: Vec<&Task>) -> Vec<&str> {
+        order.into_iter().map(|task| task.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_dependencies_run_first_once() {
+        let graph = graph(vec![
+            Task::new("build", "cargo build").with_depends_on(["fmt", "generate"]),
+            Task::new("test", "cargo test").with_depends_on(["build", "fmt"]),
+            Task::new("fmt", "cargo fmt"),
+            Task::new("generate", "./gen.sh").with_depends_on(["fmt"]),
+        ]);
+
+        assert_eq!(
+            names(graph.execution_order("test").unwrap()),
+            ["fmt", "generate", "build", "test"]
+        );
+    }
+
+    #[test]
+    fn test_unknown_tasks_and_cycles() {
+        let graph = graph(vec![
+            Task::new("a", "true").with_depends_on(["b"]),
+            Task::new("b", "true").with_depends_on(["a"]),
+            Task::new("c", "true").with_depends_on(["missing"]),
+        ]);
+
+        let cycle = graph.execution_order("a").unwrap_err().to_string();
+        assert!(cycle.contains("a -> b -> a"), "{}", cycle);
+
+        let missing = graph.execution_order("c").unwrap_err().to_string();
+        assert!(missing.contains("'c' depends on unknown task 'missing'"));
+
+        let unknown = graph.execution_order("nope").unwrap_err().to_string();
+        assert!(unknown.contains("available: a, b, c"));
+    }
+
+    #[test]
+    fn test_unknown_task_suggestion() {
+        let graph = graph(vec![
+            Task::new("build", "cargo build"),
+            Task::new("test", "cargo test").with_depends_on(["biuld"]),
+        ]);
+
+        let typo = graph.execution_order("tset").unwrap_err().to_string();
+        assert!(
+            typo.contains("Unknown task 'tset', did you mean 'test'?"),
+            "{}",
+            typo
+        );
+
+        let dependency = graph.execution_order("test").unwrap_err().to_string();
+        assert!(
+            dependency.contains("did you mean 'build'?"),
+            "{}",
+            dependency
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_process_uses_task_cwd_and_env() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("app")).unwrap();
+
+        let task = Task::new(
+            "check",
+            r#"[ "$MODE" = ci ] && [ "$(basename "$PWD")" = app ]"#,
+        )
+        .with_cwd("app")
+        .with_env("MODE", "ci");
+
+        assert!(task.process(temp_dir.path()).run().unwrap().success());
+        assert!(
+            Task::new("script", "cd app\n[ \"$(basename \"$PWD\")\" = app ]\n")
+                .run(temp_dir.path())
+                .unwrap()
+                .success()
+        );
+        assert!(
+            !Task::new("fail", "exit 4")
+                .process(temp_dir.path())
+                .run()
+                .unwrap()
+                .success()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_independent_tasks_run_in_parallel() {
+        let graph = graph(vec![
+            Task::new("a", "sleep 0.3"),
+            Task::new("b", "sleep 0.3"),
+            Task::new("c", "sleep 0.3"),
+            Task::new("all", "true").with_depends_on(["a", "b", "c"]),
+        ]);
+
+        let started = std::time::Instant::now();
+        let outcomes = graph
+            .runner()
+            .with_jobs(NonZeroUsize::new(3).unwrap())
+            .run("all", |_| {})
+            .unwrap();
+
+        assert!(started.elapsed() < Duration::from_millis(800));
+        assert!(
+            outcomes
+                .iter()
+                .all(|outcome| outcome.status == TaskStatus::Passed)
+        );
+        assert_eq!(outcomes.last().unwrap().name, "all");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fail_fast_and_keep_going() {
+        let graph = graph(vec![
+            Task::new("broken", "exit 2"),
+            Task::new("slow", "sleep 0.2"),
+            Task::new("after", "true").with_depends_on(["slow"]),
+            Task::new("all", "true").with_depends_on(["broken", "after"]),
+        ]);
+        let statuses = |keep_going| -> Vec<(String, TaskStatus)> {
+            graph
+                .runner()
+                .with_jobs(NonZeroUsize::new(2).unwrap())
+                .with_keep_going(keep_going)
+                .run("all", |_| {})
+                .unwrap()
+                .into_iter()
+                .map(|outcome| (outcome.name, outcome.status))
+                .collect()
+        };
+
+        // Fail fast lets the running task finish but starts nothing new
+        assert_eq!(
+            statuses(false),
+            [
+                ("broken".to_string(), TaskStatus::Failed),
+                ("slow".to_string(), TaskStatus::Passed),
+                ("after".to_string(), TaskStatus::Skipped),
+                ("all".to_string(), TaskStatus::Skipped),
+            ]
+        );
+        assert_eq!(
+            statuses(true),
+            [
+                ("broken".to_string(), TaskStatus::Failed),
+                ("slow".to_string(), TaskStatus::Passed),
+                ("after".to_string(), TaskStatus::Passed),
+                ("all".to_string(), TaskStatus::Skipped),
+            ]
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! Finding programs on `PATH`, like `which`.
+//!
+//! Names containing a path separator are checked as given, relative to the
+//! current directory, the way shells do. On Windows, names without an
+//! extension are tried with each extension in `PATHEXT` (`.COM`, `.EXE`,
+//! `.BAT`, `.CMD`, ...).
+
+use std::path::{Path, PathBuf};
+use tram_core::{is_executable, search_path};
+
+/// Extensions tried on Windows when `PATHEXT` isn't set.
+const DEFAULT_PATHEXT: &str = ".COM;.EXE;.BAT;.CMD";
+
+/// The first match for `name` on `PATH`.
+pub fn which(name: &str) -> Option<PathBuf> {
+    which_in(name, search_path()).into_iter().next()
+}
+
+/// Every match for `name` on `PATH`, in order, like `which -a`. Later
+/// matches are shadowed by the first.
+pub fn which_all(name: &str) -> Vec<PathBuf> {
+    which_in(name, search_path())
+}
+
+/// Every match for `name` in `dirs`, in order.
+pub fn which_in(name: &str, dirs: impl IntoIterator<Item = PathBuf>) -> Vec<PathBuf> {
+    if name.is_empty() {
+        return Vec::new();
+    }
+
+    if Path::new(name).components().count() > 1 {
+        return candidates(Path::new(name))
+            .into_iter()
+            .filter(|path| is_executable(path))
+            .take(1)
+            .collect();
+    }
+
+    let mut found: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        // An empty entry means the current directory, but only to shells
+        // that still honor it; like modern `which`, skip it
+        if dir.as_os_str().is_empty() {
+            continue;
+        }
+
+        for path in candidates(&dir.join(name)) {
+            if is_executable(&path) && !found.contains(&path) {
+                found.push(path);
+            }
+        }
+    }
+
+    found
+}
+
+/// The paths to try for a program: with each `PATHEXT` extension on
+/// Windows when it has none, as given otherwise.
+fn candidates(path: &Path) -> Vec<PathBuf> {
+    if !cfg!(windows) || path.extension().is_some() {
+        return vec![path.to_path_buf()];
+    }
+
+    std::env::var("PATHEXT")
+        .unwrap_or_else(|_| DEFAULT_PATHEXT.to_string())
+        .split(';')
+        .filter(|ext| !ext.is_empty())
+        .map(|ext| {
+            let mut file = path.as_os_str().to_owned();
+            file.push(ext.to_lowercase());
+            PathBuf::from(file)
+        })
+        .collect()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    fn executable(dir: &Path, name: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_which_in_finds_every_match_in_order() {
+        let first = TempDir::new().unwrap();
+        let second = TempDir::new().unwrap();
+        let a = executable(first.path(), "tool");
+        let b = executable(second.path(), "tool");
+        std::fs::write(first.path().join("plain"), "").unwrap();
+
+        let dirs = || {
+            vec![
+                first.path().to_path_buf(),
+                PathBuf::new(),
+                second.path().to_path_buf(),
+                first.path().to_path_buf(),
+            ]
+        };
+
+        assert_eq!(which_in("tool", dirs()), [a, b]);
+        assert!(which_in("plain", dirs()).is_empty());
+        assert!(which_in("missing", dirs()).is_empty());
+        assert!(which_in("", dirs()).is_empty());
+    }
+
+    #[test]
+    fn test_paths_are_checked_as_given() {
+        let dir = TempDir::new().unwrap();
+        let tool = executable(dir.path(), "tool");
+
+        let found = which_in(tool.to_str().unwrap(), Vec::new());
+        assert_eq!(found, [tool]);
+        assert!(which_in(dir.path().join("nope").to_str().unwrap(), Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_which_uses_path() {
+        assert!(which("sh").is_some_and(|path| path.is_absolute()));
+        assert_eq!(which_all("sh").first(), which("sh").as_ref());
+    }
+}
@@ -0,0 +1,124 @@
+//! Prompts answered with their defaults, for CI, pipes, and `--yes`.
+
+use crate::prompter::Prompter;
+use crate::prompts::{self, Confirm, Input, MultiSelect, Password, Select};
+use tram_core::{AppResult, TramError};
+
+/// Answers every prompt with its default without asking. A prompt with no
+/// default fails with [`TramError::MissingInput`] naming it, so a script gets
+/// a usage error saying what to pass instead of hanging on a prompt.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultsPrompter;
+
+impl DefaultsPrompter {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+fn missing(prompt: &str) -> TramError {
+    TramError::MissingInput {
+        inputs: vec![prompt.to_string()],
+    }
+}
+
+impl Prompter for DefaultsPrompter {
+    fn input(&self, prompt: &Input) -> AppResult<String> {
+        let answer = prompt.fallback().ok_or_else(|| missing(&prompt.prompt))?;
+
+        prompts::check(&prompt.validators, &answer).map_err(|message| TramError::InvalidInput {
+            input: prompt.prompt.clone(),
+            message,
+        })?;
+
+        Ok(answer)
+    }
+
+    fn confirm(&self, prompt: &Confirm) -> AppResult<bool> {
+        Ok(prompt.default.ok_or_else(|| missing(&prompt.prompt))?)
+    }
+
+    fn select(&self, prompt: &Select) -> AppResult<usize> {
+        Ok(prompt.default.ok_or_else(|| missing(&prompt.prompt))?)
+    }
+
+    fn multiselect(&self, prompt: &MultiSelect) -> AppResult<Vec<usize>> {
+        Ok(prompt.checked())
+    }
+
+    fn password(&self, prompt: &Password) -> AppResult<String> {
+        Err(missing(&prompt.prompt).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate;
+
+    #[test]
+    fn test_answers_with_defaults() {
+        let prompter = DefaultsPrompter::new();
+
+        assert_eq!(
+            prompter
+                .input(&Input::new("Name").with_default("World"))
+                .unwrap(),
+            "World"
+        );
+        assert_eq!(
+            prompter
+                .input(&Input::new("Description").allow_empty(true))
+                .unwrap(),
+            ""
+        );
+        assert!(
+            prompter
+                .confirm(&Confirm::new("Continue?").with_default(true))
+                .unwrap()
+        );
+        assert_eq!(
+            prompter
+                .select(&Select::new("Pick", ["a", "b"]).with_default(1))
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            prompter
+                .multiselect(&MultiSelect::new("Pick", ["a", "b", "c"]).with_checked([2]))
+                .unwrap(),
+            [2]
+        );
+    }
+
+    #[test]
+    fn test_missing_defaults_are_usage_errors() {
+        let prompter = DefaultsPrompter::new();
+
+        let error = prompter.input(&Input::new("Name")).unwrap_err();
+        let error = error.downcast_ref::<TramError>().unwrap();
+        assert!(matches!(error, TramError::MissingInput { inputs } if inputs == &["Name"]));
+        assert_eq!(error.exit_code(), tram_core::ExitCode::Usage);
+
+        assert!(prompter.confirm(&Confirm::new("Continue?")).is_err());
+        assert!(prompter.select(&Select::new("Pick", ["a"])).is_err());
+        assert!(
+            prompter
+                .password(&Password::new("Token").allow_empty(true))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_defaults_are_validated() {
+        let error = DefaultsPrompter::new()
+            .input(
+                &Input::new("Name")
+                    .with_default("")
+                    .with_validator(validate::non_empty()),
+            )
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "Invalid Name: a value is required");
+    }
+}
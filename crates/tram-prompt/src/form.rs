@@ -0,0 +1,321 @@
+//! Multi-step forms built from prompts.
+//!
+//! A [`Form`] is a list of named fields, each with its own prompt, default,
+//! and validators, asked in order through any [`Prompter`]. The answers come
+//! back as [`Answers`], looked up by field name. Fields can depend on
+//! earlier answers with [`Form::when`].
+//!
+//! Types that are filled in by a wizard implement [`PromptForm`]: they
+//! describe the form from their current values, which become the defaults,
+//! and apply the answers back to themselves.
+//!
+//! Without a terminal every field takes its default, and the fields that
+//! have none are reported together in one
+//! [`TramError::MissingInput`](tram_core::TramError::MissingInput), so a
+//! script learns everything it needs to pass at once.
+
+use crate::prompter::Prompter;
+use crate::prompts::{Confirm, Input, MultiSelect, Password, Select};
+use std::fmt;
+use std::sync::Arc;
+use tram_core::{AppResult, TramError};
+
+type Condition = Arc<dyn Fn(&Answers) -> bool + Send + Sync>;
+
+/// The prompt asked for a field.
+#[derive(Debug, Clone)]
+pub enum FieldPrompt {
+    Input(Input),
+    Confirm(Confirm),
+    Select(Select),
+    MultiSelect(MultiSelect),
+    Password(Password),
+}
+
+impl FieldPrompt {
+    fn ask(&self, prompter: &dyn Prompter) -> AppResult<Value> {
+        Ok(match self {
+            FieldPrompt::Input(prompt) => Value::Text(prompter.input(prompt)?),
+            FieldPrompt::Password(prompt) => Value::Text(prompter.password(prompt)?),
+            FieldPrompt::Confirm(prompt) => Value::Bool(prompter.confirm(prompt)?),
+            FieldPrompt::Select(prompt) => Value::Index(prompter.select(prompt)?),
+            FieldPrompt::MultiSelect(prompt) => Value::Indices(prompter.multiselect(prompt)?),
+        })
+    }
+}
+
+/// A named field in a form.
+#[derive(Clone)]
+pub struct Field {
+    pub name: String,
+    pub prompt: FieldPrompt,
+    condition: Option<Condition>,
+}
+
+impl fmt::Debug for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Field")
+            .field("name", &self.name)
+            .field("prompt", &self.prompt)
+            .field("conditional", &self.condition.is_some())
+            .finish()
+    }
+}
+
+/// An answer to one field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    /// From an input or password prompt
+    Text(String),
+    /// From a confirm prompt
+    Bool(bool),
+    /// From a select prompt
+    Index(usize),
+    /// From a multiselect prompt
+    Indices(Vec<usize>),
+}
+
+/// The answers to a form, by field name. Fields skipped by
+/// [`Form::when`] have no answer.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers {
+    values: Vec<(String, Value)>,
+}
+
+impl Answers {
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn text(&self, name: &str) -> Option<&str> {
+        match self.get(name) {
+            Some(Value::Text(text)) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn bool(&self, name: &str) -> Option<bool> {
+        match self.get(name) {
+            Some(Value::Bool(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn index(&self, name: &str) -> Option<usize> {
+        match self.get(name) {
+            Some(Value::Index(index)) => Some(*index),
+            _ => None,
+        }
+    }
+
+    pub fn indices(&self, name: &str) -> Option<&[usize]> {
+        match self.get(name) {
+            Some(Value::Indices(indexes)) => Some(indexes),
+            _ => None,
+        }
+    }
+
+    /// Field names and answers, in the order they were asked.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    fn insert(&mut self, name: &str, value: Value) {
+        self.values.push((name.to_string(), value));
+    }
+}
+
+/// Fields asked one after another.
+#[derive(Debug, Clone, Default)]
+pub struct Form {
+    pub fields: Vec<Field>,
+}
+
+impl Form {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn field(mut self, name: impl Into<String>, prompt: FieldPrompt) -> Self {
+        self.fields.push(Field {
+            name: name.into(),
+            prompt,
+            condition: None,
+        });
+        self
+    }
+
+    pub fn input(self, name: impl Into<String>, prompt: Input) -> Self {
+        self.field(name, FieldPrompt::Input(prompt))
+    }
+
+    pub fn confirm(self, name: impl Into<String>, prompt: Confirm) -> Self {
+        self.field(name, FieldPrompt::Confirm(prompt))
+    }
+
+    pub fn select(self, name: impl Into<String>, prompt: Select) -> Self {
+        self.field(name, FieldPrompt::Select(prompt))
+    }
+
+    pub fn multiselect(self, name: impl Into<String>, prompt: MultiSelect) -> Self {
+        self.field(name, FieldPrompt::MultiSelect(prompt))
+    }
+
+    pub fn password(self, name: impl Into<String>, prompt: Password) -> Self {
+        self.field(name, FieldPrompt::Password(prompt))
+    }
+
+    /// Only ask the last field added when `condition` holds for the answers
+    /// so far.
+    pub fn when(mut self, condition: impl Fn(&Answers) -> bool + Send + Sync + 'static) -> Self {
+        if let Some(field) = self.fields.last_mut() {
+            field.condition = Some(Arc::new(condition));
+        }
+        self
+    }
+
+    /// Ask every field in order.
+    pub fn ask(&self, prompter: &dyn Prompter) -> AppResult<Answers> {
+        let mut answers = Answers::default();
+        let mut missing = Vec::new();
+
+        for field in &self.fields {
+            if let Some(condition) = &field.condition
+                && !condition(&answers)
+            {
+                continue;
+            }
+
+            match field.prompt.ask(prompter) {
+                Ok(value) => answers.insert(&field.name, value),
+                // Keep going, to report every missing answer at once
+                Err(error) => match error.downcast::<TramError>() {
+                    Ok(TramError::MissingInput { inputs }) => missing.extend(inputs),
+                    Ok(error) => return Err(error.into()),
+                    Err(error) => return Err(error),
+                },
+            }
+        }
+
+        if missing.is_empty() {
+            Ok(answers)
+        } else {
+            Err(TramError::MissingInput { inputs: missing }.into())
+        }
+    }
+}
+
+/// A type filled in by a form, such as a command's settings.
+///
+/// ```
+/// use tram_prompt::{Answer, Answers, Confirm, Form, Input, PromptForm, ScriptedPrompter};
+///
+/// struct Greeting {
+///     name: String,
+///     shout: bool,
+/// }
+///
+/// impl PromptForm for Greeting {
+///     fn form(&self) -> Form {
+///         Form::new()
+///             .input("name", Input::new("Name").with_default(&self.name))
+///             .confirm("shout", Confirm::new("Shout?").with_default(self.shout))
+///     }
+///
+///     fn apply(&mut self, answers: &Answers) -> tram_core::AppResult<()> {
+///         self.name = answers.text("name").unwrap_or_default().to_string();
+///         self.shout = answers.bool("shout").unwrap_or(self.shout);
+///         Ok(())
+///     }
+/// }
+///
+/// let mut greeting = Greeting { name: "World".into(), shout: false };
+/// greeting
+///     .prompt(&ScriptedPrompter::new([Answer::from("tram"), Answer::from(true)]))
+///     .unwrap();
+/// assert_eq!(greeting.name, "tram");
+/// assert!(greeting.shout);
+/// ```
+pub trait PromptForm {
+    /// The form to ask, with the current values as defaults.
+    fn form(&self) -> Form;
+
+    /// Update the values from the form's answers.
+    fn apply(&mut self, answers: &Answers) -> AppResult<()>;
+
+    /// Ask the form and apply the answers.
+    fn prompt(&mut self, prompter: &dyn Prompter) -> AppResult<()> {
+        let answers = self.form().ask(prompter)?;
+        self.apply(&answers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Answer, DefaultsPrompter, ScriptedPrompter};
+
+    fn form() -> Form {
+        Form::new()
+            .input("name", Input::new("Name"))
+            .select(
+                "license",
+                Select::new("License", ["MIT", "Apache-2.0"]).with_default(0),
+            )
+            .confirm("git", Confirm::new("Git?").with_default(true))
+            .confirm("hooks", Confirm::new("Hooks?"))
+            .when(|answers| answers.bool("git") == Some(true))
+            .multiselect("extras", MultiSelect::new("Extras", ["CI", "Docs"]))
+    }
+
+    #[test]
+    fn test_answers_by_name() {
+        let prompter = ScriptedPrompter::new([
+            Answer::from("tram"),
+            Answer::Default,
+            Answer::from(true),
+            Answer::from(false),
+            Answer::from(vec![1]),
+        ]);
+
+        let answers = form().ask(&prompter).unwrap();
+        assert_eq!(answers.text("name"), Some("tram"));
+        assert_eq!(answers.index("license"), Some(0));
+        assert_eq!(answers.bool("hooks"), Some(false));
+        assert_eq!(answers.indices("extras"), Some(&[1][..]));
+        assert_eq!(answers.text("git"), None);
+        assert_eq!(
+            answers.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            ["name", "license", "git", "hooks", "extras"]
+        );
+    }
+
+    #[test]
+    fn test_conditional_fields_are_skipped() {
+        let prompter = ScriptedPrompter::new([
+            Answer::from("tram"),
+            Answer::from(1),
+            Answer::from(false),
+            Answer::Default,
+        ]);
+
+        let answers = form().ask(&prompter).unwrap();
+        assert_eq!(answers.get("hooks"), None);
+        assert_eq!(prompter.asked(), ["Name", "License", "Git?", "Extras"]);
+    }
+
+    #[test]
+    fn test_every_missing_default_is_reported() {
+        let error = form().ask(&DefaultsPrompter::new()).unwrap_err();
+
+        match error.downcast_ref::<TramError>() {
+            Some(TramError::MissingInput { inputs }) => assert_eq!(inputs, &["Name", "Hooks?"]),
+            other => panic!("expected missing input, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! Interactive prompts for Tram CLI applications.
+//!
+//! Commands ask questions through the [`Prompter`] trait instead of calling
+//! dialoguer directly:
+//!
+//! - [`TerminalPrompter`] asks on the terminal, styled with the `tram-ui`
+//!   theme
+//! - [`DefaultsPrompter`] answers with each prompt's default, for CI, pipes,
+//!   and `--yes`, failing with a usage error when a prompt has none
+//! - [`ScriptedPrompter`] answers from a list, for tests
+//!
+//! [`prompter`] picks between the first two. Prompts are described with
+//! [`Input`], [`Confirm`], [`Select`], [`MultiSelect`], and [`Password`],
+//! and answers can be checked with the helpers in [`validate`].
+
+pub mod defaults;
+pub mod form;
+pub mod prompter;
+pub mod prompts;
+pub mod scripted;
+pub mod terminal;
+pub mod theme;
+pub mod validate;
+
+pub use defaults::*;
+pub use form::*;
+pub use prompter::*;
+pub use prompts::*;
+pub use scripted::*;
+pub use terminal::*;
+pub use theme::*;
+
+use std::sync::Arc;
+
+/// The prompter for this run: the terminal when someone can answer (see
+/// `tram_core::can_prompt`), the prompts' defaults otherwise.
+pub fn prompter(interactive: bool) -> Arc<dyn Prompter> {
+    if interactive {
+        Arc::new(TerminalPrompter::new())
+    } else {
+        Arc::new(DefaultsPrompter::new())
+    }
+}
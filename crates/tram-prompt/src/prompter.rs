@@ -0,0 +1,34 @@
+//! The interface every prompt backend implements.
+
+use crate::prompts::{Confirm, Input, MultiSelect, Password, Select};
+use std::fmt::Debug;
+use tram_core::AppResult;
+
+/// Something that can answer prompts: a person at a terminal
+/// ([`TerminalPrompter`](crate::TerminalPrompter)), the prompts' own defaults
+/// ([`DefaultsPrompter`](crate::DefaultsPrompter)), or a script
+/// ([`ScriptedPrompter`](crate::ScriptedPrompter)).
+///
+/// Commands take a `&dyn Prompter` rather than calling dialoguer directly, so
+/// they behave the same in CI as in a terminal and can be tested without one.
+pub trait Prompter: Debug + Send + Sync {
+    /// Ask for a line of text.
+    fn input(&self, prompt: &Input) -> AppResult<String>;
+
+    /// Ask a yes or no question.
+    fn confirm(&self, prompt: &Confirm) -> AppResult<bool>;
+
+    /// Ask for one item, returning its index.
+    fn select(&self, prompt: &Select) -> AppResult<usize>;
+
+    /// Ask for any number of items, returning their indexes in order.
+    fn multiselect(&self, prompt: &MultiSelect) -> AppResult<Vec<usize>>;
+
+    /// Ask for a hidden line of text.
+    fn password(&self, prompt: &Password) -> AppResult<String>;
+
+    /// Whether a person is answering, e.g. to decide whether to ask at all.
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
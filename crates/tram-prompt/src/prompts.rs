@@ -0,0 +1,263 @@
+//! The questions a [`Prompter`](crate::Prompter) can ask.
+//!
+//! Each prompt is a small builder holding the question, its default, and any
+//! validation, so the same prompt can be answered on a terminal, from
+//! defaults, or from a script.
+
+use std::fmt;
+use std::sync::Arc;
+
+/// Checks an answer, returning a message saying what's wrong with it.
+pub type Validator = Arc<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+/// Run every validator, stopping at the first complaint.
+pub(crate) fn check(validators: &[Validator], answer: &str) -> Result<(), String> {
+    validators.iter().try_for_each(|validate| validate(answer))
+}
+
+/// A line of text.
+#[derive(Clone, Default)]
+pub struct Input {
+    pub prompt: String,
+    /// Returned when the answer is left empty
+    pub default: Option<String>,
+    /// Text already typed in, which can be edited or cleared
+    pub initial_text: Option<String>,
+    /// Accept an empty answer when there's no default
+    pub allow_empty: bool,
+    pub validators: Vec<Validator>,
+}
+
+impl Input {
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_default(mut self, default: impl Into<String>) -> Self {
+        self.default = Some(default.into());
+        self
+    }
+
+    pub fn with_initial_text(mut self, text: impl Into<String>) -> Self {
+        self.initial_text = Some(text.into());
+        self
+    }
+
+    pub fn allow_empty(mut self, allow_empty: bool) -> Self {
+        self.allow_empty = allow_empty;
+        self
+    }
+
+    /// Reject answers `validate` complains about. See [`crate::validate`]
+    /// for common checks.
+    pub fn with_validator(
+        mut self,
+        validate: impl Fn(&str) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        self.validators.push(Arc::new(validate));
+        self
+    }
+
+    /// The answer to use without a terminal, if there is one.
+    pub(crate) fn fallback(&self) -> Option<String> {
+        self.default
+            .clone()
+            .or_else(|| self.initial_text.clone())
+            .or_else(|| self.allow_empty.then(String::new))
+    }
+}
+
+/// A yes or no question.
+#[derive(Debug, Clone, Default)]
+pub struct Confirm {
+    pub prompt: String,
+    pub default: Option<bool>,
+}
+
+impl Confirm {
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            default: None,
+        }
+    }
+
+    pub fn with_default(mut self, default: bool) -> Self {
+        self.default = Some(default);
+        self
+    }
+}
+
+/// One choice from a list, answered with its index.
+#[derive(Debug, Clone, Default)]
+pub struct Select {
+    pub prompt: String,
+    pub items: Vec<String>,
+    /// Index of the item selected at first
+    pub default: Option<usize>,
+}
+
+impl Select {
+    pub fn new(
+        prompt: impl Into<String>,
+        items: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            prompt: prompt.into(),
+            items: items.into_iter().map(Into::into).collect(),
+            default: None,
+        }
+    }
+
+    /// Select the item at `index` at first. Out-of-range indexes are ignored.
+    pub fn with_default(mut self, index: usize) -> Self {
+        self.default = (index < self.items.len()).then_some(index);
+        self
+    }
+}
+
+/// Any number of choices from a list, answered with their indexes.
+#[derive(Debug, Clone, Default)]
+pub struct MultiSelect {
+    pub prompt: String,
+    pub items: Vec<String>,
+    /// Whether each item is checked at first
+    pub defaults: Vec<bool>,
+}
+
+impl MultiSelect {
+    pub fn new(
+        prompt: impl Into<String>,
+        items: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        let items: Vec<String> = items.into_iter().map(Into::into).collect();
+
+        Self {
+            prompt: prompt.into(),
+            defaults: vec![false; items.len()],
+            items,
+        }
+    }
+
+    /// Check these items at first.
+    pub fn with_checked(mut self, indexes: impl IntoIterator<Item = usize>) -> Self {
+        for index in indexes {
+            if let Some(checked) = self.defaults.get_mut(index) {
+                *checked = true;
+            }
+        }
+        self
+    }
+
+    /// Indexes of the items checked at first.
+    pub(crate) fn checked(&self) -> Vec<usize> {
+        self.defaults
+            .iter()
+            .enumerate()
+            .filter(|(_, checked)| **checked)
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+/// A hidden line of text. Passwords never have defaults.
+#[derive(Clone, Default)]
+pub struct Password {
+    pub prompt: String,
+    /// Ask a second time with this prompt, and insist both answers match
+    pub confirmation: Option<String>,
+    pub allow_empty: bool,
+    pub validators: Vec<Validator>,
+}
+
+impl Password {
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_confirmation(mut self, prompt: impl Into<String>) -> Self {
+        self.confirmation = Some(prompt.into());
+        self
+    }
+
+    pub fn allow_empty(mut self, allow_empty: bool) -> Self {
+        self.allow_empty = allow_empty;
+        self
+    }
+
+    pub fn with_validator(
+        mut self,
+        validate: impl Fn(&str) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        self.validators.push(Arc::new(validate));
+        self
+    }
+}
+
+impl fmt::Debug for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Input")
+            .field("prompt", &self.prompt)
+            .field("default", &self.default)
+            .field("initial_text", &self.initial_text)
+            .field("allow_empty", &self.allow_empty)
+            .field("validators", &self.validators.len())
+            .finish()
+    }
+}
+
+impl fmt::Debug for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Password")
+            .field("prompt", &self.prompt)
+            .field("confirmation", &self.confirmation)
+            .field("allow_empty", &self.allow_empty)
+            .field("validators", &self.validators.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_fallback() {
+        assert_eq!(Input::new("Name").fallback(), None);
+        assert_eq!(
+            Input::new("Name").allow_empty(true).fallback(),
+            Some(String::new())
+        );
+        assert_eq!(
+            Input::new("Name")
+                .with_initial_text("draft")
+                .with_default("World")
+                .fallback(),
+            Some("World".to_string())
+        );
+    }
+
+    #[test]
+    fn test_select_defaults_stay_in_range() {
+        assert_eq!(
+            Select::new("Pick", ["a", "b"]).with_default(1).default,
+            Some(1)
+        );
+        assert_eq!(
+            Select::new("Pick", ["a", "b"]).with_default(2).default,
+            None
+        );
+        assert_eq!(
+            MultiSelect::new("Pick", ["a", "b", "c"])
+                .with_checked([0, 2, 5])
+                .checked(),
+            [0, 2]
+        );
+    }
+}
@@ -0,0 +1,253 @@
+//! Prompts answered from a script, for tests.
+
+use crate::defaults::DefaultsPrompter;
+use crate::prompter::Prompter;
+use crate::prompts::{self, Confirm, Input, MultiSelect, Password, Select};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tram_core::{AppResult, TramError};
+
+/// A scripted answer to the next prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    /// For [`Input`] and [`Password`]
+    Text(String),
+    /// For [`Confirm`]
+    Bool(bool),
+    /// For [`Select`]
+    Index(usize),
+    /// For [`MultiSelect`]
+    Indices(Vec<usize>),
+    /// Accept whatever the prompt would answer without a terminal
+    Default,
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Self {
+        Answer::Text(text.to_string())
+    }
+}
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Answer::Text(text)
+    }
+}
+
+impl From<bool> for Answer {
+    fn from(value: bool) -> Self {
+        Answer::Bool(value)
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(index: usize) -> Self {
+        Answer::Index(index)
+    }
+}
+
+impl From<Vec<usize>> for Answer {
+    fn from(indexes: Vec<usize>) -> Self {
+        Answer::Indices(indexes)
+    }
+}
+
+/// Answers prompts from a queue, in order, and records what was asked.
+///
+/// Answers go through the prompt's validators, so a test can check that a
+/// bad answer is rejected with [`TramError::InvalidInput`].
+///
+/// # Panics
+///
+/// Every method panics when the queue is empty or the next answer is the
+/// wrong kind for the prompt, since either means the test script doesn't
+/// match the prompts the code asks.
+#[derive(Debug, Default)]
+pub struct ScriptedPrompter {
+    answers: Mutex<VecDeque<Answer>>,
+    asked: Mutex<Vec<String>>,
+}
+
+impl ScriptedPrompter {
+    pub fn new(answers: impl IntoIterator<Item = impl Into<Answer>>) -> Self {
+        Self {
+            answers: Mutex::new(answers.into_iter().map(Into::into).collect()),
+            asked: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Queue another answer.
+    pub fn push(&self, answer: impl Into<Answer>) {
+        self.answers.lock().unwrap().push_back(answer.into());
+    }
+
+    /// The prompts asked so far, in order.
+    pub fn asked(&self) -> Vec<String> {
+        self.asked.lock().unwrap().clone()
+    }
+
+    /// Answers not yet used.
+    pub fn remaining(&self) -> usize {
+        self.answers.lock().unwrap().len()
+    }
+
+    fn next(&self, prompt: &str) -> Answer {
+        self.asked.lock().unwrap().push(prompt.to_string());
+        self.answers
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| panic!("no scripted answer for prompt {:?}", prompt))
+    }
+}
+
+fn mismatch(prompt: &str, answer: &Answer) -> ! {
+    panic!(
+        "scripted answer {:?} doesn't fit prompt {:?}",
+        answer, prompt
+    )
+}
+
+fn validated(prompt: &str, validators: &[prompts::Validator], answer: String) -> AppResult<String> {
+    prompts::check(validators, &answer).map_err(|message| TramError::InvalidInput {
+        input: prompt.to_string(),
+        message,
+    })?;
+
+    Ok(answer)
+}
+
+impl Prompter for ScriptedPrompter {
+    fn input(&self, prompt: &Input) -> AppResult<String> {
+        match self.next(&prompt.prompt) {
+            Answer::Text(text) => validated(&prompt.prompt, &prompt.validators, text),
+            Answer::Default => DefaultsPrompter.input(prompt),
+            answer => mismatch(&prompt.prompt, &answer),
+        }
+    }
+
+    fn confirm(&self, prompt: &Confirm) -> AppResult<bool> {
+        match self.next(&prompt.prompt) {
+            Answer::Bool(value) => Ok(value),
+            Answer::Default => DefaultsPrompter.confirm(prompt),
+            answer => mismatch(&prompt.prompt, &answer),
+        }
+    }
+
+    fn select(&self, prompt: &Select) -> AppResult<usize> {
+        match self.next(&prompt.prompt) {
+            Answer::Index(index) if index < prompt.items.len() => Ok(index),
+            Answer::Default => DefaultsPrompter.select(prompt),
+            answer => mismatch(&prompt.prompt, &answer),
+        }
+    }
+
+    fn multiselect(&self, prompt: &MultiSelect) -> AppResult<Vec<usize>> {
+        match self.next(&prompt.prompt) {
+            Answer::Indices(indexes) if indexes.iter().all(|i| *i < prompt.items.len()) => {
+                Ok(indexes)
+            }
+            Answer::Default => DefaultsPrompter.multiselect(prompt),
+            answer => mismatch(&prompt.prompt, &answer),
+        }
+    }
+
+    fn password(&self, prompt: &Password) -> AppResult<String> {
+        match self.next(&prompt.prompt) {
+            Answer::Text(text) if text.is_empty() && !prompt.allow_empty => {
+                Err(TramError::MissingInput {
+                    inputs: vec![prompt.prompt.clone()],
+                }
+                .into())
+            }
+            Answer::Text(text) => validated(&prompt.prompt, &prompt.validators, text),
+            Answer::Default => DefaultsPrompter.password(prompt),
+            answer => mismatch(&prompt.prompt, &answer),
+        }
+    }
+
+    fn is_interactive(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate;
+
+    #[test]
+    fn test_answers_in_order() {
+        let prompter = ScriptedPrompter::new([
+            Answer::from("tram"),
+            Answer::from(false),
+            Answer::from(2),
+            Answer::from(vec![0, 1]),
+            Answer::Default,
+        ]);
+
+        assert_eq!(prompter.input(&Input::new("Name")).unwrap(), "tram");
+        assert!(
+            !prompter
+                .confirm(&Confirm::new("Git?").with_default(true))
+                .unwrap()
+        );
+        assert_eq!(
+            prompter
+                .select(&Select::new("Type", ["a", "b", "c"]))
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            prompter
+                .multiselect(&MultiSelect::new("Features", ["x", "y"]))
+                .unwrap(),
+            [0, 1]
+        );
+        assert!(
+            prompter
+                .confirm(&Confirm::new("CI?").with_default(true))
+                .unwrap()
+        );
+
+        assert_eq!(
+            prompter.asked(),
+            ["Name", "Git?", "Type", "Features", "CI?"]
+        );
+        assert_eq!(prompter.remaining(), 0);
+    }
+
+    #[test]
+    fn test_answers_are_validated() {
+        let prompter = ScriptedPrompter::new(["2fast", "s3cret"]);
+
+        let error = prompter
+            .input(&Input::new("Task").with_validator(validate::identifier()))
+            .unwrap_err();
+        assert!(error.to_string().starts_with("Invalid Task: "));
+
+        let error = prompter
+            .password(&Password::new("Token").with_validator(validate::min_length(8)))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid Token: must be at least 8 characters"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't fit prompt \"Name\"")]
+    fn test_wrong_kind_of_answer_panics() {
+        ScriptedPrompter::new([true])
+            .input(&Input::new("Name"))
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "no scripted answer for prompt \"Name\"")]
+    fn test_running_out_of_answers_panics() {
+        ScriptedPrompter::new(Vec::<Answer>::new())
+            .input(&Input::new("Name"))
+            .unwrap();
+    }
+}
@@ -0,0 +1,113 @@
+//! Prompts answered by a person at the terminal, drawn by dialoguer on
+//! stderr. Esc at a confirmation or selection cancels the command.
+
+use crate::prompter::Prompter;
+use crate::prompts::{self, Confirm, Input, MultiSelect, Password, Select};
+use crate::theme::dialoguer_theme;
+use tram_core::{AppResult, TramError, t};
+
+/// Asks on the terminal, re-asking until the answer passes validation.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TerminalPrompter;
+
+impl TerminalPrompter {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Prompter for TerminalPrompter {
+    fn input(&self, prompt: &Input) -> AppResult<String> {
+        let theme = dialoguer_theme();
+        let mut input = dialoguer::Input::<String>::with_theme(theme.as_ref())
+            .with_prompt(&prompt.prompt)
+            .allow_empty(prompt.allow_empty);
+
+        if let Some(default) = &prompt.default {
+            input = input.default(default.clone());
+        }
+        if let Some(text) = &prompt.initial_text {
+            input = input.with_initial_text(text);
+        }
+        if !prompt.validators.is_empty() {
+            let validators = prompt.validators.clone();
+            input = input.validate_with(move |answer: &String| prompts::check(&validators, answer));
+        }
+
+        Ok(input.interact_text().map_err(prompt_error)?)
+    }
+
+    fn confirm(&self, prompt: &Confirm) -> AppResult<bool> {
+        let theme = dialoguer_theme();
+        let mut confirm =
+            dialoguer::Confirm::with_theme(theme.as_ref()).with_prompt(&prompt.prompt);
+
+        if let Some(default) = prompt.default {
+            confirm = confirm.default(default);
+        }
+
+        Ok(confirm
+            .interact_opt()
+            .map_err(prompt_error)?
+            .ok_or(TramError::Cancelled)?)
+    }
+
+    fn select(&self, prompt: &Select) -> AppResult<usize> {
+        let theme = dialoguer_theme();
+
+        Ok(dialoguer::Select::with_theme(theme.as_ref())
+            .with_prompt(&prompt.prompt)
+            .items(&prompt.items)
+            .default(prompt.default.unwrap_or(0))
+            .interact_opt()
+            .map_err(prompt_error)?
+            .ok_or(TramError::Cancelled)?)
+    }
+
+    fn multiselect(&self, prompt: &MultiSelect) -> AppResult<Vec<usize>> {
+        let theme = dialoguer_theme();
+
+        Ok(dialoguer::MultiSelect::with_theme(theme.as_ref())
+            .with_prompt(&prompt.prompt)
+            .items(&prompt.items)
+            .defaults(&prompt.defaults)
+            .interact_opt()
+            .map_err(prompt_error)?
+            .ok_or(TramError::Cancelled)?)
+    }
+
+    fn password(&self, prompt: &Password) -> AppResult<String> {
+        let theme = dialoguer_theme();
+        let mut password = dialoguer::Password::with_theme(theme.as_ref())
+            .with_prompt(&prompt.prompt)
+            .allow_empty_password(prompt.allow_empty);
+
+        if let Some(confirmation) = &prompt.confirmation {
+            password = password.with_confirmation(confirmation, t!("prompt-password-mismatch"));
+        }
+        if !prompt.validators.is_empty() {
+            let validators = prompt.validators.clone();
+            password =
+                password.validate_with(move |answer: &String| prompts::check(&validators, answer));
+        }
+
+        Ok(password.interact().map_err(prompt_error)?)
+    }
+
+    fn is_interactive(&self) -> bool {
+        true
+    }
+}
+
+/// Map a prompt failure to an error, treating Ctrl+C as an interruption.
+pub fn prompt_error(error: dialoguer::Error) -> TramError {
+    match error {
+        // Ctrl+C at a prompt
+        dialoguer::Error::IO(e) if e.kind() == std::io::ErrorKind::Interrupted => {
+            TramError::Interrupted
+        }
+        error => TramError::Prompt {
+            message: error.to_string(),
+        },
+    }
+}
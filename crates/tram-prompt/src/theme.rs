@@ -0,0 +1,64 @@
+//! dialoguer's look, taken from the shared `tram-ui` theme.
+
+use dialoguer::theme::{ColorfulTheme, SimpleTheme, Theme};
+use tram_core::{Stream, color_enabled};
+use tram_ui::{Color, Style};
+
+/// The prompt theme: the configured theme's styles when stderr supports
+/// color, plain text otherwise.
+pub fn dialoguer_theme() -> Box<dyn Theme> {
+    if !color_enabled(Stream::Stderr) {
+        return Box::new(SimpleTheme);
+    }
+
+    let ui = tram_ui::theme();
+    let styled = |style: Style, text: &str| console_style(style).apply_to(text.to_string());
+
+    Box::new(ColorfulTheme {
+        defaults_style: console_style(ui.info),
+        prompt_style: console_style(ui.heading),
+        prompt_prefix: styled(ui.warning, "?"),
+        prompt_suffix: styled(ui.muted, "›"),
+        success_prefix: styled(ui.success, "✔"),
+        success_suffix: styled(ui.muted, "·"),
+        error_prefix: styled(ui.error, "✘"),
+        error_style: console_style(ui.error),
+        hint_style: console_style(ui.muted),
+        values_style: console_style(ui.success),
+        active_item_style: console_style(ui.info),
+        active_item_prefix: styled(ui.success, "❯"),
+        checked_item_prefix: styled(ui.success, "✔"),
+        unchecked_item_prefix: styled(ui.muted, "⬚"),
+        picked_item_prefix: styled(ui.success, "❯"),
+        ..ColorfulTheme::default()
+    })
+}
+
+/// The same style for dialoguer's terminal library.
+fn console_style(style: Style) -> console::Style {
+    let console = console::Style::new().for_stderr();
+    let mut console = match style.color() {
+        Some(Color::Black) => console.black(),
+        Some(Color::Red) => console.red(),
+        Some(Color::Green) => console.green(),
+        Some(Color::Yellow) => console.yellow(),
+        Some(Color::Blue) => console.blue(),
+        Some(Color::Magenta) => console.magenta(),
+        Some(Color::Cyan) => console.cyan(),
+        Some(Color::White) => console.white(),
+        Some(Color::Gray) => console.black().bright(),
+        None => console,
+    };
+
+    if style.is_bold() {
+        console = console.bold();
+    }
+    if style.is_italic() {
+        console = console.italic();
+    }
+    if style.is_underline() {
+        console = console.underlined();
+    }
+
+    console
+}
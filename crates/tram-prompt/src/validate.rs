@@ -0,0 +1,135 @@
+//! Common answer checks, for [`Input::with_validator`](crate::Input::with_validator)
+//! and [`Password::with_validator`](crate::Password::with_validator).
+//!
+//! Each helper returns a validator; messages are localized and lowercase, so
+//! they read well after the prompt name, e.g. "Invalid Name: a value is
+//! required".
+
+use tram_core::t;
+
+/// Reject blank answers.
+pub fn non_empty() -> impl Fn(&str) -> Result<(), String> + Send + Sync + 'static {
+    |answer: &str| {
+        if answer.trim().is_empty() {
+            Err(t!("prompt-required"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Reject answers shorter than `min` characters.
+pub fn min_length(min: usize) -> impl Fn(&str) -> Result<(), String> + Send + Sync + 'static {
+    move |answer: &str| {
+        if answer.chars().count() < min {
+            Err(t!("prompt-too-short", min = min))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Reject answers longer than `max` characters.
+pub fn max_length(max: usize) -> impl Fn(&str) -> Result<(), String> + Send + Sync + 'static {
+    move |answer: &str| {
+        if answer.chars().count() > max {
+            Err(t!("prompt-too-long", max = max))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Accept only whole numbers from `min` to `max`, inclusive.
+pub fn number_in_range(
+    min: i64,
+    max: i64,
+) -> impl Fn(&str) -> Result<(), String> + Send + Sync + 'static {
+    move |answer: &str| match answer.trim().parse::<i64>() {
+        Ok(number) if (min..=max).contains(&number) => Ok(()),
+        Ok(_) => Err(t!("prompt-out-of-range", min = min, max = max)),
+        Err(_) => Err(t!("prompt-not-a-number")),
+    }
+}
+
+/// Accept only one of `values`, ignoring case.
+pub fn one_of<S: AsRef<str>>(
+    values: impl IntoIterator<Item = S>,
+) -> impl Fn(&str) -> Result<(), String> + Send + Sync + 'static {
+    let values: Vec<String> = values
+        .into_iter()
+        .map(|value| value.as_ref().to_string())
+        .collect();
+
+    move |answer: &str| {
+        if values
+            .iter()
+            .any(|value| value.eq_ignore_ascii_case(answer.trim()))
+        {
+            Ok(())
+        } else {
+            Err(t!("prompt-not-one-of", values = values.join(", ")))
+        }
+    }
+}
+
+/// Accept names such as `my-task` or `api_v2`: letters, digits, `-`, and
+/// `_`, starting with a letter.
+pub fn identifier() -> impl Fn(&str) -> Result<(), String> + Send + Sync + 'static {
+    |answer: &str| {
+        let mut chars = answer.chars();
+        let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+        if valid {
+            Ok(())
+        } else {
+            Err(t!("prompt-invalid-identifier"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lengths() {
+        assert!(non_empty()("tram").is_ok());
+        assert_eq!(non_empty()("  "), Err("a value is required".to_string()));
+        assert!(min_length(3)("abc").is_ok());
+        assert_eq!(
+            min_length(3)("ab"),
+            Err("must be at least 3 characters".to_string())
+        );
+        assert!(max_length(2)("éé").is_ok());
+        assert_eq!(
+            max_length(2)("abc"),
+            Err("must be at most 2 characters".to_string())
+        );
+    }
+
+    #[test]
+    fn test_number_in_range() {
+        let validate = number_in_range(1, 10);
+
+        assert!(validate(" 10 ").is_ok());
+        assert_eq!(validate("11"), Err("must be between 1 and 10".to_string()));
+        assert_eq!(validate("ten"), Err("must be a whole number".to_string()));
+    }
+
+    #[test]
+    fn test_one_of_and_identifier() {
+        let validate = one_of(["json", "yaml"]);
+        assert!(validate("JSON").is_ok());
+        assert_eq!(
+            validate("toml"),
+            Err("must be one of: json, yaml".to_string())
+        );
+
+        assert!(identifier()("api_v2-beta").is_ok());
+        assert!(identifier()("2fast").is_err());
+        assert!(identifier()("").is_err());
+        assert!(identifier()("has space").is_err());
+    }
+}
@@ -0,0 +1,57 @@
+//! Pushing recorded metrics to a collector.
+
+use crate::metrics::Snapshot;
+use crate::otlp::{DEFAULT_OTLP_ENDPOINT, OtlpExporter};
+use crate::prometheus::{DEFAULT_PUSHGATEWAY_ENDPOINT, PushgatewayExporter};
+use tram_config::{MetricsExporter, TelemetryConfig};
+use tram_core::{AppResult, SecretResolver};
+use tram_http::HttpClient;
+
+/// Sends a [`Snapshot`] somewhere.
+pub trait Exporter: Send + Sync {
+    /// Short name such as `otlp`, for logs.
+    fn name(&self) -> &str;
+
+    /// Send everything in the snapshot.
+    fn export(&self, snapshot: &Snapshot) -> AppResult<()>;
+}
+
+/// The exporter the `[telemetry]` config section asks for, or `None` when
+/// export is off. Header values may be `secret://` references, resolved
+/// with `secrets`.
+pub fn from_config(
+    config: &TelemetryConfig,
+    client: HttpClient,
+    secrets: &SecretResolver,
+) -> AppResult<Option<Box<dyn Exporter>>> {
+    let headers = config
+        .headers
+        .iter()
+        .map(|(name, value)| Ok((name.clone(), secrets.resolve(value)?)))
+        .collect::<AppResult<Vec<_>>>()?;
+
+    let exporter: Box<dyn Exporter> = match config.exporter {
+        MetricsExporter::None => return Ok(None),
+        MetricsExporter::Otlp => Box::new(
+            OtlpExporter::new(
+                config.endpoint.as_deref().unwrap_or(DEFAULT_OTLP_ENDPOINT),
+                client,
+            )
+            .with_service_name(&config.service_name)
+            .with_headers(headers),
+        ),
+        MetricsExporter::Prometheus => Box::new(
+            PushgatewayExporter::new(
+                config
+                    .endpoint
+                    .as_deref()
+                    .unwrap_or(DEFAULT_PUSHGATEWAY_ENDPOINT),
+                &config.service_name,
+                client,
+            )
+            .with_headers(headers),
+        ),
+    };
+
+    Ok(Some(exporter))
+}
@@ -0,0 +1,27 @@
+//! Metrics for Tram CLI applications.
+//!
+//! [`metrics()`] is a process-wide registry of counters and histograms,
+//! labelled per series. Tram records command durations ([`COMMAND_DURATION`]),
+//! cache hits and misses ([`CACHE_HITS`], [`CACHE_MISSES`]), and watch
+//! triggers ([`WATCH_BATCHES`], [`WATCH_TRIGGERS`]); applications can define
+//! their own [`Metric`]s the same way.
+//!
+//! When a command finishes, a [`Snapshot`] of everything recorded is pushed
+//! by the [`Exporter`] the config's `[telemetry]` section selects:
+//!
+//! - [`OtlpExporter`] sends OTLP/HTTP JSON to an OpenTelemetry collector
+//! - [`PushgatewayExporter`] sends the text format to a Prometheus
+//!   Pushgateway
+//!
+//! This is separate from the opt-in usage telemetry of `tram telemetry`:
+//! metrics only go to a collector you configure.
+
+pub mod exporter;
+pub mod metrics;
+pub mod otlp;
+pub mod prometheus;
+
+pub use exporter::*;
+pub use metrics::*;
+pub use otlp::*;
+pub use prometheus::*;
@@ -0,0 +1,309 @@
+//! An in-process registry of counters and histograms.
+
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Histogram bounds for durations in seconds, from 5 ms to 5 minutes.
+pub const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0,
+];
+
+/// How long each command took, labelled with `command` and `outcome`
+/// (`success` or `failure`).
+pub const COMMAND_DURATION: Metric = Metric {
+    name: "tram.command.duration",
+    unit: "s",
+    description: "How long commands took to run",
+    kind: MetricKind::Histogram {
+        bounds: DURATION_BUCKETS,
+    },
+};
+
+/// Cache lookups that found an entry. With [`CACHE_MISSES`], gives the hit
+/// rate.
+pub const CACHE_HITS: Metric = Metric {
+    name: "tram.cache.hits",
+    unit: "{lookup}",
+    description: "Cache lookups that found an entry",
+    kind: MetricKind::Counter,
+};
+
+/// Cache lookups that found nothing, or only an expired entry.
+pub const CACHE_MISSES: Metric = Metric {
+    name: "tram.cache.misses",
+    unit: "{lookup}",
+    description: "Cache lookups that found no entry",
+    kind: MetricKind::Counter,
+};
+
+/// Batches of file changes handled by `tram watch`.
+pub const WATCH_BATCHES: Metric = Metric {
+    name: "tram.watch.batches",
+    unit: "{batch}",
+    description: "Batches of file changes handled by watch mode",
+    kind: MetricKind::Counter,
+};
+
+/// Watch tasks run because matching files changed, labelled with `task`.
+pub const WATCH_TRIGGERS: Metric = Metric {
+    name: "tram.watch.triggers",
+    unit: "{run}",
+    description: "Watch tasks run because matching files changed",
+    kind: MetricKind::Counter,
+};
+
+/// Whether a metric counts events or records a distribution of values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MetricKind {
+    Counter,
+    /// Values are counted in buckets with these upper bounds, plus one
+    /// bucket for everything above the last
+    Histogram {
+        bounds: &'static [f64],
+    },
+}
+
+/// A metric's name and description, shared by every labelled series.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Metric {
+    /// Dotted name, e.g. `tram.command.duration`
+    pub name: &'static str,
+    /// UCUM unit, e.g. `s` or `{lookup}`
+    pub unit: &'static str,
+    pub description: &'static str,
+    pub kind: MetricKind,
+}
+
+/// Values recorded in a histogram.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    pub bounds: &'static [f64],
+    /// Values in each bucket, one more than there are bounds
+    pub bucket_counts: Vec<u64>,
+    pub count: u64,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            bucket_counts: vec![0; bounds.len() + 1],
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        let bucket = self
+            .bounds
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(self.bounds.len());
+
+        self.bucket_counts[bucket] += 1;
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+}
+
+/// The current value of a series.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Counter(u64),
+    Histogram(Histogram),
+}
+
+/// One metric with one set of labels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    pub metric: Metric,
+    /// Label names and values, sorted by name
+    pub labels: Vec<(String, String)>,
+    pub value: Value,
+}
+
+/// Every series recorded since the registry was created, for export.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    /// When recording started, in nanoseconds since the Unix epoch
+    pub start_time_nanos: u64,
+    /// When the snapshot was taken, in nanoseconds since the Unix epoch
+    pub time_nanos: u64,
+    /// Series ordered by metric name, then labels
+    pub series: Vec<Series>,
+}
+
+/// Series by metric name and labels.
+type SeriesMap = BTreeMap<(&'static str, Vec<(String, String)>), Series>;
+
+/// Counters and histograms recorded by this process.
+#[derive(Debug)]
+pub struct Metrics {
+    start_time_nanos: u64,
+    series: Mutex<SeriesMap>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            start_time_nanos: unix_nanos(),
+            series: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Add `by` to a counter.
+    pub fn increment(&self, metric: &Metric, labels: &[(&str, &str)], by: u64) {
+        debug_assert_eq!(metric.kind, MetricKind::Counter, "{}", metric.name);
+
+        self.update(metric, labels, |value| {
+            if let Value::Counter(count) = value {
+                *count += by;
+            }
+        });
+    }
+
+    /// Record a value in a histogram.
+    pub fn observe(&self, metric: &Metric, labels: &[(&str, &str)], value: f64) {
+        debug_assert!(
+            matches!(metric.kind, MetricKind::Histogram { .. }),
+            "{}",
+            metric.name
+        );
+
+        self.update(metric, labels, |series| {
+            if let Value::Histogram(histogram) = series {
+                histogram.observe(value);
+            }
+        });
+    }
+
+    /// Whether nothing has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.series.lock().map(|map| map.is_empty()).unwrap_or(true)
+    }
+
+    /// Everything recorded so far.
+    pub fn snapshot(&self) -> Snapshot {
+        let series = self
+            .series
+            .lock()
+            .map(|map| map.values().cloned().collect())
+            .unwrap_or_default();
+
+        Snapshot {
+            start_time_nanos: self.start_time_nanos,
+            time_nanos: unix_nanos(),
+            series,
+        }
+    }
+
+    fn update(&self, metric: &Metric, labels: &[(&str, &str)], apply: impl FnOnce(&mut Value)) {
+        let mut labels: Vec<(String, String)> = labels
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        labels.sort();
+
+        // A poisoned lock only loses metrics; never fail the command over it
+        let Ok(mut map) = self.series.lock() else {
+            return;
+        };
+
+        let series = map
+            .entry((metric.name, labels.clone()))
+            .or_insert_with(|| Series {
+                metric: *metric,
+                labels,
+                value: match metric.kind {
+                    MetricKind::Counter => Value::Counter(0),
+                    MetricKind::Histogram { bounds } => Value::Histogram(Histogram::new(bounds)),
+                },
+            });
+        apply(&mut series.value);
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The process-wide registry.
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::new)
+}
+
+fn unix_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters_by_label() {
+        let metrics = Metrics::new();
+        assert!(metrics.is_empty());
+
+        metrics.increment(&WATCH_TRIGGERS, &[("task", "test")], 1);
+        metrics.increment(&WATCH_TRIGGERS, &[("task", "lint")], 1);
+        metrics.increment(&WATCH_TRIGGERS, &[("task", "test")], 2);
+
+        let snapshot = metrics.snapshot();
+        let values: Vec<(&str, &Value)> = snapshot
+            .series
+            .iter()
+            .map(|series| (series.labels[0].1.as_str(), &series.value))
+            .collect();
+        assert_eq!(
+            values,
+            [("lint", &Value::Counter(1)), ("test", &Value::Counter(3))]
+        );
+        assert!(snapshot.time_nanos >= snapshot.start_time_nanos);
+    }
+
+    #[test]
+    fn test_histogram_buckets() {
+        let metrics = Metrics::new();
+        let labels = [("outcome", "success"), ("command", "build")];
+
+        for seconds in [0.001, 0.2, 0.25, 400.0] {
+            metrics.observe(&COMMAND_DURATION, &labels, seconds);
+        }
+
+        let snapshot = metrics.snapshot();
+        let series = &snapshot.series[0];
+        assert_eq!(
+            series.labels,
+            [
+                ("command".to_string(), "build".to_string()),
+                ("outcome".to_string(), "success".to_string())
+            ]
+        );
+
+        let Value::Histogram(histogram) = &series.value else {
+            panic!("not a histogram");
+        };
+        assert_eq!(histogram.count, 4);
+        assert_eq!(histogram.bucket_counts[0], 1);
+        assert_eq!(histogram.bucket_counts[5], 2);
+        assert_eq!(histogram.bucket_counts[DURATION_BUCKETS.len()], 1);
+        assert_eq!(histogram.min, 0.001);
+        assert_eq!(histogram.max, 400.0);
+        assert!((histogram.sum - 400.451).abs() < 1e-9);
+    }
+}
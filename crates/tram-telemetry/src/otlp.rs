@@ -0,0 +1,249 @@
+//! Export to an OpenTelemetry collector over OTLP/HTTP with JSON.
+//!
+//! Each export carries what one run recorded, so sums and histograms use
+//! delta temporality and the collector adds runs together.
+
+use crate::exporter::Exporter;
+use crate::metrics::{Histogram, Series, Snapshot, Value};
+use serde_json::{Value as Json, json};
+use tram_core::AppResult;
+use tram_http::HttpClient;
+
+/// Default collector when no endpoint is configured.
+pub const DEFAULT_OTLP_ENDPOINT: &str = "http://localhost:4318";
+
+/// Path metrics are posted to under the collector's base URL.
+const METRICS_PATH: &str = "/v1/metrics";
+
+/// `AGGREGATION_TEMPORALITY_DELTA` in the OTLP protocol.
+const DELTA: u8 = 1;
+
+/// Pushes metrics to an OTLP/HTTP collector.
+#[derive(Debug, Clone)]
+pub struct OtlpExporter {
+    url: String,
+    service_name: String,
+    headers: Vec<(String, String)>,
+    client: HttpClient,
+}
+
+impl OtlpExporter {
+    /// Export to the collector at `endpoint`, e.g. `http://localhost:4318`.
+    /// `/v1/metrics` is appended unless the URL already ends with it.
+    pub fn new(endpoint: &str, client: HttpClient) -> Self {
+        let endpoint = endpoint.trim_end_matches('/');
+        let url = if endpoint.ends_with(METRICS_PATH) {
+            endpoint.to_string()
+        } else {
+            format!("{}{}", endpoint, METRICS_PATH)
+        };
+
+        Self {
+            url,
+            service_name: "tram".to_string(),
+            headers: Vec::new(),
+            client,
+        }
+    }
+
+    /// Report as this `service.name`.
+    pub fn with_service_name(mut self, service_name: impl Into<String>) -> Self {
+        self.service_name = service_name.into();
+        self
+    }
+
+    /// Send these headers with every export, e.g. an API key.
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// The URL metrics are posted to.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl Exporter for OtlpExporter {
+    fn name(&self) -> &str {
+        "otlp"
+    }
+
+    fn export(&self, snapshot: &Snapshot) -> AppResult<()> {
+        let body = encode_otlp(snapshot, &self.service_name).to_string();
+
+        self.client.post(
+            &self.url,
+            "application/json",
+            &self.headers,
+            body.as_bytes(),
+        )
+    }
+}
+
+/// An `ExportMetricsServiceRequest` in the OTLP JSON encoding.
+pub fn encode_otlp(snapshot: &Snapshot, service_name: &str) -> Json {
+    let mut metrics: Vec<Json> = Vec::new();
+
+    for series in &snapshot.series {
+        let point = data_point(series, snapshot);
+
+        // Series of one metric are adjacent; add to the last if it matches
+        if let Some(last) = metrics.last_mut()
+            && last["name"] == series.metric.name
+        {
+            let kind = if last.get("sum").is_some() {
+                "sum"
+            } else {
+                "histogram"
+            };
+            if let Some(points) = last[kind]["dataPoints"].as_array_mut() {
+                points.push(point);
+            }
+            continue;
+        }
+
+        let data = match series.value {
+            Value::Counter(_) => json!({
+                "sum": {
+                    "dataPoints": [point],
+                    "aggregationTemporality": DELTA,
+                    "isMonotonic": true,
+                }
+            }),
+            Value::Histogram(_) => json!({
+                "histogram": {
+                    "dataPoints": [point],
+                    "aggregationTemporality": DELTA,
+                }
+            }),
+        };
+
+        let mut metric = json!({
+            "name": series.metric.name,
+            "unit": series.metric.unit,
+            "description": series.metric.description,
+        });
+        if let (Some(metric), Json::Object(data)) = (metric.as_object_mut(), data) {
+            metric.extend(data);
+        }
+        metrics.push(metric);
+    }
+
+    json!({
+        "resourceMetrics": [{
+            "resource": {
+                "attributes": [
+                    attribute("service.name", service_name),
+                    attribute("service.version", env!("CARGO_PKG_VERSION")),
+                ]
+            },
+            "scopeMetrics": [{
+                "scope": { "name": "tram-telemetry", "version": env!("CARGO_PKG_VERSION") },
+                "metrics": metrics,
+            }]
+        }]
+    })
+}
+
+/// One series as a data point. 64-bit integers are strings in OTLP JSON.
+fn data_point(series: &Series, snapshot: &Snapshot) -> Json {
+    let attributes: Vec<Json> = series
+        .labels
+        .iter()
+        .map(|(name, value)| attribute(name, value))
+        .collect();
+    let times = json!({
+        "attributes": attributes,
+        "startTimeUnixNano": snapshot.start_time_nanos.to_string(),
+        "timeUnixNano": snapshot.time_nanos.to_string(),
+    });
+
+    let values = match &series.value {
+        Value::Counter(count) => json!({ "asInt": count.to_string() }),
+        Value::Histogram(histogram) => histogram_values(histogram),
+    };
+
+    let mut point = times;
+    if let (Some(point), Json::Object(values)) = (point.as_object_mut(), values) {
+        point.extend(values);
+    }
+    point
+}
+
+fn histogram_values(histogram: &Histogram) -> Json {
+    let mut values = json!({
+        "count": histogram.count.to_string(),
+        "sum": histogram.sum,
+        "bucketCounts": histogram
+            .bucket_counts
+            .iter()
+            .map(|count| count.to_string())
+            .collect::<Vec<_>>(),
+        "explicitBounds": histogram.bounds,
+    });
+
+    if histogram.count > 0 {
+        values["min"] = json!(histogram.min);
+        values["max"] = json!(histogram.max);
+    }
+    values
+}
+
+fn attribute(key: &str, value: &str) -> Json {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::{COMMAND_DURATION, Metrics, WATCH_TRIGGERS};
+
+    #[test]
+    fn test_metrics_path_is_appended_once() {
+        for endpoint in [
+            "http://localhost:4318",
+            "http://localhost:4318/",
+            "http://localhost:4318/v1/metrics",
+        ] {
+            assert_eq!(
+                OtlpExporter::new(endpoint, HttpClient::new()).url(),
+                "http://localhost:4318/v1/metrics"
+            );
+        }
+    }
+
+    #[test]
+    fn test_encode() {
+        let metrics = Metrics::new();
+        metrics.observe(&COMMAND_DURATION, &[("command", "build")], 0.2);
+        metrics.increment(&WATCH_TRIGGERS, &[("task", "lint")], 1);
+        metrics.increment(&WATCH_TRIGGERS, &[("task", "test")], 2);
+
+        let request = encode_otlp(&metrics.snapshot(), "my-cli");
+        let resource = &request["resourceMetrics"][0];
+        assert_eq!(
+            resource["resource"]["attributes"][0],
+            json!({ "key": "service.name", "value": { "stringValue": "my-cli" } })
+        );
+
+        let encoded = resource["scopeMetrics"][0]["metrics"].as_array().unwrap();
+        assert_eq!(encoded.len(), 2);
+
+        let duration = &encoded[0];
+        assert_eq!(duration["name"], "tram.command.duration");
+        assert_eq!(duration["unit"], "s");
+        assert_eq!(duration["histogram"]["aggregationTemporality"], 1);
+        let point = &duration["histogram"]["dataPoints"][0];
+        assert_eq!(point["count"], "1");
+        assert_eq!(point["bucketCounts"][5], "1");
+        assert_eq!(point["attributes"][0]["key"], "command");
+
+        let triggers = &encoded[1]["sum"];
+        assert_eq!(triggers["isMonotonic"], true);
+        let points = triggers["dataPoints"].as_array().unwrap();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[1]["asInt"], "2");
+        assert_eq!(points[1]["attributes"][0]["value"]["stringValue"], "test");
+    }
+}
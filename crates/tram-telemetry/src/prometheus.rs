@@ -0,0 +1,234 @@
+//! Export to a Prometheus Pushgateway in the text exposition format.
+//!
+//! The gateway keeps the values of the last push rather than adding runs
+//! together, so counters show what the most recent command recorded.
+
+use crate::exporter::Exporter;
+use crate::metrics::{Metric, Snapshot, Value};
+use std::fmt::Write;
+use tram_core::AppResult;
+use tram_http::HttpClient;
+
+/// Default Pushgateway when no endpoint is configured.
+pub const DEFAULT_PUSHGATEWAY_ENDPOINT: &str = "http://localhost:9091";
+
+/// Content type of the text exposition format.
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Pushes metrics to a Prometheus Pushgateway.
+#[derive(Debug, Clone)]
+pub struct PushgatewayExporter {
+    url: String,
+    headers: Vec<(String, String)>,
+    client: HttpClient,
+}
+
+impl PushgatewayExporter {
+    /// Push to the gateway at `endpoint`, e.g. `http://localhost:9091`,
+    /// grouped under `job`.
+    pub fn new(endpoint: &str, job: &str, client: HttpClient) -> Self {
+        Self {
+            url: format!(
+                "{}/metrics/job/{}",
+                endpoint.trim_end_matches('/'),
+                path_segment(job)
+            ),
+            headers: Vec::new(),
+            client,
+        }
+    }
+
+    /// Send these headers with every push, e.g. basic auth.
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// The URL metrics are pushed to.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl Exporter for PushgatewayExporter {
+    fn name(&self) -> &str {
+        "prometheus"
+    }
+
+    fn export(&self, snapshot: &Snapshot) -> AppResult<()> {
+        self.client.post(
+            &self.url,
+            CONTENT_TYPE,
+            &self.headers,
+            encode_prometheus(snapshot).as_bytes(),
+        )
+    }
+}
+
+/// Metrics in the Prometheus text exposition format.
+pub fn encode_prometheus(snapshot: &Snapshot) -> String {
+    let mut out = String::new();
+    let mut family = None;
+
+    for series in &snapshot.series {
+        let name = metric_name(&series.metric);
+
+        // Series of one metric are adjacent; describe each metric once
+        if family != Some(series.metric.name) {
+            family = Some(series.metric.name);
+            let kind = match series.value {
+                Value::Counter(_) => "counter",
+                Value::Histogram(_) => "histogram",
+            };
+            let _ = writeln!(out, "# HELP {} {}", name, series.metric.description);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        }
+
+        match &series.value {
+            Value::Counter(count) => {
+                let _ = writeln!(out, "{}{} {}", name, labels(&series.labels, None), count);
+            }
+            Value::Histogram(histogram) => {
+                // Prometheus buckets are cumulative
+                let mut cumulative = 0;
+                for (bound, count) in histogram.bounds.iter().zip(&histogram.bucket_counts) {
+                    cumulative += count;
+                    let _ = writeln!(
+                        out,
+                        "{}_bucket{} {}",
+                        name,
+                        labels(&series.labels, Some(&bound.to_string())),
+                        cumulative
+                    );
+                }
+                let _ = writeln!(
+                    out,
+                    "{}_bucket{} {}",
+                    name,
+                    labels(&series.labels, Some("+Inf")),
+                    histogram.count
+                );
+                let _ = writeln!(
+                    out,
+                    "{}_sum{} {}",
+                    name,
+                    labels(&series.labels, None),
+                    histogram.sum
+                );
+                let _ = writeln!(
+                    out,
+                    "{}_count{} {}",
+                    name,
+                    labels(&series.labels, None),
+                    histogram.count
+                );
+            }
+        }
+    }
+
+    out
+}
+
+/// `tram.command.duration` in seconds becomes `tram_command_duration_seconds`,
+/// and counters end in `_total`.
+fn metric_name(metric: &Metric) -> String {
+    let mut name: String = metric
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    if metric.unit == "s" {
+        name.push_str("_seconds");
+    }
+    if matches!(metric.kind, crate::metrics::MetricKind::Counter) {
+        name.push_str("_total");
+    }
+
+    name
+}
+
+/// `{name="value",...}`, with `le` added for histogram buckets.
+fn labels(labels: &[(String, String)], le: Option<&str>) -> String {
+    let mut pairs: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape(value)))
+        .collect();
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{}\"", le));
+    }
+
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// A job name safe to use as a URL path segment.
+fn path_segment(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "-_.~".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::{CACHE_HITS, COMMAND_DURATION, DURATION_BUCKETS, Metrics};
+
+    #[test]
+    fn test_push_url() {
+        let exporter =
+            PushgatewayExporter::new("http://localhost:9091/", "my cli", HttpClient::new());
+        assert_eq!(exporter.url(), "http://localhost:9091/metrics/job/my_cli");
+    }
+
+    #[test]
+    fn test_encode() {
+        let metrics = Metrics::new();
+        metrics.increment(&CACHE_HITS, &[], 3);
+        metrics.observe(
+            &COMMAND_DURATION,
+            &[("command", "say \"hi\""), ("outcome", "success")],
+            0.2,
+        );
+
+        let text = encode_prometheus(&metrics.snapshot());
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(
+            &lines[..3],
+            [
+                "# HELP tram_cache_hits_total Cache lookups that found an entry",
+                "# TYPE tram_cache_hits_total counter",
+                "tram_cache_hits_total 3",
+            ]
+        );
+        assert_eq!(lines[4], "# TYPE tram_command_duration_seconds histogram");
+        assert_eq!(
+            lines[5],
+            r#"tram_command_duration_seconds_bucket{command="say \"hi\"",outcome="success",le="0.005"} 0"#
+        );
+        assert!(text.contains(r#"outcome="success",le="0.25"} 1"#));
+        assert!(text.contains(r#"outcome="success",le="+Inf"} 1"#));
+        assert!(text.contains(
+            r#"tram_command_duration_seconds_count{command="say \"hi\"",outcome="success"} 1"#
+        ));
+        assert_eq!(lines.len(), 3 + 2 + DURATION_BUCKETS.len() + 3);
+    }
+}
@@ -0,0 +1,127 @@
+//! ANSI-aware helpers for asserting on terminal output.
+//!
+//! Progress bars and spinners redraw lines in place with carriage returns
+//! and escape sequences, so their raw output is hard to compare. These
+//! helpers strip the styling, or replay the output like a terminal would and
+//! return the lines left on screen.
+
+/// Remove ANSI escape sequences (colors, cursor movement, erasing).
+pub fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+
+        // CSI sequences end with a byte in `@`..=`~`; others are two bytes
+        if chars.peek() == Some(&'[') {
+            chars.next();
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        } else {
+            chars.next();
+        }
+    }
+
+    out
+}
+
+/// Replay terminal output and return the lines left on screen, without
+/// styling or trailing whitespace.
+///
+/// Handles `\r`, `\n`, erase-line (`ESC[K`, `ESC[2K`), and cursor-up
+/// (`ESC[nA`), which is what in-place redrawing uses.
+pub fn screen_lines(text: &str) -> Vec<String> {
+    let mut lines: Vec<Vec<char>> = vec![Vec::new()];
+    let mut row = 0;
+    let mut col = 0;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' => col = 0,
+            '\n' => {
+                row += 1;
+                col = 0;
+                if row == lines.len() {
+                    lines.push(Vec::new());
+                }
+            }
+            '\x1b' if chars.peek() == Some(&'[') => {
+                chars.next();
+                let mut params = String::new();
+                let mut command = None;
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        command = Some(c);
+                        break;
+                    }
+                    params.push(c);
+                }
+
+                match command {
+                    Some('K') if params.is_empty() || params == "0" => lines[row].truncate(col),
+                    Some('K') if params == "2" => lines[row].clear(),
+                    Some('A') => {
+                        let count = params.parse::<usize>().unwrap_or(1);
+                        row = row.saturating_sub(count);
+                    }
+                    _ => {}
+                }
+            }
+            '\x1b' => {
+                chars.next();
+            }
+            c => {
+                let line = &mut lines[row];
+                if col < line.len() {
+                    line[col] = c;
+                } else {
+                    line.resize(col, ' ');
+                    line.push(c);
+                }
+                col += 1;
+            }
+        }
+    }
+
+    let mut lines: Vec<String> = lines
+        .into_iter()
+        .map(|line| line.into_iter().collect::<String>().trim_end().to_string())
+        .collect();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi("\x1b[32m✓\x1b[0m done"), "✓ done");
+        assert_eq!(strip_ansi("\r\x1b[Kplain"), "\rplain");
+        assert_eq!(strip_ansi("no escapes"), "no escapes");
+    }
+
+    #[test]
+    fn test_screen_lines_replays_redraws() {
+        assert_eq!(
+            screen_lines("\r\x1b[K[--] 0%\r\x1b[K[=-] 50%\r\x1b[K[==] 100%\n"),
+            vec!["[==] 100%"]
+        );
+        assert_eq!(
+            screen_lines("a 1\nb 1\n\x1b[2A\r\x1b[Ka 2\n\r\x1b[K\x1b[1mb 2\x1b[0m\n"),
+            vec!["a 2", "b 2"]
+        );
+    }
+}
@@ -74,3 +74,28 @@ macro_rules! assert_dir_exists {
         );
     };
 }
+
+/// Assert that text contains a string once ANSI styling is stripped
+#[macro_export]
+macro_rules! assert_plain_contains {
+    ($text:expr, $expected:expr) => {
+        let plain = $crate::ansi::strip_ansi(&$text);
+        assert!(
+            plain.contains($expected),
+            "Output does not contain '{}'\nActual output (ANSI stripped): {}",
+            $expected,
+            plain
+        );
+    };
+}
+
+/// Assert on the lines terminal output leaves on screen, after in-place
+/// redraws and without styling
+#[macro_export]
+macro_rules! assert_screen_eq {
+    ($text:expr, $expected:expr) => {
+        let lines = $crate::ansi::screen_lines(&$text);
+        let expected: Vec<String> = $expected.iter().map(|line| line.to_string()).collect();
+        assert_eq!(lines, expected, "Raw output: {:?}", $text);
+    };
+}
@@ -61,3 +61,668 @@ impl TempFile {
         Ok(())
     }
 }
+
+/// A project that can be written into a directory for a test.
+pub trait ProjectFixture {
+    /// Package name
+    fn name(&self) -> &str;
+
+    /// Package version
+    fn version(&self) -> &str;
+
+    /// Write the manifest and sources into `dir`, but not the lockfile,
+    /// which a monorepo keeps at its root
+    fn write_project(&self, dir: &Path) -> Result<(), std::io::Error>;
+
+    /// Write the project, lockfile included, into `dir`
+    fn write_to(&self, dir: &Path) -> Result<(), std::io::Error>;
+
+    /// Write the project into a new temporary directory
+    fn build(&self) -> Result<TempDir, std::io::Error> {
+        let temp_dir = TempDir::new()?;
+        self.write_to(temp_dir.path())?;
+        Ok(temp_dir)
+    }
+}
+
+/// A Cargo package: `Cargo.toml`, `src/main.rs` or `src/lib.rs`, and `Cargo.lock`
+#[derive(Debug, Clone)]
+pub struct RustProjectFixture {
+    name: String,
+    version: String,
+    library: bool,
+    /// Dependency name and its TOML spec
+    dependencies: Vec<(String, String)>,
+    lockfile: bool,
+    files: Vec<(PathBuf, String)>,
+}
+
+impl RustProjectFixture {
+    /// Create a binary package named `name`
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: "0.1.0".to_string(),
+            library: false,
+            dependencies: Vec::new(),
+            lockfile: true,
+            files: Vec::new(),
+        }
+    }
+
+    /// Set the package version
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    /// Make it a library, with `src/lib.rs` instead of `src/main.rs`
+    pub fn library(mut self) -> Self {
+        self.library = true;
+        self
+    }
+
+    /// Depend on a crates.io package
+    pub fn dependency(mut self, name: impl Into<String>, version: &str) -> Self {
+        self.dependencies
+            .push((name.into(), format!("\"{}\"", version)));
+        self
+    }
+
+    /// Depend on a package at `path`, relative to this one
+    pub fn path_dependency(mut self, name: impl Into<String>, path: &str) -> Self {
+        self.dependencies
+            .push((name.into(), format!("{{ path = \"{}\" }}", path)));
+        self
+    }
+
+    /// Leave out `Cargo.lock`
+    pub fn without_lockfile(mut self) -> Self {
+        self.lockfile = false;
+        self
+    }
+
+    /// Add a file at `path`, relative to the package
+    pub fn file(mut self, path: impl Into<PathBuf>, contents: impl Into<String>) -> Self {
+        self.files.push((path.into(), contents.into()));
+        self
+    }
+
+    fn manifest(&self) -> String {
+        let mut manifest = format!(
+            "[package]\nname = \"{}\"\nversion = \"{}\"\nedition = \"2024\"\n",
+            self.name, self.version
+        );
+
+        if !self.dependencies.is_empty() {
+            manifest.push_str("\n[dependencies]\n");
+            for (name, spec) in &self.dependencies {
+                manifest.push_str(&format!("{} = {}\n", name, spec));
+            }
+        }
+
+        manifest
+    }
+}
+
+impl ProjectFixture for RustProjectFixture {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn write_project(&self, dir: &Path) -> Result<(), std::io::Error> {
+        write_file(dir, "Cargo.toml", &self.manifest())?;
+        write_file(dir, ".gitignore", "/target\n")?;
+
+        if self.library {
+            write_file(
+                dir,
+                "src/lib.rs",
+                "pub fn add(left: u64, right: u64) -> u64 {\n    left + right\n}\n",
+            )?;
+        } else {
+            write_file(
+                dir,
+                "src/main.rs",
+                &format!(
+                    "fn main() {{\n    println!(\"Hello from {}!\");\n}}\n",
+                    self.name
+                ),
+            )?;
+        }
+
+        write_files(dir, &self.files)
+    }
+
+    fn write_to(&self, dir: &Path) -> Result<(), std::io::Error> {
+        self.write_project(dir)?;
+
+        if self.lockfile {
+            write_file(
+                dir,
+                "Cargo.lock",
+                &cargo_lock([self as &dyn ProjectFixture]),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The package manager whose lockfile a Node.js fixture gets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PackageManager {
+    #[default]
+    Npm,
+    Yarn,
+    Pnpm,
+}
+
+impl PackageManager {
+    /// The lockfile's name, e.g. `package-lock.json`
+    pub fn lockfile(&self) -> &'static str {
+        match self {
+            PackageManager::Npm => "package-lock.json",
+            PackageManager::Yarn => "yarn.lock",
+            PackageManager::Pnpm => "pnpm-lock.yaml",
+        }
+    }
+}
+
+/// A Node.js package: `package.json`, `src/index.js`, and a lockfile
+#[derive(Debug, Clone)]
+pub struct NodeProjectFixture {
+    name: String,
+    version: String,
+    package_manager: PackageManager,
+    dependencies: Vec<(String, String)>,
+    scripts: Vec<(String, String)>,
+    lockfile: bool,
+    files: Vec<(PathBuf, String)>,
+}
+
+impl NodeProjectFixture {
+    /// Create a package named `name`, locked with npm
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: "1.0.0".to_string(),
+            package_manager: PackageManager::default(),
+            dependencies: Vec::new(),
+            scripts: Vec::new(),
+            lockfile: true,
+            files: Vec::new(),
+        }
+    }
+
+    /// Set the package version
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    /// Lock with another package manager
+    pub fn package_manager(mut self, package_manager: PackageManager) -> Self {
+        self.package_manager = package_manager;
+        self
+    }
+
+    /// Depend on a package, e.g. `("react", "^18.0.0")` or `("ui", "workspace:*")`
+    pub fn dependency(mut self, name: impl Into<String>, version: impl Into<String>) -> Self {
+        self.dependencies.push((name.into(), version.into()));
+        self
+    }
+
+    /// Add an npm script
+    pub fn script(mut self, name: impl Into<String>, command: impl Into<String>) -> Self {
+        self.scripts.push((name.into(), command.into()));
+        self
+    }
+
+    /// Leave out the lockfile
+    pub fn without_lockfile(mut self) -> Self {
+        self.lockfile = false;
+        self
+    }
+
+    /// Add a file at `path`, relative to the package
+    pub fn file(mut self, path: impl Into<PathBuf>, contents: impl Into<String>) -> Self {
+        self.files.push((path.into(), contents.into()));
+        self
+    }
+
+    fn manifest(&self) -> serde_json::Value {
+        let mut manifest = serde_json::json!({
+            "name": self.name,
+            "version": self.version,
+            "main": "src/index.js",
+        });
+
+        if !self.scripts.is_empty() {
+            manifest["scripts"] = string_map(&self.scripts);
+        }
+
+        if !self.dependencies.is_empty() {
+            manifest["dependencies"] = string_map(&self.dependencies);
+        }
+
+        manifest
+    }
+}
+
+impl ProjectFixture for NodeProjectFixture {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn write_project(&self, dir: &Path) -> Result<(), std::io::Error> {
+        write_json(dir, "package.json", &self.manifest())?;
+        write_file(dir, ".gitignore", "node_modules/\n")?;
+        write_file(
+            dir,
+            "src/index.js",
+            &format!("module.exports = {{ name: \"{}\" }};\n", self.name),
+        )?;
+
+        write_files(dir, &self.files)
+    }
+
+    fn write_to(&self, dir: &Path) -> Result<(), std::io::Error> {
+        self.write_project(dir)?;
+
+        if self.lockfile {
+            write_node_lockfile(dir, self.package_manager, Some(self), &[])?;
+        }
+
+        Ok(())
+    }
+}
+
+/// What kind of workspace a [`MonorepoFixture`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MonorepoKind {
+    Cargo,
+    Node(PackageManager),
+}
+
+/// A Cargo or Node.js workspace with its members nested under `crates/` or
+/// `packages/`, and one lockfile at the root
+pub struct MonorepoFixture {
+    kind: MonorepoKind,
+    members: Vec<(PathBuf, Box<dyn ProjectFixture>)>,
+    lockfile: bool,
+    files: Vec<(PathBuf, String)>,
+}
+
+impl MonorepoFixture {
+    /// A virtual Cargo workspace, with members under `crates/`
+    pub fn cargo() -> Self {
+        Self::with_kind(MonorepoKind::Cargo)
+    }
+
+    /// A private Node.js workspace, with members under `packages/`. pnpm
+    /// lists them in `pnpm-workspace.yaml`, npm and Yarn in `package.json`.
+    pub fn node(package_manager: PackageManager) -> Self {
+        Self::with_kind(MonorepoKind::Node(package_manager))
+    }
+
+    fn with_kind(kind: MonorepoKind) -> Self {
+        Self {
+            kind,
+            members: Vec::new(),
+            lockfile: true,
+            files: Vec::new(),
+        }
+    }
+
+    /// Add a member under `crates/` or `packages/`, named after the package
+    pub fn member(self, project: impl ProjectFixture + 'static) -> Self {
+        let parent = match self.kind {
+            MonorepoKind::Cargo => "crates",
+            MonorepoKind::Node(_) => "packages",
+        };
+        let path = Path::new(parent).join(project.name());
+        self.member_at(path, project)
+    }
+
+    /// Add a member at `path`, relative to the workspace root
+    pub fn member_at(
+        mut self,
+        path: impl Into<PathBuf>,
+        project: impl ProjectFixture + 'static,
+    ) -> Self {
+        self.members.push((path.into(), Box::new(project)));
+        self
+    }
+
+    /// Leave out the lockfile
+    pub fn without_lockfile(mut self) -> Self {
+        self.lockfile = false;
+        self
+    }
+
+    /// Add a file at `path`, relative to the workspace root
+    pub fn file(mut self, path: impl Into<PathBuf>, contents: impl Into<String>) -> Self {
+        self.files.push((path.into(), contents.into()));
+        self
+    }
+
+    /// Write the workspace and its members into `dir`
+    pub fn write_to(&self, dir: &Path) -> Result<(), std::io::Error> {
+        let paths: Vec<String> = self
+            .members
+            .iter()
+            .map(|(path, _)| path.to_string_lossy().replace('\\', "/"))
+            .collect();
+
+        match self.kind {
+            MonorepoKind::Cargo => {
+                let members: Vec<String> =
+                    paths.iter().map(|path| format!("\"{}\"", path)).collect();
+                write_file(
+                    dir,
+                    "Cargo.toml",
+                    &format!(
+                        "[workspace]\nresolver = \"2\"\nmembers = [{}]\n",
+                        members.join(", ")
+                    ),
+                )?;
+                write_file(dir, ".gitignore", "/target\n")?;
+            }
+            MonorepoKind::Node(PackageManager::Pnpm) => {
+                write_json(dir, "package.json", &serde_json::json!({ "private": true }))?;
+                let packages: String = paths
+                    .iter()
+                    .map(|path| format!("  - '{}'\n", path))
+                    .collect();
+                write_file(
+                    dir,
+                    "pnpm-workspace.yaml",
+                    &format!("packages:\n{}", packages),
+                )?;
+                write_file(dir, ".gitignore", "node_modules/\n")?;
+            }
+            MonorepoKind::Node(_) => {
+                write_json(
+                    dir,
+                    "package.json",
+                    &serde_json::json!({ "private": true, "workspaces": paths }),
+                )?;
+                write_file(dir, ".gitignore", "node_modules/\n")?;
+            }
+        }
+
+        for (path, project) in &self.members {
+            project.write_project(&dir.join(path))?;
+        }
+
+        if self.lockfile {
+            match self.kind {
+                MonorepoKind::Cargo => write_file(
+                    dir,
+                    "Cargo.lock",
+                    &cargo_lock(self.members.iter().map(|(_, project)| project.as_ref())),
+                )?,
+                MonorepoKind::Node(package_manager) => {
+                    let members: Vec<(&Path, &dyn ProjectFixture)> = self
+                        .members
+                        .iter()
+                        .map(|(path, project)| (path.as_path(), project.as_ref()))
+                        .collect();
+                    write_node_lockfile(dir, package_manager, None, &members)?;
+                }
+            }
+        }
+
+        write_files(dir, &self.files)
+    }
+
+    /// Write the workspace into a new temporary directory
+    pub fn build(&self) -> Result<TempDir, std::io::Error> {
+        let temp_dir = TempDir::new()?;
+        self.write_to(temp_dir.path())?;
+        Ok(temp_dir)
+    }
+}
+
+/// Write `contents` to `path` under `dir`, creating parent directories
+fn write_file(dir: &Path, path: impl AsRef<Path>, contents: &str) -> Result<(), std::io::Error> {
+    let path = dir.join(path);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, contents)
+}
+
+fn write_files(dir: &Path, files: &[(PathBuf, String)]) -> Result<(), std::io::Error> {
+    files
+        .iter()
+        .try_for_each(|(path, contents)| write_file(dir, path, contents))
+}
+
+fn write_json(dir: &Path, path: &str, value: &serde_json::Value) -> Result<(), std::io::Error> {
+    let json = serde_json::to_string_pretty(value).map_err(std::io::Error::other)?;
+    write_file(dir, path, &format!("{}\n", json))
+}
+
+fn string_map(pairs: &[(String, String)]) -> serde_json::Value {
+    pairs
+        .iter()
+        .map(|(key, value)| (key.clone(), serde_json::Value::from(value.as_str())))
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+/// A `Cargo.lock` listing the local packages, sorted by name
+fn cargo_lock<'a>(packages: impl IntoIterator<Item = &'a dyn ProjectFixture>) -> String {
+    let mut packages: Vec<_> = packages
+        .into_iter()
+        .map(|package| (package.name(), package.version()))
+        .collect();
+    packages.sort();
+
+    let mut lock = "# This file is automatically @generated by Cargo.\n\
+                    # It is not intended for manual editing.\n\
+                    version = 4\n"
+        .to_string();
+    for (name, version) in packages {
+        lock.push_str(&format!(
+            "\n[[package]]\nname = \"{}\"\nversion = \"{}\"\n",
+            name, version
+        ));
+    }
+    lock
+}
+
+/// Write the package manager's lockfile for a package, or for a workspace root
+/// without a package of its own and its members
+fn write_node_lockfile(
+    dir: &Path,
+    package_manager: PackageManager,
+    root: Option<&dyn ProjectFixture>,
+    members: &[(&Path, &dyn ProjectFixture)],
+) -> Result<(), std::io::Error> {
+    match package_manager {
+        PackageManager::Npm => {
+            let mut packages = serde_json::Map::new();
+            packages.insert(
+                String::new(),
+                match root {
+                    Some(project) => serde_json::json!({
+                        "name": project.name(),
+                        "version": project.version(),
+                    }),
+                    None => serde_json::json!({
+                        "workspaces": members
+                            .iter()
+                            .map(|(path, _)| path.to_string_lossy().replace('\\', "/"))
+                            .collect::<Vec<_>>(),
+                    }),
+                },
+            );
+            for (path, project) in members {
+                let path = path.to_string_lossy().replace('\\', "/");
+                packages.insert(
+                    format!("node_modules/{}", project.name()),
+                    serde_json::json!({ "resolved": path, "link": true }),
+                );
+                packages.insert(
+                    path,
+                    serde_json::json!({ "name": project.name(), "version": project.version() }),
+                );
+            }
+
+            let mut lock = serde_json::json!({
+                "lockfileVersion": 3,
+                "requires": true,
+                "packages": packages,
+            });
+            if let Some(project) = root {
+                lock["name"] = project.name().into();
+                lock["version"] = project.version().into();
+            }
+            write_json(dir, package_manager.lockfile(), &lock)
+        }
+        PackageManager::Yarn => write_file(
+            dir,
+            package_manager.lockfile(),
+            "# THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.\n\
+             # yarn lockfile v1\n",
+        ),
+        PackageManager::Pnpm => {
+            let mut lock = "lockfileVersion: '9.0'\n\nimporters:\n\n  .: {}\n".to_string();
+            for (path, _) in members {
+                lock.push_str(&format!(
+                    "\n  {}: {{}}\n",
+                    path.to_string_lossy().replace('\\', "/")
+                ));
+            }
+            write_file(dir, package_manager.lockfile(), &lock)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tram_workspace::{ProjectType, WorkspaceGraph, WorkspaceMembers};
+
+    #[test]
+    fn test_rust_project() {
+        let temp_dir = RustProjectFixture::new("demo")
+            .dependency("serde", "1.0")
+            .file("tests/smoke.rs", "#[test]\nfn smoke() {}\n")
+            .build()
+            .unwrap();
+        let root = temp_dir.path();
+
+        assert_eq!(ProjectType::detect(root), Some(ProjectType::Rust));
+        assert!(root.join("src/main.rs").exists());
+        assert!(root.join("tests/smoke.rs").exists());
+
+        let manifest: toml::Table = std::fs::read_to_string(root.join("Cargo.toml"))
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(manifest["package"]["name"].as_str(), Some("demo"));
+        assert_eq!(manifest["dependencies"]["serde"].as_str(), Some("1.0"));
+
+        let lock: toml::Table = std::fs::read_to_string(root.join("Cargo.lock"))
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(lock["package"][0]["name"].as_str(), Some("demo"));
+    }
+
+    #[test]
+    fn test_node_project() {
+        let temp_dir = NodeProjectFixture::new("web")
+            .package_manager(PackageManager::Pnpm)
+            .dependency("react", "^18.0.0")
+            .script("test", "node --test")
+            .build()
+            .unwrap();
+        let root = temp_dir.path();
+
+        assert_eq!(ProjectType::detect(root), Some(ProjectType::NodeJs));
+        assert!(root.join("pnpm-lock.yaml").exists());
+        assert!(!root.join("package-lock.json").exists());
+
+        let manifest: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(root.join("package.json")).unwrap())
+                .unwrap();
+        assert_eq!(manifest["name"], "web");
+        assert_eq!(manifest["dependencies"]["react"], "^18.0.0");
+        assert_eq!(manifest["scripts"]["test"], "node --test");
+    }
+
+    #[test]
+    fn test_cargo_monorepo() {
+        let temp_dir = MonorepoFixture::cargo()
+            .member(RustProjectFixture::new("core").library())
+            .member(RustProjectFixture::new("cli").path_dependency("core", "../core"))
+            .member_at(
+                "tools/xtask",
+                RustProjectFixture::new("xtask").without_lockfile(),
+            )
+            .build()
+            .unwrap();
+        let root = temp_dir.path();
+
+        // Members share the root lockfile
+        assert!(root.join("Cargo.lock").exists());
+        assert!(!root.join("crates/cli/Cargo.lock").exists());
+
+        let graph = WorkspaceGraph::discover(root).unwrap();
+        let members: Vec<_> = graph
+            .members
+            .iter()
+            .map(|member| (member.name.as_str(), member.path.to_string_lossy()))
+            .collect();
+        assert_eq!(
+            members,
+            [
+                ("cli", "crates/cli".into()),
+                ("core", "crates/core".into()),
+                ("xtask", "tools/xtask".into()),
+            ]
+        );
+        assert_eq!(graph.members[0].dependencies, ["core"]);
+    }
+
+    #[test]
+    fn test_node_monorepos() {
+        for package_manager in [
+            PackageManager::Npm,
+            PackageManager::Yarn,
+            PackageManager::Pnpm,
+        ] {
+            let temp_dir = MonorepoFixture::node(package_manager)
+                .member(NodeProjectFixture::new("ui"))
+                .member(NodeProjectFixture::new("app").dependency("ui", "*"))
+                .build()
+                .unwrap();
+            let root = temp_dir.path();
+
+            assert!(root.join(package_manager.lockfile()).exists());
+            assert!(
+                !root
+                    .join("packages/ui")
+                    .join(package_manager.lockfile())
+                    .exists()
+            );
+
+            let members = WorkspaceMembers::discover(root).unwrap().members;
+            let names: Vec<_> = members.iter().map(|member| member.name.as_str()).collect();
+            assert_eq!(names, ["app", "ui"], "{:?}", package_manager);
+        }
+    }
+}
@@ -5,7 +5,9 @@
 //!
 //! - Test fixtures for common scenarios
 //! - CLI command testing helpers
-//! - Custom assertion macros
+//! - Scripted prompts in a pseudo-terminal with `PtyTestRunner` (Unix only)
+//! - Custom assertion macros, including ANSI-aware ones for terminal output
+//! - Snapshot tests against golden files with `assert_snapshot!`
 //! - Mock builders for configuration and workspace objects
 //! - Integration test utilities
 //!
@@ -31,16 +33,24 @@
 //! }
 //! ```
 
+pub mod ansi;
 pub mod assertions;
 pub mod cli;
 pub mod fixtures;
 pub mod mocks;
+#[cfg(unix)]
+pub mod pty;
+pub mod snapshot;
 
 // Re-export commonly used items
 // pub use assertions::*; // Uncomment when macros are used
+pub use ansi::*;
 pub use cli::*;
 pub use fixtures::*;
 pub use mocks::*;
+#[cfg(unix)]
+pub use pty::*;
+pub use snapshot::*;
 
 // Re-export useful testing dependencies
 pub use tempfile;
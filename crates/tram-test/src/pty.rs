@@ -0,0 +1,430 @@
+//! Driving interactive programs in a pseudo-terminal.
+//!
+//! [`PtyTestRunner`] starts a program with a pseudo-terminal as its stdin,
+//! stdout, and stderr, so it behaves as it would for a person at a terminal:
+//! prompts are shown and answered from the keyboard. A test scripts the
+//! conversation with [`PtySession::expect`] and [`PtySession::send_line`],
+//! each bounded by a timeout, so a prompt that never appears fails the test
+//! instead of hanging it.
+//!
+//! CI variables such as `CI` are removed from the program's environment,
+//! since they turn prompts off. Only available on Unix.
+//!
+//! ```rust,ignore
+//! let mut session = PtyTestRunner::new(env!("CARGO_BIN_EXE_tram"))
+//!     .args(["new", "app"])
+//!     .spawn()?;
+//!
+//! session.expect("Project type")?;
+//! session.send_key(Key::Enter)?;
+//! session.expect("Description")?;
+//! session.send_line("A new app")?;
+//! assert!(session.wait()?.success());
+//! ```
+
+use crate::ansi::strip_ansi;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+use tram_core::CI_ENV_VARS;
+
+/// How long `expect` and `wait` wait unless set with
+/// [`PtyTestRunner::with_timeout`].
+pub const DEFAULT_PTY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A key that doesn't type text, as a terminal sends it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Enter,
+    Up,
+    Down,
+    Left,
+    Right,
+    Backspace,
+    Tab,
+    Escape,
+    CtrlC,
+}
+
+impl Key {
+    fn bytes(self) -> &'static [u8] {
+        match self {
+            Key::Enter => b"\r",
+            Key::Up => b"\x1b[A",
+            Key::Down => b"\x1b[B",
+            Key::Right => b"\x1b[C",
+            Key::Left => b"\x1b[D",
+            Key::Backspace => b"\x7f",
+            Key::Tab => b"\t",
+            Key::Escape => b"\x1b",
+            Key::CtrlC => b"\x03",
+        }
+    }
+}
+
+/// Helper for testing interactive CLI applications in a pseudo-terminal
+#[derive(Debug)]
+pub struct PtyTestRunner {
+    command: String,
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+    current_dir: Option<PathBuf>,
+    timeout: Duration,
+    size: (u16, u16),
+}
+
+impl PtyTestRunner {
+    /// Create a runner for the given command, in an 80x24 terminal
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            args: Vec::new(),
+            env: Vec::new(),
+            current_dir: None,
+            timeout: DEFAULT_PTY_TIMEOUT,
+            size: (80, 24),
+        }
+    }
+
+    /// Add an argument to the command
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Add multiple arguments to the command
+    pub fn args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set an environment variable. `TERM` defaults to `xterm-256color`.
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Set the current directory for the command
+    pub fn current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+    /// How long each `expect` and the final `wait` may take.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The terminal's size in columns and rows.
+    pub fn with_size(mut self, columns: u16, rows: u16) -> Self {
+        self.size = (columns, rows);
+        self
+    }
+
+    /// Start the command in a new pseudo-terminal.
+    pub fn spawn(self) -> io::Result<PtySession> {
+        let (master, slave) = open_pty(self.size)?;
+
+        let mut cmd = Command::new(&self.command);
+        cmd.args(&self.args);
+        for name in CI_ENV_VARS {
+            cmd.env_remove(name);
+        }
+        cmd.env("TERM", "xterm-256color");
+        for (key, value) in &self.env {
+            cmd.env(key, value);
+        }
+        if let Some(dir) = &self.current_dir {
+            cmd.current_dir(dir);
+        }
+
+        cmd.stdin(Stdio::from(slave.try_clone()?))
+            .stdout(Stdio::from(slave.try_clone()?))
+            .stderr(Stdio::from(slave));
+
+        // SAFETY: only async-signal-safe calls between fork and exec. The
+        // new session makes the pseudo-terminal the controlling terminal.
+        unsafe {
+            cmd.pre_exec(|| {
+                if libc::setsid() == -1 || libc::ioctl(0, libc::TIOCSCTTY, 0) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+
+        let child = cmd.spawn()?;
+        // Close our copies of the terminal, so reads end when the child exits
+        drop(cmd);
+
+        let mut reader = File::from(master.try_clone()?);
+        let (sender, output) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            // Reading fails with EIO on Linux once the child has exited
+            while let Ok(read @ 1..) = reader.read(&mut buf) {
+                if sender.send(buf[..read].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(PtySession {
+            child,
+            writer: File::from(master),
+            output,
+            raw: Vec::new(),
+            consumed: 0,
+            timeout: self.timeout,
+        })
+    }
+}
+
+/// A running command in a pseudo-terminal. The command is killed if the
+/// session is dropped before it exits.
+#[derive(Debug)]
+pub struct PtySession {
+    child: Child,
+    writer: File,
+    output: Receiver<Vec<u8>>,
+    raw: Vec<u8>,
+    /// How much of the text has been matched by `expect`
+    consumed: usize,
+    timeout: Duration,
+}
+
+impl PtySession {
+    /// Wait until `text` appears in the output after the previous match,
+    /// and return the output in between. Output is compared without ANSI
+    /// styling and cursor movement.
+    ///
+    /// Fails with [`io::ErrorKind::TimedOut`] if `text` doesn't appear in
+    /// time, or [`io::ErrorKind::UnexpectedEof`] if the command exits first;
+    /// the error includes the output since the previous match.
+    pub fn expect(&mut self, text: &str) -> io::Result<String> {
+        let deadline = Instant::now() + self.timeout;
+
+        loop {
+            let screen = self.output();
+            if let Some(found) = screen[self.consumed..].find(text) {
+                let before = screen[self.consumed..self.consumed + found].to_string();
+                self.consumed += found + text.len();
+                return Ok(before);
+            }
+
+            if let Err(kind) = self.read(deadline) {
+                return Err(io::Error::new(
+                    kind,
+                    format!(
+                        "Expected {:?} within {:?}; output since the last match:\n{}",
+                        text,
+                        self.timeout,
+                        &screen[self.consumed..]
+                    ),
+                ));
+            }
+        }
+    }
+
+    /// Type `text` without pressing Enter.
+    pub fn send(&mut self, text: &str) -> io::Result<()> {
+        self.writer.write_all(text.as_bytes())?;
+        self.writer.flush()
+    }
+
+    /// Type `text` and press Enter.
+    pub fn send_line(&mut self, text: &str) -> io::Result<()> {
+        self.send(text)?;
+        self.send_key(Key::Enter)
+    }
+
+    /// Press a key, e.g. [`Key::Down`] to move through a selection.
+    pub fn send_key(&mut self, key: Key) -> io::Result<()> {
+        self.writer.write_all(key.bytes())?;
+        self.writer.flush()
+    }
+
+    /// Everything the command has written so far, without ANSI styling and
+    /// cursor movement.
+    pub fn output(&self) -> String {
+        strip_ansi(&String::from_utf8_lossy(&self.raw))
+    }
+
+    /// Wait for the command to exit, killing it if it takes longer than the
+    /// timeout.
+    pub fn wait(mut self) -> io::Result<PtyOutput> {
+        let deadline = Instant::now() + self.timeout;
+
+        let status = loop {
+            if let Some(status) = self.child.try_wait()? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                self.child.kill()?;
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!(
+                        "Command didn't exit within {:?}; output:\n{}",
+                        self.timeout,
+                        self.output()
+                    ),
+                ));
+            }
+            let _ = self.read(Instant::now() + Duration::from_millis(50));
+        };
+
+        // Collect what was written just before exiting
+        while self
+            .read(Instant::now() + Duration::from_millis(100))
+            .is_ok()
+        {}
+
+        Ok(PtyOutput {
+            status,
+            output: self.output(),
+        })
+    }
+
+    /// Take in the next chunk of output, or fail at `deadline` or when the
+    /// command has closed the terminal.
+    fn read(&mut self, deadline: Instant) -> Result<(), io::ErrorKind> {
+        let wait = deadline.saturating_duration_since(Instant::now());
+        match self.output.recv_timeout(wait) {
+            Ok(chunk) => {
+                self.raw.extend(chunk);
+                Ok(())
+            }
+            Err(RecvTimeoutError::Timeout) => Err(io::ErrorKind::TimedOut),
+            Err(RecvTimeoutError::Disconnected) => Err(io::ErrorKind::UnexpectedEof),
+        }
+    }
+}
+
+impl Drop for PtySession {
+    fn drop(&mut self) {
+        if let Ok(None) = self.child.try_wait() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+/// How a command run in a pseudo-terminal exited, and what it wrote.
+#[derive(Debug)]
+pub struct PtyOutput {
+    status: ExitStatus,
+    output: String,
+}
+
+impl PtyOutput {
+    /// Check if the command succeeded
+    pub fn success(&self) -> bool {
+        self.status.success()
+    }
+
+    /// Get the exit code
+    pub fn exit_code(&self) -> Option<i32> {
+        self.status.code()
+    }
+
+    /// Everything the command wrote, stdout and stderr interleaved, without
+    /// ANSI styling and cursor movement
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+}
+
+/// Open a pseudo-terminal of the given size, returning its master and slave
+/// ends.
+fn open_pty((columns, rows): (u16, u16)) -> io::Result<(OwnedFd, OwnedFd)> {
+    let mut master = -1;
+    let mut slave = -1;
+    let mut size = libc::winsize {
+        ws_row: rows,
+        ws_col: columns,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+
+    // SAFETY: openpty only writes the two descriptors and reads the size
+    let result = unsafe {
+        libc::openpty(
+            &mut master,
+            &mut slave,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &raw mut size,
+        )
+    };
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: openpty succeeded, so both are open descriptors nothing else owns
+    let (master, slave) = unsafe { (OwnedFd::from_raw_fd(master), OwnedFd::from_raw_fd(slave)) };
+
+    // The child gets the slave end only
+    // SAFETY: setting a flag on a descriptor we own
+    if unsafe { libc::fcntl(master.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok((master, slave))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shell(script: &str) -> PtyTestRunner {
+        PtyTestRunner::new("sh").args(["-c", script])
+    }
+
+    #[test]
+    fn test_expect_and_send_line() {
+        let mut session = shell(r#"test -t 0 && printf "Name? "; read name; echo "Hello $name""#)
+            .spawn()
+            .unwrap();
+
+        session.expect("Name? ").unwrap();
+        session.send_line("Ada").unwrap();
+        session.expect("Hello Ada").unwrap();
+
+        let output = session.wait().unwrap();
+        assert!(output.success());
+        assert!(output.output().contains("Hello Ada"));
+    }
+
+    #[test]
+    fn test_expect_times_out() {
+        let mut session = shell("sleep 5")
+            .with_timeout(Duration::from_millis(200))
+            .spawn()
+            .unwrap();
+
+        let error = session.expect("never printed").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn test_expect_fails_when_command_exits() {
+        let mut session = shell("echo done").spawn().unwrap();
+
+        session.expect("done").unwrap();
+        let error = session.expect("more").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_ci_variables_are_removed() {
+        let session = shell(r#"echo "ci=${CI:-unset}""#).spawn().unwrap();
+
+        let output = session.wait().unwrap();
+        assert!(output.output().contains("ci=unset"), "{}", output.output());
+    }
+}
@@ -0,0 +1,330 @@
+//! Snapshot testing: compare output against golden files.
+//!
+//! [`assert_snapshot!`](crate::assert_snapshot) stores a test's expected
+//! output in `tests/snapshots/<name>.snap` of the calling crate. Output is
+//! normalized before it's compared, so styling, line endings, the system
+//! temp directory, the home directory, and timestamps don't make snapshots
+//! differ between runs and machines.
+//!
+//! Run the tests with `UPDATE_SNAPSHOTS=1` to write new snapshots and
+//! regenerate changed ones, then review them with `git diff`.
+
+use crate::ansi::strip_ansi;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Set to `1` to write snapshots instead of comparing against them.
+pub const UPDATE_SNAPSHOTS_ENV: &str = "UPDATE_SNAPSHOTS";
+
+static TIMESTAMP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:?\d{2})?\b")
+        .expect("timestamp pattern is valid")
+});
+
+/// The [`Snapshot`] `name` in the calling crate's `tests/snapshots`
+/// directory, for adding filters before asserting.
+///
+/// ```rust,ignore
+/// tram_test::snapshot!("init")
+///     .with_path(project.path(), "[PROJECT]")
+///     .assert(output.stdout());
+/// ```
+#[macro_export]
+macro_rules! snapshot {
+    ($name:expr) => {
+        $crate::snapshot::Snapshot::new(
+            ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots"),
+            $name,
+        )
+    };
+}
+
+/// Assert that output matches the snapshot `name` in the calling crate's
+/// `tests/snapshots` directory.
+///
+/// ```rust,ignore
+/// let output = runner.arg("status").run().await?;
+/// tram_test::assert_snapshot!("status", output.stdout());
+/// ```
+#[macro_export]
+macro_rules! assert_snapshot {
+    ($name:expr, $output:expr) => {
+        $crate::snapshot!($name).assert($output)
+    };
+}
+
+/// A golden file and the normalization applied to output compared with it.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    path: PathBuf,
+    filters: Vec<(Regex, String)>,
+}
+
+impl Snapshot {
+    /// The snapshot `name` in `dir`, stored as `<name>.snap`.
+    pub fn new(dir: impl Into<PathBuf>, name: &str) -> Self {
+        Self {
+            path: dir.into().join(format!("{}.snap", name)),
+            filters: Vec::new(),
+        }
+    }
+
+    /// Replace `path`, such as a test's project directory, with
+    /// `placeholder`, e.g. `[ROOT]`.
+    pub fn with_path(mut self, path: &Path, placeholder: &str) -> Self {
+        let mut paths = vec![path.to_path_buf()];
+        // Output may use the resolved path, e.g. /private/var on macOS
+        if let Ok(canonical) = path.canonicalize() {
+            paths.push(canonical);
+        }
+
+        for path in paths {
+            let pattern = regex::escape(&path.to_string_lossy());
+            self = self.with_filter(&pattern, placeholder);
+        }
+        self
+    }
+
+    /// Replace everything matching the regex `pattern` with `replacement`,
+    /// which may refer to capture groups as `$1`.
+    ///
+    /// # Panics
+    ///
+    /// If `pattern` isn't a valid regex.
+    pub fn with_filter(mut self, pattern: &str, replacement: &str) -> Self {
+        let regex = Regex::new(pattern)
+            .unwrap_or_else(|e| panic!("Invalid snapshot filter '{}': {}", pattern, e));
+        self.filters.push((regex, replacement.to_string()));
+        self
+    }
+
+    /// Where the snapshot is stored.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// `output` as it's stored: normalized, then this snapshot's filters
+    /// applied, in the order they were added.
+    pub fn normalize(&self, output: &str) -> String {
+        // Specific paths first, so they win over the temp directory
+        let filtered =
+            self.filters
+                .iter()
+                .fold(output.to_string(), |text, (regex, replacement)| {
+                    regex.replace_all(&text, replacement.as_str()).into_owned()
+                });
+        normalize(&filtered)
+    }
+
+    /// Compare `output` with the snapshot, or write it with
+    /// `UPDATE_SNAPSHOTS=1`.
+    ///
+    /// # Panics
+    ///
+    /// If the output differs from the snapshot or there's no snapshot yet.
+    pub fn assert(&self, output: impl AsRef<str>) {
+        let update = std::env::var(UPDATE_SNAPSHOTS_ENV).is_ok_and(|value| value == "1");
+        self.check(output.as_ref(), update);
+    }
+
+    fn check(&self, output: &str, update: bool) {
+        let actual = self.normalize(output);
+
+        if update {
+            if fs::read_to_string(&self.path).ok().as_deref() != Some(actual.as_str()) {
+                if let Some(parent) = self.path.parent() {
+                    fs::create_dir_all(parent)
+                        .unwrap_or_else(|e| panic!("Failed to create {}: {}", parent.display(), e));
+                }
+                fs::write(&self.path, &actual).unwrap_or_else(|e| {
+                    panic!("Failed to write snapshot {}: {}", self.path.display(), e)
+                });
+            }
+            return;
+        }
+
+        let Ok(expected) = fs::read_to_string(&self.path) else {
+            panic!(
+                "Missing snapshot {}\nRun with {}=1 to create it. Output:\n{}",
+                self.path.display(),
+                UPDATE_SNAPSHOTS_ENV,
+                actual
+            );
+        };
+
+        if expected.replace("\r\n", "\n") != actual {
+            panic!(
+                "Output doesn't match snapshot {}\nRun with {}=1 to update it.\n{}",
+                self.path.display(),
+                UPDATE_SNAPSHOTS_ENV,
+                diff(&expected, &actual)
+            );
+        }
+    }
+}
+
+/// Normalize output that varies between runs and machines: ANSI styling,
+/// `\r\n` line endings, paths in the system temp and home directories
+/// (`[TEMP]/...`, `[HOME]/...`), and RFC 3339 or `YYYY-MM-DD HH:MM:SS`
+/// timestamps (`[TIMESTAMP]`).
+pub fn normalize(output: &str) -> String {
+    let mut text = strip_ansi(output).replace("\r\n", "\n");
+
+    // Each temp directory has a random name, so it's replaced as a whole
+    for temp in dir_variants(std::env::temp_dir()) {
+        let pattern = format!(r"{}[/\\][^/\\\s]+", regex::escape(&temp));
+        if let Ok(regex) = Regex::new(&pattern) {
+            text = regex.replace_all(&text, "[TEMP]").into_owned();
+        }
+    }
+    if let Some(home) = std::env::var_os("HOME").filter(|home| home.len() > 1) {
+        for home in dir_variants(PathBuf::from(home)) {
+            if let Ok(regex) = Regex::new(&format!(r"{}\b", regex::escape(&home))) {
+                text = regex.replace_all(&text, "[HOME]").into_owned();
+            }
+        }
+    }
+
+    TIMESTAMP.replace_all(&text, "[TIMESTAMP]").into_owned()
+}
+
+/// A directory as given and resolved, without a trailing separator.
+fn dir_variants(dir: PathBuf) -> Vec<String> {
+    let mut variants = vec![dir.clone()];
+    if let Ok(canonical) = dir.canonicalize()
+        && canonical != dir
+    {
+        variants.push(canonical);
+    }
+
+    variants
+        .into_iter()
+        .map(|dir| {
+            dir.to_string_lossy()
+                .trim_end_matches(['/', '\\'])
+                .to_string()
+        })
+        .filter(|dir| !dir.is_empty())
+        .collect()
+}
+
+/// Lines of `expected` missing from `actual` as `-`, and the lines that
+/// replaced them as `+`.
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    // Longest common subsequence of lines
+    let mut lengths = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lengths[i][j] = if expected[i] == actual[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            lines.push(format!("  {}", expected[i]));
+            i += 1;
+            j += 1;
+        } else if i < expected.len()
+            && (j == actual.len() || lengths[i + 1][j] >= lengths[i][j + 1])
+        {
+            lines.push(format!("- {}", expected[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+ {}", actual[j]));
+            j += 1;
+        }
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_normalize() {
+        let temp = std::env::temp_dir().join(".tmpAbC123").join("project");
+        let output = format!(
+            "\x1b[32mCreated\x1b[0m {}\r\nat 2026-10-17T09:30:00.123Z\r\n",
+            temp.display()
+        );
+
+        assert_eq!(
+            normalize(&output),
+            "Created [TEMP]/project\nat [TIMESTAMP]\n"
+        );
+        assert_eq!(
+            normalize("logged 2026-10-17 09:30:00"),
+            "logged [TIMESTAMP]"
+        );
+        assert_eq!(normalize("version 1.2.3"), "version 1.2.3");
+
+        if let Some(home) = std::env::var_os("HOME").filter(|home| home.len() > 1) {
+            let home = home.to_string_lossy().trim_end_matches('/').to_string();
+            assert_eq!(normalize(&format!("{}/.config", home)), "[HOME]/.config");
+            assert_eq!(normalize(&format!("{}x", home)), format!("{}x", home));
+        }
+    }
+
+    #[test]
+    fn test_snapshot_filters() {
+        let snapshot = Snapshot::new("snapshots", "status")
+            .with_path(Path::new("/work/app"), "[ROOT]")
+            .with_filter(r"took \d+ms", "took [DURATION]");
+
+        assert_eq!(snapshot.path(), Path::new("snapshots/status.snap"));
+        assert_eq!(
+            snapshot.normalize("/work/app/src took 42ms"),
+            "[ROOT]/src took [DURATION]"
+        );
+    }
+
+    #[test]
+    fn test_assert_matches_stored_snapshot() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("greeting.snap"), "Hello\r\nworld\n").unwrap();
+
+        Snapshot::new(dir.path(), "greeting").check("\x1b[1mHello\x1b[0m\nworld\n", false);
+    }
+
+    #[test]
+    fn test_assert_reports_differences() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("list.snap"), "a\nb\nc\n").unwrap();
+
+        let panic = std::panic::catch_unwind(|| {
+            Snapshot::new(dir.path(), "list").check("a\nB\nc\n", false);
+        })
+        .unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(message.contains("  a\n- b\n+ B\n  c"), "{}", message);
+
+        let missing = std::panic::catch_unwind(|| {
+            Snapshot::new(dir.path(), "missing").check("output", false);
+        });
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_update_writes_snapshot() {
+        let dir = TempDir::new().unwrap();
+        let snapshot = Snapshot::new(dir.path().join("nested"), "created");
+
+        snapshot.check("fresh\n", true);
+        assert_eq!(fs::read_to_string(snapshot.path()).unwrap(), "fresh\n");
+        snapshot.check("fresh\n", false);
+    }
+}
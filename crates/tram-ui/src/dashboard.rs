@@ -0,0 +1,574 @@
+//! A full-screen dashboard for long-running modes such as watch and the
+//! daemon.
+//!
+//! A [`Dashboard`] collects what the mode is doing (its config, the paths it
+//! watches, recent events, and a history of task runs) from any thread.
+//! Collecting is always available; drawing it needs the `dashboard` feature,
+//! which brings in ratatui. [`Dashboard::run`] takes over the terminal until
+//! the user presses `q` (or [`Dashboard::stop`] is called), and the terminal
+//! is put back the way it was when it returns, errors, or the process
+//! panics.
+
+use crate::log_view::TaskStatus;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Whether this build can draw dashboards.
+pub const DASHBOARD_AVAILABLE: bool = cfg!(feature = "dashboard");
+
+/// Recent events kept on the dashboard.
+pub const DASHBOARD_EVENTS: usize = 200;
+
+/// Task runs kept in the dashboard's history.
+pub const DASHBOARD_HISTORY: usize = 100;
+
+/// An event shown on the dashboard, stamped with the time since it started.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DashboardEvent {
+    pub at: Duration,
+    pub message: String,
+}
+
+/// One run of a task in the dashboard's history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskRun {
+    pub task: String,
+    /// When the run started, since the dashboard did
+    pub at: Duration,
+    pub status: TaskStatus,
+}
+
+#[derive(Debug)]
+struct DashboardState {
+    config: Vec<(String, String)>,
+    watched: Vec<String>,
+    events: VecDeque<DashboardEvent>,
+    history: VecDeque<TaskRun>,
+    stopped: bool,
+}
+
+/// Live state for a full-screen dashboard. Clones share the same dashboard.
+#[derive(Debug, Clone)]
+pub struct Dashboard {
+    title: String,
+    started: Instant,
+    state: Arc<Mutex<DashboardState>>,
+}
+
+impl Dashboard {
+    /// An empty dashboard headed by `title`, e.g. `tram watch`.
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            started: Instant::now(),
+            state: Arc::new(Mutex::new(DashboardState {
+                config: Vec::new(),
+                watched: Vec::new(),
+                events: VecDeque::new(),
+                history: VecDeque::new(),
+                stopped: false,
+            })),
+        }
+    }
+
+    /// The title the dashboard is headed by.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Replace the config panel with `(setting, value)` rows.
+    pub fn set_config<I, K, V>(&self, rows: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let rows = rows
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect();
+        self.update(|state| state.config = rows);
+    }
+
+    /// Add an entry to the watched paths, e.g. a directory or a glob.
+    pub fn add_watched(&self, path: impl Into<String>) {
+        let path = path.into();
+        self.update(|state| state.watched.push(path));
+    }
+
+    /// Add an event, dropping the oldest past [`DASHBOARD_EVENTS`].
+    pub fn event(&self, message: impl Into<String>) {
+        let event = DashboardEvent {
+            at: self.started.elapsed(),
+            message: message.into(),
+        };
+        self.update(|state| {
+            if state.events.len() == DASHBOARD_EVENTS {
+                state.events.pop_front();
+            }
+            state.events.push_back(event);
+        });
+    }
+
+    /// Record a task's status. [`TaskStatus::Running`] starts a new run;
+    /// any other status finishes the task's latest running one, or is
+    /// recorded as a run of its own.
+    pub fn record_run(&self, task: &str, status: TaskStatus) {
+        let at = self.started.elapsed();
+        self.update(|state| {
+            if status != TaskStatus::Running
+                && let Some(run) = state
+                    .history
+                    .iter_mut()
+                    .rev()
+                    .find(|run| run.task == task && run.status == TaskStatus::Running)
+            {
+                run.status = status;
+                return;
+            }
+
+            if state.history.len() == DASHBOARD_HISTORY {
+                state.history.pop_front();
+            }
+            state.history.push_back(TaskRun {
+                task: task.to_string(),
+                at,
+                status,
+            });
+        });
+    }
+
+    /// Recent events, oldest first.
+    pub fn events(&self) -> Vec<DashboardEvent> {
+        self.read(|state| state.events.iter().cloned().collect())
+    }
+
+    /// Task runs, oldest first.
+    pub fn history(&self) -> Vec<TaskRun> {
+        self.read(|state| state.history.iter().cloned().collect())
+    }
+
+    /// Ask [`Dashboard::run`] to return, e.g. on Ctrl+C from another thread.
+    pub fn stop(&self) {
+        self.update(|state| state.stopped = true);
+    }
+
+    /// Whether the dashboard has been stopped.
+    pub fn is_stopped(&self) -> bool {
+        self.read(|state| state.stopped)
+    }
+
+    fn update(&self, f: impl FnOnce(&mut DashboardState)) {
+        if let Ok(mut state) = self.state.lock() {
+            f(&mut state);
+        }
+    }
+
+    fn read<T: Default>(&self, f: impl FnOnce(&DashboardState) -> T) -> T {
+        self.state.lock().map(|state| f(&state)).unwrap_or_default()
+    }
+}
+
+#[cfg(not(feature = "dashboard"))]
+impl Dashboard {
+    /// Draw the dashboard until the user quits. This build has no
+    /// dashboard support, so it fails straight away; check
+    /// [`DASHBOARD_AVAILABLE`] first.
+    pub fn run(&self) -> std::io::Result<()> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "built without the dashboard feature",
+        ))
+    }
+}
+
+#[cfg(feature = "dashboard")]
+mod draw {
+    use super::{Dashboard, DashboardState};
+    use crate::format::format_duration;
+    use crate::log_view::TaskStatus;
+    use crate::theme::{Style, theme};
+    use crossterm::cursor::{Hide, Show};
+    use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+    use crossterm::execute;
+    use crossterm::terminal::{
+        EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+    };
+    use ratatui::Frame;
+    use ratatui::backend::CrosstermBackend;
+    use ratatui::layout::{Constraint, Layout, Rect};
+    use ratatui::style::{Color, Modifier};
+    use ratatui::text::{Line, Span};
+    use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Row, Table};
+    use std::io;
+    use std::sync::Once;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+    use tram_core::{Stream, color_enabled};
+
+    /// How often the dashboard redraws and checks for keys.
+    const TICK: Duration = Duration::from_millis(250);
+
+    /// Whether the terminal is in raw mode on the alternate screen.
+    static TERMINAL_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+    static PANIC_HOOK: Once = Once::new();
+
+    /// Holds the terminal in raw mode on the alternate screen, and puts it
+    /// back when dropped.
+    struct TerminalGuard;
+
+    impl TerminalGuard {
+        fn enter() -> io::Result<Self> {
+            install_panic_hook();
+            enable_raw_mode()?;
+            TERMINAL_ACTIVE.store(true, Ordering::SeqCst);
+            // Restores raw mode if entering the alternate screen fails
+            let guard = TerminalGuard;
+            execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+            Ok(guard)
+        }
+    }
+
+    impl Drop for TerminalGuard {
+        fn drop(&mut self) {
+            restore_terminal();
+        }
+    }
+
+    fn restore_terminal() {
+        if TERMINAL_ACTIVE.swap(false, Ordering::SeqCst) {
+            let _ = disable_raw_mode();
+            let _ = execute!(io::stdout(), LeaveAlternateScreen, Show);
+        }
+    }
+
+    /// Restore the terminal before the panic message (and crash report) is
+    /// printed, so they're readable and the shell still works afterwards.
+    fn install_panic_hook() {
+        PANIC_HOOK.call_once(|| {
+            let previous = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                restore_terminal();
+                previous(info);
+            }));
+        });
+    }
+
+    impl Dashboard {
+        /// Draw the dashboard until the user presses `q`, Esc, or Ctrl+C, or
+        /// [`Dashboard::stop`] is called. Blocks, so run it on its own
+        /// thread; the terminal is restored before it returns.
+        pub fn run(&self) -> io::Result<()> {
+            let _guard = TerminalGuard::enter()?;
+            let mut terminal = ratatui::Terminal::new(CrosstermBackend::new(io::stdout()))?;
+            terminal.clear()?;
+
+            while !self.is_stopped() {
+                terminal.draw(|frame| self.draw(frame))?;
+
+                if event::poll(TICK)?
+                    && let Event::Key(key) = event::read()?
+                    && key.kind == KeyEventKind::Press
+                    && quits(key.code, key.modifiers)
+                {
+                    self.stop();
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Draw the dashboard into `frame`.
+        pub fn draw(&self, frame: &mut Frame) {
+            let elapsed = self.started.elapsed();
+            let Ok(state) = self.state.lock() else {
+                return;
+            };
+            let styles = Styles::new();
+
+            let [header, body] =
+                Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(frame.area());
+            let [left, right] =
+                Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                    .areas(body);
+            let [config, watched] =
+                Layout::vertical([Constraint::Percentage(60), Constraint::Percentage(40)])
+                    .areas(left);
+            let [events, history] =
+                Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .areas(right);
+
+            frame.render_widget(
+                Paragraph::new(Line::from(vec![
+                    Span::styled(self.title.clone(), styles.heading),
+                    Span::raw(format!("  up {}", format_duration(elapsed))),
+                    Span::styled("  q quit", styles.muted),
+                ])),
+                header,
+            );
+            draw_config(frame, config, &state, &styles);
+            draw_watched(frame, watched, &state, &styles);
+            draw_events(frame, events, &state, &styles);
+            draw_history(frame, history, &state, &styles);
+        }
+    }
+
+    fn quits(code: KeyCode, modifiers: KeyModifiers) -> bool {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => true,
+            KeyCode::Char('c') => modifiers.contains(KeyModifiers::CONTROL),
+            _ => false,
+        }
+    }
+
+    fn panel<'a>(title: &'a str, styles: &Styles) -> Block<'a> {
+        Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled(title, styles.heading))
+    }
+
+    fn draw_config(frame: &mut Frame, area: Rect, state: &DashboardState, styles: &Styles) {
+        let width = state
+            .config
+            .iter()
+            .map(|(key, _)| key.chars().count())
+            .max()
+            .unwrap_or_default();
+        let rows = state.config.iter().map(|(key, value)| {
+            Row::new([
+                Span::styled(key.clone(), styles.muted),
+                Span::raw(value.clone()),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [Constraint::Length(width as u16), Constraint::Fill(1)],
+        )
+        .block(panel("Config", styles));
+        frame.render_widget(table, area);
+    }
+
+    fn draw_watched(frame: &mut Frame, area: Rect, state: &DashboardState, styles: &Styles) {
+        let items = state
+            .watched
+            .iter()
+            .map(|path| ListItem::new(path.as_str()));
+        frame.render_widget(List::new(items).block(panel("Watched paths", styles)), area);
+    }
+
+    /// Newest events at the bottom, as in a log.
+    fn draw_events(frame: &mut Frame, area: Rect, state: &DashboardState, styles: &Styles) {
+        let rows = usize::from(area.height.saturating_sub(2));
+        let skip = state.events.len().saturating_sub(rows);
+        let items = state.events.iter().skip(skip).map(|event| {
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:>6} ", format_duration(event.at)), styles.muted),
+                Span::raw(event.message.as_str()),
+            ]))
+        });
+        frame.render_widget(List::new(items).block(panel("Recent events", styles)), area);
+    }
+
+    /// Newest runs first.
+    fn draw_history(frame: &mut Frame, area: Rect, state: &DashboardState, styles: &Styles) {
+        let rows = state.history.iter().rev().map(|run| {
+            let style = match run.status {
+                TaskStatus::Passed(_) => styles.success,
+                TaskStatus::Failed(_) => styles.error,
+                TaskStatus::Running => styles.info,
+                TaskStatus::Idle => styles.muted,
+            };
+            Row::new([
+                Span::styled(format_duration(run.at), styles.muted),
+                Span::raw(run.task.clone()),
+                Span::styled(describe_status(run.status), style),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(7),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(Row::new(["Time", "Task", "Result"]).style(styles.heading))
+        .block(panel("Task history", styles));
+        frame.render_widget(table, area);
+    }
+
+    /// A task status as shown in the history table.
+    fn describe_status(status: TaskStatus) -> String {
+        match status {
+            TaskStatus::Idle => "idle".to_string(),
+            TaskStatus::Running => "running".to_string(),
+            TaskStatus::Passed(duration) => format!("passed in {:.1?}", duration),
+            TaskStatus::Failed(code) => format!("failed ({})", code),
+        }
+    }
+
+    /// The theme's styles in ratatui terms, or plain ones without color.
+    struct Styles {
+        success: ratatui::style::Style,
+        error: ratatui::style::Style,
+        heading: ratatui::style::Style,
+        info: ratatui::style::Style,
+        muted: ratatui::style::Style,
+    }
+
+    impl Styles {
+        fn new() -> Self {
+            let theme = theme();
+            let convert = |style: Style| {
+                if color_enabled(Stream::Stdout) {
+                    to_ratatui(style)
+                } else {
+                    ratatui::style::Style::default()
+                }
+            };
+
+            Self {
+                success: convert(theme.success),
+                error: convert(theme.error),
+                heading: convert(theme.heading),
+                info: convert(theme.info),
+                muted: convert(theme.muted),
+            }
+        }
+    }
+
+    fn to_ratatui(style: Style) -> ratatui::style::Style {
+        use crate::theme::Color as ThemeColor;
+
+        let mut converted = ratatui::style::Style::default();
+        if let Some(color) = style.color() {
+            converted = converted.fg(match color {
+                ThemeColor::Black => Color::Black,
+                ThemeColor::Red => Color::Red,
+                ThemeColor::Green => Color::Green,
+                ThemeColor::Yellow => Color::Yellow,
+                ThemeColor::Blue => Color::Blue,
+                ThemeColor::Magenta => Color::Magenta,
+                ThemeColor::Cyan => Color::Cyan,
+                ThemeColor::White => Color::White,
+                ThemeColor::Gray => Color::DarkGray,
+            });
+        }
+
+        for (on, modifier) in [
+            (style.is_bold(), Modifier::BOLD),
+            (style.is_italic(), Modifier::ITALIC),
+            (style.is_underline(), Modifier::UNDERLINED),
+        ] {
+            if on {
+                converted = converted.add_modifier(modifier);
+            }
+        }
+
+        converted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runs_are_finished_in_place() {
+        let dashboard = Dashboard::new("tram watch");
+        dashboard.record_run("lint", TaskStatus::Running);
+        dashboard.record_run("test", TaskStatus::Running);
+        dashboard.record_run("lint", TaskStatus::Failed(2));
+        dashboard.record_run("test", TaskStatus::Passed(Duration::from_secs(1)));
+        dashboard.record_run("lint", TaskStatus::Failed(1));
+
+        let history: Vec<(String, TaskStatus)> = dashboard
+            .history()
+            .into_iter()
+            .map(|run| (run.task, run.status))
+            .collect();
+        assert_eq!(
+            history,
+            [
+                ("lint".to_string(), TaskStatus::Failed(2)),
+                (
+                    "test".to_string(),
+                    TaskStatus::Passed(Duration::from_secs(1))
+                ),
+                ("lint".to_string(), TaskStatus::Failed(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_events_and_history_are_capped() {
+        let dashboard = Dashboard::new("tram daemon");
+        for i in 0..DASHBOARD_EVENTS + 5 {
+            dashboard.event(format!("event {}", i));
+        }
+        for _ in 0..DASHBOARD_HISTORY + 5 {
+            dashboard.record_run("build", TaskStatus::Running);
+        }
+
+        let events = dashboard.events();
+        assert_eq!(events.len(), DASHBOARD_EVENTS);
+        assert_eq!(events[0].message, "event 5");
+        assert_eq!(dashboard.history().len(), DASHBOARD_HISTORY);
+    }
+
+    #[test]
+    fn test_clones_share_state() {
+        let dashboard = Dashboard::new("tram watch");
+        let clone = dashboard.clone();
+        clone.event("changed");
+        clone.stop();
+
+        assert_eq!(dashboard.events()[0].message, "changed");
+        assert!(dashboard.is_stopped());
+    }
+
+    #[cfg(not(feature = "dashboard"))]
+    #[test]
+    fn test_run_fails_without_the_feature() {
+        let error = Dashboard::new("tram watch").run().unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::Unsupported);
+    }
+
+    #[cfg(feature = "dashboard")]
+    #[test]
+    fn test_draws_every_panel() {
+        use ratatui::{Terminal, backend::TestBackend};
+
+        let dashboard = Dashboard::new("tram watch");
+        dashboard.set_config([("Log level", "info")]);
+        dashboard.add_watched("/work/project");
+        dashboard.event("2 files changed");
+        dashboard.record_run("lint", TaskStatus::Failed(1));
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|frame| dashboard.draw(frame)).unwrap();
+
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        for text in [
+            "tram watch",
+            "Config",
+            "Log level",
+            "Watched paths",
+            "/work/project",
+            "Recent events",
+            "2 files changed",
+            "Task history",
+            "failed (1)",
+        ] {
+            assert!(screen.contains(text), "missing {:?}", text);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Where an indicator's line goes: straight to a terminal, or to its slot
+//! in a [`MultiProgress`].
+
+use crate::multi::MultiProgress;
+use crate::term::Term;
+
+#[derive(Debug, Clone)]
+pub(crate) enum DrawTarget {
+    Term(Term),
+    Multi { multi: MultiProgress, index: usize },
+}
+
+impl DrawTarget {
+    pub fn term(&self) -> &Term {
+        match self {
+            DrawTarget::Term(term) => term,
+            DrawTarget::Multi { multi, .. } => multi.term(),
+        }
+    }
+
+    /// Redraw the line in place. Only on interactive terminals.
+    pub fn draw(&self, line: &str) {
+        if !self.term().is_interactive() {
+            return;
+        }
+
+        match self {
+            DrawTarget::Term(term) => term.write(&format!("\r\x1b[K{}", line)),
+            DrawTarget::Multi { multi, index } => multi.set_line(*index, line),
+        }
+    }
+
+    /// Print a line that stays, for terminals that can't redraw.
+    pub fn println(&self, line: &str) {
+        match self {
+            DrawTarget::Term(term) => term.write(&format!("{}\n", line)),
+            DrawTarget::Multi { multi, .. } => multi.println(line),
+        }
+    }
+
+    /// Leave `line` as the indicator's final line, or clear it.
+    pub fn finish(&self, line: Option<&str>) {
+        let term = self.term();
+
+        if !term.is_interactive() {
+            if let Some(line) = line {
+                self.println(line);
+            }
+            return;
+        }
+
+        match (self, line) {
+            (DrawTarget::Term(term), Some(line)) => term.write(&format!("\r\x1b[K{}\n", line)),
+            (DrawTarget::Term(term), None) => term.write("\r\x1b[K"),
+            (DrawTarget::Multi { multi, index }, line) => {
+                multi.set_line(*index, line.unwrap_or_default())
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Human-readable durations, rates, and estimates.
+
+use std::time::Duration;
+
+/// A short duration such as `45s`, `2m05s`, or `1h02m`.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// A per-second rate such as `12.5/s`.
+pub fn format_rate(per_second: f64) -> String {
+    if per_second >= 100.0 {
+        format!("{:.0}/s", per_second)
+    } else {
+        format!("{:.1}/s", per_second)
+    }
+}
+
+/// Items finished per second so far.
+pub fn rate(position: u64, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+
+    if secs > 0.0 {
+        position as f64 / secs
+    } else {
+        0.0
+    }
+}
+
+/// Time left at the rate so far, or `None` before there's a rate to go by.
+pub fn eta(position: u64, total: u64, elapsed: Duration) -> Option<Duration> {
+    if position >= total {
+        return Some(Duration::ZERO);
+    }
+
+    let rate = rate(position, elapsed);
+    (rate > 0.0).then(|| Duration::from_secs_f64((total - position) as f64 / rate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(400)), "0s");
+        assert_eq!(format_duration(Duration::from_secs(45)), "45s");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2m05s");
+        assert_eq!(format_duration(Duration::from_secs(3720)), "1h02m");
+    }
+
+    #[test]
+    fn test_rate_and_eta() {
+        let elapsed = Duration::from_secs(4);
+
+        assert_eq!(rate(10, elapsed), 2.5);
+        assert_eq!(format_rate(rate(10, elapsed)), "2.5/s");
+        assert_eq!(format_rate(1234.0), "1234/s");
+        assert_eq!(eta(10, 20, elapsed), Some(Duration::from_secs(4)));
+        assert_eq!(eta(20, 20, elapsed), Some(Duration::ZERO));
+        assert_eq!(eta(0, 20, elapsed), None);
+        assert_eq!(eta(5, 20, Duration::ZERO), None);
+    }
+}
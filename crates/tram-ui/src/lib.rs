@@ -0,0 +1,45 @@
+//! Terminal UI components for Tram CLI applications.
+//!
+//! - [`ProgressBar`] for work with a known size, with rates and ETAs
+//! - [`Spinner`] for work without one
+//! - [`MultiProgress`] to draw several of them at once, for concurrent work
+//! - [`Template`] to choose what a line shows, e.g. `"{bar} {pos}/{total}"`
+//! - [`Table`] for tabular output, fitted to the terminal or exported as
+//!   CSV/TSV
+//! - [`Tree`] for hierarchies such as directories and task dependencies
+//! - [`LogView`] for a full-screen pane of task output with a status line,
+//!   as in `watch --ui`
+//! - [`Dashboard`] for a full-screen overview of a long-running mode: its
+//!   config, watched paths, recent events, and task history (drawing it
+//!   needs the `dashboard` feature)
+//! - [`Theme`] to style all of them by role (success, warning, heading, ...)
+//!
+//! Everything draws to a [`Term`], which decides whether lines are redrawn
+//! in place (an interactive terminal) or printed once (pipes, CI logs, and
+//! `TERM=dumb`), and whether they're colored (following `tram-core`'s color
+//! detection, so `NO_COLOR` and `--color never` are honored).
+
+pub mod dashboard;
+mod draw;
+pub mod format;
+pub mod log_view;
+pub mod multi;
+pub mod progress_bar;
+pub mod spinner;
+pub mod table;
+pub mod template;
+pub mod term;
+pub mod theme;
+pub mod tree;
+
+pub use dashboard::*;
+pub use format::*;
+pub use log_view::*;
+pub use multi::*;
+pub use progress_bar::*;
+pub use spinner::*;
+pub use table::*;
+pub use template::*;
+pub use term::*;
+pub use theme::*;
+pub use tree::*;
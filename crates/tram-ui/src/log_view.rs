@@ -0,0 +1,484 @@
+//! A full-screen pane of task output with a status line, for watch modes.
+//!
+//! On an interactive terminal the view takes over the alternate screen, so
+//! the shell's scrollback is left alone: the most recent lines fill the
+//! pane (optionally only one task's, and scrolled back on request), a status
+//! line shows each task's last result, and a help line lists the key
+//! bindings. Elsewhere it degrades to plain logs: each line is printed as
+//! `[task] line` as it arrives, and the screen-only parts are skipped.
+
+use crate::term::Term;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use unicode_width::UnicodeWidthChar;
+
+/// Lines of output kept for scrolling back.
+pub const LOG_VIEW_CAPACITY: usize = 2_000;
+
+/// Rows used below the pane: the status line and the help line.
+const FOOTER_ROWS: usize = 2;
+
+/// The last result of a task shown in a [`LogView`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TaskStatus {
+    /// Hasn't run yet
+    #[default]
+    Idle,
+    Running,
+    Passed(Duration),
+    /// Failed with this exit code
+    Failed(i32),
+}
+
+#[derive(Debug)]
+struct LogViewState {
+    tasks: Vec<(String, TaskStatus)>,
+    /// Output lines with the index of the task that wrote them, or `None`
+    /// for messages that belong to no task
+    lines: VecDeque<(Option<usize>, String)>,
+    filter: Option<usize>,
+    /// Lines scrolled back from the newest; 0 follows new output
+    scroll: usize,
+    help: String,
+    size: (usize, usize),
+    /// Whether the alternate screen is in use
+    active: bool,
+}
+
+/// A live log viewer. Clones share the same view.
+#[derive(Debug, Clone)]
+pub struct LogView {
+    term: Term,
+    state: Arc<Mutex<LogViewState>>,
+}
+
+impl LogView {
+    /// A view of `tasks`, drawn on standard output.
+    pub fn new<I, S>(tasks: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self::with_term(Term::stdout(), tasks)
+    }
+
+    /// A view of `tasks`, drawn on the given terminal.
+    pub fn with_term<I, S>(term: Term, tasks: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let size = (
+            tram_core::terminal_height().unwrap_or(24),
+            tram_core::terminal_width().unwrap_or(80),
+        );
+
+        Self {
+            term,
+            state: Arc::new(Mutex::new(LogViewState {
+                tasks: tasks
+                    .into_iter()
+                    .map(|name| (name.into(), TaskStatus::Idle))
+                    .collect(),
+                lines: VecDeque::new(),
+                filter: None,
+                scroll: 0,
+                help: String::new(),
+                size,
+                active: false,
+            })),
+        }
+    }
+
+    /// Draw in `rows` by `columns` instead of the terminal's size.
+    pub fn with_size(self, rows: usize, columns: usize) -> Self {
+        self.update(|state| state.size = (rows, columns));
+        self
+    }
+
+    /// Text for the bottom line, e.g. the key bindings.
+    pub fn with_help(self, help: impl Into<String>) -> Self {
+        let help = help.into();
+        self.update(|state| state.help = help);
+        self
+    }
+
+    /// Whether the view takes over the screen, rather than printing logs.
+    pub fn is_interactive(&self) -> bool {
+        self.term.is_interactive()
+    }
+
+    /// Switch to the alternate screen and draw the view. Does nothing when
+    /// the terminal isn't interactive.
+    pub fn start(&self) {
+        if !self.term.is_interactive() {
+            return;
+        }
+
+        self.redraw(|state| {
+            state.active = true;
+            "\x1b[?1049h\x1b[?25l".to_string()
+        });
+    }
+
+    /// Leave the alternate screen, restoring what was there before.
+    pub fn finish(&self) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        if state.active {
+            state.active = false;
+            self.term.write("\x1b[?25h\x1b[?1049l");
+        }
+    }
+
+    /// Names of the tasks, in status line order.
+    pub fn tasks(&self) -> Vec<String> {
+        self.read(|state| state.tasks.iter().map(|(name, _)| name.clone()).collect())
+    }
+
+    /// Add a line of output from `task`.
+    pub fn push_line(&self, task: &str, line: &str) {
+        if !self.term.is_interactive() {
+            self.term.write(&format!("[{}] {}\n", task, line));
+            return;
+        }
+
+        self.redraw(|state| {
+            let index = state.task_index(task);
+            state.push((Some(index), line.to_string()));
+            String::new()
+        });
+    }
+
+    /// Add a line that belongs to no task, shown whatever the filter.
+    pub fn message(&self, line: &str) {
+        if !self.term.is_interactive() {
+            self.term.write(&format!("{}\n", line));
+            return;
+        }
+
+        self.redraw(|state| {
+            state.push((None, line.to_string()));
+            String::new()
+        });
+    }
+
+    /// Record a task's latest result on the status line.
+    pub fn set_status(&self, task: &str, status: TaskStatus) {
+        self.redraw(|state| {
+            let index = state.task_index(task);
+            state.tasks[index].1 = status;
+            String::new()
+        });
+    }
+
+    /// The last result recorded for `task`.
+    pub fn status(&self, task: &str) -> TaskStatus {
+        self.read(|state| {
+            state
+                .tasks
+                .iter()
+                .find(|(name, _)| name == task)
+                .map(|(_, status)| *status)
+                .unwrap_or_default()
+        })
+    }
+
+    /// Only show lines from `task`, or from every task with `None`.
+    pub fn set_filter(&self, task: Option<&str>) {
+        self.redraw(|state| {
+            state.filter = task.and_then(|task| state.tasks.iter().position(|(n, _)| n == task));
+            state.scroll = 0;
+            String::new()
+        });
+    }
+
+    /// The task lines are filtered to, if any.
+    pub fn filter(&self) -> Option<String> {
+        self.read(|state| state.filter.map(|index| state.tasks[index].0.clone()))
+    }
+
+    /// Scroll back towards older lines, or forward with a negative count.
+    /// Scrolling all the way forward follows new output again.
+    pub fn scroll(&self, lines: isize) {
+        self.redraw(|state| {
+            let visible = state.visible().count();
+            let max = visible.saturating_sub(state.pane_rows());
+            state.scroll = state.scroll.saturating_add_signed(lines).min(max);
+            String::new()
+        });
+    }
+
+    /// Follow new output again after scrolling back.
+    pub fn follow(&self) {
+        self.redraw(|state| {
+            state.scroll = 0;
+            String::new()
+        });
+    }
+
+    /// Redraw the whole view, e.g. after the terminal was resized.
+    pub fn resize(&self, rows: usize, columns: usize) {
+        self.redraw(|state| {
+            state.size = (rows, columns);
+            "\x1b[2J".to_string()
+        });
+    }
+
+    /// The rows the view currently shows, without escape sequences other
+    /// than styling.
+    pub fn frame(&self) -> Vec<String> {
+        self.read(|state| state.frame(&self.term))
+    }
+
+    /// Apply `change`, then redraw if the view is on screen. `change`
+    /// returns escape sequences to write before the frame.
+    fn redraw(&self, change: impl FnOnce(&mut LogViewState) -> String) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let mut out = change(&mut state);
+        if !state.active {
+            return;
+        }
+
+        out.push_str("\x1b[H");
+        let rows = state.frame(&self.term);
+        for (i, row) in rows.iter().enumerate() {
+            out.push_str(row);
+            out.push_str("\x1b[K");
+            if i + 1 < rows.len() {
+                out.push_str("\r\n");
+            }
+        }
+        self.term.write(&out);
+    }
+
+    fn update(&self, f: impl FnOnce(&mut LogViewState)) {
+        if let Ok(mut state) = self.state.lock() {
+            f(&mut state);
+        }
+    }
+
+    fn read<T: Default>(&self, f: impl FnOnce(&LogViewState) -> T) -> T {
+        self.state.lock().map(|state| f(&state)).unwrap_or_default()
+    }
+}
+
+impl LogViewState {
+    /// Index of `task`, adding it to the status line if it's new.
+    fn task_index(&mut self, task: &str) -> usize {
+        match self.tasks.iter().position(|(name, _)| name == task) {
+            Some(index) => index,
+            None => {
+                self.tasks.push((task.to_string(), TaskStatus::Idle));
+                self.tasks.len() - 1
+            }
+        }
+    }
+
+    fn push(&mut self, line: (Option<usize>, String)) {
+        if self.lines.len() == LOG_VIEW_CAPACITY {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+
+        // Keep a scrolled-back view still while output arrives
+        if self.scroll > 0
+            && self
+                .filter
+                .is_none_or(|filter| line_matches(&self.lines, filter))
+        {
+            self.scroll += 1;
+        }
+    }
+
+    fn pane_rows(&self) -> usize {
+        self.size.0.saturating_sub(FOOTER_ROWS).max(1)
+    }
+
+    fn visible(&self) -> impl DoubleEndedIterator<Item = &(Option<usize>, String)> {
+        self.lines
+            .iter()
+            .filter(move |(task, _)| match self.filter {
+                Some(filter) => task.is_none_or(|task| task == filter),
+                None => true,
+            })
+    }
+
+    fn frame(&self, term: &Term) -> Vec<String> {
+        let theme = term.theme();
+        let width = self.size.1;
+        let rows = self.pane_rows();
+
+        let mut pane: Vec<String> = self
+            .visible()
+            .rev()
+            .skip(self.scroll)
+            .take(rows)
+            .map(|(task, line)| match task {
+                Some(task) => {
+                    let prefix = format!("[{}] ", self.tasks[*task].0);
+                    let line = truncate(&format!("{}{}", prefix, line), width);
+                    match line.strip_prefix(&prefix) {
+                        Some(rest) => format!("{}{}", term.style(&prefix, theme.muted), rest),
+                        None => line,
+                    }
+                }
+                None => term.style(&truncate(line, width), theme.heading),
+            })
+            .collect();
+        pane.reverse();
+        pane.resize(rows, String::new());
+
+        let status = self
+            .tasks
+            .iter()
+            .enumerate()
+            .map(|(index, (name, status))| {
+                let (mark, style) = match status {
+                    TaskStatus::Idle => ("–".to_string(), theme.muted),
+                    TaskStatus::Running => ("…".to_string(), theme.info),
+                    TaskStatus::Passed(duration) => (format!("✓ {:.1?}", duration), theme.success),
+                    TaskStatus::Failed(code) => (format!("✗ {}", code), theme.error),
+                };
+                let label = format!("{} {}", index + 1, name);
+                let label = if self.filter == Some(index) {
+                    term.style(&format!("[{}]", label), theme.heading)
+                } else {
+                    label
+                };
+
+                format!("{} {}", label, term.style(&mark, style))
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+
+        pane.push(status);
+        pane.push(term.style(&truncate(&self.help, width), theme.muted));
+        pane
+    }
+}
+
+/// Whether the newest line is shown with `filter` on.
+fn line_matches(lines: &VecDeque<(Option<usize>, String)>, filter: usize) -> bool {
+    lines
+        .back()
+        .is_some_and(|(task, _)| task.is_none_or(|task| task == filter))
+}
+
+/// Cut `text` to fit `width` columns, so long lines don't wrap and push the
+/// view out of place.
+fn truncate(text: &str, width: usize) -> String {
+    let mut used = 0;
+    text.chars()
+        .take_while(|c| {
+            used += c.width().unwrap_or(0);
+            used <= width
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(rows: usize) -> (LogView, crate::term::TermBuffer) {
+        let (term, buffer) = Term::buffered(true, false);
+        let view = LogView::with_term(term, ["fmt", "test"])
+            .with_size(rows, 40)
+            .with_help("r re-run");
+        (view, buffer)
+    }
+
+    #[test]
+    fn test_pane_shows_newest_lines_and_statuses() {
+        let (view, _) = view(5);
+        view.start();
+        view.push_line("fmt", "one");
+        view.push_line("test", "two");
+        view.message("batch");
+        view.push_line("test", "three");
+        view.set_status("fmt", TaskStatus::Passed(Duration::from_millis(1200)));
+        view.set_status("test", TaskStatus::Failed(1));
+
+        assert_eq!(
+            view.frame(),
+            [
+                "[test] two",
+                "batch",
+                "[test] three",
+                "1 fmt ✓ 1.2s  2 test ✗ 1",
+                "r re-run",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_and_scroll() {
+        let (view, _) = view(4);
+        for i in 0..3 {
+            view.push_line("fmt", &format!("fmt {}", i));
+            view.push_line("test", &format!("test {}", i));
+        }
+
+        view.set_filter(Some("test"));
+        assert_eq!(view.filter().as_deref(), Some("test"));
+        assert_eq!(view.frame()[..2], ["[test] test 1", "[test] test 2"]);
+        assert_eq!(view.frame()[2], "1 fmt –  [2 test] –");
+
+        view.scroll(1);
+        assert_eq!(view.frame()[..2], ["[test] test 0", "[test] test 1"]);
+        // Can't scroll past the oldest line
+        view.scroll(10);
+        assert_eq!(view.frame()[0], "[test] test 0");
+        // New output doesn't move a scrolled-back view
+        view.push_line("test", "test 3");
+        assert_eq!(view.frame()[0], "[test] test 0");
+
+        view.follow();
+        view.set_filter(None);
+        assert_eq!(view.frame()[..2], ["[test] test 2", "[test] test 3"]);
+    }
+
+    #[test]
+    fn test_long_lines_are_cut_to_width() {
+        let (view, _) = view(3);
+        view.push_line("fmt", &"x".repeat(100));
+
+        assert_eq!(view.frame()[0].chars().count(), 40);
+    }
+
+    #[test]
+    fn test_degrades_to_plain_logs() {
+        let (term, buffer) = Term::buffered(false, false);
+        let view = LogView::with_term(term, ["fmt"]).with_help("keys");
+        view.start();
+        view.push_line("fmt", "formatted 3 files");
+        view.message("2 files changed");
+        view.set_status("fmt", TaskStatus::Passed(Duration::from_secs(1)));
+        view.finish();
+
+        assert_eq!(
+            buffer.contents(),
+            "[fmt] formatted 3 files\n2 files changed\n"
+        );
+        assert_eq!(
+            view.status("fmt"),
+            TaskStatus::Passed(Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn test_uses_and_restores_the_alternate_screen() {
+        let (view, buffer) = view(3);
+        view.start();
+        view.finish();
+
+        let contents = buffer.contents();
+        assert!(contents.starts_with("\x1b[?1049h"));
+        assert!(contents.ends_with("\x1b[?1049l"));
+    }
+}
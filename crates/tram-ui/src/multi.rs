@@ -0,0 +1,172 @@
+//! Several indicators drawn together, one line each.
+//!
+//! On an interactive terminal every change redraws the whole block in
+//! place, so bars updated from different tasks never overwrite each other.
+//! Elsewhere each indicator prints its own lines as it would alone.
+
+use crate::draw::DrawTarget;
+use crate::progress_bar::ProgressBar;
+use crate::spinner::Spinner;
+use crate::term::Term;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Default)]
+struct MultiState {
+    lines: Vec<String>,
+    /// Lines drawn last time, which the cursor has to move back over
+    drawn: usize,
+}
+
+/// Manager for concurrent progress indicators. Clones share the same block.
+#[derive(Debug, Clone)]
+pub struct MultiProgress {
+    term: Term,
+    state: Arc<Mutex<MultiState>>,
+}
+
+impl MultiProgress {
+    /// Draw on standard error.
+    pub fn new() -> Self {
+        Self::with_term(Term::stderr())
+    }
+
+    /// Draw on the given terminal.
+    pub fn with_term(term: Term) -> Self {
+        Self {
+            term,
+            state: Arc::new(Mutex::new(MultiState::default())),
+        }
+    }
+
+    /// Add a bar below the existing indicators.
+    pub fn add(&self, bar: ProgressBar) -> ProgressBar {
+        bar.attach(self.target());
+        bar
+    }
+
+    /// Add a spinner below the existing indicators.
+    pub fn add_spinner(&self, spinner: Spinner) -> Spinner {
+        spinner.attach(self.target());
+        spinner
+    }
+
+    /// Print a line above the indicators, e.g. a log message.
+    pub fn println(&self, line: &str) {
+        if !self.term.is_interactive() {
+            self.term.write(&format!("{}\n", line));
+            return;
+        }
+
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let mut out = move_up(state.drawn);
+        out.push_str(&format!("\r\x1b[K{}\n", line));
+        out.push_str(&render(&state.lines));
+        state.drawn = state.lines.len();
+
+        self.term.write(&out);
+    }
+
+    pub(crate) fn term(&self) -> &Term {
+        &self.term
+    }
+
+    pub(crate) fn set_line(&self, index: usize, line: &str) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let Some(slot) = state.lines.get_mut(index) else {
+            return;
+        };
+        *slot = line.to_string();
+
+        let mut out = move_up(state.drawn);
+        out.push_str(&render(&state.lines));
+        state.drawn = state.lines.len();
+
+        self.term.write(&out);
+    }
+
+    fn target(&self) -> DrawTarget {
+        let index = self
+            .state
+            .lock()
+            .map(|mut state| {
+                state.lines.push(String::new());
+                state.lines.len() - 1
+            })
+            .unwrap_or_default();
+
+        DrawTarget::Multi {
+            multi: self.clone(),
+            index,
+        }
+    }
+}
+
+impl Default for MultiProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn move_up(lines: usize) -> String {
+    if lines > 0 {
+        format!("\x1b[{}A", lines)
+    } else {
+        String::new()
+    }
+}
+
+fn render(lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|line| format!("\r\x1b[K{}\n", line))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Template;
+    use tram_test::assert_screen_eq;
+
+    #[test]
+    fn test_bars_redraw_in_their_own_lines() {
+        let (term, buffer) = Term::buffered(true, true);
+        let multi = MultiProgress::with_term(term);
+        let template = Template::new("{prefix} [{bar:4}] {pos}/{total}");
+
+        let first = multi.add(
+            ProgressBar::new(4)
+                .with_prefix("a")
+                .with_template(template.clone()),
+        );
+        let second = multi.add(ProgressBar::new(2).with_prefix("b").with_template(template));
+
+        first.inc(1);
+        second.inc(1);
+        multi.println("halfway");
+        first.inc(3);
+        second.inc(1);
+        second.finish();
+
+        assert_screen_eq!(
+            buffer.contents(),
+            ["halfway", "a [====] 4/4", "b [====] 2/2"]
+        );
+    }
+
+    #[test]
+    fn test_non_interactive_prints_lines() {
+        let (term, buffer) = Term::buffered(false, false);
+        let multi = MultiProgress::with_term(term);
+
+        let spinner = multi.add_spinner(Spinner::new("waiting"));
+        multi.println("note");
+        spinner.finish_with_message("done");
+
+        assert_eq!(buffer.contents(), "waiting\nnote\n✓ done\n");
+    }
+}
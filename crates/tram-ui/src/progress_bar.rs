@@ -0,0 +1,304 @@
+//! Progress bars for work with a known size.
+
+use crate::draw::DrawTarget;
+use crate::template::{Template, Values};
+use crate::term::Term;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Without an interactive terminal, a line is printed each time the bar
+/// passes another multiple of this percentage.
+pub const NON_INTERACTIVE_STEP_PERCENT: u64 = 25;
+
+#[derive(Debug)]
+struct BarState {
+    position: u64,
+    total: u64,
+    prefix: String,
+    message: String,
+    template: Template,
+    started: Instant,
+    target: DrawTarget,
+    /// Last milestone printed without an interactive terminal
+    printed_step: u64,
+    finished: bool,
+}
+
+impl BarState {
+    fn line(&self) -> String {
+        self.template.render(
+            &Values {
+                position: self.position,
+                total: Some(self.total),
+                elapsed: self.started.elapsed(),
+                spinner: "",
+                prefix: &self.prefix,
+                message: &self.message,
+            },
+            self.target.term(),
+        )
+    }
+
+    fn percent(&self) -> u64 {
+        match self.total {
+            0 => 100,
+            total => self.position.min(total) * 100 / total,
+        }
+    }
+
+    fn draw(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        if self.target.term().is_interactive() {
+            self.target.draw(&self.line());
+            return;
+        }
+
+        let step = self.percent() / NON_INTERACTIVE_STEP_PERCENT;
+        if step > self.printed_step && self.percent() < 100 {
+            self.printed_step = step;
+            self.target.println(&self.line());
+        }
+    }
+}
+
+/// A progress bar. Clones share the same bar, so one can be handed to each
+/// task working on it.
+#[derive(Debug, Clone)]
+pub struct ProgressBar {
+    state: Arc<Mutex<BarState>>,
+}
+
+impl ProgressBar {
+    /// A bar for `total` items, drawn on standard error.
+    pub fn new(total: u64) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(BarState {
+                position: 0,
+                total,
+                prefix: String::new(),
+                message: String::new(),
+                template: Template::default(),
+                started: Instant::now(),
+                target: DrawTarget::Term(Term::stderr()),
+                printed_step: 0,
+                finished: false,
+            })),
+        }
+    }
+
+    /// Draw on the given terminal.
+    pub fn with_term(self, term: Term) -> Self {
+        self.attach(DrawTarget::Term(term));
+        self
+    }
+
+    /// Choose what the line shows. See [`Template`] for the keys.
+    pub fn with_template(self, template: impl Into<Template>) -> Self {
+        self.update(|state| state.template = template.into());
+        self
+    }
+
+    /// Text for the `{prefix}` key, e.g. the name of the task.
+    pub fn with_prefix(self, prefix: impl Into<String>) -> Self {
+        self.update(|state| state.prefix = prefix.into());
+        self
+    }
+
+    /// Text for the `{msg}` key.
+    pub fn with_message(self, message: impl Into<String>) -> Self {
+        self.update(|state| state.message = message.into());
+        self
+    }
+
+    /// Advance by `delta` items.
+    pub fn inc(&self, delta: u64) {
+        self.update(|state| {
+            state.position = state.position.saturating_add(delta);
+            state.draw();
+        });
+    }
+
+    /// Jump to `position` items.
+    pub fn set_position(&self, position: u64) {
+        self.update(|state| {
+            state.position = position;
+            state.draw();
+        });
+    }
+
+    /// Change the total, e.g. once more work is discovered.
+    pub fn set_length(&self, total: u64) {
+        self.update(|state| {
+            state.total = total;
+            state.draw();
+        });
+    }
+
+    /// Change the `{msg}` text.
+    pub fn set_message(&self, message: impl Into<String>) {
+        self.update(|state| {
+            state.message = message.into();
+            state.draw();
+        });
+    }
+
+    /// Items done so far.
+    pub fn position(&self) -> u64 {
+        self.read(|state| state.position)
+    }
+
+    /// Total items.
+    pub fn length(&self) -> u64 {
+        self.read(|state| state.total)
+    }
+
+    /// Time since the bar was created.
+    pub fn elapsed(&self) -> Duration {
+        self.read(|state| state.started.elapsed())
+    }
+
+    /// Items per second so far.
+    pub fn rate(&self) -> f64 {
+        self.read(|state| crate::format::rate(state.position, state.started.elapsed()))
+    }
+
+    /// Estimated time left, once there's a rate to go by.
+    pub fn eta(&self) -> Option<Duration> {
+        self.read(|state| crate::format::eta(state.position, state.total, state.started.elapsed()))
+    }
+
+    /// The line as currently drawn.
+    pub fn line(&self) -> String {
+        self.read(BarState::line)
+    }
+
+    /// Whether the bar has been finished.
+    pub fn is_finished(&self) -> bool {
+        self.read(|state| state.finished)
+    }
+
+    /// Stop, leaving the bar on screen as it is.
+    pub fn finish(&self) {
+        self.finish_inner(|state| Some(state.line()));
+    }
+
+    /// Stop, replacing the bar with a check mark and `message`.
+    pub fn finish_with_message(&self, message: impl Into<String>) {
+        let message = message.into();
+        self.finish_inner(|state| {
+            Some(format!(
+                "{} {}",
+                state
+                    .target
+                    .term()
+                    .style("✓", state.target.term().theme().success),
+                message
+            ))
+        });
+    }
+
+    /// Stop and remove the bar.
+    pub fn finish_and_clear(&self) {
+        self.finish_inner(|_| None);
+    }
+
+    pub(crate) fn attach(&self, target: DrawTarget) {
+        self.update(|state| {
+            state.target = target;
+            state.draw();
+        });
+    }
+
+    fn finish_inner(&self, line: impl FnOnce(&BarState) -> Option<String>) {
+        self.update(|state| {
+            if state.finished {
+                return;
+            }
+            state.finished = true;
+            state.target.finish(line(state).as_deref());
+        });
+    }
+
+    fn update(&self, f: impl FnOnce(&mut BarState)) {
+        if let Ok(mut state) = self.state.lock() {
+            f(&mut state);
+        }
+    }
+
+    fn read<T: Default>(&self, f: impl FnOnce(&BarState) -> T) -> T {
+        self.state.lock().map(|state| f(&state)).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tram_test::{assert_plain_contains, assert_screen_eq};
+
+    #[test]
+    fn test_interactive_bar_redraws_in_place() {
+        let (term, buffer) = Term::buffered(true, true);
+        let bar = ProgressBar::new(4)
+            .with_term(term)
+            .with_template("[{bar:8}] {percent}% {pos}/{total} {msg}");
+
+        bar.inc(1);
+        bar.set_message("halfway");
+        bar.inc(1);
+        assert_eq!(bar.position(), 2);
+        assert_plain_contains!(bar.line(), "[====----]  50% 2/4 halfway");
+
+        bar.set_position(4);
+        bar.finish();
+
+        assert!(buffer.contents().contains("\x1b[32m"));
+        assert_screen_eq!(buffer.contents(), ["[========] 100% 4/4 halfway"]);
+    }
+
+    #[test]
+    fn test_non_interactive_bar_prints_milestones() {
+        let (term, buffer) = Term::buffered(false, false);
+        let bar = ProgressBar::new(8)
+            .with_term(term)
+            .with_template("{percent}% {pos}/{total}");
+
+        for _ in 0..8 {
+            bar.inc(1);
+        }
+        bar.finish_with_message("copied 8 files");
+
+        assert_eq!(
+            buffer.contents(),
+            " 25% 2/8\n 50% 4/8\n 75% 6/8\n✓ copied 8 files\n"
+        );
+    }
+
+    #[test]
+    fn test_finish_and_clear() {
+        let (term, buffer) = Term::buffered(true, false);
+        let bar = ProgressBar::new(2).with_term(term);
+
+        bar.inc(1);
+        bar.finish_and_clear();
+        bar.inc(1);
+
+        assert!(bar.is_finished());
+        assert_screen_eq!(buffer.contents(), [] as [&str; 0]);
+    }
+
+    #[test]
+    fn test_rate_and_eta_are_available() {
+        let bar = ProgressBar::new(10).with_term(Term::hidden());
+
+        assert_eq!(bar.eta(), None);
+        std::thread::sleep(Duration::from_millis(10));
+        bar.inc(5);
+
+        assert!(bar.rate() > 0.0);
+        assert!(bar.eta().is_some());
+        assert!(bar.elapsed() >= Duration::from_millis(10));
+    }
+}
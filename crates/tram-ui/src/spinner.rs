@@ -0,0 +1,300 @@
+//! Spinners for work without a known size.
+
+use crate::draw::DrawTarget;
+use crate::template::{Template, Values};
+use crate::term::Term;
+use crate::theme::{Style, Theme};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use tram_core::ProgressDisplay;
+
+/// Braille frames used unless [`Spinner::with_frames`] says otherwise.
+pub const DEFAULT_FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+#[derive(Debug)]
+struct SpinnerState {
+    frames: Vec<String>,
+    frame: usize,
+    message: String,
+    template: Template,
+    started: Instant,
+    target: DrawTarget,
+    /// Message printed last without an interactive terminal
+    printed: Option<String>,
+    finished: bool,
+}
+
+impl SpinnerState {
+    fn line(&self) -> String {
+        self.template.render(
+            &Values {
+                position: 0,
+                total: None,
+                elapsed: self.started.elapsed(),
+                spinner: self.frames.get(self.frame).map_or("", String::as_str),
+                prefix: "",
+                message: &self.message,
+            },
+            self.target.term(),
+        )
+    }
+
+    fn draw(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        if self.target.term().is_interactive() {
+            self.target.draw(&self.line());
+            return;
+        }
+
+        // Frames mean nothing in a log, so only print the message as it
+        // changes.
+        if self.printed.as_deref() != Some(self.message.as_str()) {
+            self.printed = Some(self.message.clone());
+            self.target.println(&self.message);
+        }
+    }
+}
+
+/// A spinner. Clones share the same spinner.
+#[derive(Debug, Clone)]
+pub struct Spinner {
+    state: Arc<Mutex<SpinnerState>>,
+}
+
+impl Spinner {
+    /// A spinner showing `message`, drawn on standard error.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(SpinnerState {
+                frames: DEFAULT_FRAMES
+                    .iter()
+                    .map(|frame| frame.to_string())
+                    .collect(),
+                frame: 0,
+                message: message.into(),
+                template: Template::new(Template::SPINNER),
+                started: Instant::now(),
+                target: DrawTarget::Term(Term::stderr()),
+                printed: None,
+                finished: false,
+            })),
+        }
+    }
+
+    /// Draw on the given terminal.
+    pub fn with_term(self, term: Term) -> Self {
+        self.update(|state| state.target = DrawTarget::Term(term));
+        self
+    }
+
+    /// Cycle through these frames instead of the defaults.
+    pub fn with_frames(self, frames: &[&str]) -> Self {
+        if !frames.is_empty() {
+            self.update(|state| {
+                state.frames = frames.iter().map(|frame| frame.to_string()).collect();
+            });
+        }
+        self
+    }
+
+    /// Choose what the line shows. See [`Template`] for the keys.
+    pub fn with_template(self, template: impl Into<Template>) -> Self {
+        self.update(|state| state.template = template.into());
+        self
+    }
+
+    /// Advance to the next frame and redraw.
+    pub fn tick(&self) {
+        self.update(|state| {
+            state.frame = (state.frame + 1) % state.frames.len();
+            state.draw();
+        });
+    }
+
+    /// Change the message.
+    pub fn set_message(&self, message: impl Into<String>) {
+        self.update(|state| {
+            state.message = message.into();
+            state.draw();
+        });
+    }
+
+    /// Tick every `interval` on a background thread until the spinner is
+    /// finished or dropped.
+    pub fn enable_steady_tick(&self, interval: Duration) {
+        let state: Weak<Mutex<SpinnerState>> = Arc::downgrade(&self.state);
+
+        std::thread::spawn(move || {
+            loop {
+                std::thread::sleep(interval);
+                let Some(state) = state.upgrade() else {
+                    return;
+                };
+                let spinner = Spinner { state };
+                if spinner.is_finished() {
+                    return;
+                }
+                spinner.tick();
+            }
+        });
+    }
+
+    /// The line as currently drawn.
+    pub fn line(&self) -> String {
+        self.read(SpinnerState::line)
+    }
+
+    /// Whether the spinner has been finished.
+    pub fn is_finished(&self) -> bool {
+        self.read(|state| state.finished)
+    }
+
+    /// Stop, replacing the spinner with a check mark and `message`.
+    pub fn finish_with_message(&self, message: impl Into<String>) {
+        self.finish_with_mark("✓", |theme| theme.success, message.into());
+    }
+
+    /// Stop, replacing the spinner with a cross and `message`.
+    pub fn fail_with_message(&self, message: impl Into<String>) {
+        self.finish_with_mark("✗", |theme| theme.error, message.into());
+    }
+
+    /// Stop and remove the spinner.
+    pub fn finish_and_clear(&self) {
+        self.finish_inner(|_| None);
+    }
+
+    pub(crate) fn attach(&self, target: DrawTarget) {
+        self.update(|state| {
+            state.target = target;
+            state.draw();
+        });
+    }
+
+    fn finish_with_mark(&self, mark: &str, style: fn(&Theme) -> Style, message: String) {
+        self.finish_inner(|state| {
+            Some(format!(
+                "{} {}",
+                state
+                    .target
+                    .term()
+                    .style(mark, style(state.target.term().theme())),
+                message
+            ))
+        });
+    }
+
+    fn finish_inner(&self, line: impl FnOnce(&SpinnerState) -> Option<String>) {
+        self.update(|state| {
+            if state.finished {
+                return;
+            }
+            state.finished = true;
+            state.target.finish(line(state).as_deref());
+        });
+    }
+
+    fn update(&self, f: impl FnOnce(&mut SpinnerState)) {
+        if let Ok(mut state) = self.state.lock() {
+            f(&mut state);
+        }
+    }
+
+    fn read<T: Default>(&self, f: impl FnOnce(&SpinnerState) -> T) -> T {
+        self.state.lock().map(|state| f(&state)).unwrap_or_default()
+    }
+}
+
+/// Draws a `tram_core::Progress`, one step per message.
+impl ProgressDisplay for Spinner {
+    fn show(&self, line: &str) {
+        self.set_message(line);
+    }
+
+    fn clear(&self) {
+        self.finish_and_clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tram_test::{assert_plain_contains, assert_screen_eq};
+
+    #[test]
+    fn test_interactive_spinner_cycles_frames() {
+        let (term, buffer) = Term::buffered(true, false);
+        let spinner = Spinner::new("loading")
+            .with_term(term)
+            .with_frames(&["-", "\\", "|", "/"]);
+
+        spinner.tick();
+        assert_eq!(spinner.line(), "\\ loading");
+        spinner.tick();
+        spinner.set_message("still loading");
+        assert_screen_eq!(buffer.contents(), ["| still loading"]);
+
+        spinner.fail_with_message("gave up");
+        assert_screen_eq!(buffer.contents(), ["✗ gave up"]);
+        assert!(spinner.is_finished());
+    }
+
+    #[test]
+    fn test_non_interactive_spinner_prints_message_changes() {
+        let (term, buffer) = Term::buffered(false, false);
+        let spinner = Spinner::new("resolving").with_term(term);
+
+        spinner.tick();
+        spinner.tick();
+        spinner.set_message("downloading");
+        spinner.tick();
+        spinner.finish_with_message("installed");
+
+        assert_eq!(buffer.contents(), "resolving\ndownloading\n✓ installed\n");
+    }
+
+    #[test]
+    fn test_finish_marks_are_colored() {
+        let (term, buffer) = Term::buffered(true, true);
+        let spinner = Spinner::new("checking").with_term(term);
+
+        spinner.finish_with_message("ok");
+
+        assert!(buffer.contents().contains("\x1b[32m✓\x1b[0m"));
+        assert_plain_contains!(buffer.contents(), "✓ ok");
+    }
+
+    #[test]
+    fn test_spinner_draws_progress_steps() {
+        let (term, buffer) = Term::buffered(false, false);
+        let progress =
+            tram_core::Progress::new(Some(2)).with_display(Spinner::new("").with_term(term));
+
+        progress.step("Writing files");
+        progress.step("Initializing git");
+        progress.finish();
+
+        assert_eq!(
+            buffer.contents(),
+            "[1/2] Writing files\n[2/2] Initializing git\n"
+        );
+    }
+
+    #[test]
+    fn test_steady_tick_stops_when_finished() {
+        let (term, buffer) = Term::buffered(true, false);
+        let spinner = Spinner::new("waiting").with_term(term);
+
+        spinner.enable_steady_tick(Duration::from_millis(1));
+        std::thread::sleep(Duration::from_millis(20));
+        spinner.finish_and_clear();
+        let contents = buffer.contents();
+        std::thread::sleep(Duration::from_millis(10));
+
+        assert!(contents.contains("waiting"));
+        assert_eq!(buffer.contents(), contents);
+    }
+}
@@ -0,0 +1,472 @@
+//! Table rendering for human-readable output.
+//!
+//! [`Table`] sizes columns to their content, shrinks and wraps them to fit the
+//! terminal, aligns cells per column, and draws Unicode, ASCII, or no borders.
+//! Headers take the theme's heading style and rows can be styled when color
+//! is enabled; widths are measured in terminal columns so wide characters and
+//! emoji line up. The same table exports as CSV or TSV for spreadsheets and
+//! scripts.
+
+use crate::theme::{Style, Theme, theme};
+use unicode_width::UnicodeWidthStr;
+
+pub use tram_core::BorderStyle;
+
+/// Columns are never shrunk below this width when fitting the terminal.
+const MIN_COLUMN_WIDTH: usize = 8;
+
+/// How cell text is aligned within its column.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Align {
+    #[default]
+    Left,
+    Right,
+    Center,
+}
+
+/// Border characters: left, fill, junction, right for each rule, then the
+/// vertical edge and separator.
+struct Borders {
+    top: [&'static str; 4],
+    middle: [&'static str; 4],
+    bottom: [&'static str; 4],
+    vertical: &'static str,
+}
+
+fn borders(style: BorderStyle) -> Option<Borders> {
+    match style {
+        BorderStyle::Unicode => Some(Borders {
+            top: ["┌", "─", "┬", "┐"],
+            middle: ["├", "─", "┼", "┤"],
+            bottom: ["└", "─", "┴", "┘"],
+            vertical: "│",
+        }),
+        BorderStyle::Ascii => Some(Borders {
+            top: ["+", "-", "+", "+"],
+            middle: ["+", "-", "+", "+"],
+            bottom: ["+", "-", "+", "+"],
+            vertical: "|",
+        }),
+        BorderStyle::None => None,
+    }
+}
+
+/// A table of text cells under a header row.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Table {
+    pub title: Option<String>,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// Style for each row, drawn when color is enabled
+    pub row_styles: Vec<Option<Style>>,
+    pub align: Vec<Align>,
+    pub border: BorderStyle,
+    /// Total width to fit within, usually the terminal width
+    pub max_width: Option<usize>,
+    /// Style the header row and apply row styles
+    pub color: bool,
+    /// Styles for the header
+    pub theme: Theme,
+}
+
+impl Table {
+    pub fn new(headers: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let headers: Vec<String> = headers.into_iter().map(Into::into).collect();
+
+        Self {
+            align: vec![Align::Left; headers.len()],
+            headers,
+            theme: theme(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_border(mut self, border: BorderStyle) -> Self {
+        self.border = border;
+        self
+    }
+
+    pub fn with_max_width(mut self, width: Option<usize>) -> Self {
+        self.max_width = width;
+        self
+    }
+
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Align a column's cells, including its header.
+    pub fn with_align(mut self, column: usize, align: Align) -> Self {
+        if let Some(slot) = self.align.get_mut(column) {
+            *slot = align;
+        }
+        self
+    }
+
+    /// Add a row. Missing cells are left empty; extra cells are dropped.
+    pub fn add_row(&mut self, row: impl IntoIterator<Item = impl Into<String>>) {
+        let mut row: Vec<String> = row.into_iter().map(Into::into).collect();
+        row.resize(self.headers.len(), String::new());
+        self.rows.push(row);
+        self.row_styles.push(None);
+    }
+
+    /// Add a row drawn in `style`, e.g. red for a failure.
+    pub fn add_styled_row(
+        &mut self,
+        row: impl IntoIterator<Item = impl Into<String>>,
+        style: Style,
+    ) {
+        self.add_row(row);
+        if let Some(slot) = self.row_styles.last_mut() {
+            *slot = Some(style);
+        }
+    }
+
+    /// Whether the table has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Render the table, without a trailing newline.
+    pub fn render(&self) -> String {
+        let widths = self.column_widths();
+        let borders = borders(self.border);
+        let mut lines = Vec::new();
+
+        if let Some(title) = &self.title {
+            lines.push(title.clone());
+            lines.push(String::new());
+        }
+
+        match &borders {
+            Some(borders) => {
+                lines.push(rule(&widths, borders.top));
+                self.push_row(&mut lines, &self.headers, &widths, Some(self.theme.heading));
+                lines.push(rule(&widths, borders.middle));
+                for (index, row) in self.rows.iter().enumerate() {
+                    self.push_row(&mut lines, row, &widths, self.row_style(index));
+                }
+                lines.push(rule(&widths, borders.bottom));
+            }
+            None => {
+                self.push_row(&mut lines, &self.headers, &widths, Some(self.theme.heading));
+                let dashes: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+                lines.push(format!("  {}", dashes.join("  ")));
+                for (index, row) in self.rows.iter().enumerate() {
+                    self.push_row(&mut lines, row, &widths, self.row_style(index));
+                }
+            }
+        }
+
+        lines.join("\n")
+    }
+
+    /// The header and rows as CSV, quoting cells as RFC 4180 requires.
+    pub fn to_csv(&self) -> String {
+        self.export(
+            |cell| {
+                if cell.contains([',', '"', '\n', '\r']) {
+                    format!("\"{}\"", cell.replace('"', "\"\""))
+                } else {
+                    cell.to_string()
+                }
+            },
+            ",",
+        )
+    }
+
+    /// The header and rows as TSV. Tabs and line breaks in cells become
+    /// spaces, since TSV has no quoting.
+    pub fn to_tsv(&self) -> String {
+        self.export(|cell| cell.replace(['\t', '\n', '\r'], " "), "\t")
+    }
+
+    fn export(&self, escape: impl Fn(&str) -> String, separator: &str) -> String {
+        std::iter::once(&self.headers)
+            .chain(&self.rows)
+            .map(|row| {
+                row.iter()
+                    .map(|cell| escape(cell))
+                    .collect::<Vec<_>>()
+                    .join(separator)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn row_style(&self, index: usize) -> Option<Style> {
+        self.row_styles.get(index).copied().flatten()
+    }
+
+    /// Natural column widths, shrunk (widest first) to fit `max_width`.
+    fn column_widths(&self) -> Vec<usize> {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| text_width(h)).collect();
+
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(text_width(cell));
+            }
+        }
+
+        let Some(max_width) = self.max_width else {
+            return widths;
+        };
+
+        let columns = widths.len();
+        let overhead = match self.border {
+            // "│ " before each cell, " " after, and the closing "│"
+            BorderStyle::Unicode | BorderStyle::Ascii => columns * 3 + 1,
+            // Two-space indent and two-space gaps
+            BorderStyle::None => columns * 2,
+        };
+        let available = max_width.saturating_sub(overhead);
+
+        while widths.iter().sum::<usize>() > available {
+            let Some((widest, width)) = widths
+                .iter()
+                .copied()
+                .enumerate()
+                .max_by_key(|(_, width)| *width)
+            else {
+                break;
+            };
+
+            if width <= MIN_COLUMN_WIDTH {
+                break;
+            }
+            widths[widest] -= 1;
+        }
+
+        widths
+    }
+
+    fn push_row(
+        &self,
+        lines: &mut Vec<String>,
+        cells: &[String],
+        widths: &[usize],
+        style: Option<Style>,
+    ) {
+        let wrapped: Vec<Vec<String>> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| wrap(cell, *width))
+            .collect();
+        let height = wrapped.iter().map(Vec::len).max().unwrap_or(1);
+        let style = style.filter(|_| self.color);
+
+        for line in 0..height {
+            let padded: Vec<String> = wrapped
+                .iter()
+                .zip(widths)
+                .zip(&self.align)
+                .map(|((cell, width), align)| {
+                    let text = cell.get(line).map(String::as_str).unwrap_or_default();
+                    pad(text, *width, *align, style)
+                })
+                .collect();
+
+            lines.push(match borders(self.border) {
+                Some(borders) => format!(
+                    "{v} {} {v}",
+                    padded.join(&format!(" {} ", borders.vertical)),
+                    v = borders.vertical
+                ),
+                None => format!("  {}", padded.join("  ")).trim_end().to_string(),
+            });
+        }
+    }
+}
+
+/// Width of text in terminal columns.
+pub fn text_width(text: &str) -> usize {
+    text.lines().map(UnicodeWidthStr::width).max().unwrap_or(0)
+}
+
+fn rule(widths: &[usize], [left, fill, junction, right]: [&str; 4]) -> String {
+    let segments: Vec<String> = widths.iter().map(|w| fill.repeat(w + 2)).collect();
+    format!("{}{}{}", left, segments.join(junction), right)
+}
+
+fn pad(text: &str, width: usize, align: Align, style: Option<Style>) -> String {
+    let space = width.saturating_sub(UnicodeWidthStr::width(text));
+    let (left, right) = match align {
+        Align::Left => (0, space),
+        Align::Right => (space, 0),
+        Align::Center => (space / 2, space - space / 2),
+    };
+    let text = match style {
+        Some(style) => style.paint(text),
+        None => text.to_string(),
+    };
+
+    format!("{}{}{}", " ".repeat(left), text, " ".repeat(right))
+}
+
+/// Word-wrap text to a width, breaking words that don't fit on a line alone.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut current = String::new();
+
+        for word in paragraph.split_whitespace() {
+            let needed = if current.is_empty() {
+                UnicodeWidthStr::width(word)
+            } else {
+                UnicodeWidthStr::width(current.as_str()) + 1 + UnicodeWidthStr::width(word)
+            };
+
+            if needed <= width {
+                if !current.is_empty() {
+                    current.push(' ');
+                }
+                current.push_str(word);
+                continue;
+            }
+
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+
+            // Hard-break words longer than the column
+            for c in word.chars() {
+                let c_width = unicode_width::UnicodeWidthChar::width(c).unwrap_or(0);
+                if UnicodeWidthStr::width(current.as_str()) + c_width > width {
+                    lines.push(std::mem::take(&mut current));
+                }
+                current.push(c);
+            }
+        }
+
+        lines.push(current);
+    }
+
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Table {
+        let mut table = Table::new(["Name", "Size"]).with_align(1, Align::Right);
+        table.add_row(["config.toml", "12"]);
+        table.add_row(["cache", "1024"]);
+        table
+    }
+
+    #[test]
+    fn test_unicode_borders() {
+        assert_eq!(
+            sample().render(),
+            [
+                "┌─────────────┬──────┐",
+                "│ Name        │ Size │",
+                "├─────────────┼──────┤",
+                "│ config.toml │   12 │",
+                "│ cache       │ 1024 │",
+                "└─────────────┴──────┘",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_ascii_and_borderless() {
+        let ascii = sample().with_border(BorderStyle::Ascii).render();
+        assert!(ascii.starts_with("+-------------+------+\n| Name        | Size |"));
+
+        let plain = sample()
+            .with_border(BorderStyle::None)
+            .with_title("Files:")
+            .render();
+        assert_eq!(
+            plain,
+            [
+                "Files:",
+                "",
+                "  Name         Size",
+                "  -----------  ----",
+                "  config.toml    12",
+                "  cache        1024",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_wraps_to_max_width() {
+        let mut table = Table::new(["Key", "Description"]).with_max_width(Some(30));
+        table.add_row(["a", "a long description that will not fit on one line"]);
+
+        let rendered = table.render();
+        assert!(rendered.lines().all(|line| text_width(line) <= 30));
+        assert!(rendered.contains("│ a   │ a long description   │"));
+        assert!(rendered.contains("│     │ that will not fit on │"));
+    }
+
+    #[test]
+    fn test_bold_header_and_wide_chars() {
+        let mut table = Table::new(["Status"]).with_color(true);
+        table.add_row(["✓ 完了"]);
+
+        let rendered = table.render();
+        assert!(rendered.contains("│ \x1b[1mStatus\x1b[0m │"));
+        assert!(rendered.contains("│ ✓ 完了 │"));
+    }
+
+    #[test]
+    fn test_styled_rows_only_with_color() {
+        let mut table = Table::new(["Task", "Status"]);
+        table.add_row(["build", "ok"]);
+        table.add_styled_row(["test", "failed"], Style::RED);
+
+        assert!(!table.render().contains('\x1b'));
+
+        let colored = table.with_color(true).render();
+        assert!(colored.contains("│ \x1b[31mtest\x1b[0m  │ \x1b[31mfailed\x1b[0m │"));
+        assert!(colored.contains("│ build │ ok     │"));
+        tram_test::assert_plain_contains!(colored, "│ test  │ failed │");
+    }
+
+    #[test]
+    fn test_csv_and_tsv_export() {
+        let mut table = Table::new(["Name", "Note"]).with_title("ignored");
+        table.add_row(["a,b", "say \"hi\""]);
+        table.add_row(["tab\there", "two\nlines"]);
+
+        assert_eq!(
+            table.to_csv(),
+            "Name,Note\n\"a,b\",\"say \"\"hi\"\"\"\ntab\there,\"two\nlines\""
+        );
+        assert_eq!(
+            table.to_tsv(),
+            "Name\tNote\na,b\tsay \"hi\"\ntab here\ttwo lines"
+        );
+    }
+
+    #[test]
+    fn test_wrap_breaks_long_words() {
+        assert_eq!(wrap("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("", 4), [""]);
+        assert_eq!(wrap("one\ntwo", 10), ["one", "two"]);
+    }
+}
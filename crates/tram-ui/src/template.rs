@@ -0,0 +1,267 @@
+//! Templates describing what a progress line shows.
+//!
+//! A template is text with `{key}` placeholders, optionally with a width as
+//! `{key:width}`. For `{bar}` the width is the bar's length; for the other
+//! keys it pads the value, so columns line up across lines. Unknown keys are
+//! printed as written.
+//!
+//! | Key | Value |
+//! | --- | --- |
+//! | `bar` | The bar itself, 30 columns unless given a width |
+//! | `spinner` | The spinner's current frame |
+//! | `pos`, `total` | Items done, and the total |
+//! | `percent` | Percent done, padded to 3 columns |
+//! | `rate` | Items per second, e.g. `12.5/s` |
+//! | `eta` | Estimated time left, e.g. `1m05s` |
+//! | `elapsed` | Time since the start |
+//! | `prefix`, `msg` | The indicator's prefix and message |
+
+use crate::format::{format_duration, format_rate};
+use crate::term::Term;
+use std::time::Duration;
+
+/// Default bar length in columns.
+pub const DEFAULT_BAR_WIDTH: usize = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Bar,
+    Spinner,
+    Pos,
+    Total,
+    Percent,
+    Rate,
+    Eta,
+    Elapsed,
+    Prefix,
+    Msg,
+}
+
+impl Key {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "bar" => Key::Bar,
+            "spinner" => Key::Spinner,
+            "pos" => Key::Pos,
+            "total" => Key::Total,
+            "percent" => Key::Percent,
+            "rate" => Key::Rate,
+            "eta" => Key::Eta,
+            "elapsed" => Key::Elapsed,
+            "prefix" => Key::Prefix,
+            "msg" => Key::Msg,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Key { key: Key, width: Option<usize> },
+}
+
+/// A parsed progress line template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    /// The default for progress bars.
+    pub const BAR: &str = "[{bar}] {percent}% {pos}/{total} ({rate}, ETA {eta}) {msg}";
+
+    /// The default for spinners.
+    pub const SPINNER: &str = "{spinner} {msg}";
+
+    /// Parse a template.
+    pub fn new(template: &str) -> Self {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut rest = template;
+
+        while let Some(start) = rest.find('{') {
+            literal.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+
+            let Some(end) = after.find('}') else {
+                literal.push_str(&rest[start..]);
+                rest = "";
+                break;
+            };
+
+            let placeholder = &after[..end];
+            let (name, width) = match placeholder.split_once(':') {
+                Some((name, width)) => (name, width.parse::<usize>().ok()),
+                None => (placeholder, None),
+            };
+
+            match Key::parse(name) {
+                Some(key) => {
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Part::Key { key, width });
+                }
+                None => literal.push_str(&rest[start..start + end + 2]),
+            }
+
+            rest = &after[end + 1..];
+        }
+
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+
+        Self { parts }
+    }
+
+    /// Render a line for the given state.
+    pub(crate) fn render(&self, values: &Values<'_>, term: &Term) -> String {
+        let mut line = String::new();
+
+        for part in &self.parts {
+            match part {
+                Part::Literal(text) => line.push_str(text),
+                Part::Key {
+                    key: Key::Bar,
+                    width,
+                } => line.push_str(&bar(values, width.unwrap_or(DEFAULT_BAR_WIDTH), term)),
+                Part::Key {
+                    key: Key::Spinner, ..
+                } => line.push_str(&term.style(values.spinner, term.theme().info)),
+                Part::Key { key, width } => {
+                    let value = value(*key, values);
+                    match width {
+                        Some(width) => line.push_str(&format!("{:<width$}", value)),
+                        None => line.push_str(&value),
+                    }
+                }
+            }
+        }
+
+        line.trim_end().to_string()
+    }
+}
+
+impl Default for Template {
+    fn default() -> Self {
+        Self::new(Self::BAR)
+    }
+}
+
+impl From<&str> for Template {
+    fn from(template: &str) -> Self {
+        Self::new(template)
+    }
+}
+
+/// The state a line is rendered from.
+pub(crate) struct Values<'a> {
+    pub position: u64,
+    pub total: Option<u64>,
+    pub elapsed: Duration,
+    pub spinner: &'a str,
+    pub prefix: &'a str,
+    pub message: &'a str,
+}
+
+impl Values<'_> {
+    fn fraction(&self) -> f64 {
+        match self.total {
+            Some(0) => 1.0,
+            Some(total) => (self.position as f64 / total as f64).min(1.0),
+            None => 0.0,
+        }
+    }
+}
+
+fn value(key: Key, values: &Values<'_>) -> String {
+    match key {
+        Key::Pos => values.position.to_string(),
+        Key::Total => values
+            .total
+            .map(|total| total.to_string())
+            .unwrap_or_else(|| "?".to_string()),
+        Key::Percent => format!("{:>3}", (values.fraction() * 100.0) as u64),
+        Key::Rate => format_rate(crate::format::rate(values.position, values.elapsed)),
+        Key::Eta => values
+            .total
+            .and_then(|total| crate::format::eta(values.position, total, values.elapsed))
+            .map(format_duration)
+            .unwrap_or_else(|| "?".to_string()),
+        Key::Elapsed => format_duration(values.elapsed),
+        Key::Prefix => values.prefix.to_string(),
+        Key::Msg => values.message.to_string(),
+        Key::Bar | Key::Spinner => String::new(),
+    }
+}
+
+fn bar(values: &Values<'_>, width: usize, term: &Term) -> String {
+    let filled = ((values.fraction() * width as f64) as usize).min(width);
+
+    format!(
+        "{}{}",
+        term.style(&"=".repeat(filled), term.theme().success),
+        term.style(&"-".repeat(width - filled), term.theme().muted)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(position: u64, total: Option<u64>) -> Values<'static> {
+        Values {
+            position,
+            total,
+            elapsed: Duration::from_secs(5),
+            spinner: "⠋",
+            prefix: "build",
+            message: "compiling",
+        }
+    }
+
+    #[test]
+    fn test_render_keys() {
+        let term = Term::hidden();
+        let template = Template::new("{prefix}: [{bar:10}] {percent}% {pos}/{total}");
+
+        assert_eq!(
+            template.render(&values(5, Some(10)), &term),
+            "build: [=====-----]  50% 5/10"
+        );
+        assert_eq!(
+            Template::default().render(&values(5, Some(10)), &term),
+            "[===============---------------]  50% 5/10 (1.0/s, ETA 5s) compiling"
+        );
+        assert_eq!(
+            Template::new(Template::SPINNER).render(&values(0, None), &term),
+            "⠋ compiling"
+        );
+    }
+
+    #[test]
+    fn test_widths_and_unknown_keys() {
+        let term = Term::hidden();
+
+        assert_eq!(
+            Template::new("{msg:12}|{nope} {unclosed").render(&values(0, None), &term),
+            "compiling   |{nope} {unclosed"
+        );
+        assert_eq!(
+            Template::new("{total} {eta}").render(&values(0, None), &term),
+            "? ?"
+        );
+    }
+
+    #[test]
+    fn test_bar_is_colored_with_color() {
+        let (term, _) = Term::buffered(true, true);
+        let line = Template::new("{bar:4}").render(&values(1, Some(2)), &term);
+
+        assert_eq!(line, "\x1b[32m==\x1b[0m\x1b[90m--\x1b[0m");
+        tram_test::assert_plain_contains!(line, "==--");
+    }
+}
@@ -0,0 +1,172 @@
+//! Where progress is drawn, and how.
+
+use crate::theme::{Style, Theme, theme};
+use std::io::{IsTerminal, Write};
+use std::sync::{Arc, Mutex};
+use tram_core::{Stream, color_enabled};
+
+/// Output captured by a [`Term::buffered`] terminal, for tests.
+#[derive(Debug, Clone, Default)]
+pub struct TermBuffer(Arc<Mutex<String>>);
+
+impl TermBuffer {
+    /// Everything written so far, escape sequences included.
+    pub fn contents(&self) -> String {
+        self.0.lock().map(|text| text.clone()).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Sink {
+    Stderr,
+    Stdout,
+    Buffer(TermBuffer),
+    Hidden,
+}
+
+/// A stream the indicators draw to.
+#[derive(Debug, Clone)]
+pub struct Term {
+    sink: Sink,
+    interactive: bool,
+    color: bool,
+    theme: Theme,
+}
+
+impl Term {
+    /// Standard error, where progress belongs so it never mixes with a
+    /// command's output.
+    pub fn stderr() -> Self {
+        Self {
+            sink: Sink::Stderr,
+            interactive: is_interactive(
+                std::io::stderr().is_terminal(),
+                std::env::var("TERM").ok().as_deref(),
+            ),
+            color: color_enabled(Stream::Stderr),
+            theme: theme(),
+        }
+    }
+
+    /// Standard output.
+    pub fn stdout() -> Self {
+        Self {
+            sink: Sink::Stdout,
+            interactive: is_interactive(
+                std::io::stdout().is_terminal(),
+                std::env::var("TERM").ok().as_deref(),
+            ),
+            color: color_enabled(Stream::Stdout),
+            theme: theme(),
+        }
+    }
+
+    /// A terminal that records its output, behaving as interactive and
+    /// colored as asked, with the default theme.
+    pub fn buffered(interactive: bool, color: bool) -> (Self, TermBuffer) {
+        let buffer = TermBuffer::default();
+        let term = Self {
+            sink: Sink::Buffer(buffer.clone()),
+            interactive,
+            color,
+            theme: Theme::default(),
+        };
+
+        (term, buffer)
+    }
+
+    /// A terminal that discards everything.
+    pub fn hidden() -> Self {
+        Self {
+            sink: Sink::Hidden,
+            interactive: false,
+            color: false,
+            theme: Theme::default(),
+        }
+    }
+
+    /// The same terminal without styling, e.g. for a `--no-color` flag.
+    pub fn without_color(mut self) -> Self {
+        self.color = false;
+        self
+    }
+
+    /// Draw with `theme` instead of the one from [`init_theme`](crate::init_theme).
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Whether lines are redrawn in place.
+    pub fn is_interactive(&self) -> bool {
+        self.interactive
+    }
+
+    /// Whether output is styled.
+    pub fn has_color(&self) -> bool {
+        self.color
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Style text if this terminal has color.
+    pub fn style(&self, text: &str, style: Style) -> String {
+        if self.color {
+            style.paint(text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    pub(crate) fn write(&self, text: &str) {
+        match &self.sink {
+            Sink::Stderr => {
+                let mut stderr = std::io::stderr().lock();
+                let _ = stderr.write_all(text.as_bytes());
+                let _ = stderr.flush();
+            }
+            Sink::Stdout => {
+                let mut stdout = std::io::stdout().lock();
+                let _ = stdout.write_all(text.as_bytes());
+                let _ = stdout.flush();
+            }
+            Sink::Buffer(buffer) => {
+                if let Ok(mut contents) = buffer.0.lock() {
+                    contents.push_str(text);
+                }
+            }
+            Sink::Hidden => {}
+        }
+    }
+}
+
+/// Whether a stream can redraw lines in place: it's a terminal, and not a
+/// dumb one.
+pub fn is_interactive(is_terminal: bool, term: Option<&str>) -> bool {
+    is_terminal && term != Some("dumb")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dumb_terminals_are_not_interactive() {
+        assert!(is_interactive(true, Some("xterm-256color")));
+        assert!(is_interactive(true, None));
+        assert!(!is_interactive(true, Some("dumb")));
+        assert!(!is_interactive(false, Some("xterm")));
+    }
+
+    #[test]
+    fn test_style_only_with_color() {
+        let (colored, _) = Term::buffered(true, true);
+        let (plain, _) = Term::buffered(true, false);
+
+        assert_eq!(colored.style("ok", Style::GREEN), "\x1b[32mok\x1b[0m");
+        assert_eq!(plain.style("ok", Style::GREEN), "ok");
+        assert_eq!(colored.without_color().style("ok", Style::GREEN), "ok");
+    }
+}
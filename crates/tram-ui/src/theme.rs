@@ -0,0 +1,330 @@
+//! Styles and the theme that gives them meaning.
+//!
+//! Components style output by role rather than by color: a finished spinner
+//! uses [`Theme::success`], a table header [`Theme::heading`]. The theme is
+//! read once at startup from the config's `[theme]` section with
+//! [`Theme::from_config`] and installed with [`init_theme`], so progress,
+//! tables, and prompts all agree. Whether styles are drawn at all is still
+//! up to color detection in `tram-core`.
+
+use std::fmt;
+use std::sync::RwLock;
+use tram_config::ThemeConfig;
+use tram_core::{AppResult, TramError};
+
+static THEME: RwLock<Option<Theme>> = RwLock::new(None);
+
+/// A foreground color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    /// Bright black, which most terminals show as gray
+    Gray,
+}
+
+impl Color {
+    const ALL: [(Color, &str); 9] = [
+        (Color::Black, "black"),
+        (Color::Red, "red"),
+        (Color::Green, "green"),
+        (Color::Yellow, "yellow"),
+        (Color::Blue, "blue"),
+        (Color::Magenta, "magenta"),
+        (Color::Cyan, "cyan"),
+        (Color::White, "white"),
+        (Color::Gray, "gray"),
+    ];
+
+    fn code(self) -> u8 {
+        match self {
+            Color::Black => 30,
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+            Color::White => 37,
+            Color::Gray => 90,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(color, _)| *color == self)
+            .map(|(_, name)| *name)
+            .unwrap_or_default()
+    }
+}
+
+/// A text style: an optional color plus attributes.
+///
+/// Parsed from words such as `"bold green"` or `"underline"`; `"none"` is
+/// the plain style.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    color: Option<Color>,
+    bold: bool,
+    italic: bool,
+    underline: bool,
+}
+
+impl Style {
+    pub const PLAIN: Style = Style::new();
+    pub const BOLD: Style = Style::new().bold();
+    pub const RED: Style = Style::new().fg(Color::Red);
+    pub const GREEN: Style = Style::new().fg(Color::Green);
+    pub const YELLOW: Style = Style::new().fg(Color::Yellow);
+    pub const BLUE: Style = Style::new().fg(Color::Blue);
+    pub const CYAN: Style = Style::new().fg(Color::Cyan);
+    pub const GRAY: Style = Style::new().fg(Color::Gray);
+
+    pub const fn new() -> Self {
+        Self {
+            color: None,
+            bold: false,
+            italic: false,
+            underline: false,
+        }
+    }
+
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub const fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub const fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    pub const fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    pub fn color(&self) -> Option<Color> {
+        self.color
+    }
+
+    pub fn is_bold(&self) -> bool {
+        self.bold
+    }
+
+    pub fn is_italic(&self) -> bool {
+        self.italic
+    }
+
+    pub fn is_underline(&self) -> bool {
+        self.underline
+    }
+
+    /// Whether the style changes nothing.
+    pub fn is_plain(&self) -> bool {
+        *self == Self::PLAIN
+    }
+
+    /// Wrap text in this style's escape codes, whether or not the terminal
+    /// has color. Prefer [`Term::style`](crate::Term::style), which checks.
+    pub fn paint(&self, text: &str) -> String {
+        if text.is_empty() || self.is_plain() {
+            return text.to_string();
+        }
+
+        let codes: Vec<String> = [
+            self.bold.then_some(1),
+            self.italic.then_some(3),
+            self.underline.then_some(4),
+            self.color.map(Color::code),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|code| code.to_string())
+        .collect();
+
+        format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text)
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_plain() {
+            return write!(f, "none");
+        }
+
+        let words: Vec<&str> = [
+            self.bold.then_some("bold"),
+            self.italic.then_some("italic"),
+            self.underline.then_some("underline"),
+            self.color.map(Color::name),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        write!(f, "{}", words.join(" "))
+    }
+}
+
+impl std::str::FromStr for Style {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut style = Style::new();
+
+        for word in s.split_whitespace() {
+            style = match word.to_lowercase().as_str() {
+                "none" | "plain" => style,
+                "bold" => style.bold(),
+                "italic" => style.italic(),
+                "underline" => style.underline(),
+                "grey" | "dim" => style.fg(Color::Gray),
+                name => match Color::ALL.iter().find(|(_, known)| *known == name) {
+                    Some((color, _)) => style.fg(*color),
+                    None => return Err(format!("Invalid style: {}", s)),
+                },
+            };
+        }
+
+        Ok(style)
+    }
+}
+
+/// Styles for each role output can play.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Finished work and passing checks
+    pub success: Style,
+    /// Warnings and skipped work
+    pub warning: Style,
+    /// Failures
+    pub error: Style,
+    /// Titles and table headers
+    pub heading: Style,
+    /// Highlights such as spinner frames and prompt answers
+    pub info: Style,
+    /// Secondary text such as hints and unfilled bar segments
+    pub muted: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            success: Style::GREEN,
+            warning: Style::YELLOW,
+            error: Style::RED,
+            heading: Style::BOLD,
+            info: Style::CYAN,
+            muted: Style::GRAY,
+        }
+    }
+}
+
+impl Theme {
+    /// A theme from the config's `[theme]` section.
+    pub fn from_config(config: &ThemeConfig) -> AppResult<Self> {
+        let parse = |key: &str, value: &str| {
+            value
+                .parse::<Style>()
+                .map_err(|message| TramError::InvalidConfig {
+                    message: format!("theme.{}: {}", key, message),
+                })
+        };
+
+        Ok(Self {
+            success: parse("success", &config.success)?,
+            warning: parse("warning", &config.warning)?,
+            error: parse("error", &config.error)?,
+            heading: parse("heading", &config.heading)?,
+            info: parse("info", &config.info)?,
+            muted: parse("muted", &config.muted)?,
+        })
+    }
+}
+
+/// Use `theme` for everything drawn from now on.
+pub fn init_theme(theme: Theme) {
+    if let Ok(mut current) = THEME.write() {
+        *current = Some(theme);
+    }
+}
+
+/// The theme for this run: the one passed to [`init_theme`], or the default.
+pub fn theme() -> Theme {
+    THEME
+        .read()
+        .ok()
+        .and_then(|current| *current)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display_styles() {
+        let style: Style = "Bold  green".parse().unwrap();
+
+        assert_eq!(style, Style::GREEN.bold());
+        assert_eq!(style.to_string(), "bold green");
+        assert_eq!("none".parse::<Style>(), Ok(Style::PLAIN));
+        assert_eq!("grey".parse::<Style>(), Ok(Style::GRAY));
+        assert_eq!(
+            "bold sparkly".parse::<Style>(),
+            Err("Invalid style: bold sparkly".to_string())
+        );
+    }
+
+    #[test]
+    fn test_paint_combines_codes() {
+        assert_eq!(Style::RED.paint("x"), "\x1b[31mx\x1b[0m");
+        assert_eq!(
+            Style::CYAN.bold().underline().paint("x"),
+            "\x1b[1;4;36mx\x1b[0m"
+        );
+        assert_eq!(Style::PLAIN.paint("x"), "x");
+        assert_eq!(Style::BOLD.paint(""), "");
+    }
+
+    #[test]
+    fn test_theme_from_config() {
+        let config = ThemeConfig {
+            success: "bold blue".into(),
+            warning: "yellow".into(),
+            error: "red".into(),
+            heading: "underline".into(),
+            info: "magenta".into(),
+            muted: "none".into(),
+        };
+
+        let theme = Theme::from_config(&config).unwrap();
+        assert_eq!(theme.success, Style::BLUE.bold());
+        assert_eq!(theme.heading, Style::new().underline());
+        assert_eq!(theme.muted, Style::PLAIN);
+
+        let invalid = ThemeConfig {
+            error: "scarlet".into(),
+            ..config
+        };
+        assert!(
+            Theme::from_config(&invalid)
+                .unwrap_err()
+                .to_string()
+                .contains("theme.error: Invalid style: scarlet")
+        );
+    }
+}
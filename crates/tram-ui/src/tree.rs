@@ -0,0 +1,339 @@
+//! Tree rendering for hierarchical output.
+//!
+//! [`Tree`] draws one or more [`TreeNode`]s with Unicode or ASCII branches,
+//! the way `tree` lists a directory. Nodes can be styled when color is
+//! enabled, and children can be loaded lazily, e.g. reading a directory only
+//! when the tree is drawn that deep. Past [`Tree::with_max_depth`], nodes
+//! that have or may have children end in `…` and lazy children aren't
+//! loaded at all.
+
+use crate::table::BorderStyle;
+use crate::theme::Style;
+use std::fmt;
+use std::sync::Arc;
+
+/// Marker for a node whose children are past the maximum depth.
+const COLLAPSED: &str = "…";
+
+/// Characters used to draw branches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TreeStyle {
+    /// `├──`, `└──`, and `│`
+    #[default]
+    Unicode,
+    /// `|--`, `` `-- ``, and `|`, for terminals without Unicode support
+    Ascii,
+}
+
+impl TreeStyle {
+    /// Branch for a child, for its last sibling, and the continuation below
+    /// a child that has later siblings.
+    fn branches(self) -> [&'static str; 3] {
+        match self {
+            TreeStyle::Unicode => ["├── ", "└── ", "│   "],
+            TreeStyle::Ascii => ["|-- ", "`-- ", "|   "],
+        }
+    }
+}
+
+impl From<BorderStyle> for TreeStyle {
+    /// Match the configured table borders: ASCII tables get ASCII trees.
+    fn from(border: BorderStyle) -> Self {
+        match border {
+            BorderStyle::Ascii => TreeStyle::Ascii,
+            BorderStyle::Unicode | BorderStyle::None => TreeStyle::Unicode,
+        }
+    }
+}
+
+type Loader = Arc<dyn Fn() -> Vec<TreeNode> + Send + Sync>;
+
+#[derive(Clone)]
+enum Children {
+    Loaded(Vec<TreeNode>),
+    Lazy(Loader),
+}
+
+/// A labeled node and its children.
+#[derive(Clone)]
+pub struct TreeNode {
+    label: String,
+    style: Option<Style>,
+    children: Children,
+}
+
+impl TreeNode {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            style: None,
+            children: Children::Loaded(Vec::new()),
+        }
+    }
+
+    /// A node whose children are loaded by `load` when they're first needed.
+    pub fn lazy(
+        label: impl Into<String>,
+        load: impl Fn() -> Vec<TreeNode> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            children: Children::Lazy(Arc::new(load)),
+            ..Self::new(label)
+        }
+    }
+
+    /// Draw the label in `style` when color is enabled.
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    pub fn with_child(mut self, child: TreeNode) -> Self {
+        self.add_child(child);
+        self
+    }
+
+    pub fn with_children(mut self, children: impl IntoIterator<Item = TreeNode>) -> Self {
+        for child in children {
+            self.add_child(child);
+        }
+        self
+    }
+
+    /// Add a child after the existing ones, loading lazy children first.
+    pub fn add_child(&mut self, child: TreeNode) {
+        self.expand().push(child);
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Whether the children have been loaded.
+    pub fn is_expanded(&self) -> bool {
+        matches!(self.children, Children::Loaded(_))
+    }
+
+    /// Load lazy children, keeping them for later renders, and return them.
+    pub fn expand(&mut self) -> &mut Vec<TreeNode> {
+        if let Children::Lazy(load) = &self.children {
+            self.children = Children::Loaded(load());
+        }
+
+        match &mut self.children {
+            Children::Loaded(children) => children,
+            Children::Lazy(_) => unreachable!("children were just loaded"),
+        }
+    }
+
+    /// Children to draw, loading lazy ones without keeping them.
+    fn children(&self) -> std::borrow::Cow<'_, [TreeNode]> {
+        match &self.children {
+            Children::Loaded(children) => children.as_slice().into(),
+            Children::Lazy(load) => load().into(),
+        }
+    }
+
+    /// Whether there's anything to hide when collapsed.
+    fn may_have_children(&self) -> bool {
+        match &self.children {
+            Children::Loaded(children) => !children.is_empty(),
+            Children::Lazy(_) => true,
+        }
+    }
+}
+
+impl fmt::Debug for TreeNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("TreeNode");
+        debug
+            .field("label", &self.label)
+            .field("style", &self.style);
+        match &self.children {
+            Children::Loaded(children) => debug.field("children", children),
+            Children::Lazy(_) => debug.field("children", &"<lazy>"),
+        };
+        debug.finish()
+    }
+}
+
+/// One or more trees, drawn one after another.
+#[derive(Debug, Clone, Default)]
+pub struct Tree {
+    pub roots: Vec<TreeNode>,
+    pub style: TreeStyle,
+    /// Levels below the roots to draw; deeper nodes are collapsed
+    pub max_depth: Option<usize>,
+    /// Apply node styles
+    pub color: bool,
+}
+
+impl Tree {
+    pub fn new(root: TreeNode) -> Self {
+        Self::from_roots([root])
+    }
+
+    pub fn from_roots(roots: impl IntoIterator<Item = TreeNode>) -> Self {
+        Self {
+            roots: roots.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_style(mut self, style: impl Into<TreeStyle>) -> Self {
+        self.style = style.into();
+        self
+    }
+
+    pub fn with_max_depth(mut self, depth: Option<usize>) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Whether there's nothing to draw.
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// Render the trees, without a trailing newline.
+    pub fn render(&self) -> String {
+        let mut lines = Vec::new();
+
+        for root in &self.roots {
+            lines.push(self.label(root, 0));
+            self.push_children(&mut lines, root, "", 1);
+        }
+
+        lines.join("\n")
+    }
+
+    fn push_children(&self, lines: &mut Vec<String>, node: &TreeNode, prefix: &str, depth: usize) {
+        if self.max_depth.is_some_and(|max| depth > max) {
+            return;
+        }
+
+        let [branch, last_branch, continuation] = self.style.branches();
+        let children = node.children();
+
+        for (index, child) in children.iter().enumerate() {
+            let last = index + 1 == children.len();
+            lines.push(format!(
+                "{}{}{}",
+                prefix,
+                if last { last_branch } else { branch },
+                self.label(child, depth)
+            ));
+
+            let indent = if last { "    " } else { continuation };
+            self.push_children(lines, child, &format!("{}{}", prefix, indent), depth + 1);
+        }
+    }
+
+    fn label(&self, node: &TreeNode, depth: usize) -> String {
+        let label = match node.style.filter(|_| self.color) {
+            Some(style) => style.paint(&node.label),
+            None => node.label.clone(),
+        };
+
+        let collapsed = self.max_depth.is_some_and(|max| depth >= max) && node.may_have_children();
+        if collapsed {
+            format!("{} {}", label, COLLAPSED)
+        } else {
+            label
+        }
+    }
+}
+
+impl fmt::Display for Tree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn sample() -> TreeNode {
+        TreeNode::new("tram")
+            .with_child(
+                TreeNode::new("crates")
+                    .with_child(TreeNode::new("tram-core"))
+                    .with_child(TreeNode::new("tram-ui")),
+            )
+            .with_child(TreeNode::new("src").with_child(TreeNode::new("main.rs")))
+            .with_child(TreeNode::new("Cargo.toml"))
+    }
+
+    #[test]
+    fn test_unicode_branches() {
+        assert_eq!(
+            Tree::new(sample()).render(),
+            [
+                "tram",
+                "├── crates",
+                "│   ├── tram-core",
+                "│   └── tram-ui",
+                "├── src",
+                "│   └── main.rs",
+                "└── Cargo.toml",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_ascii_branches_and_several_roots() {
+        let tree = Tree::from_roots([
+            TreeNode::new("a").with_child(TreeNode::new("b").with_child(TreeNode::new("c"))),
+            TreeNode::new("d"),
+        ])
+        .with_style(BorderStyle::Ascii);
+
+        assert_eq!(tree.render(), ["a", "`-- b", "    `-- c", "d"].join("\n"));
+    }
+
+    #[test]
+    fn test_lazy_children_load_only_when_drawn() {
+        let loads = Arc::new(AtomicUsize::new(0));
+        let counter = loads.clone();
+        let node = TreeNode::new("root").with_child(TreeNode::lazy("dir", move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            vec![TreeNode::new("file")]
+        }));
+
+        let shallow = Tree::new(node.clone()).with_max_depth(Some(1));
+        assert_eq!(shallow.render(), "root\n└── dir …");
+        assert_eq!(loads.load(Ordering::SeqCst), 0);
+
+        let mut deep = Tree::new(node);
+        assert_eq!(deep.render(), "root\n└── dir\n    └── file");
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+        let dir = &mut deep.roots[0].expand()[0];
+        assert!(!dir.is_expanded());
+        assert_eq!(dir.expand()[0].label(), "file");
+        assert!(dir.is_expanded());
+        deep.render();
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_node_styles_only_with_color() {
+        let node = TreeNode::new("run")
+            .with_child(TreeNode::new("failed").with_style(Style::RED))
+            .with_style(Style::BOLD);
+
+        assert_eq!(Tree::new(node.clone()).render(), "run\n└── failed");
+        assert_eq!(
+            Tree::new(node).with_color(true).render(),
+            "\x1b[1mrun\x1b[0m\n└── \x1b[31mfailed\x1b[0m"
+        );
+    }
+}
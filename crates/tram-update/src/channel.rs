@@ -0,0 +1,108 @@
+//! Release channels.
+//!
+//! The stable channel only sees releases without a pre-release suffix; the
+//! beta channel also sees pre-releases such as `1.2.0-beta.1` and picks
+//! whichever version is highest.
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use tram_core::TramError;
+
+/// Which releases an update may install.
+#[derive(
+    clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    /// Releases without a pre-release suffix
+    #[default]
+    Stable,
+    /// Pre-releases as well as stable releases
+    Beta,
+}
+
+impl Channel {
+    /// The channel a version was released on.
+    pub fn of(version: &Version) -> Self {
+        if version.pre.is_empty() {
+            Self::Stable
+        } else {
+            Self::Beta
+        }
+    }
+
+    /// The channel to update from: the one asked for, or otherwise the one
+    /// the running version came from, so beta users stay on betas.
+    pub fn resolve(requested: Option<Channel>, current: &Version) -> Self {
+        requested.unwrap_or_else(|| Self::of(current))
+    }
+
+    /// Whether releases of `version` are published on this channel.
+    pub fn includes(self, version: &Version) -> bool {
+        match self {
+            Self::Stable => version.pre.is_empty(),
+            Self::Beta => true,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Stable => "stable",
+            Self::Beta => "beta",
+        }
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Channel {
+    type Err = TramError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "stable" => Ok(Self::Stable),
+            "beta" => Ok(Self::Beta),
+            _ => Err(TramError::InvalidInput {
+                input: format!("release channel '{}'", s),
+                message: "expected stable or beta".to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channels_include_prereleases_on_beta() {
+        let beta = Version::parse("0.3.0-beta.2").unwrap();
+        let stable = Version::new(0, 2, 0);
+
+        assert!(Channel::Stable.includes(&stable));
+        assert!(!Channel::Stable.includes(&beta));
+        assert!(Channel::Beta.includes(&stable));
+        assert!(Channel::Beta.includes(&beta));
+    }
+
+    #[test]
+    fn test_resolve_follows_running_version() {
+        let beta = Version::parse("0.3.0-beta.1").unwrap();
+        let stable = Version::new(0, 2, 0);
+
+        assert_eq!(Channel::resolve(None, &beta), Channel::Beta);
+        assert_eq!(Channel::resolve(None, &stable), Channel::Stable);
+        assert_eq!(
+            Channel::resolve(Some(Channel::Stable), &beta),
+            Channel::Stable
+        );
+        assert_eq!("Beta".parse::<Channel>().unwrap(), Channel::Beta);
+        assert!("nightly".parse::<Channel>().is_err());
+    }
+}
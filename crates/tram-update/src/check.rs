@@ -0,0 +1,265 @@
+//! Opt-in checks for newer releases of the CLI.
+//!
+//! The [`UpdateChecker`] asks a [`ReleaseSource`] (crates.io or GitHub releases)
+//! for the latest published version at most once per interval, remembers the
+//! answer in the [`StateStore`], and reports when a newer version exists so the
+//! CLI can print a short notice.
+
+use crate::channel::Channel;
+use crate::source::{ReleaseSource, parse_version};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tram_core::{AppResult, StateStore};
+
+/// State key under which update check results are stored.
+const STATE_KEY: &str = "updateCheck";
+
+/// Default time between network checks.
+pub const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Result of a check that found a newer version.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdateInfo {
+    pub current: Version,
+    pub latest: Version,
+}
+
+impl UpdateInfo {
+    /// Short, non-intrusive notice suitable for printing at shutdown.
+    pub fn notice(&self, app_name: &str) -> String {
+        tram_core::t!(
+            "update-available",
+            app = app_name,
+            current = self.current.to_string(),
+            latest = self.latest.to_string()
+        )
+    }
+}
+
+/// Persisted result of the last network check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateCheckState {
+    last_checked: u64,
+    latest_version: Option<String>,
+    #[serde(default)]
+    channel: Channel,
+}
+
+/// Throttled checker for newer releases.
+pub struct UpdateChecker {
+    current: Version,
+    source: Box<dyn ReleaseSource>,
+    state: StateStore,
+    channel: Channel,
+    interval: Duration,
+}
+
+impl UpdateChecker {
+    /// Create a checker for the given current version and release source,
+    /// following the channel the current version was released on.
+    pub fn new(
+        current_version: &str,
+        source: impl ReleaseSource + 'static,
+        state: StateStore,
+    ) -> AppResult<Self> {
+        let current = parse_version(current_version)?;
+
+        Ok(Self {
+            channel: Channel::of(&current),
+            current,
+            source: Box::new(source),
+            state,
+            interval: DEFAULT_CHECK_INTERVAL,
+        })
+    }
+
+    /// Look for releases on this channel instead.
+    pub fn with_channel(mut self, channel: Channel) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    /// Override how often the release source is queried.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Check for a newer version.
+    ///
+    /// The release source is only queried when the last check is older than
+    /// the configured interval or was for another channel; otherwise the
+    /// remembered result is reused. A failed lookup counts as a check too, so
+    /// an offline machine isn't asked again until the interval has passed.
+    pub fn check(&self) -> AppResult<Option<UpdateInfo>> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        let cached = self
+            .state
+            .get::<UpdateCheckState>(STATE_KEY)?
+            .filter(|state| state.channel == self.channel);
+        let due = cached
+            .as_ref()
+            .is_none_or(|state| now.saturating_sub(state.last_checked) >= self.interval.as_secs());
+
+        let latest = if due {
+            tracing::debug!(
+                "Checking {} for a newer {} release",
+                self.source.name(),
+                self.channel
+            );
+
+            let latest = match self.source.latest_version(self.channel) {
+                Ok(latest) => latest,
+                Err(e) => {
+                    self.state.set(
+                        STATE_KEY,
+                        &UpdateCheckState {
+                            last_checked: now,
+                            latest_version: cached.and_then(|state| state.latest_version),
+                            channel: self.channel,
+                        },
+                    )?;
+                    return Err(e);
+                }
+            };
+            self.state.set(
+                STATE_KEY,
+                &UpdateCheckState {
+                    last_checked: now,
+                    latest_version: Some(latest.to_string()),
+                    channel: self.channel,
+                },
+            )?;
+            Some(latest)
+        } else {
+            cached
+                .and_then(|state| state.latest_version)
+                .and_then(|v| Version::parse(&v).ok())
+        };
+
+        Ok(latest
+            .filter(|latest| *latest > self.current)
+            .map(|latest| UpdateInfo {
+                current: self.current.clone(),
+                latest,
+            }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    struct FixedSource {
+        stable: &'static str,
+        beta: &'static str,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl FixedSource {
+        fn new(version: &'static str) -> Self {
+            Self {
+                stable: version,
+                beta: version,
+                calls: Arc::new(AtomicUsize::new(0)),
+            }
+        }
+    }
+
+    impl ReleaseSource for FixedSource {
+        fn name(&self) -> String {
+            "fixed".into()
+        }
+
+        fn latest_version(&self, channel: Channel) -> AppResult<Version> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            parse_version(match channel {
+                Channel::Stable => self.stable,
+                Channel::Beta => self.beta,
+            })
+        }
+    }
+
+    #[test]
+    fn test_reports_newer_version_and_throttles() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = FixedSource::new("v0.2.0");
+        let calls = Arc::clone(&source.calls);
+        let state = StateStore::new(temp_dir.path().join("state.json"));
+        let checker = UpdateChecker::new("0.1.0", source, state).unwrap();
+
+        let info = checker.check().unwrap().expect("should find an update");
+        assert_eq!(info.latest, Version::new(0, 2, 0));
+        assert!(info.notice("tram").contains("0.1.0 -> 0.2.0"));
+
+        // Second check within the interval reuses the stored result
+        assert!(checker.check().unwrap().is_some());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_failed_lookup_is_not_retried_within_the_interval() {
+        struct OfflineSource(Arc<AtomicUsize>);
+
+        impl ReleaseSource for OfflineSource {
+            fn name(&self) -> String {
+                "offline".into()
+            }
+
+            fn latest_version(&self, _: Channel) -> AppResult<Version> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Err(tram_core::TramError::Update {
+                    message: "no network".into(),
+                }
+                .into())
+            }
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let state = StateStore::new(temp_dir.path().join("state.json"));
+        let checker =
+            UpdateChecker::new("0.1.0", OfflineSource(Arc::clone(&calls)), state).unwrap();
+
+        assert!(checker.check().is_err());
+        assert_eq!(checker.check().unwrap(), None);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_no_update_when_current() {
+        let temp_dir = TempDir::new().unwrap();
+        let state = StateStore::new(temp_dir.path().join("state.json"));
+        let checker = UpdateChecker::new("0.1.0", FixedSource::new("0.1.0"), state).unwrap();
+
+        assert_eq!(checker.check().unwrap(), None);
+    }
+
+    #[test]
+    fn test_channel_change_checks_again() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.json");
+        let source = || FixedSource {
+            beta: "0.2.0-beta.1",
+            ..FixedSource::new("0.1.0")
+        };
+
+        let stable = UpdateChecker::new("0.1.0", source(), StateStore::new(&state_path)).unwrap();
+        assert_eq!(stable.check().unwrap(), None);
+
+        let beta = UpdateChecker::new("0.1.0", source(), StateStore::new(&state_path))
+            .unwrap()
+            .with_channel(Channel::Beta);
+        let info = beta.check().unwrap().expect("should find a beta");
+        assert_eq!(info.latest.to_string(), "0.2.0-beta.1");
+    }
+}
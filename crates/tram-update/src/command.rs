@@ -0,0 +1,53 @@
+//! A ready-made `self-update` subcommand.
+//!
+//! Add [`SelfUpdateArgs`] to your CLI and run it with a [`SelfUpdater`]:
+//!
+//! ```ignore
+//! #[derive(clap::Subcommand)]
+//! enum Commands {
+//!     /// Update mycli to the latest release
+//!     SelfUpdate(tram_update::SelfUpdateArgs),
+//! }
+//!
+//! Commands::SelfUpdate(args) => {
+//!     let updater = SelfUpdater::github(
+//!         "mycli",
+//!         env!("CARGO_PKG_VERSION"),
+//!         env!("CARGO_PKG_REPOSITORY"),
+//!         HttpClient::new(),
+//!     )?;
+//!     args.run(updater)?
+//! }
+//! ```
+
+use crate::channel::Channel;
+use crate::updater::{SelfUpdateOutcome, SelfUpdater};
+use tram_core::AppResult;
+
+/// Options for a `self-update` subcommand.
+#[derive(clap::Args, Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelfUpdateArgs {
+    /// Release channel to update from [default: the running version's]
+    #[arg(long, value_enum)]
+    pub channel: Option<Channel>,
+
+    /// Only report whether a newer release exists
+    #[arg(long)]
+    pub check: bool,
+}
+
+impl SelfUpdateArgs {
+    /// Apply these options to `updater` and run it.
+    pub fn run(&self, updater: SelfUpdater) -> AppResult<SelfUpdateOutcome> {
+        let updater = match self.channel {
+            Some(channel) => updater.with_channel(channel),
+            None => updater,
+        };
+
+        if self.check {
+            updater.check_outcome()
+        } else {
+            updater.update()
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Release downloads for self-update, through an [`HttpClient`].
+
+use crate::channel::Channel;
+use crate::source::{BinaryReleaseSource, Release, ReleaseAsset, ReleaseSource};
+use crate::verify::{verify_checksum, verify_signature};
+use semver::Version;
+use tram_core::{AppResult, Cache, TramError};
+use tram_http::{Checksum, HttpClient, Verification};
+
+/// A release source whose assets are downloaded with an [`HttpClient`], so
+/// self-updates honor the `[http]` proxy and CA settings, retry failures,
+/// and show their progress. With a cache, verified binaries are kept there
+/// and reused if the same release is installed again.
+///
+/// Release lookups are left to the wrapped source; give it the same client
+/// (e.g. [`GitHubReleasesSource::with_client`](crate::GitHubReleasesSource::with_client))
+/// so they honor the settings too.
+#[derive(Debug)]
+pub struct HttpReleaseSource<S> {
+    source: S,
+    client: HttpClient,
+    cache: Option<Cache>,
+}
+
+impl<S: BinaryReleaseSource> HttpReleaseSource<S> {
+    pub fn new(source: S, client: HttpClient) -> Self {
+        Self {
+            source,
+            client,
+            cache: None,
+        }
+    }
+
+    /// Download binaries into `cache` with [`HttpClient::download_verified`].
+    pub fn with_cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+}
+
+impl<S: BinaryReleaseSource> ReleaseSource for HttpReleaseSource<S> {
+    fn name(&self) -> String {
+        self.source.name()
+    }
+
+    fn latest_version(&self, channel: Channel) -> AppResult<Version> {
+        self.source.latest_version(channel)
+    }
+}
+
+impl<S: BinaryReleaseSource> BinaryReleaseSource for HttpReleaseSource<S> {
+    fn latest_release(&self, channel: Channel) -> AppResult<Release> {
+        self.source.latest_release(channel)
+    }
+
+    fn download(&self, asset: &ReleaseAsset) -> AppResult<Vec<u8>> {
+        self.client.get_bytes(&asset.url)
+    }
+
+    fn download_verified(
+        &self,
+        asset: &ReleaseAsset,
+        checksum: &str,
+        signature: Option<(&str, &str)>,
+    ) -> AppResult<Vec<u8>> {
+        let Some(cache) = &self.cache else {
+            let data = self.download(asset)?;
+            verify_checksum(&data, checksum)?;
+            if let Some((signature, public_key)) = signature {
+                verify_signature(&data, signature, public_key)?;
+            }
+            return Ok(data);
+        };
+
+        let mut verification = Verification::new(Checksum::sha256_from_file(checksum));
+        if let Some((signature, public_key)) = signature {
+            verification = verification.with_minisign(signature, public_key);
+        }
+
+        let path = self
+            .client
+            .download_verified(&asset.url, verification, cache)?;
+        std::fs::read(&path).map_err(|e| {
+            TramError::Cache {
+                message: format!("Failed to read {}: {}", path.display(), e),
+            }
+            .into()
+        })
+    }
+}
@@ -0,0 +1,171 @@
+//! Replacing an executable with rollback.
+//!
+//! A new binary is first staged next to the target, with the target's
+//! permissions, so a failed write never touches the installed one. Installing
+//! renames the original to a backup, moves the staged binary into place, and
+//! runs it with `--version`; if any step fails the backup is put back.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tram_core::{AppResult, TramError};
+
+/// A new executable written next to the one it will replace.
+///
+/// The staged file is removed if it is dropped without being installed.
+#[derive(Debug)]
+pub struct StagedExecutable {
+    target: PathBuf,
+    staged: PathBuf,
+    backup: PathBuf,
+}
+
+impl StagedExecutable {
+    /// Write `contents` next to `target`, ready to replace it.
+    pub fn stage(target: &Path, contents: &[u8]) -> AppResult<Self> {
+        let file_name = target
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .ok_or_else(|| update_error(format!("Invalid executable path {}", target.display())))?;
+        let dir = target.parent().unwrap_or(Path::new("."));
+
+        let staged = Self {
+            target: target.to_path_buf(),
+            staged: dir.join(format!(".{}.new", file_name)),
+            backup: dir.join(format!(".{}.old", file_name)),
+        };
+
+        fs::write(&staged.staged, contents).map_err(|e| io_error("write", &staged.staged, e))?;
+
+        if let Ok(meta) = fs::metadata(target) {
+            fs::set_permissions(&staged.staged, meta.permissions())
+                .map_err(|e| io_error("set permissions on", &staged.staged, e))?;
+        }
+
+        Ok(staged)
+    }
+
+    /// The executable that will be replaced.
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+
+    /// Move the staged executable into place, restoring the original if it
+    /// can't be moved or doesn't run.
+    pub fn install(self) -> AppResult<()> {
+        let target = &self.target;
+
+        fs::rename(target, &self.backup).map_err(|e| io_error("back up", target, e))?;
+
+        let installed = fs::rename(&self.staged, target)
+            .map_err(|e| io_error("install", target, e))
+            .and_then(|_| smoke_test(target));
+
+        if let Err(e) = installed {
+            self.rollback().map_err(|restore| {
+                update_error(format!(
+                    "{}; restoring the previous version also failed ({}), it is at {}",
+                    e,
+                    restore,
+                    self.backup.display()
+                ))
+            })?;
+
+            return Err(e.into());
+        }
+
+        // Windows can't delete a running executable; the backup is replaced next time
+        let _ = fs::remove_file(&self.backup);
+
+        Ok(())
+    }
+
+    /// Put the backup of the original back in place.
+    fn rollback(&self) -> std::io::Result<()> {
+        let _ = fs::remove_file(&self.target);
+        fs::rename(&self.backup, &self.target)
+    }
+}
+
+impl Drop for StagedExecutable {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.staged);
+    }
+}
+
+/// Atomically replace an executable, restoring the original on failure.
+pub fn replace_executable(target: &Path, contents: &[u8]) -> AppResult<()> {
+    StagedExecutable::stage(target, contents)?.install()
+}
+
+/// Make sure a freshly installed binary actually runs.
+fn smoke_test(path: &Path) -> Result<(), TramError> {
+    let output = Command::new(path)
+        .arg("--version")
+        .output()
+        .map_err(|e| update_error(format!("New version failed to start: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(update_error(format!(
+            "New version failed to run ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(())
+}
+
+fn io_error(action: &str, path: &Path, e: std::io::Error) -> TramError {
+    update_error(format!("Failed to {} {}: {}", action, path.display(), e))
+}
+
+fn update_error(message: String) -> TramError {
+    TramError::Update { message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_dropped_stage_leaves_target_alone() {
+        let temp_dir = TempDir::new().unwrap();
+        let exe = temp_dir.path().join("tram");
+        fs::write(&exe, "old").unwrap();
+
+        let staged = StagedExecutable::stage(&exe, b"new").unwrap();
+        assert_eq!(staged.target(), exe);
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+        drop(staged);
+
+        assert_eq!(fs::read_to_string(&exe).unwrap(), "old");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_replace_executable_and_rollback() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let exe = temp_dir.path().join("tram");
+        fs::write(&exe, "#!/bin/sh\necho old\n").unwrap();
+        fs::set_permissions(&exe, fs::Permissions::from_mode(0o755)).unwrap();
+
+        // A new binary that runs is swapped in and keeps the permissions
+        replace_executable(&exe, b"#!/bin/sh\necho tram 0.2.0\n").unwrap();
+        assert!(fs::read_to_string(&exe).unwrap().contains("0.2.0"));
+        assert_eq!(
+            fs::metadata(&exe).unwrap().permissions().mode() & 0o777,
+            0o755
+        );
+
+        // A new binary that fails to run is rolled back
+        let err = replace_executable(&exe, b"#!/bin/sh\nexit 1\n").unwrap_err();
+        assert!(err.to_string().contains("New version failed to run"));
+        assert!(fs::read_to_string(&exe).unwrap().contains("0.2.0"));
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+}
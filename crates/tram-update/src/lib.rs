@@ -0,0 +1,38 @@
+//! Update checks and signed self-updates for Tram CLI applications.
+//!
+//! - [`UpdateChecker`] looks for newer releases at most once a day and
+//!   produces a short notice
+//! - [`SelfUpdater`] downloads the release asset for this [`Platform`],
+//!   verifies its SHA-256 checksum and, with a public key, its minisign
+//!   signature, and swaps it in for the running executable with rollback
+//! - [`Channel`] picks between stable releases and pre-releases; both
+//!   default to the channel of the running version
+//! - [`SelfUpdateArgs`] gives a downstream CLI a `mycli self-update`
+//!   subcommand with `--channel` and `--check`
+//!
+//! Releases come from a [`ReleaseSource`]: GitHub releases or crates.io,
+//! looked up and downloaded with a [`tram_http::HttpClient`] so they honor
+//! proxy, CA, and retry settings. [`HttpReleaseSource`] adds download
+//! progress and caching.
+//! Failures are [`TramError::Update`](tram_core::TramError::Update) or
+//! network errors, both exiting with the network exit code.
+
+pub mod channel;
+pub mod check;
+pub mod command;
+pub mod http;
+pub mod install;
+pub mod platform;
+pub mod source;
+pub mod updater;
+pub mod verify;
+
+pub use channel::*;
+pub use check::*;
+pub use command::*;
+pub use http::*;
+pub use install::*;
+pub use platform::*;
+pub use source::*;
+pub use updater::*;
+pub use verify::*;
@@ -0,0 +1,165 @@
+//! Picking the release asset built for a platform.
+//!
+//! Release tooling names binaries in different ways, so several names are
+//! tried, most specific first:
+//!
+//! - `<app>-<os>-<arch>`, e.g. `tram-macos-aarch64` (Rust's own names)
+//! - the same with common aliases, e.g. `tram-darwin-arm64` or
+//!   `tram-linux-amd64`
+//! - `<app>-<target>`, with the Rust target triple, e.g.
+//!   `tram-x86_64-unknown-linux-gnu`
+//!
+//! Windows names end in `.exe`. The checksum and signature are the chosen
+//! asset's name plus `.sha256` and `.minisig`.
+
+use crate::source::{Release, ReleaseAsset};
+
+/// An operating system and CPU architecture, named as in
+/// [`std::env::consts`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Platform {
+    pub os: String,
+    pub arch: String,
+}
+
+impl Platform {
+    pub fn new(os: impl Into<String>, arch: impl Into<String>) -> Self {
+        Self {
+            os: os.into(),
+            arch: arch.into(),
+        }
+    }
+
+    /// The platform this binary was built for.
+    pub fn current() -> Self {
+        Self::new(std::env::consts::OS, std::env::consts::ARCH)
+    }
+
+    /// Suffix of executables on this platform.
+    pub fn exe_suffix(&self) -> &'static str {
+        if self.os == "windows" { ".exe" } else { "" }
+    }
+
+    /// Asset names that may hold `app` for this platform, most specific
+    /// first.
+    pub fn asset_names(&self, app: &str) -> Vec<String> {
+        let suffix = self.exe_suffix();
+        let mut names = Vec::new();
+
+        for os in os_aliases(&self.os) {
+            for arch in arch_aliases(&self.arch) {
+                names.push(format!("{}-{}-{}{}", app, os, arch, suffix));
+            }
+        }
+        for target in self.targets() {
+            names.push(format!("{}-{}{}", app, target, suffix));
+        }
+
+        names.dedup();
+        names
+    }
+
+    /// The asset in `release` built for this platform.
+    pub fn select<'a>(&self, app: &str, release: &'a Release) -> Option<&'a ReleaseAsset> {
+        self.asset_names(app)
+            .iter()
+            .find_map(|name| release.asset(name))
+    }
+
+    /// Rust target triples for this platform.
+    fn targets(&self) -> Vec<String> {
+        let arch = &self.arch;
+        match self.os.as_str() {
+            "linux" => vec![
+                format!("{}-unknown-linux-gnu", arch),
+                format!("{}-unknown-linux-musl", arch),
+            ],
+            "macos" => vec![format!("{}-apple-darwin", arch)],
+            "windows" => vec![
+                format!("{}-pc-windows-msvc", arch),
+                format!("{}-pc-windows-gnu", arch),
+            ],
+            os => vec![format!("{}-unknown-{}", arch, os)],
+        }
+    }
+}
+
+impl Default for Platform {
+    fn default() -> Self {
+        Self::current()
+    }
+}
+
+fn os_aliases(os: &str) -> Vec<&str> {
+    match os {
+        "macos" => vec!["macos", "darwin", "apple"],
+        "windows" => vec!["windows", "win"],
+        os => vec![os],
+    }
+}
+
+fn arch_aliases(arch: &str) -> Vec<&str> {
+    match arch {
+        "x86_64" => vec!["x86_64", "amd64", "x64"],
+        "aarch64" => vec!["aarch64", "arm64"],
+        "x86" => vec!["x86", "i686", "386"],
+        arch => vec![arch],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semver::Version;
+
+    fn release(names: &[&str]) -> Release {
+        Release {
+            version: Version::new(1, 0, 0),
+            assets: names
+                .iter()
+                .map(|name| ReleaseAsset {
+                    name: name.to_string(),
+                    url: format!("https://example.com/{}", name),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_asset_names_prefer_rust_names() {
+        let names = Platform::new("linux", "x86_64").asset_names("tram");
+
+        assert_eq!(names[0], "tram-linux-x86_64");
+        assert!(names.contains(&"tram-linux-amd64".to_string()));
+        assert!(names.contains(&"tram-x86_64-unknown-linux-musl".to_string()));
+        assert_eq!(
+            Platform::new("windows", "x86_64").asset_names("tram")[0],
+            "tram-windows-x86_64.exe"
+        );
+    }
+
+    #[test]
+    fn test_select_matches_aliases_and_targets() {
+        let mac = Platform::new("macos", "aarch64");
+        let linux = Platform::new("linux", "x86_64");
+        let release = release(&[
+            "tram-darwin-arm64",
+            "tram-darwin-arm64.sha256",
+            "tram-x86_64-unknown-linux-gnu",
+        ]);
+
+        assert_eq!(
+            mac.select("tram", &release).map(|a| a.name.as_str()),
+            Some("tram-darwin-arm64")
+        );
+        assert_eq!(
+            linux.select("tram", &release).map(|a| a.name.as_str()),
+            Some("tram-x86_64-unknown-linux-gnu")
+        );
+        assert!(
+            Platform::new("freebsd", "x86_64")
+                .select("tram", &release)
+                .is_none()
+        );
+    }
+}
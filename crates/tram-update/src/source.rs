@@ -0,0 +1,373 @@
+//! Where releases are published.
+//!
+//! A [`ReleaseSource`] knows the latest version on each [`Channel`]; a
+//! [`BinaryReleaseSource`] also lists the files attached to a release so
+//! they can be downloaded and installed.
+//!
+//! The built-in sources make their requests with an [`HttpClient`], the
+//! default one unless given another with `with_client`, such as one built
+//! from the `[http]` config section.
+
+use crate::channel::Channel;
+use crate::verify::{verify_checksum, verify_signature};
+use semver::Version;
+use tram_core::{AppResult, TramError};
+use tram_http::HttpClient;
+
+/// How many GitHub releases are searched for the latest pre-release.
+const GITHUB_RELEASES_PER_PAGE: usize = 30;
+
+/// The GitHub REST API, used unless a source is given another base URL.
+pub const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// A place where released versions are published.
+pub trait ReleaseSource: Send + Sync {
+    /// Human-readable name of the source, used in logs.
+    fn name(&self) -> String;
+
+    /// Look up the latest version published on `channel`.
+    fn latest_version(&self, channel: Channel) -> AppResult<Version>;
+}
+
+/// A file attached to a release.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub url: String,
+}
+
+/// A published release and its downloadable files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    pub version: Version,
+    pub assets: Vec<ReleaseAsset>,
+}
+
+impl Release {
+    /// Find an asset by file name.
+    pub fn asset(&self, name: &str) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+}
+
+/// A release source that publishes binaries.
+pub trait BinaryReleaseSource: ReleaseSource {
+    /// Look up the latest release on `channel` and its assets.
+    fn latest_release(&self, channel: Channel) -> AppResult<Release>;
+
+    /// Download an asset's contents.
+    fn download(&self, asset: &ReleaseAsset) -> AppResult<Vec<u8>>;
+
+    /// Download an asset and check it against a checksum file's contents
+    /// and, when given, a minisign signature and public key.
+    fn download_verified(
+        &self,
+        asset: &ReleaseAsset,
+        checksum: &str,
+        signature: Option<(&str, &str)>,
+    ) -> AppResult<Vec<u8>> {
+        let data = self.download(asset)?;
+        verify_checksum(&data, checksum)?;
+
+        if let Some((signature, public_key)) = signature {
+            verify_signature(&data, signature, public_key)?;
+        }
+
+        Ok(data)
+    }
+}
+
+/// Releases published to crates.io.
+#[derive(Debug, Clone)]
+pub struct CratesIoSource {
+    pub crate_name: String,
+    client: HttpClient,
+}
+
+impl CratesIoSource {
+    pub fn new(crate_name: impl Into<String>) -> Self {
+        Self {
+            crate_name: crate_name.into(),
+            client: HttpClient::new(),
+        }
+    }
+
+    /// Look up releases with `client`.
+    pub fn with_client(mut self, client: HttpClient) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+impl ReleaseSource for CratesIoSource {
+    fn name(&self) -> String {
+        format!("crates.io/{}", self.crate_name)
+    }
+
+    fn latest_version(&self, channel: Channel) -> AppResult<Version> {
+        let url = format!("https://crates.io/api/v1/crates/{}", self.crate_name);
+        let body: serde_json::Value = self.client.get_json(&url)?;
+
+        let version = match channel {
+            Channel::Stable => body.pointer("/crate/max_stable_version"),
+            Channel::Beta => body.pointer("/crate/max_version"),
+        }
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| unexpected_response(&url))?;
+
+        parse_version(version)
+    }
+}
+
+/// Releases published on GitHub.
+///
+/// The stable channel follows the release GitHub marks as latest, which is
+/// never a draft or pre-release. The beta channel picks the highest version
+/// among the most recent releases, pre-releases included.
+#[derive(Debug, Clone)]
+pub struct GitHubReleasesSource {
+    /// Repository in `owner/name` form
+    pub repository: String,
+    /// Base URL of the REST API, [`GITHUB_API_URL`] unless set for GitHub
+    /// Enterprise or a mirror
+    pub api_url: String,
+    client: HttpClient,
+}
+
+impl GitHubReleasesSource {
+    pub fn new(repository: impl Into<String>) -> Self {
+        Self {
+            repository: repository.into(),
+            api_url: GITHUB_API_URL.to_string(),
+            client: HttpClient::new(),
+        }
+    }
+
+    /// Look up releases and download their assets with `client`.
+    pub fn with_client(mut self, client: HttpClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Look up releases through another API, such as
+    /// `https://github.example.com/api/v3` for GitHub Enterprise.
+    pub fn with_api_url(mut self, url: impl Into<String>) -> Self {
+        self.api_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Build a source from a repository in `owner/name` form or a
+    /// `https://github.com/` URL.
+    pub fn parse(repository: &str) -> Option<Self> {
+        let repository = repository.trim();
+        if repository.contains("://") {
+            return Self::from_repository_url(repository);
+        }
+
+        match repository.split_once('/') {
+            Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => {
+                Some(Self::new(repository))
+            }
+            _ => None,
+        }
+    }
+
+    /// Build a source from a repository URL such as `CARGO_PKG_REPOSITORY`.
+    pub fn from_repository_url(url: &str) -> Option<Self> {
+        let path = url
+            .trim_end_matches('/')
+            .trim_end_matches(".git")
+            .strip_prefix("https://github.com/")?;
+
+        let mut parts = path.split('/');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(owner), Some(name), None) if !owner.is_empty() && !name.is_empty() => {
+                Some(Self::new(format!("{}/{}", owner, name)))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl ReleaseSource for GitHubReleasesSource {
+    fn name(&self) -> String {
+        if self.api_url == GITHUB_API_URL {
+            format!("github.com/{}", self.repository)
+        } else {
+            format!("{}/repos/{}", self.api_url, self.repository)
+        }
+    }
+
+    fn latest_version(&self, channel: Channel) -> AppResult<Version> {
+        self.latest_release(channel).map(|release| release.version)
+    }
+}
+
+impl BinaryReleaseSource for GitHubReleasesSource {
+    fn latest_release(&self, channel: Channel) -> AppResult<Release> {
+        match channel {
+            Channel::Stable => {
+                let url = format!("{}/repos/{}/releases/latest", self.api_url, self.repository);
+                Ok(github_release(&self.client.get_json(&url)?)
+                    .ok_or_else(|| unexpected_response(&url))?)
+            }
+            Channel::Beta => {
+                let url = format!(
+                    "{}/repos/{}/releases?per_page={}",
+                    self.api_url, self.repository, GITHUB_RELEASES_PER_PAGE
+                );
+                self.client
+                    .get_json::<serde_json::Value>(&url)?
+                    .as_array()
+                    .ok_or_else(|| unexpected_response(&url))?
+                    .iter()
+                    .filter(|release| release.get("draft").and_then(|v| v.as_bool()) != Some(true))
+                    .filter_map(github_release)
+                    .filter(|release| channel.includes(&release.version))
+                    .max_by(|a, b| a.version.cmp(&b.version))
+                    .ok_or_else(|| {
+                        TramError::Update {
+                            message: format!("No releases published on {}", self.name()),
+                        }
+                        .into()
+                    })
+            }
+        }
+    }
+
+    fn download(&self, asset: &ReleaseAsset) -> AppResult<Vec<u8>> {
+        self.client.get_bytes(&asset.url)
+    }
+}
+
+/// A release from a GitHub API release object.
+fn github_release(body: &serde_json::Value) -> Option<Release> {
+    let tag = body.get("tag_name")?.as_str()?;
+    let assets = body
+        .get("assets")
+        .and_then(|v| v.as_array())
+        .map(|assets| {
+            assets
+                .iter()
+                .filter_map(|asset| {
+                    Some(ReleaseAsset {
+                        name: asset.get("name")?.as_str()?.to_string(),
+                        url: asset.get("browser_download_url")?.as_str()?.to_string(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(Release {
+        version: parse_version(tag).ok()?,
+        assets,
+    })
+}
+
+/// Parse a version string, tolerating a leading `v` as used in git tags.
+pub fn parse_version(version: &str) -> AppResult<Version> {
+    Version::parse(version.trim().trim_start_matches('v')).map_err(|e| {
+        TramError::Network {
+            message: format!("Invalid version '{}': {}", version, e),
+        }
+        .into()
+    })
+}
+
+fn unexpected_response(url: &str) -> TramError {
+    TramError::Network {
+        message: format!("Unexpected response from {}", url),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_github_source_from_repository_url() {
+        let source =
+            GitHubReleasesSource::from_repository_url("https://github.com/moonrepo/tram").unwrap();
+        assert_eq!(source.repository, "moonrepo/tram");
+
+        assert!(GitHubReleasesSource::from_repository_url("https://example.com/a/b").is_none());
+    }
+
+    #[test]
+    fn test_github_source_parse() {
+        let source = GitHubReleasesSource::parse("moonrepo/tram").unwrap();
+        assert_eq!(source.repository, "moonrepo/tram");
+        assert_eq!(source.name(), "github.com/moonrepo/tram");
+        assert_eq!(
+            GitHubReleasesSource::parse("https://github.com/moonrepo/tram.git")
+                .unwrap()
+                .repository,
+            "moonrepo/tram"
+        );
+        assert!(GitHubReleasesSource::parse("tram").is_none());
+        assert!(GitHubReleasesSource::parse("moonrepo/tram/extra").is_none());
+
+        let enterprise = source.with_api_url("https://github.example.com/api/v3/");
+        assert_eq!(enterprise.api_url, "https://github.example.com/api/v3");
+        assert_eq!(
+            enterprise.name(),
+            "https://github.example.com/api/v3/repos/moonrepo/tram"
+        );
+    }
+
+    #[test]
+    fn test_github_release_parsing() {
+        let body = serde_json::json!({
+            "tag_name": "v0.3.0-beta.1",
+            "assets": [
+                {"name": "tram-linux-x86_64", "browser_download_url": "https://example.com/a"},
+                {"name": "missing-url"}
+            ]
+        });
+
+        let release = github_release(&body).unwrap();
+        assert_eq!(release.version.to_string(), "0.3.0-beta.1");
+        assert_eq!(release.assets.len(), 1);
+        assert!(github_release(&serde_json::json!({"tag_name": "nightly"})).is_none());
+    }
+
+    #[test]
+    fn test_github_source_uses_its_client() {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let api_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = String::new();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            while reader.read_line(&mut request).unwrap() > 2 && !request.ends_with("\r\n\r\n") {}
+
+            let body = r#"{"tag_name": "v1.2.3", "assets": []}"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+            request
+        });
+
+        let release = GitHubReleasesSource::new("moonrepo/tram")
+            .with_api_url(&api_url)
+            .with_client(HttpClient::new().with_user_agent("release-probe/1.0"))
+            .latest_release(Channel::Stable)
+            .unwrap();
+
+        assert_eq!(release.version.to_string(), "1.2.3");
+        let request = server.join().unwrap().to_lowercase();
+        assert!(request.starts_with("get /repos/moonrepo/tram/releases/latest"));
+        assert!(
+            request.contains("user-agent: release-probe/1.0"),
+            "{}",
+            request
+        );
+    }
+}
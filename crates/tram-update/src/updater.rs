@@ -0,0 +1,389 @@
+//! Replacing the running executable with the latest release.
+
+use crate::channel::Channel;
+use crate::http::HttpReleaseSource;
+use crate::install::StagedExecutable;
+use crate::platform::Platform;
+use crate::source::{BinaryReleaseSource, GitHubReleasesSource, Release, parse_version};
+use semver::Version;
+use std::path::PathBuf;
+use tram_core::{AppResult, TramError};
+use tram_http::HttpClient;
+
+/// What [`SelfUpdater::update`] did.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelfUpdateOutcome {
+    /// The running version is already the latest
+    UpToDate { version: Version },
+    /// A newer version exists but was not installed
+    Available { current: Version, latest: Version },
+    /// The executable was replaced
+    Updated {
+        from: Version,
+        to: Version,
+        path: PathBuf,
+    },
+}
+
+/// Downloads, verifies, and installs new releases of the running CLI.
+///
+/// The release comes from the updater's [`Channel`], which defaults to the
+/// channel of the running version. The asset for the [`Platform`] is
+/// checked against its `.sha256` checksum and, when a public key is set,
+/// its `.minisig` signature, then staged and swapped in with rollback.
+pub struct SelfUpdater {
+    app_name: String,
+    current: Version,
+    source: Box<dyn BinaryReleaseSource>,
+    channel: Option<Channel>,
+    platform: Platform,
+    public_key: Option<String>,
+    executable: Option<PathBuf>,
+}
+
+impl SelfUpdater {
+    pub fn new(
+        app_name: impl Into<String>,
+        current_version: &str,
+        source: impl BinaryReleaseSource + 'static,
+    ) -> AppResult<Self> {
+        Ok(Self {
+            app_name: app_name.into(),
+            current: parse_version(current_version)?,
+            source: Box::new(source),
+            channel: None,
+            platform: Platform::current(),
+            public_key: None,
+            executable: None,
+        })
+    }
+
+    /// An updater for releases of a GitHub repository, given by URL such as
+    /// `env!("CARGO_PKG_REPOSITORY")`, downloaded with `client`.
+    pub fn github(
+        app_name: impl Into<String>,
+        current_version: &str,
+        repository_url: &str,
+        client: HttpClient,
+    ) -> AppResult<Self> {
+        let source =
+            GitHubReleasesSource::from_repository_url(repository_url).ok_or_else(|| {
+                TramError::Update {
+                    message: format!("'{}' is not a GitHub repository URL", repository_url),
+                }
+            })?;
+
+        Self::new(
+            app_name,
+            current_version,
+            HttpReleaseSource::new(source.with_client(client.clone()), client),
+        )
+    }
+
+    /// Update from this channel instead of the running version's.
+    pub fn with_channel(mut self, channel: Channel) -> Self {
+        self.channel = Some(channel);
+        self
+    }
+
+    /// Install binaries built for this platform instead of the current one.
+    pub fn with_platform(mut self, platform: Platform) -> Self {
+        self.platform = platform;
+        self
+    }
+
+    /// Require a minisign signature made with this base64 public key.
+    pub fn with_public_key(mut self, key: impl Into<String>) -> Self {
+        self.public_key = Some(key.into());
+        self
+    }
+
+    /// Replace this executable instead of the running one.
+    pub fn with_executable(mut self, path: impl Into<PathBuf>) -> Self {
+        self.executable = Some(path.into());
+        self
+    }
+
+    /// The channel releases are looked up on.
+    pub fn channel(&self) -> Channel {
+        Channel::resolve(self.channel, &self.current)
+    }
+
+    /// The running version.
+    pub fn current_version(&self) -> &Version {
+        &self.current
+    }
+
+    /// The latest release, if it is newer than the running version.
+    pub fn check(&self) -> AppResult<Option<Release>> {
+        let release = self.source.latest_release(self.channel())?;
+
+        Ok((release.version > self.current).then_some(release))
+    }
+
+    /// Report whether a newer release exists, without installing it.
+    pub fn check_outcome(&self) -> AppResult<SelfUpdateOutcome> {
+        Ok(match self.check()? {
+            Some(release) => SelfUpdateOutcome::Available {
+                current: self.current.clone(),
+                latest: release.version,
+            },
+            None => SelfUpdateOutcome::UpToDate {
+                version: self.current.clone(),
+            },
+        })
+    }
+
+    /// Install the latest release if it is newer than the running version.
+    pub fn update(&self) -> AppResult<SelfUpdateOutcome> {
+        match self.check()? {
+            Some(release) => self.install(&release),
+            None => Ok(SelfUpdateOutcome::UpToDate {
+                version: self.current.clone(),
+            }),
+        }
+    }
+
+    /// Download, verify, and install a specific release.
+    pub fn install(&self, release: &Release) -> AppResult<SelfUpdateOutcome> {
+        let asset = self
+            .platform
+            .select(&self.app_name, release)
+            .ok_or_else(|| {
+                update_error(format!(
+                    "Release {} has no binary for {}-{} (looked for {})",
+                    release.version,
+                    self.platform.os,
+                    self.platform.arch,
+                    self.platform.asset_names(&self.app_name).join(", ")
+                ))
+            })?;
+
+        let checksum_name = format!("{}.sha256", asset.name);
+        let checksum_asset = release.asset(&checksum_name).ok_or_else(|| {
+            update_error(format!(
+                "Release {} has no checksum ({})",
+                release.version, checksum_name
+            ))
+        })?;
+
+        let checksum = String::from_utf8_lossy(&self.source.download(checksum_asset)?).into_owned();
+
+        let signature = match &self.public_key {
+            Some(public_key) => {
+                let signature_name = format!("{}.minisig", asset.name);
+                let signature_asset = release.asset(&signature_name).ok_or_else(|| {
+                    update_error(format!(
+                        "Release {} is not signed ({})",
+                        release.version, signature_name
+                    ))
+                })?;
+                let signature = self.source.download(signature_asset)?;
+                Some((String::from_utf8_lossy(&signature).into_owned(), public_key))
+            }
+            None => None,
+        };
+
+        tracing::debug!("Downloading {}", asset.url);
+        let binary = self.source.download_verified(
+            asset,
+            &checksum,
+            signature
+                .as_ref()
+                .map(|(signature, public_key)| (signature.as_str(), public_key.as_str())),
+        )?;
+
+        let path = match &self.executable {
+            Some(path) => path.clone(),
+            None => std::env::current_exe().map_err(|e| {
+                update_error(format!("Failed to locate the running executable: {}", e))
+            })?,
+        };
+
+        StagedExecutable::stage(&path, &binary)?.install()?;
+
+        Ok(SelfUpdateOutcome::Updated {
+            from: self.current.clone(),
+            to: release.version.clone(),
+            path,
+        })
+    }
+}
+
+fn update_error(message: String) -> TramError {
+    TramError::Update { message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::{ReleaseAsset, ReleaseSource};
+    use sha2::{Digest, Sha256};
+    use std::collections::HashMap;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// A source publishing `stable` on the stable channel and, when set,
+    /// `beta` on the beta channel, each with the same files.
+    struct FakeSource {
+        stable: &'static str,
+        beta: Option<&'static str>,
+        files: HashMap<String, Vec<u8>>,
+    }
+
+    impl ReleaseSource for FakeSource {
+        fn name(&self) -> String {
+            "fake".into()
+        }
+
+        fn latest_version(&self, channel: Channel) -> AppResult<Version> {
+            self.latest_release(channel).map(|release| release.version)
+        }
+    }
+
+    impl BinaryReleaseSource for FakeSource {
+        fn latest_release(&self, channel: Channel) -> AppResult<Release> {
+            let version = match channel {
+                Channel::Stable => self.stable,
+                Channel::Beta => self.beta.unwrap_or(self.stable),
+            };
+
+            Ok(Release {
+                version: parse_version(version)?,
+                assets: self
+                    .files
+                    .keys()
+                    .map(|name| ReleaseAsset {
+                        name: name.clone(),
+                        url: format!("fake://{}", name),
+                    })
+                    .collect(),
+            })
+        }
+
+        fn download(&self, asset: &ReleaseAsset) -> AppResult<Vec<u8>> {
+            Ok(self.files[&asset.name].clone())
+        }
+    }
+
+    fn source_with_binary(binary: &[u8], checksum: Option<String>) -> FakeSource {
+        let asset = Platform::current().asset_names("tram").remove(0);
+        let checksum =
+            checksum.unwrap_or_else(|| format!("{:x}  {}\n", Sha256::digest(binary), asset));
+
+        FakeSource {
+            stable: "v0.2.0",
+            beta: None,
+            files: HashMap::from([
+                (format!("{}.sha256", asset), checksum.into_bytes()),
+                (asset, binary.to_vec()),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_up_to_date() {
+        let source = source_with_binary(b"new", None);
+        let updater = SelfUpdater::new("tram", "0.2.0", source).unwrap();
+
+        assert_eq!(
+            updater.update().unwrap(),
+            SelfUpdateOutcome::UpToDate {
+                version: Version::new(0, 2, 0)
+            }
+        );
+    }
+
+    #[test]
+    fn test_channel_follows_running_version() {
+        let source = || FakeSource {
+            beta: Some("0.3.0-beta.1"),
+            ..source_with_binary(b"new", None)
+        };
+
+        let stable = SelfUpdater::new("tram", "0.1.0", source()).unwrap();
+        assert_eq!(stable.channel(), Channel::Stable);
+        assert_eq!(
+            stable.check_outcome().unwrap(),
+            SelfUpdateOutcome::Available {
+                current: Version::new(0, 1, 0),
+                latest: Version::new(0, 2, 0)
+            }
+        );
+
+        let beta = SelfUpdater::new("tram", "0.3.0-alpha.1", source()).unwrap();
+        assert_eq!(beta.channel(), Channel::Beta);
+        assert_eq!(
+            beta.check().unwrap().unwrap().version.to_string(),
+            "0.3.0-beta.1"
+        );
+
+        let pinned = SelfUpdater::new("tram", "0.3.0-alpha.1", source())
+            .unwrap()
+            .with_channel(Channel::Stable);
+        assert!(pinned.check().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_missing_platform_binary() {
+        let source = source_with_binary(b"new", None);
+        let updater = SelfUpdater::new("tram", "0.1.0", source)
+            .unwrap()
+            .with_platform(Platform::new("plan9", "mips"));
+
+        let err = updater.update().unwrap_err();
+        assert!(err.to_string().contains("no binary for plan9-mips"));
+    }
+
+    #[test]
+    fn test_checksum_mismatch_leaves_executable_alone() {
+        let temp_dir = TempDir::new().unwrap();
+        let exe = temp_dir.path().join("tram");
+        fs::write(&exe, "old").unwrap();
+
+        let source = source_with_binary(b"new", Some("0".repeat(64)));
+        let updater = SelfUpdater::new("tram", "0.1.0", source)
+            .unwrap()
+            .with_executable(&exe);
+
+        let err = updater.update().unwrap_err();
+        assert!(err.to_string().contains("Checksum mismatch"));
+        assert_eq!(fs::read_to_string(&exe).unwrap(), "old");
+    }
+
+    #[test]
+    fn test_signature_required_with_public_key() {
+        let source = source_with_binary(b"new", None);
+        let updater = SelfUpdater::new("tram", "0.1.0", source)
+            .unwrap()
+            .with_public_key("RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3");
+
+        let err = updater.update().unwrap_err();
+        assert!(err.to_string().contains("is not signed"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_update_replaces_executable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let exe = temp_dir.path().join("tram");
+        fs::write(&exe, "#!/bin/sh\necho old\n").unwrap();
+        fs::set_permissions(&exe, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let updater = SelfUpdater::new(
+            "tram",
+            "0.1.0",
+            source_with_binary(b"#!/bin/sh\necho tram 0.2.0\n", None),
+        )
+        .unwrap()
+        .with_executable(&exe);
+
+        assert!(matches!(
+            updater.update().unwrap(),
+            SelfUpdateOutcome::Updated { ref to, .. } if *to == Version::new(0, 2, 0)
+        ));
+        assert!(fs::read_to_string(&exe).unwrap().contains("0.2.0"));
+    }
+}
@@ -0,0 +1,72 @@
+//! Checking downloaded binaries before they are installed.
+
+use sha2::{Digest, Sha256};
+use tram_core::{AppResult, TramError};
+
+/// Check data against a checksum file in `sha256sum` format (or a bare digest).
+pub fn verify_checksum(data: &[u8], checksum_file: &str) -> AppResult<()> {
+    let expected = checksum_file
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    let actual = format!("{:x}", Sha256::digest(data));
+
+    if expected != actual {
+        return Err(TramError::Update {
+            message: format!(
+                "Checksum mismatch: expected {}, got {}",
+                if expected.is_empty() {
+                    "<empty>"
+                } else {
+                    &expected
+                },
+                actual
+            ),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Check data against a minisign signature.
+pub fn verify_signature(data: &[u8], signature: &str, public_key: &str) -> AppResult<()> {
+    let update_error = |message: String| TramError::Update { message };
+
+    let public_key = minisign_verify::PublicKey::from_base64(public_key)
+        .map_err(|e| update_error(format!("Invalid public key: {}", e)))?;
+    let signature = minisign_verify::Signature::decode(signature)
+        .map_err(|e| update_error(format!("Invalid signature: {}", e)))?;
+
+    public_key
+        .verify(data, &signature, false)
+        .map_err(|e| update_error(format!("Signature verification failed: {}", e)).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_checksum() {
+        let digest = format!("{:x}", Sha256::digest(b"test"));
+
+        assert!(verify_checksum(b"test", &format!("{}  tram\n", digest)).is_ok());
+        assert!(verify_checksum(b"test", &digest.to_uppercase()).is_ok());
+        let err = verify_checksum(b"test", "").unwrap_err();
+        assert!(err.to_string().contains("expected <empty>"));
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let public_key = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+        let signature = "untrusted comment: signature from minisign secret key
+RUQf6LRCGA9i559r3g7V1qNyJDApGip8MfqcadIgT9CuhV3EMhHoN1mGTkUidF/z7SrlQgXdy8ofjb7bNJJylDOocrCo8KLzZwo=
+trusted comment: timestamp:1633700835\tfile:test\tprehashed
+wLMDjy9FLAuxZ3q4NlEvkgtyhrr0gtTu6KC4KBJdITbbOeAi1zBIYo0v4iTgt8jJpIidRJnp94ABQkJAgAooBQ==";
+
+        assert!(verify_signature(b"test", signature, public_key).is_ok());
+        assert!(verify_signature(b"tampered", signature, public_key).is_err());
+    }
+}
@@ -0,0 +1,58 @@
+//! Compares workspace root detection from a deep directory against probing
+//! every marker with its own `exists()` call, as detection used to.
+//!
+//! Run with `cargo bench -p tram-workspace`. The gap widens on network
+//! filesystems, where each `stat` is a round trip.
+
+use std::hint::black_box;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tram_workspace::{ROOT_MARKERS, WorkspaceDetector};
+
+const DEPTH: usize = 40;
+const ITERATIONS: u32 = 2_000;
+
+/// The previous approach: one `exists()` per marker per ancestor.
+fn probe_each_marker(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| ROOT_MARKERS.iter().any(|marker| dir.join(marker).exists()))
+        .map(Path::to_path_buf)
+}
+
+fn time(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let temp_dir = tempfile::TempDir::new().expect("create temp dir");
+    let root = temp_dir.path().join("project");
+    let deep = (0..DEPTH).fold(root.clone(), |dir, level| {
+        dir.join(format!("level{}", level))
+    });
+    std::fs::create_dir_all(&deep).expect("create nested dirs");
+    std::fs::write(root.join("Cargo.toml"), "[package]\nname = \"bench\"\n").expect("write marker");
+
+    let detector = WorkspaceDetector::from_dir(deep.clone());
+    assert_eq!(detector.detect_root().ok(), Some(root.clone()));
+    assert_eq!(probe_each_marker(&deep), Some(root));
+
+    let listed = time(|| {
+        black_box(detector.detect_root().ok());
+    });
+    let probed = time(|| {
+        black_box(probe_each_marker(black_box(&deep)));
+    });
+
+    println!("detect_root from {} levels deep:", DEPTH);
+    println!("  one read_dir per level:  {:>10.1?}", listed);
+    println!("  one exists() per marker: {:>10.1?}", probed);
+    println!(
+        "  speedup: {:.1}x",
+        probed.as_secs_f64() / listed.as_secs_f64()
+    );
+}
@@ -0,0 +1,141 @@
+//! What git knows about a workspace.
+//!
+//! [`GitInfo`] asks the `git` binary, so a workspace outside a repository,
+//! or a machine without git, simply has no git info rather than an error.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// The repository state of a workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitInfo {
+    /// The work tree root, which may be above the workspace root
+    pub root: PathBuf,
+    /// `None` when `HEAD` is detached
+    pub branch: Option<String>,
+    /// Full hash of `HEAD`; `None` before the first commit
+    pub commit: Option<String>,
+    /// Whether git reports changes; `None` if git couldn't tell
+    pub dirty: Option<bool>,
+}
+
+impl GitInfo {
+    /// The state of the repository containing `root`, or `None` outside one.
+    pub fn detect(root: &Path) -> Option<Self> {
+        git_dir(root)?;
+        let work_tree = git(root, &["rev-parse", "--show-toplevel"])?;
+
+        Some(Self {
+            root: PathBuf::from(work_tree),
+            branch: git(root, &["symbolic-ref", "--quiet", "--short", "HEAD"]),
+            commit: git(root, &["rev-parse", "--verify", "--quiet", "HEAD"]),
+            dirty: is_dirty(root),
+        })
+    }
+}
+
+/// The `.git` directory of the repository containing `root`, if any.
+/// Worktrees, whose `.git` is a file, are followed to their git directory.
+pub fn git_dir(root: &Path) -> Option<PathBuf> {
+    root.ancestors().find_map(|dir| {
+        let dot_git = dir.join(".git");
+        if dot_git.is_dir() {
+            return Some(dot_git);
+        }
+
+        let pointer = std::fs::read_to_string(&dot_git).ok()?;
+        let target = pointer.strip_prefix("gitdir:")?.trim();
+        Some(dir.join(target))
+    })
+}
+
+/// Whether `git status` lists any changes, `None` if git couldn't tell.
+pub fn is_dirty(root: &Path) -> Option<bool> {
+    let output = Command::new("git")
+        .args(["status", "--porcelain", "--ignore-submodules=dirty"])
+        .current_dir(root)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    output.status.success().then_some(!output.stdout.is_empty())
+}
+
+/// A git command's trimmed stdout, `None` if it failed or printed nothing.
+fn git(root: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(root)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    let stdout = stdout.trim();
+    (output.status.success() && !stdout.is_empty()).then(|| stdout.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn run_git(dir: &Path, args: &[&str]) -> bool {
+        Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+
+    #[test]
+    fn test_no_git_info_outside_a_repository() {
+        let temp_dir = TempDir::new().unwrap();
+
+        assert_eq!(GitInfo::detect(temp_dir.path()), None);
+    }
+
+    #[test]
+    fn test_detects_branch_commit_and_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        if !run_git(root, &["init", "--quiet", "--initial-branch=main"]) {
+            // No git on this machine
+            return;
+        }
+
+        let info = GitInfo::detect(root).unwrap();
+        assert_eq!(info.branch.as_deref(), Some("main"));
+        assert_eq!(info.commit, None);
+        assert_eq!(info.dirty, Some(false));
+
+        std::fs::write(root.join("README.md"), "# Test\n").unwrap();
+        assert_eq!(GitInfo::detect(root).unwrap().dirty, Some(true));
+
+        run_git(root, &["add", "README.md"]);
+        let committed = run_git(
+            root,
+            &[
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "--quiet",
+                "-m",
+                "Initial commit",
+            ],
+        );
+        assert!(committed);
+
+        let info = GitInfo::detect(root).unwrap();
+        assert_eq!(info.commit.map(|hash| hash.len()), Some(40));
+        assert_eq!(info.dirty, Some(false));
+    }
+}
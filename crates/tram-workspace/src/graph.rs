@@ -0,0 +1,588 @@
+//! Workspace member dependency graphs.
+//!
+//! [`WorkspaceGraph::discover`] finds the members of a Cargo workspace
+//! (`[workspace] members`), an npm, Yarn, or pnpm workspace (`workspaces` in
+//! `package.json`), or a Go workspace (`use` in `go.work`), and the
+//! dependencies between them. A single project without a workspace is a
+//! graph of one member.
+//!
+//! [`WorkspaceMembers::discover`] lists the same members without reading
+//! their dependencies.
+
+use crate::ProjectType;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tram_core::{AppResult, TramError};
+
+/// A package in the workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceMember {
+    /// Package name (or Go module path)
+    pub name: String,
+    /// Directory relative to the workspace root, `.` for the root itself
+    pub path: PathBuf,
+    pub project_type: ProjectType,
+    /// Names of the other members this one depends on
+    pub dependencies: Vec<String>,
+}
+
+/// The members of a workspace and the dependencies between them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceGraph {
+    pub root: PathBuf,
+    /// Sorted by name
+    pub members: Vec<WorkspaceMember>,
+}
+
+/// A project nested in a workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberProject {
+    /// Package name (or Go module path)
+    pub name: String,
+    /// Directory relative to the workspace root, `.` for the root itself
+    pub path: PathBuf,
+    pub project_type: ProjectType,
+}
+
+/// The projects in a workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceMembers {
+    pub root: PathBuf,
+    /// Sorted by path
+    pub members: Vec<MemberProject>,
+}
+
+impl WorkspaceMembers {
+    /// Find the Cargo, npm, and Go workspace members under `root`.
+    pub fn discover(root: &Path) -> AppResult<Self> {
+        let mut members: Vec<MemberProject> = find(root)?
+            .into_iter()
+            .map(|member| MemberProject {
+                path: relative(root, &member.dir),
+                name: member.name,
+                project_type: member.project_type,
+            })
+            .collect();
+        members.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.name.cmp(&b.name)));
+        members.dedup_by(|a, b| a.name == b.name && a.path == b.path);
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            members,
+        })
+    }
+}
+
+/// A member before its dependencies are narrowed to other members.
+struct Found {
+    name: String,
+    dir: PathBuf,
+    project_type: ProjectType,
+    dependencies: BTreeSet<String>,
+}
+
+impl WorkspaceGraph {
+    /// Find the members of the workspace at `root` and how they depend on
+    /// each other.
+    pub fn discover(root: &Path) -> AppResult<Self> {
+        let found = find(root)?;
+        let names: BTreeSet<String> = found.iter().map(|member| member.name.clone()).collect();
+        let mut members: Vec<WorkspaceMember> = found
+            .into_iter()
+            .map(|member| {
+                let path = relative(root, &member.dir);
+
+                WorkspaceMember {
+                    dependencies: member
+                        .dependencies
+                        .into_iter()
+                        .filter(|dependency| {
+                            *dependency != member.name && names.contains(dependency)
+                        })
+                        .collect(),
+                    name: member.name,
+                    path,
+                    project_type: member.project_type,
+                }
+            })
+            .collect();
+        members.sort_by(|a, b| a.name.cmp(&b.name));
+        members.dedup_by(|a, b| a.name == b.name);
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            members,
+        })
+    }
+
+    /// Each dependency as a `(dependent, dependency)` pair.
+    pub fn edges(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.members.iter().flat_map(|member| {
+            member
+                .dependencies
+                .iter()
+                .map(move |dependency| (member.name.as_str(), dependency.as_str()))
+        })
+    }
+
+    /// The graph in Graphviz DOT, with an arrow from each member to the
+    /// members it depends on.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph workspace {\n    rankdir=LR;\n    node [shape=box];\n");
+        for member in &self.members {
+            dot.push_str(&format!("    {};\n", dot_id(&member.name)));
+        }
+        for (from, to) in self.edges() {
+            dot.push_str(&format!("    {} -> {};\n", dot_id(from), dot_id(to)));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The graph as a Mermaid flowchart.
+    pub fn to_mermaid(&self) -> String {
+        // Mermaid ids can't contain most punctuation, so members are numbered
+        let ids: BTreeMap<&str, String> = self
+            .members
+            .iter()
+            .enumerate()
+            .map(|(index, member)| (member.name.as_str(), format!("m{}", index)))
+            .collect();
+
+        let mut mermaid = String::from("graph LR\n");
+        for member in &self.members {
+            mermaid.push_str(&format!(
+                "    {}[\"{}\"]\n",
+                ids[member.name.as_str()],
+                member.name.replace('"', "#quot;")
+            ));
+        }
+        for (from, to) in self.edges() {
+            mermaid.push_str(&format!("    {} --> {}\n", ids[from], ids[to]));
+        }
+        mermaid
+    }
+}
+
+fn find(root: &Path) -> AppResult<Vec<Found>> {
+    let mut found = cargo_members(root)?;
+    found.extend(npm_members(root)?);
+    found.extend(go_members(root)?);
+    Ok(found)
+}
+
+fn relative(root: &Path, dir: &Path) -> PathBuf {
+    dir.strip_prefix(root)
+        .ok()
+        .filter(|path| !path.as_os_str().is_empty())
+        .map_or_else(|| PathBuf::from("."), Path::to_path_buf)
+}
+
+fn dot_id(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn read(path: &Path) -> AppResult<String> {
+    Ok(
+        fs::read_to_string(path).map_err(|e| TramError::InvalidConfig {
+            message: format!("Failed to read {}: {}", path.display(), e),
+        })?,
+    )
+}
+
+fn invalid(path: &Path, error: impl std::fmt::Display) -> TramError {
+    TramError::InvalidConfig {
+        message: format!("Failed to parse {}: {}", path.display(), error),
+    }
+}
+
+/// Directories matching workspace member patterns, minus the excluded ones.
+fn expand(root: &Path, patterns: &[String], exclude: &[String]) -> Vec<PathBuf> {
+    let matches = |patterns: &[String]| -> BTreeSet<PathBuf> {
+        patterns
+            .iter()
+            .filter_map(|pattern| glob::glob(&root.join(pattern).to_string_lossy()).ok())
+            .flatten()
+            .filter_map(Result::ok)
+            .filter(|path| path.is_dir())
+            .collect()
+    };
+
+    let excluded = matches(exclude);
+    matches(patterns)
+        .into_iter()
+        .filter(|dir| !excluded.contains(dir))
+        .collect()
+}
+
+fn string_list(value: Option<&toml::Value>) -> Vec<String> {
+    value
+        .and_then(toml::Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(ToString::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn cargo_members(root: &Path) -> AppResult<Vec<Found>> {
+    let manifest_path = root.join("Cargo.toml");
+    if !manifest_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let manifest: toml::Table = read(&manifest_path)?
+        .parse()
+        .map_err(|e| invalid(&manifest_path, e))?;
+
+    let mut dirs = vec![root.to_path_buf()];
+    if let Some(workspace) = manifest.get("workspace") {
+        dirs.extend(expand(
+            root,
+            &string_list(workspace.get("members")),
+            &string_list(workspace.get("exclude")),
+        ));
+    }
+
+    let mut members = Vec::new();
+    for dir in dirs {
+        let path = dir.join("Cargo.toml");
+        let Ok(contents) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let manifest: toml::Table = contents.parse().map_err(|e| invalid(&path, e))?;
+        let Some(name) = manifest
+            .get("package")
+            .and_then(|package| package.get("name"))
+            .and_then(toml::Value::as_str)
+        else {
+            // A virtual workspace root has no package of its own
+            continue;
+        };
+
+        let dependencies = ["dependencies", "dev-dependencies", "build-dependencies"]
+            .iter()
+            .filter_map(|table| manifest.get(*table).and_then(toml::Value::as_table))
+            .flatten()
+            .map(|(key, spec)| {
+                // `alias = { package = "real-name", ... }`
+                spec.get("package")
+                    .and_then(toml::Value::as_str)
+                    .unwrap_or(key)
+                    .to_string()
+            })
+            .collect();
+
+        members.push(Found {
+            name: name.to_string(),
+            dir,
+            project_type: ProjectType::Rust,
+            dependencies,
+        });
+    }
+
+    Ok(members)
+}
+
+fn npm_members(root: &Path) -> AppResult<Vec<Found>> {
+    let manifest_path = root.join("package.json");
+    if !manifest_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let manifest: serde_json::Value =
+        serde_json::from_str(&read(&manifest_path)?).map_err(|e| invalid(&manifest_path, e))?;
+
+    // `"workspaces": [...]` or Yarn's `"workspaces": { "packages": [...] }`
+    let workspaces = manifest
+        .get("workspaces")
+        .map(|workspaces| workspaces.get("packages").unwrap_or(workspaces));
+    let mut patterns: Vec<String> = workspaces
+        .and_then(serde_json::Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(ToString::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    patterns.extend(pnpm_patterns(root)?);
+
+    let (exclude, include): (Vec<String>, Vec<String>) = patterns
+        .into_iter()
+        .partition(|pattern| pattern.starts_with('!'));
+    let exclude: Vec<String> = exclude
+        .into_iter()
+        .map(|pattern| pattern[1..].to_string())
+        .collect();
+
+    let mut dirs = vec![root.to_path_buf()];
+    dirs.extend(expand(root, &include, &exclude));
+
+    let mut members = Vec::new();
+    for dir in dirs {
+        let path = dir.join("package.json");
+        let Ok(contents) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let manifest: serde_json::Value =
+            serde_json::from_str(&contents).map_err(|e| invalid(&path, e))?;
+        let Some(name) = manifest.get("name").and_then(serde_json::Value::as_str) else {
+            continue;
+        };
+
+        let dependencies = [
+            "dependencies",
+            "devDependencies",
+            "peerDependencies",
+            "optionalDependencies",
+        ]
+        .iter()
+        .filter_map(|field| manifest.get(*field).and_then(serde_json::Value::as_object))
+        .flat_map(|dependencies| dependencies.keys().cloned())
+        .collect();
+
+        members.push(Found {
+            name: name.to_string(),
+            dir,
+            project_type: ProjectType::NodeJs,
+            dependencies,
+        });
+    }
+
+    Ok(members)
+}
+
+/// The `packages` patterns from `pnpm-workspace.yaml`, a list of
+/// `- 'pattern'` lines.
+fn pnpm_patterns(root: &Path) -> AppResult<Vec<String>> {
+    let path = root.join("pnpm-workspace.yaml");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    Ok(read(&path)?
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("- "))
+        .map(|pattern| pattern.trim().trim_matches(['\'', '"']).to_string())
+        .collect())
+}
+
+/// Directives in a `go.mod` or `go.work` file, e.g. every `require` module
+/// in both the `require x v1` and `require ( ... )` forms.
+fn go_directive(contents: &str, directive: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut in_block = false;
+
+    for line in contents.lines() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+
+        if in_block {
+            if line == ")" {
+                in_block = false;
+            } else if let Some(value) = line.split_whitespace().next() {
+                values.push(value.to_string());
+            }
+        } else if let Some(rest) = line.strip_prefix(directive) {
+            let rest = rest.trim();
+            if rest == "(" {
+                in_block = true;
+            } else if let Some(value) = rest.split_whitespace().next() {
+                values.push(value.to_string());
+            }
+        }
+    }
+
+    values
+}
+
+fn go_members(root: &Path) -> AppResult<Vec<Found>> {
+    let work_path = root.join("go.work");
+    let dirs = if work_path.exists() {
+        go_directive(&read(&work_path)?, "use")
+            .into_iter()
+            .map(|dir| root.join(dir))
+            .collect()
+    } else {
+        vec![root.to_path_buf()]
+    };
+
+    let mut members = Vec::new();
+    for dir in dirs {
+        let Ok(contents) = fs::read_to_string(dir.join("go.mod")) else {
+            continue;
+        };
+        let Some(name) = go_directive(&contents, "module").into_iter().next() else {
+            continue;
+        };
+
+        members.push(Found {
+            name,
+            dir,
+            project_type: ProjectType::Go,
+            dependencies: go_directive(&contents, "require").into_iter().collect(),
+        });
+    }
+
+    Ok(members)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_cargo_workspace() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(
+            root,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/old\"]\n",
+        );
+        write(
+            root,
+            "crates/core/Cargo.toml",
+            "[package]\nname = \"core\"\n",
+        );
+        write(
+            root,
+            "crates/config/Cargo.toml",
+            "[package]\nname = \"config\"\n[dependencies]\ncore = { path = \"../core\" }\nserde = \"1\"\n",
+        );
+        write(
+            root,
+            "crates/cli/Cargo.toml",
+            "[package]\nname = \"cli\"\n[dependencies]\ncfg = { package = \"config\", path = \"../config\" }\n[dev-dependencies]\ncore = { path = \"../core\" }\n",
+        );
+        write(root, "crates/old/Cargo.toml", "[package]\nname = \"old\"\n");
+
+        let graph = WorkspaceGraph::discover(root).unwrap();
+        let names: Vec<&str> = graph.members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["cli", "config", "core"]);
+        assert_eq!(graph.members[2].path, Path::new("crates/core"));
+        assert_eq!(
+            graph.edges().collect::<Vec<_>>(),
+            [("cli", "config"), ("cli", "core"), ("config", "core")]
+        );
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph workspace {\n"));
+        assert!(dot.contains("    \"cli\" -> \"config\";\n"));
+
+        let mermaid = graph.to_mermaid();
+        assert!(mermaid.contains("    m0[\"cli\"]\n"));
+        assert!(mermaid.contains("    m1 --> m2\n"));
+    }
+
+    #[test]
+    fn test_npm_and_go_workspaces() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(
+            root,
+            "package.json",
+            r#"{"private": true, "workspaces": ["packages/*"]}"#,
+        );
+        write(root, "packages/ui/package.json", r#"{"name": "@acme/ui"}"#);
+        write(
+            root,
+            "packages/web/package.json",
+            r#"{"name": "web", "dependencies": {"@acme/ui": "*", "react": "^18"}}"#,
+        );
+        write(
+            root,
+            "go.work",
+            "go 1.21\n\nuse (\n\t./svc/api\n\t./svc/lib\n)\n",
+        );
+        write(
+            root,
+            "svc/api/go.mod",
+            "module example.com/api\n\nrequire example.com/lib v0.0.0 // local\n",
+        );
+        write(root, "svc/lib/go.mod", "module example.com/lib\n");
+
+        let graph = WorkspaceGraph::discover(root).unwrap();
+        assert_eq!(
+            graph.edges().collect::<Vec<_>>(),
+            [("example.com/api", "example.com/lib"), ("web", "@acme/ui")]
+        );
+        assert_eq!(graph.members.len(), 4);
+    }
+
+    #[test]
+    fn test_single_project() {
+        let temp_dir = TempDir::new().unwrap();
+        write(
+            temp_dir.path(),
+            "Cargo.toml",
+            "[package]\nname = \"solo\"\n",
+        );
+
+        let graph = WorkspaceGraph::discover(temp_dir.path()).unwrap();
+        assert_eq!(graph.members.len(), 1);
+        assert_eq!(graph.members[0].path, Path::new("."));
+        assert_eq!(graph.edges().count(), 0);
+    }
+
+    #[test]
+    fn test_workspace_members() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(
+            root,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\"]\n\n[package]\nname = \"app\"\n",
+        );
+        write(
+            root,
+            "crates/core/Cargo.toml",
+            "[package]\nname = \"core\"\n",
+        );
+        write(
+            root,
+            "package.json",
+            r#"{"private": true, "workspaces": ["web"]}"#,
+        );
+        write(root, "web/package.json", r#"{"name": "web"}"#);
+        write(root, "go.work", "go 1.21\n\nuse ./tools\n");
+        write(root, "tools/go.mod", "module example.com/tools\n");
+
+        let members = WorkspaceMembers::discover(root).unwrap();
+        let found: Vec<(&str, &str, ProjectType)> = members
+            .members
+            .iter()
+            .map(|m| {
+                (
+                    m.path.to_str().unwrap(),
+                    m.name.as_str(),
+                    m.project_type.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (".", "app", ProjectType::Rust),
+                ("crates/core", "core", ProjectType::Rust),
+                ("tools", "example.com/tools", ProjectType::Go),
+                ("web", "web", ProjectType::NodeJs),
+            ]
+        );
+    }
+}
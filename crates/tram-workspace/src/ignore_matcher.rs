@@ -0,0 +1,189 @@
+//! Which paths in a workspace are ignored.
+//!
+//! [`IgnoreMatcher`] combines the project type's ignore patterns with the
+//! workspace's `.gitignore` and `.ignore` files, wherever they are in the
+//! tree. Like git, a file's patterns apply to its own directory and below,
+//! a deeper file wins over a shallower one, and `!pattern` re-includes a
+//! path. At each level `.ignore` wins over `.gitignore`, as it does for
+//! ripgrep. `.git/` is always ignored.
+
+use crate::ProjectType;
+use ignore::Match;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::{Path, PathBuf};
+use tram_core::{AppResult, TramError};
+
+/// Ignore files read in each directory, lowest precedence first.
+pub const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// Matches paths against a workspace's ignore files and project defaults.
+#[derive(Debug, Clone)]
+pub struct IgnoreMatcher {
+    root: PathBuf,
+    /// `.git/` and the project type's patterns, below every ignore file
+    defaults: Gitignore,
+    /// Each directory's ignore files, deepest directory first
+    files: Vec<Gitignore>,
+}
+
+impl IgnoreMatcher {
+    /// Read the ignore files under a workspace root, skipping directories
+    /// they or the project type's patterns ignore.
+    pub fn new(root: impl AsRef<Path>, project_type: Option<&ProjectType>) -> AppResult<Self> {
+        let root = root
+            .as_ref()
+            .canonicalize()
+            .map_err(|_| TramError::WorkspaceNotFound)?;
+
+        let mut builder = GitignoreBuilder::new(&root);
+        let patterns = project_type
+            .map(|kind| kind.ignore_patterns())
+            .unwrap_or_default();
+        for pattern in std::iter::once(&".git/").chain(patterns) {
+            builder
+                .add_line(None, pattern)
+                .map_err(|e| TramError::InvalidConfig {
+                    message: format!("Invalid ignore pattern '{}': {}", pattern, e),
+                })?;
+        }
+        let defaults = builder.build().map_err(|e| ignore_error(&root, &e))?;
+
+        let mut matcher = Self {
+            root,
+            defaults,
+            files: Vec::new(),
+        };
+        let mut dirs = vec![matcher.root.clone()];
+        while let Some(dir) = dirs.pop() {
+            // Ancestors were read first, so this keeps the deepest in front
+            if let Some(file) = read_ignore_files(&dir)? {
+                matcher.files.insert(0, file);
+            }
+
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            let mut subdirs: Vec<PathBuf> = entries
+                .flatten()
+                .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+                .map(|entry| entry.path())
+                .filter(|path| !matcher.is_ignored(path))
+                .collect();
+            subdirs.sort();
+            dirs.extend(subdirs.into_iter().rev());
+        }
+
+        Ok(matcher)
+    }
+
+    /// The canonical workspace root.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Whether a path is ignored. Paths outside the workspace always are.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return true;
+        };
+        let is_dir = path.is_dir();
+
+        for file in self
+            .files
+            .iter()
+            .filter(|file| path.starts_with(file.path()))
+        {
+            match file.matched_path_or_any_parents(path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+
+        self.defaults
+            .matched_path_or_any_parents(relative, is_dir)
+            .is_ignore()
+    }
+}
+
+/// The patterns of a directory's ignore files, if it has any.
+fn read_ignore_files(dir: &Path) -> AppResult<Option<Gitignore>> {
+    let paths: Vec<PathBuf> = IGNORE_FILES
+        .iter()
+        .map(|name| dir.join(name))
+        .filter(|path| path.is_file())
+        .collect();
+    if paths.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GitignoreBuilder::new(dir);
+    for path in &paths {
+        if let Some(e) = builder.add(path) {
+            return Err(ignore_error(path, &e).into());
+        }
+    }
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| ignore_error(dir, &e).into())
+}
+
+fn ignore_error(path: &Path, error: &ignore::Error) -> TramError {
+    TramError::InvalidConfig {
+        message: format!(
+            "Failed to read ignore patterns in {}: {}",
+            path.display(),
+            error
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_combines_project_patterns_and_ignore_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        fs::write(root.join(".ignore"), "fixtures/\n").unwrap();
+
+        let matcher = IgnoreMatcher::new(root, Some(&ProjectType::Rust)).unwrap();
+        let root = matcher.root().to_path_buf();
+
+        assert!(matcher.is_ignored(&root.join("target/debug/tram")));
+        assert!(matcher.is_ignored(&root.join("build.log")));
+        assert!(matcher.is_ignored(&root.join("fixtures/data.json")));
+        assert!(matcher.is_ignored(&root.join(".git/index")));
+        assert!(!matcher.is_ignored(&root.join("src/main.rs")));
+        assert!(matcher.is_ignored(Path::new("/elsewhere/main.rs")));
+    }
+
+    #[test]
+    fn test_nested_ignore_files_apply_below_their_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("web/src")).unwrap();
+        fs::write(root.join(".gitignore"), "*.tmp\ngenerated/\n").unwrap();
+        fs::write(root.join("web/.gitignore"), "!keep.tmp\n*.cache\n").unwrap();
+        fs::write(root.join("web/.ignore"), "src/vendor.js\n").unwrap();
+
+        let matcher = IgnoreMatcher::new(root, None).unwrap();
+        let root = matcher.root().to_path_buf();
+
+        assert!(matcher.is_ignored(&root.join("web/scratch.tmp")));
+        assert!(!matcher.is_ignored(&root.join("web/keep.tmp")));
+        assert!(matcher.is_ignored(&root.join("keep.tmp")));
+        assert!(matcher.is_ignored(&root.join("web/src/app.cache")));
+        assert!(!matcher.is_ignored(&root.join("app.cache")));
+        assert!(matcher.is_ignored(&root.join("web/src/vendor.js")));
+        assert!(!matcher.is_ignored(&root.join("web/src/app.js")));
+        assert!(matcher.is_ignored(&root.join("web/generated/api.ts")));
+    }
+}
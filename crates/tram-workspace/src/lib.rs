@@ -3,9 +3,20 @@
 //! Provides simple, practical utilities for detecting project roots
 //! and working with workspace structures.
 
+use serde::Serialize;
 use std::path::{Path, PathBuf};
 use tram_core::{AppResult, TramError};
 
+mod git;
+mod graph;
+mod ignore_matcher;
+mod watcher;
+
+pub use git::*;
+pub use graph::*;
+pub use ignore_matcher::*;
+pub use watcher::*;
+
 /// Simple workspace detector that finds project roots by looking for common indicators.
 #[derive(Debug, Clone)]
 pub struct WorkspaceDetector {
@@ -43,30 +54,54 @@ impl WorkspaceDetector {
     }
 
     /// Check if a directory appears to be a workspace root.
+    ///
+    /// The directory is listed once and the markers are looked up in the
+    /// listing, rather than probing each marker with its own `stat`, which
+    /// adds up on network filesystems. Directories that can't be listed
+    /// fall back to probing.
     fn is_workspace_root(&self, path: &Path) -> bool {
-        // Version control directories
-        if path.join(".git").exists() || path.join(".hg").exists() || path.join(".svn").exists() {
-            return true;
+        match std::fs::read_dir(path) {
+            Ok(entries) => entries
+                .filter_map(Result::ok)
+                .any(|entry| is_root_marker(&entry.file_name())),
+            Err(_) => ROOT_MARKERS.iter().any(|marker| path.join(marker).exists()),
         }
-
-        // Common project files
-        let project_files = [
-            "Cargo.toml",     // Rust
-            "package.json",   // Node.js
-            "pyproject.toml", // Python
-            "setup.py",       // Python
-            "go.mod",         // Go
-            "build.gradle",   // Gradle
-            "pom.xml",        // Maven
-            "Makefile",       // Make
-            "justfile",       // Just
-            ".project",       // Eclipse
-        ];
-
-        project_files.iter().any(|&file| path.join(file).exists())
     }
 }
 
+/// Files and directories whose presence marks a workspace root.
+pub const ROOT_MARKERS: [&str; 13] = [
+    // Version control directories
+    ".git",
+    ".hg",
+    ".svn",
+    // Common project files
+    "Cargo.toml",     // Rust
+    "package.json",   // Node.js
+    "pyproject.toml", // Python
+    "setup.py",       // Python
+    "go.mod",         // Go
+    "build.gradle",   // Gradle
+    "pom.xml",        // Maven
+    "Makefile",       // Make
+    "justfile",       // Just
+    ".project",       // Eclipse
+];
+
+/// Whether a directory entry is one of the [`ROOT_MARKERS`]. Names are
+/// compared ignoring case on macOS and Windows, whose filesystems usually
+/// do, as probing each marker there would.
+fn is_root_marker(name: &std::ffi::OsStr) -> bool {
+    ROOT_MARKERS.iter().any(|marker| {
+        if cfg!(any(target_os = "macos", windows)) {
+            name.to_str()
+                .is_some_and(|name| name.eq_ignore_ascii_case(marker))
+        } else {
+            name == *marker
+        }
+    })
+}
+
 impl Default for WorkspaceDetector {
     fn default() -> Self {
         Self::new().unwrap_or_else(|_| Self::from_dir(PathBuf::from(".")))
@@ -74,7 +109,8 @@ impl Default for WorkspaceDetector {
 }
 
 /// Project type detection based on files present.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ProjectType {
     Rust,
     NodeJs,
@@ -102,6 +138,19 @@ impl ProjectType {
         }
     }
 
+    /// The type's name as it's serialized, e.g. `nodejs`, for output that
+    /// scripts read.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProjectType::Rust => "rust",
+            ProjectType::NodeJs => "nodejs",
+            ProjectType::Python => "python",
+            ProjectType::Go => "go",
+            ProjectType::Java => "java",
+            ProjectType::Generic => "generic",
+        }
+    }
+
     /// Get common ignore patterns for this project type.
     pub fn ignore_patterns(&self) -> &[&str] {
         match self {
@@ -120,6 +169,40 @@ impl ProjectType {
             ProjectType::Generic => &["build/", "dist/", "out/"],
         }
     }
+
+    /// Tools that must be on `PATH` to build this project type.
+    pub fn required_tools(&self) -> &[&str] {
+        match self {
+            ProjectType::Rust => &["cargo", "rustc"],
+            ProjectType::NodeJs => &["node", "npm"],
+            ProjectType::Python => &["python3"],
+            ProjectType::Go => &["go"],
+            ProjectType::Java => &["java"],
+            ProjectType::Generic => &[],
+        }
+    }
+
+    /// Commands that format-check, lint, build, and test this project type,
+    /// in the order they should run.
+    pub fn check_commands(&self) -> &[&[&str]] {
+        match self {
+            ProjectType::Rust => &[
+                &["cargo", "fmt", "--check"],
+                &["cargo", "clippy"],
+                &["cargo", "build"],
+                &["cargo", "test"],
+            ],
+            ProjectType::NodeJs => &[&["npm", "test"]],
+            ProjectType::Python => &[&["python3", "-m", "pytest"]],
+            ProjectType::Go => &[
+                &["go", "vet", "./..."],
+                &["go", "build", "./..."],
+                &["go", "test", "./..."],
+            ],
+            ProjectType::Java => &[&["mvn", "verify"]],
+            ProjectType::Generic => &[],
+        }
+    }
 }
 
 #[cfg(test)]
@@ -128,6 +211,20 @@ mod tests {
     use std::fs;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_project_type_name_matches_serialization() {
+        for kind in [
+            ProjectType::Rust,
+            ProjectType::NodeJs,
+            ProjectType::Python,
+            ProjectType::Go,
+            ProjectType::Java,
+            ProjectType::Generic,
+        ] {
+            assert_eq!(serde_json::to_value(&kind).unwrap(), kind.name());
+        }
+    }
+
     #[test]
     fn test_detect_rust_project() {
         let temp_dir = TempDir::new().unwrap();
@@ -140,6 +237,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_required_tools() {
+        assert_eq!(ProjectType::Rust.required_tools(), ["cargo", "rustc"]);
+        assert!(ProjectType::Generic.required_tools().is_empty());
+        assert_eq!(
+            ProjectType::Rust.check_commands()[0],
+            ["cargo", "fmt", "--check"]
+        );
+    }
+
     #[test]
     fn test_workspace_detector() {
         let temp_dir = TempDir::new().unwrap();
@@ -151,4 +258,29 @@ mod tests {
 
         assert_eq!(root, temp_dir.path());
     }
+
+    #[test]
+    fn test_detect_root_from_nested_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        let nested = project.join("src").join("deep").join("er");
+        fs::create_dir_all(&nested).unwrap();
+        fs::create_dir(project.join(".git")).unwrap();
+        // Similar names aren't markers
+        fs::write(project.join("src").join("Cargo.toml.bak"), "").unwrap();
+
+        let root = WorkspaceDetector::from_dir(nested).detect_root().unwrap();
+
+        assert_eq!(root, project);
+    }
+
+    #[test]
+    fn test_root_marker_case() {
+        assert!(is_root_marker("Makefile".as_ref()));
+        assert!(!is_root_marker("Makefile.am".as_ref()));
+        assert_eq!(
+            is_root_marker("makefile".as_ref()),
+            cfg!(any(target_os = "macos", windows))
+        );
+    }
 }
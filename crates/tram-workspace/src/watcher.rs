@@ -0,0 +1,280 @@
+//! Recursive file watching for a workspace.
+//!
+//! [`WorkspaceWatcher`] watches the workspace root with notify, drops events
+//! for paths its [`IgnoreMatcher`] ignores, and groups bursts of events (an
+//! editor save, a `git checkout`) into a single batch of
+//! [`WorkspaceChange`]s.
+
+use crate::{IgnoreMatcher, ProjectType};
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+use tram_core::{AppResult, TramError};
+
+/// Default quiet period that ends a batch of changes.
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// How a watched path changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Removed,
+}
+
+impl std::fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChangeKind::Created => write!(f, "created"),
+            ChangeKind::Modified => write!(f, "modified"),
+            ChangeKind::Removed => write!(f, "removed"),
+        }
+    }
+}
+
+/// A single changed path, absolute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceChange {
+    pub path: PathBuf,
+    pub kind: ChangeKind,
+}
+
+/// Watches a workspace for changes to files that aren't ignored.
+#[derive(Debug)]
+pub struct WorkspaceWatcher {
+    ignore: IgnoreMatcher,
+    debounce: Duration,
+}
+
+impl WorkspaceWatcher {
+    /// Create a watcher for a workspace root, ignoring `.git/`, the
+    /// workspace's `.gitignore` and `.ignore` files, and the project type's
+    /// ignore patterns.
+    pub fn new(root: impl AsRef<Path>, project_type: Option<&ProjectType>) -> AppResult<Self> {
+        Ok(Self {
+            ignore: IgnoreMatcher::new(root, project_type)?,
+            debounce: DEFAULT_DEBOUNCE,
+        })
+    }
+
+    /// How long to wait for more events before ending a batch.
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// The canonical workspace root being watched.
+    pub fn root(&self) -> &Path {
+        self.ignore.root()
+    }
+
+    /// Whether changes to a path are ignored.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        self.ignore.is_ignored(path)
+    }
+
+    /// Start watching the workspace recursively.
+    pub fn watch(self) -> AppResult<WorkspaceEvents> {
+        let (tx, rx) = mpsc::channel();
+
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = tx.send(event);
+        })
+        .map_err(|e| watch_error(&e))?;
+
+        watcher
+            .watch(self.root(), RecursiveMode::Recursive)
+            .map_err(|e| watch_error(&e))?;
+
+        tracing::debug!("Watching {} for changes", self.root().display());
+
+        Ok(WorkspaceEvents {
+            watcher: self,
+            rx,
+            _notify: watcher,
+        })
+    }
+}
+
+fn watch_error(error: &notify::Error) -> TramError {
+    TramError::InvalidConfig {
+        message: format!("Failed to watch workspace: {}", error),
+    }
+}
+
+/// A running watch, yielding batches of changes.
+pub struct WorkspaceEvents {
+    watcher: WorkspaceWatcher,
+    rx: Receiver<notify::Result<Event>>,
+    _notify: RecommendedWatcher,
+}
+
+impl WorkspaceEvents {
+    /// The canonical workspace root being watched.
+    pub fn root(&self) -> &Path {
+        self.watcher.root()
+    }
+
+    /// Block until the next batch of changes, or `None` if the watcher stopped.
+    pub fn next_batch(&self) -> Option<Vec<WorkspaceChange>> {
+        self.collect_batch(None)
+    }
+
+    /// Like [`next_batch`](Self::next_batch), but give up after `timeout`
+    /// without any changes.
+    pub fn next_batch_timeout(&self, timeout: Duration) -> Option<Vec<WorkspaceChange>> {
+        self.collect_batch(Some(Instant::now() + timeout))
+    }
+
+    fn collect_batch(&self, deadline: Option<Instant>) -> Option<Vec<WorkspaceChange>> {
+        let mut changes = BTreeMap::new();
+
+        // Wait for the first relevant change
+        while changes.is_empty() {
+            let event = match deadline {
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+                    self.rx.recv_timeout(timeout).ok()?
+                }
+                None => self.rx.recv().ok()?,
+            };
+            self.record(event, &mut changes);
+        }
+
+        // Then take everything until the workspace goes quiet
+        loop {
+            match self.rx.recv_timeout(self.watcher.debounce) {
+                Ok(event) => self.record(event, &mut changes),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        Some(
+            changes
+                .into_iter()
+                .map(|(path, kind)| WorkspaceChange { path, kind })
+                .collect(),
+        )
+    }
+
+    /// Add an event's paths to the batch, keeping the latest kind per path
+    /// except that a new file stays `Created` while it's being written.
+    fn record(&self, event: notify::Result<Event>, changes: &mut BTreeMap<PathBuf, ChangeKind>) {
+        let event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                tracing::warn!("File watcher error: {}", e);
+                return;
+            }
+        };
+
+        let kind = match event.kind {
+            EventKind::Create(_) => ChangeKind::Created,
+            EventKind::Remove(_) => ChangeKind::Removed,
+            EventKind::Modify(ModifyKind::Metadata(_)) => return,
+            EventKind::Modify(_) => ChangeKind::Modified,
+            _ => return,
+        };
+
+        for path in event.paths {
+            if self.watcher.is_ignored(&path) {
+                tracing::trace!("Ignoring change to {}", path.display());
+                continue;
+            }
+
+            changes
+                .entry(path)
+                .and_modify(|existing| {
+                    if !(*existing == ChangeKind::Created && kind == ChangeKind::Modified) {
+                        *existing = kind;
+                    }
+                })
+                .or_insert(kind);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_ignores_project_and_gitignore_patterns() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+
+        let watcher = WorkspaceWatcher::new(root, Some(&ProjectType::Rust)).unwrap();
+        let root = watcher.root().to_path_buf();
+
+        assert!(watcher.is_ignored(&root.join("target/debug/tram")));
+        assert!(watcher.is_ignored(&root.join("build.log")));
+        assert!(watcher.is_ignored(&root.join(".git/index")));
+        assert!(!watcher.is_ignored(&root.join("src/main.rs")));
+        assert!(watcher.is_ignored(Path::new("/elsewhere/main.rs")));
+    }
+
+    #[test]
+    fn test_batches_changes_outside_ignored_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("target")).unwrap();
+
+        let events = WorkspaceWatcher::new(root, Some(&ProjectType::Rust))
+            .unwrap()
+            .with_debounce(Duration::from_millis(100))
+            .watch()
+            .unwrap();
+        let root = root.canonicalize().unwrap();
+
+        fs::write(root.join("target/ignored.o"), "").unwrap();
+        fs::write(root.join("lib.rs"), "fn main() {}").unwrap();
+
+        let batch = events
+            .next_batch_timeout(Duration::from_secs(5))
+            .expect("should see the change");
+
+        assert!(
+            batch
+                .iter()
+                .any(|change| change.path == root.join("lib.rs"))
+        );
+        assert!(
+            batch
+                .iter()
+                .all(|change| !change.path.starts_with(root.join("target")))
+        );
+    }
+
+    #[test]
+    fn test_burst_of_changes_is_one_batch() {
+        let temp_dir = TempDir::new().unwrap();
+        let events = WorkspaceWatcher::new(temp_dir.path(), None)
+            .unwrap()
+            .with_debounce(Duration::from_millis(300))
+            .watch()
+            .unwrap();
+
+        for i in 0..50 {
+            fs::write(temp_dir.path().join(format!("file{}.rs", i)), "").unwrap();
+        }
+
+        let batch = events
+            .next_batch_timeout(Duration::from_secs(5))
+            .expect("should see the changes");
+        assert_eq!(batch.len(), 50);
+        assert!(
+            events
+                .next_batch_timeout(Duration::from_millis(500))
+                .is_none()
+        );
+    }
+}
@@ -13,7 +13,9 @@ use miette::Result;
 use starbase::{App, AppSession};
 use std::path::PathBuf;
 use tracing::info;
-use tram_config::{ConfigChangeHandler, ConfigWatcher, LogLevel, OutputFormat, TramConfig};
+use tram_config::{
+    ConfigChangeHandler, ConfigOverlay, ConfigWatcher, LogLevel, OutputFormat, TramConfig,
+};
 
 /// Configuration usage CLI example
 #[derive(Parser, Debug)]
@@ -214,7 +216,7 @@ async fn watch_config(file: Option<PathBuf>, duration: u64) -> Result<()> {
     show_config(&config, false);
 
     // Set up config watcher
-    let watcher = ConfigWatcher::new(config, file.map(|f| vec![f]))
+    let watcher = ConfigWatcher::new(config, file.map(|f| vec![f]), ConfigOverlay::default())
         .await
         .map_err(|e| miette::miette!("Failed to create config watcher: {}", e))?;
 
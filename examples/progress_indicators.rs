@@ -15,6 +15,7 @@ use starbase::{App, AppSession};
 use std::time::Duration;
 use tokio::time::{Instant, sleep};
 use tracing::info;
+use tram_ui::{MultiProgress, ProgressBar, Spinner, Term};
 
 /// Progress indicators CLI example
 #[derive(Parser, Debug)]
@@ -118,129 +119,13 @@ impl AppSession for ProgressSession {
     }
 }
 
-/// Simple progress bar implementation
-struct ProgressBar {
-    current: usize,
-    total: usize,
-    width: usize,
-    start_time: Instant,
-    use_color: bool,
-}
-
-impl ProgressBar {
-    fn new(total: usize, use_color: bool) -> Self {
-        Self {
-            current: 0,
-            total,
-            width: 50,
-            start_time: Instant::now(),
-            use_color,
-        }
-    }
-
-    fn update(&mut self, current: usize) {
-        self.current = current;
-        self.render();
-    }
-
-    fn finish(&self) {
-        println!();
-        let elapsed = self.start_time.elapsed();
-        if self.use_color {
-            println!(
-                "\x1b[32m✓ Completed in {:.2}s\x1b[0m",
-                elapsed.as_secs_f64()
-            );
-        } else {
-            println!("✓ Completed in {:.2}s", elapsed.as_secs_f64());
-        }
-    }
-
-    fn render(&self) {
-        let percentage = if self.total > 0 {
-            (self.current as f64 / self.total as f64 * 100.0) as usize
-        } else {
-            0
-        };
-
-        let filled = (self.current as f64 / self.total as f64 * self.width as f64) as usize;
-        let empty = self.width - filled;
-
-        let elapsed = self.start_time.elapsed().as_secs_f64();
-        let rate = if elapsed > 0.0 {
-            self.current as f64 / elapsed
-        } else {
-            0.0
-        };
-        let eta = if rate > 0.0 && self.current < self.total {
-            (self.total - self.current) as f64 / rate
-        } else {
-            0.0
-        };
-
-        if self.use_color {
-            print!(
-                "\r\x1b[K\x1b[36m[\x1b[32m{}\x1b[37m{}\x1b[36m] \x1b[33m{:3}%\x1b[0m {}/{} \x1b[90m({:.1}/s, ETA: {:.0}s)\x1b[0m",
-                "=".repeat(filled),
-                "-".repeat(empty),
-                percentage,
-                self.current,
-                self.total,
-                rate,
-                eta
-            );
-        } else {
-            print!(
-                "\r\x1b[K[{}{}] {:3}% {}/{} ({:.1}/s, ETA: {:.0}s)",
-                "=".repeat(filled),
-                "-".repeat(empty),
-                percentage,
-                self.current,
-                self.total,
-                rate,
-                eta
-            );
-        }
-        use std::io::{self, Write};
-        let _ = io::stdout().flush();
-    }
-}
-
-/// Simple spinner implementation
-struct Spinner {
-    frames: Vec<&'static str>,
-    current_frame: usize,
-    use_color: bool,
-}
-
-impl Spinner {
-    fn new(use_color: bool) -> Self {
-        Self {
-            frames: vec!["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
-            current_frame: 0,
-            use_color,
-        }
-    }
-
-    fn update(&mut self, message: &str) {
-        let frame = self.frames[self.current_frame];
-        self.current_frame = (self.current_frame + 1) % self.frames.len();
-
-        if self.use_color {
-            print!("\r\x1b[K\x1b[36m{}\x1b[0m {}", frame, message);
-        } else {
-            print!("\r\x1b[K{} {}", frame, message);
-        }
-        use std::io::{self, Write};
-        let _ = io::stdout().flush();
-    }
-
-    fn finish(&self, message: &str) {
-        if self.use_color {
-            println!("\r\x1b[K\x1b[32m✓\x1b[0m {}", message);
-        } else {
-            println!("\r\x1b[K✓ {}", message);
-        }
+/// Terminal for the demos, honoring `--no-color`
+fn term(use_color: bool) -> Term {
+    let term = Term::stderr();
+    if use_color {
+        term
+    } else {
+        term.without_color()
     }
 }
 
@@ -248,16 +133,18 @@ impl Spinner {
 async fn demo_progress_bar(steps: usize, delay: u64, use_color: bool) -> Result<()> {
     println!("Demonstrating progress bar ({} steps):", steps);
 
-    let mut progress = ProgressBar::new(steps, use_color);
+    let progress = ProgressBar::new(steps as u64).with_term(term(use_color));
+    let start = Instant::now();
 
-    for i in 0..=steps {
-        progress.update(i);
-        if i < steps {
-            sleep(Duration::from_millis(delay)).await;
-        }
+    for _ in 0..steps {
+        sleep(Duration::from_millis(delay)).await;
+        progress.inc(1);
     }
 
-    progress.finish();
+    progress.finish_with_message(format!(
+        "Completed in {:.2}s",
+        start.elapsed().as_secs_f64()
+    ));
     Ok(())
 }
 
@@ -265,24 +152,23 @@ async fn demo_progress_bar(steps: usize, delay: u64, use_color: bool) -> Result<
 async fn demo_spinner(duration: u64, use_color: bool) -> Result<()> {
     println!("Demonstrating spinner ({}s):", duration);
 
-    let mut spinner = Spinner::new(use_color);
+    let spinner = Spinner::new("Processing...").with_term(term(use_color));
+    spinner.enable_steady_tick(Duration::from_millis(80));
     let start = Instant::now();
 
     while start.elapsed().as_secs() < duration {
-        let elapsed = start.elapsed().as_secs();
-        let remaining = duration - elapsed;
-
-        spinner.update(&format!("Processing... ({}s remaining)", remaining));
+        let remaining = duration - start.elapsed().as_secs();
+        spinner.set_message(format!("Processing... ({}s remaining)", remaining));
         sleep(Duration::from_millis(100)).await;
     }
 
-    spinner.finish("Processing complete!");
+    spinner.finish_with_message("Processing complete!");
     Ok(())
 }
 
 /// Demonstrate multi-step progress
 async fn demo_multi_step(items_per_phase: usize, delay: u64, use_color: bool) -> Result<()> {
-    let phases = vec![
+    let phases = [
         ("Initializing", items_per_phase),
         ("Processing", items_per_phase * 2),
         ("Validating", items_per_phase),
@@ -291,68 +177,61 @@ async fn demo_multi_step(items_per_phase: usize, delay: u64, use_color: bool) ->
 
     println!("Demonstrating multi-step progress:");
 
-    for (phase_name, items) in phases {
-        if use_color {
-            println!("\n\x1b[1m{}\x1b[0m", phase_name);
-        } else {
-            println!("\n{}", phase_name);
-        }
-
-        let mut progress = ProgressBar::new(items, use_color);
+    let term = term(use_color);
+    for (index, (phase_name, items)) in phases.iter().enumerate() {
+        let progress = ProgressBar::new(*items as u64)
+            .with_term(term.clone())
+            .with_prefix(format!("[{}/{}] {}", index + 1, phases.len(), phase_name))
+            .with_template("{prefix:18} [{bar:40}] {pos}/{total} ETA {eta}");
 
-        for i in 0..=items {
-            progress.update(i);
-            if i < items {
-                sleep(Duration::from_millis(delay)).await;
-            }
+        for _ in 0..*items {
+            sleep(Duration::from_millis(delay)).await;
+            progress.inc(1);
         }
 
         progress.finish();
     }
 
-    if use_color {
-        println!("\n\x1b[32m🎉 All phases completed successfully!\x1b[0m");
-    } else {
-        println!("\n🎉 All phases completed successfully!");
-    }
+    println!(
+        "\n{}",
+        term.style(
+            "🎉 All phases completed successfully!",
+            term.theme().success
+        )
+    );
 
     Ok(())
 }
 
 /// Demonstrate concurrent progress bars
 async fn demo_concurrent(tasks: usize, max_steps: usize, use_color: bool) -> Result<()> {
-    println!("Demonstrating concurrent progress (simulated):");
-    println!(
-        "Note: This example shows the pattern - real concurrent progress would require more complex terminal handling\n"
-    );
+    println!("Demonstrating concurrent progress:");
 
+    let multi = MultiProgress::with_term(term(use_color));
     let mut task_handles = Vec::new();
 
     for task_id in 1..=tasks {
-        let task_use_color = use_color;
-        let task_steps = max_steps - (task_id * 2); // Vary the number of steps
+        // Vary the number of steps
+        let task_steps = max_steps.saturating_sub(task_id * 2).max(1);
+        let progress = multi.add(
+            ProgressBar::new(task_steps as u64)
+                .with_prefix(format!("task {}", task_id))
+                .with_template("{prefix} [{bar:30}] {pos}/{total} ({rate}) {msg}"),
+        );
 
         let handle = tokio::spawn(async move {
-            let mut progress = ProgressBar::new(task_steps, task_use_color);
-
-            for i in 0..=task_steps {
-                progress.update(i);
-
-                if i < task_steps {
-                    // Vary delay to simulate different task speeds
-                    let delay = 100 + (task_id as u64 * 50);
-                    sleep(Duration::from_millis(delay)).await;
-                }
+            for _ in 0..task_steps {
+                // Vary delay to simulate different task speeds
+                let delay = 100 + (task_id as u64 * 50);
+                sleep(Duration::from_millis(delay)).await;
+                progress.inc(1);
             }
 
+            progress.set_message("done");
             progress.finish();
-            println!("Task {} completed", task_id);
         });
 
         task_handles.push(handle);
-
-        // Small delay between starting tasks
-        sleep(Duration::from_millis(200)).await;
     }
 
     // Wait for all tasks to complete
@@ -362,11 +241,7 @@ async fn demo_concurrent(tasks: usize, max_steps: usize, use_color: bool) -> Res
             .map_err(|e| miette::miette!("Task failed: {}", e))?;
     }
 
-    if use_color {
-        println!("\n\x1b[32m✓ All concurrent tasks completed!\x1b[0m");
-    } else {
-        println!("\n✓ All concurrent tasks completed!");
-    }
+    multi.println("✓ All concurrent tasks completed!");
 
     Ok(())
 }
@@ -375,35 +250,23 @@ async fn demo_concurrent(tasks: usize, max_steps: usize, use_color: bool) -> Res
 async fn demo_file_processing(files: usize, delay: u64, use_color: bool) -> Result<()> {
     println!("Demonstrating file processing progress:");
 
-    let file_names = (1..=files)
-        .map(|i| format!("file_{:03}.txt", i))
-        .collect::<Vec<_>>();
-
-    let mut progress = ProgressBar::new(files, use_color);
+    let multi = MultiProgress::with_term(term(use_color));
+    let progress = multi
+        .add(ProgressBar::new(files as u64).with_template("[{bar}] {pos}/{total} ETA {eta} {msg}"));
 
-    for (i, filename) in file_names.iter().enumerate() {
-        progress.update(i);
-
-        // Show current file being processed
-        if use_color {
-            println!("\n\x1b[90mProcessing: {}\x1b[0m", filename);
-        } else {
-            println!("\nProcessing: {}", filename);
-        }
+    for i in 1..=files {
+        let filename = format!("file_{:03}.txt", i);
+        progress.set_message(filename.clone());
 
         // Simulate file processing
         sleep(Duration::from_millis(delay)).await;
 
-        // Show completion
-        if use_color {
-            println!("\x1b[32m  ✓ Completed: {}\x1b[0m", filename);
-        } else {
-            println!("  ✓ Completed: {}", filename);
-        }
+        // Completed files scroll above the bar
+        multi.println(&format!("  ✓ Completed: {}", filename));
+        progress.inc(1);
     }
 
-    progress.update(files);
-    progress.finish();
+    progress.finish_with_message(format!("Processed {} files", files));
 
     Ok(())
 }
@@ -0,0 +1,129 @@
+//! Saving tokens with `tram auth`.
+//!
+//! Tokens are kept under the `tram` service in the OS keychain or, where no
+//! keychain is running, in the encrypted credentials file in the data
+//! directory. Config refers to them as `secret://keyring/<name>`. `login`
+//! and `logout` honor `--dry-run`, and no action ever prints a token.
+
+use serde::Serialize;
+use std::io::Read;
+use tram_core::{APP_NAME, TramError, t};
+use tram_keyring::Keyring;
+use tram_output::{CommandOutput, Render};
+use tram_prompt::Password;
+
+use crate::cli::AuthAction;
+use crate::session::TramSession;
+
+/// Run a `tram auth` action.
+pub fn execute(session: &TramSession, action: AuthAction) -> tram_core::AppResult<CommandOutput> {
+    let keyring = Keyring::open_default()?;
+    let backend = keyring.backend().to_string();
+
+    let (name, status) = match action {
+        AuthAction::Login { name, stdin } => {
+            let token = if stdin {
+                read_token()?
+            } else {
+                session.prompter.password(&Password::new(t!(
+                    "auth-token-prompt",
+                    name = name.as_str()
+                )))?
+            };
+            if !session.dry_run {
+                keyring.store(APP_NAME, &name, &token)?;
+            }
+            (name, AuthStatus::Saved)
+        }
+        AuthAction::Logout { name } => {
+            let stored = if session.dry_run {
+                keyring.get(APP_NAME, &name)?.is_some()
+            } else {
+                keyring.delete(APP_NAME, &name)?
+            };
+            let status = if stored {
+                AuthStatus::Removed
+            } else {
+                AuthStatus::Missing
+            };
+            (name, status)
+        }
+        AuthAction::Status { name } => {
+            let status = if keyring.get(APP_NAME, &name)?.is_some() {
+                AuthStatus::Stored
+            } else {
+                AuthStatus::Missing
+            };
+            (name, status)
+        }
+    };
+
+    CommandOutput::new(&AuthResult {
+        name,
+        backend,
+        status,
+        dry_run: session.dry_run,
+    })
+}
+
+/// A token piped to stdin, without its trailing newline.
+fn read_token() -> tram_core::AppResult<String> {
+    let mut token = String::new();
+    std::io::stdin()
+        .read_to_string(&mut token)
+        .map_err(|e| TramError::Secret {
+            message: format!("Failed to read the token from stdin: {}", e),
+        })?;
+
+    let token = token.trim_end_matches(['\n', '\r']);
+    if token.is_empty() {
+        return Err(TramError::InvalidInput {
+            input: "token".to_string(),
+            message: "stdin was empty".to_string(),
+        }
+        .into());
+    }
+
+    Ok(token.to_string())
+}
+
+/// What `tram auth` did, or found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuthStatus {
+    Saved,
+    Removed,
+    Stored,
+    Missing,
+}
+
+/// Result of `tram auth`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthResult {
+    pub name: String,
+    /// `keychain` or `encrypted-file`
+    pub backend: String,
+    pub status: AuthStatus,
+    pub dry_run: bool,
+}
+
+impl Render for AuthResult {
+    fn render_plain(&self) -> String {
+        let name = self.name.as_str();
+        let backend = self.backend.as_str();
+
+        let message = match self.status {
+            AuthStatus::Saved => t!("auth-saved", name = name, backend = backend),
+            AuthStatus::Removed => t!("auth-removed", name = name, backend = backend),
+            AuthStatus::Stored => t!("auth-stored", name = name, backend = backend),
+            AuthStatus::Missing => t!("auth-missing", name = name, backend = backend),
+        };
+
+        if self.dry_run && matches!(self.status, AuthStatus::Saved | AuthStatus::Removed) {
+            format!("{}\n{}", t!("auth-dry-run"), message)
+        } else {
+            message
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Running many commands against one session with `tram batch`.
+//!
+//! Each line of input is one invocation, either shell-quoted like the
+//! interactive shell (`generate component Button --template-type rust`) or a
+//! JSON array of arguments (`["new", "my-app", "--skip-prompts"]`). Blank
+//! lines and `#` comments are skipped. Every command runs even if an earlier
+//! one failed, and the results are reported together at the end. `--dry-run`
+//! applies per line; other global options are fixed for the whole batch.
+
+use clap::{CommandFactory, FromArgMatches};
+use serde::Serialize;
+use std::io::Read;
+use std::path::Path;
+use tram_core::{ErrorReport, ExitCode, TramError, t};
+use tram_output::{CommandOutput, Render, Renderer};
+use tram_ui::Table;
+
+use crate::cli::{Cli, Commands, WorkspaceAction};
+use crate::commands::execute_command;
+use crate::session::TramSession;
+
+/// The outcome of one line of the batch.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchItem {
+    /// Line number in the input, starting at 1
+    pub line: usize,
+    pub command: String,
+    pub exit_code: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorReport>,
+    #[serde(skip)]
+    plain: String,
+}
+
+impl BatchItem {
+    fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+
+    fn fail(&mut self, error: ErrorReport) {
+        self.exit_code = error.exit_code;
+        self.error = Some(error);
+    }
+}
+
+/// Result of `tram batch`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchResult {
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BatchItem>,
+}
+
+impl Render for BatchResult {
+    fn render_plain(&self) -> String {
+        let mut lines = Vec::new();
+
+        for item in &self.results {
+            match &item.error {
+                None => {
+                    lines.push(t!("batch-succeeded", command = item.command.clone()));
+                    lines.extend(item.plain.lines().map(|line| format!("  {}", line)));
+                }
+                Some(error) => lines.push(t!(
+                    "batch-failed",
+                    command = item.command.clone(),
+                    error = error.message.clone()
+                )),
+            }
+        }
+
+        lines.push(t!(
+            "batch-summary",
+            succeeded = self.succeeded,
+            total = self.results.len()
+        ));
+
+        lines.join("\n")
+    }
+
+    fn render_table(&self) -> Option<Table> {
+        let mut table = Table::new([t!("batch-line"), t!("batch-command"), t!("batch-status")]);
+
+        for item in &self.results {
+            let status = match &item.error {
+                None => t!("batch-status-ok"),
+                Some(error) => format!("{}: {}", t!("batch-status-failed"), error.message),
+            };
+            table.add_row([item.line.to_string(), item.command.clone(), status]);
+        }
+
+        Some(table)
+    }
+}
+
+/// Run every command in the input, then print the results. Fails if any
+/// command failed.
+pub async fn run(session: &TramSession, input: &Path) -> tram_core::AppResult<()> {
+    let text = read_input(input)?;
+    let mut results = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        results.push(run_line(index + 1, line, session).await);
+    }
+
+    let failed = results.iter().filter(|item| !item.succeeded()).count();
+    let result = BatchResult {
+        succeeded: results.len() - failed,
+        failed,
+        results,
+    };
+
+    Renderer::from_config(&session.config)
+        .with_verbosity(session.verbosity)
+        .print(&CommandOutput::new(&result)?)?;
+
+    if failed > 0 {
+        return Err(TramError::Batch {
+            failed,
+            total: result.results.len(),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+fn read_input(input: &Path) -> tram_core::AppResult<String> {
+    let read = if input == Path::new("-") {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text).map(|_| text)
+    } else {
+        std::fs::read_to_string(input)
+    };
+
+    read.map_err(|e| {
+        TramError::Io {
+            message: format!("Failed to read {}: {}", input.display(), e),
+        }
+        .into()
+    })
+}
+
+/// Parse and run one line, capturing its result instead of printing it.
+async fn run_line(number: usize, line: &str, session: &TramSession) -> BatchItem {
+    let mut item = BatchItem {
+        line: number,
+        command: line.to_string(),
+        exit_code: ExitCode::Success.code(),
+        output: None,
+        error: None,
+        plain: String::new(),
+    };
+
+    let words = match split_line(line) {
+        Ok(words) => words,
+        Err(message) => {
+            item.fail(usage_error(message));
+            return item;
+        }
+    };
+    item.command =
+        shlex::try_join(words.iter().map(String::as_str)).unwrap_or_else(|_| words.join(" "));
+
+    let args = std::iter::once("tram".to_string()).chain(words);
+    let (cli, matches) = match Cli::command()
+        .try_get_matches_from(args)
+        .and_then(|matches| Cli::from_arg_matches(&matches).map(|cli| (cli, matches)))
+    {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            // Only the first line, not clap's usage and help hint
+            let message = e.to_string();
+            let message = message.lines().next().unwrap_or_default();
+            item.fail(usage_error(
+                message.trim_start_matches("error: ").to_string(),
+            ));
+            return item;
+        }
+    };
+
+    if let Commands::External(args) = &cli.command {
+        let (name, _) = crate::plugins::split_args(args);
+        let wasm = crate::plugins::registry(session.workspace_root.as_deref());
+        if !crate::plugins::is_plugin(&name, &wasm) {
+            let mut error = usage_error(format!("unrecognized subcommand '{}'", name));
+            error.help = crate::plugins::suggestion(&Cli::command(), &name, &wasm);
+            item.fail(error);
+            return item;
+        }
+    }
+
+    if !runs_in_batch(&cli.command) {
+        let command = matches.subcommand_name().unwrap_or_default().to_string();
+        item.fail(usage_error(t!("batch-unsupported", command = command)));
+        return item;
+    }
+
+    let mut session = session.clone();
+    if cli.global.dry_run {
+        session.dry_run = true;
+    }
+
+    match Box::pin(execute_command(cli.command, &session)).await {
+        Ok(Some(output)) => {
+            item.output = Some(output.value().clone());
+            item.plain = output.plain().to_string();
+        }
+        Ok(None) => {}
+        Err(report) => item.fail(ErrorReport::from_report(&report)),
+    }
+
+    item
+}
+
+/// Whether a command's result can be captured into its line. Commands that
+/// take over the terminal, or print straight to stdout instead of returning
+/// output, would interleave with the batch result.
+fn runs_in_batch(command: &Commands) -> bool {
+    !matches!(
+        command,
+        Commands::Batch { .. }
+            | Commands::Shell
+            | Commands::Watch { .. }
+            | Commands::Daemon { .. }
+            | Commands::External(_)
+            | Commands::Doctor
+            | Commands::Hook { .. }
+            | Commands::PromptSegment
+            | Commands::Run {
+                task: Some(_),
+                list: false,
+                tree: false,
+                ..
+            }
+            | Commands::Examples {
+                example: Some(_),
+                list: false,
+                ..
+            }
+            | Commands::Examples {
+                list: false,
+                interactive: true,
+                ..
+            }
+            | Commands::Completions {
+                shell: Some(_),
+                install: false,
+            }
+            | Commands::Man { install: false, .. }
+            | Commands::Workspace {
+                action: Some(WorkspaceAction::Graph { .. }),
+                ..
+            }
+    )
+}
+
+/// Split a line into arguments: a JSON array of strings, or shell words.
+fn split_line(line: &str) -> Result<Vec<String>, String> {
+    if line.starts_with('[') {
+        serde_json::from_str(line).map_err(|e| t!("batch-invalid-json", error = e.to_string()))
+    } else {
+        shlex::split(line).ok_or_else(|| t!("batch-unbalanced-quotes"))
+    }
+}
+
+fn usage_error(message: String) -> ErrorReport {
+    ErrorReport {
+        code: None,
+        exit_code: ExitCode::Usage.code(),
+        message,
+        help: None,
+        chain: Vec::new(),
+    }
+}
@@ -0,0 +1,241 @@
+//! Managing the on-disk cache with `tram cache`.
+//!
+//! `info` shows where the cache lives and how much it holds, `clear` deletes
+//! every entry, and `prune` removes entries past their time-to-live and
+//! orphaned files, then evicts the least recently used entries to fit the
+//! size budget. Both cleanup actions honor `--dry-run` and report what they
+//! would remove.
+//!
+//! The budget is `cache.maxSize` in config. Once `cache.gcIntervalHours` have
+//! passed since the last prune, [`spawn_background_gc`] runs one at shutdown
+//! in a detached `tram cache prune`, so commands never wait for it. The
+//! prune holds the cache's manifest lock while it decides what to remove, so
+//! it's safe alongside other tram processes reading and writing the cache.
+
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Duration;
+use tram_config::TramConfig;
+use tram_core::{Cache, TramError, format_bytes, parse_bytes, t};
+use tram_output::{CommandOutput, Render};
+use tram_ui::Table;
+
+use crate::cli::CacheAction;
+use crate::session::TramSession;
+
+/// Result of `tram cache info`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheInfo {
+    pub path: PathBuf,
+    pub entries: usize,
+    pub expired: usize,
+    pub size: u64,
+    /// Size budget from config, if any
+    pub max_size: Option<u64>,
+}
+
+impl Render for CacheInfo {
+    fn render_plain(&self) -> String {
+        [
+            t!("cache-path", path = self.path.display().to_string()),
+            t!(
+                "cache-entries",
+                count = self.entries,
+                expired = self.expired
+            ),
+            match self.max_size {
+                Some(max_size) => t!(
+                    "cache-size-budget",
+                    size = format_bytes(self.size),
+                    max_size = format_bytes(max_size)
+                ),
+                None => t!("cache-size", size = format_bytes(self.size)),
+            },
+        ]
+        .join("\n")
+    }
+
+    fn render_table(&self) -> Option<Table> {
+        let mut table = Table::new([t!("workspace-property"), t!("config-value")]);
+        table.add_row([t!("cache-path-label"), self.path.display().to_string()]);
+        table.add_row([t!("cache-entries-label"), self.entries.to_string()]);
+        table.add_row([t!("cache-expired-label"), self.expired.to_string()]);
+        table.add_row([t!("cache-size-label"), format_bytes(self.size)]);
+        table.add_row([
+            t!("cache-max-size-label"),
+            self.max_size
+                .map(format_bytes)
+                .unwrap_or_else(|| t!("cache-unlimited")),
+        ]);
+
+        Some(table)
+    }
+}
+
+/// Result of `tram cache clear` and `tram cache prune`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheCleanup {
+    pub path: PathBuf,
+    /// Entries removed, or that would be in a dry run
+    pub removed: usize,
+    /// Bytes freed, or that would be in a dry run
+    pub freed: u64,
+    /// Orphaned files removed by `prune`, or that would be in a dry run
+    pub orphaned: usize,
+    pub dry_run: bool,
+}
+
+impl Render for CacheCleanup {
+    fn render_plain(&self) -> String {
+        let (key, orphaned_key) = if self.dry_run {
+            ("cache-dry-run", "cache-orphaned-dry-run")
+        } else {
+            ("cache-removed", "cache-orphaned")
+        };
+
+        let message = t!(
+            key,
+            count = self.removed,
+            size = format_bytes(self.freed),
+            path = self.path.display().to_string()
+        );
+
+        if self.orphaned > 0 {
+            format!("{}\n{}", message, t!(orphaned_key, count = self.orphaned))
+        } else {
+            message
+        }
+    }
+}
+
+/// Open the default cache with the size budget from config.
+pub fn open(config: &TramConfig) -> tram_core::AppResult<Cache> {
+    let cache = Cache::open_default()?;
+
+    Ok(match configured_max_size(config)? {
+        Some(max_size) => cache.with_max_size(max_size),
+        None => cache,
+    })
+}
+
+/// `cache.maxSize` in bytes.
+fn configured_max_size(config: &TramConfig) -> tram_core::AppResult<Option<u64>> {
+    config
+        .cache
+        .max_size
+        .as_deref()
+        .map(|size| {
+            parse_bytes(size).map_err(|e| {
+                TramError::InvalidConfig {
+                    message: format!("cache.maxSize: {}", e),
+                }
+                .into()
+            })
+        })
+        .transpose()
+}
+
+/// Start a detached `tram cache prune` if garbage collection is due. Runs
+/// at shutdown, and never fails the command that triggered it.
+///
+/// The child is given this cache and its size limit rather than loading
+/// config again, so a limit from `--config` or `--profile` still applies.
+pub fn spawn_background_gc(config: &TramConfig) {
+    if config.cache.gc_interval_hours == 0 {
+        return;
+    }
+    // Nothing to collect, and no reason to create the cache, before first use
+    if !tram_core::cache_dir().is_some_and(|dir| dir.exists()) {
+        return;
+    }
+    let Ok(cache) = Cache::open_default() else {
+        return;
+    };
+    let interval = Duration::from_secs(config.cache.gc_interval_hours * 60 * 60);
+    // Claim this run before starting it so other commands don't start one too
+    match cache.claim_gc(interval) {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(e) => {
+            tracing::debug!("Skipping cache garbage collection: {}", e);
+            return;
+        }
+    }
+    let Ok(exe) = std::env::current_exe() else {
+        return;
+    };
+
+    let mut command = std::process::Command::new(exe);
+    command
+        .args(["--log-level", "error", "--format", "json", "cache", "prune"])
+        .env(tram_core::CACHE_DIR_ENV, cache.root())
+        .env(crate::daemon::NO_DAEMON_ENV, "1")
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    if let Ok(Some(max_size)) = configured_max_size(config) {
+        command.arg("--max-size").arg(format!("{}B", max_size));
+    }
+    // Keep Ctrl+C in this terminal from interrupting it
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+    match command.spawn() {
+        Ok(_) => tracing::debug!("Started background cache garbage collection"),
+        Err(e) => tracing::debug!("Failed to start cache garbage collection: {}", e),
+    }
+}
+
+/// Run a `tram cache` action against the default cache.
+pub fn run(session: &TramSession, action: CacheAction) -> tram_core::AppResult<CommandOutput> {
+    let cache = Cache::open_default()?;
+    let path = cache.root().to_path_buf();
+    let stats = cache.stats()?;
+    let max_size = configured_max_size(&session.config)?;
+
+    match action {
+        CacheAction::Info => CommandOutput::new(&CacheInfo {
+            path,
+            entries: stats.entries,
+            expired: stats.expired,
+            size: stats.size,
+            max_size,
+        }),
+
+        CacheAction::Clear => {
+            if !session.dry_run {
+                cache.clear()?;
+            }
+
+            CommandOutput::new(&CacheCleanup {
+                path,
+                removed: stats.entries,
+                freed: stats.size,
+                orphaned: 0,
+                dry_run: session.dry_run,
+            })
+        }
+
+        CacheAction::Prune { max_size: flag } => {
+            let max_size = match flag {
+                Some(size) => Some(parse_bytes(&size)?),
+                None => max_size,
+            };
+            let pruned = if session.dry_run {
+                cache.prune_preview(max_size)?
+            } else {
+                cache.prune(max_size)?
+            };
+
+            CommandOutput::new(&CacheCleanup {
+                path,
+                removed: pruned.entries(),
+                freed: pruned.freed,
+                orphaned: pruned.orphaned,
+                dry_run: session.dry_run,
+            })
+        }
+    }
+}
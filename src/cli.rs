@@ -3,8 +3,10 @@
 //! This module defines the command-line interface structure using clap's derive API,
 //! including all commands, options, and argument types.
 
-use clap::Parser;
+use clap::builder::{PossibleValue, PossibleValuesParser, TypedValueParser};
+use clap::{ArgAction, Parser};
 use clap_complete::shells::Shell;
+use std::ffi::OsString;
 
 /// CLI structure demonstrating clap + starbase patterns.
 #[derive(Parser, Debug)]
@@ -24,21 +26,115 @@ pub struct Cli {
 /// Global CLI options that apply to all commands.
 #[derive(Parser, Debug)]
 pub struct GlobalOptions {
-    /// Log level (debug, info, warn, error)
-    #[arg(long, default_value = "info")]
+    /// Log level (trace, debug, info, warn, error)
+    #[arg(long, default_value = "info", value_parser = log_level_values(), ignore_case = true)]
     pub log_level: String,
 
-    /// Output format (json, yaml, table)
-    #[arg(long, default_value = "table")]
-    pub format: String,
+    /// Only print errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
 
-    /// Disable colored output
-    #[arg(long, default_value = "false")]
+    /// Print more detail; repeat for more (-v debug logs, -vv trace logs)
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Output format (json, yaml, table, plain, csv, tsv) [default: from config, or table]
+    #[arg(long, value_parser = output_format_values(), ignore_case = true)]
+    pub format: Option<String>,
+
+    /// When to use colors (auto, always, never) [default: auto]
+    #[arg(long, value_enum, value_name = "WHEN")]
+    pub color: Option<ColorWhen>,
+
+    /// Disable colored output (same as --color never)
+    #[arg(long, default_value = "false", conflicts_with = "color")]
     pub no_color: bool,
 
     /// Config file path
     #[arg(long)]
     pub config: Option<std::path::PathBuf>,
+
+    /// Also write complete debug logs to this file, e.g. to attach to a bug report
+    #[arg(long, global = true, value_name = "PATH")]
+    pub log_file: Option<std::path::PathBuf>,
+
+    /// Apply the named profile from the config file's `[profiles]` table
+    #[arg(long, global = true, env = "TRAM_PROFILE", value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Show the files and directories a command would create or modify, without writing them
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Never prompt: take the defaults, or fail listing the inputs that have
+    /// none. Implied without a terminal or in CI
+    #[arg(short, long, visible_alias = "non-interactive", global = true)]
+    pub yes: bool,
+
+    /// Never use the network: skip update checks and metrics, use cached
+    /// downloads, and fail anything that needs a connection
+    #[arg(long, global = true)]
+    pub offline: bool,
+
+    /// Stop the command if it runs longer than this, exiting with code 124
+    #[arg(long, global = true, env = "TRAM_TIMEOUT", value_name = "SECS")]
+    pub timeout: Option<std::num::NonZeroU64>,
+
+    /// Emit machine-readable lifecycle events to stderr
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    pub events: Option<EventsFormat>,
+
+    /// Write lifecycle events as JSON lines to this file descriptor
+    #[arg(long, value_name = "FD")]
+    pub events_fd: Option<i32>,
+
+    /// Write a JSON summary of the run (files created and modified,
+    /// warnings, duration, exit code) to this file when the command ends
+    #[arg(long, global = true, value_name = "PATH")]
+    pub summary_file: Option<std::path::PathBuf>,
+
+    /// Print a breakdown of where time was spent
+    #[arg(long)]
+    pub timings: bool,
+
+    /// Write timings as a Chrome trace (chrome://tracing, Perfetto) to this file
+    #[arg(long, value_name = "FILE")]
+    pub timings_trace: Option<std::path::PathBuf>,
+}
+
+/// When to use colors
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorWhen {
+    /// Color terminals, honoring NO_COLOR, CLICOLOR, and CLICOLOR_FORCE
+    Auto,
+    /// Always color, even when piped
+    Always,
+    /// Never color
+    Never,
+}
+
+/// Formats for the lifecycle event stream
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventsFormat {
+    /// One JSON object per line
+    Json,
+}
+
+/// Shells supported by `tram hook`
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// Release channels for `tram self-update`
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReleaseChannel {
+    /// Releases without a pre-release suffix
+    Stable,
+    /// Pre-releases as well as stable releases
+    Beta,
 }
 
 /// Available CLI commands.
@@ -49,19 +145,39 @@ pub enum Commands {
         /// Project name
         name: String,
         /// Project type (rust, nodejs, python, go, java, generic)
-        #[arg(long, default_value = "rust")]
+        #[arg(long, default_value = "rust", value_parser = project_type_values(), ignore_case = true)]
         project_type: String,
         /// Project description
         #[arg(long)]
         description: Option<String>,
-        /// Skip interactive prompts
+        /// Author for the project manifest, e.g. "Jane Doe <jane@example.com>"
+        #[arg(long)]
+        author: Option<String>,
+        /// SPDX license identifier for the project manifest (e.g. MIT, Apache-2.0).
+        /// MIT, Apache-2.0, GPL-3.0-only, BSD-3-Clause, MPL-2.0, ISC, and
+        /// Unlicense are also written to LICENSE
+        #[arg(long, value_name = "SPDX")]
+        license: Option<String>,
+        /// Initialize a git repository with a .gitignore
+        #[arg(long)]
+        git: bool,
+        /// Add a GitHub Actions workflow that builds and tests the project
+        #[arg(long)]
+        ci: bool,
+        /// Skip interactive prompts and use the flags and defaults as given
         #[arg(long)]
         skip_prompts: bool,
     },
     /// Generate templates for common CLI patterns
     Generate {
-        /// Template type (command, config-section, error-type, session-extension)
-        #[arg(long, default_value = "command")]
+        /// Template type (command, config-section, error-type, session-extension),
+        /// or custom:NAME for a template in the workspace's .tram/templates
+        #[arg(
+            long,
+            default_value = "command",
+            value_parser = template_type_values(),
+            ignore_case = true
+        )]
         template_type: String,
         /// Name of the item to generate (e.g., "backup", "deploy")
         name: String,
@@ -74,23 +190,63 @@ pub enum Commands {
         /// Write the template to filesystem (default: show to stdout)
         #[arg(long)]
         write: bool,
+        /// If the file already exists, show a unified diff from it to the
+        /// generated template instead of failing; nothing is written
+        #[arg(long, conflicts_with = "force")]
+        diff: bool,
+        /// Overwrite the file if it already exists
+        #[arg(long, requires = "write")]
+        force: bool,
+        /// Also declare a written command's module and add it to the
+        /// `Commands` enum and its dispatch, at `// tram:generate:*` markers
+        #[arg(long, requires = "write")]
+        register: bool,
+    },
+    /// Bring a project created by `new` up to date with the current templates
+    Upgrade {
+        /// Project directory (defaults to current directory)
+        path: Option<std::path::PathBuf>,
     },
-    /// Initialize a new project (legacy command)
+    /// Initialize a new project (deprecated: use `new`)
     Init {
         /// Project name
         name: String,
-        /// Use verbose output
-        #[arg(short, long)]
-        verbose: bool,
     },
     /// Show workspace information
     Workspace {
-        /// Show detailed project information
+        /// Show detailed project information, including git status
+        /// (always included with `--format json`)
         #[arg(short, long)]
         detailed: bool,
+        /// List the projects in a Cargo, npm, or Go workspace
+        #[arg(long)]
+        members: bool,
+        #[command(subcommand)]
+        action: Option<WorkspaceAction>,
     },
     /// Show configuration information
-    Config,
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+    },
+    /// List deprecated commands, flags, and config keys
+    Deprecations,
+    /// Check the environment for common problems
+    Doctor,
+    /// Show the resolved settings and their sources, env vars, and directories
+    Env,
+    /// List the exit codes tram can return
+    ExitCodes,
+    /// Show, clear, or prune the on-disk cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Turn anonymous usage telemetry on or off, or show its status
+    Telemetry {
+        #[command(subcommand)]
+        action: TelemetryAction,
+    },
     /// Watch mode - monitor files and reload config automatically
     Watch {
         /// Watch configuration files for hot reload
@@ -99,19 +255,79 @@ pub enum Commands {
         /// Run checks on file changes (format, lint, build, test)
         #[arg(long, default_value = "true")]
         check: bool,
+        /// Shell command to run as the checks, instead of `just check` or the
+        /// project type's checks [default: from config]
+        #[arg(long, value_name = "COMMAND")]
+        check_command: Option<String>,
+        /// Milliseconds to wait for more changes before running [default: from config, or 200]
+        #[arg(long, value_name = "MS")]
+        debounce: Option<u64>,
+        /// Show task output in a full-screen view with each task's last
+        /// result and keys to re-run or filter tasks (plain logs when stdout
+        /// isn't a terminal)
+        #[arg(long)]
+        ui: bool,
+        /// Show a full-screen dashboard of the config, watched paths, recent
+        /// events, and task history (needs a build with the `dashboard`
+        /// feature)
+        #[arg(long, conflicts_with = "ui")]
+        dashboard: bool,
+    },
+    /// Run a task defined in the config, after the tasks it depends on
+    Run {
+        /// Task to run
+        #[arg(required_unless_present_any = ["list", "tree"])]
+        task: Option<String>,
+        /// List the configured tasks
+        #[arg(long, conflicts_with = "task")]
+        list: bool,
+        /// Show the task's dependency tree instead of running it, or every
+        /// task's when no task is given
+        #[arg(long, conflicts_with_all = ["list", "jobs", "keep_going"])]
+        tree: bool,
+        /// Run at most this many tasks at once [default: number of CPUs]
+        #[arg(short, long, value_name = "N")]
+        jobs: Option<std::num::NonZeroUsize>,
+        /// Keep running tasks that don't depend on a failed one
+        #[arg(short, long)]
+        keep_going: bool,
     },
     /// Run interactive examples demonstrating CLI patterns
     Examples {
-        /// Example to run
+        /// Example to run; lists the examples when omitted
         #[arg(value_enum)]
-        example: ExampleType,
+        example: Option<ExampleType>,
+        /// List the examples with descriptions
+        #[arg(long, conflicts_with_all = ["example", "interactive"])]
+        list: bool,
+        /// Run the demo in-process instead of describing it; asks which one
+        /// when no example is given
+        #[arg(short, long)]
+        interactive: bool,
     },
     /// Generate shell completions
     Completions {
-        /// Shell to generate completions for
+        /// Shell to generate completions for [default with --install: your
+        /// login shell, from $SHELL]
+        #[arg(value_enum, required_unless_present = "install")]
+        shell: Option<Shell>,
+        /// Write the script to the shell's completion directory instead of
+        /// stdout; PowerShell's goes next to `$PROFILE`, which loads it
+        #[arg(long)]
+        install: bool,
+    },
+    /// Print a shell hook that exports workspace variables and refreshes them on `cd`
+    Hook {
+        /// Shell to print the hook for
         #[arg(value_enum)]
-        shell: Shell,
+        shell: HookShell,
+        /// Print the variables for the current directory instead of the hook
+        #[arg(long)]
+        env: bool,
     },
+    /// Print a compact workspace status (project icon, name, and a `*` when
+    /// the git work tree is dirty) for shell prompts; JSON with `--format json`
+    PromptSegment,
     /// Generate manual pages
     Man {
         /// Output directory for man pages
@@ -120,9 +336,222 @@ pub enum Commands {
         /// Generate only specific section (1-9, default: all)
         #[arg(short, long)]
         section: Option<u8>,
+        /// Install the pages into the man path and update the man database
+        #[arg(long, conflicts_with = "output_dir")]
+        install: bool,
+        /// Install under `<DIR>/share/man` instead of the man path
+        #[arg(long, value_name = "DIR", requires = "install")]
+        prefix: Option<std::path::PathBuf>,
+    },
+    /// Generate Markdown reference pages for docs sites
+    Docs {
+        /// Directory to write the pages to, one per command
+        #[arg(short, long, default_value = "./docs/cli")]
+        output: std::path::PathBuf,
+    },
+    /// Generate OS package manifests with the man pages and completions
+    Package {
+        /// Package manager to generate for
+        #[arg(long, value_enum)]
+        format: PackageFormat,
+        /// Directory to write the manifest and artifacts to
+        #[arg(short, long, default_value = "./dist/package")]
+        output: std::path::PathBuf,
+        /// Release archive URL; `{version}` is replaced by the version
+        /// [default: the GitHub release asset for the format's target]
+        #[arg(long)]
+        url: Option<String>,
+        /// SHA-256 checksum of the release archive
+        #[arg(long, value_name = "HASH")]
+        sha256: Option<String>,
+        /// Package maintainer as `Name <email>`, required for deb
+        #[arg(long, value_name = "NAME")]
+        maintainer: Option<String>,
+    },
+    /// Start an interactive shell that runs commands against one session
+    Shell,
+    /// Run many commands against one session, one per line of input
+    Batch {
+        /// File of commands, or `-` to read them from stdin
+        #[arg(value_name = "INPUT")]
+        input: std::path::PathBuf,
+    },
+    /// Keep a warm session running in the background and serve commands
+    /// to other tram invocations, for fast editor integrations
+    Daemon {
+        #[command(subcommand)]
+        action: DaemonAction,
+    },
+    /// List WASM plugins and render their templates
+    Plugins {
+        #[command(subcommand)]
+        action: PluginAction,
+    },
+    /// Save, remove, and check credentials in the OS keychain
+    Auth {
+        #[command(subcommand)]
+        action: AuthAction,
+    },
+    /// Update tram to the latest release
+    SelfUpdate {
+        /// Release channel to update from [default: the running version's]
+        #[arg(long, value_enum)]
+        channel: Option<ReleaseChannel>,
+
+        /// Only report whether a newer release exists
+        #[arg(long)]
+        check: bool,
+    },
+    /// Run a command from a WASM plugin, or a `tram-<name>` plugin found on PATH
+    #[command(external_subcommand)]
+    External(Vec<OsString>),
+}
+
+/// Actions for `tram cache`
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum CacheAction {
+    /// Show the cache location, entry counts, size, and size budget
+    Info,
+    /// Delete every cache entry
+    Clear,
+    /// Delete expired entries and orphaned files, and shrink the cache to its size budget
+    Prune {
+        /// Evict the least recently used entries until the cache fits,
+        /// e.g. `1GB` or `512MiB` (defaults to `cache.maxSize` in config)
+        #[arg(long, value_name = "SIZE")]
+        max_size: Option<String>,
     },
 }
 
+/// Actions for `tram daemon`
+#[derive(clap::Subcommand, Clone, Copy, Debug)]
+pub enum DaemonAction {
+    /// Serve commands from this workspace until stopped
+    Start {
+        /// Start in the background and return once it's listening
+        #[arg(long)]
+        detach: bool,
+        /// Show a full-screen dashboard of the config, recent events, and
+        /// served commands (needs a build with the `dashboard` feature)
+        #[arg(long, conflicts_with = "detach")]
+        dashboard: bool,
+    },
+    /// Ask the running daemon to stop
+    Stop,
+    /// Show whether a daemon is running and what it has served
+    Status,
+}
+
+/// Actions for `tram auth`
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum AuthAction {
+    /// Save a token, usable in config as `secret://keyring/<NAME>`
+    Login {
+        /// Name to save the token under, e.g. `registry`
+        name: String,
+        /// Read the token from stdin instead of prompting for it
+        #[arg(long)]
+        stdin: bool,
+    },
+    /// Remove a saved token
+    Logout {
+        /// Name the token was saved under
+        name: String,
+    },
+    /// Show whether a token is saved, and where
+    Status {
+        /// Name the token was saved under
+        name: String,
+    },
+}
+
+/// Actions for `tram plugins`
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum PluginAction {
+    /// List installed WASM plugins and what they provide
+    List,
+    /// Render a template from a WASM plugin into files
+    Generate {
+        /// Template name, as shown by `tram plugins list`
+        template: String,
+        /// Name of the item to generate (e.g., "backup", "deploy")
+        name: String,
+        /// Target directory (defaults to current directory)
+        #[arg(long)]
+        target_dir: Option<std::path::PathBuf>,
+        /// Template variable, repeatable
+        #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_variable)]
+        variables: Vec<(String, String)>,
+    },
+}
+
+/// Actions for `tram config`
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum ConfigAction {
+    /// List the environment variables tram reads, what they do, and their
+    /// current values
+    EnvVars,
+    /// Print the effective value of a setting
+    Get {
+        /// Key path, e.g. `logLevel` or `http.timeoutSecs` (snake_case works too)
+        key: String,
+    },
+    /// Change a setting in the active config file, creating `tram.toml` in
+    /// the current directory if there isn't one
+    Set {
+        /// Key path, e.g. `logLevel` or `tasks.lint.command`
+        key: String,
+        /// New value; lists are written as `[a, b]`
+        value: String,
+    },
+}
+
+/// Actions for `tram telemetry`
+#[derive(clap::Subcommand, Clone, Copy, Debug)]
+pub enum TelemetryAction {
+    /// Record anonymous usage data
+    On,
+    /// Stop recording usage data
+    Off,
+    /// Show whether telemetry is on and what has been recorded
+    Status,
+}
+
+/// Actions for `tram workspace`
+#[derive(clap::Subcommand, Clone, Copy, Debug)]
+pub enum WorkspaceAction {
+    /// Print the dependency graph between workspace members
+    Graph {
+        /// Graph format
+        #[arg(long, value_enum, default_value = "dot")]
+        format: GraphFormat,
+    },
+}
+
+/// Formats for `tram workspace graph`
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT, for `dot -Tsvg`
+    Dot,
+    /// Members and their dependencies as JSON
+    Json,
+    /// A Mermaid flowchart, for Markdown docs
+    Mermaid,
+}
+
+/// Package managers for `tram package`
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PackageFormat {
+    /// A Homebrew formula
+    Homebrew,
+    /// A Debian package staging tree for `dpkg-deb --build`
+    Deb,
+    /// An RPM spec file
+    Rpm,
+    /// A Scoop app manifest
+    Scoop,
+}
+
 /// Available example types
 #[derive(clap::ValueEnum, Clone, Debug)]
 pub enum ExampleType {
@@ -139,3 +568,102 @@ pub enum ExampleType {
     /// File system operations
     FileOperations,
 }
+
+/// Valid `--project-type` values for validation and shell completion.
+/// Aliases are accepted but not offered as completions.
+pub fn project_type_values() -> PossibleValuesParser {
+    PossibleValuesParser::new([
+        PossibleValue::new("rust").help("Cargo project"),
+        PossibleValue::new("nodejs")
+            .aliases(["node", "js"])
+            .help("npm package"),
+        PossibleValue::new("python")
+            .alias("py")
+            .help("Python package with pyproject.toml"),
+        PossibleValue::new("go").help("Go module"),
+        PossibleValue::new("java").help("Maven project"),
+        PossibleValue::new("generic").help("README only"),
+    ])
+}
+
+/// Prefix of `--template-type` values naming a template in the workspace's
+/// `.tram/templates`
+const CUSTOM_TEMPLATE_PREFIX: &str = "custom:";
+
+/// The template name in a `custom:NAME` template type.
+pub fn custom_template_name(template_type: &str) -> Option<&str> {
+    template_type
+        .get(..CUSTOM_TEMPLATE_PREFIX.len())
+        .filter(|prefix| prefix.eq_ignore_ascii_case(CUSTOM_TEMPLATE_PREFIX))
+        .map(|_| &template_type[CUSTOM_TEMPLATE_PREFIX.len()..])
+        .filter(|name| !name.is_empty())
+}
+
+/// Valid `--template-type` values: the built-in types, which are offered as
+/// completions, or `custom:NAME`.
+pub fn template_type_values() -> TemplateTypeParser {
+    TemplateTypeParser(PossibleValuesParser::new([
+        PossibleValue::new("command")
+            .alias("cmd")
+            .help("CLI subcommand"),
+        PossibleValue::new("config-section")
+            .alias("config")
+            .help("Configuration section struct"),
+        PossibleValue::new("error-type")
+            .alias("error")
+            .help("Error enum with diagnostics"),
+        PossibleValue::new("session-extension")
+            .alias("session")
+            .help("Session lifecycle extension"),
+    ]))
+}
+
+/// Parses `--template-type`, accepting `custom:NAME` as well as the
+/// built-in types.
+#[derive(Clone)]
+pub struct TemplateTypeParser(PossibleValuesParser);
+
+impl TypedValueParser for TemplateTypeParser {
+    type Value = String;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<String, clap::Error> {
+        match value.to_str() {
+            Some(value) if custom_template_name(value).is_some() => Ok(value.to_string()),
+            _ => self.0.parse_ref(cmd, arg, value),
+        }
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        self.0.possible_values()
+    }
+}
+
+/// Parse a `--var KEY=VALUE` template variable.
+fn parse_variable(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got '{}'", value)),
+    }
+}
+
+/// Valid `--log-level` values, matching the `logLevel` config key.
+pub fn log_level_values() -> PossibleValuesParser {
+    PossibleValuesParser::new(["trace", "debug", "info", "warn", "error"])
+}
+
+/// Valid `--format` values, matching the `outputFormat` config key.
+pub fn output_format_values() -> PossibleValuesParser {
+    PossibleValuesParser::new([
+        PossibleValue::new("json").help("Pretty-printed JSON"),
+        PossibleValue::new("yaml").help("YAML"),
+        PossibleValue::new("table").help("Aligned tables for terminals"),
+        PossibleValue::new("plain").help("Plain text for scripts"),
+        PossibleValue::new("csv").help("Tables as comma-separated values"),
+        PossibleValue::new("tsv").help("Tables as tab-separated values"),
+    ])
+}
@@ -3,60 +3,148 @@
 //! This module contains the implementation of all CLI commands, handling the business logic
 //! for each subcommand while maintaining separation from the CLI argument parsing.
 
+use serde::Serialize;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use tracing::{debug, info, warn};
-use tram_config::ConfigWatcher;
-use tram_core::{InitConfig, ProjectInitializer, TemplateConfig, TemplateGenerator};
+use tram_config::{ConfigChangeHandler, ConfigWatcher, OutputFormat, TramConfig};
+use tram_core::{
+    Cache, ChangeSet, ExitCode, FileChange, FileChangeKind, InitConfig, LicenseKind,
+    ProjectInitializer, ProjectUpgrader, Signal, Signals, TemplateConfig, TemplateGenerator,
+    TemplateType, TramError, UpgradeReport, UpgradeStatus, t,
+};
+use tram_http::HttpClient;
+use tram_output::{CommandOutput, Render};
+use tram_ui::{Align, Table};
+use tram_update::{
+    Channel, GitHubReleasesSource, HttpReleaseSource, SelfUpdateArgs, SelfUpdateOutcome,
+    SelfUpdater,
+};
+use tram_workspace::{GitInfo, MemberProject, ProjectType, WorkspaceGraph, WorkspaceMembers};
 
-use crate::cli::Commands;
-use crate::dev_tools::{generate_completions, generate_man_pages};
+use crate::cli::{
+    Commands, ConfigAction, DaemonAction, GraphFormat, PackageFormat, ReleaseChannel,
+    WorkspaceAction,
+};
+use crate::dev_tools::{
+    generate_completions, generate_docs, generate_man_pages, generate_package, install_completions,
+    install_man_pages,
+};
 use crate::examples::run_example;
-use crate::session::{TramSession, WatchConfigHandler};
+use crate::session::{OutputMode, TramSession, WatchConfigHandler};
 use crate::utils::{
     parse_project_type, parse_template_type, project_type_display, template_type_display,
 };
+use crate::watch::Checks;
 
-/// Execute a CLI command with the session.
-pub async fn execute_command(command: Commands, session: &TramSession) -> tram_core::AppResult<()> {
+/// How much the session may print around a command's output.
+///
+/// Completion scripts and man pages are piped into files and other tools, so
+/// nothing but the command's own output may appear.
+pub fn output_mode(command: &Commands) -> OutputMode {
     match command {
+        Commands::Completions { .. }
+        | Commands::Hook { .. }
+        | Commands::PromptSegment
+        | Commands::Config {
+            action: Some(ConfigAction::Get { .. }),
+        }
+        | Commands::Workspace {
+            action: Some(WorkspaceAction::Graph { .. }),
+            ..
+        }
+        | Commands::Man { install: false, .. } => OutputMode::Clean,
+        _ => OutputMode::Normal,
+    }
+}
+
+/// Whether a command listens for signals itself, rather than being stopped
+/// by the ones `main.rs` listens for.
+pub fn handles_signals(command: &Commands) -> bool {
+    matches!(
+        command,
+        Commands::Watch { .. }
+            | Commands::Daemon {
+                action: DaemonAction::Start { detach: false, .. }
+            }
+    )
+}
+
+/// Execute a CLI command with the session.
+///
+/// Commands with a result return it for `main.rs` to render in the requested
+/// output format; long-running and streaming commands print directly and
+/// return `None`.
+pub async fn execute_command(
+    command: Commands,
+    session: &TramSession,
+) -> tram_core::AppResult<Option<CommandOutput>> {
+    let output = match command {
         Commands::New {
             name,
             project_type,
             description,
+            author,
+            license,
+            git,
+            ci,
             skip_prompts,
         } => {
             info!("Creating new project: {}", name);
 
-            if !skip_prompts {
-                // In future iterations, we would add interactive prompts here
-                // For now, just note that interactive mode is planned
-                debug!("Interactive prompts would be shown here (future feature)");
-            }
-
-            let project_type = parse_project_type(&project_type);
-            let current_dir =
-                std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
-            let project_path = current_dir.join(&name);
+            let project_path = tram_core::current_dir().join(&name);
 
-            let init_config = InitConfig {
+            let mut init_config = InitConfig {
                 name: name.clone(),
                 path: project_path,
-                project_type,
+                project_type: parse_project_type(&project_type),
                 description,
-                author: None,
+                author,
+                license: license.map(LicenseKind::from),
+                year: tram_core::current_year(),
+                git,
+                ci,
             };
 
-            let initializer = ProjectInitializer::new();
-            initializer.create_project(&init_config)?;
-
-            println!(
-                "✓ Created new {} project: {}",
-                project_type_display(&init_config.project_type),
-                name
-            );
-            if let Some(desc) = &init_config.description {
-                println!("  Description: {}", desc);
+            // Flags given on the command line become the prompts' defaults
+            if crate::wizard::should_prompt(session, skip_prompts) {
+                crate::wizard::prompt(session.prompter.as_ref(), &mut init_config)?;
+            } else {
+                debug!("Not prompting; using flags and defaults");
             }
+
+            let progress = session.progress(Some(ProjectInitializer::steps(&init_config)));
+            let initializer = ProjectInitializer::new()
+                .with_dry_run(session.dry_run)
+                .with_progress(progress.clone());
+            let changes = initializer.create_project(&init_config)?;
+            progress.finish();
+
+            CommandOutput::new(&NewResult {
+                name,
+                project_type: project_type_display(&init_config.project_type).to_string(),
+                path: init_config.path,
+                description: init_config.description,
+                author: init_config.author,
+                license: init_config.license.as_ref().map(ToString::to_string),
+                git: init_config.git,
+                ci: init_config.ci,
+                dry_run: session.dry_run,
+                changes,
+            })?
+        }
+
+        Commands::Upgrade { path } => {
+            let root = path
+                .map(tram_core::expand_path)
+                .unwrap_or_else(tram_core::current_dir);
+            info!("Upgrading project: {}", root.display());
+
+            let report = ProjectUpgrader::new()
+                .with_dry_run(session.dry_run)
+                .upgrade(&root)?;
+
+            CommandOutput::new(&UpgradeResult(report))?
         }
 
         Commands::Generate {
@@ -65,13 +153,16 @@ pub async fn execute_command(command: Commands, session: &TramSession) -> tram_c
             description,
             target_dir,
             write,
+            diff,
+            force,
+            register,
         } => {
             info!("Generating {} template: {}", template_type, name);
 
             let template_type = parse_template_type(&template_type);
-            let target_dir = target_dir.unwrap_or_else(|| {
-                std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."))
-            });
+            let target_dir = target_dir
+                .map(tram_core::expand_path)
+                .unwrap_or_else(tram_core::current_dir);
 
             let mut parameters = HashMap::new();
             if let Some(desc) = description {
@@ -85,46 +176,62 @@ pub async fn execute_command(command: Commands, session: &TramSession) -> tram_c
                 parameters,
             };
 
-            let generator = TemplateGenerator::new()?;
+            let progress = session.progress(Some(1 + u64::from(write) + u64::from(register)));
+            let mut generator = TemplateGenerator::new()?
+                .with_dry_run(session.dry_run)
+                .with_force(force)
+                .with_progress(progress.clone());
+            if let TemplateType::Custom(_) = &template_type {
+                let root = session
+                    .workspace_root
+                    .clone()
+                    .unwrap_or_else(tram_core::current_dir);
+                generator = generator.with_custom_templates(&root)?;
+            }
             let template = generator.generate_template(&template_config)?;
 
-            if write {
-                generator.write_template(&template)?;
-                println!(
-                    "✓ Generated {} template: {} -> {}",
-                    template_type_display(&template_type),
-                    name,
-                    template.file_path.display()
-                );
+            let diff = if diff {
+                generator.diff_template(&template)?
             } else {
-                println!(
-                    "Generated {} template for '{}':",
-                    template_type_display(&template_type),
-                    name
-                );
-                println!("File path: {}", template.file_path.display());
-                println!("\n{}", "=".repeat(80));
-                println!("{}", template.content);
-                println!("{}", "=".repeat(80));
-                println!("\nTo write to filesystem, add the --write flag");
+                None
+            };
+            if diff.is_none() && !force && template.file_path.exists() {
+                return Err(tram_core::TramError::Conflict {
+                    path: template.file_path.display().to_string(),
+                }
+                .into());
             }
-        }
-
-        Commands::Init { name, verbose } => {
-            println!("🚀 Initializing project: {}", name);
 
-            if verbose {
-                println!("Verbose mode enabled");
-                if let Some(root) = &session.workspace_root {
-                    println!("Workspace root: {}", root.display());
-                }
-                println!("Config: {:?}", session.config);
+            let write = write && diff.is_none();
+            let mut changes = if write {
+                generator.write_template(&template)?
+            } else {
+                Vec::new()
+            };
+            let mut missing_markers = Vec::new();
+            if register && write {
+                let registration = generator.register_command(&template_config)?;
+                changes.extend(registration.changes);
+                missing_markers = registration.missing_markers;
             }
+            progress.finish();
+
+            CommandOutput::new(&GenerateResult {
+                name,
+                template_type: template_type_display(&template_type),
+                path: template.file_path,
+                written: write && !session.dry_run,
+                content: template.content,
+                diff,
+                dry_run: session.dry_run,
+                changes,
+                missing_markers,
+            })?
+        }
 
+        Commands::Init { name } => {
             // Legacy command - for now, just create a generic project
-            let current_dir =
-                std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
-            let project_path = current_dir.join(&name);
+            let project_path = tram_core::current_dir().join(&name);
 
             let init_config = InitConfig {
                 name: name.clone(),
@@ -132,47 +239,177 @@ pub async fn execute_command(command: Commands, session: &TramSession) -> tram_c
                 project_type: tram_core::InitProjectType::Generic,
                 description: Some("A new project".to_string()),
                 author: None,
+                license: None,
+                year: tram_core::current_year(),
+                git: false,
+                ci: false,
+            };
+
+            let initializer = ProjectInitializer::new().with_dry_run(session.dry_run);
+            let (changes, warning) = match initializer.create_project(&init_config) {
+                Ok(changes) => (changes, None),
+                Err(e) => (Vec::new(), Some(e.to_string())),
+            };
+
+            CommandOutput::new(&InitResult {
+                name,
+                path: init_config.path,
+                warning,
+                dry_run: session.dry_run,
+                changes,
+                details: session.verbosity.is_verbose().then(|| InitDetails {
+                    workspace_root: session.workspace_root.clone(),
+                    config: session.config.clone(),
+                }),
+            })?
+        }
+
+        Commands::Workspace {
+            action: Some(WorkspaceAction::Graph { format }),
+            ..
+        } => {
+            let Some(root) = &session.workspace_root else {
+                return Err(tram_core::TramError::WorkspaceNotFound.into());
             };
 
-            let initializer = ProjectInitializer::new();
-            if let Err(e) = initializer.create_project(&init_config) {
-                println!("Warning: Could not create project files: {}", e);
+            let graph = WorkspaceGraph::discover(root)?;
+            match format {
+                GraphFormat::Dot => print!("{}", graph.to_dot()),
+                GraphFormat::Mermaid => print!("{}", graph.to_mermaid()),
+                GraphFormat::Json => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&graph).map_err(|e| {
+                        tram_core::TramError::Output {
+                            message: format!("Failed to serialize workspace graph: {}", e),
+                        }
+                    })?
+                ),
             }
+            return Ok(None);
+        }
+
+        Commands::Workspace {
+            detailed,
+            members,
+            action: None,
+        } => {
+            let Some(root) = &session.workspace_root else {
+                return Err(tram_core::TramError::WorkspaceNotFound.into());
+            };
+
+            let project_type = session.project_type.as_ref();
+            // Plugin detectors can name project types tram only sees as generic
+            let detected = match project_type {
+                Some(ProjectType::Generic) | None => crate::plugins::detect_project_type(root),
+                Some(_) => None,
+            };
 
-            println!("Project '{}' initialized!", name);
+            // JSON is for scripts, which get the whole document without flags
+            let json = session.config.output_format == OutputFormat::Json;
+            let detailed = detailed || json;
+
+            CommandOutput::new(&WorkspaceResult {
+                root: root.clone(),
+                project_type: detected.or_else(|| project_type.map(|kind| kind.name().to_string())),
+                ignore_patterns: project_type.filter(|_| detailed).map(|kind| {
+                    kind.ignore_patterns()
+                        .iter()
+                        .map(|p| p.to_string())
+                        .collect()
+                }),
+                members: match members || json {
+                    true => Some(WorkspaceMembers::discover(root)?.members),
+                    false => None,
+                },
+                git: detailed.then(|| GitInfo::detect(root)),
+            })?
         }
 
-        Commands::Workspace { detailed } => {
-            if let Some(root) = &session.workspace_root {
-                println!("Workspace root: {}", root.display());
+        Commands::Config { action: None } => CommandOutput::new(&ConfigView {
+            profile: session.profile(),
+            config: &session.config,
+        })?,
 
-                if let Some(project_type) = &session.project_type {
-                    println!("Project type: {:?}", project_type);
+        Commands::Config {
+            action: Some(ConfigAction::EnvVars),
+        } => crate::env::env_vars(session)?,
 
-                    if detailed {
-                        println!("Ignore patterns: {:?}", project_type.ignore_patterns());
-                    }
+        Commands::Config {
+            action: Some(ConfigAction::Get { key }),
+        } => {
+            let value = tram_config::config_value(&session.config, &key).map_err(|e| {
+                TramError::InvalidConfig {
+                    message: e.to_string(),
                 }
+            })?;
+
+            CommandOutput::new(&ConfigValueResult { key, value })?
+        }
+
+        Commands::Config {
+            action: Some(ConfigAction::Set { key, value }),
+        } => {
+            let (file, created) = match &session.config_file {
+                Some(file) => (file.clone(), false),
+                None => (tram_core::current_dir().join(NEW_CONFIG_FILE), true),
+            };
+            let contents = if created {
+                String::new()
             } else {
-                return Err(tram_core::TramError::WorkspaceNotFound.into());
-            }
+                std::fs::read_to_string(&file).map_err(|e| TramError::InvalidConfig {
+                    message: format!("Failed to read {}: {}", file.display(), e),
+                })?
+            };
+
+            let edit =
+                tram_config::set_config_value(&file, &contents, &key, &value).map_err(|e| {
+                    TramError::InvalidConfig {
+                        message: e.to_string(),
+                    }
+                })?;
+            let mut changes = ChangeSet::new(session.dry_run);
+            changes.write_file(&file, &edit.contents)?;
+
+            CommandOutput::new(&ConfigSetResult {
+                key: edit.key,
+                value: edit.value,
+                file,
+                created,
+                dry_run: session.dry_run,
+                changes: changes.into_changes(),
+            })?
         }
 
-        Commands::Config => {
-            println!("Current configuration:");
-            println!("   Log level: {}", session.config.log_level);
-            println!("   Output format: {}", session.config.output_format);
-            println!("   Colors: {}", session.config.color);
+        Commands::Deprecations => crate::deprecations::list()?,
 
-            if let Some(workspace_root) = &session.config.workspace_root {
-                println!("   Workspace root: {}", workspace_root.display());
-            }
+        Commands::Doctor => {
+            crate::doctor::run(session)?;
+            return Ok(None);
         }
 
+        Commands::Env => crate::env::report(session)?,
+
+        Commands::ExitCodes => CommandOutput::new(&ExitCodeList {
+            codes: ExitCode::ALL.into_iter().map(ExitCodeInfo::from).collect(),
+        })?,
+
+        Commands::Cache { action } => crate::cache::run(session, action)?,
+
+        Commands::Telemetry { action } => crate::telemetry::run(action)?,
+
         Commands::Watch {
             config: watch_config,
             check,
+            check_command,
+            debounce,
+            ui,
+            dashboard,
         } => {
+            let dashboard = if dashboard {
+                crate::dashboard::new("tram watch", session)?
+            } else {
+                None
+            };
             info!("Starting watch mode...");
 
             if watch_config {
@@ -187,99 +424,946 @@ pub async fn execute_command(command: Commands, session: &TramSession) -> tram_c
                 info!("⚡ Auto-checks: DISABLED");
             }
 
-            println!("Watch mode started. Press Ctrl+C to stop.");
+            if !session.verbosity.is_quiet() {
+                println!("{}", t!("watch-started"));
+            }
 
-            let mut tasks = Vec::new();
+            // Watch mode handles its own signals: SIGHUP reloads the config
+            let mut signals = Signals::new()?;
 
             // Set up config watcher if enabled
+            let mut config_watcher = None;
             if watch_config {
-                let config_watcher = ConfigWatcher::new(session.config.clone(), None)
-                    .await
-                    .map_err(|e| tram_core::TramError::InvalidConfig {
-                        message: format!("Failed to start config watcher: {}", e),
-                    })?;
+                let watcher = ConfigWatcher::new(
+                    session.config.clone(),
+                    Some(session.config_files.clone()),
+                    session.config_overlay.clone(),
+                )
+                .await
+                .map_err(|e| tram_core::TramError::InvalidConfig {
+                    message: format!("Failed to start config watcher: {}", e),
+                })?;
 
-                let handler = WatchConfigHandler;
-                if let Err(e) = config_watcher.start_with_handler(handler).await {
+                if let Err(e) = watcher.start_with_handler(WatchConfigHandler).await {
                     warn!("Failed to start config change handler: {}", e);
                 }
 
-                // Keep the watcher alive by storing it
-                tasks.push(tokio::spawn(async move {
-                    // Keep the config_watcher alive for the duration of the task
-                    let _watcher = config_watcher;
-                    // Wait indefinitely (until the task is cancelled)
-                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(300));
-                    loop {
-                        interval.tick().await;
-                    }
-                }));
+                config_watcher = Some(watcher);
             }
 
-            // Set up file watching for code changes if enabled
-            if check {
-                tasks.push(tokio::spawn(async move {
-                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(2));
-                    let mut last_check = std::time::Instant::now();
-
-                    loop {
-                        interval.tick().await;
-
-                        // Simple implementation: check if any Rust files have been modified
-                        // In a real implementation, you'd use a proper file watcher
-                        let current_time = std::time::Instant::now();
-                        if current_time.duration_since(last_check).as_secs() >= 2 {
-                            debug!("Running periodic checks (placeholder for file-based trigger)");
-                            last_check = current_time;
-
-                            // Here you would run `just check` or equivalent
-                            // For now, just log that we would run checks
-                            debug!("Would run: just check");
-                        }
+            if let (Some(dashboard), Some(_)) = (&dashboard, &config_watcher) {
+                dashboard.add_watched(crate::dashboard::config_files_label(session));
+            }
+
+            // Run checks and configured watch tasks when workspace files change
+            let watch_tasks = crate::watch::load_tasks(&session.config.watch)?;
+            let mut watching_files = false;
+            let mut view = None;
+            if check || !watch_tasks.is_empty() {
+                match &session.workspace_root {
+                    Some(root) => {
+                        let debounce = std::time::Duration::from_millis(
+                            debounce.unwrap_or(session.config.watch.debounce_ms),
+                        );
+                        let checks = match (
+                            check,
+                            check_command.or_else(|| session.config.watch.check_command.clone()),
+                        ) {
+                            (false, _) => Checks::Off,
+                            (true, Some(command)) => Checks::Command(command),
+                            (true, None) => Checks::Detected,
+                        };
+                        view = crate::watch::start(
+                            root,
+                            session,
+                            checks,
+                            watch_tasks,
+                            debounce,
+                            ui,
+                            dashboard.clone(),
+                        )?;
+                        watching_files = true;
                     }
-                }));
+                    None => warn!("No workspace detected; file watching is disabled"),
+                }
             }
 
-            if tasks.is_empty() {
+            // Run `[schedules]` for as long as watch mode does
+            let scheduler = crate::schedule::start(session)?;
+
+            if config_watcher.is_none() && !watching_files && scheduler.is_none() {
                 warn!("No watch features enabled. Use --config or --check flags.");
-                return Ok(());
+                return Ok(None);
             }
 
-            // Wait for Ctrl+C
-            tokio::signal::ctrl_c()
-                .await
-                .map_err(|e| tram_core::TramError::InvalidConfig {
-                    message: format!("Failed to wait for Ctrl+C: {}", e),
-                })?;
+            let mut dashboard = dashboard.map(crate::dashboard::show);
+
+            // Run until Ctrl+C, SIGTERM, or the dashboard is closed,
+            // reloading the config on SIGHUP
+            let signal = loop {
+                tokio::select! {
+                    signal = signals.recv() => match signal {
+                        Signal::Hangup => match &config_watcher {
+                            Some(watcher) => match watcher.reload().await {
+                                Ok(config) => {
+                                    if let Some(dashboard) = &dashboard {
+                                        crate::dashboard::set_config(
+                                            dashboard.dashboard(),
+                                            session.profile(),
+                                            &config,
+                                        );
+                                        dashboard.dashboard().event(t!("dashboard-config-reloaded"));
+                                    }
+                                    WatchConfigHandler.handle_config_change(&config).await
+                                }
+                                Err(e) => WatchConfigHandler.handle_config_error(e).await,
+                            },
+                            None => info!("Config hot reload is disabled; ignoring SIGHUP"),
+                        },
+                        signal => break Some(signal),
+                    },
+                    _ = crate::dashboard::closed(&mut dashboard) => break None,
+                }
+            };
 
+            if let Some(view) = view {
+                view.finish();
+            }
+            if let Some(dashboard) = dashboard {
+                dashboard.close().await;
+            }
             info!("Shutting down watch mode...");
+            drop(scheduler);
+            // Checks and tasks run in their own process groups, out of reach of Ctrl+C
+            tram_process::kill_all();
+            drop(config_watcher);
 
-            // Cancel all tasks
-            for task in tasks {
-                task.abort();
+            if !session.verbosity.is_quiet() {
+                println!("{}", t!("watch-stopped"));
             }
+            // Closing the dashboard is a normal way out of watch mode
+            return match signal {
+                Some(signal) => Err(tram_core::TramError::Signal { signal }.into()),
+                None => Ok(None),
+            };
+        }
 
-            println!("Watch mode stopped.");
+        Commands::Run {
+            task, tree: true, ..
+        } => crate::run::tree(session, task.as_deref())?,
+
+        Commands::Run {
+            task,
+            list,
+            jobs,
+            keep_going,
+            ..
+        } => match task {
+            Some(task) if !list => {
+                info!("Running task: {}", task);
+                return crate::run::run(session, &task, jobs, keep_going);
+            }
+            _ => crate::run::list(session)?,
+        },
+
+        Commands::Examples { list: true, .. }
+        | Commands::Examples {
+            example: None,
+            interactive: false,
+            ..
+        } => crate::examples::list()?,
+
+        Commands::Examples {
+            example,
+            interactive: true,
+            ..
+        } => {
+            info!("Running example demo: {:?}", example);
+            crate::examples::run_demo(example, session).await?;
+            return Ok(None);
         }
 
-        Commands::Examples { example } => {
+        Commands::Examples {
+            example: Some(example),
+            ..
+        } => {
             info!("Running example: {:?}", example);
             run_example(example, session).await?;
+            return Ok(None);
+        }
+
+        Commands::Hook { shell, env } => {
+            crate::hook::run(session, shell, env);
+            return Ok(None);
+        }
+
+        // Normally answered by `main.rs` before a session starts
+        Commands::PromptSegment => {
+            crate::prompt_segment::run(session.config.output_format == OutputFormat::Json)?;
+            return Ok(None);
         }
 
-        Commands::Completions { shell } => {
+        Commands::Completions { shell, install } => {
             info!("Generating completions for {:?}", shell);
-            generate_completions(shell)?;
+
+            match shell {
+                Some(shell) if !install => {
+                    generate_completions(shell)?;
+                    return Ok(None);
+                }
+                shell => install_completions(shell)?,
+            }
+        }
+
+        Commands::Man {
+            section,
+            install: true,
+            prefix,
+            ..
+        } => {
+            info!("Installing manual pages");
+            install_man_pages(
+                prefix.map(tram_core::expand_path).as_deref(),
+                section,
+                session.dry_run,
+            )?
         }
 
         Commands::Man {
             output_dir,
             section,
+            ..
         } => {
             info!("Generating manual pages");
-            generate_man_pages(&output_dir, section)?;
+            generate_man_pages(&tram_core::expand_path(output_dir), section)?;
+            return Ok(None);
         }
+
+        Commands::Docs { output } => {
+            info!("Generating Markdown docs");
+            generate_docs(&tram_core::expand_path(output), session.dry_run)?
+        }
+
+        Commands::Package {
+            format,
+            output,
+            url,
+            sha256,
+            maintainer,
+        } => {
+            info!("Generating packaging for {:?}", format);
+            let format = match format {
+                PackageFormat::Homebrew => tram_core::PackageFormat::Homebrew,
+                PackageFormat::Deb => tram_core::PackageFormat::Deb,
+                PackageFormat::Rpm => tram_core::PackageFormat::Rpm,
+                PackageFormat::Scoop => tram_core::PackageFormat::Scoop,
+            };
+            generate_package(
+                format,
+                &tram_core::expand_path(output),
+                url,
+                sha256,
+                maintainer,
+                session.dry_run,
+            )?
+        }
+
+        Commands::Shell => {
+            crate::shell::run(session).await?;
+            return Ok(None);
+        }
+
+        Commands::Batch { input } => {
+            crate::batch::run(session, &input).await?;
+            return Ok(None);
+        }
+
+        Commands::Daemon { action } => return crate::daemon::run(session, action).await,
+
+        Commands::Plugins { action } => crate::plugins::execute(session, action)?,
+
+        Commands::Auth { action } => crate::auth::execute(session, action)?,
+
+        Commands::SelfUpdate { channel, check } => {
+            info!("Checking for a newer release");
+
+            let args = SelfUpdateArgs {
+                channel: channel.map(|channel| match channel {
+                    ReleaseChannel::Stable => Channel::Stable,
+                    ReleaseChannel::Beta => Channel::Beta,
+                }),
+                check,
+            };
+
+            let source = crate::session::release_source(&session.config.update)?;
+            let client = HttpClient::from_config(&session.config.http)?.with_progress(true);
+            let cache = crate::cache::open(&session.config).ok();
+            let outcome =
+                tokio::task::spawn_blocking(move || self_update(source, client, cache, &args))
+                    .await
+                    .map_err(|e| tram_core::TramError::Update {
+                        message: format!("Update task failed: {}", e),
+                    })??;
+
+            CommandOutput::new(&SelfUpdateResult::from(outcome))?
+        }
+
+        Commands::External(_) => {
+            unreachable!("plugin subcommands are run before the session starts")
+        }
+    };
+
+    Ok(Some(output))
+}
+
+/// Result of `tram new`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewResult {
+    pub name: String,
+    pub project_type: String,
+    pub path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// Whether a git repository was initialized
+    pub git: bool,
+    /// Whether a CI workflow was added
+    pub ci: bool,
+    /// Whether this was a `--dry-run` that wrote nothing
+    pub dry_run: bool,
+    /// Directories and files created, or that would be in a dry run
+    pub changes: Vec<FileChange>,
+}
+
+impl Render for NewResult {
+    fn render_plain(&self) -> String {
+        if self.dry_run {
+            return dry_run_lines(&self.changes).join("\n");
+        }
+
+        let mut lines = vec![t!(
+            "project-created",
+            kind = self.project_type.as_str(),
+            name = self.name.as_str()
+        )];
+
+        if let Some(desc) = &self.description {
+            lines.push(t!("project-description", description = desc.as_str()));
+        }
+
+        if let Some(author) = &self.author {
+            lines.push(t!("project-author", author = author.as_str()));
+        }
+
+        if let Some(license) = &self.license {
+            lines.push(t!("project-license", license = license.as_str()));
+        }
+
+        lines.join("\n")
     }
+}
 
-    Ok(())
+/// Result of `tram upgrade`.
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct UpgradeResult(pub UpgradeReport);
+
+impl Render for UpgradeResult {
+    fn render_plain(&self) -> String {
+        let report = &self.0;
+        if report.files.is_empty() {
+            return t!("upgrade-up-to-date", version = report.to_version.as_str());
+        }
+
+        let mut lines = vec![t!(
+            "upgrade-summary",
+            from = report.from_version.as_str(),
+            to = report.to_version.as_str()
+        )];
+
+        for file in &report.files {
+            let path = file.path.as_str();
+            lines.push(match file.status {
+                UpgradeStatus::Updated => t!("upgrade-updated", path = path),
+                UpgradeStatus::Merged => t!("upgrade-merged", path = path),
+                UpgradeStatus::Added => t!("upgrade-added", path = path),
+                UpgradeStatus::UpToDate => t!("upgrade-current", path = path),
+                UpgradeStatus::Conflict => t!("upgrade-conflict", path = path),
+                UpgradeStatus::Missing => t!("upgrade-missing", path = path),
+            });
+
+            if let Some(diff) = &file.diff {
+                lines.push(diff.trim_end().to_string());
+            }
+        }
+
+        let conflicts = report.conflicts();
+        if conflicts > 0 {
+            lines.push(t!("upgrade-conflicts", count = conflicts));
+        }
+
+        if report.dry_run {
+            lines.extend(dry_run_lines(&report.changes));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Result of `tram generate`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateResult {
+    pub name: String,
+    pub template_type: String,
+    pub path: PathBuf,
+    /// Whether the template was written to disk, or only previewed
+    pub written: bool,
+    pub content: String,
+    /// With `--diff`, the changes from the existing file to the generated
+    /// content, empty if they match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+    /// Whether this was a `--dry-run` that wrote nothing
+    pub dry_run: bool,
+    /// Directories and file written with `--write`, or that would be in a dry run
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<FileChange>,
+    /// `--register` markers that weren't found in the project's sources
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing_markers: Vec<&'static str>,
+}
+
+impl Render for GenerateResult {
+    fn render_plain(&self) -> String {
+        let path = self.path.display().to_string();
+        let missing = self
+            .missing_markers
+            .iter()
+            .map(|marker| t!("template-marker-missing", marker = *marker));
+
+        if let Some(diff) = &self.diff {
+            if diff.is_empty() {
+                return t!("template-unchanged", path = path);
+            }
+            return [
+                t!("template-diff", path = path),
+                String::new(),
+                diff.trim_end().to_string(),
+                String::new(),
+                t!("template-force-hint"),
+            ]
+            .join("\n");
+        }
+
+        if self.dry_run && !self.changes.is_empty() {
+            return dry_run_lines(&self.changes)
+                .into_iter()
+                .chain(missing)
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+
+        if self.written {
+            let registered = self
+                .changes
+                .iter()
+                .filter(|change| {
+                    change.path != self.path && change.kind != FileChangeKind::CreateDir
+                })
+                .map(|change| {
+                    t!(
+                        "template-registered",
+                        path = change.path.display().to_string()
+                    )
+                });
+
+            return std::iter::once(t!(
+                "template-written",
+                kind = self.template_type.as_str(),
+                name = self.name.as_str(),
+                path = path
+            ))
+            .chain(registered)
+            .chain(missing)
+            .collect::<Vec<_>>()
+            .join("\n");
+        }
+
+        let rule = "=".repeat(80);
+        [
+            t!(
+                "template-preview",
+                kind = self.template_type.as_str(),
+                name = self.name.as_str()
+            ),
+            t!("template-file-path", path = path),
+            String::new(),
+            rule.clone(),
+            self.content.clone(),
+            rule,
+            String::new(),
+            t!("template-write-hint"),
+        ]
+        .join("\n")
+    }
+}
+
+/// Result of the deprecated `tram init`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitResult {
+    pub name: String,
+    pub path: PathBuf,
+    /// Why project files could not be created, if they weren't
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    /// Whether this was a `--dry-run` that wrote nothing
+    pub dry_run: bool,
+    /// Directories and files created, or that would be in a dry run
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<FileChange>,
+    /// Extra context included with `-v`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<InitDetails>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitDetails {
+    pub workspace_root: Option<PathBuf>,
+    pub config: TramConfig,
+}
+
+impl Render for InitResult {
+    fn render_plain(&self) -> String {
+        let mut lines = vec![t!("project-initializing", name = self.name.as_str())];
+
+        if let Some(details) = &self.details {
+            lines.push(t!("verbose-enabled"));
+            if let Some(root) = &details.workspace_root {
+                lines.push(t!("workspace-root", path = root.display().to_string()));
+            }
+            lines.push(format!("Config: {:?}", details.config));
+        }
+
+        if let Some(warning) = &self.warning {
+            lines.push(t!("project-files-warning", error = warning.as_str()));
+        }
+
+        if self.dry_run {
+            lines.extend(dry_run_lines(&self.changes));
+            return lines.join("\n");
+        }
+
+        lines.push(t!("project-initialized", name = self.name.as_str()));
+        lines.join("\n")
+    }
+}
+
+/// The report for a `--dry-run`: a header, then one line per change.
+pub(crate) fn dry_run_lines(changes: &[FileChange]) -> Vec<String> {
+    std::iter::once(t!("dry-run-header"))
+        .chain(changes.iter().map(|change| {
+            let path = change.path.display().to_string();
+
+            match change.kind {
+                FileChangeKind::CreateDir => t!("dry-run-create-dir", path = path),
+                FileChangeKind::CreateFile => t!("dry-run-create-file", path = path),
+                FileChangeKind::ModifyFile => t!("dry-run-modify-file", path = path),
+            }
+        }))
+        .collect()
+}
+
+/// Result of `tram workspace`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceResult {
+    pub root: PathBuf,
+    pub project_type: Option<String>,
+    /// Included with `--detailed`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore_patterns: Option<Vec<String>>,
+    /// Included with `--members`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub members: Option<Vec<MemberProject>>,
+    /// Included with `--detailed`; `null` outside a git repository
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git: Option<Option<GitInfo>>,
+}
+
+impl WorkspaceResult {
+    fn member_lines(&self) -> Vec<String> {
+        self.members
+            .iter()
+            .flatten()
+            .map(|member| {
+                t!(
+                    "workspace-member",
+                    name = member.name.as_str(),
+                    kind = member.project_type.name(),
+                    path = member.path.display().to_string()
+                )
+            })
+            .collect()
+    }
+}
+
+impl Render for WorkspaceResult {
+    fn render_plain(&self) -> String {
+        let mut lines = vec![t!("workspace-root", path = self.root.display().to_string())];
+
+        if let Some(kind) = &self.project_type {
+            lines.push(t!("workspace-project-type", kind = kind.as_str()));
+
+            if let Some(patterns) = &self.ignore_patterns {
+                lines.push(t!(
+                    "workspace-ignore-patterns",
+                    patterns = format!("{:?}", patterns)
+                ));
+            }
+        }
+
+        if let Some(Some(git)) = &self.git {
+            lines.push(t!("workspace-git", summary = git_summary(git)));
+        }
+
+        if self.members.is_some() {
+            lines.push(t!("workspace-members"));
+            lines.extend(
+                self.member_lines()
+                    .into_iter()
+                    .map(|line| format!("  {}", line)),
+            );
+        }
+
+        lines.join("\n")
+    }
+
+    fn render_table(&self) -> Option<Table> {
+        let mut table = Table::new([t!("workspace-property"), t!("config-value")]);
+        table.add_row([t!("workspace-root-label"), self.root.display().to_string()]);
+
+        if let Some(kind) = &self.project_type {
+            table.add_row([t!("workspace-project-type-label"), kind.clone()]);
+        }
+
+        if let Some(patterns) = &self.ignore_patterns {
+            table.add_row([t!("workspace-ignore-patterns-label"), patterns.join(", ")]);
+        }
+
+        if let Some(Some(git)) = &self.git {
+            table.add_row([t!("workspace-git-label"), git_summary(git)]);
+        }
+
+        for line in self.member_lines() {
+            table.add_row([t!("workspace-member-label"), line]);
+        }
+
+        Some(table)
+    }
+}
+
+/// The branch (or short commit when detached) and whether there are changes,
+/// e.g. `main, dirty`.
+fn git_summary(git: &GitInfo) -> String {
+    let head = git
+        .branch
+        .clone()
+        .or_else(|| {
+            git.commit
+                .as_ref()
+                .map(|hash| hash.chars().take(7).collect())
+        })
+        .unwrap_or_else(|| t!("workspace-git-no-commits"));
+
+    match git.dirty {
+        Some(true) => format!("{}, {}", head, t!("workspace-git-dirty")),
+        Some(false) => format!("{}, {}", head, t!("workspace-git-clean")),
+        None => head,
+    }
+}
+
+/// Result of `tram config`: the effective config and the profile applied to it.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigView<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<&'a str>,
+    #[serde(flatten)]
+    pub config: &'a TramConfig,
+}
+
+impl Render for ConfigView<'_> {
+    fn render_plain(&self) -> String {
+        config_rows(self)
+            .into_iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn render_table(&self) -> Option<Table> {
+        let mut table =
+            Table::new([t!("config-setting"), t!("config-value")]).with_title(t!("config-header"));
+
+        for (key, value) in config_rows(self) {
+            table.add_row([key, value]);
+        }
+
+        Some(table)
+    }
+}
+
+/// Where `tram config set` creates a config file when there isn't one.
+const NEW_CONFIG_FILE: &str = "tram.toml";
+
+/// Result of `tram config get`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigValueResult {
+    pub key: String,
+    pub value: serde_json::Value,
+}
+
+impl Render for ConfigValueResult {
+    fn render_plain(&self) -> String {
+        display_value(&self.value)
+    }
+}
+
+/// Result of `tram config set`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSetResult {
+    /// The key path as the config spells it
+    pub key: String,
+    pub value: serde_json::Value,
+    pub file: PathBuf,
+    /// Whether the config file was created
+    pub created: bool,
+    pub dry_run: bool,
+    pub changes: Vec<FileChange>,
+}
+
+impl Render for ConfigSetResult {
+    fn render_plain(&self) -> String {
+        if self.dry_run {
+            return dry_run_lines(&self.changes).join("\n");
+        }
+
+        let path = self.file.display().to_string();
+        let mut lines = Vec::new();
+        if self.created {
+            lines.push(t!("config-file-created", path = path.as_str()));
+        }
+        lines.push(t!(
+            "config-set",
+            key = self.key.as_str(),
+            value = display_value(&self.value),
+            path = path.as_str()
+        ));
+
+        lines.join("\n")
+    }
+}
+
+/// A config value as typed on the command line: strings unquoted, anything
+/// else as JSON.
+fn display_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+pub(crate) fn config_rows(view: &ConfigView) -> Vec<(String, String)> {
+    let config = view.config;
+    let mut rows = Vec::new();
+
+    if let Some(profile) = view.profile {
+        rows.push((t!("config-profile"), profile.to_string()));
+    }
+
+    rows.extend([
+        (t!("config-log-level"), config.log_level.to_string()),
+        (t!("config-output-format"), config.output_format.to_string()),
+        (t!("config-colors"), config.color.to_string()),
+        (t!("config-table-border"), config.table_border.to_string()),
+        (
+            t!("config-update-checks"),
+            config.check_for_updates.to_string(),
+        ),
+    ]);
+
+    if let Some(workspace_root) = &config.workspace_root {
+        rows.push((
+            t!("config-workspace-root"),
+            workspace_root.display().to_string(),
+        ));
+    }
+
+    if let Some(log_file) = &config.log_file {
+        rows.push((t!("config-log-file"), log_file.display().to_string()));
+        rows.push((t!("config-log-rotation"), config.log_rotation.to_string()));
+    }
+
+    if !config.watch.tasks.is_empty() {
+        let names: Vec<&str> = config.watch.tasks.keys().map(String::as_str).collect();
+        rows.push((t!("config-watch-tasks"), names.join(", ")));
+    }
+
+    if !config.tasks.is_empty() {
+        let names: Vec<&str> = config.tasks.keys().map(String::as_str).collect();
+        rows.push((t!("config-tasks"), names.join(", ")));
+    }
+
+    if !config.schedules.is_empty() {
+        let names: Vec<&str> = config.schedules.keys().map(String::as_str).collect();
+        rows.push((t!("config-schedules"), names.join(", ")));
+    }
+
+    if !config.profiles.is_empty() {
+        let names: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+        rows.push((t!("config-profiles"), names.join(", ")));
+    }
+
+    rows
+}
+
+/// Replace the running executable with the latest release from `source` on the
+/// chosen channel, downloading and verifying it into the cache.
+fn self_update(
+    source: GitHubReleasesSource,
+    client: HttpClient,
+    cache: Option<Cache>,
+    args: &SelfUpdateArgs,
+) -> tram_core::AppResult<SelfUpdateOutcome> {
+    let mut source = HttpReleaseSource::new(source.with_client(client.clone()), client);
+    if let Some(cache) = cache {
+        source = source.with_cache(cache);
+    }
+
+    args.run(SelfUpdater::new("tram", env!("CARGO_PKG_VERSION"), source)?)
+}
+
+/// Result of `tram self-update`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfUpdateResult {
+    pub previous_version: String,
+    pub version: String,
+    pub updated: bool,
+    /// Whether a newer version was found by `--check` but not installed
+    pub available: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+impl From<SelfUpdateOutcome> for SelfUpdateResult {
+    fn from(outcome: SelfUpdateOutcome) -> Self {
+        match outcome {
+            SelfUpdateOutcome::UpToDate { version } => Self {
+                previous_version: version.to_string(),
+                version: version.to_string(),
+                updated: false,
+                available: false,
+                path: None,
+            },
+            SelfUpdateOutcome::Available { current, latest } => Self {
+                previous_version: current.to_string(),
+                version: latest.to_string(),
+                updated: false,
+                available: true,
+                path: None,
+            },
+            SelfUpdateOutcome::Updated { from, to, path } => Self {
+                previous_version: from.to_string(),
+                version: to.to_string(),
+                updated: true,
+                available: false,
+                path: Some(path),
+            },
+        }
+    }
+}
+
+impl Render for SelfUpdateResult {
+    fn render_plain(&self) -> String {
+        if self.updated {
+            t!(
+                "self-update-done",
+                from = self.previous_version.as_str(),
+                to = self.version.as_str()
+            )
+        } else if self.available {
+            t!(
+                "self-update-available",
+                current = self.previous_version.as_str(),
+                latest = self.version.as_str()
+            )
+        } else {
+            t!("self-update-current", version = self.version.as_str())
+        }
+    }
+}
+
+/// An exit code as listed by `tram exit-codes`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExitCodeInfo {
+    pub code: u8,
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+impl From<ExitCode> for ExitCodeInfo {
+    fn from(exit_code: ExitCode) -> Self {
+        Self {
+            code: exit_code.code(),
+            name: exit_code.name(),
+            description: exit_code.description(),
+        }
+    }
+}
+
+/// Result of `tram exit-codes`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExitCodeList {
+    pub codes: Vec<ExitCodeInfo>,
+}
+
+impl Render for ExitCodeList {
+    fn render_plain(&self) -> String {
+        self.codes
+            .iter()
+            .map(|info| format!("{:>3}  {:<20} {}", info.code, info.name, info.description))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn render_table(&self) -> Option<Table> {
+        let mut table = Table::new([
+            t!("exit-codes-code"),
+            t!("exit-codes-name"),
+            t!("exit-codes-description"),
+        ])
+        .with_align(0, Align::Right);
+
+        for info in &self.codes {
+            table.add_row([
+                info.code.to_string(),
+                info.name.to_string(),
+                info.description.to_string(),
+            ]);
+        }
+
+        Some(table)
+    }
 }
@@ -0,0 +1,815 @@
+//! A resident session that serves commands over local IPC, with `tram daemon`.
+//!
+//! `tram daemon start` loads the config, detects the workspace, and watches
+//! the config file once, then listens on a Unix socket (a named pipe on
+//! Windows). Other tram invocations in the same workspace send it their
+//! arguments and print what it sends back, skipping startup entirely, which
+//! matters for editors that query tram on every keystroke.
+//!
+//! Only read-only commands whose output doesn't depend on the caller's
+//! environment are delegated (`workspace`, `config`, `deprecations`,
+//! `exit-codes`, `cache info`), and only without flags that change the
+//! session itself, such as `--config` or `--profile`. Anything else, a caller
+//! outside the daemon's workspace or with different `TRAM_*` variables, or a
+//! daemon that can't be reached in time, runs locally as usual. Set
+//! `TRAM_NO_DAEMON=1` to never delegate.
+//!
+//! Each connection carries one request and one response, as JSON lines.
+//! Connections are served one at a time, each with a deadline, so a client
+//! that stalls can't hold up the others for long.
+
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tracing::{debug, info, warn};
+use tram_config::{ConfigWatcher, OutputFormat};
+use tram_core::{
+    ColorChoice, ColorSupport, ErrorReport, ExitCode, Signal, Signals, Stream, TramError,
+    Verbosity, exit_code_for, t, terminal_width,
+};
+use tram_output::{CommandOutput, Render, Renderer, write_stdout};
+use tram_ui::{Dashboard, Table, TaskStatus, format_duration};
+
+use crate::cli::{CacheAction, Cli, ColorWhen, Commands, DaemonAction};
+use crate::commands::execute_command;
+use crate::session::TramSession;
+
+/// Overrides where the daemon listens.
+pub const SOCKET_ENV: &str = "TRAM_DAEMON_SOCKET";
+
+/// Set to anything to run every command locally.
+pub const NO_DAEMON_ENV: &str = "TRAM_NO_DAEMON";
+
+/// How long a detached daemon gets to start listening.
+const START_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a client waits to connect before running the command itself.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long the daemon waits for a request once a client has connected.
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long a connection may take from request to reply, on either side.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A message from a client.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum Request {
+    /// Run a command, given its arguments without the program name
+    #[serde(rename_all = "camelCase")]
+    Run {
+        args: Vec<String>,
+        cwd: PathBuf,
+        /// Whether the client's stdout should be colored
+        color: bool,
+        /// The client's terminal width, for fitting tables
+        width: Option<usize>,
+        /// The client's `TRAM_*` variables, which must match the daemon's
+        #[serde(default)]
+        env: BTreeMap<String, String>,
+    },
+    Status,
+    Stop,
+}
+
+/// The daemon's reply.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum Response {
+    /// The command ran; print its output and exit with its code
+    #[serde(rename_all = "camelCase")]
+    Finished {
+        exit_code: u8,
+        stdout: String,
+        stderr: String,
+    },
+    /// The daemon won't run this; the client should run it itself
+    Declined {
+        reason: String,
+    },
+    Status(DaemonStatus),
+    Stopping,
+}
+
+/// Result of `tram daemon status`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaemonStatus {
+    pub running: bool,
+    pub socket: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_root: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uptime_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requests: Option<u64>,
+}
+
+impl DaemonStatus {
+    fn stopped(socket: PathBuf) -> Self {
+        Self {
+            running: false,
+            socket,
+            pid: None,
+            workspace_root: None,
+            uptime_secs: None,
+            requests: None,
+        }
+    }
+}
+
+impl Render for DaemonStatus {
+    fn render_plain(&self) -> String {
+        let socket = self.socket.display().to_string();
+
+        match (self.pid, self.uptime_secs, self.requests) {
+            (Some(pid), Some(uptime), Some(requests)) if self.running => t!(
+                "daemon-running",
+                pid = pid,
+                socket = socket,
+                uptime = format_duration(Duration::from_secs(uptime)),
+                requests = requests
+            ),
+            _ => t!("daemon-not-running", socket = socket),
+        }
+    }
+
+    fn render_table(&self) -> Option<Table> {
+        let mut table = Table::new([t!("workspace-property"), t!("config-value")]);
+        let state = if self.running {
+            t!("daemon-state-running")
+        } else {
+            t!("daemon-state-stopped")
+        };
+        table.add_row([t!("daemon-status-label"), state]);
+        table.add_row([t!("daemon-socket-label"), self.socket.display().to_string()]);
+
+        if let Some(pid) = self.pid {
+            table.add_row([t!("daemon-pid-label"), pid.to_string()]);
+        }
+        if let Some(root) = &self.workspace_root {
+            table.add_row([t!("daemon-workspace-label"), root.display().to_string()]);
+        }
+        if let Some(uptime) = self.uptime_secs {
+            table.add_row([
+                t!("daemon-uptime-label"),
+                format_duration(Duration::from_secs(uptime)),
+            ]);
+        }
+        if let Some(requests) = self.requests {
+            table.add_row([t!("daemon-requests-label"), requests.to_string()]);
+        }
+
+        Some(table)
+    }
+}
+
+/// Result of `tram daemon stop`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaemonStop {
+    pub stopped: bool,
+    pub socket: PathBuf,
+}
+
+impl Render for DaemonStop {
+    fn render_plain(&self) -> String {
+        let socket = self.socket.display().to_string();
+
+        if self.stopped {
+            t!("daemon-stopping", socket = socket)
+        } else {
+            t!("daemon-not-running", socket = socket)
+        }
+    }
+}
+
+/// Where the daemon listens: `TRAM_DAEMON_SOCKET`, or `daemon.sock` in the
+/// data directory (a per-user named pipe on Windows). There's no shared
+/// fallback another user could take over, so this fails without a data
+/// directory.
+pub fn socket_path() -> tram_core::AppResult<PathBuf> {
+    if let Some(path) = std::env::var_os(SOCKET_ENV).filter(|path| !path.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+
+    if cfg!(windows) {
+        let user = std::env::var("USERNAME").unwrap_or_default();
+        return Ok(PathBuf::from(format!(r"\\.\pipe\tram-daemon-{}", user)));
+    }
+
+    match tram_core::data_dir() {
+        Some(dir) => Ok(dir.join("daemon.sock")),
+        None => Err(TramError::Daemon {
+            message: format!(
+                "Unable to determine the data directory for the daemon's socket; set {}",
+                SOCKET_ENV
+            ),
+        }
+        .into()),
+    }
+}
+
+/// The `TRAM_*` variables that shape a session, which a delegated command
+/// must share with the daemon. The ones that only pick a daemon are left
+/// out.
+fn session_env() -> BTreeMap<String, String> {
+    std::env::vars()
+        .filter(|(key, _)| key.starts_with("TRAM_") && key != SOCKET_ENV && key != NO_DAEMON_ENV)
+        .collect()
+}
+
+/// Whether a command may be sent to the daemon: a read-only command that
+/// returns its output, with no flags that would need a session of its own.
+/// The only global flags a request may carry are the ones [`run_request`]
+/// applies (`--format`, `--quiet`, `--dry-run`) and the color flags the
+/// caller decides.
+pub fn delegates(cli: &Cli) -> bool {
+    let global = &cli.global;
+    let own_session = global.log_level != "info"
+        || global.verbose > 0
+        || global.config.is_some()
+        || global.log_file.is_some()
+        || global.profile.is_some()
+        || global.yes
+        || global.offline
+        || global.timeout.is_some()
+        || global.events.is_some()
+        || global.events_fd.is_some()
+        || global.summary_file.is_some()
+        || global.timings
+        || global.timings_trace.is_some();
+
+    !own_session
+        && matches!(
+            cli.command,
+            Commands::Workspace { action: None, .. }
+                | Commands::Config { action: None }
+                | Commands::Deprecations
+                | Commands::ExitCodes
+                | Commands::Cache {
+                    action: CacheAction::Info
+                }
+        )
+}
+
+/// Send the command to a running daemon and print its output, returning
+/// the exit code; `None` when it should run locally instead.
+pub async fn delegate(cli: &Cli, args: &[OsString]) -> Option<u8> {
+    if std::env::var_os(NO_DAEMON_ENV).is_some_and(|value| !value.is_empty()) || !delegates(cli) {
+        return None;
+    }
+
+    let args = args
+        .iter()
+        .skip(1)
+        .map(|arg| arg.to_str().map(String::from))
+        .collect::<Option<Vec<_>>>()?;
+    let choice = match (cli.global.color, cli.global.no_color) {
+        (Some(ColorWhen::Always), _) => ColorChoice::Always,
+        (Some(ColorWhen::Never), _) | (_, true) => ColorChoice::Never,
+        _ => ColorChoice::Auto,
+    };
+    let request = Request::Run {
+        args,
+        cwd: tram_core::current_dir(),
+        color: ColorSupport::detect(choice).enabled(Stream::Stdout),
+        width: terminal_width(),
+        env: session_env(),
+    };
+
+    match send(&socket_path().ok()?, &request).await {
+        Ok(Response::Finished {
+            exit_code,
+            stdout,
+            stderr,
+        }) => {
+            // A closed pipe on either end isn't worth a panic
+            if let Err(e) = write_stdout(&stdout) {
+                debug!("{}", e);
+            }
+            let _ = std::io::stderr().lock().write_all(stderr.as_bytes());
+            Some(exit_code)
+        }
+        Ok(Response::Declined { reason }) => {
+            debug!("Daemon declined the command: {}", reason);
+            None
+        }
+        Ok(response) => {
+            debug!("Unexpected daemon response: {:?}", response);
+            None
+        }
+        Err(e) => {
+            debug!("Not using the daemon: {}", e);
+            None
+        }
+    }
+}
+
+/// Run a `tram daemon` action.
+pub async fn run(
+    session: &TramSession,
+    action: DaemonAction,
+) -> tram_core::AppResult<Option<CommandOutput>> {
+    let socket = socket_path()?;
+
+    match action {
+        DaemonAction::Start { detach: true, .. } => {
+            start_detached(&socket).await?;
+            Ok(Some(CommandOutput::new(&status(&socket).await)?))
+        }
+        DaemonAction::Start {
+            detach: false,
+            dashboard,
+        } => {
+            serve(session, &socket, dashboard).await?;
+            Ok(None)
+        }
+        DaemonAction::Stop => {
+            let stopped = matches!(send(&socket, &Request::Stop).await, Ok(Response::Stopping));
+            Ok(Some(CommandOutput::new(&DaemonStop { stopped, socket })?))
+        }
+        DaemonAction::Status => Ok(Some(CommandOutput::new(&status(&socket).await)?)),
+    }
+}
+
+/// Ask the daemon on `socket` for its status.
+async fn status(socket: &Path) -> DaemonStatus {
+    match send(socket, &Request::Status).await {
+        Ok(Response::Status(status)) => status,
+        _ => DaemonStatus::stopped(socket.to_path_buf()),
+    }
+}
+
+/// Start `tram daemon start` in the background with the same global
+/// options, and wait until it's listening.
+async fn start_detached(socket: &Path) -> tram_core::AppResult<()> {
+    let daemon_error = |message: String| TramError::Daemon { message };
+
+    if send(socket, &Request::Status).await.is_ok() {
+        return Err(daemon_error(format!(
+            "A daemon is already running on {}",
+            socket.display()
+        ))
+        .into());
+    }
+
+    let exe = std::env::current_exe()
+        .map_err(|e| daemon_error(format!("Failed to locate the tram executable: {}", e)))?;
+    let args = std::env::args_os().skip(1).filter(|arg| arg != "--detach");
+
+    let mut command = std::process::Command::new(exe);
+    command
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    // Keep Ctrl+C in this terminal from reaching the daemon
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| daemon_error(format!("Failed to start the daemon: {}", e)))?;
+
+    let started = Instant::now();
+    while started.elapsed() < START_TIMEOUT {
+        if send(socket, &Request::Status).await.is_ok() {
+            return Ok(());
+        }
+        if let Ok(Some(status)) = child.try_wait() {
+            return Err(
+                daemon_error(format!("The daemon exited during startup ({})", status)).into(),
+            );
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    Err(daemon_error(format!(
+        "The daemon didn't start listening on {} within {}s",
+        socket.display(),
+        START_TIMEOUT.as_secs()
+    ))
+    .into())
+}
+
+/// What every connection handler shares.
+struct Daemon {
+    session: TramSession,
+    socket: PathBuf,
+    /// Requests are served from the workspace the daemon started in
+    root: PathBuf,
+    config_watcher: Option<ConfigWatcher>,
+    started: Instant,
+    requests: AtomicU64,
+    /// Set by `tram daemon stop`
+    stopping: AtomicBool,
+    /// Served commands are recorded here with `--dashboard`
+    dashboard: Option<Dashboard>,
+}
+
+/// Listen on `socket` until `tram daemon stop`, Ctrl+C, or SIGTERM (or the
+/// dashboard is closed). SIGHUP reloads the config, which otherwise reloads
+/// when its file changes.
+async fn serve(session: &TramSession, socket: &Path, dashboard: bool) -> tram_core::AppResult<()> {
+    if send(socket, &Request::Status).await.is_ok() {
+        return Err(TramError::Daemon {
+            message: format!("A daemon is already running on {}", socket.display()),
+        }
+        .into());
+    }
+
+    let dashboard = if dashboard {
+        crate::dashboard::new("tram daemon", session)?
+    } else {
+        None
+    };
+
+    let mut listener = transport::bind(socket).map_err(|e| TramError::Daemon {
+        message: format!("Failed to listen on {}: {}", socket.display(), e),
+    })?;
+    let mut signals = Signals::new()?;
+
+    let config_watcher = match ConfigWatcher::new(
+        session.config.clone(),
+        Some(session.config_files.clone()),
+        session.config_overlay.clone(),
+    )
+    .await
+    {
+        Ok(watcher) => Some(watcher),
+        Err(e) => {
+            warn!("Failed to watch the config for changes: {}", e);
+            None
+        }
+    };
+
+    // Run `[schedules]` for as long as the daemon does
+    let scheduler = crate::schedule::start(session)?;
+
+    if let Some(dashboard) = &dashboard {
+        if config_watcher.is_some() {
+            dashboard.add_watched(crate::dashboard::config_files_label(session));
+        }
+        dashboard.event(t!("daemon-started", socket = socket.display().to_string()));
+    }
+
+    let daemon = Daemon {
+        session: session.clone(),
+        socket: socket.to_path_buf(),
+        root: session
+            .workspace_root
+            .clone()
+            .unwrap_or_else(tram_core::current_dir),
+        config_watcher,
+        started: Instant::now(),
+        requests: AtomicU64::new(0),
+        stopping: AtomicBool::new(false),
+        dashboard: dashboard.clone(),
+    };
+
+    if !session.verbosity.is_quiet() {
+        println!(
+            "{}",
+            t!("daemon-started", socket = socket.display().to_string())
+        );
+    }
+
+    let mut dashboard = dashboard.map(crate::dashboard::show);
+
+    // Connections are served one at a time: commands borrow the session,
+    // which can't move to another task. Each request is short, and a
+    // connection that runs past its deadline is dropped
+    let result = loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(stream) => {
+                    match tokio::time::timeout(REPLY_TIMEOUT, handle(stream, &daemon)).await {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => debug!("Daemon connection failed: {}", e),
+                        Err(_) => debug!("Daemon connection timed out"),
+                    }
+                    if daemon.stopping.load(Ordering::Relaxed) {
+                        break Ok(());
+                    }
+                }
+                Err(e) => warn!("Failed to accept a daemon connection: {}", e),
+            },
+            signal = signals.recv() => match signal {
+                Signal::Hangup => match &daemon.config_watcher {
+                    Some(watcher) => match watcher.reload().await {
+                        Ok(config) => {
+                            info!("Configuration reloaded");
+                            if let Some(dashboard) = &daemon.dashboard {
+                                crate::dashboard::set_config(
+                                    dashboard,
+                                    session.profile(),
+                                    &config,
+                                );
+                                dashboard.event(t!("dashboard-config-reloaded"));
+                            }
+                        }
+                        Err(e) => warn!("Configuration reload failed: {}", e),
+                    },
+                    None => info!("Config hot reload is disabled; ignoring SIGHUP"),
+                },
+                signal => break Err(TramError::Signal { signal }),
+            },
+            // Closing the dashboard stops the daemon, like `tram daemon stop`
+            _ = crate::dashboard::closed(&mut dashboard) => break Ok(()),
+        }
+    };
+
+    if let Some(dashboard) = dashboard {
+        dashboard.close().await;
+    }
+    drop(listener);
+    drop(scheduler);
+    if !session.verbosity.is_quiet() {
+        println!("{}", t!("daemon-stopped"));
+    }
+
+    result.map_err(Into::into)
+}
+
+/// Serve one connection: read a request, reply, and hang up.
+async fn handle<S>(stream: S, daemon: &Daemon) -> std::io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut stream = BufReader::new(stream);
+    let mut line = String::new();
+    tokio::time::timeout(READ_TIMEOUT, stream.read_line(&mut line))
+        .await
+        .map_err(|_| std::io::Error::from(std::io::ErrorKind::TimedOut))??;
+
+    let response = match serde_json::from_str(&line) {
+        Ok(Request::Run {
+            args,
+            cwd,
+            color,
+            width,
+            env,
+        }) => {
+            daemon.requests.fetch_add(1, Ordering::Relaxed);
+            let command = args.join(" ");
+            let started = Instant::now();
+            let response = run_request(daemon, args, &cwd, color, width, &env).await;
+            if let Some(dashboard) = &daemon.dashboard {
+                record_request(dashboard, &command, &response, started.elapsed());
+            }
+            response
+        }
+        Ok(Request::Status) => Response::Status(DaemonStatus {
+            running: true,
+            socket: daemon.socket.clone(),
+            pid: Some(std::process::id()),
+            workspace_root: daemon.session.workspace_root.clone(),
+            uptime_secs: Some(daemon.started.elapsed().as_secs()),
+            requests: Some(daemon.requests.load(Ordering::Relaxed)),
+        }),
+        Ok(Request::Stop) => {
+            daemon.stopping.store(true, Ordering::Relaxed);
+            Response::Stopping
+        }
+        Err(e) => Response::Declined {
+            reason: format!("Invalid request: {}", e),
+        },
+    };
+
+    let mut reply = serde_json::to_string(&response).map_err(std::io::Error::other)?;
+    reply.push('\n');
+    stream.get_mut().write_all(reply.as_bytes()).await?;
+    stream.get_mut().shutdown().await
+}
+
+/// Add a served command to the dashboard's history, or note why it wasn't
+/// served.
+fn record_request(dashboard: &Dashboard, command: &str, response: &Response, took: Duration) {
+    match response {
+        Response::Finished { exit_code, .. } => {
+            let status = match exit_code {
+                0 => TaskStatus::Passed(took),
+                code => TaskStatus::Failed(i32::from(*code)),
+            };
+            dashboard.record_run(command, status);
+        }
+        Response::Declined { reason } => dashboard.event(t!(
+            "dashboard-request-declined",
+            command = command,
+            reason = reason.as_str()
+        )),
+        Response::Status(_) | Response::Stopping => {}
+    }
+}
+
+/// Run a client's command against the warm session, capturing what it would
+/// have printed.
+async fn run_request(
+    daemon: &Daemon,
+    args: Vec<String>,
+    cwd: &Path,
+    color: bool,
+    width: Option<usize>,
+    env: &BTreeMap<String, String>,
+) -> Response {
+    if !cwd.starts_with(&daemon.root) {
+        return Response::Declined {
+            reason: t!("daemon-outside-workspace", path = cwd.display().to_string()),
+        };
+    }
+
+    // The session was built from the daemon's environment, so a caller with
+    // other `TRAM_*` settings would get output meant for someone else
+    let own_env = session_env();
+    if *env != own_env {
+        let differing: BTreeSet<&str> = env
+            .keys()
+            .chain(own_env.keys())
+            .filter(|key| env.get(*key) != own_env.get(*key))
+            .map(String::as_str)
+            .collect();
+        let vars: Vec<&str> = differing.into_iter().collect();
+        return Response::Declined {
+            reason: t!("daemon-different-env", vars = vars.join(", ")),
+        };
+    }
+
+    let cli = match Cli::try_parse_from(std::iter::once("tram".to_string()).chain(args)) {
+        Ok(cli) => cli,
+        Err(e) => {
+            return Response::Finished {
+                exit_code: ExitCode::Usage.code(),
+                stdout: String::new(),
+                stderr: e.to_string(),
+            };
+        }
+    };
+    if !delegates(&cli) {
+        return Response::Declined {
+            reason: t!("daemon-unsupported", command = format!("{:?}", cli.command)),
+        };
+    }
+
+    let mut session = daemon.session.clone();
+    if let Some(watcher) = &daemon.config_watcher {
+        session.config = watcher.get_config().await;
+    }
+    if let Some(format) = &cli.global.format
+        && let Ok(format) = format.parse::<OutputFormat>()
+    {
+        session.config.output_format = format;
+    }
+    if cli.global.quiet {
+        session.verbosity = Verbosity::Quiet;
+    }
+    if cli.global.dry_run {
+        session.dry_run = true;
+    }
+
+    let json_errors = session.config.output_format == OutputFormat::Json;
+    let color = color && (session.config.color || cli.global.color.is_some());
+
+    let result = match Box::pin(execute_command(cli.command, &session)).await {
+        Ok(Some(output)) if !session.verbosity.is_quiet() => Renderer::from_config(&session.config)
+            .with_color(color)
+            .with_max_width(width)
+            .render(&output)
+            .map(|rendered| match rendered.is_empty() {
+                true => rendered,
+                false => rendered + "\n",
+            }),
+        Ok(_) => Ok(String::new()),
+        Err(report) => Err(report),
+    };
+
+    match result {
+        Ok(stdout) => Response::Finished {
+            exit_code: ExitCode::Success.code(),
+            stdout,
+            stderr: String::new(),
+        },
+        Err(report) => Response::Finished {
+            exit_code: exit_code_for(&report).code(),
+            stdout: String::new(),
+            stderr: if json_errors {
+                format!("{}\n", ErrorReport::from_report(&report).to_json())
+            } else {
+                format!("Error: {:?}\n", report)
+            },
+        },
+    }
+}
+
+/// Send one request to the daemon on `socket` and read its reply, giving up
+/// on a daemon that doesn't answer in time.
+async fn send(socket: &Path, request: &Request) -> std::io::Result<Response> {
+    let timed_out = |_| std::io::Error::from(std::io::ErrorKind::TimedOut);
+
+    let stream = tokio::time::timeout(CONNECT_TIMEOUT, transport::connect(socket))
+        .await
+        .map_err(timed_out)??;
+    let mut stream = BufReader::new(stream);
+
+    let exchange = async {
+        let mut line = serde_json::to_string(request).map_err(std::io::Error::other)?;
+        line.push('\n');
+        stream.get_mut().write_all(line.as_bytes()).await?;
+
+        let mut reply = String::new();
+        stream.read_line(&mut reply).await?;
+        serde_json::from_str(&reply).map_err(std::io::Error::other)
+    };
+
+    tokio::time::timeout(REPLY_TIMEOUT, exchange)
+        .await
+        .map_err(timed_out)?
+}
+
+#[cfg(unix)]
+mod transport {
+    use std::path::{Path, PathBuf};
+    use tokio::net::{UnixListener, UnixStream};
+
+    pub async fn connect(socket: &Path) -> std::io::Result<UnixStream> {
+        UnixStream::connect(socket).await
+    }
+
+    /// Listens on a socket file, removing it when dropped.
+    pub struct Listener {
+        listener: UnixListener,
+        socket: PathBuf,
+    }
+
+    /// Listen on `socket`, replacing a file left behind by a daemon that
+    /// didn't shut down cleanly.
+    pub fn bind(socket: &Path) -> std::io::Result<Listener> {
+        if let Some(parent) = socket.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if socket.exists() {
+            std::fs::remove_file(socket)?;
+        }
+
+        Ok(Listener {
+            listener: UnixListener::bind(socket)?,
+            socket: socket.to_path_buf(),
+        })
+    }
+
+    impl Listener {
+        pub async fn accept(&mut self) -> std::io::Result<UnixStream> {
+            self.listener.accept().await.map(|(stream, _)| stream)
+        }
+    }
+
+    impl Drop for Listener {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.socket);
+        }
+    }
+}
+
+#[cfg(windows)]
+mod transport {
+    use std::path::{Path, PathBuf};
+    use tokio::net::windows::named_pipe::{
+        ClientOptions, NamedPipeClient, NamedPipeServer, ServerOptions,
+    };
+
+    pub async fn connect(pipe: &Path) -> std::io::Result<NamedPipeClient> {
+        ClientOptions::new().open(pipe)
+    }
+
+    /// Listens on a named pipe, creating a new instance for each client.
+    pub struct Listener {
+        server: NamedPipeServer,
+        pipe: PathBuf,
+    }
+
+    pub fn bind(pipe: &Path) -> std::io::Result<Listener> {
+        Ok(Listener {
+            server: ServerOptions::new()
+                .first_pipe_instance(true)
+                .create(pipe)?,
+            pipe: pipe.to_path_buf(),
+        })
+    }
+
+    impl Listener {
+        pub async fn accept(&mut self) -> std::io::Result<NamedPipeServer> {
+            self.server.connect().await?;
+            let next = ServerOptions::new().create(&self.pipe)?;
+            Ok(std::mem::replace(&mut self.server, next))
+        }
+    }
+}
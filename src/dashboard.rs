@@ -0,0 +1,122 @@
+//! `--dashboard` for `tram watch` and `tram daemon start`.
+//!
+//! A mode creates the dashboard with [`new`], fills in what it watches, and
+//! [`show`]s it once it has printed its startup messages. It's drawn on a
+//! blocking thread while the mode's own loop keeps running; the loop waits
+//! on [`closed`] alongside its signals, so pressing `q` stops the mode like
+//! Ctrl+C would. Logs to stderr are paused while the dashboard is up, since
+//! they'd draw over it.
+
+use tokio::task::{JoinError, JoinHandle};
+use tracing::{info, warn};
+use tram_config::TramConfig;
+use tram_core::{TramError, t};
+use tram_ui::{DASHBOARD_AVAILABLE, Dashboard, Term};
+
+use crate::commands::{ConfigView, config_rows};
+use crate::session::TramSession;
+
+/// A dashboard being drawn.
+pub struct RunningDashboard {
+    dashboard: Dashboard,
+    task: Option<JoinHandle<std::io::Result<()>>>,
+    /// How drawing ended, once the user has closed the dashboard
+    result: Option<Result<std::io::Result<()>, JoinError>>,
+}
+
+impl RunningDashboard {
+    /// The dashboard's state, to record events and task runs on.
+    pub fn dashboard(&self) -> &Dashboard {
+        &self.dashboard
+    }
+
+    /// Wait until the user closes the dashboard.
+    pub async fn closed(&mut self) {
+        match &mut self.task {
+            Some(task) => {
+                self.result = Some(task.await);
+                self.task = None;
+            }
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Close the dashboard if it's still up, restoring the terminal and
+    /// resuming logs.
+    pub async fn close(mut self) {
+        self.dashboard.stop();
+        if let Some(task) = self.task.take() {
+            self.result = Some(task.await);
+        }
+        tram_core::pause_console_logs(false);
+
+        match self.result {
+            Some(Ok(Err(e))) => warn!("The dashboard failed: {}", e),
+            Some(Err(e)) => warn!("The dashboard failed: {}", e),
+            _ => {}
+        }
+    }
+}
+
+/// A dashboard headed by `title`, showing the session's config, for a mode
+/// to fill in before it's shown. `None` when stdout isn't a terminal, in
+/// which case the mode runs with its usual output.
+pub fn new(title: &str, session: &TramSession) -> tram_core::AppResult<Option<Dashboard>> {
+    if !DASHBOARD_AVAILABLE {
+        return Err(TramError::InvalidInput {
+            input: "--dashboard".to_string(),
+            message: t!("dashboard-unavailable"),
+        }
+        .into());
+    }
+
+    if !Term::stdout().is_interactive() {
+        info!("{}", t!("dashboard-not-terminal"));
+        return Ok(None);
+    }
+
+    let dashboard = Dashboard::new(title);
+    set_config(&dashboard, session.profile(), &session.config);
+    Ok(Some(dashboard))
+}
+
+/// Show the config panel for `config`, e.g. after a reload.
+pub fn set_config(dashboard: &Dashboard, profile: Option<&str>, config: &TramConfig) {
+    dashboard.set_config(config_rows(&ConfigView { profile, config }));
+}
+
+/// The watched config files, for the dashboard's watched list.
+pub fn config_files_label(session: &TramSession) -> String {
+    if session.config_files.is_empty() {
+        return t!("dashboard-config-files");
+    }
+
+    session
+        .config_files
+        .iter()
+        .map(|file| file.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Take over the terminal with `dashboard`, once nothing else will print.
+pub fn show(dashboard: Dashboard) -> RunningDashboard {
+    tram_core::pause_console_logs(true);
+    let drawn = dashboard.clone();
+    let task = tokio::task::spawn_blocking(move || drawn.run());
+
+    RunningDashboard {
+        dashboard,
+        task: Some(task),
+        result: None,
+    }
+}
+
+/// Wait until `dashboard` is closed, or forever without one, for use in
+/// `tokio::select!`.
+pub async fn closed(dashboard: &mut Option<RunningDashboard>) {
+    match dashboard {
+        Some(dashboard) => dashboard.closed().await,
+        None => std::future::pending().await,
+    }
+}
@@ -0,0 +1,112 @@
+//! Deprecated commands, flags, and config keys.
+//!
+//! Everything tram is retiring is registered here so users get a single
+//! warning with a migration hint, and `tram deprecations` can list it all.
+
+use clap::ArgMatches;
+use clap::parser::ValueSource;
+use serde::Serialize;
+use std::path::PathBuf;
+use tram_config::TramConfig;
+use tram_core::{Deprecation, DeprecationKind, deprecations, register_deprecation, t};
+use tram_output::{CommandOutput, Render};
+use tram_ui::Table;
+
+/// Register tram's deprecations. Call once at startup, before checking.
+pub fn register() {
+    register_deprecation(
+        Deprecation::command("init", "0.1.0")
+            .with_removal("0.3.0")
+            .with_replacement("tram new <name> --project-type generic"),
+    );
+    // Never used to find the workspace, which is always detected
+    register_deprecation(Deprecation::config_key("workspaceRoot", "0.1.0").with_removal("0.3.0"));
+}
+
+/// Warn about deprecated commands and flags used on the command line.
+///
+/// Flags are registered as `--flag` for global options or `command --flag`
+/// for subcommand options.
+pub fn check(matches: &ArgMatches) {
+    check_flags(matches, None);
+
+    if let Some((command, sub_matches)) = matches.subcommand() {
+        tram_core::warn_if_deprecated(DeprecationKind::Command, command);
+        check_flags(sub_matches, Some(command));
+    }
+}
+
+fn check_flags(matches: &ArgMatches, command: Option<&str>) {
+    for id in matches.ids() {
+        if matches.value_source(id.as_str()) != Some(ValueSource::CommandLine) {
+            continue;
+        }
+
+        let flag = format!("--{}", id.as_str().replace('_', "-"));
+        let name = match command {
+            Some(command) => format!("{} {}", command, flag),
+            None => flag,
+        };
+
+        tram_core::warn_if_deprecated(DeprecationKind::Flag, &name);
+    }
+}
+
+/// Warn about deprecated settings in the config files that were loaded.
+pub fn check_config(files: &[PathBuf]) {
+    for file in files {
+        // The file already loaded, so it parses
+        if let Ok(keys) = TramConfig::keys_set_in_file(file) {
+            tram_core::warn_deprecated_config_keys(keys.iter().map(String::as_str));
+        }
+    }
+}
+
+/// Every registered deprecation, rendered in the requested output format.
+pub fn list() -> tram_core::AppResult<CommandOutput> {
+    CommandOutput::new(&DeprecationList(deprecations()))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+struct DeprecationList(Vec<Deprecation>);
+
+impl Render for DeprecationList {
+    fn render_plain(&self) -> String {
+        if self.0.is_empty() {
+            return t!("deprecations-none");
+        }
+
+        self.0
+            .iter()
+            .map(Deprecation::describe)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn render_table(&self) -> Option<Table> {
+        if self.0.is_empty() {
+            return None;
+        }
+
+        let mut table = Table::new([
+            t!("deprecation-kind"),
+            t!("deprecation-name"),
+            t!("deprecation-since"),
+            t!("deprecation-removal"),
+            t!("deprecation-replacement"),
+        ]);
+
+        for deprecation in &self.0 {
+            table.add_row([
+                deprecation.kind.to_string(),
+                deprecation.name.clone(),
+                deprecation.since.clone(),
+                deprecation.removal.clone().unwrap_or_default(),
+                deprecation.replacement.clone().unwrap_or_default(),
+            ]);
+        }
+
+        Some(table)
+    }
+}
@@ -1,18 +1,54 @@
-//! Developer tools for shell completions and manual page generation.
+//! Developer tools for shell completions, manual pages, Markdown docs, and
+//! OS packaging.
 //!
-//! This module provides functionality for generating shell completion scripts
-//! and manual pages, which are essential for CLI tool distribution and usability.
+//! This module provides functionality for generating shell completion scripts,
+//! manual pages, Markdown reference pages, and package manifests, which are
+//! essential for CLI tool distribution and usability.
 
 use clap::CommandFactory;
+use clap::builder::PossibleValuesParser;
 use clap_complete::{generate, shells::Shell};
 use clap_mangen::Man;
+use serde::Serialize;
 use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tram_core::{CompletionShell, MarkdownDocs, PackageFormat, Packager, TramError, t};
+use tram_output::{CommandOutput, Render};
 
 use crate::cli::Cli;
 
+/// The CLI definition to complete from: [`Cli::command`] with every config
+/// key path offered for `config get` and `config set`. The keys are added
+/// here rather than in `cli.rs`, which the build script compiles without
+/// the config crate. Only for listing completions; parse with
+/// [`Cli::command`], which accepts keys that aren't listed, such as task
+/// entries.
+pub fn completion_command() -> clap::Command {
+    static KEYS: OnceLock<Vec<String>> = OnceLock::new();
+
+    let keys = KEYS.get_or_init(|| {
+        tram_config::config_keys()
+            .into_iter()
+            .map(|key| key.path)
+            .collect()
+    });
+    let with_keys = |command: clap::Command| {
+        command.mut_arg("key", |arg| {
+            arg.value_parser(PossibleValuesParser::new(keys.iter().map(String::as_str)))
+        })
+    };
+
+    Cli::command().mut_subcommand("config", |config| {
+        config
+            .mut_subcommand("get", with_keys)
+            .mut_subcommand("set", with_keys)
+    })
+}
+
 /// Generate shell completions to stdout
 pub fn generate_completions(shell: Shell) -> tram_core::AppResult<()> {
-    let mut cmd = Cli::command();
+    let mut cmd = completion_command();
     let name = cmd.get_name().to_string();
     generate(shell, &mut cmd, name, &mut io::stdout());
     println!();
@@ -24,20 +60,27 @@ pub fn generate_completions(shell: Shell) -> tram_core::AppResult<()> {
             println!("# eval \"$(tram completions bash)\"");
             println!("# Or save to a file:");
             println!("# tram completions bash > ~/.bash_completion.d/tram");
+            println!("# Or let tram pick the directory:");
+            println!("# tram completions bash --install");
         }
         Shell::Zsh => {
             println!("# To install zsh completions, add this to your ~/.zshrc:");
             println!("# eval \"$(tram completions zsh)\"");
             println!("# Or save to a file in your fpath:");
             println!("# tram completions zsh > ~/.zsh/completions/_tram");
+            println!("# Or let tram pick the directory:");
+            println!("# tram completions zsh --install");
         }
         Shell::Fish => {
             println!("# To install fish completions:");
             println!("# tram completions fish > ~/.config/fish/completions/tram.fish");
+            println!("# Or: tram completions fish --install");
         }
         Shell::PowerShell => {
             println!("# To install PowerShell completions, add this to your $PROFILE:");
             println!("# Invoke-Expression (& tram completions powershell)");
+            println!("# Or let tram add them to your profile:");
+            println!("# tram completions powershell --install");
         }
         _ => {}
     }
@@ -45,62 +88,294 @@ pub fn generate_completions(shell: Shell) -> tram_core::AppResult<()> {
     Ok(())
 }
 
-/// Generate manual pages
-pub fn generate_man_pages(
-    output_dir: &std::path::Path,
-    section: Option<u8>,
-) -> tram_core::AppResult<()> {
-    use std::fs;
+/// The user's login shell name, e.g. `zsh`, from `SHELL`. Windows has no
+/// `SHELL`, so PowerShell is assumed there.
+pub fn current_shell() -> Option<String> {
+    let Some(shell) = std::env::var_os("SHELL") else {
+        return cfg!(windows).then(|| "powershell".to_string());
+    };
 
-    // Create output directory if it doesn't exist
-    fs::create_dir_all(output_dir).map_err(|e| tram_core::TramError::InvalidConfig {
-        message: format!("Failed to create output directory: {}", e),
+    Path::new(&shell)
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .map(|name| match name {
+            "pwsh" => "powershell".to_string(),
+            name => name.to_string(),
+        })
+}
+
+/// Where completions for a shell are installed so the shell loads them
+/// without changes to its rc file (zsh needs the directory on `fpath`).
+/// PowerShell's go next to `$PROFILE`, which dot-sources them.
+pub fn completion_install_path(shell: Shell) -> Option<PathBuf> {
+    if shell == Shell::PowerShell {
+        return powershell_profile_path()
+            .and_then(|profile| Some(profile.parent()?.join("tram-completions.ps1")));
+    }
+
+    let home = tram_core::home_dir()?;
+    let xdg = |var: &str, default: &str| {
+        std::env::var_os(var)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(default))
+    };
+
+    match shell {
+        Shell::Bash => {
+            Some(xdg("XDG_DATA_HOME", ".local/share").join("bash-completion/completions/tram"))
+        }
+        Shell::Zsh => Some(home.join(".zsh/completions/_tram")),
+        Shell::Fish => Some(xdg("XDG_CONFIG_HOME", ".config").join("fish/completions/tram.fish")),
+        _ => None,
+    }
+}
+
+/// The PowerShell profile script: `TRAM_POWERSHELL_PROFILE` when set, else
+/// `$PROFILE` as reported by `pwsh` (or Windows PowerShell), else where
+/// PowerShell 7 keeps it by default.
+pub fn powershell_profile_path() -> Option<PathBuf> {
+    if let Some(profile) = std::env::var_os("TRAM_POWERSHELL_PROFILE").filter(|p| !p.is_empty()) {
+        return Some(PathBuf::from(profile));
+    }
+
+    for program in ["pwsh", "powershell"] {
+        let profile = std::process::Command::new(program)
+            .args([
+                "-NoLogo",
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                "$PROFILE",
+            ])
+            .stdin(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map(|profile| profile.trim().to_string())
+            .filter(|profile| !profile.is_empty());
+
+        if let Some(profile) = profile {
+            return Some(PathBuf::from(profile));
+        }
+    }
+
+    let home = tram_core::home_dir()?;
+    let dir = if cfg!(windows) {
+        home.join("Documents").join("PowerShell")
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".config"))
+            .join("powershell")
+    };
+
+    Some(dir.join("Microsoft.PowerShell_profile.ps1"))
+}
+
+/// Dot-source the completion script from the PowerShell profile, unless it
+/// already is. Returns whether the profile changed.
+fn register_powershell_completions(profile: &Path, script: &Path) -> io::Result<bool> {
+    let existing = match std::fs::read_to_string(profile) {
+        Ok(existing) => existing,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let line = format!(". \"{}\"", script.display());
+
+    if existing.lines().any(|existing| existing.trim() == line) {
+        return Ok(false);
+    }
+
+    let mut contents = existing;
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    contents.push_str("\n# tram completions\n");
+    contents.push_str(&line);
+    contents.push('\n');
+
+    if let Some(parent) = profile.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(profile, contents)?;
+    Ok(true)
+}
+
+/// Result of `tram completions [shell] --install`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionsInstalled {
+    pub shell: String,
+    /// Whether the shell was detected from `SHELL`, rather than given
+    pub detected: bool,
+    pub path: PathBuf,
+    /// The PowerShell profile that loads the script
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<PathBuf>,
+    /// Whether the profile was changed to load it, rather than already
+    /// doing so
+    pub registered: bool,
+}
+
+impl Render for CompletionsInstalled {
+    fn render_plain(&self) -> String {
+        let mut message = String::new();
+        if self.detected {
+            message.push_str(&t!(
+                "completions-detected-shell",
+                shell = self.shell.as_str()
+            ));
+            message.push('\n');
+        }
+        message.push_str(&t!(
+            "completions-installed",
+            shell = self.shell.as_str(),
+            path = self.path.display().to_string()
+        ));
+
+        if self.shell == "zsh" {
+            message.push('\n');
+            message.push_str(&t!("completions-zsh-fpath"));
+        }
+        if let Some(profile) = &self.profile {
+            let key = if self.registered {
+                "completions-powershell-registered"
+            } else {
+                "completions-powershell-already-registered"
+            };
+            message.push('\n');
+            message.push_str(&t!(key, path = profile.display().to_string()));
+        }
+
+        message
+    }
+}
+
+/// Write completions for a shell to its completion directory, creating it
+/// if needed. Without a shell, the user's login shell gets them.
+pub fn install_completions(shell: Option<Shell>) -> tram_core::AppResult<CommandOutput> {
+    let detected = shell.is_none();
+    let shell = match shell {
+        Some(shell) => shell,
+        None => {
+            let name = current_shell();
+            name.as_deref()
+                .and_then(|name| name.parse::<Shell>().ok())
+                .ok_or_else(|| TramError::InvalidConfig {
+                    message: match &name {
+                        Some(name) => t!("completions-unsupported-shell", shell = name.as_str()),
+                        None => t!("completions-no-shell"),
+                    },
+                })?
+        }
+    };
+
+    let path = completion_install_path(shell).ok_or_else(|| TramError::InvalidConfig {
+        message: t!("completions-install-unsupported", shell = shell.to_string()),
     })?;
 
+    let mut cmd = completion_command();
+    let name = cmd.get_name().to_string();
+    let mut script = Vec::new();
+    generate(shell, &mut cmd, name, &mut script);
+
+    let write_error = |e: io::Error| TramError::Io {
+        message: format!("Failed to write {}: {}", path.display(), e),
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(write_error)?;
+    }
+    std::fs::write(&path, script).map_err(write_error)?;
+
+    // PowerShell has no completion directory; the profile loads the script
+    let mut profile = None;
+    let mut registered = false;
+    if shell == Shell::PowerShell
+        && let Some(profile_path) = powershell_profile_path()
+    {
+        registered =
+            register_powershell_completions(&profile_path, &path).map_err(|e| TramError::Io {
+                message: format!("Failed to write {}: {}", profile_path.display(), e),
+            })?;
+        profile = Some(profile_path);
+    }
+
+    CommandOutput::new(&CompletionsInstalled {
+        shell: shell.to_string(),
+        detected,
+        path,
+        profile,
+        registered,
+    })
+}
+
+/// Render the man pages for `section` (all sections when `None`), as file
+/// names and their roff contents.
+fn render_man_pages(section: Option<u8>) -> tram_core::AppResult<Vec<(String, Vec<u8>)>> {
     let cmd = Cli::command();
     let app_name = "tram";
+    let mut pages = Vec::new();
+
+    // Only section 1 has pages
+    if section.is_some_and(|section| section != 1) {
+        return Ok(pages);
+    }
+
+    // Main command man page
+    let man = Man::new(cmd.clone());
+    let mut buffer = Vec::new();
+    man.render(&mut buffer)
+        .map_err(|e| tram_core::TramError::InvalidConfig {
+            message: format!("Failed to generate man page: {}", e),
+        })?;
+    buffer.extend_from_slice(tram_core::ExitCode::man_section().as_bytes());
+    pages.push((format!("{}.1", app_name), buffer));
+
+    // Subcommand man pages
+    for subcommand in cmd.get_subcommands() {
+        let subcommand_name = subcommand.get_name();
+
+        let man = Man::new(subcommand.clone())
+            .title(format!("{}-{}", app_name, subcommand_name))
+            .section("1")
+            .source(format!("{} {}", app_name, env!("CARGO_PKG_VERSION")))
+            .manual("User Commands");
 
-    // Generate main command man page (section 1)
-    if section.is_none() || section == Some(1) {
-        let man = Man::new(cmd.clone());
         let mut buffer = Vec::new();
         man.render(&mut buffer)
             .map_err(|e| tram_core::TramError::InvalidConfig {
-                message: format!("Failed to generate man page: {}", e),
+                message: format!("Failed to generate subcommand man page: {}", e),
             })?;
 
-        let man_file = output_dir.join(format!("{}.1", app_name));
-        fs::write(&man_file, buffer).map_err(|e| tram_core::TramError::InvalidConfig {
-            message: format!("Failed to write man page: {}", e),
-        })?;
-
-        println!("Generated man page: {}", man_file.display());
+        pages.push((format!("{}-{}.1", app_name, subcommand_name), buffer));
     }
 
-    // Generate subcommand man pages
-    for subcommand in cmd.get_subcommands() {
-        let subcommand_name = subcommand.get_name();
+    Ok(pages)
+}
 
-        if section.is_none() || section == Some(1) {
-            let man = Man::new(subcommand.clone())
-                .title(format!("{}-{}", app_name, subcommand_name))
-                .section("1")
-                .source(format!("{} {}", app_name, env!("CARGO_PKG_VERSION")))
-                .manual("User Commands");
-
-            let mut buffer = Vec::new();
-            man.render(&mut buffer)
-                .map_err(|e| tram_core::TramError::InvalidConfig {
-                    message: format!("Failed to generate subcommand man page: {}", e),
-                })?;
-
-            let man_file = output_dir.join(format!("{}-{}.1", app_name, subcommand_name));
-            fs::write(&man_file, buffer).map_err(|e| tram_core::TramError::InvalidConfig {
-                message: format!("Failed to write subcommand man page: {}", e),
-            })?;
+/// Generate manual pages
+pub fn generate_man_pages(
+    output_dir: &std::path::Path,
+    section: Option<u8>,
+) -> tram_core::AppResult<()> {
+    use std::fs;
 
-            println!("Generated man page: {}", man_file.display());
-        }
+    // Create output directory if it doesn't exist
+    fs::create_dir_all(output_dir).map_err(|e| tram_core::TramError::Io {
+        message: format!("Failed to create output directory: {}", e),
+    })?;
+
+    for (name, buffer) in render_man_pages(section)? {
+        let man_file = output_dir.join(name);
+        fs::write(&man_file, buffer).map_err(|e| tram_core::TramError::Io {
+            message: format!("Failed to write man page: {}", e),
+        })?;
+
+        println!("Generated man page: {}", man_file.display());
     }
 
     println!();
@@ -113,9 +388,394 @@ pub fn generate_man_pages(
     );
     println!("  sudo mandb  # Update man database");
     println!();
+    println!("Or install for the current user:");
+    println!("  tram man --install");
+    println!();
     println!("To view locally:");
     println!("  man -M {} tram", output_dir.display());
     println!("  man -M {} tram-new", output_dir.display());
 
     Ok(())
 }
+
+/// The man directory pages are installed under: `<prefix>/share/man` when a
+/// prefix is given, else the first writable directory on `MANPATH`, else the
+/// per-user `~/.local/share/man`, which `man` searches when `~/.local/bin` is
+/// on `PATH`.
+pub fn man_install_dir(prefix: Option<&Path>) -> Option<PathBuf> {
+    if let Some(prefix) = prefix {
+        return Some(prefix.join("share/man"));
+    }
+
+    let manpath = std::env::var_os("MANPATH").unwrap_or_default();
+    if let Some(dir) = std::env::split_paths(&manpath).find(|dir| is_writable_dir(dir)) {
+        return Some(dir);
+    }
+
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| tram_core::home_dir().map(|home| home.join(".local/share")))?;
+
+    Some(data_home.join("man"))
+}
+
+fn is_writable_dir(dir: &Path) -> bool {
+    !dir.as_os_str().is_empty()
+        && std::fs::metadata(dir).is_ok_and(|meta| meta.is_dir() && !meta.permissions().readonly())
+}
+
+/// Result of `tram man --install`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManInstalled {
+    /// The `man1` directory the pages were copied to
+    pub path: PathBuf,
+    pub pages: Vec<String>,
+    /// Whether `mandb` ran to refresh the man database
+    pub mandb: bool,
+    pub dry_run: bool,
+}
+
+impl Render for ManInstalled {
+    fn render_plain(&self) -> String {
+        let key = if self.dry_run {
+            "man-install-dry-run"
+        } else {
+            "man-installed"
+        };
+        let mut lines = vec![t!(
+            key,
+            count = self.pages.len(),
+            path = self.path.display().to_string()
+        )];
+
+        if self.mandb {
+            lines.push(t!("man-mandb-updated"));
+        }
+        if !self.dry_run {
+            lines.push(t!("man-install-view"));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Result of `tram man --install` on Windows, which has no `man`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManInstallSkipped {
+    pub reason: String,
+}
+
+impl Render for ManInstallSkipped {
+    fn render_plain(&self) -> String {
+        self.reason.clone()
+    }
+}
+
+/// Copy the man pages into the man directory (see [`man_install_dir`]) and
+/// refresh the man database with `mandb` when it's installed. On Windows,
+/// nothing is installed unless a prefix is given (e.g. an MSYS2 or Cygwin
+/// root), and the output says where to find the docs instead.
+pub fn install_man_pages(
+    prefix: Option<&Path>,
+    section: Option<u8>,
+    dry_run: bool,
+) -> tram_core::AppResult<CommandOutput> {
+    if cfg!(windows) && prefix.is_none() {
+        return CommandOutput::new(&ManInstallSkipped {
+            reason: t!("man-install-windows"),
+        });
+    }
+
+    let root = man_install_dir(prefix).ok_or_else(|| TramError::InvalidConfig {
+        message: t!("man-install-no-dir"),
+    })?;
+    let man1 = root.join("man1");
+    let pages = render_man_pages(section)?;
+
+    let write_error = |path: &Path, e: io::Error| TramError::Io {
+        message: format!("Failed to write {}: {}", path.display(), e),
+    };
+
+    let mut mandb = false;
+    if !dry_run {
+        std::fs::create_dir_all(&man1).map_err(|e| write_error(&man1, e))?;
+
+        for (name, buffer) in &pages {
+            let path = man1.join(name);
+            std::fs::write(&path, buffer).map_err(|e| write_error(&path, e))?;
+            set_readable(&path).map_err(|e| write_error(&path, e))?;
+        }
+
+        mandb = update_man_db(&root);
+    }
+
+    CommandOutput::new(&ManInstalled {
+        path: man1,
+        pages: pages.into_iter().map(|(name, _)| name).collect(),
+        mandb,
+        dry_run,
+    })
+}
+
+/// Pages must be world-readable for `man` run by other users.
+#[cfg(unix)]
+fn set_readable(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o644))
+}
+
+#[cfg(not(unix))]
+fn set_readable(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Run `mandb` on the man directory, returning whether it succeeded. A
+/// missing `mandb` (e.g. on macOS, which indexes pages lazily) isn't an error.
+fn update_man_db(root: &Path) -> bool {
+    match std::process::Command::new("mandb")
+        .arg("--quiet")
+        .arg(root)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+    {
+        Ok(status) => status.success(),
+        Err(e) => {
+            tracing::debug!("Not running mandb: {}", e);
+            false
+        }
+    }
+}
+
+/// Result of `tram docs`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocsGenerated {
+    pub path: PathBuf,
+    pub pages: Vec<String>,
+    pub dry_run: bool,
+}
+
+impl Render for DocsGenerated {
+    fn render_plain(&self) -> String {
+        let key = if self.dry_run {
+            "docs-dry-run"
+        } else {
+            "docs-generated"
+        };
+
+        t!(
+            key,
+            count = self.pages.len(),
+            path = self.path.display().to_string()
+        )
+    }
+}
+
+/// Tram's reference pages, with examples for the commands people reach
+/// for first and the exit codes on the top-level page.
+fn markdown_docs() -> MarkdownDocs {
+    MarkdownDocs::new(Cli::command())
+        .with_example(
+            "",
+            "Show the detected workspace as JSON",
+            "tram --format json workspace",
+        )
+        .with_example(
+            "new",
+            "Create a Rust project with a git repository and CI, without prompts",
+            "tram new my-cli --git --ci --skip-prompts",
+        )
+        .with_example(
+            "run",
+            "List the tasks defined in the config",
+            "tram run --list",
+        )
+        .with_example(
+            "run",
+            "Run `test` after the tasks it depends on, two at a time",
+            "tram run test --jobs 2",
+        )
+        .with_example(
+            "watch",
+            "Run the checks and watch tasks whenever files change",
+            "tram watch --check",
+        )
+        .with_example(
+            "cache prune",
+            "Shrink the cache to 1 GB",
+            "tram cache prune --max-size 1GB",
+        )
+        .with_example(
+            "completions",
+            "Install zsh completions",
+            "tram completions zsh --install",
+        )
+        .with_example(
+            "man",
+            "Install the man pages for the current user",
+            "tram man --install",
+        )
+        .with_example(
+            "docs",
+            "Write these pages for a docs site",
+            "tram docs --output docs/cli",
+        )
+        .with_example(
+            "daemon start",
+            "Start a daemon in the background",
+            "tram daemon start --detach",
+        )
+        .with_example(
+            "auth login",
+            "Save a token from a CI secret",
+            "echo \"$TOKEN\" | tram auth login registry --stdin",
+        )
+        .with_example(
+            "self-update",
+            "Check for a newer release without installing it",
+            "tram self-update --check",
+        )
+        .with_section("", tram_core::ExitCode::markdown_section())
+}
+
+/// Write a Markdown page per command into `output`, or list the pages
+/// that would be written.
+pub fn generate_docs(output: &Path, dry_run: bool) -> tram_core::AppResult<CommandOutput> {
+    let docs = markdown_docs();
+
+    let pages = if dry_run {
+        docs.render()
+            .into_iter()
+            .map(|page| page.file_name)
+            .collect()
+    } else {
+        docs.write(output)?
+            .iter()
+            .filter_map(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .collect()
+    };
+
+    CommandOutput::new(&DocsGenerated {
+        path: output.to_path_buf(),
+        pages,
+        dry_run,
+    })
+}
+
+/// Result of `tram package`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageGenerated {
+    pub format: String,
+    pub version: String,
+    pub path: PathBuf,
+    pub files: Vec<PathBuf>,
+    pub dry_run: bool,
+}
+
+impl Render for PackageGenerated {
+    fn render_plain(&self) -> String {
+        let key = if self.dry_run {
+            "package-dry-run"
+        } else {
+            "package-generated"
+        };
+
+        let mut lines = vec![t!(
+            key,
+            format = self.format.as_str(),
+            version = self.version.as_str(),
+            path = self.path.display().to_string()
+        )];
+        lines.extend(
+            self.files
+                .iter()
+                .map(|file| format!("  {}", file.display())),
+        );
+        lines.join("\n")
+    }
+}
+
+/// Tram's packaging, with its man pages and the completions for the
+/// shells packages install them for.
+fn packager(
+    url: Option<String>,
+    sha256: Option<String>,
+    maintainer: Option<String>,
+) -> tram_core::AppResult<Packager> {
+    let mut cmd = completion_command();
+    let mut packager = Packager::new(&cmd)
+        .with_homepage(env!("CARGO_PKG_HOMEPAGE"))
+        .with_repository(env!("CARGO_PKG_REPOSITORY"))
+        .with_license(env!("CARGO_PKG_LICENSE"));
+
+    for (file_name, contents) in render_man_pages(Some(1))? {
+        packager = packager.with_man_page(file_name, contents);
+    }
+    for (shell, package_shell) in [
+        (Shell::Bash, CompletionShell::Bash),
+        (Shell::Zsh, CompletionShell::Zsh),
+        (Shell::Fish, CompletionShell::Fish),
+    ] {
+        let mut script = Vec::new();
+        generate(shell, &mut cmd, "tram", &mut script);
+        packager = packager.with_completion(package_shell, script);
+    }
+
+    if let Some(url) = url {
+        packager = packager.with_url(url);
+    }
+    if let Some(sha256) = sha256 {
+        packager = packager.with_sha256(sha256);
+    }
+    // Fall back to the first of the crate's authors
+    let maintainer = maintainer.or_else(|| {
+        env!("CARGO_PKG_AUTHORS")
+            .split(':')
+            .next()
+            .filter(|author| !author.is_empty())
+            .map(String::from)
+    });
+    if let Some(maintainer) = maintainer {
+        packager = packager.with_maintainer(maintainer);
+    }
+
+    Ok(packager)
+}
+
+/// Write the manifest for `format`, and the man pages and completions it
+/// installs, into `output`, or list the files that would be written.
+pub fn generate_package(
+    format: PackageFormat,
+    output: &Path,
+    url: Option<String>,
+    sha256: Option<String>,
+    maintainer: Option<String>,
+    dry_run: bool,
+) -> tram_core::AppResult<CommandOutput> {
+    let packager = packager(url, sha256, maintainer)?;
+
+    let files = if dry_run {
+        packager
+            .render(format)?
+            .into_iter()
+            .map(|file| output.join(file.path))
+            .collect()
+    } else {
+        packager.write(format, output)?
+    };
+
+    CommandOutput::new(&PackageGenerated {
+        format: format.as_str().to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        path: output.to_path_buf(),
+        files,
+        dry_run,
+    })
+}
@@ -0,0 +1,373 @@
+//! Environment checks for `tram doctor`.
+//!
+//! Each check reports pass, warn, or fail with a hint on how to fix it, so
+//! "it doesn't work on my machine" reports start from the same diagnostics.
+
+use clap_complete::Shell;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tram_config::TramConfig;
+use tram_core::{TramError, t};
+use tram_output::{CommandOutput, Render, Renderer};
+use tram_process::{ExecResult, OutputMode, Process, which};
+use tram_ui::Table;
+
+use crate::dev_tools::{completion_install_path, current_shell, powershell_profile_path};
+use crate::session::TramSession;
+
+/// How long to wait for a tool to print its version.
+const TOOL_VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl CheckStatus {
+    fn label(&self) -> String {
+        match self {
+            CheckStatus::Pass => t!("doctor-pass"),
+            CheckStatus::Warn => t!("doctor-warn"),
+            CheckStatus::Fail => t!("doctor-fail"),
+        }
+    }
+}
+
+/// A single environment check.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+    /// How to fix a warning or failure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: String, message: String) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Pass,
+            message,
+            hint: None,
+        }
+    }
+
+    fn warn(name: String, message: String, hint: String) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Warn,
+            message,
+            hint: Some(hint),
+        }
+    }
+
+    fn fail(name: String, message: String, hint: String) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Fail,
+            message,
+            hint: Some(hint),
+        }
+    }
+}
+
+/// Result of `tram doctor`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DoctorReport {
+    pub checks: Vec<Check>,
+}
+
+impl DoctorReport {
+    fn count(&self, status: CheckStatus) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status == status)
+            .count()
+    }
+
+    fn summary(&self) -> String {
+        t!(
+            "doctor-summary",
+            passed = self.count(CheckStatus::Pass),
+            warnings = self.count(CheckStatus::Warn),
+            failures = self.count(CheckStatus::Fail)
+        )
+    }
+}
+
+impl Render for DoctorReport {
+    fn render_plain(&self) -> String {
+        let mut lines = Vec::new();
+
+        for check in &self.checks {
+            let marker = match check.status {
+                CheckStatus::Pass => "✓",
+                CheckStatus::Warn => "!",
+                CheckStatus::Fail => "✗",
+            };
+            lines.push(format!("{} {}: {}", marker, check.name, check.message));
+
+            if let Some(hint) = &check.hint {
+                lines.push(format!("    {}", hint));
+            }
+        }
+
+        lines.push(String::new());
+        lines.push(self.summary());
+        lines.join("\n")
+    }
+
+    fn render_table(&self) -> Option<Table> {
+        let mut table = Table::new([
+            t!("doctor-check"),
+            t!("doctor-status"),
+            t!("doctor-details"),
+            t!("doctor-hint"),
+        ])
+        .with_title(self.summary());
+
+        let theme = tram_ui::theme();
+        for check in &self.checks {
+            let row = [
+                check.name.clone(),
+                check.status.label(),
+                check.message.clone(),
+                check.hint.clone().unwrap_or_default(),
+            ];
+            match check.status {
+                CheckStatus::Pass => table.add_row(row),
+                CheckStatus::Warn => table.add_styled_row(row, theme.warning),
+                CheckStatus::Fail => table.add_styled_row(row, theme.error),
+            }
+        }
+
+        Some(table)
+    }
+}
+
+/// Run every environment check and print the report, failing when any
+/// check failed so scripts and CI can tell.
+pub fn run(session: &TramSession) -> tram_core::AppResult<()> {
+    let mut checks = vec![check_config(), check_workspace(session)];
+    checks.extend(check_tools(session));
+    checks.push(check_cache_dir());
+    checks.push(check_completions());
+
+    let report = DoctorReport { checks };
+    Renderer::from_config(&session.config)
+        .with_verbosity(session.verbosity)
+        .print(&CommandOutput::new(&report)?)?;
+
+    let failed = report.count(CheckStatus::Fail);
+    if failed > 0 {
+        return Err(TramError::Doctor {
+            failed,
+            total: report.checks.len(),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+fn check_config() -> Check {
+    let name = t!("doctor-config");
+
+    let files = TramConfig::config_files();
+    if files.is_empty() {
+        return Check::pass(name, t!("doctor-config-defaults"));
+    }
+
+    // Each layer is checked on its own, so the message names the bad file
+    for file in &files {
+        if let Err(e) = TramConfig::load_from_file(&file.path) {
+            return Check::fail(
+                name,
+                t!(
+                    "doctor-config-invalid",
+                    path = file.path.display().to_string(),
+                    error = e.to_string()
+                ),
+                t!("doctor-config-hint"),
+            );
+        }
+    }
+
+    let paths: Vec<String> = files
+        .iter()
+        .map(|file| file.path.display().to_string())
+        .collect();
+    Check::pass(name, t!("doctor-config-valid", paths = paths.join(", ")))
+}
+
+fn check_workspace(session: &TramSession) -> Check {
+    let name = t!("doctor-workspace");
+
+    match &session.workspace_root {
+        Some(root) => Check::pass(
+            name,
+            t!("doctor-workspace-found", path = root.display().to_string()),
+        ),
+        None => Check::warn(
+            name,
+            t!("doctor-workspace-missing"),
+            t!("doctor-workspace-hint"),
+        ),
+    }
+}
+
+/// One check per tool the detected project type needs.
+fn check_tools(session: &TramSession) -> Vec<Check> {
+    let Some(project_type) = &session.project_type else {
+        return Vec::new();
+    };
+
+    project_type
+        .required_tools()
+        .iter()
+        .map(|tool| {
+            let name = t!("doctor-tool", tool = *tool);
+
+            match which(tool) {
+                Some(path) => match tool_version(&path) {
+                    Some(version) => Check::pass(name, format!("{} ({})", path.display(), version)),
+                    None => Check::pass(name, path.display().to_string()),
+                },
+                None => Check::fail(
+                    name,
+                    t!("doctor-tool-missing", tool = *tool),
+                    t!(
+                        "doctor-tool-hint",
+                        tool = *tool,
+                        kind = format!("{:?}", project_type)
+                    ),
+                ),
+            }
+        })
+        .collect()
+}
+
+/// The first line a tool prints for `--version`, if it answers quickly.
+fn tool_version(path: &Path) -> Option<String> {
+    let result = Process::new(path.display().to_string(), ["--version"])
+        .with_output(OutputMode::Capture)
+        .with_timeout(TOOL_VERSION_TIMEOUT)
+        .run()
+        .ok()
+        .filter(ExecResult::success)?;
+
+    // Some tools, such as older javas, print their version on stderr
+    [&result.stdout, &result.stderr]
+        .into_iter()
+        .flat_map(|output| output.lines())
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+fn check_cache_dir() -> Check {
+    let name = t!("doctor-cache");
+
+    let Some(dir) = tram_core::cache_dir() else {
+        return Check::fail(name, t!("doctor-cache-unknown"), t!("doctor-cache-hint"));
+    };
+
+    let path = dir.display().to_string();
+    match ensure_writable(&dir) {
+        Ok(()) => Check::pass(name, path),
+        Err(e) => Check::fail(
+            name,
+            t!(
+                "doctor-cache-unwritable",
+                path = path,
+                error = e.to_string()
+            ),
+            t!("doctor-cache-hint"),
+        ),
+    }
+}
+
+/// Create the directory if needed and prove a file can be written to it.
+fn ensure_writable(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+
+    let probe = dir.join(format!(".doctor-{}", std::process::id()));
+    std::fs::write(&probe, b"")?;
+    std::fs::remove_file(&probe)
+}
+
+fn check_completions() -> Check {
+    let name = t!("doctor-completions");
+
+    let Some(shell) = current_shell() else {
+        return Check::warn(
+            name,
+            t!("doctor-completions-no-shell"),
+            t!("doctor-completions-hint", shell = "<shell>"),
+        );
+    };
+
+    if completions_installed(&shell) {
+        Check::pass(name, t!("doctor-completions-found", shell = shell.as_str()))
+    } else {
+        Check::warn(
+            name,
+            t!("doctor-completions-missing", shell = shell.as_str()),
+            t!("doctor-completions-hint", shell = shell.as_str()),
+        )
+    }
+}
+
+/// Whether completions for a shell are installed in a known location or
+/// loaded from the shell's rc file.
+fn completions_installed(shell: &str) -> bool {
+    let Some(home) = tram_core::home_dir() else {
+        return false;
+    };
+
+    // Wherever `tram completions <shell> --install` would put them
+    let installed = shell
+        .parse::<Shell>()
+        .ok()
+        .and_then(completion_install_path);
+
+    let (files, rc_files): (Vec<PathBuf>, Vec<PathBuf>) = match shell {
+        "bash" => (
+            vec![
+                home.join(".bash_completion.d/tram"),
+                home.join(".local/share/bash-completion/completions/tram"),
+                PathBuf::from("/usr/share/bash-completion/completions/tram"),
+                PathBuf::from("/etc/bash_completion.d/tram"),
+            ],
+            vec![home.join(".bashrc"), home.join(".bash_profile")],
+        ),
+        "zsh" => (
+            vec![
+                home.join(".zsh/completions/_tram"),
+                PathBuf::from("/usr/share/zsh/site-functions/_tram"),
+                PathBuf::from("/usr/local/share/zsh/site-functions/_tram"),
+            ],
+            vec![home.join(".zshrc")],
+        ),
+        "fish" => (
+            vec![home.join(".config/fish/completions/tram.fish")],
+            vec![home.join(".config/fish/config.fish")],
+        ),
+        "powershell" => (Vec::new(), powershell_profile_path().into_iter().collect()),
+        _ => (Vec::new(), Vec::new()),
+    };
+
+    files.iter().chain(&installed).any(|path| path.is_file())
+        || rc_files.iter().any(|path| {
+            std::fs::read_to_string(path).is_ok_and(|rc| rc.contains("tram completions"))
+        })
+}
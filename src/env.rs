@@ -0,0 +1,355 @@
+//! The resolved runtime environment for `tram env`.
+//!
+//! Shows each top-level setting's effective value and where it came from
+//! (a flag, an env var, the active profile, the config file, or the default), the environment
+//! variables tram reads, and the directories it uses, for working out why
+//! the CLI is behaving the way it is. `tram config env-vars` lists every
+//! variable tram knows about, set or not, with what it does.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tram_config::{SettingSource, config_env_vars, setting_env_vars};
+use tram_core::{EnvVar, REDACTED, TramError, is_sensitive_key, register_env_var, t};
+use tram_output::{CommandOutput, Render};
+use tram_ui::Table;
+
+use crate::session::TramSession;
+
+/// Register the variables read by the tram binary itself and by the config.
+/// Call once at startup.
+pub fn register() {
+    for var in config_env_vars() {
+        register_env_var(var);
+    }
+
+    let vars = [
+        EnvVar::new("TRAM_PROFILE", "Config profile to apply, like --profile"),
+        EnvVar::new(
+            "TRAM_TIMEOUT",
+            "Stop commands running longer than this many seconds, like --timeout",
+        ),
+        EnvVar::new(
+            crate::daemon::SOCKET_ENV,
+            "Socket the daemon listens on and commands connect to",
+        ),
+        EnvVar::new(
+            crate::daemon::NO_DAEMON_ENV,
+            "Any value runs every command locally instead of in the daemon",
+        ),
+        EnvVar::new(
+            tram_keyring::BACKEND_ENV,
+            "Credential backend to use: keychain or file",
+        ),
+        EnvVar::new(
+            tram_keyring::PASSPHRASE_ENV,
+            "Passphrase for the encrypted credentials file",
+        ),
+        EnvVar::new(
+            "TRAM_POWERSHELL_PROFILE",
+            "PowerShell profile that completions are registered in",
+        ),
+        EnvVar::new(
+            "HTTPS_PROXY",
+            "Proxy for HTTPS requests when http.proxy is unset",
+        ),
+        EnvVar::new(
+            "HTTP_PROXY",
+            "Proxy for HTTP requests when http.proxy is unset",
+        ),
+        EnvVar::new(
+            "ALL_PROXY",
+            "Proxy for all requests when http.proxy is unset",
+        ),
+        EnvVar::new("NO_PROXY", "Hosts that bypass the proxy"),
+        EnvVar::new(
+            "SHELL",
+            "Shell that completions and hooks are generated for",
+        ),
+    ];
+    for var in vars {
+        register_env_var(var);
+    }
+}
+
+/// A setting's effective value and where it came from.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingInfo {
+    pub key: String,
+    pub value: serde_json::Value,
+    pub source: SettingSource,
+}
+
+/// Directories and files tram uses.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvPaths {
+    pub config_file: Option<PathBuf>,
+    pub config_dir: Option<PathBuf>,
+    pub data_dir: Option<PathBuf>,
+    pub cache_dir: Option<PathBuf>,
+    pub workspace_root: Option<PathBuf>,
+    pub current_dir: PathBuf,
+    pub executable: Option<PathBuf>,
+}
+
+impl EnvPaths {
+    fn rows(&self) -> Vec<(String, String)> {
+        let display = |path: &Option<PathBuf>| {
+            path.as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|| t!("env-not-set"))
+        };
+
+        vec![
+            (t!("env-config-file"), display(&self.config_file)),
+            (t!("env-config-dir"), display(&self.config_dir)),
+            (t!("env-data-dir"), display(&self.data_dir)),
+            (t!("env-cache-dir"), display(&self.cache_dir)),
+            (t!("env-workspace-root"), display(&self.workspace_root)),
+            (
+                t!("env-current-dir"),
+                self.current_dir.display().to_string(),
+            ),
+            (t!("env-executable"), display(&self.executable)),
+        ]
+    }
+}
+
+/// Result of `tram env`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvReport {
+    pub settings: Vec<SettingInfo>,
+    /// Relevant variables that are set, with secrets redacted
+    pub variables: BTreeMap<String, String>,
+    pub paths: EnvPaths,
+}
+
+impl Render for EnvReport {
+    fn render_plain(&self) -> String {
+        let mut lines = vec![t!("env-settings")];
+        for setting in &self.settings {
+            lines.push(format!(
+                "  {} = {} ({})",
+                setting.key,
+                value_display(&setting.value),
+                setting.source
+            ));
+        }
+
+        lines.push(String::new());
+        lines.push(t!("env-variables"));
+        if self.variables.is_empty() {
+            lines.push(format!("  {}", t!("env-not-set")));
+        }
+        for (name, value) in &self.variables {
+            lines.push(format!("  {}={}", name, value));
+        }
+
+        lines.push(String::new());
+        lines.push(t!("env-paths"));
+        for (name, value) in self.paths.rows() {
+            lines.push(format!("  {}: {}", name, value));
+        }
+
+        lines.join("\n")
+    }
+
+    fn render_table(&self) -> Option<Table> {
+        let mut table = Table::new([t!("env-name"), t!("env-value"), t!("env-source")]);
+
+        for setting in &self.settings {
+            table.add_row([
+                setting.key.clone(),
+                value_display(&setting.value),
+                setting.source.to_string(),
+            ]);
+        }
+
+        for (name, value) in &self.variables {
+            table.add_row([name.clone(), value.clone(), t!("env-source-variable")]);
+        }
+
+        for (name, value) in self.paths.rows() {
+            table.add_row([name, value, String::new()]);
+        }
+
+        Some(table)
+    }
+}
+
+fn value_display(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => t!("env-not-set"),
+        serde_json::Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Collect the resolved environment for the session.
+pub fn report(session: &TramSession) -> tram_core::AppResult<CommandOutput> {
+    let sources = session
+        .config
+        .setting_sources(&session.config_files, session.profile())
+        .map_err(|e| TramError::InvalidConfig {
+            message: format!("Failed to read config sources: {}", e),
+        })?;
+
+    let values = serde_json::to_value(&session.config).map_err(|e| TramError::Output {
+        message: format!("Failed to serialize config: {}", e),
+    })?;
+
+    let settings = setting_env_vars()
+        .into_iter()
+        .map(|(key, _)| SettingInfo {
+            value: values.get(&key).cloned().unwrap_or_default(),
+            // Flags are applied last, over everything else
+            source: match session.flag_overrides.get(key.as_str()) {
+                Some(flag) => SettingSource::Flag(flag.clone()),
+                None => sources.get(&key).cloned().unwrap_or(SettingSource::Default),
+            },
+            key,
+        })
+        .collect();
+
+    let known = tram_core::env_vars();
+    let variables = std::env::vars()
+        .filter(|(name, _)| name.starts_with("TRAM_") || known.iter().any(|var| var.matches(name)))
+        .map(|(name, value)| {
+            if is_sensitive_key(&name.to_lowercase()) {
+                (name, REDACTED.to_string())
+            } else {
+                (name, value)
+            }
+        })
+        .collect();
+
+    let paths = EnvPaths {
+        config_file: session.config_file.clone(),
+        config_dir: tram_core::config_dir(),
+        data_dir: tram_core::data_dir(),
+        cache_dir: tram_core::cache_dir(),
+        workspace_root: session.workspace_root.clone(),
+        current_dir: tram_core::current_dir(),
+        executable: std::env::current_exe().ok(),
+    };
+
+    CommandOutput::new(&EnvReport {
+        settings,
+        variables,
+        paths,
+    })
+}
+
+/// A known environment variable and its current value.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvVarInfo {
+    #[serde(flatten)]
+    pub var: EnvVar,
+    /// Current value, with secrets redacted; for families like
+    /// `TRAM_SECRET_*`, the names that are set
+    pub value: Option<String>,
+    /// Flag given on this run that wins over the variable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overridden_by: Option<String>,
+}
+
+impl EnvVarInfo {
+    fn value_display(&self) -> String {
+        let value = self.value.clone().unwrap_or_else(|| t!("env-not-set"));
+
+        match &self.overridden_by {
+            Some(flag) => t!("env-var-overridden", value = value, flag = flag.as_str()),
+            None => value,
+        }
+    }
+
+    fn effect(&self) -> String {
+        match &self.var.setting {
+            Some(setting) if !self.var.description.contains(setting.as_str()) => {
+                format!("{} ({})", self.var.description, setting)
+            }
+            _ => self.var.description.clone(),
+        }
+    }
+}
+
+/// Result of `tram config env-vars`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvVarList {
+    pub variables: Vec<EnvVarInfo>,
+}
+
+impl Render for EnvVarList {
+    fn render_plain(&self) -> String {
+        self.variables
+            .iter()
+            .map(|info| {
+                format!(
+                    "{}={}\n  {}",
+                    info.var.name,
+                    info.value_display(),
+                    info.effect()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn render_table(&self) -> Option<Table> {
+        let mut table = Table::new([t!("env-name"), t!("env-value"), t!("env-effect")]);
+
+        for info in &self.variables {
+            table.add_row([info.var.name.clone(), info.value_display(), info.effect()]);
+        }
+
+        Some(table)
+    }
+}
+
+/// List every known environment variable with its current value.
+pub fn env_vars(session: &TramSession) -> tram_core::AppResult<CommandOutput> {
+    let set: BTreeMap<String, String> = std::env::vars().collect();
+    let redact = |name: &str, value: &str| {
+        if is_sensitive_key(&name.to_lowercase()) {
+            REDACTED.to_string()
+        } else {
+            value.to_string()
+        }
+    };
+
+    let variables = tram_core::env_vars()
+        .into_iter()
+        .map(|var| {
+            let value = if var.is_prefix() {
+                let names: Vec<&str> = set
+                    .keys()
+                    .filter(|name| var.matches(name))
+                    .map(String::as_str)
+                    .collect();
+                (!names.is_empty()).then(|| names.join(", "))
+            } else {
+                set.get(&var.name).map(|value| redact(&var.name, value))
+            };
+
+            let overridden_by = var
+                .setting
+                .as_ref()
+                .filter(|_| value.is_some())
+                .and_then(|setting| session.flag_overrides.get(setting.as_str()))
+                .cloned();
+
+            EnvVarInfo {
+                var,
+                value,
+                overridden_by,
+            }
+        })
+        .collect();
+
+    CommandOutput::new(&EnvVarList { variables })
+}
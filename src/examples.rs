@@ -2,10 +2,332 @@
 //!
 //! This module provides descriptions and guidance for the various example programs
 //! that demonstrate different CLI patterns and features available in Tram.
+//! With `--interactive` a short version of each demo runs in-process, using
+//! the same progress indicators and prompts as the built-in commands.
+
+use clap::ValueEnum;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tram_core::{Progress, TramError};
+use tram_output::{CommandOutput, Render};
+use tram_prompt::{Confirm, Input, MultiSelect, Password, Prompter, Select, validate};
+use tram_ui::Table;
 
 use crate::cli::ExampleType;
 use crate::session::TramSession;
 
+/// An example, as listed by `tram examples --list`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExampleInfo {
+    pub name: String,
+    pub description: String,
+    /// The standalone program in `examples/`
+    pub program: String,
+}
+
+/// Result of `tram examples --list`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExampleList {
+    pub examples: Vec<ExampleInfo>,
+}
+
+impl Render for ExampleList {
+    fn render_plain(&self) -> String {
+        let width = self
+            .examples
+            .iter()
+            .map(|example| example.name.len())
+            .max()
+            .unwrap_or_default();
+
+        self.examples
+            .iter()
+            .map(|example| format!("{:<width$}  {}", example.name, example.description))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn render_table(&self) -> Option<Table> {
+        let mut table = Table::new(["Name", "Description", "Program"]);
+
+        for example in &self.examples {
+            table.add_row([
+                example.name.clone(),
+                example.description.clone(),
+                example.program.clone(),
+            ]);
+        }
+
+        Some(table)
+    }
+}
+
+/// The example's name on the command line, e.g. `progress-indicators`.
+fn example_name(example: &ExampleType) -> String {
+    example
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+/// The example's one-line description, from its doc comment.
+fn example_description(example: &ExampleType) -> String {
+    example
+        .to_possible_value()
+        .and_then(|value| value.get_help().map(ToString::to_string))
+        .unwrap_or_default()
+}
+
+/// The standalone program in `examples/` for an example.
+fn example_program(example: &ExampleType) -> &'static str {
+    match example {
+        ExampleType::BasicCommand => "basic_command",
+        ExampleType::AsyncOperations => "async_operations",
+        ExampleType::ConfigUsage => "config_usage",
+        ExampleType::ProgressIndicators => "progress_indicators",
+        ExampleType::InteractivePrompts => "interactive_prompts",
+        ExampleType::FileOperations => "file_operations",
+    }
+}
+
+/// List every example with its description.
+pub fn list() -> tram_core::AppResult<CommandOutput> {
+    CommandOutput::new(&ExampleList {
+        examples: ExampleType::value_variants()
+            .iter()
+            .map(|example| ExampleInfo {
+                name: example_name(example),
+                description: example_description(example),
+                program: example_program(example).to_string(),
+            })
+            .collect(),
+    })
+}
+
+/// Run an example's demo in-process, asking which one when none is given.
+pub async fn run_demo(
+    example: Option<ExampleType>,
+    session: &TramSession,
+) -> tram_core::AppResult<()> {
+    let interactive = session.interactive;
+
+    let example = match example {
+        Some(example) => example,
+        None if interactive => {
+            let examples = ExampleType::value_variants();
+            let items: Vec<String> = examples
+                .iter()
+                .map(|example| {
+                    format!(
+                        "{} - {}",
+                        example_name(example),
+                        example_description(example)
+                    )
+                })
+                .collect();
+
+            let selected = session
+                .prompter
+                .select(&Select::new("Which example?", items).with_default(0))?;
+
+            examples[selected].clone()
+        }
+        None => {
+            return Err(TramError::MissingInput {
+                inputs: vec![
+                    "EXAMPLE (basic-command, async-operations, config-usage, progress-indicators, interactive-prompts, or file-operations)".to_string(),
+                ],
+            }
+            .into());
+        }
+    };
+
+    println!("=== {} ===", example_description(&example));
+
+    match example {
+        ExampleType::BasicCommand => demo_basic_command(session.prompter.as_ref())?,
+        ExampleType::AsyncOperations => demo_async_operations(session).await?,
+        ExampleType::ConfigUsage => demo_config_usage(session)?,
+        ExampleType::ProgressIndicators => demo_progress(session).await,
+        ExampleType::InteractivePrompts => {
+            if !interactive {
+                return Err(miette::miette!(
+                    "The interactive prompts example needs an interactive terminal, outside CI and without --yes"
+                ));
+            }
+            demo_prompts(session.prompter.as_ref())?
+        }
+        ExampleType::FileOperations => demo_file_operations()?,
+    }
+
+    println!();
+    println!(
+        "💡 The full version is examples/{}.rs: cargo run --example {}",
+        example_program(&example),
+        example_program(&example)
+    );
+
+    Ok(())
+}
+
+fn demo_basic_command(prompter: &dyn Prompter) -> tram_core::AppResult<()> {
+    // Without a terminal the default is used, so this never blocks
+    let name = prompter.input(&Input::new("Your name").with_default("World"))?;
+
+    println!("Hello, {}!", name);
+    println!("Arguments are parsed by clap, and the command runs inside a starbase session.");
+
+    Ok(())
+}
+
+async fn demo_async_operations(session: &TramSession) -> tram_core::AppResult<()> {
+    let jobs = [("download", 600), ("index", 300), ("compress", 450)];
+    let progress = session.progress(Some(jobs.len() as u64));
+    let started = Instant::now();
+
+    let mut set = tokio::task::JoinSet::new();
+    for (name, millis) in jobs {
+        set.spawn(async move {
+            tokio::time::sleep(Duration::from_millis(millis)).await;
+            (name, millis)
+        });
+    }
+
+    let mut finished = Vec::new();
+    while let Some(result) = set.join_next().await {
+        let (name, millis) = result.map_err(|e| miette::miette!("Job failed: {}", e))?;
+        progress.step(format!("Finished {} ({}ms)", name, millis));
+        finished.push(name);
+    }
+    progress.finish();
+
+    let sequential: u64 = jobs.iter().map(|(_, millis)| millis).sum();
+    println!("Finished in order: {}", finished.join(", "));
+    println!(
+        "Ran concurrently in {}ms instead of {}ms one after another",
+        started.elapsed().as_millis(),
+        sequential
+    );
+
+    Ok(())
+}
+
+fn demo_config_usage(session: &TramSession) -> tram_core::AppResult<()> {
+    let sources = session
+        .config
+        .setting_sources(&session.config_files, session.profile())
+        .map_err(|e| TramError::InvalidConfig {
+            message: format!("Failed to read config sources: {}", e),
+        })?;
+
+    println!("Each setting and where it came from:");
+    println!(
+        "  logLevel = {} ({})",
+        session.config.log_level, sources["logLevel"]
+    );
+    println!(
+        "  outputFormat = {} ({})",
+        session.config.output_format, sources["outputFormat"]
+    );
+    println!("  color = {} ({})", session.config.color, sources["color"]);
+    println!();
+    println!("Flags beat env vars, which beat the config file, which beats the defaults.");
+    println!("Try: TRAM_COLOR=false tram examples config-usage --interactive");
+
+    Ok(())
+}
+
+async fn demo_progress(session: &TramSession) {
+    let steps = [
+        "Resolving",
+        "Downloading",
+        "Unpacking",
+        "Verifying",
+        "Installing",
+    ];
+    let progress = session.progress(Some(steps.len() as u64));
+
+    for step in steps {
+        progress.step(step);
+        tokio::time::sleep(Duration::from_millis(400)).await;
+    }
+    progress.finish();
+
+    let plain = Progress::new(None);
+    plain.step("Without a terminal, each step is an info log line instead");
+
+    println!("✓ Done");
+}
+
+fn demo_prompts(prompter: &dyn Prompter) -> tram_core::AppResult<()> {
+    let name = prompter.input(&Input::new("Project name").with_validator(validate::non_empty()))?;
+
+    let languages = ["Rust", "TypeScript", "Python", "Go"];
+    let language = prompter.select(&Select::new("Language", languages).with_default(0))?;
+
+    let features = ["Logging", "Config file", "Shell completions", "Man pages"];
+    let chosen = prompter.multiselect(&MultiSelect::new("Features (space to toggle)", features))?;
+
+    let token =
+        prompter.password(&Password::new("API token (hidden, optional)").allow_empty(true))?;
+
+    let confirmed = prompter.confirm(&Confirm::new("Looks good?").with_default(true))?;
+
+    let chosen: Vec<&str> = chosen.into_iter().map(|index| features[index]).collect();
+    println!();
+    println!("Name: {}", name.trim());
+    println!("Language: {}", languages[language]);
+    println!("Features: {}", chosen.join(", "));
+    println!("Token: {}", if token.is_empty() { "none" } else { "set" });
+    println!("Confirmed: {}", confirmed);
+
+    Ok(())
+}
+
+fn demo_file_operations() -> tram_core::AppResult<()> {
+    let io_error = |e: std::io::Error| TramError::InvalidConfig {
+        message: format!("File example failed: {}", e),
+    };
+
+    let dir = std::env::temp_dir().join(format!("tram-example-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).map_err(io_error)?;
+    println!("Working in {}", dir.display());
+
+    for (name, content) in [
+        ("notes.txt", "Remember to write tests\n"),
+        ("config.toml", "logLevel = \"debug\"\n"),
+        ("data.json", "{\"items\": [1, 2, 3]}\n"),
+    ] {
+        std::fs::write(dir.join(name), content).map_err(io_error)?;
+        println!("  wrote {} ({} bytes)", name, content.len());
+    }
+
+    let mut entries: Vec<_> = std::fs::read_dir(&dir)
+        .map_err(io_error)?
+        .filter_map(Result::ok)
+        .collect();
+    entries.sort_by_key(|entry| entry.file_name());
+    println!("Directory listing:");
+    for entry in entries {
+        let size = entry.metadata().map(|meta| meta.len()).unwrap_or_default();
+        println!(
+            "  {:<12} {:>4} bytes",
+            entry.file_name().to_string_lossy(),
+            size
+        );
+    }
+
+    let notes = std::fs::read_to_string(dir.join("notes.txt")).map_err(io_error)?;
+    println!("notes.txt says: {}", notes.trim());
+
+    std::fs::remove_dir_all(&dir).map_err(io_error)?;
+    println!("Cleaned up {}", dir.display());
+
+    Ok(())
+}
+
 /// Run an example demonstrating CLI patterns
 pub async fn run_example(example: ExampleType, session: &TramSession) -> tram_core::AppResult<()> {
     match example {
@@ -0,0 +1,101 @@
+//! Shell integration for `tram hook`.
+//!
+//! `tram hook <shell>` prints a snippet to `eval` from the shell's rc file.
+//! The snippet runs `tram hook <shell> --env` whenever the directory changes,
+//! which prints the commands that export the current workspace root and
+//! project type (or unset them outside a workspace), so prompts and other
+//! tools can read them without running tram themselves.
+
+use clap::CommandFactory;
+use std::path::Path;
+
+use crate::cli::{Cli, HookShell};
+use crate::session::TramSession;
+
+/// Exported as the workspace root. Deliberately not `TRAM_WORKSPACE_ROOT`,
+/// which would pin the workspace for every later command.
+pub const WORKSPACE_VAR: &str = "TRAM_WORKSPACE";
+
+/// Exported as the detected project type, e.g. `rust`.
+pub const PROJECT_TYPE_VAR: &str = "TRAM_PROJECT_TYPE";
+
+/// Refreshes on every prompt, but only runs tram when the directory changed.
+const BASH_HOOK: &str = r#"_tram_hook() {
+  if [ "$PWD" != "${_TRAM_HOOK_PWD-}" ]; then
+    _TRAM_HOOK_PWD="$PWD"
+    eval "$({bin} hook bash --env 2>/dev/null)"
+  fi
+}
+case ";${PROMPT_COMMAND-};" in
+  *";_tram_hook;"*) ;;
+  *) PROMPT_COMMAND="_tram_hook${PROMPT_COMMAND:+;$PROMPT_COMMAND}" ;;
+esac
+"#;
+
+const ZSH_HOOK: &str = r#"_tram_hook() {
+  eval "$({bin} hook zsh --env 2>/dev/null)"
+}
+typeset -ag chpwd_functions
+if (( ! ${chpwd_functions[(I)_tram_hook]} )); then
+  chpwd_functions+=(_tram_hook)
+fi
+_tram_hook
+"#;
+
+const FISH_HOOK: &str = r#"function _tram_hook --on-variable PWD
+    {bin} hook fish --env 2>/dev/null | source
+end
+_tram_hook
+"#;
+
+/// The snippet that installs the hook, calling the CLI as `bin`.
+pub fn script(shell: HookShell, bin: &str) -> String {
+    let template = match shell {
+        HookShell::Bash => BASH_HOOK,
+        HookShell::Zsh => ZSH_HOOK,
+        HookShell::Fish => FISH_HOOK,
+    };
+
+    template.replace("{bin}", bin)
+}
+
+/// Commands that set the variables for a workspace, or unset them.
+pub fn env_commands(shell: HookShell, root: Option<&Path>, project_type: Option<&str>) -> String {
+    let root = root.map(|root| root.display().to_string());
+
+    [
+        (WORKSPACE_VAR, root.as_deref()),
+        (PROJECT_TYPE_VAR, project_type),
+    ]
+    .into_iter()
+    .map(|(name, value)| match (shell, value) {
+        (HookShell::Fish, Some(value)) => format!("set -gx {} {}", name, fish_quote(value)),
+        (HookShell::Fish, None) => format!("set -e {}", name),
+        (_, Some(value)) => format!("export {}={}", name, posix_quote(value)),
+        (_, None) => format!("unset {}", name),
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// Print the hook, or with `env` the variables for the session's directory.
+pub fn run(session: &TramSession, shell: HookShell, env: bool) {
+    if env {
+        let project_type = session.project_type.as_ref().map(|kind| kind.name());
+
+        println!(
+            "{}",
+            env_commands(shell, session.workspace_root.as_deref(), project_type)
+        );
+    } else {
+        print!("{}", script(shell, Cli::command().get_name()));
+    }
+}
+
+fn posix_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+fn fish_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', r"\\").replace('\'', r"\'"))
+}
@@ -3,90 +3,425 @@
 //! This demonstrates proper integration of clap and starbase without
 //! unnecessary abstractions.
 
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
 use miette::Result;
 use starbase::App;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
 use tracing::debug;
-use tram_config::{OutputFormat, TramConfig};
+use tram_config::{ConfigOverlay, OutputFormat, TramConfig};
+use tram_core::{
+    BuildInfo, ColorChoice, CrashReporter, ErrorReport, Event, EventTarget, ExitCode, Timings,
+    TramError, Verbosity, can_prompt, emit, exit_code_for, init_color, init_events,
+    spawn_signal_listener, suppress_deprecation_warnings, t,
+};
+use tram_output::Renderer;
+use tram_ui::{Theme, init_theme};
 
+mod auth;
+mod batch;
+mod cache;
 mod cli;
 mod commands;
+mod daemon;
+mod dashboard;
+mod deprecations;
 mod dev_tools;
+mod doctor;
+mod env;
 mod examples;
+mod hook;
+mod metrics;
+mod migrations;
+mod plugins;
+mod prompt_segment;
+mod run;
+mod schedule;
 mod session;
+mod shell;
+mod summary;
+mod telemetry;
 mod utils;
+mod watch;
+mod wizard;
 
-use cli::Cli;
+use cli::{Cli, ColorWhen, Commands};
 use commands::execute_command;
 use session::TramSession;
 
+/// Whether failures are printed as a JSON object rather than a miette report,
+/// set as soon as the output format is known
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
 #[tokio::main]
-async fn main() -> Result<()> {
-    // Parse CLI arguments
-    let cli = Cli::parse();
+async fn main() -> std::process::ExitCode {
+    match try_main().await {
+        Ok(code) => {
+            summary::finish(code, JSON_ERRORS.load(Ordering::Relaxed));
+            std::process::ExitCode::from(code)
+        }
+        Err(error) => {
+            // Keep the error's own code, so scripts can tell failures apart
+            let exit_code = exit_code_for(&error);
+            if JSON_ERRORS.load(Ordering::Relaxed) {
+                eprintln!("{}", ErrorReport::from_report(&error).to_json());
+            } else {
+                eprintln!("Error: {:?}", error);
+            }
+
+            // Put the failure in the log file too, so it's a complete record
+            debug!("Command failed: {:?}", error);
+            if let Some(path) = tram_core::log_file() {
+                eprintln!(
+                    "{}",
+                    t!("log-file-written", path = path.display().to_string())
+                );
+            }
+            summary::finish(exit_code.code(), JSON_ERRORS.load(Ordering::Relaxed));
+
+            // A signal or --timeout can stop a command mid-way on another
+            // thread; exit without waiting for it
+            if let Some(TramError::Signal { .. } | TramError::TimedOut { .. }) =
+                error.downcast_ref::<TramError>()
+            {
+                let _ = std::io::stdout().flush();
+                let _ = std::io::stderr().flush();
+                std::process::exit(exit_code.code().into());
+            }
+
+            exit_code.into()
+        }
+    }
+}
+
+/// Run the CLI, returning the process exit code.
+async fn try_main() -> Result<u8> {
+    let timings = Timings::new();
+
+    // Parse CLI arguments, keeping the matches around for the command name.
+    // Plugins are only looked up on PATH when help will list them.
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let mut command = Cli::command();
+    if plugins::wants_help(&args) {
+        command = plugins::with_plugin_help(command);
+    }
+    let matches = command.get_matches_from(&args);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let command_name = matches.subcommand_name().unwrap_or_default().to_string();
+
+    // Until the config is loaded, only the flag can ask for json errors
+    if let Some(format) = &cli.global.format {
+        JSON_ERRORS.store(format.eq_ignore_ascii_case("json"), Ordering::Relaxed);
+    }
+
+    // Collect the run summary from the start, so it has every warning
+    summary::start(&command_name, &cli.global);
+
+    // -q silences everything but errors, including deprecation warnings
+    let verbosity = Verbosity::from_flags(cli.global.quiet, cli.global.verbose);
+    if verbosity.is_quiet() {
+        suppress_deprecation_warnings(true);
+    }
+
+    // Warn once about deprecated commands and flags before doing any work
+    deprecations::register();
+    deprecations::check(&matches);
+    migrations::register();
+    env::register();
+
+    // Prompts run this on every render, so answer before loading anything
+    if let Commands::PromptSegment = &cli.command {
+        let json = cli
+            .global
+            .format
+            .as_deref()
+            .is_some_and(|format| format.eq_ignore_ascii_case("json"));
+        prompt_segment::run(json)?;
+        return Ok(ExitCode::Success.code());
+    }
+
+    // Let a running daemon answer read-only commands from its warm session
+    if let Some(code) = daemon::delegate(&cli, &args).await {
+        return Ok(code);
+    }
 
     // Debug CLI arguments
     debug!("CLI log_level: {}", cli.global.log_level);
-    debug!("CLI format: {}", cli.global.format);
+    debug!("CLI format: {:?}", cli.global.format);
+    debug!("CLI color: {:?}", cli.global.color);
     debug!("CLI no_color: {}", cli.global.no_color);
 
-    // Load base configuration using the methods we wrote in tram-config
-    let mut config = if let Some(config_path) = &cli.global.config {
-        TramConfig::load_from_file(config_path)
-    } else {
-        TramConfig::load_from_common_paths()
-    }
-    .map_err(|e| miette::miette!("Configuration error: {}", e))?;
+    // Load the file given with --config, or merge every config layer
+    let (mut config, config_files) = timings
+        .measure("config load", || match &cli.global.config {
+            Some(config_path) => TramConfig::load_from_file(config_path)
+                .map(|config| (config, vec![tram_core::expand_path(config_path)])),
+            None => TramConfig::load_layered().map(|layered| {
+                let files = layered.files.into_iter().map(|file| file.path).collect();
+                (layered.config, files)
+            }),
+        })
+        .map_err(|e| TramError::InvalidConfig {
+            message: tram_config::describe_error(e.as_ref()),
+        })?;
+    deprecations::check_config(&config_files);
 
-    // Config loaded successfully
-
-    // Apply CLI overrides directly to the config struct (highest precedence)
+    // The selected profile, then CLI overrides (highest precedence), are
+    // overlaid on the files, and again whenever a watcher reloads them.
+    // Remember which flag set each one for `tram env`
+    let mut overlay = ConfigOverlay {
+        profile: cli.global.profile.clone(),
+        ..Default::default()
+    };
+    let flags = &mut overlay.flags;
+    let mut flag_overrides = BTreeMap::new();
     if cli.global.log_level != "info" {
+        flag_overrides.insert("logLevel", "--log-level".to_string());
         match cli.global.log_level.to_lowercase().as_str() {
-            "debug" => config.log_level = tram_config::LogLevel::Debug,
-            "info" => config.log_level = tram_config::LogLevel::Info,
-            "warn" => config.log_level = tram_config::LogLevel::Warn,
-            "error" => config.log_level = tram_config::LogLevel::Error,
+            "trace" => flags.log_level = Some(tram_config::LogLevel::Trace),
+            "debug" => flags.log_level = Some(tram_config::LogLevel::Debug),
+            "info" => flags.log_level = Some(tram_config::LogLevel::Info),
+            "warn" => flags.log_level = Some(tram_config::LogLevel::Warn),
+            "error" => flags.log_level = Some(tram_config::LogLevel::Error),
             _ => {
-                return Err(miette::miette!(
-                    "Invalid log level: {}",
-                    cli.global.log_level
-                ));
+                return Err(TramError::InvalidInput {
+                    input: "log level".to_string(),
+                    message: cli.global.log_level.clone(),
+                }
+                .into());
             }
         }
     }
 
-    if cli.global.format != "table" {
-        match cli.global.format.to_lowercase().as_str() {
-            "json" => config.output_format = OutputFormat::Json,
-            "yaml" => config.output_format = OutputFormat::Yaml,
-            "table" => config.output_format = OutputFormat::Table,
+    // -q / -v pick the log level unless --log-level was given explicitly
+    if cli.global.log_level == "info"
+        && let Some(level) = verbosity.log_level()
+    {
+        flags.log_level = Some(level.into());
+        let flag = if cli.global.quiet {
+            "--quiet"
+        } else {
+            "--verbose"
+        };
+        flag_overrides.insert("logLevel", flag.to_string());
+    }
+
+    // Only override the format when the flag was given, so `--format table`
+    // can win over a config file that asks for json
+    if let Some(format) = &cli.global.format {
+        flag_overrides.insert("outputFormat", "--format".to_string());
+        match format.to_lowercase().as_str() {
+            "json" => flags.output_format = Some(OutputFormat::Json),
+            "yaml" => flags.output_format = Some(OutputFormat::Yaml),
+            "table" => flags.output_format = Some(OutputFormat::Table),
+            "plain" => flags.output_format = Some(OutputFormat::Plain),
+            "csv" => flags.output_format = Some(OutputFormat::Csv),
+            "tsv" => flags.output_format = Some(OutputFormat::Tsv),
             _ => {
-                return Err(miette::miette!(
-                    "Invalid output format: {}",
-                    cli.global.format
-                ));
+                return Err(TramError::InvalidInput {
+                    input: "output format".to_string(),
+                    message: format.clone(),
+                }
+                .into());
             }
         }
     }
+    if let Some(path) = &cli.global.log_file {
+        flags.log_file = Some(tram_core::expand_path(path));
+        flag_overrides.insert("logFile", "--log-file".to_string());
+    }
 
     if cli.global.no_color {
-        config.color = false;
+        flags.color = Some(false);
+        flag_overrides.insert("color", "--no-color".to_string());
     }
 
+    if cli.global.offline {
+        flags.offline = Some(true);
+        flag_overrides.insert("offline", "--offline".to_string());
+    }
+
+    overlay
+        .apply(&mut config)
+        .map_err(|e| TramError::InvalidConfig {
+            message: e.to_string(),
+        })?;
+    JSON_ERRORS.store(
+        config.output_format == OutputFormat::Json,
+        Ordering::Relaxed,
+    );
+    tram_core::set_offline(config.offline);
+
+    // Decide on colors for stdout and stderr; --color beats the environment,
+    // which beats a config file that turns colors off
+    let color_choice = match cli.global.color {
+        Some(ColorWhen::Always) => ColorChoice::Always,
+        Some(ColorWhen::Never) => ColorChoice::Never,
+        Some(ColorWhen::Auto) => ColorChoice::Auto,
+        None if !config.color => ColorChoice::Never,
+        None => ColorChoice::Auto,
+    };
+    init_color(color_choice);
+    init_theme(Theme::from_config(&config.theme)?);
+
+    // Replace raw panic output with a crash report now that the config is known
+    CrashReporter::new("tram", env!("CARGO_PKG_VERSION"))
+        .with_issue_url(concat!(
+            env!("CARGO_PKG_REPOSITORY"),
+            "/issues/new?template=crash_report.md"
+        ))
+        .with_config(&config)
+        .with_build_info(BuildInfo {
+            commit: option_env!("TRAM_BUILD_COMMIT").map(String::from),
+            target: option_env!("TRAM_BUILD_TARGET").map(String::from),
+            profile: option_env!("TRAM_BUILD_PROFILE").map(String::from),
+        })
+        .install();
+
+    // Start the machine-readable event stream if requested
+    if let Some(fd) = cli.global.events_fd {
+        init_events(EventTarget::Fd(fd))?;
+    } else if cli.global.events.is_some() {
+        init_events(EventTarget::Stderr)?;
+    }
+
+    // Unknown subcommands run `tram-<name>` plugins with the effective
+    // config, without starting a session
+    if let Commands::External(plugin_args) = &cli.command {
+        let code = plugins::run(
+            Cli::command(),
+            plugin_args,
+            &config,
+            config_files.last().map(|f| f.as_path()),
+        )?;
+        // The low byte is what the shell sees
+        summary::finish(code as u8, JSON_ERRORS.load(Ordering::Relaxed));
+        std::process::exit(code);
+    }
+    summary::start_for_config(&command_name, &config, cli.global.dry_run);
+
     // Create application session with config
-    let mut session = TramSession::with_config(config)?;
+    let mut session = TramSession::with_config(config)?
+        .with_timings(timings.clone())
+        .with_verbosity(verbosity)
+        .with_dry_run(cli.global.dry_run)
+        .with_interactive(can_prompt(cli.global.yes))
+        .with_config_sources(config_files, flag_overrides)
+        .with_config_overlay(overlay)
+        .with_output_mode(commands::output_mode(&cli.command));
+    let show_timings = cli.global.timings || cli.global.timings_trace.is_some();
+    let timings_trace = cli.global.timings_trace.clone();
+
+    // Ctrl+C, SIGTERM, and SIGHUP stop the command and run the shutdown
+    // phase, unless the command handles them itself
+    let mut signals = if commands::handles_signals(&cli.command) {
+        None
+    } else {
+        Some(spawn_signal_listener()?)
+    };
+    let timeout = cli
+        .global
+        .timeout
+        .map(|secs| Duration::from_secs(secs.get()));
+    let started = Instant::now();
+    let stopped_command = command_name.clone();
 
     // Create starbase app and run it with our session
     let app = App::default();
 
-    app.run_with_session(&mut session, |session| async move {
+    let run = app.run_with_session(&mut session, |session| async move {
+        emit(Event::CommandStarted {
+            command: command_name.clone(),
+        });
+
         // Execute the command
-        execute_command(cli.command, &session).await?;
-        Ok(Some(0))
-    })
-    .await
-    .map_err(|e| miette::miette!("Application error: {}", e))?;
+        let started = Instant::now();
+        let result = {
+            let _timing = session.timings.start("command exec");
+            execute_command(cli.command, &session).await
+        };
+
+        let duration_ms = started.elapsed().as_millis() as u64;
+        session.record_usage(&command_name, result.is_ok(), duration_ms);
+        metrics::record_command(&command_name, result.is_ok(), started.elapsed());
+        emit(Event::CommandFinished {
+            command: command_name,
+            success: result.is_ok(),
+            duration_ms,
+        });
+
+        // Render the command's result in the requested format
+        if let Some(output) = result? {
+            Renderer::from_config(&session.config)
+                .with_verbosity(session.verbosity)
+                .print(&output)?;
+        }
+
+        Ok(Some(ExitCode::Success.code()))
+    });
+
+    let signal = async {
+        match signals.as_mut() {
+            Some(signals) => signals.recv().await,
+            None => std::future::pending().await,
+        }
+    };
+
+    let stopped_by = tokio::select! {
+        result = run => Ok(result),
+        Some(signal) = signal => Err(TramError::Signal { signal }),
+        Ok(()) = deadline(timeout) => Err(TramError::TimedOut {
+            seconds: timeout.map(|t| t.as_secs()).unwrap_or_default(),
+        }),
+    };
+
+    let result = match stopped_by {
+        Ok(result) => result,
+        Err(reason) => {
+            emit(Event::CommandFinished {
+                command: stopped_command,
+                success: false,
+                duration_ms: started.elapsed().as_millis() as u64,
+            });
+            // The command is abandoned, so stop the processes it started too
+            tram_process::kill_all();
+            session.stop(&reason).await?;
+
+            Err(reason.into())
+        }
+    };
+
+    // Report timings even when the command failed; slow failures need profiling too
+    if show_timings {
+        eprintln!();
+        eprint!("{}", timings.render_table());
+
+        if let Some(path) = timings_trace {
+            let path = tram_core::expand_path(path);
+            timings.write_chrome_trace(&path)?;
+            eprintln!("Chrome trace written to: {}", path.display());
+        }
+    }
+
+    result
+}
+
+/// Fires once `timeout` has passed, or never without one. The wait runs on
+/// its own thread so a command that blocks every runtime worker still times
+/// out.
+fn deadline(timeout: Option<Duration>) -> oneshot::Receiver<()> {
+    let (sender, receiver) = oneshot::channel();
+
+    if let Some(timeout) = timeout {
+        std::thread::spawn(move || {
+            std::thread::sleep(timeout);
+            let _ = sender.send(());
+        });
+    }
 
-    Ok(())
+    receiver
 }
@@ -0,0 +1,86 @@
+//! Metrics pushed to the collector in the `[telemetry]` config section.
+//!
+//! Commands record their duration as they finish and `tram watch` counts
+//! batches and task runs as they happen; cache hits and misses are added at
+//! shutdown, when everything is pushed in one request. Nothing is recorded
+//! for usage telemetry here, and an export that fails only logs a warning.
+
+use std::time::Duration;
+use tracing::{debug, warn};
+use tram_config::{MetricsExporter, TramConfig};
+use tram_core::{APP_NAME, SecretResolver, cache_lookups, is_secret_reference};
+use tram_http::HttpClient;
+use tram_keyring::{Keyring, KeyringSecretProvider};
+use tram_telemetry::{CACHE_HITS, CACHE_MISSES, COMMAND_DURATION, metrics};
+
+/// Longest an export may hold up the end of a command.
+const EXPORT_TIMEOUT_SECS: u64 = 5;
+
+/// Record how long a command took.
+pub fn record_command(command: &str, success: bool, duration: Duration) {
+    let outcome = if success { "success" } else { "failure" };
+
+    metrics().observe(
+        &COMMAND_DURATION,
+        &[("command", command), ("outcome", outcome)],
+        duration.as_secs_f64(),
+    );
+}
+
+/// Push everything recorded by this process, if an exporter is configured.
+pub async fn export(config: &TramConfig) {
+    if config.telemetry.exporter == MetricsExporter::None {
+        return;
+    }
+    if config.offline {
+        debug!("Offline mode is on, skipping the metrics export");
+        return;
+    }
+
+    let lookups = cache_lookups();
+    if lookups.hits > 0 {
+        metrics().increment(&CACHE_HITS, &[], lookups.hits);
+    }
+    if lookups.misses > 0 {
+        metrics().increment(&CACHE_MISSES, &[], lookups.misses);
+    }
+    if metrics().is_empty() {
+        return;
+    }
+
+    let config = config.clone();
+    let exported = tokio::task::spawn_blocking(move || push(&config)).await;
+
+    match exported {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => warn!("Failed to export metrics: {}", e),
+        Err(e) => warn!("Metrics export task failed: {}", e),
+    }
+}
+
+fn push(config: &TramConfig) -> tram_core::AppResult<()> {
+    // One quick attempt; a slow collector must not slow every command down
+    let mut http = config.http.clone();
+    http.timeout_secs = http.timeout_secs.min(EXPORT_TIMEOUT_SECS);
+    let client = HttpClient::from_config(&http)?.with_retries(0);
+
+    let mut secrets = SecretResolver::with_defaults();
+    if config
+        .telemetry
+        .headers
+        .values()
+        .any(|value| is_secret_reference(value))
+    {
+        secrets = secrets.with_provider(KeyringSecretProvider::new(
+            Keyring::open_default()?,
+            APP_NAME,
+        ));
+    }
+
+    if let Some(exporter) = tram_telemetry::from_config(&config.telemetry, client, &secrets)? {
+        exporter.export(&metrics().snapshot())?;
+        debug!("Exported metrics with {}", exporter.name());
+    }
+
+    Ok(())
+}
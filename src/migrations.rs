@@ -0,0 +1,60 @@
+//! Versioned migrations of the files tram keeps between runs.
+//!
+//! When a release changes the shape of a file in the data directory, such
+//! as `state.json`, register a migration to the next data version here.
+//! The session runs pending migrations at startup, before anything reads
+//! those files, backing up what they change to `backups/` in the data
+//! directory.
+
+use tracing::info;
+use tram_core::t;
+use tram_migrate::{Migrator, registered_migrations};
+
+use crate::session::TramSession;
+
+/// Register tram's migrations. Call once at startup, before running them.
+pub fn register() {
+    // No file formats have changed yet. When one does, add e.g.
+    // `register_migration(Migration::new(1, "Describe the change", |files| { ... }))`
+}
+
+/// Bring the data directory up to the latest version, if any migration is
+/// pending. With `--dry-run`, only says what would run.
+pub fn run(session: &TramSession) -> tram_core::AppResult<()> {
+    // Without migrations or a data directory there is nothing to bring up to
+    // date, so don't let either stop the command
+    if registered_migrations().is_empty() || tram_core::data_dir().is_none() {
+        return Ok(());
+    }
+    let migrator = Migrator::open_default()?;
+
+    let pending = migrator.pending()?;
+    let Some(last) = pending.last() else {
+        return Ok(());
+    };
+
+    if session.dry_run {
+        info!(
+            "Would migrate data from version {} to {}",
+            migrator.current_version()?,
+            last.version
+        );
+        return Ok(());
+    }
+
+    let report = migrator.run()?;
+    if !session.verbosity.is_quiet() {
+        let message = match &report.backup {
+            Some(backup) => t!(
+                "migrated-data-with-backup",
+                from = report.from,
+                to = report.to,
+                backup = backup.display().to_string()
+            ),
+            None => t!("migrated-data", from = report.from, to = report.to),
+        };
+        eprintln!("{}", message);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,428 @@
+//! Plugin subcommands and `tram plugins`.
+//!
+//! Unknown subcommands run a command from a WASM plugin when one provides
+//! it, and otherwise a `tram-<name>` executable from PATH; both are listed
+//! under "External commands" at the end of `--help`. WASM plugins are
+//! sandboxed: their commands may only read the workspace, and their
+//! templates are written by tram itself. Executables run with the
+//! environment, plus variables describing the workspace and the effective
+//! config (see [`plugin_env`]).
+
+use clap::Command;
+use clap::error::ErrorKind;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use tram_config::TramConfig;
+use tram_core::{
+    FileChange, TramError, did_you_mean, discover_plugins, find_plugin, run_plugin, t,
+};
+use tram_output::{CommandOutput, Render};
+use tram_plugin::{Capabilities, PluginRegistry, WasmPlugin};
+use tram_ui::Table;
+use tram_workspace::{ProjectType, WorkspaceDetector};
+
+use crate::cli::PluginAction;
+use crate::commands::dry_run_lines;
+use crate::hook::{PROJECT_TYPE_VAR, WORKSPACE_VAR};
+use crate::session::TramSession;
+
+const APP_NAME: &str = "tram";
+
+/// The path of the running tram executable, for plugins that call back.
+pub const BIN_VAR: &str = "TRAM";
+
+/// The running tram's version.
+pub const VERSION_VAR: &str = "TRAM_VERSION";
+
+/// The highest-precedence config file, when there is one.
+pub const CONFIG_FILE_VAR: &str = "TRAM_CONFIG_FILE";
+
+/// The effective config as JSON, after config layers, env vars, profile,
+/// and flags.
+pub const CONFIG_VAR: &str = "TRAM_CONFIG";
+
+/// Whether the arguments ask for top-level help, so plugin discovery only
+/// scans PATH when the list will be shown.
+pub fn wants_help(args: &[OsString]) -> bool {
+    args.get(1).is_some_and(|arg| arg == "help")
+        || args
+            .iter()
+            .skip(1)
+            .any(|arg| arg == "-h" || arg == "--help")
+}
+
+/// WASM plugins installed for the workspace at `root` and for the user.
+pub fn registry(root: Option<&Path>) -> PluginRegistry {
+    PluginRegistry::discover(PluginRegistry::plugin_dirs(root))
+}
+
+/// The workspace around the current directory, found without a session.
+fn workspace_root() -> Option<PathBuf> {
+    WorkspaceDetector::new().ok()?.detect_root().ok()
+}
+
+/// Add discovered plugins to the help output.
+pub fn with_plugin_help(command: Command) -> Command {
+    let wasm = registry(workspace_root().as_deref());
+    let mut plugins: Vec<(String, String)> = wasm
+        .plugins()
+        .iter()
+        .flat_map(|plugin| {
+            plugin.manifest().commands.iter().map(|command| {
+                let about = command
+                    .about
+                    .clone()
+                    .unwrap_or_else(|| plugin.path().display().to_string());
+                (command.name.clone(), about)
+            })
+        })
+        .collect();
+    plugins.extend(
+        discover_plugins(APP_NAME)
+            .into_iter()
+            .map(|plugin| (plugin.name, plugin.path.display().to_string())),
+    );
+    if plugins.is_empty() {
+        return command;
+    }
+
+    let width = plugins
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or_default();
+    let mut help = String::from("External commands:");
+
+    for (name, about) in plugins {
+        help.push_str(&format!("\n  {:<width$}  {}", name, about));
+    }
+
+    command.after_help(help)
+}
+
+/// Run the plugin for an unknown subcommand, returning its exit code.
+///
+/// `args` starts with the subcommand name, as captured by clap.
+pub fn run(
+    command: Command,
+    args: &[OsString],
+    config: &TramConfig,
+    config_file: Option<&Path>,
+) -> tram_core::AppResult<i32> {
+    dispatch(command, args, config, config_file).unwrap_or_else(|e| e.exit())
+}
+
+/// Run the plugin for an unknown subcommand, or return the error clap
+/// reports for any other unknown subcommand.
+pub fn dispatch(
+    command: Command,
+    args: &[OsString],
+    config: &TramConfig,
+    config_file: Option<&Path>,
+) -> Result<tram_core::AppResult<i32>, clap::Error> {
+    let (name, rest) = split_args(args);
+    let root = workspace_root();
+    let wasm = registry(root.as_deref());
+
+    if let Some(plugin) = wasm.find_command(&name) {
+        return Ok(run_wasm(plugin, &name, rest, root.as_deref()));
+    }
+
+    let path = find(command, &name, &wasm)?;
+    Ok(plugin_env(config, config_file, root.as_deref())
+        .and_then(|env| run_plugin(&path, rest, env)))
+}
+
+/// Variables set for `tram-<name>` executables: the tram executable and
+/// version, the workspace root and project type under the names `tram hook`
+/// exports, the config file, and the effective config as JSON.
+pub fn plugin_env(
+    config: &TramConfig,
+    config_file: Option<&Path>,
+    root: Option<&Path>,
+) -> tram_core::AppResult<Vec<(&'static str, OsString)>> {
+    let config_json = serde_json::to_string(config).map_err(|e| TramError::Output {
+        message: format!("Failed to serialize config for the plugin: {}", e),
+    })?;
+
+    let mut env = vec![
+        (VERSION_VAR, OsString::from(env!("CARGO_PKG_VERSION"))),
+        (CONFIG_VAR, OsString::from(config_json)),
+    ];
+    if let Ok(bin) = std::env::current_exe() {
+        env.push((BIN_VAR, bin.into_os_string()));
+    }
+    if let Some(file) = config_file {
+        env.push((CONFIG_FILE_VAR, file.as_os_str().to_owned()));
+    }
+    if let Some(root) = root {
+        env.push((WORKSPACE_VAR, root.as_os_str().to_owned()));
+        if let Some(kind) = ProjectType::detect(root) {
+            env.push((PROJECT_TYPE_VAR, OsString::from(kind.name())));
+        }
+    }
+
+    Ok(env)
+}
+
+/// Run a WASM plugin command in the current directory, letting it read the
+/// workspace (or just the current directory outside one).
+fn run_wasm(
+    plugin: &WasmPlugin,
+    name: &str,
+    args: &[OsString],
+    root: Option<&Path>,
+) -> tram_core::AppResult<i32> {
+    let cwd = tram_core::current_dir();
+    let args: Vec<String> = args
+        .iter()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    let capabilities = Capabilities::new(&cwd).with_read(root.unwrap_or(&cwd));
+
+    let outcome = plugin.run_command(name, &args, capabilities)?;
+    print!("{}", outcome.stdout);
+    eprint!("{}", outcome.stderr);
+
+    Ok(outcome.exit_code)
+}
+
+/// Split captured plugin arguments into the subcommand name and the rest.
+pub fn split_args(args: &[OsString]) -> (String, &[OsString]) {
+    let name = args
+        .first()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    (name, args.get(1..).unwrap_or_default())
+}
+
+/// Find the executable plugin for a subcommand, or the error clap reports
+/// for any other unknown subcommand, suggesting the closest built-in or
+/// plugin.
+fn find(mut command: Command, name: &str, wasm: &PluginRegistry) -> Result<PathBuf, clap::Error> {
+    find_plugin(APP_NAME, name).ok_or_else(|| {
+        let mut message = format!("unrecognized subcommand '{}'", name);
+        if let Some(suggestion) = suggestion(&command, name, wasm) {
+            message.push_str("\n\n  ");
+            message.push_str(&suggestion);
+        }
+
+        command.error(ErrorKind::InvalidSubcommand, message)
+    })
+}
+
+/// Whether a WASM plugin or `tram-<name>` executable provides `name`.
+pub fn is_plugin(name: &str, wasm: &PluginRegistry) -> bool {
+    wasm.find_command(name).is_some() || find_plugin(APP_NAME, name).is_some()
+}
+
+/// A did-you-mean tip naming the built-in or plugin closest to an unknown
+/// subcommand.
+pub fn suggestion(command: &Command, name: &str, wasm: &PluginRegistry) -> Option<String> {
+    let plugins: Vec<String> = discover_plugins(APP_NAME)
+        .into_iter()
+        .map(|plugin| plugin.name)
+        .chain(wasm.plugins().iter().flat_map(|plugin| {
+            plugin
+                .manifest()
+                .commands
+                .iter()
+                .map(|command| command.name.clone())
+        }))
+        .collect();
+    let builtins = command
+        .get_subcommands()
+        .filter(|sub| !sub.is_hide_set())
+        .map(|sub| sub.get_name());
+
+    did_you_mean(name, builtins.chain(plugins.iter().map(String::as_str)))
+        .map(|suggestion| t!("did-you-mean", suggestion = suggestion))
+}
+
+/// The project type a WASM plugin detector recognizes at `root`.
+pub fn detect_project_type(root: &Path) -> Option<String> {
+    registry(Some(root)).detect(root).map(|(kind, _)| kind)
+}
+
+/// Run a `tram plugins` action.
+pub fn execute(session: &TramSession, action: PluginAction) -> tram_core::AppResult<CommandOutput> {
+    let wasm = registry(session.workspace_root.as_deref());
+
+    match action {
+        PluginAction::List => CommandOutput::new(&PluginList {
+            plugins: wasm.plugins().iter().map(PluginInfo::from).collect(),
+            dirs: PluginRegistry::plugin_dirs(session.workspace_root.as_deref()),
+        }),
+        PluginAction::Generate {
+            template,
+            name,
+            target_dir,
+            variables,
+        } => {
+            let plugin = wasm
+                .find_template(&template)
+                .ok_or_else(|| TramError::Plugin {
+                    message: format!("No plugin provides a template named '{}'", template),
+                })?;
+            let target_dir = target_dir
+                .map(tram_core::expand_path)
+                .unwrap_or_else(tram_core::current_dir);
+            let variables: BTreeMap<String, String> = variables.into_iter().collect();
+
+            let changes =
+                plugin.generate(&template, &name, &variables, &target_dir, session.dry_run)?;
+
+            CommandOutput::new(&PluginGenerateResult {
+                plugin: plugin.name().to_string(),
+                template,
+                name,
+                dry_run: session.dry_run,
+                changes,
+            })
+        }
+    }
+}
+
+/// Result of `tram plugins list`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginList {
+    pub plugins: Vec<PluginInfo>,
+    /// Where plugins are looked for, in priority order
+    pub dirs: Vec<PathBuf>,
+}
+
+/// One installed WASM plugin.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginInfo {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub path: PathBuf,
+    pub commands: Vec<String>,
+    pub templates: Vec<String>,
+    pub detector: bool,
+}
+
+impl From<&WasmPlugin> for PluginInfo {
+    fn from(plugin: &WasmPlugin) -> Self {
+        let manifest = plugin.manifest();
+
+        Self {
+            name: manifest.name.clone(),
+            version: manifest.version.clone(),
+            path: plugin.path().to_path_buf(),
+            commands: manifest.commands.iter().map(|c| c.name.clone()).collect(),
+            templates: manifest.templates.iter().map(|t| t.name.clone()).collect(),
+            detector: manifest.detector,
+        }
+    }
+}
+
+impl PluginInfo {
+    /// What the plugin adds, e.g. `commands: lint, fmt; detector`.
+    fn provides(&self) -> String {
+        let mut provides = Vec::new();
+        if !self.commands.is_empty() {
+            provides.push(t!("plugins-commands", names = self.commands.join(", ")));
+        }
+        if !self.templates.is_empty() {
+            provides.push(t!("plugins-templates", names = self.templates.join(", ")));
+        }
+        if self.detector {
+            provides.push(t!("plugins-detector"));
+        }
+
+        provides.join("; ")
+    }
+}
+
+impl Render for PluginList {
+    fn render_plain(&self) -> String {
+        if self.plugins.is_empty() {
+            let dirs: Vec<String> = self
+                .dirs
+                .iter()
+                .map(|dir| dir.display().to_string())
+                .collect();
+            return t!("plugins-none", dirs = dirs.join(", "));
+        }
+
+        self.plugins
+            .iter()
+            .map(|plugin| {
+                let name = match &plugin.version {
+                    Some(version) => format!("{} {}", plugin.name, version),
+                    None => plugin.name.clone(),
+                };
+                format!(
+                    "{} ({})\n  {}",
+                    name,
+                    plugin.path.display(),
+                    plugin.provides()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn render_table(&self) -> Option<Table> {
+        if self.plugins.is_empty() {
+            return None;
+        }
+
+        let mut table = Table::new([
+            t!("plugins-name"),
+            t!("plugins-version"),
+            t!("plugins-provides"),
+            t!("plugins-path"),
+        ]);
+        for plugin in &self.plugins {
+            table.add_row([
+                plugin.name.clone(),
+                plugin.version.clone().unwrap_or_default(),
+                plugin.provides(),
+                plugin.path.display().to_string(),
+            ]);
+        }
+
+        Some(table)
+    }
+}
+
+/// Result of `tram plugins generate`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginGenerateResult {
+    pub plugin: String,
+    pub template: String,
+    pub name: String,
+    pub dry_run: bool,
+    pub changes: Vec<FileChange>,
+}
+
+impl Render for PluginGenerateResult {
+    fn render_plain(&self) -> String {
+        if self.dry_run {
+            return dry_run_lines(&self.changes).join("\n");
+        }
+
+        std::iter::once(t!(
+            "plugins-template-written",
+            template = self.template.as_str(),
+            plugin = self.plugin.as_str(),
+            name = self.name.as_str()
+        ))
+        .chain(
+            self.changes
+                .iter()
+                .map(|change| format!("  {}", change.path.display())),
+        )
+        .collect::<Vec<_>>()
+        .join("\n")
+    }
+}
@@ -0,0 +1,141 @@
+//! `tram prompt-segment`: a compact workspace status for shell prompts.
+//!
+//! Prompt engines such as starship and powerlevel10k run it on every prompt,
+//! so `main.rs` answers it before loading the config or starting a session,
+//! and each workspace's segment is cached for [`CACHE_TTL`]. The cache key
+//! includes the modification times of git's index and `HEAD`, so staging,
+//! committing, or switching branches shows up on the next prompt; edits to
+//! tracked files show up once the entry expires.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+use tram_core::{Cache, CacheKey};
+use tram_workspace::{ProjectType, WorkspaceDetector, git_dir, is_dirty};
+
+/// How long a segment is reused before git is asked again.
+pub const CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// Marker appended to the workspace name when the work tree has changes.
+const DIRTY_MARKER: &str = "*";
+
+/// What the prompt shows about the current workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptSegment {
+    /// The workspace root's directory name
+    pub name: String,
+    pub root: PathBuf,
+    /// e.g. `rust`
+    pub project_type: String,
+    pub icon: String,
+    /// Whether git reports changes; `None` outside a git repository
+    pub dirty: Option<bool>,
+}
+
+impl PromptSegment {
+    /// The segment for the workspace at `root`, asking git for its status.
+    fn detect(root: &Path) -> Self {
+        let project_type = ProjectType::detect(root).unwrap_or(ProjectType::Generic);
+        let name = root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| root.display().to_string());
+
+        Self {
+            name,
+            root: root.to_path_buf(),
+            project_type: project_type.name().to_string(),
+            icon: icon(&project_type).to_string(),
+            dirty: git_dir(root).and_then(|_| is_dirty(root)),
+        }
+    }
+
+    /// The compact form, e.g. `🦀 tram*`.
+    pub fn render(&self) -> String {
+        let marker = if self.dirty == Some(true) {
+            DIRTY_MARKER
+        } else {
+            ""
+        };
+        format!("{} {}{}", self.icon, self.name, marker)
+    }
+}
+
+/// Print the segment for the current directory, compact or as JSON.
+/// Outside a workspace the compact form is empty and the JSON is `null`.
+pub fn run(json: bool) -> tram_core::AppResult<()> {
+    let segment = WorkspaceDetector::new()?
+        .detect_root()
+        .ok()
+        .map(|root| cached(&root));
+
+    if json {
+        let json = serde_json::to_string(&segment).map_err(|e| tram_core::TramError::Output {
+            message: format!("Failed to serialize the prompt segment: {}", e),
+        })?;
+        println!("{}", json);
+    } else if let Some(segment) = segment {
+        println!("{}", segment.render());
+    }
+
+    Ok(())
+}
+
+/// The segment for `root` from the cache, or detected and cached. A cache
+/// that can't be read or written is skipped; the prompt matters more.
+fn cached(root: &Path) -> PromptSegment {
+    let cache = Cache::open_default().ok();
+    let key = cache_key(root);
+
+    if let Some(segment) = cache
+        .as_ref()
+        .and_then(|cache| cache.get(&key).ok().flatten())
+        .and_then(|data| serde_json::from_slice(&data).ok())
+    {
+        return segment;
+    }
+
+    let segment = PromptSegment::detect(root);
+    if let (Some(cache), Ok(data)) = (&cache, serde_json::to_vec(&segment)) {
+        let _ = cache.put_with_ttl(&key, &data, Some(CACHE_TTL));
+    }
+    segment
+}
+
+/// Changes whenever git's index or `HEAD` does.
+fn cache_key(root: &Path) -> CacheKey {
+    let modified = |path: PathBuf| {
+        std::fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|since| since.as_nanos().to_string())
+            .unwrap_or_default()
+    };
+    let (index, head) = match git_dir(root) {
+        Some(git_dir) => (
+            modified(git_dir.join("index")),
+            modified(git_dir.join("HEAD")),
+        ),
+        None => Default::default(),
+    };
+
+    CacheKey::from_parts([
+        "prompt-segment".to_string(),
+        root.display().to_string(),
+        index,
+        head,
+    ])
+}
+
+fn icon(project_type: &ProjectType) -> &'static str {
+    match project_type {
+        ProjectType::Rust => "🦀",
+        ProjectType::NodeJs => "⬢",
+        ProjectType::Python => "🐍",
+        ProjectType::Go => "🐹",
+        ProjectType::Java => "☕",
+        ProjectType::Generic => "📁",
+    }
+}
@@ -0,0 +1,379 @@
+//! Config-defined tasks for `tram run`.
+//!
+//! Tasks come from the `tasks` section of the config and run from the
+//! workspace root (or the current directory outside a workspace), each after
+//! the tasks it depends on. Independent tasks run in parallel up to `--jobs`,
+//! and every run ends with a summary of each task's status and duration.
+
+use serde::Serialize;
+use std::num::NonZeroUsize;
+use std::time::{Duration, Instant};
+use tram_config::{OutputFormat, TramConfig};
+use tram_core::{TramError, t};
+use tram_output::{CommandOutput, Render, Renderer};
+use tram_process::{Task, TaskGraph, TaskOutcome, TaskStatus};
+use tram_ui::{Align, Table, Tree, TreeNode};
+
+use crate::session::TramSession;
+
+/// Build the task graph from the config.
+fn graph(session: &TramSession) -> TaskGraph {
+    let root = session
+        .workspace_root
+        .clone()
+        .unwrap_or_else(tram_core::current_dir);
+
+    TaskGraph::new(root, tasks(&session.config))
+}
+
+fn tasks(config: &TramConfig) -> Vec<Task> {
+    config
+        .tasks
+        .iter()
+        .map(|(name, task)| Task {
+            name: name.clone(),
+            command: task.command.clone(),
+            cwd: task.cwd.clone(),
+            env: task.env.clone(),
+            depends_on: task.depends_on.clone(),
+        })
+        .collect()
+}
+
+/// A task as shown by `tram run --list`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskInfo {
+    pub name: String,
+    pub command: String,
+    pub depends_on: Vec<String>,
+}
+
+/// Result of `tram run --list`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskList {
+    pub tasks: Vec<TaskInfo>,
+}
+
+impl Render for TaskList {
+    fn render_plain(&self) -> String {
+        if self.tasks.is_empty() {
+            return t!("run-no-tasks");
+        }
+
+        self.tasks
+            .iter()
+            .map(|task| format!("{}: {}", task.name, task.command))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn render_table(&self) -> Option<Table> {
+        if self.tasks.is_empty() {
+            return None;
+        }
+
+        let mut table = Table::new([t!("run-task"), t!("run-command"), t!("run-depends-on")]);
+        for task in &self.tasks {
+            table.add_row([
+                task.name.clone(),
+                task.command.clone(),
+                task.depends_on.join(", "),
+            ]);
+        }
+
+        Some(table)
+    }
+}
+
+/// List the configured tasks.
+pub fn list(session: &TramSession) -> tram_core::AppResult<CommandOutput> {
+    let tasks = graph(session)
+        .tasks()
+        .map(|task| TaskInfo {
+            name: task.name.clone(),
+            command: task.command.clone(),
+            depends_on: task.depends_on.clone(),
+        })
+        .collect();
+
+    CommandOutput::new(&TaskList { tasks })
+}
+
+/// A task and its dependencies, as shown by `tram run --tree`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskNode {
+    pub name: String,
+    pub command: String,
+    pub depends_on: Vec<TaskNode>,
+}
+
+impl TaskNode {
+    fn tree_node(&self) -> TreeNode {
+        TreeNode::new(format!("{}: {}", self.name, self.command))
+            .with_children(self.depends_on.iter().map(TaskNode::tree_node))
+    }
+}
+
+/// Result of `tram run --tree`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskTree {
+    pub tasks: Vec<TaskNode>,
+}
+
+impl Render for TaskTree {
+    fn render_plain(&self) -> String {
+        if self.tasks.is_empty() {
+            return t!("run-no-tasks");
+        }
+
+        Tree::from_roots(self.tasks.iter().map(TaskNode::tree_node)).render()
+    }
+}
+
+/// Show `task`'s dependency tree, or the trees of every task nothing else
+/// depends on.
+pub fn tree(session: &TramSession, task: Option<&str>) -> tram_core::AppResult<CommandOutput> {
+    let graph = graph(session);
+
+    let roots: Vec<&str> = match task {
+        Some(task) => vec![task],
+        None => graph
+            .tasks()
+            .filter(|task| {
+                !graph
+                    .tasks()
+                    .any(|other| other.depends_on.contains(&task.name))
+            })
+            .map(|task| task.name.as_str())
+            .collect(),
+    };
+
+    // Surface unknown tasks and cycles the way `tram run` would
+    for task in graph.tasks().map(|task| task.name.as_str()).chain(task) {
+        graph.execution_order(task)?;
+    }
+
+    let tasks = roots
+        .into_iter()
+        .filter_map(|name| task_node(&graph, name))
+        .collect();
+
+    CommandOutput::new(&TaskTree { tasks })
+}
+
+fn task_node(graph: &TaskGraph, name: &str) -> Option<TaskNode> {
+    let task = graph.get(name)?;
+
+    Some(TaskNode {
+        name: task.name.clone(),
+        command: task.command.clone(),
+        depends_on: task
+            .depends_on
+            .iter()
+            .filter_map(|dependency| task_node(graph, dependency))
+            .collect(),
+    })
+}
+
+/// A task's part in a run.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskSummary {
+    pub name: String,
+    pub status: TaskStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    pub duration_ms: u128,
+}
+
+impl From<TaskOutcome> for TaskSummary {
+    fn from(outcome: TaskOutcome) -> Self {
+        Self {
+            name: outcome.name,
+            status: outcome.status,
+            exit_code: outcome.exit_code,
+            duration_ms: outcome.duration.as_millis(),
+        }
+    }
+}
+
+impl TaskSummary {
+    fn status_label(&self) -> String {
+        match (self.status, self.exit_code) {
+            (TaskStatus::Passed, _) => t!("run-passed"),
+            (TaskStatus::Failed, Some(code)) => t!("run-failed-code", code = code),
+            (TaskStatus::Failed, None) => t!("run-failed"),
+            (TaskStatus::Skipped, _) => t!("run-skipped"),
+        }
+    }
+
+    fn duration(&self) -> String {
+        match self.status {
+            TaskStatus::Skipped => String::new(),
+            _ => format_duration(self.duration_ms),
+        }
+    }
+}
+
+/// Result of `tram run <task>`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunResult {
+    pub task: String,
+    pub success: bool,
+    /// Every task involved, in dependency order
+    pub tasks: Vec<TaskSummary>,
+    pub duration_ms: u128,
+}
+
+impl RunResult {
+    fn count(&self, status: TaskStatus) -> usize {
+        self.tasks
+            .iter()
+            .filter(|task| task.status == status)
+            .count()
+    }
+
+    fn summary(&self) -> String {
+        t!(
+            "run-summary",
+            passed = self.count(TaskStatus::Passed),
+            failed = self.count(TaskStatus::Failed),
+            skipped = self.count(TaskStatus::Skipped),
+            duration = format_duration(self.duration_ms)
+        )
+    }
+}
+
+impl Render for RunResult {
+    fn render_plain(&self) -> String {
+        let mut lines: Vec<String> = self
+            .tasks
+            .iter()
+            .map(|task| {
+                let marker = match task.status {
+                    TaskStatus::Passed => "✓",
+                    TaskStatus::Failed => "✗",
+                    TaskStatus::Skipped => "-",
+                };
+                format!(
+                    "{} {}: {} {}",
+                    marker,
+                    task.name,
+                    task.status_label(),
+                    task.duration()
+                )
+                .trim_end()
+                .to_string()
+            })
+            .collect();
+
+        lines.push(String::new());
+        lines.push(self.summary());
+        lines.join("\n")
+    }
+
+    fn render_table(&self) -> Option<Table> {
+        let mut table = Table::new([t!("run-task"), t!("run-status"), t!("run-duration")])
+            .with_align(2, Align::Right)
+            .with_title(self.summary());
+
+        let theme = tram_ui::theme();
+        for task in &self.tasks {
+            let row = [task.name.clone(), task.status_label(), task.duration()];
+            match task.status {
+                TaskStatus::Passed => table.add_row(row),
+                TaskStatus::Failed => table.add_styled_row(row, theme.error),
+                TaskStatus::Skipped => table.add_styled_row(row, theme.muted),
+            }
+        }
+
+        Some(table)
+    }
+}
+
+fn format_duration(ms: u128) -> String {
+    format!("{:.1?}", Duration::from_millis(ms as u64))
+}
+
+/// Run a task after its dependencies, up to `jobs` tasks at once.
+///
+/// The summary is printed before a failed run returns its error, so the
+/// statuses of the other tasks aren't lost.
+pub fn run(
+    session: &TramSession,
+    name: &str,
+    jobs: Option<NonZeroUsize>,
+    keep_going: bool,
+) -> tram_core::AppResult<Option<CommandOutput>> {
+    let graph = graph(session);
+    let quiet = session.verbosity.is_quiet();
+
+    // Keep stdout for the document when the result is json or yaml
+    let structured = matches!(
+        session.config.output_format,
+        OutputFormat::Json | OutputFormat::Yaml
+    );
+    let mut runner = graph
+        .runner()
+        .with_keep_going(keep_going)
+        .with_stdout_to_stderr(structured);
+    if let Some(jobs) = jobs {
+        runner = runner.with_jobs(jobs);
+    }
+
+    let started = Instant::now();
+    let outcomes = runner.run(name, |task| {
+        if !quiet {
+            eprintln!(
+                "{}",
+                t!(
+                    "run-task-starting",
+                    name = task.name.as_str(),
+                    command = task.command.as_str()
+                )
+            );
+        }
+    })?;
+
+    let result = RunResult {
+        task: name.to_string(),
+        success: outcomes
+            .iter()
+            .all(|outcome| outcome.status == TaskStatus::Passed),
+        tasks: outcomes.into_iter().map(TaskSummary::from).collect(),
+        duration_ms: started.elapsed().as_millis(),
+    };
+    let output = CommandOutput::new(&result)?;
+
+    if result.success {
+        return Ok(Some(output));
+    }
+
+    Renderer::from_config(&session.config)
+        .with_verbosity(session.verbosity)
+        .print(&output)?;
+
+    let failed: Vec<&TaskSummary> = result
+        .tasks
+        .iter()
+        .filter(|task| task.status == TaskStatus::Failed)
+        .collect();
+
+    // A task killed by Ctrl+C means the whole run was interrupted
+    let sigint = 128 + 2;
+    if failed.iter().any(|task| task.exit_code == Some(sigint)) {
+        return Err(TramError::Interrupted.into());
+    }
+
+    let failed: Vec<&str> = failed.iter().map(|task| task.name.as_str()).collect();
+
+    Err(TramError::Task {
+        message: format!("Failed tasks: {}", failed.join(", ")),
+    }
+    .into())
+}
@@ -0,0 +1,60 @@
+//! Config-declared commands run on a schedule.
+//!
+//! While `tram watch` or the daemon is running, each entry of `[schedules]`
+//! runs its command from the workspace root whenever its cron expression
+//! comes due, e.g. an hourly `tram cache prune`. Runs are recorded in the
+//! state store, so an occurrence missed while neither was running happens
+//! once at the next start, and a watch and a daemon running together don't
+//! both run it. Changes to `[schedules]` apply the next time either starts.
+
+use std::time::Duration;
+use tracing::warn;
+use tram_config::TramConfig;
+use tram_core::{StateStore, TramError, t};
+use tram_process::{Schedule, ScheduledTask, Scheduler, SchedulerHandle};
+
+use crate::session::TramSession;
+
+/// Parse the configured schedules, in name order.
+pub fn load_tasks(config: &TramConfig) -> tram_core::AppResult<Vec<ScheduledTask>> {
+    config
+        .schedules
+        .iter()
+        .map(|(name, schedule)| {
+            let parsed = Schedule::parse(&schedule.cron).map_err(|e| TramError::InvalidConfig {
+                message: format!("Invalid cron for schedule '{}': {}", name, e),
+            })?;
+
+            Ok(ScheduledTask::new(name, parsed, &schedule.run)
+                .with_jitter(Duration::from_secs(schedule.jitter_secs)))
+        })
+        .collect()
+}
+
+/// Start running the configured schedules; `None` when there are none.
+/// Keep the handle for as long as they should run.
+pub fn start(session: &TramSession) -> tram_core::AppResult<Option<SchedulerHandle>> {
+    let tasks = load_tasks(&session.config)?;
+    if tasks.is_empty() {
+        return Ok(None);
+    }
+
+    let root = session
+        .workspace_root
+        .clone()
+        .unwrap_or_else(tram_core::current_dir);
+    let mut scheduler = Scheduler::new(root, tasks);
+    match StateStore::open_default() {
+        Ok(state) => scheduler = scheduler.with_state(state),
+        Err(e) => warn!("Scheduled runs won't be recorded: {}", e),
+    }
+
+    if !session.verbosity.is_quiet() {
+        println!(
+            "{}",
+            t!("schedules-started", count = scheduler.tasks().len())
+        );
+    }
+
+    Ok(Some(scheduler.start()))
+}
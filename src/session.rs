@@ -5,11 +5,40 @@
 
 use async_trait::async_trait;
 use starbase::AppSession;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tracing::{debug, info, warn};
-use tram_config::{ConfigChangeHandler, OutputFormat, TramConfig};
-use tram_core::init_tracing;
+use tram_config::{
+    ConfigChangeHandler, ConfigOverlay, HttpConfig, OutputFormat, TramConfig, UpdateConfig,
+};
+use tram_core::{
+    LoggingOptions, Progress, StateStore, Telemetry, TelemetryConsent, Timings, TramError,
+    UsageEvent, Verbosity, t,
+};
+use tram_http::HttpClient;
+use tram_prompt::Prompter;
+use tram_ui::{Spinner, Term};
+use tram_update::{GitHubReleasesSource, UpdateChecker, UpdateInfo};
 use tram_workspace::{ProjectType, WorkspaceDetector};
 
+/// Seconds the background update check waits for the release lookup.
+const UPDATE_CHECK_TIMEOUT_SECS: u64 = 3;
+
+/// How often a progress spinner moves to its next frame.
+const SPINNER_TICK: Duration = Duration::from_millis(80);
+
+/// How much the session prints around a command's own output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputMode {
+    /// Workspace details before the command and "Done!" after it, on stderr
+    #[default]
+    Normal,
+    /// Only the command's own output, for commands whose output is consumed
+    /// by other tools, such as completion scripts and man pages
+    Clean,
+}
+
 /// Application session - directly implements starbase's AppSession.
 #[derive(Clone, Debug)]
 pub struct TramSession {
@@ -17,6 +46,32 @@ pub struct TramSession {
     pub workspace: WorkspaceDetector,
     pub workspace_root: Option<std::path::PathBuf>,
     pub project_type: Option<ProjectType>,
+    /// Result of the background update check, filled in when it completes
+    pub update_notice: Arc<Mutex<Option<UpdateInfo>>>,
+    /// Time spent in each phase, reported with `--timings`
+    pub timings: Timings,
+    /// Output detail requested with `-q` / `-v`
+    pub verbosity: Verbosity,
+    /// Report file changes instead of making them (`--dry-run`)
+    pub dry_run: bool,
+    /// Config files the settings were merged from, lowest precedence first
+    pub config_files: Vec<std::path::PathBuf>,
+    /// The highest-precedence config file, which `tram config set` edits
+    pub config_file: Option<std::path::PathBuf>,
+    /// Settings overridden on the command line, by config key, with the flag used
+    pub flag_overrides: BTreeMap<&'static str, String>,
+    /// The `--profile` and flag settings applied over the config files, and
+    /// reapplied when a config watcher reloads them
+    pub config_overlay: ConfigOverlay,
+    /// Whether lifecycle messages are printed around the command
+    pub output_mode: OutputMode,
+    /// Whether prompts may be shown: a terminal on stdin and stderr, no CI,
+    /// and no `--yes`
+    pub interactive: bool,
+    /// Answers prompts: the terminal when interactive, defaults otherwise
+    pub prompter: Arc<dyn Prompter>,
+    /// Whether a signal or `--timeout` stopped the command before it finished
+    pub stopped: bool,
 }
 
 impl TramSession {
@@ -26,52 +81,270 @@ impl TramSession {
             workspace: WorkspaceDetector::new()?,
             workspace_root: None,
             project_type: None,
+            update_notice: Arc::new(Mutex::new(None)),
+            timings: Timings::new(),
+            verbosity: Verbosity::Normal,
+            dry_run: false,
+            config_files: Vec::new(),
+            config_file: None,
+            flag_overrides: BTreeMap::new(),
+            config_overlay: ConfigOverlay::default(),
+            output_mode: OutputMode::Normal,
+            interactive: false,
+            prompter: tram_prompt::prompter(false),
+            stopped: false,
         })
     }
+
+    /// Record phase timings into an existing recorder.
+    pub fn with_timings(mut self, timings: Timings) -> Self {
+        self.timings = timings;
+        self
+    }
+
+    /// Set the output detail requested on the command line.
+    pub fn with_verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.verbosity = verbosity;
+        self
+    }
+
+    /// Report file changes instead of making them.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Set whether lifecycle messages are printed around the command.
+    pub fn with_output_mode(mut self, output_mode: OutputMode) -> Self {
+        self.output_mode = output_mode;
+        self
+    }
+
+    /// Set whether prompts may be shown.
+    pub fn with_interactive(mut self, interactive: bool) -> Self {
+        self.interactive = interactive;
+        self.prompter = tram_prompt::prompter(interactive);
+        self
+    }
+
+    /// Whether lifecycle messages should be printed.
+    fn shows_status(&self) -> bool {
+        self.output_mode == OutputMode::Normal && !self.verbosity.is_quiet()
+    }
+
+    /// Record the config files that were merged, lowest precedence first,
+    /// and which flags overrode them.
+    pub fn with_config_sources(
+        mut self,
+        config_files: Vec<std::path::PathBuf>,
+        flag_overrides: BTreeMap<&'static str, String>,
+    ) -> Self {
+        self.config_file = config_files.last().cloned();
+        self.config_files = config_files;
+        self.flag_overrides = flag_overrides;
+        self
+    }
+
+    /// A progress indicator for a long-running command: a spinner on an
+    /// interactive terminal, log lines for machine-readable output and pipes.
+    pub fn progress(&self, total: Option<u64>) -> Progress {
+        let progress = Progress::new(total);
+        let machine_output = matches!(
+            self.config.output_format,
+            OutputFormat::Json | OutputFormat::Yaml | OutputFormat::Csv | OutputFormat::Tsv
+        );
+        let term = Term::stderr();
+        if machine_output || self.verbosity.is_quiet() || !term.is_interactive() {
+            return progress;
+        }
+
+        let spinner = Spinner::new("").with_term(term);
+        spinner.enable_steady_tick(SPINNER_TICK);
+        progress.with_display(spinner)
+    }
+
+    /// Record the profile and flag settings applied over the config files.
+    pub fn with_config_overlay(mut self, overlay: ConfigOverlay) -> Self {
+        self.config_overlay = overlay;
+        self
+    }
+
+    /// Config profile applied over the files' settings, if any.
+    pub fn profile(&self) -> Option<&str> {
+        self.config_overlay.profile.as_deref()
+    }
+
+    /// Run the shutdown phase for a command a signal or `--timeout` stopped
+    /// part way.
+    pub async fn stop(&mut self, reason: &TramError) -> tram_core::AppResult<()> {
+        warn!("{}, shutting down", reason);
+        self.stopped = true;
+        self.shutdown().await?;
+
+        Ok(())
+    }
+
+    /// Record a finished command, if the user turned telemetry on.
+    /// Telemetry must never break a command, so failures are only logged.
+    pub fn record_usage(&self, command: &str, success: bool, duration_ms: u64) {
+        let recorded = Telemetry::open_default().and_then(|telemetry| {
+            telemetry.record(&UsageEvent::new(command, success, duration_ms))
+        });
+
+        if let Err(e) = recorded {
+            debug!("Failed to record telemetry: {}", e);
+        }
+    }
+
+    /// Explain telemetry on the first run, before the user has decided.
+    fn show_first_run_notice(&self) -> tram_core::AppResult<()> {
+        if self.verbosity.is_quiet() || !StateStore::open_default()?.first_run()? {
+            return Ok(());
+        }
+
+        let telemetry = Telemetry::open_default()?;
+        if telemetry.env_override().is_none() && telemetry.consent()? == TelemetryConsent::Undecided
+        {
+            eprintln!("{}", t!("telemetry-notice"));
+        }
+
+        Ok(())
+    }
+
+    /// Check for a newer release in the background so startup isn't delayed.
+    /// The result is picked up in the shutdown phase if it finished in time.
+    /// It runs on its own thread rather than tokio's blocking pool, which the
+    /// runtime waits for on exit, so a slow lookup never holds up the command.
+    fn spawn_update_check(&self) {
+        let notice = Arc::clone(&self.update_notice);
+
+        let config = self.config.clone();
+        std::thread::spawn(move || match check_for_updates(&config) {
+            Ok(Some(info)) => {
+                if let Ok(mut guard) = notice.lock() {
+                    *guard = Some(info);
+                }
+            }
+            Ok(None) => debug!("No newer release available"),
+            Err(e) => debug!("Update check failed: {}", e),
+        });
+    }
+}
+
+/// Check the configured GitHub releases for a newer version, through the
+/// `[http]` proxy and CA settings but with a short timeout and no retries,
+/// since nobody is waiting for the answer.
+fn check_for_updates(config: &TramConfig) -> tram_core::AppResult<Option<UpdateInfo>> {
+    let http = HttpConfig {
+        timeout_secs: config.http.timeout_secs.min(UPDATE_CHECK_TIMEOUT_SECS),
+        retries: 0,
+        ..config.http.clone()
+    };
+    let source = release_source(&config.update)?.with_client(HttpClient::from_config(&http)?);
+
+    UpdateChecker::new(
+        env!("CARGO_PKG_VERSION"),
+        source,
+        StateStore::open_default()?,
+    )?
+    .check()
+}
+
+/// The GitHub releases tram updates from: `update.repository` and
+/// `update.apiUrl` in config, or tram's own repository.
+pub fn release_source(config: &UpdateConfig) -> tram_core::AppResult<GitHubReleasesSource> {
+    let source = match &config.repository {
+        Some(repository) => {
+            GitHubReleasesSource::parse(repository).ok_or_else(|| tram_core::TramError::Update {
+                message: format!(
+                    "update.repository: '{}' is not owner/name or a GitHub URL",
+                    repository
+                ),
+            })?
+        }
+        None => GitHubReleasesSource::from_repository_url(env!("CARGO_PKG_REPOSITORY"))
+            .ok_or_else(|| tram_core::TramError::Update {
+                message: "No GitHub repository configured for releases".to_string(),
+            })?,
+    };
+
+    Ok(match &config.api_url {
+        Some(url) => source.with_api_url(url),
+        None => source,
+    })
 }
 
 #[async_trait]
 impl AppSession for TramSession {
     async fn startup(&mut self) -> tram_core::AppResult<Option<u8>> {
+        let _timing = self.timings.start("startup");
+
         // Initialize tracing before anything else
         let use_json = matches!(self.config.output_format, OutputFormat::Json);
-        init_tracing(&self.config.log_level.to_string(), use_json)?;
+        self.timings.measure("tracing init", || {
+            LoggingOptions::new(self.config.log_level.to_string())
+                .with_json(use_json)
+                .with_file(self.config.log_file.clone())
+                .with_rotation(self.config.log_rotation)
+                .init()
+        })?;
 
         info!("Starting Tram CLI application");
         debug!("Configuration: {:?}", self.config);
 
+        // Bring files from older releases up to date before anything reads them
+        self.timings
+            .measure("migrations", || crate::migrations::run(self))?;
+
         // Configuration validation is handled by schematic automatically
 
         // Detect workspace
-        if let Ok(root) = self.workspace.detect_root() {
+        let detected = self
+            .timings
+            .measure("workspace detect", || self.workspace.detect_root());
+
+        if let Ok(root) = detected {
             self.workspace_root = Some(root.clone());
-            self.project_type = ProjectType::detect(&root);
+            self.project_type = self
+                .timings
+                .measure("project type detect", || ProjectType::detect(&root));
             info!("Detected workspace at: {}", root.display());
         } else {
             debug!("No workspace detected");
         }
 
+        if let Err(e) = self.show_first_run_notice() {
+            debug!("Failed to check for the first run: {}", e);
+        }
+
+        if self.config.check_for_updates && !self.config.offline {
+            self.spawn_update_check();
+        }
+
         Ok(None)
     }
 
     async fn analyze(&mut self) -> tram_core::AppResult<Option<u8>> {
         // This phase would typically validate the environment,
         // check dependencies, build task graphs, etc.
+        let _timing = self.timings.start("analyze");
 
         debug!("Analyzing workspace environment");
 
-        // Skip workspace info for utility commands that need clean stdout
-        let args: Vec<String> = std::env::args().collect();
-        let is_utility_command = args.len() >= 2 && (args[1] == "completions" || args[1] == "man");
+        if self.shows_status()
+            && let Some(root) = &self.workspace_root
+        {
+            eprintln!(
+                "{}",
+                t!("working-in-workspace", path = root.display().to_string())
+            );
 
-        if !is_utility_command {
-            if let Some(root) = &self.workspace_root {
-                eprintln!("Working in {} workspace", root.display());
-
-                if let Some(project_type) = &self.project_type {
-                    eprintln!("Detected {:?} project", project_type);
-                    info!("Project type: {:?}", project_type);
-                }
+            if let Some(project_type) = &self.project_type {
+                eprintln!(
+                    "{}",
+                    t!("detected-project", kind = format!("{:?}", project_type))
+                );
+                info!("Project type: {:?}", project_type);
             }
         }
 
@@ -80,16 +353,24 @@ impl AppSession for TramSession {
 
     async fn shutdown(&mut self) -> tram_core::AppResult<Option<u8>> {
         // Cleanup - save caches, write state, etc.
+        let _timing = self.timings.start("shutdown");
         debug!("Shutting down application");
-        
-        // Skip "Done!" message for utility commands that need clean stdout
-        let args: Vec<String> = std::env::args().collect();
-        let is_utility_command = args.len() >= 2 && (args[1] == "completions" || args[1] == "man");
-        
-        if !is_utility_command {
-            eprintln!("Done!");
+
+        if self.shows_status() && !self.stopped {
+            eprintln!("{}", t!("done"));
+
+            if let Ok(guard) = self.update_notice.lock()
+                && let Some(info) = guard.as_ref()
+            {
+                eprintln!("{}", info.notice("tram"));
+            }
+        }
+
+        if !self.dry_run {
+            crate::cache::spawn_background_gc(&self.config);
         }
-        
+        crate::metrics::export(&self.config).await;
+
         Ok(None)
     }
 }
@@ -0,0 +1,363 @@
+//! Interactive shell for `tram shell`.
+//!
+//! Runs subcommands repeatedly against the session that started the shell,
+//! so config loading and workspace analysis happen once instead of on every
+//! invocation. Lines are parsed exactly like command-line arguments; `--format`,
+//! `-q`, `-v`, and `--dry-run` apply per line, other global options are fixed
+//! at startup.
+
+use clap::{CommandFactory, FromArgMatches};
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use tram_config::OutputFormat;
+use tram_core::{TramError, Verbosity, t};
+use tram_output::Renderer;
+
+use crate::cli::{Cli, Commands};
+use crate::commands::execute_command;
+use crate::session::TramSession;
+
+const PROMPT: &str = "tram> ";
+
+/// Words that leave the shell.
+const EXIT_WORDS: [&str; 2] = ["exit", "quit"];
+
+/// Run the read-eval-print loop until `exit`, `quit`, or end of input.
+pub async fn run(session: &TramSession) -> tram_core::AppResult<()> {
+    let mut editor =
+        Editor::<ShellHelper, DefaultHistory>::new().map_err(|e| TramError::InvalidConfig {
+            message: format!("Failed to start the shell: {}", e),
+        })?;
+    editor.set_helper(Some(ShellHelper::new()));
+
+    let history = history_path();
+    if let Some(path) = &history {
+        // A missing history file just means this is the first session
+        let _ = editor.load_history(path);
+    }
+
+    if !session.verbosity.is_quiet() {
+        eprintln!(
+            "{}",
+            t!("shell-welcome", version = env!("CARGO_PKG_VERSION"))
+        );
+    }
+
+    loop {
+        let line = match editor.readline(PROMPT) {
+            Ok(line) => line,
+            // Ctrl+C discards the current line, like a regular shell
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                return Err(TramError::InvalidConfig {
+                    message: format!("Failed to read input: {}", e),
+                }
+                .into());
+            }
+        };
+
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let _ = editor.add_history_entry(line);
+
+        if EXIT_WORDS.contains(&line) {
+            break;
+        }
+
+        // A failing command shouldn't end the shell
+        if let Err(e) = run_line(line, session).await {
+            eprintln!("{:?}", e);
+        }
+    }
+
+    if let Some(path) = &history {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = editor.save_history(path);
+    }
+
+    Ok(())
+}
+
+/// Parse and run a single line of input.
+async fn run_line(line: &str, session: &TramSession) -> tram_core::AppResult<()> {
+    let Some(words) = shlex::split(line) else {
+        eprintln!("{}", t!("shell-unbalanced-quotes"));
+        return Ok(());
+    };
+
+    let args = std::iter::once("tram".to_string()).chain(words);
+    let parsed = Cli::command()
+        .try_get_matches_from(args)
+        .and_then(|matches| Cli::from_arg_matches(&matches).map(|cli| (cli, matches)));
+
+    let (cli, matches) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            // Help, version, and usage errors are printed but never exit
+            let _ = e.print();
+            return Ok(());
+        }
+    };
+
+    crate::deprecations::check(&matches);
+
+    let mut session = session.clone();
+    if let Some(format) = &cli.global.format {
+        session.config.output_format = format
+            .parse::<OutputFormat>()
+            .map_err(|message| TramError::InvalidConfig { message })?;
+    }
+    if cli.global.quiet || cli.global.verbose > 0 {
+        session.verbosity = Verbosity::from_flags(cli.global.quiet, cli.global.verbose);
+    }
+    if cli.global.dry_run {
+        session.dry_run = true;
+    }
+
+    match cli.command {
+        Commands::Shell => eprintln!("{}", t!("shell-nested")),
+
+        Commands::External(args) => match crate::plugins::dispatch(
+            Cli::command(),
+            &args,
+            &session.config,
+            session.config_file.as_deref(),
+        ) {
+            Ok(result) => {
+                result?;
+            }
+            Err(e) => {
+                let _ = e.print();
+            }
+        },
+
+        command => {
+            if let Some(output) = Box::pin(execute_command(command, &session)).await? {
+                Renderer::from_config(&session.config)
+                    .with_verbosity(session.verbosity)
+                    .print(&output)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Where shell history is kept between sessions.
+fn history_path() -> Option<PathBuf> {
+    tram_core::data_dir().map(|dir| dir.join("shell_history"))
+}
+
+/// Tab completion for subcommand names, flags, and their values.
+struct ShellHelper {
+    commands: Vec<String>,
+    global_flags: Vec<String>,
+    command_flags: HashMap<String, Vec<String>>,
+    /// Flags whose next word is their value, not a subcommand
+    value_flags: HashSet<String>,
+    /// Valid values for flags that have a fixed set (e.g. `--format`)
+    flag_values: HashMap<String, Vec<String>>,
+    /// Nested subcommands of a subcommand (e.g. `config get`)
+    subcommands: HashMap<String, Vec<String>>,
+    /// Valid values for a subcommand's positional argument (e.g. example
+    /// names), keyed by the subcommand's words (e.g. `config get`)
+    positional_values: HashMap<String, Vec<String>>,
+    /// Valid values for `config set`, by key path
+    config_values: HashMap<String, Vec<String>>,
+}
+
+impl ShellHelper {
+    fn new() -> Self {
+        let cli = crate::dev_tools::completion_command();
+        let mut value_flags = HashSet::new();
+        let mut flag_values = HashMap::new();
+
+        let mut long_flags = |command: &clap::Command| -> Vec<String> {
+            command
+                .get_arguments()
+                .filter(|arg| !arg.is_hide_set())
+                .filter_map(|arg| {
+                    let flag = format!("--{}", arg.get_long()?);
+                    if arg.get_action().takes_values() {
+                        value_flags.insert(flag.clone());
+
+                        let values = possible_values(arg);
+                        if !values.is_empty() {
+                            flag_values.insert(flag.clone(), values);
+                        }
+                    }
+                    Some(flag)
+                })
+                .collect()
+        };
+
+        let global_flags = long_flags(&cli);
+        let mut commands = Vec::new();
+        let mut command_flags = HashMap::new();
+        let mut subcommands = HashMap::new();
+        let mut positional_values = HashMap::new();
+
+        let mut add_positional_values = |name: &str, command: &clap::Command| {
+            if let Some(values) = command
+                .get_positionals()
+                .map(possible_values)
+                .find(|values| !values.is_empty())
+            {
+                positional_values.insert(name.to_string(), values);
+            }
+        };
+
+        for sub in cli.get_subcommands() {
+            if sub.is_hide_set() || sub.get_name() == "shell" {
+                continue;
+            }
+
+            let name = sub.get_name().to_string();
+            command_flags.insert(name.clone(), long_flags(sub));
+            add_positional_values(&name, sub);
+
+            let nested: Vec<String> = sub
+                .get_subcommands()
+                .filter(|nested| !nested.is_hide_set())
+                .map(|nested| {
+                    let nested_name = nested.get_name().to_string();
+                    add_positional_values(&format!("{} {}", name, nested_name), nested);
+                    nested_name
+                })
+                .collect();
+            if !nested.is_empty() {
+                subcommands.insert(name.clone(), nested);
+            }
+
+            commands.push(name);
+        }
+
+        commands.extend(EXIT_WORDS.iter().map(|word| word.to_string()));
+        commands.sort();
+
+        let config_values = tram_config::config_keys()
+            .into_iter()
+            .filter(|key| !key.values.is_empty())
+            .map(|key| (key.path, key.values.into_iter().map(String::from).collect()))
+            .collect();
+
+        Self {
+            commands,
+            global_flags,
+            command_flags,
+            value_flags,
+            flag_values,
+            subcommands,
+            positional_values,
+            config_values,
+        }
+    }
+
+    /// Candidates for the word being typed, given the words before it.
+    fn candidates(&self, before: &str, word: &str) -> Vec<String> {
+        let mut words = Vec::new();
+        let mut value_for = None;
+
+        for token in before.split_whitespace() {
+            if value_for.take().is_some() {
+                continue;
+            }
+
+            if token.starts_with('-') {
+                if self.value_flags.contains(token) {
+                    value_for = Some(token);
+                }
+            } else {
+                words.push(token);
+            }
+        }
+        let command = words.first().copied();
+
+        let pool: Vec<&String> = match (value_for, command, word.starts_with('-')) {
+            (Some(flag), _, _) => self.flag_values.get(flag).into_iter().flatten().collect(),
+            (None, None, false) => self.commands.iter().collect(),
+            (None, None, true) => self.global_flags.iter().collect(),
+            (None, Some(command), true) => self
+                .command_flags
+                .get(command)
+                .into_iter()
+                .flatten()
+                .chain(&self.global_flags)
+                .collect(),
+            (None, Some(_), false) => self.positional_candidates(&words),
+        };
+
+        pool.into_iter()
+            .filter(|candidate| candidate.starts_with(word))
+            .cloned()
+            .collect()
+    }
+
+    /// Candidates for a positional word, given the subcommand words before
+    /// it: nested subcommands, their argument's values, or the values of
+    /// the key `config set` is changing.
+    fn positional_candidates(&self, words: &[&str]) -> Vec<&String> {
+        let values = match words {
+            ["config", "set", key] => self.config_values.get(*key),
+            [command] if self.subcommands.contains_key(*command) => self.subcommands.get(*command),
+            [command, nested] if self.subcommands.contains_key(*command) => self
+                .positional_values
+                .get(&format!("{} {}", command, nested)),
+            [command, ..] if !self.subcommands.contains_key(*command) => {
+                self.positional_values.get(*command)
+            }
+            _ => None,
+        };
+
+        values.into_iter().flatten().collect()
+    }
+}
+
+/// Non-hidden possible values of an argument, for completion.
+fn possible_values(arg: &clap::Arg) -> Vec<String> {
+    arg.get_possible_values()
+        .into_iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| value.get_name().to_string())
+        .collect()
+}
+
+impl Completer for ShellHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos]
+            .rfind(char::is_whitespace)
+            .map_or(0, |index| index + 1);
+
+        Ok((start, self.candidates(&line[..start], &line[start..pos])))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
@@ -0,0 +1,81 @@
+//! The run summary: `--summary-file`, and a `{"summary": ...}` line on
+//! stderr with `--format json`.
+//!
+//! Collection starts as soon as the arguments are parsed when either asks
+//! for a summary, or once the config is loaded if it sets the json format.
+//! The summary is written when the command ends, whether it succeeded,
+//! failed, or was stopped, after any error report.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tram_config::{OutputFormat, TramConfig};
+use tram_core::{RunSummary, t};
+
+use crate::cli::GlobalOptions;
+
+/// Where `--summary-file` asked for the summary
+static FILE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Set by `-q`, which keeps the summary off stderr
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Start collecting if the flags ask for a summary.
+pub fn start(command: &str, global: &GlobalOptions) {
+    QUIET.store(global.quiet, Ordering::Relaxed);
+    if let Some(path) = &global.summary_file {
+        let _ = FILE.set(tram_core::expand_path(path));
+    }
+
+    let json = global
+        .format
+        .as_deref()
+        .is_some_and(|format| format.eq_ignore_ascii_case("json"));
+    if FILE.get().is_some() || json {
+        tram_core::start_summary(command, global.dry_run);
+    }
+}
+
+/// Start collecting once the config turns out to ask for json output, if
+/// the flags didn't already.
+pub fn start_for_config(command: &str, config: &TramConfig, dry_run: bool) {
+    if config.output_format == OutputFormat::Json && !tram_core::summary_enabled() {
+        tram_core::start_summary(command, dry_run);
+    }
+}
+
+/// Write the summary for a command that exited with `exit_code`: to the
+/// `--summary-file`, and to stderr when `json` errors are in use.
+pub fn finish(exit_code: u8, json: bool) {
+    let Some(summary) = tram_core::finish_summary(exit_code) else {
+        return;
+    };
+
+    if let Some(path) = FILE.get()
+        && let Err(e) = write_file(path, &summary)
+    {
+        eprintln!(
+            "{}",
+            t!(
+                "summary-write-failed",
+                path = path.display().to_string(),
+                error = e.to_string()
+            )
+        );
+    }
+
+    if json && !QUIET.load(Ordering::Relaxed) {
+        let line = serde_json::json!({ "summary": summary });
+        eprintln!("{}", line);
+    }
+}
+
+fn write_file(path: &Path, summary: &RunSummary) -> std::io::Result<()> {
+    if let Some(parent) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut json = serde_json::to_string_pretty(summary).map_err(std::io::Error::other)?;
+    json.push('\n');
+    std::fs::write(path, json)
+}
@@ -0,0 +1,116 @@
+//! Managing usage telemetry consent with `tram telemetry`.
+//!
+//! `on` and `off` store the user's answer in the state store, and `status`
+//! shows the answer, any environment override, and how many events have
+//! been recorded.
+
+use serde::Serialize;
+use std::path::PathBuf;
+use tram_core::{Telemetry, TelemetryConsent, t};
+use tram_output::{CommandOutput, Render};
+use tram_ui::Table;
+
+use crate::cli::TelemetryAction;
+
+/// Result of `tram telemetry status`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryStatus {
+    /// Whether events are recorded on this run
+    pub enabled: bool,
+    pub consent: TelemetryConsent,
+    /// Set by `TRAM_TELEMETRY` or `DO_NOT_TRACK`
+    pub env_override: Option<bool>,
+    pub events_path: PathBuf,
+    pub recorded_events: usize,
+}
+
+impl TelemetryStatus {
+    fn consent_label(&self) -> String {
+        match self.consent {
+            TelemetryConsent::Enabled => t!("telemetry-status-enabled"),
+            TelemetryConsent::Disabled => t!("telemetry-status-disabled"),
+            TelemetryConsent::Undecided => t!("telemetry-status-undecided"),
+        }
+    }
+
+    fn override_label(&self) -> Option<String> {
+        self.env_override.map(|enabled| {
+            t!(
+                "telemetry-env-override",
+                state = if enabled { "on" } else { "off" }
+            )
+        })
+    }
+}
+
+impl Render for TelemetryStatus {
+    fn render_plain(&self) -> String {
+        let mut lines = vec![t!("telemetry-status", status = self.consent_label())];
+        lines.extend(self.override_label());
+        lines.push(t!(
+            "telemetry-events",
+            count = self.recorded_events,
+            path = self.events_path.display().to_string()
+        ));
+
+        lines.join("\n")
+    }
+
+    fn render_table(&self) -> Option<Table> {
+        let mut table = Table::new([t!("workspace-property"), t!("config-value")]);
+        table.add_row([t!("telemetry-consent-label"), self.consent_label()]);
+        if let Some(label) = self.override_label() {
+            table.add_row([t!("telemetry-override-label"), label]);
+        }
+        table.add_row([
+            t!("telemetry-events-label"),
+            self.recorded_events.to_string(),
+        ]);
+        table.add_row([
+            t!("telemetry-path-label"),
+            self.events_path.display().to_string(),
+        ]);
+
+        Some(table)
+    }
+}
+
+/// Result of `tram telemetry on` and `tram telemetry off`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryChange {
+    pub enabled: bool,
+}
+
+impl Render for TelemetryChange {
+    fn render_plain(&self) -> String {
+        if self.enabled {
+            t!("telemetry-on")
+        } else {
+            t!("telemetry-off")
+        }
+    }
+}
+
+/// Run a `tram telemetry` action.
+pub fn run(action: TelemetryAction) -> tram_core::AppResult<CommandOutput> {
+    let telemetry = Telemetry::open_default()?;
+
+    match action {
+        TelemetryAction::On | TelemetryAction::Off => {
+            let enabled = matches!(action, TelemetryAction::On);
+            telemetry.set_enabled(enabled)?;
+
+            CommandOutput::new(&TelemetryChange { enabled })
+        }
+
+        TelemetryAction::Status => CommandOutput::new(&TelemetryStatus {
+            enabled: telemetry.is_enabled()?,
+            consent: telemetry.consent()?,
+            env_override: telemetry.env_override(),
+            events_path: telemetry.events_path().to_path_buf(),
+            recorded_events: telemetry.recorded_events(),
+        }),
+    }
+}
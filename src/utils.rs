@@ -5,6 +5,8 @@
 
 use tram_core::{InitProjectType, TemplateType};
 
+use crate::cli::custom_template_name;
+
 /// Parse project type string to InitProjectType.
 pub fn parse_project_type(type_str: &str) -> InitProjectType {
     match type_str.to_lowercase().as_str() {
@@ -31,6 +33,10 @@ pub fn project_type_display(project_type: &InitProjectType) -> &'static str {
 
 /// Parse template type string to TemplateType.
 pub fn parse_template_type(type_str: &str) -> TemplateType {
+    if let Some(name) = custom_template_name(type_str) {
+        return TemplateType::Custom(name.to_string());
+    }
+
     match type_str.to_lowercase().as_str() {
         "command" | "cmd" => TemplateType::Command,
         "config-section" | "config" => TemplateType::ConfigSection,
@@ -41,11 +47,12 @@ pub fn parse_template_type(type_str: &str) -> TemplateType {
 }
 
 /// Display name for template type.
-pub fn template_type_display(template_type: &TemplateType) -> &'static str {
+pub fn template_type_display(template_type: &TemplateType) -> String {
     match template_type {
-        TemplateType::Command => "Command",
-        TemplateType::ConfigSection => "Config Section",
-        TemplateType::ErrorType => "Error Type",
-        TemplateType::SessionExtension => "Session Extension",
+        TemplateType::Command => "Command".to_string(),
+        TemplateType::ConfigSection => "Config Section".to_string(),
+        TemplateType::ErrorType => "Error Type".to_string(),
+        TemplateType::SessionExtension => "Session Extension".to_string(),
+        TemplateType::Custom(name) => format!("Custom ({})", name),
     }
 }
@@ -0,0 +1,484 @@
+//! File-triggered work for `tram watch`.
+//!
+//! Watches the workspace and, after each batch of changes, runs the project's
+//! checks (with `--check`) and any `watch.tasks` from the config whose glob
+//! matches a changed file. Changes are debounced into batches, so a formatter
+//! rewriting fifty files causes one run, and each run reports the paths that
+//! triggered it. Task output is streamed with a `[name]` prefix and failures
+//! are summarized once the batch finishes. With `--ui` the output goes to a
+//! full-screen log view instead, with each task's last result on a status
+//! line and keys to re-run or filter tasks; with `--dashboard` progress goes
+//! to the dashboard's events and task history.
+
+use console::Key;
+use globset::{GlobBuilder, GlobMatcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+use tram_config::WatchConfig;
+use tram_core::{TramError, t};
+use tram_process::{ExecResult, OutputMode, Process};
+use tram_telemetry::{WATCH_BATCHES, WATCH_TRIGGERS, metrics};
+use tram_ui::{Dashboard, LogView, TaskStatus};
+use tram_workspace::{ProjectType, WorkspaceChange, WorkspaceWatcher};
+
+use crate::session::TramSession;
+
+/// A configured task and the files that trigger it.
+#[derive(Debug, Clone)]
+pub struct WatchTask {
+    pub name: String,
+    pub run: String,
+    glob: GlobMatcher,
+}
+
+impl WatchTask {
+    /// The glob of files that trigger this task.
+    pub fn glob(&self) -> &str {
+        self.glob.glob().glob()
+    }
+
+    /// Whether a workspace-relative path should trigger this task.
+    pub fn matches(&self, path: &Path) -> bool {
+        self.glob.is_match(path)
+    }
+}
+
+/// Compile the configured watch tasks, in name order.
+pub fn load_tasks(config: &WatchConfig) -> tram_core::AppResult<Vec<WatchTask>> {
+    config
+        .tasks
+        .iter()
+        .map(|(name, task)| {
+            let glob = GlobBuilder::new(&task.glob)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| TramError::InvalidConfig {
+                    message: format!("Invalid glob for watch task '{}': {}", name, e),
+                })?;
+
+            Ok(WatchTask {
+                name: name.clone(),
+                run: task.run.clone(),
+                glob: glob.compile_matcher(),
+            })
+        })
+        .collect()
+}
+
+/// Name of the checks on the `--ui` status line.
+const CHECKS_NAME: &str = "checks";
+
+/// Lines scrolled by Page Up and Page Down in the `--ui` view.
+const PAGE_LINES: isize = 10;
+
+/// What `watch --check` runs after each batch of changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Checks {
+    Off,
+    /// `just check` when the workspace has a justfile, otherwise the
+    /// project type's checks
+    Detected,
+    /// A shell command from `--check-command` or `watch.checkCommand`
+    Command(String),
+}
+
+/// What the watch thread acts on next.
+enum Trigger {
+    Changes(Vec<WorkspaceChange>),
+    /// A re-run asked for from the `--ui` view: one task, or everything
+    Rerun(Option<String>),
+}
+
+/// Watch the workspace on a background thread, running checks and matching
+/// tasks after every batch of changes.
+///
+/// With `ui`, output goes to a full-screen [`LogView`] with key bindings to
+/// re-run and filter tasks, which is returned so it can be closed on exit.
+/// When stdout isn't a terminal, plain logs are printed instead. With a
+/// `dashboard`, the watched paths, progress, and task runs are recorded on
+/// it instead.
+pub fn start(
+    root: &Path,
+    session: &TramSession,
+    checks: Checks,
+    tasks: Vec<WatchTask>,
+    debounce: Duration,
+    ui: bool,
+    dashboard: Option<Dashboard>,
+) -> tram_core::AppResult<Option<LogView>> {
+    let project_type = session.project_type.as_ref();
+    let checks = match checks {
+        Checks::Off => Vec::new(),
+        Checks::Detected => {
+            let checks: Vec<Process> = check_commands(root, project_type)
+                .into_iter()
+                .filter_map(|command| {
+                    let (program, args) = command.split_first()?;
+                    Some(Process::new(program, args.iter().cloned()))
+                })
+                .collect();
+            if checks.is_empty() {
+                warn!("{}", t!("watch-no-checks"));
+            }
+            checks
+        }
+        Checks::Command(command) => vec![Process::shell(&command)],
+    };
+
+    let events = WorkspaceWatcher::new(root, project_type)?
+        .with_debounce(debounce)
+        .watch()?;
+
+    let view = ui
+        .then(|| {
+            let names = (!checks.is_empty())
+                .then_some(CHECKS_NAME)
+                .into_iter()
+                .chain(tasks.iter().map(|task| task.name.as_str()));
+            LogView::new(names).with_help(t!("watch-ui-keys"))
+        })
+        .filter(|view| {
+            if !view.is_interactive() {
+                info!("{}", t!("watch-ui-not-terminal"));
+            }
+            view.is_interactive()
+        });
+
+    if let Some(dashboard) = &dashboard {
+        dashboard.add_watched(events.root().display().to_string());
+        for task in &tasks {
+            dashboard.add_watched(format!("{} ({})", task.glob(), task.name));
+        }
+    }
+
+    let (sender, triggers) = mpsc::channel();
+    let runner = Runner {
+        root: events.root().to_path_buf(),
+        checks,
+        tasks,
+        quiet: session.verbosity.is_quiet(),
+        view: view.clone(),
+        dashboard,
+    };
+
+    // Plain threads rather than blocking tasks, so Ctrl+C isn't held up
+    // waiting for the next change
+    let batches = sender.clone();
+    std::thread::spawn(move || {
+        while let Some(changes) = events.next_batch() {
+            if batches.send(Trigger::Changes(changes)).is_err() {
+                break;
+            }
+        }
+    });
+
+    if let Some(view) = &view {
+        view.start();
+        let view = view.clone();
+        std::thread::spawn(move || read_keys(&view, &sender));
+    }
+
+    std::thread::spawn(move || {
+        for trigger in triggers {
+            match trigger {
+                Trigger::Changes(changes) => runner.on_changes(&changes),
+                Trigger::Rerun(task) => runner.rerun(task.as_deref()),
+            }
+        }
+    });
+
+    Ok(view)
+}
+
+/// Handle key presses in the `--ui` view until the terminal goes away.
+///
+/// Ctrl+C arrives as a key while one is being read; reading stops there and
+/// the terminal raises SIGINT, which ends watch mode as usual.
+fn read_keys(view: &LogView, triggers: &mpsc::Sender<Trigger>) {
+    let term = console::Term::stdout();
+
+    while let Ok(key) = term.read_key() {
+        match key {
+            Key::Char('r') if triggers.send(Trigger::Rerun(view.filter())).is_err() => break,
+            Key::Char('r') => {}
+            Key::Char('a') | Key::Escape => view.set_filter(None),
+            Key::Char(digit @ '1'..='9') => {
+                let index = digit as usize - '1' as usize;
+                if let Some(name) = view.tasks().get(index) {
+                    let selected = view.filter().as_ref() == Some(name);
+                    view.set_filter((!selected).then_some(name.as_str()));
+                }
+            }
+            Key::ArrowUp => view.scroll(1),
+            Key::ArrowDown => view.scroll(-1),
+            Key::PageUp => view.scroll(PAGE_LINES),
+            Key::PageDown => view.scroll(-PAGE_LINES),
+            Key::End => view.follow(),
+            _ => {}
+        }
+    }
+}
+
+/// Runs checks and tasks, reporting to the terminal, the `--ui` view, or
+/// the dashboard.
+struct Runner {
+    root: PathBuf,
+    checks: Vec<Process>,
+    tasks: Vec<WatchTask>,
+    quiet: bool,
+    view: Option<LogView>,
+    dashboard: Option<Dashboard>,
+}
+
+impl Runner {
+    fn on_changes(&self, changes: &[WorkspaceChange]) {
+        let relative: Vec<&Path> = changes
+            .iter()
+            .map(|change| change.path.strip_prefix(&self.root).unwrap_or(&change.path))
+            .collect();
+
+        for (change, path) in changes.iter().zip(&relative) {
+            // Logs would garble the view
+            if self.view.is_some() {
+                debug!("{} {}", change.kind, path.display());
+            } else {
+                info!("{} {}", change.kind, path.display());
+            }
+        }
+        metrics().increment(&WATCH_BATCHES, &[], 1);
+
+        self.report(&t!(
+            "watch-batch",
+            count = relative.len(),
+            paths = describe_paths(&relative)
+        ));
+
+        if !self.checks.is_empty() {
+            self.run_checks();
+        }
+
+        let runs = self
+            .tasks
+            .iter()
+            .filter_map(|task| {
+                let triggers: Vec<&Path> = relative
+                    .iter()
+                    .copied()
+                    .filter(|path| task.matches(path))
+                    .collect();
+                (!triggers.is_empty()).then(|| (task, describe_paths(&triggers)))
+            })
+            .collect();
+        self.run_tasks(runs);
+    }
+
+    /// Run one task (or the checks) again, or everything with `None`.
+    fn rerun(&self, name: Option<&str>) {
+        self.report(&match name {
+            Some(name) => t!("watch-ui-rerun", name = name),
+            None => t!("watch-ui-rerun-all"),
+        });
+
+        if !self.checks.is_empty() && name.is_none_or(|name| name == CHECKS_NAME) {
+            self.run_checks();
+        }
+
+        let runs = self
+            .tasks
+            .iter()
+            .filter(|task| name.is_none_or(|name| name == task.name))
+            .map(|task| (task, t!("watch-ui-rerun-trigger")))
+            .collect();
+        self.run_tasks(runs);
+    }
+
+    /// Print a progress line, or add it to the view or dashboard.
+    fn report(&self, line: &str) {
+        match (&self.view, &self.dashboard) {
+            (Some(view), _) => view.message(line),
+            (None, Some(dashboard)) => dashboard.event(line),
+            (None, None) if !self.quiet => println!("{}", line),
+            (None, None) => {}
+        }
+    }
+
+    /// Log a warning, or show it in the view or dashboard.
+    fn warn(&self, message: &str) {
+        match (&self.view, &self.dashboard) {
+            (Some(view), _) => view.message(message),
+            (None, Some(dashboard)) => dashboard.event(message),
+            (None, None) => warn!("{}", message),
+        }
+    }
+
+    /// Send the process's output to the view under `name`, if there is one,
+    /// or keep it from drawing over the dashboard.
+    fn process(&self, process: Process, name: &str) -> Process {
+        match (&self.view, &self.dashboard) {
+            (Some(view), _) => {
+                let view = view.clone();
+                let name = name.to_string();
+                process
+                    .with_output(OutputMode::Capture)
+                    .with_line_handler(move |line| view.push_line(&name, line))
+            }
+            (None, Some(_)) => process.with_output(OutputMode::Capture),
+            (None, None) => process,
+        }
+    }
+
+    fn set_status(&self, name: &str, status: TaskStatus) {
+        if let Some(view) = &self.view {
+            view.set_status(name, status);
+        }
+        if let Some(dashboard) = &self.dashboard {
+            dashboard.record_run(name, status);
+        }
+    }
+
+    /// Run check commands in order, stopping at the first failure.
+    fn run_checks(&self) {
+        self.report(&t!("watch-checks-running"));
+        self.set_status(CHECKS_NAME, TaskStatus::Running);
+
+        let started = Instant::now();
+        let mut failure = None;
+        for check in &self.checks {
+            let process = self.process(check.clone().with_cwd(&self.root), CHECKS_NAME);
+
+            match process.run() {
+                Ok(result) if result.success() => {}
+                Ok(result) => {
+                    self.warn(&format!(
+                        "`{}` failed with exit code {}",
+                        process.command_line(),
+                        result.exit_code
+                    ));
+                    failure = Some(result.exit_code);
+                    break;
+                }
+                Err(e) => {
+                    self.warn(&e.to_string());
+                    failure = Some(1);
+                    break;
+                }
+            }
+        }
+
+        match failure {
+            None => {
+                self.set_status(CHECKS_NAME, TaskStatus::Passed(started.elapsed()));
+                self.report(&t!("watch-checks-passed"));
+            }
+            Some(code) => {
+                self.set_status(CHECKS_NAME, TaskStatus::Failed(code));
+                self.report(&t!("watch-checks-failed"));
+            }
+        }
+    }
+
+    /// Run each task, with what triggered it, then summarize the failures.
+    fn run_tasks(&self, runs: Vec<(&WatchTask, String)>) {
+        let mut failures: Vec<(&WatchTask, ExecResult)> = Vec::new();
+
+        for (task, trigger) in runs {
+            metrics().increment(&WATCH_TRIGGERS, &[("task", &task.name)], 1);
+            self.report(&t!(
+                "watch-task-running",
+                name = task.name.as_str(),
+                paths = trigger
+            ));
+            self.set_status(&task.name, TaskStatus::Running);
+
+            let process = Process::shell(&task.run)
+                .with_cwd(&self.root)
+                .with_prefix(format!("[{}] ", task.name));
+
+            match self.process(process, &task.name).run() {
+                Ok(result) if result.success() => {
+                    self.set_status(&task.name, TaskStatus::Passed(result.duration));
+                    self.report(&t!(
+                        "watch-task-passed",
+                        name = task.name.as_str(),
+                        duration = format!("{:.1?}", result.duration)
+                    ));
+                }
+                Ok(result) => {
+                    self.set_status(&task.name, TaskStatus::Failed(result.exit_code));
+                    failures.push((task, result));
+                }
+                Err(e) => {
+                    self.set_status(&task.name, TaskStatus::Failed(1));
+                    self.warn(&e.to_string());
+                }
+            }
+        }
+
+        for (task, result) in failures {
+            let summary = t!(
+                "watch-task-failed",
+                name = task.name.as_str(),
+                code = result.exit_code
+            );
+
+            // The view already shows the output
+            if let Some(view) = &self.view {
+                view.message(&summary);
+                continue;
+            }
+
+            if let Some(dashboard) = &self.dashboard {
+                dashboard.event(summary);
+                for line in &result.output_tail {
+                    dashboard.event(format!("    {}", line));
+                }
+                continue;
+            }
+
+            // Failures are printed even with -q; they're what the user is waiting for
+            eprintln!("{}", summary);
+            for line in &result.output_tail {
+                eprintln!("    {}", line);
+            }
+        }
+    }
+}
+
+/// Commands run for [`Checks::Detected`]: `just check` when the workspace
+/// has a justfile, otherwise the project type's defaults.
+fn check_commands(root: &Path, project_type: Option<&ProjectType>) -> Vec<Vec<String>> {
+    let has_justfile = ["justfile", "Justfile"]
+        .iter()
+        .any(|name| root.join(name).is_file());
+
+    if has_justfile && tram_process::which("just").is_some() {
+        return vec![vec!["just".to_string(), "check".to_string()]];
+    }
+
+    project_type
+        .map(|kind| kind.check_commands())
+        .unwrap_or_default()
+        .iter()
+        .map(|command| command.iter().map(|arg| arg.to_string()).collect())
+        .collect()
+}
+
+/// Most paths listed when reporting a batch.
+const LISTED_PATHS: usize = 5;
+
+/// A short list of paths, e.g. `src/a.rs, src/b.rs and 3 more`.
+fn describe_paths(paths: &[&Path]) -> String {
+    let listed = paths
+        .iter()
+        .take(LISTED_PATHS)
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    match paths.len().saturating_sub(LISTED_PATHS) {
+        0 => listed,
+        more => t!("watch-more-paths", paths = listed, count = more),
+    }
+}
@@ -0,0 +1,151 @@
+//! Interactive prompts for `tram new`.
+//!
+//! When `tram new` runs in a terminal without `--skip-prompts` or `--yes`,
+//! the wizard asks for the project type, description, author, license, git,
+//! and CI, using any values given as flags as the defaults. The author
+//! defaults to the git identity when `--author` isn't given. Without a
+//! terminal or in CI the flags and defaults are used as-is, so scripts never
+//! block on a prompt.
+
+use tram_core::{InitConfig, InitProjectType, LicenseKind, t};
+use tram_prompt::{Answers, Confirm, Form, Input, PromptForm, Prompter, Select};
+
+use crate::session::TramSession;
+use crate::utils::project_type_display;
+
+/// Project types in the order they're offered.
+const PROJECT_TYPES: [InitProjectType; 6] = [
+    InitProjectType::Rust,
+    InitProjectType::NodeJs,
+    InitProjectType::Python,
+    InitProjectType::Go,
+    InitProjectType::Java,
+    InitProjectType::Generic,
+];
+
+/// Whether to prompt: only when asked to, and when someone can answer.
+pub fn should_prompt(session: &TramSession, skip_prompts: bool) -> bool {
+    !skip_prompts && session.interactive
+}
+
+/// The settings `tram new` asks for, as a form over the init config.
+struct ProjectSettings<'a>(&'a mut InitConfig);
+
+impl ProjectSettings<'_> {
+    /// Licenses offered, before the "no license" choice. A license given as
+    /// a flag is offered even if it isn't a built-in one.
+    fn licenses(&self) -> Vec<LicenseKind> {
+        let mut licenses = LicenseKind::BUILT_IN.to_vec();
+        if let Some(license) = &self.0.license
+            && !licenses.contains(license)
+        {
+            licenses.insert(0, license.clone());
+        }
+        licenses
+    }
+}
+
+impl PromptForm for ProjectSettings<'_> {
+    fn form(&self) -> Form {
+        let config = &self.0;
+        let type_names: Vec<&str> = PROJECT_TYPES.iter().map(project_type_display).collect();
+
+        let licenses = self.licenses();
+        let mut license_items: Vec<String> = licenses.iter().map(ToString::to_string).collect();
+        license_items.push(t!("new-prompt-license-none"));
+
+        Form::new()
+            .select(
+                "type",
+                Select::new(t!("new-prompt-type"), type_names).with_default(
+                    PROJECT_TYPES
+                        .iter()
+                        .position(|kind| *kind == config.project_type)
+                        .unwrap_or(0),
+                ),
+            )
+            .input(
+                "description",
+                Input::new(t!("new-prompt-description"))
+                    .with_initial_text(config.description.clone().unwrap_or_default())
+                    .allow_empty(true),
+            )
+            .input(
+                "author",
+                Input::new(t!("new-prompt-author"))
+                    .with_initial_text(
+                        config
+                            .author
+                            .clone()
+                            .or_else(git_author)
+                            .unwrap_or_default(),
+                    )
+                    .allow_empty(true),
+            )
+            .select(
+                "license",
+                Select::new(t!("new-prompt-license"), license_items).with_default(
+                    config
+                        .license
+                        .as_ref()
+                        .and_then(|license| licenses.iter().position(|kind| kind == license))
+                        .unwrap_or(licenses.len()),
+                ),
+            )
+            .confirm(
+                "git",
+                Confirm::new(t!("new-prompt-git")).with_default(config.git),
+            )
+            .confirm(
+                "ci",
+                Confirm::new(t!("new-prompt-ci")).with_default(config.ci),
+            )
+    }
+
+    fn apply(&mut self, answers: &Answers) -> tram_core::AppResult<()> {
+        let licenses = self.licenses();
+        let config = &mut self.0;
+
+        if let Some(selected) = answers.index("type") {
+            config.project_type = PROJECT_TYPES[selected].clone();
+        }
+        if let Some(description) = answers.text("description") {
+            config.description = Some(description.trim().to_string()).filter(|d| !d.is_empty());
+        }
+        if let Some(author) = answers.text("author") {
+            config.author = Some(author.trim().to_string()).filter(|a| !a.is_empty());
+        }
+        if let Some(selected) = answers.index("license") {
+            config.license = licenses.get(selected).cloned();
+        }
+        config.git = answers.bool("git").unwrap_or(config.git);
+        config.ci = answers.bool("ci").unwrap_or(config.ci);
+
+        Ok(())
+    }
+}
+
+/// The git identity as `Name <email>`, or just the name without an email.
+fn git_author() -> Option<String> {
+    let git_config = |key: &str| {
+        let output = std::process::Command::new("git")
+            .args(["config", "--get", key])
+            .stdin(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .output()
+            .ok()?;
+        let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !value.is_empty()).then_some(value)
+    };
+
+    let name = git_config("user.name")?;
+    Some(match git_config("user.email") {
+        Some(email) => format!("{} <{}>", name, email),
+        None => name,
+    })
+}
+
+/// Ask for the project settings, starting from the values in `config`.
+pub fn prompt(prompter: &dyn Prompter, config: &mut InitConfig) -> tram_core::AppResult<()> {
+    ProjectSettings(config).prompt(prompter)
+}
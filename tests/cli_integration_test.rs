@@ -6,6 +6,7 @@
 mod common;
 
 use common::{FileAssertions, TempDir, TramCommand, init_tests};
+use tram_test::{MonorepoFixture, ProjectFixture, RustProjectFixture};
 
 #[test]
 fn test_cli_help() {
@@ -68,12 +69,48 @@ fn test_subcommand_help() {
 fn test_config_command() {
     init_tests();
 
-    let output = TramCommand::new().args(["config"]).assert_success();
+    let output = TramCommand::new()
+        .args(["--format", "table", "config"])
+        .assert_success();
+
+    output.assert_stdout_contains("Current configuration:");
+    output.assert_stdout_matches(r"Setting\s+│\s+Value");
+    output.assert_stdout_matches(r"Log level\s+│\s+\w+");
+    output.assert_stdout_matches(r"Output format\s+│\s+table");
+    output.assert_stdout_matches(r"Colors\s+│\s+(true|false)");
+}
+
+#[test]
+fn test_commands_ignore_the_users_config() {
+    init_tests();
+
+    let home = TempDir::new("user-config-test").unwrap();
+    let user_config = home.path().join(".config").join("tram");
+    std::fs::create_dir_all(&user_config).unwrap();
+    std::fs::write(user_config.join("config.toml"), "tableBorder = \"none\"\n").unwrap();
+
+    let config = TramCommand::new()
+        .env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path().join(".config"))
+        .args(["--format", "json", "config"])
+        .assert_success();
+    let config: serde_json::Value = serde_json::from_str(config.stdout()).unwrap();
+
+    assert_eq!(config["tableBorder"], "unicode");
+}
+
+#[test]
+fn test_config_command_without_a_data_directory() {
+    init_tests();
+
+    let output = TramCommand::new()
+        .env_remove("HOME")
+        .env_remove("XDG_DATA_HOME")
+        .env_remove("TRAM_DATA_DIR")
+        .args(["--format", "table", "config"])
+        .assert_success();
 
     output.assert_stdout_contains("Current configuration:");
-    output.assert_stdout_contains("Log level:");
-    output.assert_stdout_contains("Output format:");
-    output.assert_stdout_contains("Colors:");
 }
 
 #[test]
@@ -90,256 +127,2634 @@ fn test_workspace_command_no_workspace() {
 }
 
 #[test]
-fn test_workspace_command_with_workspace() {
+fn test_workspace_graph() {
     init_tests();
 
-    let output = TramCommand::new().args(["workspace"]).assert_success();
+    let temp_dir = TempDir::new("workspace-graph-test").unwrap();
+    let root = temp_dir.path();
+    MonorepoFixture::cargo()
+        .member(RustProjectFixture::new("core").library())
+        .member(RustProjectFixture::new("cli").path_dependency("core", "../core"))
+        .write_to(root)
+        .unwrap();
 
-    output.assert_stdout_contains("Workspace root:");
-    output.assert_stdout_contains("Project type:");
+    let output = TramCommand::new()
+        .current_dir(root)
+        .args(["workspace", "graph"])
+        .assert_success();
+    output.assert_stdout_contains("    \"cli\" -> \"core\";");
+
+    let output = TramCommand::new()
+        .current_dir(root)
+        .args(["workspace", "graph", "--format", "mermaid"])
+        .assert_success();
+    assert!(output.stdout().starts_with("graph LR\n"));
+    output.assert_stdout_contains("    m0 --> m1");
+
+    let output = TramCommand::new()
+        .current_dir(root)
+        .args(["workspace", "graph", "--format", "json"])
+        .assert_success();
+    let graph: serde_json::Value = serde_json::from_str(output.stdout()).unwrap();
+    assert_eq!(graph["members"][0]["name"], "cli");
+    assert_eq!(graph["members"][0]["path"], "crates/cli");
+    assert_eq!(graph["members"][0]["dependencies"][0], "core");
 }
 
 #[test]
-fn test_workspace_command_detailed() {
+fn test_workspace_members() {
     init_tests();
 
+    let temp_dir = TempDir::new("workspace-members-test").unwrap();
+    let root = temp_dir.path();
+    std::fs::write(
+        root.join("package.json"),
+        r#"{"private": true, "workspaces": ["packages/*"]}"#,
+    )
+    .unwrap();
+    std::fs::create_dir_all(root.join("packages/ui")).unwrap();
+    std::fs::write(root.join("packages/ui/package.json"), r#"{"name": "ui"}"#).unwrap();
+    std::fs::write(root.join("go.work"), "go 1.21\n\nuse ./api\n").unwrap();
+    std::fs::create_dir_all(root.join("api")).unwrap();
+    std::fs::write(root.join("api/go.mod"), "module example.com/api\n").unwrap();
+
     let output = TramCommand::new()
-        .args(["workspace", "--detailed"])
+        .current_dir(root)
+        .args(["--format", "plain", "workspace", "--members"])
         .assert_success();
+    tram_test::snapshot!("workspace_members")
+        .with_path(root, "[ROOT]")
+        .assert(output.stdout());
 
-    output.assert_stdout_contains("Workspace root:");
-    output.assert_stdout_contains("Project type:");
-    output.assert_stdout_contains("Ignore patterns:");
+    let output = TramCommand::new()
+        .current_dir(root)
+        .args(["--format", "json", "workspace", "--members"])
+        .assert_success();
+    let workspace: serde_json::Value = serde_json::from_str(output.stdout()).unwrap();
+    assert_eq!(workspace["members"][0]["path"], "api");
+    assert_eq!(workspace["members"][0]["projectType"], "go");
+    assert_eq!(workspace["members"][1]["path"], "packages/ui");
+    assert_eq!(workspace["members"][1]["projectType"], "nodejs");
 }
 
 #[test]
-fn test_examples_command() {
+fn test_json_error_output() {
     init_tests();
 
-    let examples = [
-        "basic-command",
-        "async-operations",
-        "config-usage",
-        "progress-indicators",
-        "interactive-prompts",
-        "file-operations",
-    ];
-
-    for example in &examples {
-        let output = TramCommand::new()
-            .args(["examples", example])
-            .assert_success();
+    let output = TramCommand::new()
+        .current_dir("/tmp")
+        .args(["--format", "json", "workspace"])
+        .assert_failure();
 
-        output.assert_stdout_contains("Example");
-        output.assert_stdout_contains("Key features demonstrated:");
-        output.assert_stdout_contains("For full interactive example, run:");
-    }
+    let line = output
+        .stderr()
+        .lines()
+        .find(|line| line.starts_with("{\"error\""))
+        .expect("No JSON error on stderr");
+    let error: serde_json::Value = serde_json::from_str(line).unwrap();
+    assert_eq!(error["error"]["code"], "tram::workspace_not_found");
+    assert_eq!(error["error"]["exitCode"], 4);
+    assert!(error["error"]["help"].is_string());
+    assert!(!output.stderr().contains("Error: "));
+
+    // Other formats keep the human report
+    let output = TramCommand::new()
+        .current_dir("/tmp")
+        .args(["--format", "plain", "workspace"])
+        .assert_failure();
+    output.assert_stderr_contains("Error: ");
 }
 
 #[test]
-fn test_new_command_dry_run() {
+fn test_did_you_mean_suggestions() {
     init_tests();
 
-    let temp_dir = TempDir::new("new-command-test").unwrap();
+    let output = TramCommand::new().args(["wrokspace"]).assert_failure();
+    output.assert_stderr_contains("did you mean 'workspace'?");
 
-    let output = TramCommand::new()
-        .current_dir(temp_dir.path())
-        .args(["new", "test-project", "--skip-prompts"])
-        .assert_success();
+    let temp_dir = TempDir::new("did-you-mean-test").unwrap();
+    let config_file = temp_dir.path().join("tram.toml");
 
-    output.assert_stdout_contains("Created new");
-    output.assert_stdout_contains("test-project");
+    std::fs::write(&config_file, "[tasks.test]\ncommand = \"true\"\n").unwrap();
+    let output = TramCommand::new()
+        .arg("--config")
+        .arg(&config_file)
+        .args(["--format", "plain", "run", "tset"])
+        .assert_failure();
+    output.assert_stderr_contains("did you mean 'test'?");
 
-    // Verify project directory was created
-    FileAssertions::assert_dir_exists(temp_dir.path().join("test-project"));
+    std::fs::write(&config_file, "outputFormt = \"plain\"\n").unwrap();
+    let output = TramCommand::new()
+        .arg("--config")
+        .arg(&config_file)
+        .args(["--format", "plain", "config"])
+        .assert_failure();
+    output.assert_stderr_contains("did you mean `outputFormat`?");
 }
 
 #[test]
-fn test_new_command_with_options() {
+fn test_exit_codes() {
     init_tests();
 
-    let temp_dir = TempDir::new("new-options-test").unwrap();
+    let output = TramCommand::new()
+        .args(["--format", "json", "exit-codes"])
+        .assert_success();
+    let list: serde_json::Value =
+        serde_json::from_str(output.stdout()).expect("exit-codes should be valid JSON");
+    assert!(
+        list["codes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|c| c["code"] == 4 && c["name"] == "workspace-not-found")
+    );
+
+    // Failures exit with their documented code
+    let output = TramCommand::new()
+        .current_dir("/tmp")
+        .args(["workspace"])
+        .output()
+        .expect("Failed to execute command");
+    assert_eq!(output.status.code(), Some(4));
 
+    let temp_dir = TempDir::new("exit-code-conflict-test").unwrap();
+    std::fs::create_dir(temp_dir.path().join("taken")).unwrap();
     let output = TramCommand::new()
         .current_dir(temp_dir.path())
-        .args([
-            "new",
-            "test-nodejs-project",
-            "--project-type",
-            "nodejs",
-            "--description",
-            "A test Node.js project",
-            "--skip-prompts",
-        ])
-        .assert_success();
+        .args(["new", "taken", "--skip-prompts"])
+        .output()
+        .expect("Failed to execute command");
+    assert_eq!(output.status.code(), Some(5));
 
-    output.assert_stdout_contains("Created new Node.js project: test-nodejs-project");
-    output.assert_stdout_contains("Description: A test Node.js project");
+    let output = TramCommand::new()
+        .args(["--config", "missing.toml", "config"])
+        .output()
+        .expect("Failed to execute command");
+    assert_eq!(output.status.code(), Some(3));
 }
 
 #[test]
-fn test_generate_command_to_stdout() {
+fn test_theme_from_config() {
     init_tests();
 
-    let output = TramCommand::new()
+    let temp_dir = TempDir::new("theme-test").unwrap();
+    let config_file = temp_dir.path().join("tram.toml");
+    std::fs::write(&config_file, "[theme]\nheading = \"underline magenta\"\n").unwrap();
+    let config = config_file.to_str().unwrap();
+
+    TramCommand::new()
         .args([
-            "generate",
-            "--template-type",
-            "command",
-            "backup",
-            "--description",
-            "Backup command template",
+            "--config", config, "--color", "always", "--format", "table", "env",
         ])
-        .assert_success();
+        .assert_success()
+        .assert_stdout_contains("\x1b[4;35mName\x1b[0m");
 
-    output.assert_stdout_contains("Generated Command template for 'backup':");
-    output.assert_stdout_contains("File path:");
-    output.assert_stdout_contains("To write to filesystem, add the --write flag");
+    std::fs::write(&config_file, "[theme]\nerror = \"scarlet\"\n").unwrap();
+    let output = TramCommand::new()
+        .args(["--config", config, "env"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("theme.error: Invalid style: scarlet")
+    );
 }
 
 #[test]
-fn test_generate_command_with_write() {
+fn test_log_file() {
     init_tests();
 
-    let temp_dir = TempDir::new("generate-write-test").unwrap();
+    let temp_dir = TempDir::new("log-file-test").unwrap();
+    let log_file = temp_dir.path().join("logs/tram.log");
 
-    let output = TramCommand::new()
-        .current_dir(temp_dir.path())
-        .args([
-            "generate",
-            "--template-type",
-            "command",
-            "backup",
-            "--write",
-        ])
+    // The file gets debug logs even though stderr only shows errors
+    TramCommand::new()
+        .args(["--log-level", "error", "--format", "plain", "config"])
+        .arg("--log-file")
+        .arg(&log_file)
         .assert_success();
+    FileAssertions::assert_file_contains(&log_file, "Starting Tram CLI application");
+    FileAssertions::assert_file_contains(&log_file, "DEBUG");
 
-    output.assert_stdout_contains("Generated Command template: backup");
+    // Failures point at the file
+    let output = TramCommand::new()
+        .current_dir("/tmp")
+        .env("TRAM_LOG_FILE", &log_file)
+        .args(["workspace"])
+        .assert_failure();
+    output.assert_stderr_contains("Full logs written to");
+    FileAssertions::assert_file_contains(&log_file, "Command failed");
+}
 
-    // Template should be written to filesystem
-    // (The exact file location depends on the template implementation)
+#[test]
+fn test_log_file_rotation() {
+    init_tests();
+
+    let temp_dir = TempDir::new("log-rotation-test").unwrap();
+    let log_file = temp_dir.path().join("tram.log");
+
+    // A run logs more than 1KB, so older logs are moved aside
+    TramCommand::new()
+        .args(["config"])
+        .arg("--log-file")
+        .arg(&log_file)
+        .env("TRAM_LOG_ROTATION", "1KB")
+        .assert_success();
+    FileAssertions::assert_file_contains(&log_file, "DEBUG");
+    FileAssertions::assert_file_contains(temp_dir.path().join("tram.log.1"), "DEBUG");
+
+    // Bad values are rejected when the config loads, like any other setting
+    TramCommand::new()
+        .args(["config"])
+        .env("TRAM_LOG_ROTATION", "weekly")
+        .assert_failure()
+        .assert_stderr_contains(
+            "Invalid configuration: Invalid environment variable TRAM_LOG_ROTATION",
+        );
 }
 
 #[test]
-fn test_init_legacy_command() {
+fn test_shell_hook() {
     init_tests();
 
-    let temp_dir = TempDir::new("init-legacy-test").unwrap();
+    let output = TramCommand::new().args(["hook", "bash"]).assert_success();
+    output.assert_stdout_contains("_tram_hook() {");
+    output.assert_stdout_contains("tram hook bash --env");
+    output.assert_stdout_contains("PROMPT_COMMAND");
 
     let output = TramCommand::new()
-        .current_dir(temp_dir.path())
-        .args(["init", "legacy-project"])
+        .args(["hook", "zsh", "--env"])
         .assert_success();
+    output.assert_stdout_contains("export TRAM_WORKSPACE='");
+    output.assert_stdout_contains("export TRAM_PROJECT_TYPE='rust'");
 
-    output.assert_stdout_contains("Initializing project: legacy-project");
-    output.assert_stdout_contains("Project 'legacy-project' initialized!");
+    let output = TramCommand::new()
+        .current_dir("/tmp")
+        .args(["hook", "fish", "--env"])
+        .assert_success();
+    assert_eq!(
+        output.stdout(),
+        "set -e TRAM_WORKSPACE\nset -e TRAM_PROJECT_TYPE\n"
+    );
 }
 
 #[test]
-fn test_init_verbose() {
+fn test_profile() {
     init_tests();
 
-    let temp_dir = TempDir::new("init-verbose-test").unwrap();
+    let temp_dir = TempDir::new("profile-test").unwrap();
+    let config_file = temp_dir.path().join("tram.toml");
+    std::fs::write(
+        &config_file,
+        "outputFormat = \"json\"\n\n[profiles.ci]\noutputFormat = \"plain\"\n",
+    )
+    .unwrap();
 
     let output = TramCommand::new()
-        .current_dir(temp_dir.path())
-        .args(["init", "verbose-project", "--verbose"])
+        .arg("--config")
+        .arg(&config_file)
+        .args(["--profile", "ci", "config"])
         .assert_success();
+    output.assert_stdout_contains("Profile: ci");
+    output.assert_stdout_contains("Output format: plain");
 
-    output.assert_stdout_contains("Verbose mode enabled");
-    output.assert_stdout_contains("Workspace root:");
-    output.assert_stdout_contains("Config:");
+    let output = TramCommand::new()
+        .env("TRAM_PROFILE", "ci")
+        .arg("--config")
+        .arg(&config_file)
+        .arg("config")
+        .assert_success();
+    output.assert_stdout_contains("Profile: ci");
+
+    let output = TramCommand::new()
+        .arg("--config")
+        .arg(&config_file)
+        .args(["--profile", "release", "config"])
+        .assert_failure();
+    output.assert_stderr_contains("Unknown profile 'release'");
 }
 
 #[test]
-fn test_global_options_log_level() {
+fn test_workspace_command_with_workspace() {
     init_tests();
 
     let output = TramCommand::new()
-        .args(["--log-level", "debug", "config"])
+        .args(["--format", "plain", "workspace"])
         .assert_success();
 
-    // With debug level, should see debug output in logs
-    // The exact format depends on the logging configuration
-    output.assert_stdout_contains("Current configuration:");
+    output.assert_stdout_contains("Workspace root:");
+    output.assert_stdout_contains("Project type:");
 }
 
 #[test]
-fn test_global_options_format() {
+fn test_workspace_command_detailed() {
     init_tests();
 
-    // Test JSON format
     let output = TramCommand::new()
-        .args(["--format", "json", "config"])
+        .args(["--format", "plain", "workspace", "--detailed"])
         .assert_success();
 
-    output.assert_stdout_contains("Current configuration:");
+    output.assert_stdout_contains("Workspace root:");
+    output.assert_stdout_contains("Project type:");
+    output.assert_stdout_contains("Ignore patterns:");
+}
+
+#[test]
+fn test_workspace_command_table() {
+    init_tests();
 
-    // Test YAML format
     let output = TramCommand::new()
-        .args(["--format", "yaml", "config"])
+        .args(["--format", "table", "workspace", "--detailed"])
         .assert_success();
 
-    output.assert_stdout_contains("Current configuration:");
+    output.assert_stdout_matches(r"│ Workspace root\s+│ ");
+    output.assert_stdout_matches(r"│ Project type\s+│ rust\s+│");
+    output.assert_stdout_contains("┌");
 
-    // Test Table format (default)
     let output = TramCommand::new()
-        .args(["--format", "table", "config"])
+        .env("TRAM_TABLE_BORDER", "ascii")
+        .args(["--format", "table", "workspace"])
         .assert_success();
 
-    output.assert_stdout_contains("Current configuration:");
+    output.assert_stdout_matches(r"\| Project type\s+\| rust\s+\|");
+    assert!(!output.stdout().contains("│"));
 }
 
 #[test]
-fn test_global_options_no_color() {
+fn test_workspace_command_json() {
     init_tests();
 
     let output = TramCommand::new()
-        .args(["--no-color", "config"])
+        .args(["--format", "json", "workspace"])
         .assert_success();
 
-    output.assert_stdout_contains("Current configuration:");
-    // With no-color flag, output should not contain ANSI color codes
-    // This would require more sophisticated testing to verify properly
+    // The whole document, without --detailed or --members
+    let result: serde_json::Value =
+        serde_json::from_str(output.stdout()).expect("workspace should be valid JSON");
+
+    assert!(result["root"].is_string());
+    assert_eq!(result["projectType"], "rust");
+    assert!(result["ignorePatterns"].is_array());
+    assert!(result["members"].is_array());
+    // `null` when the checkout isn't a git repository
+    assert!(result["git"].is_object() || result.get("git") == Some(&serde_json::Value::Null));
 }
 
 #[test]
-fn test_invalid_subcommand() {
+fn test_plain_format() {
     init_tests();
 
     let output = TramCommand::new()
-        .args(["invalid-command"])
-        .assert_failure();
+        .args(["--format", "plain", "config"])
+        .assert_success();
 
-    output.assert_stderr_contains("unrecognized subcommand 'invalid-command'");
+    output.assert_stdout_contains("Output format: plain");
+    assert!(!output.stdout().contains("Setting"));
 }
 
 #[test]
-fn test_invalid_global_option() {
+fn test_examples_command() {
+    init_tests();
+
+    let examples = [
+        "basic-command",
+        "async-operations",
+        "config-usage",
+        "progress-indicators",
+        "interactive-prompts",
+        "file-operations",
+    ];
+
+    for example in &examples {
+        let output = TramCommand::new()
+            .args(["examples", example])
+            .assert_success();
+
+        output.assert_stdout_contains("Example");
+        output.assert_stdout_contains("Key features demonstrated:");
+        output.assert_stdout_contains("For full interactive example, run:");
+    }
+}
+
+#[test]
+fn test_examples_list_and_interactive() {
     init_tests();
 
     let output = TramCommand::new()
-        .args(["--invalid-option"])
-        .assert_failure();
+        .args(["--format", "json", "examples", "--list"])
+        .assert_success();
+    let json: serde_json::Value = serde_json::from_str(output.stdout()).unwrap();
+    let examples = json["examples"].as_array().unwrap();
+    assert_eq!(examples.len(), 6);
+    assert_eq!(examples[0]["name"], "basic-command");
+    assert_eq!(examples[0]["program"], "basic_command");
 
-    output.assert_stderr_contains("unexpected argument '--invalid-option'");
+    let output = TramCommand::new()
+        .args(["examples", "--interactive", "file-operations"])
+        .assert_success();
+    output.assert_stdout_contains("notes.txt says: Remember to write tests");
+    output.assert_stdout_contains("Cleaned up");
+
+    // Without a terminal there's no prompt for a choice, so it's missing
+    let output = TramCommand::new()
+        .args(["--format", "plain", "examples", "--interactive"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Missing required input: EXAMPLE"));
 }
 
 #[test]
-fn test_missing_required_argument() {
+fn test_non_interactive_takes_defaults() {
     init_tests();
 
-    let output = TramCommand::new().args(["new"]).assert_failure();
+    let temp_dir = TempDir::new("non-interactive-test").unwrap();
 
-    output.assert_stderr_contains("required arguments were not provided");
+    // --yes and CI both turn prompts off; `new` falls back to its defaults
+    for (flag, ci) in [("--yes", ""), ("--non-interactive", ""), ("-y", "true")] {
+        TramCommand::new()
+            .current_dir(temp_dir.path())
+            .env("CI", ci)
+            .args(["--format", "plain", "--dry-run", flag, "new", "my-project"])
+            .assert_success()
+            .assert_stdout_contains("my-project");
+    }
 }
 
 #[test]
-fn test_cli_version_info() {
+fn test_new_command_dry_run() {
     init_tests();
 
-    let output = TramCommand::new().args(["--version"]).assert_success();
+    let temp_dir = TempDir::new("new-command-test").unwrap();
 
-    output.assert_stdout_contains("tram");
-    output.assert_stdout_contains("0.1.0");
+    let output = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["new", "test-project", "--skip-prompts"])
+        .assert_success();
+
+    output.assert_stdout_contains("Created new");
+    output.assert_stdout_contains("test-project");
+
+    // Verify project directory was created
+    FileAssertions::assert_dir_exists(temp_dir.path().join("test-project"));
+}
+
+#[test]
+fn test_new_command_with_options() {
+    init_tests();
+
+    let temp_dir = TempDir::new("new-options-test").unwrap();
+
+    let output = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args([
+            "new",
+            "test-nodejs-project",
+            "--project-type",
+            "nodejs",
+            "--description",
+            "A test Node.js project",
+            "--skip-prompts",
+        ])
+        .assert_success();
+
+    output.assert_stdout_contains("Created new Node.js project: test-nodejs-project");
+    output.assert_stdout_contains("Description: A test Node.js project");
+}
+
+#[test]
+fn test_new_command_without_terminal_uses_flags() {
+    init_tests();
+
+    let temp_dir = TempDir::new("new-no-tty-test").unwrap();
+
+    // No --skip-prompts, but stdin isn't a terminal, so nothing prompts
+    let output = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args([
+            "--format",
+            "plain",
+            "new",
+            "tooling",
+            "--project-type",
+            "python",
+            "--author",
+            "Jane Doe <jane@example.com>",
+            "--license",
+            "Apache-2.0",
+            "--git",
+            "--ci",
+        ])
+        .stdin("")
+        .assert_success();
+
+    output.assert_stdout_contains("Author: Jane Doe");
+    output.assert_stdout_contains("License: Apache-2.0");
+
+    let project = temp_dir.path().join("tooling");
+    FileAssertions::assert_file_contains(
+        project.join("pyproject.toml"),
+        "license = \"Apache-2.0\"",
+    );
+    FileAssertions::assert_file_contains(
+        project.join("pyproject.toml"),
+        "authors = [{ name = \"Jane Doe\", email = \"jane@example.com\" }]",
+    );
+    FileAssertions::assert_file_contains(project.join("LICENSE"), "Apache License");
+    FileAssertions::assert_file_contains(project.join(".gitignore"), "__pycache__/");
+    FileAssertions::assert_file_contains(
+        project.join(".github/workflows/ci.yml"),
+        "actions/setup-python",
+    );
+    FileAssertions::assert_dir_exists(project.join(".git"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_new_command_wizard_in_terminal() {
+    use tram_test::{Key, PtyTestRunner};
+
+    init_tests();
+
+    let temp_dir = TempDir::new("new-wizard-test").unwrap();
+
+    let mut session = PtyTestRunner::new(env!("CARGO_BIN_EXE_tram"))
+        .args([
+            "--format",
+            "plain",
+            "new",
+            "wizard-app",
+            "--author",
+            "Jane Doe",
+        ])
+        .current_dir(temp_dir.path())
+        .env("NO_COLOR", "1")
+        .env("TRAM_LOG_LEVEL", "error")
+        .env("TRAM_NO_DAEMON", "1")
+        .spawn()
+        .unwrap();
+
+    // Node.js is the second project type
+    session.expect("Project type").unwrap();
+    session.send_key(Key::Down).unwrap();
+    session.send_key(Key::Enter).unwrap();
+    session.expect("Description").unwrap();
+    session.send_line("Scripted in a terminal").unwrap();
+    // --author is the answer's initial text
+    session.expect("Author").unwrap();
+    session.send_key(Key::Enter).unwrap();
+    // From "None" at the bottom up to MIT at the top
+    session.expect("License").unwrap();
+    for _ in 0..7 {
+        session.send_key(Key::Up).unwrap();
+    }
+    session.send_key(Key::Enter).unwrap();
+    session.expect("Initialize a git repository?").unwrap();
+    session.send_key(Key::Enter).unwrap();
+    session.expect("CI workflow?").unwrap();
+    session.send_key(Key::Enter).unwrap();
+    session.expect("Created new Node.js project").unwrap();
+
+    let output = session.wait().unwrap();
+    assert!(output.success(), "{}", output.output());
+
+    let project = temp_dir.path().join("wizard-app");
+    let package_json = project.join("package.json");
+    FileAssertions::assert_file_contains(
+        &package_json,
+        "\"description\": \"Scripted in a terminal\"",
+    );
+    FileAssertions::assert_file_contains(&package_json, "\"author\": \"Jane Doe\"");
+    FileAssertions::assert_file_contains(project.join("LICENSE"), "MIT License");
+    FileAssertions::assert_file_contains(project.join("LICENSE"), " Jane Doe\n");
+    assert!(!project.join(".git").exists());
+}
+
+#[test]
+fn test_upgrade_command() {
+    init_tests();
+
+    let temp_dir = TempDir::new("upgrade-command-test").unwrap();
+    TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["new", "app", "--skip-prompts"])
+        .assert_success();
+    let project = temp_dir.path().join("app");
+
+    let output = TramCommand::new()
+        .current_dir(&project)
+        .args(["--format", "plain", "upgrade"])
+        .assert_success();
+    output.assert_stdout_contains("Project is up to date");
+
+    // Pretend main.rs came from an older template and the user left it alone
+    let manifest_path = project.join(".tram/project.json");
+    let mut manifest: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&manifest_path).unwrap()).unwrap();
+    let old_main = "fn main() {\n    println!(\"Hi\");\n}\n";
+    manifest["files"]["src/main.rs"] = old_main.into();
+    std::fs::write(&manifest_path, manifest.to_string()).unwrap();
+    std::fs::write(project.join("src/main.rs"), old_main).unwrap();
+
+    let output = TramCommand::new()
+        .args(["--format", "json", "upgrade"])
+        .arg(project.to_str().unwrap())
+        .assert_success();
+    let report: serde_json::Value = serde_json::from_str(output.stdout()).unwrap();
+    assert_eq!(report["files"][0]["path"], "src/main.rs");
+    assert_eq!(report["files"][0]["status"], "updated");
+    FileAssertions::assert_file_contains(project.join("src/main.rs"), "Hello, world!");
+
+    let output = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["upgrade"])
+        .assert_failure();
+    output.assert_stderr_contains("Only projects created by `tram new` can be upgraded");
+}
+
+#[test]
+fn test_batch_from_stdin() {
+    init_tests();
+
+    let temp_dir = TempDir::new("batch-test").unwrap();
+
+    let output = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["--format", "json", "batch", "-"])
+        .stdin(
+            "# projects\n\
+             new one --project-type rust --skip-prompts\n\
+             [\"--dry-run\", \"new\", \"two\", \"--skip-prompts\"]\n\
+             \n\
+             new one --skip-prompts\n",
+        )
+        .assert_failure();
+
+    let result: serde_json::Value = serde_json::from_str(output.stdout()).unwrap();
+    assert_eq!(result["succeeded"], 2);
+    assert_eq!(result["failed"], 1);
+
+    let results = result["results"].as_array().unwrap();
+    assert_eq!(results[0]["line"], 2);
+    assert_eq!(results[1]["output"]["dryRun"], true);
+    assert_eq!(results[2]["line"], 5);
+    assert_eq!(results[2]["exitCode"], 5);
+    assert_eq!(results[2]["error"]["code"], "tram::conflict");
+
+    FileAssertions::assert_dir_exists(temp_dir.path().join("one"));
+    assert!(!temp_dir.path().join("two").exists());
+}
+
+#[test]
+fn test_batch_rejects_commands_it_cannot_capture() {
+    init_tests();
+
+    let temp_dir = TempDir::new("batch-reject-test").unwrap();
+
+    let output = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["--format", "json", "batch", "-"])
+        .stdin("doctor\ncompletions bash\nworkspce\n")
+        .assert_failure();
+
+    // One document, with nothing printed around it
+    let result: serde_json::Value = serde_json::from_str(output.stdout()).unwrap();
+    assert_eq!(result["failed"], 3);
+
+    let results = result["results"].as_array().unwrap();
+    assert_eq!(results[0]["exitCode"], 2);
+    assert!(
+        results[0]["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("can't run in batch mode")
+    );
+    assert_eq!(results[1]["exitCode"], 2);
+    assert_eq!(
+        results[2]["error"]["message"],
+        "unrecognized subcommand 'workspce'"
+    );
+    assert_eq!(
+        results[2]["error"]["help"],
+        "tip: did you mean 'workspace'?"
+    );
+}
+
+#[test]
+fn test_dry_run_reports_without_writing() {
+    init_tests();
+
+    let temp_dir = TempDir::new("dry-run-test").unwrap();
+
+    let output = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args([
+            "--format",
+            "plain",
+            "--dry-run",
+            "new",
+            "planned",
+            "--project-type",
+            "rust",
+            "--skip-prompts",
+        ])
+        .assert_success();
+
+    output.assert_stdout_contains("Dry run, nothing was written");
+    output.assert_stdout_contains("create directory");
+    output.assert_stdout_contains("Cargo.toml");
+    output.assert_stdout_contains("main.rs");
+    assert!(!temp_dir.path().join("planned").exists());
+
+    let output = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args([
+            "--format",
+            "plain",
+            "generate",
+            "--template-type",
+            "command",
+            "deploy",
+            "--write",
+            "--dry-run",
+        ])
+        .assert_success();
+
+    output.assert_stdout_contains("Dry run, nothing was written");
+    assert_eq!(FileAssertions::count_files(temp_dir.path(), ".*"), 0);
+}
+
+#[test]
+fn test_generate_command_to_stdout() {
+    init_tests();
+
+    let output = TramCommand::new()
+        .args([
+            "--format",
+            "table",
+            "generate",
+            "--template-type",
+            "command",
+            "backup",
+            "--description",
+            "Backup command template",
+        ])
+        .assert_success();
+
+    output.assert_stdout_contains("Generated Command template for 'backup':");
+    output.assert_stdout_contains("File path:");
+    output.assert_stdout_contains("To write to filesystem, add the --write flag");
+}
+
+#[test]
+fn test_generate_command_yaml() {
+    init_tests();
+
+    let output = TramCommand::new()
+        .args([
+            "--format",
+            "yaml",
+            "generate",
+            "--template-type",
+            "command",
+            "backup",
+        ])
+        .assert_success();
+
+    output.assert_stdout_contains("name: backup");
+    output.assert_stdout_contains("templateType: Command");
+    output.assert_stdout_contains("written: false");
+}
+
+#[test]
+fn test_generate_command_with_write() {
+    init_tests();
+
+    let temp_dir = TempDir::new("generate-write-test").unwrap();
+
+    let output = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args([
+            "generate",
+            "--template-type",
+            "command",
+            "backup",
+            "--write",
+        ])
+        .assert_success();
+
+    output.assert_stdout_contains("Generated Command template: backup");
+
+    // Template should be written to filesystem
+    // (The exact file location depends on the template implementation)
+}
+
+#[test]
+fn test_generate_diff_and_force_on_existing_file() {
+    init_tests();
+
+    let temp_dir = TempDir::new("generate-diff-test").unwrap();
+    let generate = |extra: &[&str]| {
+        TramCommand::new()
+            .current_dir(temp_dir.path())
+            .args(["--format", "plain", "generate", "backup"])
+            .args(extra)
+    };
+
+    generate(&["--write"]).assert_success();
+    let file = temp_dir.path().join("src/commands/backup.rs");
+    let generated = std::fs::read_to_string(&file).unwrap();
+    std::fs::write(&file, generated.replace("BackupArgs", "SavedArgs")).unwrap();
+
+    generate(&["--write"])
+        .assert_failure()
+        .assert_stderr_contains("Already exists");
+
+    let output = generate(&["--write", "--diff"]).assert_success();
+    output.assert_stdout_contains("backup.rs already exists; generating would change it:");
+    output.assert_stdout_contains("--- a/src/commands/backup.rs\n+++ b/src/commands/backup.rs\n");
+    output.assert_stdout_contains("+pub struct BackupArgs");
+    output.assert_stdout_contains("-pub struct SavedArgs");
+    FileAssertions::assert_file_contains(&file, "SavedArgs");
+
+    generate(&["--write", "--force"]).assert_success();
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), generated);
+
+    generate(&["--diff"])
+        .assert_success()
+        .assert_stdout_contains("backup.rs already matches the generated template");
+}
+
+#[test]
+fn test_generate_command_with_register() {
+    init_tests();
+
+    let temp_dir = TempDir::new("generate-register-test").unwrap();
+    let src = temp_dir.path().join("src");
+    std::fs::create_dir_all(&src).unwrap();
+    std::fs::write(
+        src.join("main.rs"),
+        "enum Commands {\n    // tram:generate:commands\n}\n",
+    )
+    .unwrap();
+
+    TramCommand::new()
+        .args([
+            "--format",
+            "plain",
+            "generate",
+            "deploy",
+            "--write",
+            "--register",
+        ])
+        .arg("--target-dir")
+        .arg(temp_dir.path())
+        .assert_success()
+        .assert_stdout_contains("Registered the command in")
+        .assert_stdout_contains("Couldn't find `// tram:generate:dispatch`");
+
+    let modules = std::fs::read_to_string(src.join("commands").join("mod.rs")).unwrap();
+    assert!(modules.starts_with("pub mod deploy;\n"));
+
+    let main = std::fs::read_to_string(src.join("main.rs")).unwrap();
+    assert!(main.contains("    Deploy(crate::commands::deploy::DeployArgs),\n"));
+
+    // --register needs --write
+    TramCommand::new()
+        .args(["generate", "other", "--register"])
+        .arg("--target-dir")
+        .arg(temp_dir.path())
+        .assert_failure();
+}
+
+#[test]
+fn test_generate_custom_template() {
+    init_tests();
+
+    let temp_dir = TempDir::new("generate-custom-test").unwrap();
+    let templates = temp_dir.path().join(".tram").join("templates");
+    std::fs::create_dir_all(&templates).unwrap();
+    RustProjectFixture::new("generate-custom-test")
+        .write_to(temp_dir.path())
+        .unwrap();
+    std::fs::write(
+        templates.join("handler.rs.hbs"),
+        "pub struct {{name_pascal}}Handler; // {{description}}\n",
+    )
+    .unwrap();
+
+    TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args([
+            "generate",
+            "--template-type",
+            "custom:handler",
+            "sign-up",
+            "--description",
+            "Handles sign ups",
+            "--write",
+        ])
+        .assert_success()
+        .assert_stdout_contains("Generated Custom (handler) template: sign-up");
+
+    let generated = std::fs::read_to_string(temp_dir.path().join("sign-up.rs")).unwrap();
+    assert_eq!(generated, "pub struct SignUpHandler; // Handles sign ups\n");
+
+    TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["generate", "--template-type", "custom:missing", "other"])
+        .assert_failure()
+        .assert_stderr_contains("found: handler");
+}
+
+#[test]
+fn test_init_legacy_command() {
+    init_tests();
+
+    let temp_dir = TempDir::new("init-legacy-test").unwrap();
+
+    let output = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["init", "legacy-project"])
+        .assert_success();
+
+    output.assert_stdout_contains("Initializing project: legacy-project");
+    output.assert_stdout_contains("Project 'legacy-project' initialized!");
+}
+
+#[test]
+fn test_init_warns_deprecated() {
+    init_tests();
+
+    let temp_dir = TempDir::new("init-deprecated-test").unwrap();
+
+    let output = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["init", "old-project"])
+        .assert_success();
+
+    output.assert_stderr_contains("warning: The `init` command is deprecated");
+    output.assert_stderr_contains("Use `tram new <name> --project-type generic` instead.");
+
+    let output = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .env("TRAM_NO_DEPRECATION_WARNINGS", "1")
+        .args(["init", "quiet-project"])
+        .assert_success();
+
+    assert!(!output.stderr().contains("deprecated"));
+}
+
+#[test]
+fn test_config_file_warns_deprecated_keys() {
+    init_tests();
+
+    let temp_dir = TempDir::new("config-deprecated-test").unwrap();
+    std::fs::write(
+        temp_dir.path().join("tram.toml"),
+        "workspaceRoot = \"/srv/app\"\ncolor = false\n",
+    )
+    .unwrap();
+
+    let output = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["config", "get", "color"])
+        .assert_success();
+
+    output.assert_stdout_contains("false");
+    output.assert_stderr_contains(
+        "warning: The `workspaceRoot` config key is deprecated since 0.1.0 and will be removed in 0.3.0.",
+    );
+}
+
+#[test]
+fn test_deprecations_command_json() {
+    init_tests();
+
+    let output = TramCommand::new()
+        .args(["--format", "json", "deprecations"])
+        .assert_success();
+
+    let entries: serde_json::Value =
+        serde_json::from_str(output.stdout()).expect("deprecations should be valid JSON");
+
+    assert_eq!(entries[0]["kind"], "command");
+    assert_eq!(entries[0]["name"], "init");
+    assert_eq!(entries[0]["removal"], "0.3.0");
+}
+
+#[test]
+fn test_doctor_json() {
+    init_tests();
+
+    let output = TramCommand::new()
+        .args(["--format", "json", "doctor"])
+        .env("TRAM_CACHE_DIR", "test-tmp/doctor-cache")
+        .assert_success();
+
+    let report: serde_json::Value =
+        serde_json::from_str(output.stdout()).expect("doctor should be valid JSON");
+    let checks = report["checks"].as_array().unwrap();
+
+    let workspace = checks.iter().find(|c| c["name"] == "Workspace").unwrap();
+    assert_eq!(workspace["status"], "pass");
+
+    // The repo is a Rust workspace, so cargo is checked
+    assert!(checks.iter().any(|c| c["name"] == "Tool: cargo"));
+
+    let cache = checks
+        .iter()
+        .find(|c| c["name"] == "Cache directory")
+        .unwrap();
+    assert_eq!(cache["status"], "pass");
+}
+
+#[test]
+fn test_doctor_warns_outside_workspace() {
+    init_tests();
+
+    TramCommand::new()
+        .args(["--format", "plain", "doctor"])
+        .current_dir(std::env::temp_dir())
+        .assert_success()
+        .assert_stdout_contains("! Workspace: No workspace detected")
+        .assert_stdout_contains("Run tram from a project directory");
+}
+
+#[test]
+fn test_doctor_fails_when_a_tool_is_missing() {
+    init_tests();
+
+    let temp_dir = TempDir::new("doctor-missing-tool").unwrap();
+    std::fs::write(temp_dir.path().join("go.mod"), "module app\n").unwrap();
+
+    TramCommand::new()
+        .args(["--format", "plain", "doctor"])
+        .current_dir(temp_dir.path())
+        .env("PATH", temp_dir.path())
+        .assert_failure()
+        .assert_stdout_contains("✗ Tool: go")
+        .assert_stderr_contains("doctor checks failed");
+}
+
+#[test]
+fn test_env_shows_setting_sources() {
+    init_tests();
+
+    let output = TramCommand::new()
+        .args(["--format", "json", "--log-level", "warn", "env"])
+        .env("TRAM_TABLE_BORDER", "ascii")
+        .env("TRAM_SECRET_API_KEY", "hunter2")
+        .assert_success();
+
+    let report: serde_json::Value =
+        serde_json::from_str(output.stdout()).expect("env should be valid JSON");
+    let setting = |key: &str| {
+        report["settings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|s| s["key"] == key)
+            .unwrap()
+            .clone()
+    };
+
+    // The repo's tram.toml turns colors off; flags and env win over the file
+    assert!(
+        setting("color")["source"]["file"]
+            .as_str()
+            .unwrap()
+            .ends_with("tram.toml")
+    );
+    assert_eq!(setting("logLevel")["value"], "warn");
+    assert_eq!(setting("logLevel")["source"]["flag"], "--log-level");
+    assert_eq!(setting("tableBorder")["source"]["env"], "TRAM_TABLE_BORDER");
+    assert_eq!(setting("checkForUpdates")["source"], "default");
+
+    assert_eq!(report["variables"]["TRAM_TABLE_BORDER"], "ascii");
+    assert_eq!(report["variables"]["TRAM_SECRET_API_KEY"], "[redacted]");
+    assert!(report["paths"]["configFile"].is_string());
+    assert!(!output.stdout().contains("hunter2"));
+}
+
+#[test]
+fn test_config_layers() {
+    init_tests();
+
+    let temp_dir = TempDir::new("config-layers-test").unwrap();
+    let root = temp_dir.path();
+    let write = |path: &str, contents: &str| {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    };
+    write(
+        "etc/config.toml",
+        "tableBorder = \"ascii\"\noffline = true\n",
+    );
+    write("user/config.yaml", "tableBorder: none\ncolor: false\n");
+    write("repo/Cargo.toml", "[package]\nname = \"repo\"\n");
+    write("repo/tram.toml", "checkForUpdates = true\ncolor = true\n");
+    write("repo/app/tram.json", r#"{"outputFormat": "json"}"#);
+
+    let output = TramCommand::new()
+        .current_dir(root.join("repo/app"))
+        .env("TRAM_SYSTEM_CONFIG_DIR", root.join("etc"))
+        .env("TRAM_CONFIG_DIR", root.join("user"))
+        .args(["env"])
+        .assert_success();
+
+    let report: serde_json::Value =
+        serde_json::from_str(output.stdout()).expect("the local layer asks for JSON");
+    let source = |key: &str| {
+        let setting = report["settings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|s| s["key"] == key)
+            .unwrap()
+            .clone();
+        (
+            setting["value"].clone(),
+            setting["source"]["file"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+        )
+    };
+
+    let (value, file) = source("offline");
+    assert_eq!(value, true);
+    assert!(file.ends_with("config.toml"), "{}", file);
+    let (value, file) = source("tableBorder");
+    assert_eq!(value, "none");
+    assert!(file.ends_with("config.yaml"), "{}", file);
+    let (value, file) = source("color");
+    assert_eq!(value, true);
+    assert!(file.ends_with("repo/tram.toml"), "{}", file);
+    let (_, file) = source("outputFormat");
+    assert!(file.ends_with("tram.json"), "{}", file);
+    assert!(
+        report["paths"]["configFile"]
+            .as_str()
+            .unwrap()
+            .ends_with("tram.json")
+    );
+}
+
+#[test]
+fn test_dashboard_flag() {
+    init_tests();
+
+    TramCommand::new()
+        .args(["watch", "--ui", "--dashboard"])
+        .assert_failure()
+        .assert_stderr_contains("cannot be used with");
+
+    TramCommand::new()
+        .args(["daemon", "start", "--detach", "--dashboard"])
+        .assert_failure()
+        .assert_stderr_contains("cannot be used with");
+
+    #[cfg(not(feature = "dashboard"))]
+    TramCommand::new()
+        .args(["watch", "--dashboard"])
+        .assert_failure()
+        .assert_stderr_contains("rebuild with `cargo install tram --features dashboard`");
+}
+
+#[test]
+fn test_offline_blocks_network_commands() {
+    init_tests();
+
+    TramCommand::new()
+        .args(["--offline", "self-update", "--check"])
+        .assert_failure()
+        .assert_stderr_contains("needs the network, but offline mode is on");
+
+    let output = TramCommand::new()
+        .args(["--format", "json", "env"])
+        .env("TRAM_OFFLINE", "true")
+        .assert_success();
+    let report: serde_json::Value =
+        serde_json::from_str(output.stdout()).expect("env should be valid JSON");
+    let offline = report["settings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["key"] == "offline")
+        .unwrap()
+        .clone();
+    assert_eq!(offline["value"], true);
+    assert_eq!(offline["source"]["env"], "TRAM_OFFLINE");
+}
+
+#[test]
+fn test_self_update_rejects_invalid_repository() {
+    init_tests();
+
+    TramCommand::new()
+        .args(["self-update", "--check"])
+        .env("TRAM_UPDATE_REPOSITORY", "not-a-repository")
+        .assert_failure()
+        .assert_stderr_contains("update.repository: 'not-a-repository' is not owner/name");
+}
+
+#[test]
+fn test_config_env_vars() {
+    init_tests();
+
+    let output = TramCommand::new()
+        .args([
+            "--format",
+            "json",
+            "--log-level",
+            "warn",
+            "config",
+            "env-vars",
+        ])
+        .env("TRAM_LOG_LEVEL", "debug")
+        .env("TRAM_SECRET_API_KEY", "hunter2")
+        .env("TRAM_KEYRING_PASSPHRASE", "swordfish")
+        .assert_success();
+
+    let report: serde_json::Value =
+        serde_json::from_str(output.stdout()).expect("env-vars should be valid JSON");
+    let var = |name: &str| {
+        report["variables"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|v| v["name"] == name)
+            .unwrap_or_else(|| panic!("{} should be listed", name))
+            .clone()
+    };
+
+    assert_eq!(var("TRAM_LOG_LEVEL")["setting"], "logLevel");
+    assert_eq!(var("TRAM_LOG_LEVEL")["value"], "debug");
+    assert_eq!(var("TRAM_LOG_LEVEL")["overriddenBy"], "--log-level");
+    assert_eq!(var("TRAM_HTTP_PROXY")["setting"], "http.proxy");
+    assert!(var("TRAM_HTTP_PROXY")["value"].is_null());
+    assert_eq!(var("TRAM_SECRET_*")["value"], "TRAM_SECRET_API_KEY");
+    assert_eq!(var("TRAM_KEYRING_PASSPHRASE")["value"], "[redacted]");
+    assert!(var("NO_COLOR")["description"].is_string());
+    assert!(var("CI")["description"].is_string());
+    assert!(!output.stdout().contains("hunter2"));
+    assert!(!output.stdout().contains("swordfish"));
+}
+
+#[test]
+fn test_config_get_and_set() {
+    init_tests();
+
+    let temp_dir = TempDir::new("config-set-test").unwrap();
+    let config_file = temp_dir.path().join("tram.toml");
+
+    // Without a config file, one is created
+    TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["config", "set", "http.timeout_secs", "45"])
+        .assert_success()
+        .assert_stdout_contains("Set http.timeoutSecs to 45");
+    assert_eq!(
+        std::fs::read_to_string(&config_file).unwrap(),
+        "[http]\ntimeoutSecs = 45\n"
+    );
+
+    let output = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["config", "get", "http.timeoutSecs"])
+        .assert_success();
+    assert_eq!(output.stdout().trim(), "45");
+
+    // Comments survive edits
+    std::fs::write(
+        &config_file,
+        "# Shared settings\n[http]\ntimeoutSecs = 45 # slow proxy\n",
+    )
+    .unwrap();
+    TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["config", "set", "http.timeoutSecs", "60"])
+        .assert_success();
+    assert_eq!(
+        std::fs::read_to_string(&config_file).unwrap(),
+        "# Shared settings\n[http]\ntimeoutSecs = 60 # slow proxy\n"
+    );
+
+    // Invalid values and unknown keys leave the file alone
+    TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["config", "set", "outputFormat", "xml"])
+        .assert_failure();
+    TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["config", "get", "outputFormt"])
+        .assert_failure()
+        .assert_stderr_contains("'outputFormat'?");
+    assert!(
+        std::fs::read_to_string(&config_file)
+            .unwrap()
+            .contains("timeoutSecs = 60")
+    );
+}
+
+#[test]
+fn test_env_exports_csv_and_tsv() {
+    init_tests();
+
+    let csv = TramCommand::new()
+        .args(["--format", "csv", "--log-level", "warn", "env"])
+        .assert_success();
+    let mut lines = csv.stdout().lines();
+    assert_eq!(lines.next(), Some("Name,Value,Source"));
+    assert!(lines.any(|line| line.starts_with("logLevel,warn,")));
+    assert!(!csv.stdout().contains('│'));
+
+    let tsv = TramCommand::new()
+        .args(["--format", "tsv", "--log-level", "warn", "env"])
+        .assert_success();
+    assert!(tsv.stdout().starts_with("Name\tValue\tSource\n"));
+    assert!(tsv.stdout().contains("logLevel\twarn\t"));
+}
+
+#[test]
+fn test_cache_info_clear_and_prune() {
+    init_tests();
+
+    let temp_dir = TempDir::new("cache-command-test").unwrap();
+    let objects = temp_dir.path().join("objects/ab");
+    std::fs::create_dir_all(&objects).unwrap();
+    std::fs::write(objects.join("abc"), "12345").unwrap();
+    std::fs::write(objects.join("abd"), "123").unwrap();
+    std::fs::write(
+        temp_dir.path().join("manifest.json"),
+        r#"{"version": 1, "entries": {
+            "abc": {"digest": "abc", "label": "kept", "size": 5, "createdAt": 0, "expiresAt": null},
+            "abd": {"digest": "abd", "label": "stale", "size": 3, "createdAt": 0, "expiresAt": 1}
+        }}"#,
+    )
+    .unwrap();
+
+    let cache = |args: &[&str]| {
+        TramCommand::new()
+            .env("TRAM_CACHE_DIR", temp_dir.path())
+            .args(["--format", "plain"])
+            .args(args)
+            .assert_success()
+    };
+
+    cache(&["cache", "info"])
+        .assert_stdout_contains("Entries: 2 (1 expired)")
+        .assert_stdout_contains("Size: 8 B");
+
+    cache(&["--dry-run", "cache", "prune"])
+        .assert_stdout_contains("This would remove 1 entry (3 B)");
+    FileAssertions::assert_file_exists(objects.join("abd"));
+
+    cache(&["cache", "prune"]).assert_stdout_contains("Removed 1 entry (3 B)");
+    assert!(!objects.join("abd").exists());
+
+    cache(&["cache", "clear"]).assert_stdout_contains("Removed 1 entry (5 B)");
+    cache(&["cache", "info"]).assert_stdout_contains("Entries: 0 (0 expired)");
+}
+
+#[test]
+fn test_cache_prune_to_size_budget() {
+    init_tests();
+
+    let temp_dir = TempDir::new("cache-budget-test").unwrap();
+    let objects = temp_dir.path().join("objects/ab");
+    std::fs::create_dir_all(&objects).unwrap();
+    std::fs::write(objects.join("abc"), "12345").unwrap();
+    std::fs::write(objects.join("abd"), "123").unwrap();
+    std::fs::write(
+        temp_dir.path().join("manifest.json"),
+        r#"{"version": 1, "entries": {
+            "abc": {"digest": "abc", "label": "unused", "size": 5, "createdAt": 0, "expiresAt": null},
+            "abd": {"digest": "abd", "label": "recent", "size": 3, "createdAt": 0, "expiresAt": null, "accessedAt": 100}
+        }}"#,
+    )
+    .unwrap();
+
+    // Left behind by an interrupted write long ago
+    let orphan = objects.join("abe.tmp");
+    std::fs::write(&orphan, "partial").unwrap();
+    std::fs::File::options()
+        .write(true)
+        .open(&orphan)
+        .unwrap()
+        .set_modified(std::time::SystemTime::UNIX_EPOCH)
+        .unwrap();
+
+    let cache = |args: &[&str]| {
+        TramCommand::new()
+            .env("TRAM_CACHE_DIR", temp_dir.path())
+            .env("TRAM_CACHE_MAX_SIZE", "4B")
+            .args(["--format", "plain"])
+            .args(args)
+            .assert_success()
+    };
+
+    cache(&["cache", "info"]).assert_stdout_contains("Size: 8 B of 4 B");
+
+    cache(&["--dry-run", "cache", "prune"])
+        .assert_stdout_contains("This would remove 1 entry (12 B)")
+        .assert_stdout_contains("It would also remove 1 orphaned file");
+    FileAssertions::assert_file_exists(&orphan);
+
+    // The flag overrides the budget in config
+    cache(&["cache", "prune", "--max-size", "1KiB"])
+        .assert_stdout_contains("Removed 0 entries (7 B)")
+        .assert_stdout_contains("Also removed 1 orphaned file");
+    assert!(!orphan.exists());
+
+    // The least recently used entry goes first
+    cache(&["cache", "prune"]).assert_stdout_contains("Removed 1 entry (5 B)");
+    assert!(!objects.join("abc").exists());
+    FileAssertions::assert_file_exists(objects.join("abd"));
+
+    TramCommand::new()
+        .env("TRAM_CACHE_DIR", temp_dir.path())
+        .args(["cache", "prune", "--max-size", "lots"])
+        .assert_failure()
+        .assert_stderr_contains("expected a size such as 500MB or 1GiB");
+}
+
+#[test]
+fn test_cache_gc_runs_in_the_background_when_due() {
+    init_tests();
+
+    let temp_dir = TempDir::new("cache-gc-test").unwrap();
+    let objects = temp_dir.path().join("objects/ab");
+    std::fs::create_dir_all(&objects).unwrap();
+    std::fs::write(objects.join("abc"), "12345").unwrap();
+    std::fs::write(objects.join("abd"), "123").unwrap();
+    std::fs::write(
+        temp_dir.path().join("manifest.json"),
+        r#"{"version": 1, "entries": {
+            "abc": {"digest": "abc", "label": "over budget", "size": 5, "createdAt": 0, "expiresAt": null},
+            "abd": {"digest": "abd", "label": "stale", "size": 3, "createdAt": 0, "expiresAt": 1}
+        }}"#,
+    )
+    .unwrap();
+    // Last collected at the epoch, so one is due
+    std::fs::write(temp_dir.path().join("last-gc"), "0").unwrap();
+
+    // The budget comes from the command's --config, which the collection
+    // follows
+    let config_dir = TempDir::new("cache-gc-config").unwrap();
+    let config_file = config_dir.path().join("tram.toml");
+    std::fs::write(&config_file, "[cache]\nmaxSize = \"4B\"\n").unwrap();
+
+    TramCommand::new()
+        .env("TRAM_CACHE_DIR", temp_dir.path())
+        .arg("--config")
+        .arg(&config_file)
+        .args(["--format", "plain", "exit-codes"])
+        .assert_success();
+
+    let started = std::time::Instant::now();
+    while (objects.join("abc").exists() || objects.join("abd").exists())
+        && started.elapsed() < std::time::Duration::from_secs(10)
+    {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    assert!(!objects.join("abc").exists());
+    assert!(!objects.join("abd").exists());
+    assert_ne!(
+        std::fs::read_to_string(temp_dir.path().join("last-gc")).unwrap(),
+        "0"
+    );
+}
+
+#[test]
+fn test_slow_update_check_does_not_delay_exit() {
+    init_tests();
+
+    // Accepts the release lookup and never answers it
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let api_url = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        let _connections: Vec<_> = listener.incoming().collect();
+    });
+
+    let started = std::time::Instant::now();
+    TramCommand::new()
+        .env("TRAM_CHECK_FOR_UPDATES", "true")
+        .env("TRAM_UPDATE_API_URL", &api_url)
+        .args(["--format", "plain", "exit-codes"])
+        .assert_success();
+
+    // The lookup gives up after 3 seconds; the command doesn't wait for it
+    assert!(started.elapsed() < std::time::Duration::from_secs(2));
+}
+
+#[test]
+fn test_metrics_are_pushed_when_configured() {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    init_tests();
+
+    // Stands in for a Pushgateway: answers one push and hands it back
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let (sender, received) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request = String::new();
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                break;
+            }
+            if let Some(length) = line.to_lowercase().strip_prefix("content-length:") {
+                content_length = length.trim().parse().unwrap();
+            }
+            request.push_str(&line);
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        request.push_str(&String::from_utf8_lossy(&body));
+
+        let _ = reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n");
+        sender.send(request).unwrap();
+    });
+
+    TramCommand::new()
+        .env("TRAM_METRICS_EXPORTER", "prometheus")
+        .env("TRAM_METRICS_ENDPOINT", &endpoint)
+        .args(["--format", "plain", "exit-codes"])
+        .assert_success();
+
+    let request = received
+        .recv_timeout(std::time::Duration::from_secs(10))
+        .unwrap();
+    assert!(request.starts_with("POST /metrics/job/tram "));
+    assert!(request.contains("# TYPE tram_command_duration_seconds histogram"));
+    assert!(request.contains(
+        r#"tram_command_duration_seconds_count{command="exit-codes",outcome="success"} 1"#
+    ));
+}
+
+#[test]
+fn test_telemetry_consent() {
+    init_tests();
+
+    let temp_dir = TempDir::new("telemetry-test").unwrap();
+    let events = temp_dir.path().join("telemetry.jsonl");
+
+    let tram = |args: &[&str]| {
+        TramCommand::new()
+            .env("TRAM_DATA_DIR", temp_dir.path())
+            .env("TRAM_TELEMETRY", "")
+            .env("DO_NOT_TRACK", "")
+            .args(["--format", "plain"])
+            .args(args)
+            .assert_success()
+    };
+
+    // The first run explains telemetry, later ones don't
+    tram(&["telemetry", "status"])
+        .assert_stderr_contains("It stays off unless you run `tram telemetry on`")
+        .assert_stdout_contains("Telemetry: off (not yet enabled");
+    assert!(!tram(&["env"]).stderr().contains("tram telemetry on"));
+    assert!(!events.exists());
+
+    tram(&["telemetry", "on"]).assert_stdout_contains("Telemetry is on");
+    tram(&["env"]);
+    tram(&["telemetry", "status"]).assert_stdout_contains("Telemetry: on");
+    FileAssertions::assert_file_contains(&events, r#""command":"env","success":true"#);
+
+    tram(&["telemetry", "off"]).assert_stdout_contains("Telemetry is off");
+    let recorded = std::fs::read_to_string(&events).unwrap().lines().count();
+    tram(&["env"]);
+    assert_eq!(
+        std::fs::read_to_string(&events).unwrap().lines().count(),
+        recorded
+    );
+}
+
+#[test]
+fn test_shell_runs_commands_until_exit() {
+    init_tests();
+
+    let temp_dir = TempDir::new("shell-history-test").unwrap();
+
+    let output = TramCommand::new()
+        .args(["--format", "plain", "shell"])
+        .env("TRAM_DATA_DIR", temp_dir.path())
+        .stdin("workspace\nnot-a-command\nshell\nexit\nworkspace\n")
+        .assert_success();
+
+    // Workspace analysis runs once for the whole session
+    assert_eq!(output.stderr().matches("Working in").count(), 1);
+    output
+        .assert_stdout_contains("Workspace root:")
+        .assert_stderr_contains("unrecognized subcommand 'not-a-command'")
+        .assert_stderr_contains("Already in the tram shell");
+
+    // Nothing after `exit` runs
+    assert_eq!(output.stdout().matches("Workspace root:").count(), 1);
+
+    FileAssertions::assert_file_contains(temp_dir.path().join("shell_history"), "not-a-command");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shell_completes_config_keys() {
+    use tram_test::{Key, PtyTestRunner};
+
+    init_tests();
+
+    let temp_dir = TempDir::new("shell-completion-test").unwrap();
+
+    let mut session = PtyTestRunner::new(env!("CARGO_BIN_EXE_tram"))
+        .args(["--format", "plain", "shell"])
+        .env("NO_COLOR", "1")
+        .env("TRAM_LOG_LEVEL", "error")
+        .env("TRAM_NO_DAEMON", "1")
+        .env("TRAM_DATA_DIR", temp_dir.path().to_str().unwrap())
+        .spawn()
+        .unwrap();
+
+    session.expect("tram> ").unwrap();
+    session.send("config g").unwrap();
+    session.send_key(Key::Tab).unwrap();
+    session.send(" http.time").unwrap();
+    session.send_key(Key::Tab).unwrap();
+    session.send_key(Key::Enter).unwrap();
+    session.expect("30").unwrap();
+    session.send_line("exit").unwrap();
+
+    let output = session.wait().unwrap();
+    assert!(output.success(), "{}", output.output());
+    assert!(output.output().contains("config get http.timeoutSecs"));
+}
+
+#[test]
+fn test_init_verbose() {
+    init_tests();
+
+    let temp_dir = TempDir::new("init-verbose-test").unwrap();
+
+    let output = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["init", "verbose-project", "--verbose"])
+        .assert_success();
+
+    output.assert_stdout_contains("Verbose mode enabled");
+    output.assert_stdout_contains("Workspace root:");
+    output.assert_stdout_contains("Config:");
+}
+
+#[test]
+fn test_global_options_log_level() {
+    init_tests();
+
+    let output = TramCommand::new()
+        .args(["--log-level", "debug", "--format", "table", "config"])
+        .assert_success();
+
+    // With debug level, should see debug output in logs
+    // The exact format depends on the logging configuration
+    output.assert_stdout_contains("Current configuration:");
+    output.assert_stdout_matches(r"Log level\s+│\s+debug");
+}
+
+#[test]
+fn test_global_options_format() {
+    init_tests();
+
+    // Test JSON format
+    let output = TramCommand::new()
+        .args(["--format", "json", "config"])
+        .assert_success();
+
+    let config: serde_json::Value =
+        serde_json::from_str(output.stdout()).expect("config output should be valid JSON");
+    assert_eq!(config["outputFormat"], "json");
+    assert!(config.get("logLevel").is_some());
+    assert!(config["color"].is_boolean());
+
+    // Test YAML format
+    let output = TramCommand::new()
+        .args(["--format", "yaml", "config"])
+        .assert_success();
+
+    output.assert_stdout_contains("outputFormat: yaml");
+    output.assert_stdout_contains("logLevel:");
+
+    // Test Table format (default)
+    let output = TramCommand::new()
+        .args(["--format", "table", "config"])
+        .assert_success();
+
+    output.assert_stdout_contains("Current configuration:");
+}
+
+#[test]
+fn test_global_options_no_color() {
+    init_tests();
+
+    let output = TramCommand::new()
+        .args(["--no-color", "--format", "table", "config"])
+        .assert_success();
+
+    output.assert_stdout_contains("Current configuration:");
+    output.assert_stdout_matches(r"Colors\s+│\s+false");
+    // Table headers are only bolded when color is enabled
+    assert!(!output.stdout().contains('\x1b'));
+}
+
+#[test]
+fn test_global_options_color() {
+    init_tests();
+
+    // TramCommand sets NO_COLOR and output is piped, so auto means no color
+    let output = TramCommand::new()
+        .args(["--format", "table", "config"])
+        .assert_success();
+    assert!(!output.stdout().contains('\x1b'));
+
+    // --color always beats NO_COLOR
+    let output = TramCommand::new()
+        .args(["--color", "always", "--format", "table", "config"])
+        .assert_success();
+    output.assert_stdout_contains("\x1b[1mSetting\x1b[0m");
+
+    // CLICOLOR_FORCE colors even when piped
+    let output = TramCommand::new()
+        .env("CLICOLOR_FORCE", "1")
+        .args(["--color", "auto", "--format", "table", "config"])
+        .assert_success();
+    output.assert_stdout_contains("\x1b[1m");
+
+    let output = TramCommand::new()
+        .env("CLICOLOR_FORCE", "1")
+        .args(["--color", "never", "--format", "table", "config"])
+        .assert_success();
+    assert!(!output.stdout().contains('\x1b'));
+
+    TramCommand::new()
+        .args(["--color", "sometimes", "config"])
+        .assert_failure()
+        .assert_stderr_contains("invalid value 'sometimes'");
+}
+
+#[test]
+fn test_global_options_quiet() {
+    init_tests();
+
+    let temp_dir = TempDir::new("quiet-test").unwrap();
+
+    let output = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["init", "quiet-project", "-q"])
+        .assert_success();
+
+    assert_eq!(output.stdout(), "");
+    assert_eq!(output.stderr(), "", "deprecation warnings are silenced too");
+    assert!(temp_dir.path().join("quiet-project").exists());
+
+    // Errors are still reported
+    let output = TramCommand::new()
+        .current_dir("/tmp")
+        .args(["--quiet", "workspace"])
+        .assert_failure();
+
+    output.assert_stderr_contains("Workspace not found");
+
+    TramCommand::new()
+        .args(["-q", "-v", "config"])
+        .assert_failure()
+        .assert_stderr_contains("cannot be used with");
+}
+
+#[test]
+fn test_global_options_verbose() {
+    init_tests();
+
+    let output = TramCommand::new()
+        .args(["-v", "--format", "plain", "workspace"])
+        .assert_success();
+
+    output.assert_stdout_contains("Workspace root:");
+    output.assert_stderr_contains("DEBUG");
+    assert!(!output.stderr().contains("TRACE"));
+
+    let output = TramCommand::new()
+        .args(["-vv", "--format", "plain", "workspace"])
+        .assert_success();
+
+    output.assert_stderr_contains("TRACE");
+
+    // An explicit --log-level wins over -v
+    let output = TramCommand::new()
+        .args([
+            "-v",
+            "--log-level",
+            "warn",
+            "--format",
+            "plain",
+            "workspace",
+        ])
+        .assert_success();
+
+    assert!(!output.stderr().contains("DEBUG"));
+}
+
+#[test]
+fn test_global_options_timings() {
+    init_tests();
+
+    let temp_dir = TempDir::new("timings-test").unwrap();
+    let trace_path = temp_dir.path().join("trace.json");
+
+    let output = TramCommand::new()
+        .args(["--timings", "--timings-trace", trace_path.to_str().unwrap()])
+        .arg("config")
+        .assert_success();
+
+    output.assert_stderr_contains("config load");
+    output.assert_stderr_contains("command exec");
+    output.assert_stderr_contains("Total");
+
+    let trace: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&trace_path).unwrap()).unwrap();
+    assert!(
+        trace["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|event| event["name"] == "startup" && event["ph"] == "X")
+    );
+}
+
+#[test]
+fn test_invalid_subcommand() {
+    init_tests();
+
+    let output = TramCommand::new()
+        .args(["invalid-command"])
+        .assert_failure();
+
+    output.assert_stderr_contains("unrecognized subcommand 'invalid-command'");
+}
+
+#[test]
+fn test_invalid_global_option() {
+    init_tests();
+
+    let output = TramCommand::new()
+        .args(["--invalid-option"])
+        .assert_failure();
+
+    output.assert_stderr_contains("unexpected argument '--invalid-option'");
+}
+
+#[test]
+fn test_missing_required_argument() {
+    init_tests();
+
+    let output = TramCommand::new().args(["new"]).assert_failure();
+
+    output.assert_stderr_contains("required arguments were not provided");
+}
+
+#[test]
+fn test_cli_version_info() {
+    init_tests();
+
+    let output = TramCommand::new().args(["--version"]).assert_success();
+
+    output.assert_stdout_contains("tram");
+    output.assert_stdout_contains("0.1.0");
+}
+
+#[test]
+fn test_summary_file() {
+    init_tests();
+
+    let temp_dir = TempDir::new("summary-file-test").unwrap();
+    let summary_path = temp_dir.path().join("out").join("summary.json");
+
+    TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["new", "summary-project", "--project-type", "generic"])
+        .args(["--skip-prompts", "--summary-file"])
+        .args([&summary_path])
+        .assert_success();
+
+    let summary: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&summary_path).unwrap()).unwrap();
+    assert_eq!(summary["command"], "new");
+    assert_eq!(summary["success"], true);
+    assert_eq!(summary["exitCode"], 0);
+    assert!(summary["durationMs"].is_u64());
+    let created: Vec<&str> = summary["filesCreated"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|path| path.as_str().unwrap())
+        .collect();
+    assert!(created.iter().any(|path| path.ends_with("README.md")));
+    assert_eq!(summary["filesModified"], serde_json::json!([]));
+
+    // Failures are summarized too, and json output puts it on stderr
+    let output = TramCommand::new()
+        .current_dir("/tmp")
+        .args(["--format", "json", "workspace"])
+        .assert_failure();
+    let line = output
+        .stderr()
+        .lines()
+        .last()
+        .expect("No summary on stderr");
+    let summary: serde_json::Value = serde_json::from_str(line).unwrap();
+    assert_eq!(summary["summary"]["command"], "workspace");
+    assert_eq!(summary["summary"]["success"], false);
+    assert_eq!(summary["summary"]["exitCode"], 4);
+}
+
+#[test]
+fn test_events_json_stream() {
+    init_tests();
+
+    let temp_dir = TempDir::new("events-json-test").unwrap();
+
+    let output = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args([
+            "--events",
+            "json",
+            "new",
+            "events-project",
+            "--project-type",
+            "generic",
+            "--skip-prompts",
+        ])
+        .assert_success();
+
+    let events: Vec<serde_json::Value> = output
+        .stderr()
+        .lines()
+        .filter(|line| line.starts_with('{'))
+        .map(|line| serde_json::from_str(line).expect("event lines should be valid JSON"))
+        .collect();
+    let kinds: Vec<&str> = events.iter().filter_map(|e| e["event"].as_str()).collect();
+
+    assert_eq!(
+        kinds,
+        [
+            "commandStarted",
+            "progress",
+            "fileWritten",
+            "fileWritten",
+            "commandFinished"
+        ],
+        "stderr: {}",
+        output.stderr()
+    );
+    assert_eq!(events[0]["command"], "new");
+    assert_eq!(events[1]["current"], 1);
+    assert_eq!(events[1]["total"], 1);
+    assert!(events[2]["path"].as_str().unwrap().ends_with("README.md"));
+    assert!(
+        events[3]["path"]
+            .as_str()
+            .unwrap()
+            .ends_with("project.json")
+    );
+    assert_eq!(events[4]["success"], true);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_sigterm_runs_shutdown_and_exits_143() {
+    init_tests();
+
+    // Tasks run in the workspace root, so the marker needs an absolute path
+    let temp_dir = TempDir::new("sigterm-test").unwrap();
+    let started = temp_dir.path().join("started");
+    std::fs::write(
+        temp_dir.path().join("tram.toml"),
+        format!(
+            "[tasks.wait]\ncommand = \"touch '{}' && sleep 5\"\n",
+            started.display()
+        ),
+    )
+    .unwrap();
+
+    let child = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["--format", "plain", "--events", "json", "run", "wait"])
+        .spawn()
+        .unwrap();
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    while !started.exists() && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+
+    std::process::Command::new("kill")
+        .args(["-TERM", &child.id().to_string()])
+        .status()
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(143), "stderr: {}", stderr);
+    assert!(stderr.contains("Stopped by SIGTERM"));
+    assert!(stderr.contains(r#""event":"commandFinished""#));
+    assert!(!stderr.contains("Done!"));
+}
+
+#[test]
+fn test_timeout_stops_command_with_124() {
+    init_tests();
+
+    let temp_dir = TempDir::new("timeout-test").unwrap();
+    std::fs::write(
+        temp_dir.path().join("tram.toml"),
+        "[tasks.wait]\ncommand = \"sleep 5\"\n",
+    )
+    .unwrap();
+
+    let started = std::time::Instant::now();
+    let output = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["--format", "plain", "--timeout", "1", "run", "wait"])
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(124), "stderr: {}", stderr);
+    assert!(stderr.contains("Timed out after 1s"));
+    assert!(!stderr.contains("Done!"));
+    assert!(started.elapsed() < std::time::Duration::from_secs(4));
+}
+
+#[test]
+fn test_timeout_kills_running_tasks() {
+    init_tests();
+
+    let temp_dir = TempDir::new("timeout-kill-test").unwrap();
+    let finished = temp_dir.path().join("finished");
+    std::fs::write(
+        temp_dir.path().join("tram.toml"),
+        format!(
+            "[tasks.wait]\ncommand = \"sleep 2 && touch '{}'\"\n",
+            finished.display()
+        ),
+    )
+    .unwrap();
+
+    let output = TramCommand::new()
+        .current_dir(temp_dir.path())
+        .args(["--format", "plain", "--timeout", "1", "run", "wait"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(124));
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    assert!(!finished.exists());
+}
+
+#[test]
+fn test_run_tasks_in_dependency_order() {
+    init_tests();
+
+    let temp_dir = TempDir::new("run-tasks-test").unwrap();
+    let config_file = temp_dir.path().join("tram.toml");
+    std::fs::write(
+        &config_file,
+        r#"
+[tasks.build]
+command = "echo building"
+
+[tasks.test]
+command = "echo testing"
+dependsOn = ["build"]
+
+[tasks.broken]
+command = "exit 3"
+
+[tasks.all]
+command = "echo all"
+dependsOn = ["broken", "test"]
+"#,
+    )
+    .unwrap();
+    let config = config_file.to_str().unwrap();
+
+    let output = TramCommand::new()
+        .args(["--config", config, "--format", "plain", "run", "test"])
+        .assert_success();
+    let stdout = output.stdout();
+    let build = stdout.find("[build] building").expect(stdout);
+    let test = stdout.find("[test] testing").expect(stdout);
+    assert!(build < test);
+    output.assert_stdout_contains("2 passed, 0 failed, 0 skipped");
+
+    TramCommand::new()
+        .args(["--config", config, "--format", "plain", "run", "--list"])
+        .assert_success()
+        .assert_stdout_contains("test: echo testing");
+
+    let output = TramCommand::new()
+        .args(["--config", config, "--format", "plain", "run", "--tree"])
+        .assert_success();
+    assert_eq!(
+        output.stdout().trim_end(),
+        [
+            "all: echo all",
+            "├── broken: exit 3",
+            "└── test: echo testing",
+            "    └── build: echo building",
+        ]
+        .join("\n")
+    );
+
+    TramCommand::new()
+        .args([
+            "--config", config, "--format", "plain", "run", "--tree", "tset",
+        ])
+        .assert_failure()
+        .assert_stderr_contains("did you mean 'test'?");
+
+    // The summary is still printed when a task fails
+    let output = TramCommand::new()
+        .args([
+            "--config",
+            config,
+            "--format",
+            "json",
+            "run",
+            "all",
+            "--keep-going",
+        ])
+        .assert_failure();
+    output.assert_stderr_contains("Failed tasks: broken");
+    // Task output goes to stderr, so stdout is only the JSON result
+    output.assert_stderr_contains("[build] building");
+    let result: serde_json::Value = serde_json::from_str(output.stdout()).unwrap();
+    let statuses: Vec<(&str, &str)> = result["tasks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|task| {
+            (
+                task["name"].as_str().unwrap(),
+                task["status"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            ("broken", "failed"),
+            ("build", "passed"),
+            ("test", "passed"),
+            ("all", "skipped")
+        ]
+    );
+    assert_eq!(result["tasks"][0]["exitCode"], 3);
+
+    TramCommand::new()
+        .args(["--config", config, "run", "missing"])
+        .assert_failure()
+        .assert_stderr_contains("Unknown task 'missing'");
+}
+
+#[test]
+fn test_invalid_argument_values_are_rejected() {
+    init_tests();
+
+    TramCommand::new()
+        .args(["new", "app", "--project-type", "cobol", "--skip-prompts"])
+        .assert_failure()
+        .assert_stderr_contains("possible values: rust, nodejs, python, go, java, generic");
+
+    TramCommand::new()
+        .args(["--format", "xml", "config"])
+        .assert_failure()
+        .assert_stderr_contains("invalid value 'xml'");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_docs_writes_markdown_pages() {
+    init_tests();
+
+    let temp_dir = TempDir::new("docs-test").unwrap();
+    let docs = temp_dir.path().join("docs").join("cli");
+
+    TramCommand::new()
+        .args(["--format", "plain", "docs", "--output"])
+        .arg(&docs)
+        .assert_success()
+        .assert_stdout_contains("Markdown pages to");
+
+    let root = std::fs::read_to_string(docs.join("tram.md")).unwrap();
+    assert!(root.contains("| [`cache`](tram-cache.md) |"));
+    assert!(root.contains("## Exit status"));
+
+    let prune = std::fs::read_to_string(docs.join("tram-cache-prune.md")).unwrap();
+    assert!(prune.starts_with("# tram cache prune\n"));
+    assert!(prune.contains("| `--max-size <SIZE>` |"));
+    assert!(prune.contains("tram cache prune --max-size 1GB"));
+    assert!(!prune.contains("--log-level"));
+
+    // Nothing is written with --dry-run
+    let dry_run = temp_dir.path().join("dry-run");
+    TramCommand::new()
+        .args(["--format", "plain", "--dry-run", "docs", "--output"])
+        .arg(&dry_run)
+        .assert_success()
+        .assert_stdout_contains("Would write");
+    assert!(!dry_run.exists());
+}
+
+#[test]
+fn test_package_writes_manifests() {
+    init_tests();
+
+    let temp_dir = TempDir::new("package-test").unwrap();
+    let output = temp_dir.path().join("homebrew");
+
+    TramCommand::new()
+        .args([
+            "--format", "plain", "package", "--format", "homebrew", "--output",
+        ])
+        .arg(&output)
+        .args(["--sha256", "abc123"])
+        .assert_success()
+        .assert_stdout_contains("Wrote homebrew packaging");
+
+    let formula = std::fs::read_to_string(output.join("tram.rb")).unwrap();
+    assert!(formula.starts_with("class Tram < Formula\n"));
+    assert!(formula.contains("sha256 \"abc123\""));
+    assert!(output.join("man").join("tram.1").exists());
+    assert!(output.join("completions").join("_tram").exists());
+
+    // Debian packages need a maintainer
+    let deb = temp_dir.path().join("deb");
+    TramCommand::new()
+        .args(["package", "--format", "deb", "--output"])
+        .arg(&deb)
+        .assert_failure();
+
+    TramCommand::new()
+        .args([
+            "package",
+            "--format",
+            "deb",
+            "--maintainer",
+            "Jane <jane@example.com>",
+            "--output",
+        ])
+        .arg(&deb)
+        .assert_success();
+    let control = std::fs::read_to_string(
+        deb.join(format!("tram_{}_amd64", env!("CARGO_PKG_VERSION")))
+            .join("DEBIAN")
+            .join("control"),
+    )
+    .unwrap();
+    assert!(control.contains("Maintainer: Jane <jane@example.com>\n"));
+}
+
+#[test]
+fn test_daemon_serves_delegated_commands() {
+    init_tests();
+
+    let temp_dir = TempDir::new("daemon-test").unwrap();
+    let socket = temp_dir.path().join("daemon.sock");
+    let tram = || {
+        TramCommand::new()
+            .current_dir(temp_dir.path())
+            .env("TRAM_DAEMON_SOCKET", &socket)
+            .env("TRAM_NO_DAEMON", "")
+    };
+
+    tram()
+        .args(["--format", "plain", "daemon", "start", "--detach"])
+        .assert_success()
+        .assert_stdout_contains("Daemon running");
+
+    let config = tram().args(["--format", "json", "config"]).assert_success();
+    let config: serde_json::Value = serde_json::from_str(config.stdout()).unwrap();
+    assert_eq!(config["outputFormat"], "json");
+
+    // Commands that need a session of their own still run locally
+    tram()
+        .args(["--profile", "missing", "config"])
+        .assert_failure()
+        .assert_stderr_contains("missing");
+
+    // So do callers with other TRAM_* settings than the daemon's
+    let config = tram()
+        .env("TRAM_OUTPUT_FORMAT", "json")
+        .arg("config")
+        .assert_success();
+    let config: serde_json::Value = serde_json::from_str(config.stdout()).unwrap();
+    assert_eq!(config["outputFormat"], "json");
+
+    // A client that connects and never sends a request doesn't hold up others
+    let _stalled = std::os::unix::net::UnixStream::connect(&socket).unwrap();
+    let status = tram()
+        .args(["--format", "json", "daemon", "status"])
+        .assert_success();
+    let status: serde_json::Value = serde_json::from_str(status.stdout()).unwrap();
+    assert_eq!(status["running"], true);
+    assert_eq!(status["requests"], 2);
+
+    tram().args(["daemon", "stop"]).assert_success();
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    while socket.exists() && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    assert!(!socket.exists());
+    tram()
+        .args(["--format", "plain", "daemon", "status"])
+        .assert_success()
+        .assert_stdout_contains("No daemon is running");
+}
+
+/// Every global option in `tram --help` but help and version, with a value
+/// for the ones that take one: the first possible value, or `1`.
+fn global_flags() -> Vec<Vec<String>> {
+    let help = TramCommand::new().arg("--help").assert_success();
+    let options = help.stdout().split("Options:\n").nth(1).unwrap();
+    let lines: Vec<&str> = options.lines().collect();
+    // Option lines are indented less than their descriptions
+    let is_option = |line: &str| {
+        line.len() - line.trim_start().len() <= 6 && line.trim_start().starts_with('-')
+    };
+
+    let mut flags = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if !is_option(line) {
+            continue;
+        }
+
+        let long = line.split("--").nth(1).unwrap();
+        let name = long.split([' ', '.']).next().unwrap();
+        if name == "help" || name == "version" {
+            continue;
+        }
+
+        let mut flag = vec![format!("--{}", name)];
+        if long.contains(" <") {
+            let value = lines[index + 1..]
+                .iter()
+                .take_while(|line| !is_option(line))
+                .find_map(|line| {
+                    let line = line.trim();
+                    line.strip_prefix("[possible values: ")
+                        .or_else(|| line.strip_prefix("- "))
+                        .and_then(|values| values.split([',', ':', ']']).next())
+                })
+                .unwrap_or("1");
+            flag.push(value.to_string());
+        }
+        flags.push(flag);
+    }
+
+    flags
+}
+
+#[test]
+fn test_daemon_declines_global_flags_it_does_not_apply() {
+    init_tests();
+
+    // Applied to the daemon's session for each request, or decided by the
+    // caller; every other global flag needs a session of its own
+    const APPLIED: [&str; 5] = ["--format", "--quiet", "--dry-run", "--color", "--no-color"];
+
+    let temp_dir = TempDir::new("daemon-flags-test").unwrap();
+    let socket = temp_dir.path().join("daemon.sock");
+    let tram = || {
+        TramCommand::new()
+            .current_dir(temp_dir.path())
+            .env("TRAM_DAEMON_SOCKET", &socket)
+            .env("TRAM_CACHE_DIR", temp_dir.path().join("cache"))
+            .env("TRAM_NO_DAEMON", "")
+    };
+    let requests = || {
+        let status = tram()
+            .args(["--format", "json", "daemon", "status"])
+            .assert_success();
+        let status: serde_json::Value = serde_json::from_str(status.stdout()).unwrap();
+        status["requests"].as_u64().unwrap()
+    };
+
+    tram()
+        .args(["--format", "plain", "daemon", "start", "--detach"])
+        .assert_success();
+
+    let flags = global_flags();
+    assert!(flags.iter().any(|flag| flag[0] == "--timings"));
+    for flag in flags {
+        let before = requests();
+        tram().args(&flag).args(["cache", "info"]).output().unwrap();
+
+        let delegated = requests() > before;
+        assert_eq!(
+            delegated,
+            APPLIED.contains(&flag[0].as_str()),
+            "{} must be applied to delegated requests or rejected by daemon::delegates",
+            flag.join(" ")
+        );
+    }
+
+    // Only `cache info` is read-only
+    let before = requests();
+    tram()
+        .args(["--dry-run", "cache", "clear"])
+        .assert_success();
+    assert_eq!(requests(), before);
+
+    tram().args(["daemon", "stop"]).assert_success();
+}
+
+#[test]
+fn test_daemon_runs_scheduled_tasks() {
+    init_tests();
+
+    let temp_dir = TempDir::new("schedule-test").unwrap();
+    let socket = temp_dir.path().join("daemon.sock");
+    let marker = temp_dir.path().join("marker");
+    std::fs::write(
+        temp_dir.path().join("tram.toml"),
+        format!(
+            "[schedules.mark]\ncron = \"* * * * * *\"\nrun = \"echo ran > '{}'\"\n",
+            marker.display()
+        ),
+    )
+    .unwrap();
+    let tram = || {
+        TramCommand::new()
+            .current_dir(temp_dir.path())
+            .env("TRAM_DAEMON_SOCKET", &socket)
+            .env("TRAM_DATA_DIR", temp_dir.path().join("data"))
+    };
+
+    tram()
+        .args(["--format", "plain", "daemon", "start", "--detach"])
+        .assert_success();
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    while !marker.exists() && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    tram().args(["daemon", "stop"]).assert_success();
+
+    assert!(marker.exists());
+    let state = std::fs::read_to_string(temp_dir.path().join("data").join("state.json")).unwrap();
+    let state: serde_json::Value = serde_json::from_str(&state).unwrap();
+    assert!(state["schedules"]["mark"]["occurrence"].is_i64());
+}
+
+#[test]
+fn test_wasm_plugins_add_commands_and_templates() {
+    init_tests();
+
+    let temp_dir = TempDir::new("wasm-plugin-test").unwrap();
+    let plugins = temp_dir.path().join("data").join("plugins");
+    let target = temp_dir.path().join("out");
+    std::fs::create_dir_all(&plugins).unwrap();
+    std::fs::create_dir_all(&target).unwrap();
+
+    let manifest = r#"{"name":"hello","version":"0.1.0","commands":[{"name":"hello","about":"Say hello"}],"templates":[{"name":"notes"}]}"#;
+    let command = r#"{"exitCode":4,"stdout":"hello from wasm\n"}"#;
+    let template = r##"{"files":[{"path":"NOTES.md","contents":"# Notes\n"}]}"##;
+    let escape = |s: &str| s.replace('\\', r"\\").replace('"', r#"\""#);
+    let wasm = wat::parse_str(format!(
+        r#"(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "{}")
+  (data (i32.const 1024) "{}")
+  (data (i32.const 2048) "{}")
+  (func (export "tram_alloc") (param i32) (result i32) (i32.const 4096))
+  (func (export "tram_manifest") (result i64) (i64.const {}))
+  (func (export "tram_command") (param i32 i32) (result i64)
+    (i64.or (i64.shl (i64.const 1024) (i64.const 32)) (i64.const {})))
+  (func (export "tram_template") (param i32 i32) (result i64)
+    (i64.or (i64.shl (i64.const 2048) (i64.const 32)) (i64.const {}))))"#,
+        escape(manifest),
+        escape(command),
+        escape(template),
+        manifest.len(),
+        command.len(),
+        template.len()
+    ))
+    .unwrap();
+    std::fs::write(plugins.join("hello.wasm"), wasm).unwrap();
+
+    let tram = || TramCommand::new().env("TRAM_DATA_DIR", temp_dir.path().join("data"));
+
+    let output = tram().arg("hello").output().unwrap();
+    assert_eq!(output.status.code(), Some(4));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hello from wasm\n");
+
+    let list = tram()
+        .args(["--format", "json", "plugins", "list"])
+        .assert_success();
+    let list: serde_json::Value = serde_json::from_str(list.stdout()).unwrap();
+    assert_eq!(list["plugins"][0]["name"], "hello");
+    assert_eq!(list["plugins"][0]["commands"][0], "hello");
+
+    tram()
+        .args(["plugins", "generate", "notes", "demo", "--target-dir"])
+        .arg(&target)
+        .assert_success();
+    FileAssertions::assert_file_contains(target.join("NOTES.md"), "# Notes");
+
+    tram()
+        .args(["plugins", "generate", "missing", "demo"])
+        .assert_failure()
+        .assert_stderr_contains("No plugin provides a template named 'missing'");
+}
+
+#[test]
+fn test_auth_saves_tokens_in_encrypted_file() {
+    init_tests();
+
+    let temp_dir = TempDir::new("auth-test").unwrap();
+    let tram = || {
+        TramCommand::new()
+            .env("TRAM_DATA_DIR", temp_dir.path())
+            .env("TRAM_KEYRING_BACKEND", "file")
+            .args(["--format", "plain", "auth"])
+    };
+
+    tram()
+        .args(["login", "registry", "--stdin"])
+        .stdin("s3cr3t\n")
+        .assert_success()
+        .assert_stdout_contains("Saved registry (encrypted-file)");
+
+    let credentials = std::fs::read(temp_dir.path().join("credentials")).unwrap();
+    assert!(!credentials.windows(6).any(|window| window == b"s3cr3t"));
+
+    let status = tram().args(["status", "registry"]).assert_success();
+    status.assert_stdout_contains("registry is saved");
+    assert!(!status.stdout().contains("s3cr3t"));
+
+    tram()
+        .args(["--dry-run", "logout", "registry"])
+        .assert_success()
+        .assert_stdout_contains("Dry run, nothing was changed.");
+    tram()
+        .args(["logout", "registry"])
+        .assert_success()
+        .assert_stdout_contains("Removed registry");
+    tram()
+        .args(["status", "registry"])
+        .assert_success()
+        .assert_stdout_contains("No token named registry");
+}
+
+#[test]
+fn test_prompt_segment() {
+    init_tests();
+
+    let temp_dir = TempDir::new("prompt-segment-test").unwrap();
+    let project = temp_dir.path().join("segment-project");
+    RustProjectFixture::new("segment-project")
+        .write_to(&project)
+        .unwrap();
+    let cache_dir = temp_dir.path().join("cache");
+
+    TramCommand::new()
+        .current_dir(&project)
+        .env("TRAM_CACHE_DIR", &cache_dir)
+        .args(["prompt-segment"])
+        .assert_success()
+        .assert_stdout_contains("🦀 segment-project");
+
+    let output = TramCommand::new()
+        .current_dir(&project)
+        .env("TRAM_CACHE_DIR", &cache_dir)
+        .args(["--format", "json", "prompt-segment"])
+        .assert_success();
+    let segment: serde_json::Value = serde_json::from_str(output.stdout().trim()).unwrap();
+    assert_eq!(segment["name"], "segment-project");
+    assert_eq!(segment["projectType"], "rust");
+    assert_eq!(segment["icon"], "🦀");
+
+    // Outside a workspace there's nothing to show
+    let output = TramCommand::new()
+        .current_dir("/tmp")
+        .env("TRAM_CACHE_DIR", &cache_dir)
+        .args(["--format", "json", "prompt-segment"])
+        .assert_success();
+    assert_eq!(output.stdout().trim(), "null");
 }
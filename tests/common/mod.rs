@@ -6,14 +6,19 @@
 
 #![allow(dead_code)]
 
+use std::collections::HashSet;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Output};
-use std::sync::Once;
+use std::process::{Command, Output, Stdio};
+use std::sync::{Mutex, Once};
 
 /// Global test setup that runs once across all tests.
 static INIT: Once = Once::new();
 
+/// Tests whose directories have been emptied in this run.
+static TEST_DIRS: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
 /// Workspace root directory.
 pub fn workspace_root() -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -27,6 +32,23 @@ pub fn init_tests() {
     });
 }
 
+/// Config, data, and cache directories for the running test, so commands
+/// never read the developer's config or touch their state and cache.
+/// Commands run by the same test share them; they're emptied the first time
+/// a test asks for them.
+pub fn test_dirs() -> PathBuf {
+    let thread = std::thread::current();
+    let name = thread.name().unwrap_or("unnamed").replace("::", "-");
+    let path = workspace_root().join("test-tmp").join(".dirs").join(&name);
+
+    let mut emptied = TEST_DIRS.lock().unwrap();
+    if emptied.get_or_insert_with(HashSet::new).insert(name) && path.exists() {
+        let _ = fs::remove_dir_all(&path);
+    }
+
+    path
+}
+
 /// Test temporary directory manager.
 pub struct TempDir {
     path: PathBuf,
@@ -42,6 +64,13 @@ impl TempDir {
         // Ensure temp root exists
         fs::create_dir_all(&temp_root)?;
 
+        // Mark the temp root as a workspace of its own, so commands run in
+        // test directories don't merge the repo's tram.toml into their config
+        let marker = temp_root.join(".project");
+        if !marker.exists() {
+            fs::write(marker, "")?;
+        }
+
         let path = temp_root.join(test_name);
 
         // Remove any existing directory
@@ -80,6 +109,7 @@ impl Drop for TempDir {
 /// CLI command builder for integration tests.
 pub struct TramCommand {
     command: Command,
+    stdin: Option<String>,
 }
 
 impl TramCommand {
@@ -93,8 +123,20 @@ impl TramCommand {
         command.env("NO_COLOR", "1");
         // Set log level to error to minimize output
         command.env("TRAM_LOG_LEVEL", "error");
+        // Never hand commands to a daemon running on this machine
+        command.env("TRAM_NO_DAEMON", "1");
+        // Keep the user's config, state, and cache out of the tests
+        let dirs = test_dirs();
+        command
+            .env("TRAM_CONFIG_DIR", dirs.join("config"))
+            .env("TRAM_SYSTEM_CONFIG_DIR", dirs.join("system-config"))
+            .env("TRAM_DATA_DIR", dirs.join("data"))
+            .env("TRAM_CACHE_DIR", dirs.join("cache"));
 
-        Self { command }
+        Self {
+            command,
+            stdin: None,
+        }
     }
 
     /// Add an argument to the command.
@@ -129,9 +171,45 @@ impl TramCommand {
         self
     }
 
+    /// Remove an environment variable from the command.
+    pub fn env_remove<K: AsRef<std::ffi::OsStr>>(mut self, key: K) -> Self {
+        self.command.env_remove(key);
+        self
+    }
+
+    /// Feed text to the command's standard input.
+    pub fn stdin(mut self, input: impl Into<String>) -> Self {
+        self.stdin = Some(input.into());
+        self
+    }
+
     /// Execute the command and return the output.
     pub fn output(mut self) -> std::io::Result<Output> {
-        self.command.output()
+        let Some(input) = self.stdin else {
+            return self.command.output();
+        };
+
+        let mut child = self
+            .command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(input.as_bytes())?;
+        }
+
+        child.wait_with_output()
+    }
+
+    /// Start the command without waiting for it, capturing its output.
+    pub fn spawn(mut self) -> std::io::Result<std::process::Child> {
+        self.command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
     }
 
     /// Execute the command and assert it succeeds.
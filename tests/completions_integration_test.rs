@@ -22,6 +22,19 @@ fn test_bash_completions_generation() {
     output.assert_stdout_contains("# To install bash completions");
 }
 
+#[test]
+fn test_completions_offer_config_keys() {
+    init_tests();
+
+    for shell in ["bash", "zsh"] {
+        TramCommand::new()
+            .args(["completions", shell])
+            .assert_success()
+            .assert_stdout_contains("http.timeoutSecs")
+            .assert_stdout_contains("telemetry.exporter");
+    }
+}
+
 #[test]
 fn test_zsh_completions_generation() {
     init_tests();
@@ -61,6 +74,20 @@ fn test_powershell_completions_generation() {
     output.assert_stdout_contains("# To install PowerShell completions");
 }
 
+#[test]
+fn test_completions_output_is_clean() {
+    init_tests();
+
+    // Global flags before the subcommand must not bring back status messages
+    let output = TramCommand::new()
+        .args(["--format", "plain", "completions", "bash"])
+        .assert_success();
+
+    assert!(!output.stderr().contains("Done!"));
+    assert!(!output.stderr().contains("Working in"));
+    assert!(output.stdout().starts_with("_tram() {"));
+}
+
 #[test]
 fn test_completions_help() {
     init_tests();
@@ -124,6 +151,167 @@ fn test_bash_completion_includes_options() {
     assert!(stdout.contains("--help"));
 }
 
+#[test]
+fn test_completions_include_argument_values() {
+    init_tests();
+
+    let bash = TramCommand::new()
+        .args(["completions", "bash"])
+        .assert_success();
+
+    // Value lists for typed arguments, not just the flag names
+    for value in [
+        "rust nodejs python go java generic",
+        "command config-section error-type session-extension",
+        "trace debug info warn error",
+        "json yaml table plain",
+        "basic-command async-operations",
+    ] {
+        bash.assert_stdout_contains(value);
+    }
+
+    // Shells that show descriptions get them for each value
+    TramCommand::new()
+        .args(["completions", "zsh"])
+        .assert_success()
+        .assert_stdout_contains(r#"nodejs\:"npm package""#);
+}
+
+#[test]
+fn test_completions_install() {
+    init_tests();
+
+    let temp_dir = TempDir::new("completions-install-test").unwrap();
+
+    let output = TramCommand::new()
+        .args(["--format", "plain", "completions", "bash", "--install"])
+        .env("HOME", temp_dir.path())
+        .env("XDG_DATA_HOME", temp_dir.path().join("data"))
+        .assert_success();
+
+    let script = temp_dir
+        .path()
+        .join("data/bash-completion/completions/tram");
+    output.assert_stdout_contains("Installed bash completions to");
+    FileAssertions::assert_file_contains(&script, "complete -F _tram");
+
+    let output = TramCommand::new()
+        .args(["--format", "plain", "completions", "zsh", "--install"])
+        .env("HOME", temp_dir.path())
+        .assert_success();
+    output.assert_stdout_contains("fpath");
+    FileAssertions::assert_file_exists(temp_dir.path().join(".zsh/completions/_tram"));
+
+    TramCommand::new()
+        .args(["completions", "elvish", "--install"])
+        .env("HOME", temp_dir.path())
+        .assert_failure()
+        .assert_stderr_contains("--install isn't supported");
+}
+
+#[test]
+fn test_completions_install_detects_shell() {
+    init_tests();
+
+    let temp_dir = TempDir::new("completions-detect-test").unwrap();
+
+    let output = TramCommand::new()
+        .args(["--format", "plain", "completions", "--install"])
+        .env("HOME", temp_dir.path())
+        .env("SHELL", "/usr/local/bin/fish")
+        .env("XDG_CONFIG_HOME", temp_dir.path().join("config"))
+        .assert_success();
+    output.assert_stdout_contains("Detected fish from $SHELL");
+    FileAssertions::assert_file_exists(temp_dir.path().join("config/fish/completions/tram.fish"));
+
+    TramCommand::new()
+        .args(["completions", "--install"])
+        .env("HOME", temp_dir.path())
+        .env("SHELL", "/bin/tcsh")
+        .assert_failure()
+        .assert_stderr_contains("Your shell, tcsh, has no completion directory");
+
+    // Printing to stdout still needs a shell
+    TramCommand::new()
+        .args(["completions"])
+        .assert_failure()
+        .assert_stderr_contains("required");
+}
+
+#[test]
+fn test_completions_install_powershell() {
+    init_tests();
+
+    let temp_dir = TempDir::new("completions-powershell-test").unwrap();
+    let profile = temp_dir
+        .path()
+        .join("PowerShell")
+        .join("Microsoft.PowerShell_profile.ps1");
+    let script = temp_dir
+        .path()
+        .join("PowerShell")
+        .join("tram-completions.ps1");
+
+    TramCommand::new()
+        .args([
+            "--format",
+            "plain",
+            "completions",
+            "powershell",
+            "--install",
+        ])
+        .env("TRAM_POWERSHELL_PROFILE", &profile)
+        .assert_success()
+        .assert_stdout_contains("Added a line loading them to your PowerShell profile");
+    FileAssertions::assert_file_contains(&script, "Register-ArgumentCompleter");
+
+    // Installing again leaves the profile alone
+    TramCommand::new()
+        .args([
+            "--format",
+            "plain",
+            "completions",
+            "powershell",
+            "--install",
+        ])
+        .env("TRAM_POWERSHELL_PROFILE", &profile)
+        .assert_success()
+        .assert_stdout_contains("already loads them");
+
+    let contents = std::fs::read_to_string(&profile).unwrap();
+    assert_eq!(contents.matches("tram-completions.ps1").count(), 1);
+}
+
+/// Windows resolves `$PROFILE` itself and has no `man`.
+#[cfg(windows)]
+#[test]
+fn test_windows_install_paths() {
+    init_tests();
+
+    let temp_dir = TempDir::new("completions-windows-test").unwrap();
+    let profile = temp_dir.path().join("Microsoft.PowerShell_profile.ps1");
+    std::fs::write(&profile, "Set-PSReadLineOption -EditMode Emacs\r\n").unwrap();
+
+    TramCommand::new()
+        .args(["completions", "powershell", "--install"])
+        .env("TRAM_POWERSHELL_PROFILE", &profile)
+        .assert_success();
+
+    let contents = std::fs::read_to_string(&profile).unwrap();
+    assert!(contents.starts_with("Set-PSReadLineOption -EditMode Emacs\r\n"));
+    assert!(contents.contains(&format!(
+        ". \"{}\"",
+        temp_dir.path().join("tram-completions.ps1").display()
+    )));
+
+    TramCommand::new()
+        .args(["--format", "plain", "man", "--install"])
+        .env("USERPROFILE", temp_dir.path())
+        .assert_success()
+        .assert_stdout_contains("Windows has no `man`");
+    assert!(!temp_dir.path().join(".local").exists());
+}
+
 #[test]
 fn test_completions_save_to_file() {
     init_tests();
@@ -32,18 +32,35 @@ fn test_man_page_generation_default() {
     // Check that main man page was generated
     let main_man_page = man_dir.join("tram.1");
     FileAssertions::assert_file_exists(&main_man_page);
+    FileAssertions::assert_file_contains(&main_man_page, ".SH \"EXIT STATUS\"");
 
     // Check subcommand man pages
     let subcommands = [
         "new",
         "generate",
+        "upgrade",
         "init",
         "workspace",
         "config",
+        "deprecations",
         "watch",
         "examples",
         "completions",
         "man",
+        "docs",
+        "package",
+        "doctor",
+        "shell",
+        "batch",
+        "daemon",
+        "plugins",
+        "auth",
+        "hook",
+        "self-update",
+        "run",
+        "env",
+        "cache",
+        "exit-codes",
     ];
     for subcommand in &subcommands {
         let man_file = man_dir.join(format!("tram-{}.1", subcommand));
@@ -51,7 +68,7 @@ fn test_man_page_generation_default() {
     }
 
     // Count total generated files
-    assert_eq!(FileAssertions::count_files(&man_dir, r".*\.1$"), 10); // 1 main + 9 subcommands
+    assert_eq!(FileAssertions::count_files(&man_dir, r".*\.1$"), 28); // 1 main + 27 subcommands
 }
 
 #[test]
@@ -246,3 +263,51 @@ fn test_man_page_overwrite_existing() {
     let content = fs::read_to_string(man_dir.join("tram.1")).unwrap();
     assert!(!content.contains("dummy content"));
 }
+
+#[test]
+fn test_man_page_install() {
+    init_tests();
+
+    let temp_dir = TempDir::new("man-install-test").unwrap();
+    let man1 = temp_dir.path().join("share/man/man1");
+
+    let output = TramCommand::new()
+        .args([
+            "--format",
+            "plain",
+            "--dry-run",
+            "man",
+            "--install",
+            "--prefix",
+        ])
+        .arg(temp_dir.path())
+        .assert_success();
+    output.assert_stdout_contains("Would install 28 man pages");
+    assert!(!man1.exists());
+
+    let output = TramCommand::new()
+        .args(["--format", "json", "man", "--install", "--prefix"])
+        .arg(temp_dir.path())
+        .assert_success();
+    let result: serde_json::Value = serde_json::from_str(output.stdout()).unwrap();
+    assert_eq!(result["pages"].as_array().unwrap().len(), 28);
+
+    FileAssertions::assert_file_contains(man1.join("tram.1"), ".SH \"EXIT STATUS\"");
+    FileAssertions::assert_file_exists(man1.join("tram-new.1"));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = fs::metadata(man1.join("tram.1"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o644);
+    }
+
+    // --prefix only makes sense when installing
+    TramCommand::new()
+        .args(["man", "--prefix", "/tmp"])
+        .assert_failure();
+}
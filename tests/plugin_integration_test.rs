@@ -0,0 +1,131 @@
+//! Integration tests for external plugin subcommands.
+//!
+//! These tests put `tram-*` scripts on PATH and verify that unknown
+//! subcommands run them with the remaining arguments and exit code.
+
+#![cfg(unix)]
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+mod common;
+
+use common::{TempDir, TramCommand, init_tests};
+
+fn write_plugin(dir: &Path, name: &str, body: &str) {
+    let path = dir.join(format!("tram-{}", name));
+    fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+fn path_with(dir: &Path) -> String {
+    format!(
+        "{}:{}",
+        dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    )
+}
+
+#[test]
+fn test_plugin_receives_args_and_exit_code() {
+    init_tests();
+
+    let temp_dir = TempDir::new("plugin-run-test").unwrap();
+    write_plugin(
+        temp_dir.path(),
+        "hello",
+        r#"echo "hello $*"; echo "from plugin" >&2; exit 3"#,
+    );
+
+    let output = TramCommand::new()
+        .env("PATH", path_with(temp_dir.path()))
+        .args(["hello", "world", "--loud"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "hello world --loud\n"
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "from plugin\n");
+}
+
+#[test]
+fn test_plugin_inherits_environment() {
+    init_tests();
+
+    let temp_dir = TempDir::new("plugin-env-test").unwrap();
+    write_plugin(temp_dir.path(), "echo-env", r#"echo "$PLUGIN_TEST_VALUE""#);
+
+    let output = TramCommand::new()
+        .env("PATH", path_with(temp_dir.path()))
+        .env("PLUGIN_TEST_VALUE", "passed through")
+        .args(["echo-env"])
+        .assert_success();
+
+    assert_eq!(output.stdout(), "passed through\n");
+}
+
+#[test]
+fn test_plugin_receives_tram_context() {
+    init_tests();
+
+    let temp_dir = TempDir::new("plugin-context-test").unwrap();
+    let bin = temp_dir.path().join("bin");
+    let project = temp_dir.path().join("project");
+    fs::create_dir_all(&bin).unwrap();
+    fs::create_dir_all(&project).unwrap();
+    fs::write(project.join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+    fs::write(project.join("tram.toml"), "tableBorder = \"ascii\"\n").unwrap();
+    write_plugin(
+        &bin,
+        "context",
+        r#"printf '%s\n' "$TRAM_WORKSPACE" "$TRAM_PROJECT_TYPE" "$TRAM_CONFIG_FILE" "$TRAM_VERSION" "$TRAM_CONFIG"; [ -x "$TRAM" ]"#,
+    );
+
+    let output = TramCommand::new()
+        .current_dir(&project)
+        .env("PATH", path_with(&bin))
+        .args(["--format", "yaml", "context"])
+        .assert_success();
+
+    let lines: Vec<&str> = output.stdout().lines().collect();
+    assert_eq!(lines[0], project.display().to_string());
+    assert_eq!(lines[1], "rust");
+    assert_eq!(lines[2], project.join("tram.toml").display().to_string());
+    assert_eq!(lines[3], env!("CARGO_PKG_VERSION"));
+
+    // The config has the file's settings and the flags given before the plugin
+    let config: serde_json::Value = serde_json::from_str(lines[4]).unwrap();
+    assert_eq!(config["tableBorder"], "ascii");
+    assert_eq!(config["outputFormat"], "yaml");
+}
+
+#[test]
+fn test_plugins_listed_in_help() {
+    init_tests();
+
+    let temp_dir = TempDir::new("plugin-help-test").unwrap();
+    write_plugin(temp_dir.path(), "deploy", "true");
+
+    let output = TramCommand::new()
+        .env("PATH", path_with(temp_dir.path()))
+        .args(["--help"])
+        .assert_success();
+
+    output.assert_stdout_contains("External commands:");
+    output.assert_stdout_matches(r"deploy\s+\S*tram-deploy");
+}
+
+#[test]
+fn test_missing_plugin_is_unrecognized() {
+    init_tests();
+
+    let output = TramCommand::new()
+        .args(["definitely-not-a-plugin"])
+        .assert_failure();
+
+    output.assert_stderr_contains("unrecognized subcommand 'definitely-not-a-plugin'");
+}